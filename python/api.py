@@ -6,6 +6,10 @@ import base64
 import traceback
 import time
 
+# Bump together with BRIDGE_PROTOCOL_VERSION in src-tauri/src/python_bridge.rs whenever
+# the request or response shapes exchanged with the app change.
+BRIDGE_PROTOCOL_VERSION = 3
+
 # Ensure local imports work
 sys.path.insert(0, os.path.dirname(os.path.abspath(__file__)))
 
@@ -63,6 +67,94 @@ def send_progress(current_page, total_pages, status_message=""):
     print(json.dumps(progress_data))
     sys.stdout.flush()
 
+def score_confidence(item, page_quality=None):
+    """Fill in an item's extraction confidence (0-1) unless the parser already set one."""
+    if not isinstance(item, dict):
+        return item
+    confidence = item.get('confidence')
+    if not isinstance(confidence, (int, float)):
+        # Labels matched to a known term read more reliably than free-text lines
+        confidence = 0.9 if item.get('isImportant') else 0.7
+        if item.get('source') == 'text':
+            confidence -= 0.15
+        if page_quality is not None:
+            # Hybrid page quality is scored out of 20
+            confidence *= 0.5 + 0.5 * min(max(page_quality / 20.0, 0.0), 1.0)
+    elif confidence > 1:
+        # Some parsers score in percent
+        confidence = confidence / 100.0
+    if item.get('hasWarning') or item.get('calculationError'):
+        confidence = min(confidence, 0.5)
+    item['confidence'] = round(min(max(float(confidence), 0.0), 1.0), 3)
+    return item
+
+def _item_page(item):
+    """1-based page an item came from, or None."""
+    for key in ('stream_page_num', 'page_num'):
+        if isinstance(item.get(key), int) and item[key] > 0:
+            return item[key]
+    source_page = item.get('sourcePage')
+    if isinstance(source_page, int):
+        return source_page or None
+    digits = ''.join(c for c in str(source_page or '').split('Page')[-1] if c.isdigit())
+    return int(digits) if digits else None
+
+def _page_lines(page):
+    """(rect, text) for every text line on a page."""
+    import fitz
+    lines = []
+    for block in page.get_text('dict').get('blocks', []):
+        for line in block.get('lines', []):
+            text = ''.join(span.get('text', '') for span in line.get('spans', [])).strip()
+            if text:
+                lines.append((fitz.Rect(line['bbox']), text))
+    return lines
+
+def locate_item_sources(pdf_path, items):
+    """Note where each item sits on its page: `sourceBox` (PDF points from the top-left corner,
+    covering the whole row so the figures are included) and, when missing, `rawLine`."""
+    try:
+        import fitz
+        doc = fitz.open(pdf_path)
+    except Exception as e:
+        print(f"[api.py] Source location skipped: {e}", file=sys.stderr)
+        return
+    try:
+        lines_by_page = {}
+        for item in items:
+            if not isinstance(item, dict) or item.get('sourceBox'):
+                continue
+            page_num = _item_page(item)
+            if not page_num or page_num > len(doc):
+                continue
+            label = ((item.get('metadata') or {}).get('original_label') or item.get('label') or '').strip()
+            if len(label) < 3:
+                continue
+            page = doc[page_num - 1]
+            rects = page.search_for(label)
+            if not rects:
+                continue
+            if page_num not in lines_by_page:
+                lines_by_page[page_num] = _page_lines(page)
+            found = rects[0]
+            row = [(rect, text) for rect, text in lines_by_page[page_num]
+                   if found.y0 <= (rect.y0 + rect.y1) / 2 <= found.y1]
+            box = fitz.Rect(found)
+            for rect, _ in row:
+                box |= rect
+            if row and not item.get('rawLine'):
+                item['rawLine'] = ' '.join(text for _, text in sorted(row, key=lambda r: r[0].x0))
+            item['sourceBox'] = {
+                'page': page_num,
+                'x0': round(box.x0, 1), 'y0': round(box.y0, 1),
+                'x1': round(box.x1, 1), 'y1': round(box.y1, 1),
+                'pageWidth': round(page.rect.width, 1), 'pageHeight': round(page.rect.height, 1),
+            }
+    except Exception as e:
+        print(f"[api.py] Source location failed: {e}", file=sys.stderr)
+    finally:
+        doc.close()
+
 def send_stream_item(item_data):
     """Send individual item to frontend as it's extracted."""
     stream_data = {
@@ -146,7 +238,8 @@ def handle_xbrl_parse(filepath: str, file_name: str):
                     'previous_year': None,
                     'all_years': {period_label: value},
                     'sourcePage': 'XBRL',
-                    'category': 'Financial'
+                    'category': 'Financial',
+                    'confidence': 1.0
                 })
 
         send_progress(90, 100, 'Calculating metrics...')
@@ -207,12 +300,16 @@ def process_request(line):
         req = json.loads(line)
         cmd = req.get('command')
 
-        if cmd == 'parse':
+        if cmd == 'get_bridge_version':
+            return handle_bridge_version(req)
+        elif cmd == 'parse':
             return handle_parse(req)
         elif cmd == 'rag_search':
             return handle_rag(req)
         elif cmd == 'update_mapping':
-            return {'status': 'success', 'message': 'Mappings updated (mock)'}
+            from terminology_keywords import apply_mapping_overrides
+            apply_mapping_overrides(req.get('mappings', []))
+            return {'status': 'success', 'message': 'Mappings applied for this process'}
         elif cmd == 'calculate_metrics':
             return handle_calculate_metrics(req)
         elif cmd == 'get_db_data':
@@ -224,15 +321,29 @@ def process_request(line):
     except Exception as e:
         return {'status': 'error', 'message': str(e), 'traceback': traceback.format_exc()}
 
+def handle_bridge_version(req):
+    return {
+        'status': 'success',
+        'command': 'get_bridge_version',
+        'protocolVersion': BRIDGE_PROTOCOL_VERSION,
+        'pythonVersion': sys.version.split()[0],
+    }
+
 def handle_parse(req):
     file_path = req.get('file_path')
     content_b64 = req.get('content')
     file_name = req.get('file_name', 'document')
     use_streaming = req.get('streaming', True)  # Default to streaming
 
+    # The app owns the label mappings and sends its current set with every parse
+    if req.get('mappings'):
+        from terminology_keywords import apply_mapping_overrides
+        apply_mapping_overrides(req['mappings'])
+
     # Determine actual file path
     actual_path = None
     temp_file = None
+    decrypted_file = None
 
     # Determine file extension
     file_ext = file_name.split('.')[-1].lower() if '.' in file_name else ''
@@ -263,6 +374,24 @@ def handle_parse(req):
         # Determine document size for PDF files
         import fitz
         doc = fitz.open(actual_path)
+
+        # Encrypted PDFs: decrypt once into a temp copy so every parser can open it
+        if doc.needs_pass:
+            password = req.get('password')
+            if not password:
+                doc.close()
+                return {'status': 'error', 'code': 'PDF_PASSWORD_REQUIRED', 'message': 'PDF is password protected'}
+            if not doc.authenticate(password):
+                doc.close()
+                return {'status': 'error', 'code': 'PDF_DECRYPTION_FAILED', 'message': 'Incorrect PDF password'}
+            import tempfile
+            decrypted_file = tempfile.NamedTemporaryFile(delete=False, suffix='.pdf')
+            decrypted_file.close()
+            doc.save(decrypted_file.name, encryption=fitz.PDF_ENCRYPT_NONE)
+            doc.close()
+            actual_path = decrypted_file.name
+            doc = fitz.open(actual_path)
+
         total_pages = len(doc)
         doc.close()
 
@@ -295,6 +424,7 @@ def handle_parse(req):
                 for item in items:
                     item['stream_page_num'] = page_num + 1
                     item['stream_quality'] = page_data.get('quality_score', 0)
+                    score_confidence(item, page_data.get('quality_score'))
                     send_stream_item(item)
                     all_items.append(item)
             
@@ -327,6 +457,9 @@ def handle_parse(req):
             
             # Get data from result
             items = result.get('items', all_items)  # Use collected items if not in result
+            for item in items:
+                score_confidence(item)
+            locate_item_sources(actual_path, items)
             text = result.get('text', '')
             metadata = result.get('metadata', {})
             
@@ -338,6 +471,8 @@ def handle_parse(req):
             print(f"[api.py]   - Analysis mode: {metadata.get('analysis_mode', 'unknown')}", file=sys.stderr)
             
             # Database Persistence (batch save after streaming is complete)
+            # The app attributes the stored document by this id, not by "newest"
+            doc_id = None
             if db:
                 try:
                     # Wipe previous session data for new analysis
@@ -390,6 +525,7 @@ def handle_parse(req):
             
             return {
                 'status': 'success',
+                'docId': doc_id,
                 'metrics': calculated_metrics,
                 'extractedData': {
                     'items': items,
@@ -436,6 +572,9 @@ def handle_parse(req):
 
             # Convert to frontend-expected format
             items = result.get('items', [])
+            for item in items:
+                score_confidence(item)
+            locate_item_sources(actual_path, items)
             text = result.get('text', '')
             metadata = result.get('metadata', {})
 
@@ -446,6 +585,7 @@ def handle_parse(req):
             print(f"[api.py]   - Parser version: {metadata.get('parser_version', 'unknown')}", file=sys.stderr)
 
                 # Database Persistence
+            doc_id = None
             if db:
                 try:
                     # Wipe previous session data for new analysis
@@ -552,6 +692,7 @@ def handle_parse(req):
 
             return {
                 'status': 'success',
+                'docId': doc_id,
                 'metrics': calculated_metrics,
                 'extractedData': {
                     'items': items,
@@ -584,12 +725,13 @@ def handle_parse(req):
             'traceback': traceback.format_exc()
         }
     finally:
-        # Clean up temp file
-        if temp_file and os.path.exists(temp_file.name):
-            try:
-                os.unlink(temp_file.name)
-            except:
-                pass
+        # Clean up temp files
+        for leftover in (temp_file, decrypted_file):
+            if leftover and os.path.exists(leftover.name):
+                try:
+                    os.unlink(leftover.name)
+                except:
+                    pass
 
 def handle_rag(req):
     """Handle RAG search request using Ollama."""
@@ -699,7 +841,7 @@ def _fallback_parse(pdf_path: str, file_name: str):
         }
 
 def main():
-    # Process single request and exit (one-shot mode)
+    # Answer the version handshake, then process a single request and exit (one-shot mode)
     try:
         for line in sys.stdin:
             line = line.strip()
@@ -709,6 +851,8 @@ def main():
             if response:
                 print(json.dumps(response))
                 sys.stdout.flush()
+                if response.get('command') == 'get_bridge_version':
+                    continue
                 break
     except KeyboardInterrupt:
         pass
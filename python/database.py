@@ -14,6 +14,10 @@ DB_FILENAME = "extracted_data.db"
 
 class DatabaseManager:
     def __init__(self, db_path: str = None):
+        if db_path is None:
+            # The desktop app passes the active profile's database explicitly
+            db_path = os.environ.get("FINCALC_DB_PATH") or None
+
         if db_path is None:
             # Use parent directory (project root) for DB so both Python and Rust can find it
             base_dir = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
@@ -130,8 +134,9 @@ class DatabaseManager:
             # Determine statement type if present, else default or infer
             stmt_type = item.get('statementType', 'UNKNOWN').upper()
             
-            # Generate ID if not present
-            item_id = item.get('id', str(uuid.uuid4()))
+            # Generate ID if not present. Row keys carry the document id, as parsers number
+            # items from scratch and another document's rows must not be replaced.
+            item_id = f"{doc_id}:{item.get('id', str(uuid.uuid4()))}"
             
             cursor.execute('''
                 INSERT OR REPLACE INTO financial_items (
@@ -148,9 +153,9 @@ class DatabaseManager:
                 item.get('rowIndex', -1),
                 stmt_type,
                 item.get('isHeader', False),
-                item.get('page', 0), # source_page
+                item.get('page') or (item.get('sourceBox') or {}).get('page', 0), # source_page
                 item.get('rawLine', ''),
-                item.get('confidence', 0.0),
+                item.get('confidence'),
                 json.dumps(item)
             ))
             
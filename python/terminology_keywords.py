@@ -238,6 +238,38 @@ def build_terminology_maps():
 # Build the maps
 build_terminology_maps()
 
+def apply_mapping_overrides(mappings: List[Dict]):
+    """Overlay the app's own label mappings, sent with each parse request, on the built-in terms."""
+    for mapping in mappings or []:
+        term_key = mapping.get('key', '')
+        if not term_key:
+            continue
+        keywords = []
+        for kw in mapping.get('keywords_indas', []) + mapping.get('keywords_gaap', []) + mapping.get('keywords_ifrs', []):
+            kw_lower = kw.lower().strip()
+            if kw_lower and kw_lower not in keywords:
+                keywords.append(kw_lower)
+        term_data = dict(TERMINOLOGY_MAP.get(term_key, {}))
+        term_data.update({k: v for k, v in mapping.items() if v is not None})
+        term_data.update({
+            'key': term_key,
+            'keywords': keywords,
+            'keywords_unified': keywords,
+            'boost': term_data.get('boost', BOOST_VALUES.get(term_key, 1.5)),
+        })
+        TERMINOLOGY_MAP[term_key] = term_data
+        for keyword in keywords:
+            entries = [t for t in KEYWORD_TO_TERM.get(keyword, []) if t.get('term_key') != term_key]
+            entries.append({
+                'term_key': term_key,
+                'term_id': term_data.get('id', term_key),
+                'label': term_data.get('label', term_key),
+                'category': term_data.get('category', ''),
+                'boost': term_data['boost'],
+                'priority': term_data.get('priority', 1)
+            })
+            KEYWORD_TO_TERM[keyword] = entries
+
 # =============================================================================
 # CROSS-SECTIONAL MATCHING FUNCTIONS
 # =============================================================================
@@ -530,6 +562,7 @@ def get_term_details(term_key: str) -> Optional[Dict]:
 
 __all__ = [
     'TERMINOLOGY_MAP',
+    'apply_mapping_overrides',
     'KEYWORD_TO_TERM',
     'KEYWORD_BOOST',
     'ALL_TERMS',
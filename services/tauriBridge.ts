@@ -28,6 +28,8 @@ export interface PythonResponse {
     metadata?: any;
     message?: string;
     error?: string;
+    code?: 'PDF_PASSWORD_REQUIRED' | 'PDF_DECRYPTION_FAILED' | string;
+    cacheHit?: boolean; // Replayed from the parse cache
     progress?: number;
     currentPage?: number;
     totalPages?: number;
@@ -43,6 +45,7 @@ interface AnalysisOptions {
     indASProcessing?: boolean; // Ind AS specific processing
     parseIndianNumbers?: boolean; // Parse Indian number formats
     detectIndASSigns?: boolean; // Detect Ind AS sign conventions
+    password?: string; // For password-protected PDFs
 }
 
 // ============================================================================
@@ -62,6 +65,7 @@ export async function runPythonAnalysis(
         indASProcessing = false,
         parseIndianNumbers = false,
         detectIndASSigns = false,
+        password,
     } = options;
 
     console.log('[TauriBridge] Starting Analysis via Rust Bridge');
@@ -81,7 +85,8 @@ export async function runPythonAnalysis(
                 // Ind AS specific options
                 ind_as_processing: indASProcessing,
                 parse_indian_numbers: parseIndianNumbers,
-                detect_ind_as_signs: detectIndASSigns
+                detect_ind_as_signs: detectIndASSigns,
+                password: password || null
             }
         });
 
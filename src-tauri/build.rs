@@ -1,7 +1,12 @@
 
-
 fn main() {
+    // protoc ships as a build dependency so contributors don't need it installed
+    let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc is available for this platform");
+    std::env::set_var("PROTOC", protoc);
+    tonic_prost_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/fincalc.proto"], &["proto"])
+        .expect("failed to compile proto/fincalc.proto");
 
-  
-  tauri_build::build()
+    tauri_build::build()
 }
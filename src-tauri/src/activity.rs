@@ -0,0 +1,147 @@
+// Activity - an append-only log of the actions that shape an analysis (imports, item edits,
+// reports, AI runs, settings changes), read back as a filterable timeline for audit.
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+use tracing::warn;
+
+use crate::db;
+use crate::item_edits;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS activity_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,            -- ActivityKind, snake_case
+    document_id INTEGER,           -- pipeline DB documents.id, when the action concerns one
+    summary TEXT NOT NULL,
+    actor TEXT NOT NULL,
+    recorded_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_activity_log_recorded ON activity_log(recorded_at);
+CREATE INDEX IF NOT EXISTS idx_activity_log_document ON activity_log(document_id);
+";
+
+const DEFAULT_LIMIT: u32 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    DocumentImported,
+    ItemsEdited,
+    ReportGenerated,
+    AiAnalysis,
+    SettingsChanged,
+}
+
+impl ActivityKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ActivityKind::DocumentImported => "document_imported",
+            ActivityKind::ItemsEdited => "items_edited",
+            ActivityKind::ReportGenerated => "report_generated",
+            ActivityKind::AiAnalysis => "ai_analysis",
+            ActivityKind::SettingsChanged => "settings_changed",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "document_imported" => Some(ActivityKind::DocumentImported),
+            "items_edited" => Some(ActivityKind::ItemsEdited),
+            "report_generated" => Some(ActivityKind::ReportGenerated),
+            "ai_analysis" => Some(ActivityKind::AiAnalysis),
+            "settings_changed" => Some(ActivityKind::SettingsChanged),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityEntry {
+    pub id: i64,
+    pub kind: ActivityKind,
+    pub document_id: Option<i64>,
+    pub summary: String,
+    pub actor: String,
+    pub recorded_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ActivityFilters {
+    /// Only these kinds; all when empty
+    pub kinds: Vec<ActivityKind>,
+    pub document_id: Option<i64>,
+    pub actor: Option<String>,
+    /// Unix seconds, inclusive
+    pub since: Option<i64>,
+    pub until: Option<i64>,
+    /// Newest entries returned (default 200)
+    pub limit: Option<u32>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Log one action. `actor` defaults to the signed-in OS user, as for item edits. Failures are
+/// logged and never fail the action itself.
+pub fn record(app: &AppHandle, kind: ActivityKind, document_id: Option<i64>, summary: &str, actor: Option<&str>) {
+    let actor = item_edits::editor(actor.map(str::to_string));
+    let recorded = db::open_app_db(app).and_then(|conn| {
+        conn.execute(
+            "INSERT INTO activity_log (kind, document_id, summary, actor, recorded_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![kind.as_str(), document_id, summary, actor, now_secs()],
+        ).map_err(|e| e.to_string())
+    });
+    if let Err(e) = recorded {
+        warn!("Failed to record activity: {}", e);
+    }
+}
+
+// Tauri Commands
+/// Logged actions, newest first, narrowed by `filters`.
+#[tauri::command]
+pub fn get_activity_timeline(app: AppHandle, filters: Option<ActivityFilters>) -> Result<Vec<ActivityEntry>, String> {
+    let filters = filters.unwrap_or_default();
+    let kinds = (!filters.kinds.is_empty())
+        .then(|| serde_json::to_string(&filters.kinds.iter().map(ActivityKind::as_str).collect::<Vec<_>>()))
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    let conn = db::open_app_db(&app)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, kind, document_id, summary, actor, recorded_at FROM activity_log
+         WHERE (?1 IS NULL OR kind IN (SELECT value FROM json_each(?1)))
+           AND (?2 IS NULL OR document_id = ?2)
+           AND (?3 IS NULL OR actor = ?3)
+           AND (?4 IS NULL OR recorded_at >= ?4)
+           AND (?5 IS NULL OR recorded_at <= ?5)
+         ORDER BY recorded_at DESC, id DESC LIMIT ?6"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(
+        params![kinds, filters.document_id, filters.actor, filters.since, filters.until, filters.limit.unwrap_or(DEFAULT_LIMIT)],
+        |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<i64>>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, i64>(5)?,
+            ))
+        },
+    ).map_err(|e| e.to_string())?;
+    let mut entries = Vec::new();
+    for row in rows {
+        let (id, kind, document_id, summary, actor, recorded_at) = row.map_err(|e| e.to_string())?;
+        if let Some(kind) = ActivityKind::parse(&kind) {
+            entries.push(ActivityEntry { id, kind, document_id, summary, actor, recorded_at });
+        }
+    }
+    Ok(entries)
+}
@@ -0,0 +1,285 @@
+// AI analysis - one-shot "Analyze with AI": builds a prompt from a document's items and ratios,
+// streams the configured Ollama model's answer to the asking window and keeps it in the app DB
+use futures_util::StreamExt;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+use crate::activity::{self, ActivityKind};
+use crate::db;
+use crate::http;
+use crate::ollama;
+use crate::ratios;
+use crate::report;
+use crate::settings::SettingsState;
+use crate::statements;
+use crate::tasks::{self, TaskKind};
+use crate::timeouts::{self, Operation};
+use crate::usage::{self, UsageKind};
+use crate::windows;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS ai_analyses (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    document_id INTEGER NOT NULL,     -- pipeline DB documents.id
+    analysis_type TEXT NOT NULL,
+    model TEXT NOT NULL,
+    content TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_ai_analyses_document ON ai_analyses(document_id, created_at);
+";
+
+// Keeps the prompt inside small context windows; totals and headline lines come first anyway
+const MAX_PROMPT_ITEMS: usize = 150;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnalysisType {
+    Summary,
+    Profitability,
+    Liquidity,
+    Solvency,
+    RedFlags,
+}
+
+impl AnalysisType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AnalysisType::Summary => "summary",
+            AnalysisType::Profitability => "profitability",
+            AnalysisType::Liquidity => "liquidity",
+            AnalysisType::Solvency => "solvency",
+            AnalysisType::RedFlags => "red_flags",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "summary" => Some(AnalysisType::Summary),
+            "profitability" => Some(AnalysisType::Profitability),
+            "liquidity" => Some(AnalysisType::Liquidity),
+            "solvency" => Some(AnalysisType::Solvency),
+            "red_flags" => Some(AnalysisType::RedFlags),
+            _ => None,
+        }
+    }
+
+    fn instruction(&self) -> &'static str {
+        match self {
+            AnalysisType::Summary => "Give an executive summary of the company's financial position and performance: revenue and profit trend, margins, balance sheet strength and cash generation. End with three key takeaways.",
+            AnalysisType::Profitability => "Analyse profitability: revenue growth, gross/operating/net margins, return on equity and assets, and what drove the change from the previous year.",
+            AnalysisType::Liquidity => "Analyse liquidity and working capital: current and quick ratios, cash position, receivables, inventory and payables, and whether short-term obligations are comfortably covered.",
+            AnalysisType::Solvency => "Analyse solvency and leverage: debt levels, debt to equity, interest coverage and the ability of operating cash flow to service debt.",
+            AnalysisType::RedFlags => "Look for red flags and accounting concerns: profit not backed by cash flow, rising receivables or inventory versus revenue, jumps in debt, unusual items, or large year-on-year swings. Say plainly if nothing stands out.",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AiAnalysis {
+    pub id: i64,
+    pub document_id: i64,
+    pub analysis_type: AnalysisType,
+    pub model: String,
+    pub content: String,
+    pub created_at: i64,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn format_value(value: Option<f64>) -> String {
+    value.map(|v| format!("{:.2}", v)).unwrap_or_else(|| "-".to_string())
+}
+
+fn build_prompt(document: &statements::StoredDocument, analysis_type: AnalysisType) -> String {
+    let mut prompt = format!("Financial statements extracted from \"{}\".\n\n", document.filename);
+
+    let ratios = ratios::for_document(document);
+    if !ratios.is_empty() {
+        prompt.push_str("Key ratios (current / previous year):\n");
+        for ratio in &ratios {
+            let _ = writeln!(prompt, "- {}: {} / {} {}", ratio.name, format_value(ratio.current), format_value(ratio.previous), ratio.unit);
+        }
+        prompt.push('\n');
+    }
+
+    let items: Vec<&serde_json::Value> = document.items.iter()
+        .filter(|item| !item["isHeader"].as_bool().unwrap_or(false))
+        .collect();
+    // Totals carry the most information per line, so they survive the cut first
+    let mut kept: Vec<&serde_json::Value> = items.iter().copied().filter(|item| item["isTotal"].as_bool().unwrap_or(false)).collect();
+    kept.extend(items.iter().copied().filter(|item| !item["isTotal"].as_bool().unwrap_or(false)));
+    kept.truncate(MAX_PROMPT_ITEMS);
+    kept.sort_by_key(|item| (report::statement_of(item), item["rowIndex"].as_i64().unwrap_or(0)));
+
+    let mut statement = String::new();
+    for item in &kept {
+        let current_statement = report::statement_of(item);
+        if current_statement != statement {
+            let _ = writeln!(prompt, "\n{} (current / previous year):", current_statement.replace('_', " "));
+            statement = current_statement;
+        }
+        let _ = writeln!(
+            prompt,
+            "- {}: {} / {}",
+            item["label"].as_str().unwrap_or(""),
+            format_value(item["currentYear"].as_f64()),
+            format_value(item["previousYear"].as_f64())
+        );
+    }
+    if items.len() > kept.len() {
+        let _ = writeln!(prompt, "\n({} further line items omitted)", items.len() - kept.len());
+    }
+
+    let _ = write!(prompt, "\nTask: {} Use only the figures above and say when something can't be judged from them.", analysis_type.instruction());
+    prompt
+}
+
+fn save(app: &AppHandle, document_id: i64, analysis_type: AnalysisType, model: &str, content: &str) -> Result<AiAnalysis, String> {
+    let conn = db::open_app_db(app)?;
+    let created_at = now_secs();
+    conn.execute(
+        "INSERT INTO ai_analyses (document_id, analysis_type, model, content, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![document_id, analysis_type.as_str(), model, content, created_at],
+    ).map_err(|e| e.to_string())?;
+    Ok(AiAnalysis {
+        id: conn.last_insert_rowid(),
+        document_id,
+        analysis_type,
+        model: model.to_string(),
+        content: content.to_string(),
+        created_at,
+    })
+}
+
+async fn run(app: &AppHandle, window: &str, document_id: i64, analysis_type: AnalysisType) -> Result<AiAnalysis, String> {
+    let state = app.state::<SettingsState>();
+    let bridge_url = ollama::get_base_url(&state).await?;
+    let llm = state.read().await.get().llm.clone();
+    let document = statements::load_document(app, document_id)?;
+    let prompt = build_prompt(&document, analysis_type);
+
+    let task = tasks::register(app, TaskKind::AiAnalysis, &format!("{} ({})", document.filename, analysis_type.as_str()));
+    let progress_event = windows::scoped_event(window, "ai-analysis-progress");
+    info!(document_id, analysis = analysis_type.as_str(), model = %llm.selected_model, prompt_chars = prompt.len(), "Starting AI analysis");
+
+    let body = serde_json::json!({
+        "model": llm.selected_model,
+        "stream": true,
+        "keep_alive": llm.keep_alive,
+        "messages": [
+            { "role": "system", "content": llm.system_prompt },
+            { "role": "user", "content": prompt },
+        ],
+        "options": {
+            "num_ctx": llm.context_window,
+            "temperature": llm.temperature,
+            "top_p": llm.top_p,
+            "top_k": llm.top_k,
+            "repeat_penalty": llm.repeat_penalty,
+            "seed": llm.seed,
+            "num_predict": llm.num_predict,
+        },
+    });
+    let result = timeouts::run(app, Operation::OllamaGeneration, async {
+        let res = http::client(app).post(format!("{}/api/chat", bridge_url))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        if !res.status().is_success() {
+            return Err(format!("Ollama returned {}: {}", res.status(), res.text().await.unwrap_or_default()));
+        }
+        let mut stream = res.bytes_stream();
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut content = String::new();
+        while let Some(chunk) = stream.next().await {
+            if task.is_cancelled() {
+                return Err("AI analysis cancelled".to_string());
+            }
+            buffer.extend_from_slice(&chunk.map_err(|e| e.to_string())?);
+            while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                let Ok(value) = serde_json::from_slice::<serde_json::Value>(&line) else { continue };
+                if let Some(error) = value["error"].as_str() {
+                    return Err(error.to_string());
+                }
+                let delta = value["message"]["content"].as_str().unwrap_or("");
+                content.push_str(delta);
+                task.progress(0.0, format!("{} characters", content.len()));
+                let _ = app.emit_to(window, &progress_event, serde_json::json!({
+                    "documentId": document_id,
+                    "analysisType": analysis_type,
+                    "content": delta,
+                    "done": value["done"].as_bool().unwrap_or(false),
+                }));
+            }
+        }
+        if content.trim().is_empty() {
+            return Err("The model returned an empty answer".to_string());
+        }
+        Ok(content)
+    }).await.map_err(String::from);
+
+    let result = result.and_then(|content| save(app, document_id, analysis_type, &llm.selected_model, &content));
+    match &result {
+        Ok(_) => {
+            let summary = format!("{} analysis of {} with {}", analysis_type.as_str(), document.filename, llm.selected_model);
+            activity::record(app, ActivityKind::AiAnalysis, Some(document_id), &summary, None);
+        }
+        Err(e) => warn!(document_id, "AI analysis failed: {}", e),
+    }
+    task.finish(&result);
+    result
+}
+
+// Tauri Commands
+/// Analyse a stored document with the configured model. Tokens stream to the calling window as
+/// `ai-analysis-progress`; the finished text is saved and returned.
+#[tauri::command]
+pub async fn ai_analyze_document(
+    app: AppHandle,
+    window: tauri::WebviewWindow,
+    document_id: i64,
+    analysis_type: AnalysisType,
+) -> Result<AiAnalysis, String> {
+    usage::track(&app, UsageKind::Chat, run(&app, window.label(), document_id, analysis_type)).await
+}
+
+/// Saved analyses, newest first, optionally for one document.
+#[tauri::command]
+pub fn list_ai_analyses(app: AppHandle, document_id: Option<i64>) -> Result<Vec<AiAnalysis>, String> {
+    let conn = db::open_app_db(&app)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, document_id, analysis_type, model, content, created_at FROM ai_analyses
+         WHERE ?1 IS NULL OR document_id = ?1 ORDER BY created_at DESC, id DESC"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![document_id], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(2)?, row.get(3)?, row.get(4)?, row.get(5)?))
+    }).map_err(|e| e.to_string())?;
+    let mut analyses = Vec::new();
+    for row in rows {
+        let (id, document_id, analysis_type, model, content, created_at) = row.map_err(|e| e.to_string())?;
+        // Skip types a newer version wrote
+        let Some(analysis_type) = AnalysisType::parse(&analysis_type) else { continue };
+        analyses.push(AiAnalysis { id, document_id, analysis_type, model, content, created_at });
+    }
+    Ok(analyses)
+}
+
+#[tauri::command]
+pub fn delete_ai_analysis(app: AppHandle, id: i64) -> Result<(), String> {
+    let conn = db::open_app_db(&app)?;
+    conn.execute("DELETE FROM ai_analyses WHERE id = ?1", params![id]).map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,324 @@
+// Price Alerts - user-defined quote conditions evaluated in the background
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::db;
+use crate::scraper::{self, Quote};
+use crate::settings::{self, SettingsState};
+use crate::trading_calendar;
+use crate::tray;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS price_alerts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    condition TEXT NOT NULL,   -- 'above', 'below', 'change_percent_above', 'change_percent_below'
+    threshold REAL NOT NULL,
+    active INTEGER NOT NULL DEFAULT 1,
+    created_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS alert_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    alert_id INTEGER NOT NULL,
+    symbol TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    condition TEXT NOT NULL,
+    threshold REAL NOT NULL,
+    observed_value REAL NOT NULL,
+    triggered_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS alert_reads (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    last_read_history_id INTEGER NOT NULL
+);
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertCondition {
+    Above,
+    Below,
+    ChangePercentAbove,
+    ChangePercentBelow,
+}
+
+impl AlertCondition {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AlertCondition::Above => "above",
+            AlertCondition::Below => "below",
+            AlertCondition::ChangePercentAbove => "change_percent_above",
+            AlertCondition::ChangePercentBelow => "change_percent_below",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "above" => Some(AlertCondition::Above),
+            "below" => Some(AlertCondition::Below),
+            "change_percent_above" => Some(AlertCondition::ChangePercentAbove),
+            "change_percent_below" => Some(AlertCondition::ChangePercentBelow),
+            _ => None,
+        }
+    }
+
+    /// The quote value this condition looks at, if the quote carries it.
+    fn observed(&self, quote: &Quote) -> Option<f64> {
+        match self {
+            AlertCondition::Above | AlertCondition::Below => quote.price,
+            AlertCondition::ChangePercentAbove | AlertCondition::ChangePercentBelow => quote.change_percent,
+        }
+    }
+
+    fn is_met(&self, observed: f64, threshold: f64) -> bool {
+        match self {
+            AlertCondition::Above | AlertCondition::ChangePercentAbove => observed >= threshold,
+            AlertCondition::Below | AlertCondition::ChangePercentBelow => observed <= threshold,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceAlert {
+    pub id: i64,
+    pub symbol: String,
+    pub exchange: String,
+    pub condition: AlertCondition,
+    pub threshold: f64,
+    pub active: bool,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AlertEvent {
+    pub alert_id: i64,
+    pub symbol: String,
+    pub exchange: String,
+    pub condition: AlertCondition,
+    pub threshold: f64,
+    pub observed_value: f64,
+    pub triggered_at: i64,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn load_alerts(app: &AppHandle, active_only: bool) -> Result<Vec<PriceAlert>, String> {
+    let conn = db::open_app_db(app)?;
+    let sql = if active_only {
+        "SELECT id, symbol, exchange, condition, threshold, active, created_at FROM price_alerts WHERE active = 1 ORDER BY id"
+    } else {
+        "SELECT id, symbol, exchange, condition, threshold, active, created_at FROM price_alerts ORDER BY id"
+    };
+    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+
+    let rows = stmt.query_map([], |row| {
+        let condition: String = row.get(3)?;
+        Ok((row.get(0)?, row.get(1)?, row.get(2)?, condition, row.get(4)?, row.get::<_, i64>(5)?, row.get(6)?))
+    }).map_err(|e| e.to_string())?;
+
+    let mut alerts = Vec::new();
+    for row in rows {
+        let (id, symbol, exchange, condition, threshold, active, created_at) = row.map_err(|e| e.to_string())?;
+        // Skip rows written by a newer version with conditions we don't know
+        if let Some(condition) = AlertCondition::parse(&condition) {
+            alerts.push(PriceAlert { id, symbol, exchange, condition, threshold, active: active != 0, created_at });
+        }
+    }
+    Ok(alerts)
+}
+
+/// Alerts are one-shot: once met they are deactivated and logged to `alert_history`.
+fn trigger(app: &AppHandle, alert: &PriceAlert, observed_value: f64) -> Result<(), String> {
+    let event = AlertEvent {
+        alert_id: alert.id,
+        symbol: alert.symbol.clone(),
+        exchange: alert.exchange.clone(),
+        condition: alert.condition,
+        threshold: alert.threshold,
+        observed_value,
+        triggered_at: now_secs(),
+    };
+
+    let conn = db::open_app_db(app)?;
+    conn.execute("UPDATE price_alerts SET active = 0 WHERE id = ?1", params![alert.id])
+        .map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO alert_history (alert_id, symbol, exchange, condition, threshold, observed_value, triggered_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![event.alert_id, event.symbol, event.exchange, event.condition.as_str(), event.threshold, event.observed_value, event.triggered_at],
+    ).map_err(|e| e.to_string())?;
+
+    let body = match alert.condition {
+        AlertCondition::Above | AlertCondition::Below => format!(
+            "{} ({}) is at {:.2} ({} {:.2})", alert.symbol, alert.exchange, observed_value, alert.condition.as_str(), alert.threshold
+        ),
+        _ => format!(
+            "{} ({}) moved {:.2}% today ({} {:.2}%)", alert.symbol, alert.exchange, observed_value, alert.condition.as_str(), alert.threshold
+        ),
+    };
+    if let Err(e) = app.notification().builder().title("Price alert").body(body).show() {
+        eprintln!("[Alerts] Failed to show notification: {}", e);
+    }
+
+    let _ = app.emit("alert-triggered", &event);
+    tray::refresh(app);
+    Ok(())
+}
+
+/// Triggered alerts the user hasn't looked at since the last `mark_alerts_read`.
+pub fn unread_count(app: &AppHandle) -> Result<i64, String> {
+    let conn = db::open_app_db(app)?;
+    conn.query_row(
+        "SELECT COUNT(*) FROM alert_history
+         WHERE id > COALESCE((SELECT last_read_history_id FROM alert_reads WHERE id = 1), 0)",
+        [],
+        |row| row.get(0),
+    ).map_err(|e| e.to_string())
+}
+
+async fn evaluate_all(app: &AppHandle, max_age_secs: u64) -> Result<(), String> {
+    let alerts = load_alerts(app, true)?;
+
+    for alert in &alerts {
+        // Nothing can cross a threshold while the exchange is shut
+        if !trading_calendar::is_open(app, &alert.exchange) {
+            continue;
+        }
+        let quote = match scraper::get_quote(app, &alert.symbol, &alert.exchange, max_age_secs).await {
+            Ok(quote) => quote,
+            Err(e) => {
+                eprintln!("[Alerts] No quote for {} ({}): {}", alert.symbol, alert.exchange, e);
+                continue;
+            }
+        };
+
+        if let Some(observed) = alert.condition.observed(&quote) {
+            // One alert failing to fire mustn't hold back the rest of the pass
+            if alert.condition.is_met(observed, alert.threshold) {
+                if let Err(e) = trigger(app, alert, observed) {
+                    eprintln!("[Alerts] Failed to trigger alert {} for {}: {}", alert.id, alert.symbol, e);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Evaluates on the watchlist refresh interval so both share cached quotes.
+pub fn start_alert_task(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let interval = {
+                let state = app.state::<SettingsState>();
+                let store = state.read().await;
+                store.get().scraper.watchlist_refresh_secs.max(10)
+            };
+
+            if settings::is_offline(&app) {
+                // Quotes can't move while offline; check again next cycle
+            } else if tray::is_refresh_paused(&app) {
+                // Paused from the tray menu
+            } else if let Err(e) = evaluate_all(&app, interval).await {
+                eprintln!("[Alerts] Evaluation failed: {}", e);
+            }
+
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+        }
+    });
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn create_price_alert(
+    app: AppHandle,
+    symbol: String,
+    exchange: String,
+    condition: AlertCondition,
+    threshold: f64,
+) -> Result<PriceAlert, String> {
+    let symbol = symbol.trim().to_uppercase();
+    let exchange = exchange.trim().to_uppercase();
+    if symbol.is_empty() {
+        return Err("Symbol cannot be empty".to_string());
+    }
+    if !threshold.is_finite() {
+        return Err("Threshold must be a number".to_string());
+    }
+
+    let created_at = now_secs();
+    let conn = db::open_app_db(&app)?;
+    conn.execute(
+        "INSERT INTO price_alerts (symbol, exchange, condition, threshold, active, created_at) VALUES (?1, ?2, ?3, ?4, 1, ?5)",
+        params![symbol, exchange, condition.as_str(), threshold, created_at],
+    ).map_err(|e| e.to_string())?;
+
+    Ok(PriceAlert {
+        id: conn.last_insert_rowid(),
+        symbol,
+        exchange,
+        condition,
+        threshold,
+        active: true,
+        created_at,
+    })
+}
+
+#[tauri::command]
+pub fn list_price_alerts(app: AppHandle) -> Result<Vec<PriceAlert>, String> {
+    load_alerts(&app, false)
+}
+
+#[tauri::command]
+pub fn delete_price_alert(app: AppHandle, id: i64) -> Result<(), String> {
+    let conn = db::open_app_db(&app)?;
+    conn.execute("DELETE FROM price_alerts WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_alert_history(app: AppHandle, limit: Option<i64>) -> Result<Vec<AlertEvent>, String> {
+    let conn = db::open_app_db(&app)?;
+    let mut stmt = conn.prepare(
+        "SELECT alert_id, symbol, exchange, condition, threshold, observed_value, triggered_at
+         FROM alert_history ORDER BY triggered_at DESC LIMIT ?1"
+    ).map_err(|e| e.to_string())?;
+
+    let rows = stmt.query_map(params![limit.unwrap_or(100)], |row| {
+        let condition: String = row.get(3)?;
+        Ok((row.get(0)?, row.get(1)?, row.get(2)?, condition, row.get(4)?, row.get(5)?, row.get(6)?))
+    }).map_err(|e| e.to_string())?;
+
+    let mut events = Vec::new();
+    for row in rows {
+        let (alert_id, symbol, exchange, condition, threshold, observed_value, triggered_at) = row.map_err(|e| e.to_string())?;
+        if let Some(condition) = AlertCondition::parse(&condition) {
+            events.push(AlertEvent { alert_id, symbol, exchange, condition, threshold, observed_value, triggered_at });
+        }
+    }
+    Ok(events)
+}
+
+#[tauri::command]
+pub fn mark_alerts_read(app: AppHandle) -> Result<(), String> {
+    let conn = db::open_app_db(&app)?;
+    conn.execute(
+        "INSERT INTO alert_reads (id, last_read_history_id)
+         VALUES (1, (SELECT COALESCE(MAX(id), 0) FROM alert_history))
+         ON CONFLICT(id) DO UPDATE SET last_read_history_id = excluded.last_read_history_id",
+        [],
+    ).map_err(|e| e.to_string())?;
+    tray::refresh(&app);
+    Ok(())
+}
@@ -0,0 +1,196 @@
+// Anomalies - extracted figures that can't be right: a value tens or thousands of times its
+// prior period, usually a misread decimal point or a crore/lakh slip, or a negative where the
+// item is never negative. Checked whenever a document is stored
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn};
+
+use crate::duplicates;
+use crate::periods::{self, Basis, Period};
+use crate::statements::{self, StoredDocument};
+
+// A line item rarely moves 20x in a year; a decimal slip or wrong scale moves it 10x to 10^7x
+const JUMP_FACTOR: f64 = 20.0;
+// How close log10 of the jump must be to a whole number to read as a scale slip
+const SCALE_TOLERANCE: f64 = 0.05;
+// Per-share, percentage and ratio lines move too freely to judge by magnitude
+const UNSCALED_MARKERS: &[&str] = &["%", "per share", "ratio", "margin", "eps", "times"];
+// Lines a company reports as zero or more; a negative is an extraction error
+const NEVER_NEGATIVE: &[&str] = &[
+    "revenue from operations",
+    "total income",
+    "total revenue",
+    "total assets",
+    "total equity and liabilities",
+    "equity share capital",
+    "share capital",
+    "inventories",
+    "trade receivables",
+    "cash and cash equivalents",
+    "property, plant and equipment",
+    "total non-current assets",
+    "total current assets",
+    "depreciation and amortisation expense",
+    "depreciation and amortization expense",
+    "employee benefits expense",
+    "finance costs",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnomalyKind {
+    /// Tens of times larger or smaller than the same period a year earlier
+    MagnitudeJump,
+    /// Negative on a line that is never negative
+    SignFlip,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Anomaly {
+    pub item_id: String,
+    pub label: String,
+    pub kind: AnomalyKind,
+    pub period: Period,
+    pub value: f64,
+    /// The same period a year earlier, from this document or another of the company's
+    pub prior_period: Option<Period>,
+    pub prior_value: Option<f64>,
+    /// For jumps that are almost exactly a power of ten (10, 100, 1e5 lakh, 1e7 crore), the
+    /// factor the value is probably off by
+    pub likely_scale_error: Option<f64>,
+    pub message: String,
+}
+
+fn normalize_label(label: &str) -> String {
+    label.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// The power of ten `ratio` sits on, when it sits on one.
+fn scale_error(ratio: f64) -> Option<f64> {
+    let exponent = ratio.log10();
+    let nearest = exponent.round();
+    ((exponent - nearest).abs() <= SCALE_TOLERANCE && nearest != 0.0).then(|| 10f64.powf(nearest))
+}
+
+fn scale_name(factor: f64) -> &'static str {
+    match factor.abs().log10().round().abs() as i32 {
+        1 | 3 => "a misplaced decimal point",
+        2 => "lakh and crore mixed up",
+        5 => "lakh read as units",
+        7 => "crore read as units",
+        _ => "a wrong scale",
+    }
+}
+
+fn magnitude_jump(value: f64, prior: f64) -> Option<(f64, Option<f64>)> {
+    if value == 0.0 || prior == 0.0 {
+        return None;
+    }
+    let ratio = (value / prior).abs();
+    (ratio >= JUMP_FACTOR || ratio <= 1.0 / JUMP_FACTOR).then(|| (ratio, scale_error(ratio)))
+}
+
+/// Each item's amounts by period, with the company's other documents filling in the periods
+/// before. The item's own amounts win where both report a period.
+fn item_history(others: &[StoredDocument], item: &serde_json::Value) -> BTreeMap<Period, f64> {
+    let label = item["label"].as_str().unwrap_or("");
+    let basis = item["basis"].as_str().and_then(Basis::parse);
+    let mut history: BTreeMap<Period, f64> = periods::trend(others, label, basis).into_iter()
+        .map(|point| (point.period, point.amount))
+        .collect();
+    if let Some(amounts) = item["periods"].as_object() {
+        history.extend(amounts.iter().filter_map(|(period, value)| Some((Period::parse(period)?, value.as_f64()?))));
+    }
+    history
+}
+
+/// Implausible figures in `document`, judged against its own prior-year column and the
+/// company's other documents.
+pub fn find(app: &AppHandle, document_id: i64) -> Result<Vec<Anomaly>, String> {
+    let document = statements::load_document(app, document_id)?;
+    let mut others = Vec::new();
+    if let Some(company) = duplicates::company(&document) {
+        for summary in statements::list_documents(app)? {
+            if summary.id == document_id {
+                continue;
+            }
+            let other = statements::load_document(app, summary.id)?;
+            if duplicates::company(&other).as_deref() == Some(company.as_str()) {
+                others.push(other);
+            }
+        }
+        others.sort_by_key(|other| other.id);
+    }
+
+    let mut anomalies = Vec::new();
+    for item in &document.items {
+        if item["isHeader"].as_bool().unwrap_or(false) {
+            continue;
+        }
+        let Some(own) = item["periods"].as_object().filter(|amounts| !amounts.is_empty()) else { continue };
+        let label = item["label"].as_str().unwrap_or("").to_string();
+        let normalized = normalize_label(&label);
+        let item_id = item["id"].as_str().unwrap_or("").to_string();
+        let unscaled = UNSCALED_MARKERS.iter().any(|marker| normalized.contains(marker));
+        let never_negative = NEVER_NEGATIVE.iter().any(|line| normalized == *line || normalized.starts_with(&format!("{} ", line)));
+        let history = item_history(&others, item);
+
+        for (period, value) in own.iter().filter_map(|(period, value)| Some((Period::parse(period)?, value.as_f64()?))) {
+            let prior_period = period.prior();
+            let prior = history.get(&prior_period).copied();
+            if never_negative && value < 0.0 {
+                anomalies.push(Anomaly {
+                    item_id: item_id.clone(),
+                    label: label.clone(),
+                    kind: AnomalyKind::SignFlip,
+                    period,
+                    value,
+                    prior_period: prior.map(|_| prior_period),
+                    prior_value: prior,
+                    likely_scale_error: None,
+                    message: format!("{} is negative ({}) in {}; it is never below zero", label, value, period),
+                });
+            }
+            let Some(prior_value) = prior.filter(|_| !unscaled) else { continue };
+            let Some((ratio, factor)) = magnitude_jump(value, prior_value) else { continue };
+            let times = if ratio >= 1.0 { format!("{:.0}x", ratio) } else { format!("1/{:.0}", 1.0 / ratio) };
+            let message = match factor {
+                Some(factor) => format!("{} in {} is {} of {} ({}); looks like {}", label, period, times, prior_period, prior_value, scale_name(factor)),
+                None => format!("{} in {} is {} of {} ({})", label, period, times, prior_period, prior_value),
+            };
+            anomalies.push(Anomaly {
+                item_id: item_id.clone(),
+                label: label.clone(),
+                kind: AnomalyKind::MagnitudeJump,
+                period,
+                value,
+                prior_period: Some(prior_period),
+                prior_value: Some(prior_value),
+                likely_scale_error: factor.map(|factor| if ratio >= 1.0 { factor } else { 1.0 / factor }),
+                message,
+            });
+        }
+    }
+    Ok(anomalies)
+}
+
+/// Emit `anomalies-found` when a document just stored has implausible figures. Never fails the
+/// import that stored it.
+pub fn check(app: &AppHandle, document_id: i64) {
+    match find(app, document_id) {
+        Ok(anomalies) if !anomalies.is_empty() => {
+            info!(document_id, anomalies = anomalies.len(), "Stored document has implausible figures");
+            let _ = app.emit("anomalies-found", serde_json::json!({ "documentId": document_id, "anomalies": anomalies }));
+        }
+        Ok(_) => {}
+        Err(e) => warn!(document_id, "Anomaly check failed: {}", e),
+    }
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn get_document_anomalies(app: AppHandle, document_id: i64) -> Result<Vec<Anomaly>, String> {
+    find(&app, document_id)
+}
@@ -0,0 +1,464 @@
+// API Server - optional local REST API (127.0.0.1 + bearer token) so scripts and
+// Excel add-ins can drive the calculator through the same modules the UI uses,
+// plus a WebSocket at /events rebroadcasting the real-time events the UI receives
+// and MCP (streamable HTTP, JSON responses) at /mcp
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::extract::{Query, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Listener, Manager};
+use tokio::net::TcpListener;
+use tokio::sync::{broadcast, oneshot};
+use tracing::{error, info, warn};
+
+use crate::app_lock;
+use crate::cli;
+use crate::diagnostics;
+use crate::mcp;
+use crate::ratios::{self, Ratio};
+use crate::scraper::{self, BulkQuoteResult};
+use crate::settings::{self, ApiServerSettings, SettingsState};
+use crate::statements;
+
+const TOKEN_HEADER: &str = "x-api-token";
+// Events a slow WebSocket client may fall behind by before it starts missing some
+const EVENT_BUFFER: usize = 256;
+
+/// Internal events forwarded to /events subscribers. Settings and profile events stay
+/// private: their payloads can carry API keys.
+const BRIDGED_EVENTS: &[&str] = &[
+    "pdf-progress",
+    "db-update",
+    "quote-update",
+    "watchlist-update",
+    "alert-triggered",
+    "job-updated",
+    "background-task-updated",
+];
+
+struct Running {
+    port: u16,
+    shutdown: oneshot::Sender<()>,
+    // Dropped with the server, which closes every open /events socket
+    events: broadcast::Sender<(String, String)>,
+}
+
+pub struct ApiServer {
+    running: Mutex<Option<Running>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiServerStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub port: u16,
+    /// e.g. "http://127.0.0.1:8765" while running
+    pub address: Option<String>,
+    pub token: String,
+}
+
+struct ApiError(StatusCode, String);
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(serde_json::json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        ApiError(StatusCode::UNPROCESSABLE_ENTITY, message)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AnalyzeBody {
+    path: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DocumentQuery {
+    /// Defaults to the most recently imported document
+    document_id: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct EventsQuery {
+    /// Comma-separated event names; all bridged events when absent
+    events: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct QuotesQuery {
+    /// Comma-separated, e.g. "RELIANCE,TCS"
+    symbols: String,
+    exchange: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AnalyzeResult {
+    document_id: i64,
+    filename: String,
+    item_count: usize,
+    ratios: Vec<Ratio>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ItemsResult {
+    document_id: i64,
+    filename: String,
+    items: Vec<serde_json::Value>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MetricsResult {
+    document_id: i64,
+    filename: String,
+    ratios: Vec<Ratio>,
+}
+
+fn settings(app: &AppHandle) -> ApiServerSettings {
+    let state = app.state::<SettingsState>();
+    let api_server = settings::blocking_read(&state).get().api_server.clone();
+    api_server
+}
+
+/// 32 random bytes, hex encoded.
+fn generate_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes).map_err(|e| e.to_string())?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+// Compare without bailing at the first differing byte
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given.bytes().zip(expected.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// Browsers can't set headers on a WebSocket handshake, so /events also takes `?token=`.
+fn query_token(request: &Request) -> Option<String> {
+    if request.uri().path() != "/events" {
+        return None;
+    }
+    request.uri().query()?.split('&').find_map(|pair| {
+        let (key, value) = pair.split_once('=')?;
+        (key == "token").then(|| value.to_string())
+    })
+}
+
+/// The token clients must present; none while it's only the redaction marker the app lock
+/// leaves in its place.
+fn usable_token(config: &ApiServerSettings) -> Option<&str> {
+    Some(config.token.as_str()).filter(|token| !token.is_empty() && *token != diagnostics::REDACTED)
+}
+
+/// Whether `given` is the local API token; shared by the REST, WebSocket and gRPC endpoints.
+/// Nothing is accepted while the workspace is locked.
+pub fn token_accepted(app: &AppHandle, given: &str) -> bool {
+    if app_lock::is_locked(app) {
+        return false;
+    }
+    let config = settings(app);
+    usable_token(&config).is_some_and(|expected| tokens_match(given.trim(), expected))
+}
+
+/// The local API token, generated and saved on first use.
+pub fn ensure_token(app: &AppHandle) -> Result<String, String> {
+    let mut config = settings(app);
+    if config.token.is_empty() {
+        config.token = generate_token()?;
+        let state = app.state::<SettingsState>();
+        let mut store = settings::blocking_write(&state);
+        store.set_api_server(config.clone());
+        store.save()?;
+    }
+    Ok(config.token)
+}
+
+async fn require_token(State(app): State<AppHandle>, request: Request, next: Next) -> Response {
+    let headers = request.headers();
+    let given = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .or_else(|| headers.get(TOKEN_HEADER).and_then(|v| v.to_str().ok()))
+        .map(str::to_string)
+        .or_else(|| query_token(&request))
+        .unwrap_or_default();
+    if !token_accepted(&app, &given) {
+        warn!(path = %request.uri().path(), "Rejected API request with a missing or wrong token");
+        return ApiError(StatusCode::UNAUTHORIZED, "Missing or invalid API token".to_string()).into_response();
+    }
+    next.run(request).await
+}
+
+fn document_id(app: &AppHandle, query: &DocumentQuery) -> Result<i64, ApiError> {
+    match query.document_id {
+        Some(id) => Ok(id),
+        None => statements::latest_document_id(app).map_err(|e| ApiError(StatusCode::NOT_FOUND, e)),
+    }
+}
+
+async fn analyze(State(app): State<AppHandle>, Json(body): Json<AnalyzeBody>) -> Result<Json<AnalyzeResult>, ApiError> {
+    let path = PathBuf::from(body.path.trim());
+    if !path.is_file() {
+        return Err(ApiError(StatusCode::BAD_REQUEST, format!("File not found: {}", path.display())));
+    }
+    info!(path = %path.display(), "API analyze request");
+    let document_id = cli::import(&app, &path).await?;
+    let document = statements::load_document(&app, document_id)?;
+    Ok(Json(AnalyzeResult {
+        document_id,
+        ratios: ratios::for_document(&document),
+        item_count: document.items.len(),
+        filename: document.filename,
+    }))
+}
+
+async fn items(State(app): State<AppHandle>, Query(query): Query<DocumentQuery>) -> Result<Json<ItemsResult>, ApiError> {
+    let document_id = document_id(&app, &query)?;
+    let document = statements::load_document(&app, document_id).map_err(|e| ApiError(StatusCode::NOT_FOUND, e))?;
+    Ok(Json(ItemsResult { document_id, filename: document.filename, items: document.items }))
+}
+
+async fn metrics(State(app): State<AppHandle>, Query(query): Query<DocumentQuery>) -> Result<Json<MetricsResult>, ApiError> {
+    let document_id = document_id(&app, &query)?;
+    let document = statements::load_document(&app, document_id).map_err(|e| ApiError(StatusCode::NOT_FOUND, e))?;
+    let ratios = ratios::for_document(&document);
+    Ok(Json(MetricsResult { document_id, filename: document.filename, ratios }))
+}
+
+async fn quotes(State(app): State<AppHandle>, Query(query): Query<QuotesQuery>) -> Result<Json<Vec<BulkQuoteResult>>, ApiError> {
+    let symbols: Vec<String> = query.symbols
+        .split(',')
+        .map(|s| s.trim().to_uppercase())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if symbols.is_empty() || symbols.len() > scraper::MAX_QUOTE_LIST {
+        return Err(ApiError(StatusCode::BAD_REQUEST, format!("Pass between 1 and {} symbols", scraper::MAX_QUOTE_LIST)));
+    }
+    let exchange = query.exchange.unwrap_or_else(|| "NSE".to_string()).to_uppercase();
+    Ok(Json(scraper::quote_list(&app, symbols, &exchange).await))
+}
+
+/// One JSON-RPC message per POST; notifications are acknowledged with 202 and no body.
+async fn mcp_message(State(app): State<AppHandle>, Json(message): Json<serde_json::Value>) -> Response {
+    match mcp::handle(&app, &message).await {
+        Some(reply) => Json(reply).into_response(),
+        None => StatusCode::ACCEPTED.into_response(),
+    }
+}
+
+async fn events(
+    State(app): State<AppHandle>,
+    Query(query): Query<EventsQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    let wanted: Option<Vec<String>> = query.events.map(|names| {
+        names.split(',').map(|n| n.trim().to_string()).filter(|n| !n.is_empty()).collect()
+    });
+    if let Some(unknown) = wanted.iter().flatten().find(|name| !BRIDGED_EVENTS.contains(&name.as_str())) {
+        return Err(ApiError(StatusCode::BAD_REQUEST, format!("Unknown event: {}", unknown)));
+    }
+    let receiver = app.state::<ApiServer>()
+        .subscribe()
+        .ok_or_else(|| ApiError(StatusCode::SERVICE_UNAVAILABLE, "API server is stopping".to_string()))?;
+    Ok(upgrade.on_upgrade(move |socket| forward_events(socket, receiver, wanted)))
+}
+
+/// Send each event as `{"event": name, "payload": ...}` until either side goes away.
+async fn forward_events(mut socket: WebSocket, mut receiver: broadcast::Receiver<(String, String)>, wanted: Option<Vec<String>>) {
+    loop {
+        tokio::select! {
+            event = receiver.recv() => match event {
+                Ok((name, payload)) => {
+                    if wanted.as_ref().is_some_and(|wanted| !wanted.contains(&name)) {
+                        continue;
+                    }
+                    let payload: serde_json::Value = serde_json::from_str(&payload).unwrap_or(serde_json::Value::Null);
+                    let message = serde_json::json!({ "event": name, "payload": payload }).to_string();
+                    if socket.send(Message::Text(message.into())).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    warn!(skipped, "WebSocket client fell behind; events dropped");
+                    let message = serde_json::json!({ "event": "lagged", "payload": { "skipped": skipped } }).to_string();
+                    if socket.send(Message::Text(message.into())).await.is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                // Clients only listen; pings are answered by axum
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    let _ = socket.send(Message::Close(None)).await;
+}
+
+fn router(app: AppHandle) -> Router {
+    Router::new()
+        .route("/analyze", post(analyze))
+        .route("/items", get(items))
+        .route("/metrics", get(metrics))
+        .route("/quotes", get(quotes))
+        .route("/events", get(events))
+        .route("/mcp", post(mcp_message))
+        .route_layer(middleware::from_fn_with_state(app.clone(), require_token))
+        .with_state(app)
+}
+
+impl ApiServer {
+    pub fn new() -> Self {
+        Self { running: Mutex::new(None) }
+    }
+
+    fn port(&self) -> Option<u16> {
+        self.running.lock().ok().and_then(|running| running.as_ref().map(|r| r.port))
+    }
+
+    fn subscribe(&self) -> Option<broadcast::Receiver<(String, String)>> {
+        self.running.lock().ok()?.as_ref().map(|r| r.events.subscribe())
+    }
+
+    /// Hand an event to connected /events clients; a no-op while stopped or nobody listens.
+    fn publish(&self, name: &str, payload: &str) {
+        if let Ok(running) = self.running.lock() {
+            if let Some(running) = running.as_ref() {
+                let _ = running.events.send((name.to_string(), payload.to_string()));
+            }
+        }
+    }
+
+    pub async fn start(&self, app: &AppHandle, port: u16) -> Result<(), String> {
+        self.stop();
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .await
+            .map_err(|e| format!("Could not listen on 127.0.0.1:{}: {}", port, e))?;
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let router = router(app.clone());
+        tauri::async_runtime::spawn(async move {
+            let served = axum::serve(listener, router)
+                .with_graceful_shutdown(async {
+                    let _ = stopped.await;
+                })
+                .await;
+            if let Err(e) = served {
+                error!("API server stopped: {}", e);
+            }
+        });
+        let (events, _) = broadcast::channel(EVENT_BUFFER);
+        if let Ok(mut running) = self.running.lock() {
+            *running = Some(Running { port, shutdown, events });
+        }
+        info!(port, "API server listening on 127.0.0.1");
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        let running = self.running.lock().ok().and_then(|mut running| running.take());
+        if let Some(running) = running {
+            let _ = running.shutdown.send(());
+            info!(port = running.port, "API server stopped");
+        }
+    }
+}
+
+/// Listen for the bridged events for the life of the app; they reach clients only while the server runs.
+pub fn bridge_events(app: &AppHandle) {
+    for name in BRIDGED_EVENTS {
+        let handle = app.clone();
+        app.listen_any(*name, move |event| {
+            if let Some(server) = handle.try_state::<ApiServer>() {
+                server.publish(name, event.payload());
+            }
+        });
+    }
+}
+
+/// Start the server at launch, or on unlocking, when it was left enabled.
+pub fn start_if_enabled(app: AppHandle) {
+    let config = settings(&app);
+    if !config.enabled || usable_token(&config).is_none() || app_lock::is_locked(&app) {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = app.state::<ApiServer>().start(&app, config.port).await {
+            error!("{}", e);
+        }
+    });
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn get_api_server_status(app: AppHandle, server: tauri::State<'_, ApiServer>) -> ApiServerStatus {
+    let config = settings(&app);
+    let running = server.port();
+    ApiServerStatus {
+        enabled: config.enabled,
+        running: running.is_some(),
+        port: running.unwrap_or(config.port),
+        address: running.map(|port| format!("http://127.0.0.1:{}", port)),
+        token: config.token,
+    }
+}
+
+/// Turn the server on or off and remember the choice; a token is generated on first use.
+#[tauri::command]
+pub async fn set_api_server_enabled(
+    app: AppHandle,
+    server: tauri::State<'_, ApiServer>,
+    enabled: bool,
+    port: Option<u16>,
+) -> Result<ApiServerStatus, String> {
+    let token = ensure_token(&app)?;
+    let mut config = settings(&app);
+    config.enabled = enabled;
+    config.token = token;
+    if let Some(port) = port {
+        config.port = port;
+    }
+    if enabled {
+        server.start(&app, config.port).await?;
+    } else {
+        server.stop();
+    }
+    {
+        let state = app.state::<SettingsState>();
+        let mut store = state.write().await;
+        store.set_api_server(config);
+        store.save()?;
+    }
+    Ok(get_api_server_status(app.clone(), server))
+}
+
+/// Replace the token; clients using the old one are rejected from the next request.
+#[tauri::command]
+pub fn regenerate_api_token(app: AppHandle, server: tauri::State<'_, ApiServer>) -> Result<ApiServerStatus, String> {
+    let mut config = settings(&app);
+    config.token = generate_token()?;
+    {
+        let state = app.state::<SettingsState>();
+        let mut store = settings::blocking_write(&state);
+        store.set_api_server(config);
+        store.save()?;
+    }
+    Ok(get_api_server_status(app, server))
+}
@@ -0,0 +1,457 @@
+// App Lock - an optional passphrase or PIN for shared machines. While locked, every profile's
+// databases and chat history exist only sealed with ChaCha20-Poly1305 under a key derived from the
+// passphrase, the secrets in their settings.json are replaced by the redaction marker, and every
+// command but unlocking is refused. The workspace locks on exit, after the idle time and on
+// request; `unlock_workspace` reverses it.
+use ring::aead::LessSafeKey;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::ipc::Invoke;
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use tracing::{info, warn};
+
+use crate::api_server::{self, ApiServer};
+use crate::backup;
+use crate::diagnostics;
+use crate::grpc::{self, GrpcServer};
+use crate::profiles;
+use crate::settings::{self, AppSettings, SettingsState, SettingsStore};
+use crate::tasks;
+use crate::workspace;
+
+pub const LOCKED_ERROR: &str = "The workspace is locked; unlock it with your passphrase";
+const CONFIG_FILE: &str = "app_lock.json";
+const SEALED_EXTENSION: &str = "fclock";
+const MAGIC: &[u8] = b"FCLK\x01";
+const SALT_LEN: usize = 16;
+// Sealed with the key at setup; opening it proves a passphrase right without touching any file
+const CHECK_TEXT: &[u8] = b"fincalc-app-lock";
+// A 4-digit PIN is the shortest accepted
+const MIN_PASSPHRASE_LEN: usize = 4;
+const IDLE_CHECK_SECS: u64 = 30;
+// How often paused background work looks for the workspace to be unlocked
+const UNLOCK_CHECK_SECS: u64 = 2;
+// What the lock screen needs before the passphrase is in
+const LOCKED_COMMANDS: [&str; 4] = ["unlock_workspace", "get_app_lock_status", "get_settings", "get_preflight_status"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LockConfig {
+    salt: String,
+    check: String,
+    /// 0 never locks for idleness
+    idle_minutes: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppLockStatus {
+    pub enabled: bool,
+    pub locked: bool,
+    pub idle_minutes: u32,
+    /// Seconds since the last reported activity, while unlocked
+    pub idle_secs: Option<i64>,
+}
+
+/// The lock's configuration, and the key while the workspace is unlocked.
+pub struct AppLock {
+    config: Mutex<Option<LockConfig>>,
+    key: Mutex<Option<LessSafeKey>>,
+    last_activity: AtomicI64,
+}
+
+impl AppLock {
+    /// Reads the configuration; with the lock on, the app starts locked.
+    pub fn new(app: &AppHandle) -> Self {
+        let config = config_path(app).ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok());
+        Self { config: Mutex::new(config), key: Mutex::new(None), last_activity: AtomicI64::new(now_secs()) }
+    }
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn unhex(text: &str) -> Result<Vec<u8>, String> {
+    (0..text.len())
+        .step_by(2)
+        .map(|i| text.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| "The app lock configuration is corrupted".to_string())
+}
+
+fn config_path(app: &AppHandle) -> Result<PathBuf, String> {
+    Ok(app.path().app_data_dir().map_err(|e| e.to_string())?.join(CONFIG_FILE))
+}
+
+fn save_config(app: &AppHandle, config: &LockConfig) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(config).map_err(|e| e.to_string())?;
+    settings::write_atomic(&config_path(app)?, json.as_bytes())
+}
+
+/// MAGIC | nonce | ciphertext+tag, under the same encryption as cloud backups
+fn seal(key: &LessSafeKey, data: Vec<u8>) -> Result<Vec<u8>, String> {
+    let sealed = backup::seal(key, MAGIC, data).map_err(|_| "Failed to encrypt the workspace".to_string())?;
+    Ok([MAGIC, &sealed].concat())
+}
+
+fn open(key: &LessSafeKey, sealed: &[u8]) -> Result<Vec<u8>, String> {
+    let body = sealed.strip_prefix(MAGIC).ok_or("Not a sealed workspace file")?;
+    backup::open(key, MAGIC, body).map_err(|_| "Wrong passphrase, or a sealed file is corrupted".to_string())
+}
+
+/// The key for `passphrase` if it is the lock's passphrase.
+fn verify(config: &LockConfig, passphrase: &str) -> Result<LessSafeKey, String> {
+    let key = backup::derive_key(passphrase, &unhex(&config.salt)?)?;
+    open(&key, &unhex(&config.check)?).map_err(|_| "Wrong passphrase".to_string())?;
+    Ok(key)
+}
+
+fn sealed_path(path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.{}", path.display(), SEALED_EXTENSION))
+}
+
+/// Every profile's files the lock covers, by their plaintext paths.
+fn workspace_files(app: &AppHandle) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for (_, profile) in profiles::all_profile_files(app) {
+        files.push(profile.db);
+        files.push(profile.app_db);
+        let Ok(entries) = fs::read_dir(&profile.chat_history) else { continue };
+        for path in entries.filter_map(|e| e.ok().map(|e| e.path())).filter(|p| p.is_file()) {
+            let plain = match path.extension() {
+                Some(extension) if extension == SEALED_EXTENSION => path.with_extension(""),
+                _ => path,
+            };
+            if !files.contains(&plain) {
+                files.push(plain);
+            }
+        }
+    }
+    files
+}
+
+/// Seal a file in place. Databases are checkpointed first so nothing is left in their WAL.
+fn seal_file(key: &LessSafeKey, path: &Path) -> Result<(), String> {
+    if !path.exists() {
+        return Ok(());
+    }
+    if path.extension().is_some_and(|extension| extension == "db") {
+        let conn = Connection::open(path).map_err(|e| e.to_string())?;
+        conn.execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")
+            .map_err(|e| format!("Failed to checkpoint {}: {}", path.display(), e))?;
+    }
+    let data = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    backup::replace_file(&sealed_path(path), &seal(key, data)?)?;
+    fs::remove_file(path).map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+    for suffix in ["-wal", "-shm", "-journal"] {
+        let _ = fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+    Ok(())
+}
+
+/// Restore a sealed file over whatever is at its plaintext path, e.g. an empty database a
+/// background task created while locked.
+fn open_file(key: &LessSafeKey, path: &Path) -> Result<(), String> {
+    let sealed = sealed_path(path);
+    if !sealed.exists() {
+        return Ok(());
+    }
+    let data = fs::read(&sealed).map_err(|e| format!("Failed to read {}: {}", sealed.display(), e))?;
+    for suffix in ["-wal", "-shm", "-journal"] {
+        let _ = fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+    backup::replace_file(path, &open(key, &data)?)?;
+    fs::remove_file(&sealed).map_err(|e| format!("Failed to remove {}: {}", sealed.display(), e))
+}
+
+/// Swap the secrets in a profile's settings.json, in memory and on disk, for the redaction marker
+/// and keep the originals sealed beside it.
+fn seal_secrets(store: &mut SettingsStore, key: &LessSafeKey) -> Result<(), String> {
+    let current = serde_json::to_value(store.get()).map_err(|e| e.to_string())?;
+    let redacted = diagnostics::redact(current.clone(), false);
+    if redacted == current {
+        return Ok(());
+    }
+    let sealed = sealed_path(store.path());
+    backup::replace_file(&sealed, &seal(key, serde_json::to_vec(&current).map_err(|e| e.to_string())?)?)?;
+    let redacted: AppSettings = serde_json::from_value(redacted).map_err(|e| e.to_string())?;
+    store.replace(redacted);
+    // Twice, so the rotating backup copy holds the redacted file too
+    store.save()?;
+    store.save()
+}
+
+fn open_secrets(store: &mut SettingsStore, key: &LessSafeKey) -> Result<(), String> {
+    let sealed = sealed_path(store.path());
+    if !sealed.exists() {
+        return Ok(());
+    }
+    let original: serde_json::Value = serde_json::from_slice(&open(key, &fs::read(&sealed).map_err(|e| e.to_string())?)?)
+        .map_err(|e| e.to_string())?;
+    // Settings changed while locked are kept; only the marked secrets come back
+    let current = serde_json::to_value(store.get()).map_err(|e| e.to_string())?;
+    let restored: AppSettings = serde_json::from_value(workspace::restore_secrets(current, &original))
+        .map_err(|e| e.to_string())?;
+    store.replace(restored);
+    store.save()?;
+    fs::remove_file(&sealed).map_err(|e| e.to_string())
+}
+
+/// Apply `change` to every profile's settings: the active profile's store, so what's in memory
+/// changes too, and the others' files.
+fn each_settings(app: &AppHandle, mut change: impl FnMut(&mut SettingsStore) -> Result<(), String>) -> Result<(), String> {
+    let active = profiles::active_profile_id(app);
+    for (id, profile) in profiles::all_profile_files(app) {
+        if id == active {
+            let state = app.state::<SettingsState>();
+            change(&mut settings::blocking_write(&state))?;
+        } else if profile.settings.exists() {
+            change(&mut SettingsStore::new(profile.settings)?)?;
+        }
+    }
+    Ok(())
+}
+
+/// Seal every profile's files and secrets. If any of it fails, what was sealed is opened again,
+/// so the workspace ends up either sealed whole or as it was.
+fn seal_all(app: &AppHandle, key: &LessSafeKey) -> Result<(), String> {
+    let result = each_settings(app, |store| seal_secrets(store, key))
+        .and_then(|_| workspace_files(app).iter().try_for_each(|path| seal_file(key, path)));
+    if result.is_err() {
+        if let Err(e) = open_all(app, key) {
+            warn!("Failed to reopen the workspace after sealing failed: {}", e);
+        }
+    }
+    result
+}
+
+/// Open every profile's sealed files and secrets. Keeps going past a failure, so as much as
+/// possible is opened, and returns the first.
+fn open_all(app: &AppHandle, key: &LessSafeKey) -> Result<(), String> {
+    let mut first_error = None;
+    for path in workspace_files(app) {
+        if let Err(e) = open_file(key, &path) {
+            first_error.get_or_insert(e);
+        }
+    }
+    if let Err(e) = each_settings(app, |store| open_secrets(store, key)) {
+        first_error.get_or_insert(e);
+    }
+    first_error.map_or(Ok(()), Err)
+}
+
+/// Whether the lock is on and the passphrase hasn't been given yet.
+pub fn is_locked(app: &AppHandle) -> bool {
+    let Some(lock) = app.try_state::<AppLock>() else { return false };
+    let enabled = lock.config.lock().map(|config| config.is_some()).unwrap_or(false);
+    enabled && lock.key.lock().map(|key| key.is_none()).unwrap_or(true)
+}
+
+/// Returns once the workspace is unlocked. Background work (queued jobs, retention sweeps) waits
+/// here rather than touch sealed files.
+pub async fn unlocked(app: &AppHandle) {
+    while is_locked(app) {
+        tokio::time::sleep(Duration::from_secs(UNLOCK_CHECK_SECS)).await;
+    }
+}
+
+/// `unlocked` for a plain thread, such as a folder watcher's.
+pub fn block_until_unlocked(app: &AppHandle) {
+    while is_locked(app) {
+        std::thread::sleep(Duration::from_secs(UNLOCK_CHECK_SECS));
+    }
+}
+
+/// The REST and gRPC servers left enabled, which don't start while locked.
+fn start_servers(app: &AppHandle) {
+    api_server::start_if_enabled(app.clone());
+    grpc::start_if_enabled(app.clone());
+}
+
+/// Seal every profile. The key is dropped first, so nothing opens the app DB meanwhile; if
+/// sealing fails the workspace stays unlocked.
+fn seal_workspace(app: &AppHandle) -> Result<(), String> {
+    let lock = app.state::<AppLock>();
+    let Some(key) = lock.key.lock().map_err(|e| e.to_string())?.take() else { return Ok(()) };
+    // The local servers would answer for the workspace while it's sealed
+    app.state::<ApiServer>().stop();
+    app.state::<GrpcServer>().stop();
+    if let Err(e) = seal_all(app, &key) {
+        *lock.key.lock().map_err(|e| e.to_string())? = Some(key);
+        start_servers(app);
+        return Err(e);
+    }
+    info!("Workspace locked");
+    let _ = app.emit("app-locked", ());
+    Ok(())
+}
+
+fn open_workspace(app: &AppHandle, passphrase: &str) -> Result<(), String> {
+    let lock = app.state::<AppLock>();
+    let config = lock.config.lock().map_err(|e| e.to_string())?.clone().ok_or("The app lock is off")?;
+    let key = verify(&config, passphrase)?;
+    open_all(app, &key)?;
+    *lock.key.lock().map_err(|e| e.to_string())? = Some(key);
+    lock.last_activity.store(now_secs(), Ordering::Relaxed);
+    start_servers(app);
+    info!("Workspace unlocked");
+    let _ = app.emit("app-unlocked", ());
+    Ok(())
+}
+
+/// Lock on exit, after background tasks have stopped.
+pub fn lock_on_exit(app: &AppHandle) {
+    if let Err(e) = seal_workspace(app) {
+        warn!("Failed to lock the workspace on exit: {}", e);
+    }
+}
+
+/// Refuse every command but the lock screen's while locked.
+pub fn guarded<F>(handler: F) -> impl Fn(Invoke<Wry>) -> bool + Send + Sync + 'static
+where
+    F: Fn(Invoke<Wry>) -> bool + Send + Sync + 'static,
+{
+    move |invoke| {
+        let app = invoke.message.webview().app_handle().clone();
+        if is_locked(&app) && !LOCKED_COMMANDS.contains(&invoke.message.command()) {
+            invoke.resolver.reject(LOCKED_ERROR);
+            return true;
+        }
+        handler(invoke)
+    }
+}
+
+/// Background loop: locks once nothing has been reported for the idle time. Never while an
+/// analysis or another task is running, whose files would vanish under it.
+pub fn start_idle_task(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_secs(IDLE_CHECK_SECS)).await;
+            let lock = app.state::<AppLock>();
+            let idle_minutes = lock.config.lock().ok().and_then(|config| config.as_ref().map(|c| c.idle_minutes)).unwrap_or(0);
+            let idle = now_secs() - lock.last_activity.load(Ordering::Relaxed);
+            if idle_minutes == 0 || is_locked(&app) || idle < idle_minutes as i64 * 60 || tasks::any_running(&app) {
+                continue;
+            }
+            info!("Locking the workspace after {} idle minutes", idle_minutes);
+            let handle = app.clone();
+            match tauri::async_runtime::spawn_blocking(move || seal_workspace(&handle)).await {
+                Ok(Err(e)) => warn!("Failed to lock the idle workspace: {}", e),
+                Err(e) => warn!("Failed to lock the idle workspace: {}", e),
+                Ok(Ok(())) => {}
+            }
+        }
+    });
+}
+
+// Tauri Commands
+/// Turn the lock on with a passphrase or PIN. Nothing is sealed until the workspace locks.
+#[tauri::command]
+pub fn enable_app_lock(app: AppHandle, lock: tauri::State<'_, AppLock>, passphrase: String, idle_minutes: Option<u32>) -> Result<AppLockStatus, String> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(format!("The passphrase or PIN needs at least {} characters", MIN_PASSPHRASE_LEN));
+    }
+    let mut config = lock.config.lock().map_err(|e| e.to_string())?;
+    if config.is_some() {
+        return Err("The app lock is already on; turn it off first to change the passphrase".to_string());
+    }
+    let mut salt = [0u8; SALT_LEN];
+    getrandom::fill(&mut salt).map_err(|e| e.to_string())?;
+    let key = backup::derive_key(&passphrase, &salt)?;
+    let new_config = LockConfig {
+        salt: backup::hex(&salt),
+        check: backup::hex(&seal(&key, CHECK_TEXT.to_vec())?),
+        idle_minutes: idle_minutes.unwrap_or(0),
+    };
+    save_config(&app, &new_config)?;
+    *config = Some(new_config);
+    *lock.key.lock().map_err(|e| e.to_string())? = Some(key);
+    lock.last_activity.store(now_secs(), Ordering::Relaxed);
+    drop(config);
+    info!("App lock turned on");
+    get_app_lock_status(lock)
+}
+
+/// Turn the lock off; needs the workspace unlocked and the passphrase again. Whatever is still
+/// sealed, in any profile, is opened first: nothing could open it once the key is gone.
+#[tauri::command]
+pub async fn disable_app_lock(app: AppHandle, passphrase: String) -> Result<AppLockStatus, String> {
+    tauri::async_runtime::spawn_blocking(move || turn_off(&app, &passphrase)).await.map_err(|e| e.to_string())?
+}
+
+fn turn_off(app: &AppHandle, passphrase: &str) -> Result<AppLockStatus, String> {
+    let lock = app.state::<AppLock>();
+    let current = lock.config.lock().map_err(|e| e.to_string())?.clone().ok_or("The app lock is off")?;
+    if lock.key.lock().map_err(|e| e.to_string())?.is_none() {
+        return Err(LOCKED_ERROR.to_string());
+    }
+    let key = verify(&current, passphrase)?;
+    open_all(app, &key)?;
+    let mut config = lock.config.lock().map_err(|e| e.to_string())?;
+    let path = config_path(app)?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| e.to_string())?;
+    }
+    *config = None;
+    *lock.key.lock().map_err(|e| e.to_string())? = None;
+    drop(config);
+    info!("App lock turned off");
+    get_app_lock_status(lock)
+}
+
+/// Minutes without reported activity before the workspace locks itself; 0 turns that off.
+#[tauri::command]
+pub fn set_app_lock_idle_minutes(app: AppHandle, lock: tauri::State<'_, AppLock>, minutes: u32) -> Result<AppLockStatus, String> {
+    let mut config = lock.config.lock().map_err(|e| e.to_string())?;
+    let current = config.as_mut().ok_or("The app lock is off")?;
+    current.idle_minutes = minutes;
+    save_config(&app, current)?;
+    drop(config);
+    get_app_lock_status(lock)
+}
+
+/// Seal the workspace now.
+#[tauri::command]
+pub async fn lock_workspace(app: AppHandle) -> Result<(), String> {
+    if app.state::<AppLock>().config.lock().map_err(|e| e.to_string())?.is_none() {
+        return Err("Turn the app lock on first".to_string());
+    }
+    if tasks::any_running(&app) {
+        return Err("Wait for running tasks to finish, or cancel them, before locking".to_string());
+    }
+    tauri::async_runtime::spawn_blocking(move || seal_workspace(&app)).await.map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn unlock_workspace(app: AppHandle, passphrase: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || open_workspace(&app, &passphrase)).await.map_err(|e| e.to_string())?
+}
+
+/// Called by the frontend on user input (throttled); resets the idle timer.
+#[tauri::command]
+pub fn record_activity(lock: tauri::State<'_, AppLock>) {
+    lock.last_activity.store(now_secs(), Ordering::Relaxed);
+}
+
+#[tauri::command]
+pub fn get_app_lock_status(lock: tauri::State<'_, AppLock>) -> Result<AppLockStatus, String> {
+    let config = lock.config.lock().map_err(|e| e.to_string())?.clone();
+    let unlocked = lock.key.lock().map_err(|e| e.to_string())?.is_some();
+    Ok(AppLockStatus {
+        enabled: config.is_some(),
+        locked: config.is_some() && !unlocked,
+        idle_minutes: config.as_ref().map_or(0, |config| config.idle_minutes),
+        idle_secs: unlocked.then(|| now_secs() - lock.last_activity.load(Ordering::Relaxed)),
+    })
+}
@@ -0,0 +1,473 @@
+// Backup - encrypted backups of the active profile to S3-compatible storage (AWS S3, MinIO, R2...)
+//
+// A backup is a zip of the pipeline DB, the app DB and settings.json, sealed with ChaCha20-Poly1305
+// under a key derived from the backup passphrase, so the storage provider only ever sees ciphertext.
+// Objects are named <prefix>fincalc-backup-<profile>-<UTC timestamp>.fcbk and sort oldest first.
+use reqwest::{Client, Method, Url};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::{digest, hmac, pbkdf2};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Cursor, Read, Write};
+use std::num::NonZeroU32;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+use zip::write::SimpleFileOptions;
+
+use crate::http;
+use crate::profiles;
+use crate::settings::{self, AppSettings, CloudBackupSettings, SettingsState};
+
+const MAGIC: &[u8] = b"FCBK\x01";
+const SALT_LEN: usize = 16;
+const PBKDF2_ITERATIONS: NonZeroU32 = NonZeroU32::new(210_000).unwrap();
+const OBJECT_PREFIX: &str = "fincalc-backup-";
+const OBJECT_EXTENSION: &str = ".fcbk";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+// Archive entries
+const MANIFEST_ENTRY: &str = "manifest.json";
+const PIPELINE_DB_ENTRY: &str = "pipeline.db";
+const APP_DB_ENTRY: &str = "app.db";
+const SETTINGS_ENTRY: &str = "settings.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudBackup {
+    pub key: String,
+    pub size: u64,
+    /// As reported by the storage service (ISO 8601)
+    pub last_modified: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupResult {
+    pub backup: CloudBackup,
+    /// Older backups deleted to stay within the retention count
+    pub removed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    format: u32,
+    app_version: String,
+    profile: String,
+    created_at: i64,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn backup_settings(app: &AppHandle) -> CloudBackupSettings {
+    let state = app.state::<SettingsState>();
+    let cloud_backup = settings::blocking_read(&state).get().cloud_backup.clone();
+    cloud_backup
+}
+
+// --- Encryption ---
+
+/// Also keys the app lock's sealed files.
+pub(crate) fn derive_key(passphrase: &str, salt: &[u8]) -> Result<LessSafeKey, String> {
+    let mut key = [0u8; 32];
+    pbkdf2::derive(pbkdf2::PBKDF2_HMAC_SHA256, PBKDF2_ITERATIONS, salt, passphrase.as_bytes(), &mut key);
+    UnboundKey::new(&CHACHA20_POLY1305, &key)
+        .map(LessSafeKey::new)
+        .map_err(|_| "Failed to derive the encryption key".to_string())
+}
+
+/// nonce | ciphertext+tag, authenticated together with `aad` (the caller's file magic).
+pub(crate) fn seal(key: &LessSafeKey, aad: &[u8], mut data: Vec<u8>) -> Result<Vec<u8>, String> {
+    let mut nonce = [0u8; NONCE_LEN];
+    getrandom::fill(&mut nonce).map_err(|e| e.to_string())?;
+    key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(aad), &mut data)
+        .map_err(|_| "Failed to encrypt".to_string())?;
+    Ok([&nonce, data.as_slice()].concat())
+}
+
+/// Reverses `seal`; fails on a wrong key as on tampered or truncated data.
+pub(crate) fn open(key: &LessSafeKey, aad: &[u8], sealed: &[u8]) -> Result<Vec<u8>, String> {
+    if sealed.len() < NONCE_LEN {
+        return Err("Sealed data is truncated".to_string());
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| "Sealed data is truncated".to_string())?;
+    let mut data = ciphertext.to_vec();
+    let plain_len = key.open_in_place(nonce, Aad::from(aad), &mut data)
+        .map_err(|_| "Wrong key, or the sealed data is corrupted".to_string())?
+        .len();
+    data.truncate(plain_len);
+    Ok(data)
+}
+
+/// MAGIC | salt | nonce | ciphertext+tag
+fn seal_backup(passphrase: &str, data: Vec<u8>) -> Result<Vec<u8>, String> {
+    let mut salt = [0u8; SALT_LEN];
+    getrandom::fill(&mut salt).map_err(|e| e.to_string())?;
+    let sealed = seal(&derive_key(passphrase, &salt)?, MAGIC, data)
+        .map_err(|_| "Failed to encrypt the backup".to_string())?;
+    Ok([MAGIC, &salt, &sealed].concat())
+}
+
+fn open_backup(passphrase: &str, sealed: &[u8]) -> Result<Vec<u8>, String> {
+    let body = sealed.strip_prefix(MAGIC).ok_or("Not a Financial Calculator backup")?;
+    if body.len() < SALT_LEN + NONCE_LEN {
+        return Err("Backup is truncated".to_string());
+    }
+    let (salt, rest) = body.split_at(SALT_LEN);
+    open(&derive_key(passphrase, salt)?, MAGIC, rest)
+        .map_err(|_| "Wrong passphrase, or the backup is corrupted".to_string())
+}
+
+// --- S3 (Signature Version 4) ---
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex(digest::digest(&digest::SHA256, data).as_ref())
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), data.as_bytes()).as_ref().to_vec()
+}
+
+/// RFC 3986 percent-encoding as SigV4 wants it; `/` survives in object paths.
+fn uri_encode(value: &str, keep_slash: bool) -> String {
+    value.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            b'/' if keep_slash => "/".to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Text of every `<name>...</name>` in `xml`; ListObjectsV2 replies are flat enough for this.
+fn xml_tags<'a>(xml: &'a str, name: &str) -> Vec<&'a str> {
+    let (open, close) = (format!("<{}>", name), format!("</{}>", name));
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        let Some(end) = after.find(&close) else { break };
+        found.push(&after[..end]);
+        rest = &after[end + close.len()..];
+    }
+    found
+}
+
+fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+struct S3 {
+    client: Client,
+    config: CloudBackupSettings,
+    endpoint: Url,
+}
+
+impl S3 {
+    fn new(client: Client, config: CloudBackupSettings) -> Result<Self, String> {
+        if config.bucket.trim().is_empty() || config.access_key_id.is_empty() || config.secret_access_key.is_empty() {
+            return Err("Cloud backup is not configured; set the bucket and access keys in Settings".to_string());
+        }
+        if config.passphrase.is_empty() {
+            return Err("Set a backup passphrase first; backups are encrypted with it".to_string());
+        }
+        let endpoint = match config.endpoint.trim() {
+            "" => format!("https://s3.{}.amazonaws.com", config.region),
+            endpoint => endpoint.trim_end_matches('/').to_string(),
+        };
+        let endpoint = Url::parse(&endpoint).map_err(|e| format!("Invalid endpoint {}: {}", endpoint, e))?;
+        Ok(Self { client, config, endpoint })
+    }
+
+    /// (host header, canonical path) for an object, or the bucket itself when `key` is empty.
+    fn location(&self, key: &str) -> (String, String) {
+        let mut host = self.endpoint.host_str().unwrap_or_default().to_string();
+        if let Some(port) = self.endpoint.port() {
+            host = format!("{}:{}", host, port);
+        }
+        let bucket = self.config.bucket.trim();
+        let key = uri_encode(key, true);
+        if self.config.path_style {
+            let path = if key.is_empty() { format!("/{}", bucket) } else { format!("/{}/{}", bucket, key) };
+            (host, path)
+        } else {
+            (format!("{}.{}", bucket, host), format!("/{}", key))
+        }
+    }
+
+    async fn send(&self, method: Method, key: &str, query: &[(&str, &str)], body: Vec<u8>) -> Result<reqwest::Response, String> {
+        let (host, path) = self.location(key);
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = sha256_hex(&body);
+
+        let mut query: Vec<(String, String)> = query.iter().map(|(k, v)| (uri_encode(k, false), uri_encode(v, false))).collect();
+        query.sort();
+        let query = query.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("&");
+
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method, path, query, host, payload_hash, amz_date, signed_headers, payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, self.config.region);
+        let string_to_sign = format!("AWS4-HMAC-SHA256\n{}\n{}\n{}", amz_date, scope, sha256_hex(canonical_request.as_bytes()));
+        let signing_key = ["s3", "aws4_request"].iter().fold(
+            hmac_sha256(&hmac_sha256(format!("AWS4{}", self.config.secret_access_key).as_bytes(), &date), &self.config.region),
+            |key, part| hmac_sha256(&key, part),
+        );
+        let authorization = format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            self.config.access_key_id, scope, signed_headers, hex(&hmac_sha256(&signing_key, &string_to_sign))
+        );
+
+        let url = format!("{}://{}{}{}{}", self.endpoint.scheme(), host, path, if query.is_empty() { "" } else { "?" }, query);
+        let response = self.client
+            .request(method, url)
+            .timeout(REQUEST_TIMEOUT)
+            .header("x-amz-date", amz_date)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("authorization", authorization)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| format!("Storage request failed: {}", e))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body = response.text().await.unwrap_or_default();
+        let code = xml_tags(&body, "Code").first().map(|c| c.to_string()).unwrap_or_else(|| status.to_string());
+        let message = xml_tags(&body, "Message").first().map(|m| xml_unescape(m)).unwrap_or_default();
+        Err(format!("Storage returned {}: {} {}", status.as_u16(), code, message).trim_end().to_string())
+    }
+
+    async fn put(&self, key: &str, body: Vec<u8>) -> Result<(), String> {
+        self.send(Method::PUT, key, &[], body).await.map(|_| ())
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, String> {
+        let response = self.send(Method::GET, key, &[], Vec::new()).await?;
+        response.bytes().await.map(|b| b.to_vec()).map_err(|e| e.to_string())
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), String> {
+        self.send(Method::DELETE, key, &[], Vec::new()).await.map(|_| ())
+    }
+
+    /// Objects under `prefix`, sorted by key.
+    async fn list(&self, prefix: &str) -> Result<Vec<CloudBackup>, String> {
+        let mut objects = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let mut query = vec![("list-type", "2"), ("prefix", prefix)];
+            if let Some(token) = token.as_deref() {
+                query.push(("continuation-token", token));
+            }
+            let response = self.send(Method::GET, "", &query, Vec::new()).await?;
+            let xml = response.text().await.map_err(|e| e.to_string())?;
+            for entry in xml_tags(&xml, "Contents") {
+                let Some(key) = xml_tags(entry, "Key").first().map(|k| xml_unescape(k)) else { continue };
+                objects.push(CloudBackup {
+                    key,
+                    size: xml_tags(entry, "Size").first().and_then(|s| s.parse().ok()).unwrap_or(0),
+                    last_modified: xml_tags(entry, "LastModified").first().map(|m| m.to_string()),
+                });
+            }
+            token = match xml_tags(&xml, "IsTruncated").first() {
+                Some(&"true") => xml_tags(&xml, "NextContinuationToken").first().map(|t| xml_unescape(t)),
+                _ => None,
+            };
+            if token.is_none() {
+                break;
+            }
+        }
+        objects.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(objects)
+    }
+}
+
+// --- Archive ---
+
+/// Backups of this profile; every backup key starts with this.
+fn profile_prefix(config: &CloudBackupSettings, profile: &str) -> String {
+    format!("{}{}{}-", config.prefix.trim_start_matches('/'), OBJECT_PREFIX, profile)
+}
+
+/// A consistent copy of a live SQLite database, or None if it doesn't exist yet.
+pub fn snapshot_db(path: &Path, name: &str) -> Result<Option<Vec<u8>>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let copy = std::env::temp_dir().join(format!("fincalc-backup-{}-{}", now_secs(), name));
+    let _ = fs::remove_file(&copy);
+    let conn = Connection::open(path).map_err(|e| e.to_string())?;
+    conn.execute("VACUUM INTO ?1", params![copy.to_string_lossy()])
+        .map_err(|e| format!("Failed to snapshot {}: {}", path.display(), e))?;
+    let bytes = fs::read(&copy).map_err(|e| e.to_string());
+    let _ = fs::remove_file(&copy);
+    bytes.map(Some)
+}
+
+fn build_archive(files: &[(&str, Vec<u8>)]) -> Result<Vec<u8>, String> {
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, contents) in files {
+        zip.start_file(*name, SimpleFileOptions::default()).map_err(|e| e.to_string())?;
+        zip.write_all(contents).map_err(|e| e.to_string())?;
+    }
+    zip.finish().map(|cursor| cursor.into_inner()).map_err(|e| e.to_string())
+}
+
+/// Write next to the target and rename over it, so a failed restore never leaves half a file.
+pub fn replace_file(path: &Path, contents: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let staged = PathBuf::from(format!("{}.restore", path.display()));
+    fs::write(&staged, contents).map_err(|e| e.to_string())?;
+    fs::rename(&staged, path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))
+}
+
+// Tauri Commands
+#[tauri::command]
+pub async fn list_cloud_backups(app: AppHandle) -> Result<Vec<CloudBackup>, String> {
+    settings::ensure_online(&app)?;
+    let config = backup_settings(&app);
+    let prefix = profile_prefix(&config, &profiles::active_profile_id(&app));
+    S3::new(http::client(&app), config)?.list(&prefix).await
+}
+
+/// Encrypt and upload the active profile's databases and settings, then prune old backups.
+#[tauri::command]
+pub async fn backup_to_cloud(app: AppHandle) -> Result<BackupResult, String> {
+    settings::ensure_online(&app)?;
+    let config = backup_settings(&app);
+    let s3 = S3::new(http::client(&app), config.clone())?;
+    let profile = profiles::active_profile_id(&app);
+    let settings_json = {
+        let state = app.state::<SettingsState>();
+        let store = state.read().await;
+        serde_json::to_vec_pretty(store.get()).map_err(|e| e.to_string())?
+    };
+    let manifest = Manifest {
+        format: 1,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        profile: profile.clone(),
+        created_at: now_secs(),
+    };
+    let (pipeline_db, app_db) = (profiles::active_db_path(&app), profiles::active_app_db_path(&app));
+    let passphrase = config.passphrase.clone();
+
+    let sealed = tauri::async_runtime::spawn_blocking(move || -> Result<Vec<u8>, String> {
+        let mut files = vec![(MANIFEST_ENTRY, serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?)];
+        if let Some(bytes) = snapshot_db(&pipeline_db, PIPELINE_DB_ENTRY)? {
+            files.push((PIPELINE_DB_ENTRY, bytes));
+        }
+        if let Some(bytes) = snapshot_db(&app_db, APP_DB_ENTRY)? {
+            files.push((APP_DB_ENTRY, bytes));
+        }
+        files.push((SETTINGS_ENTRY, settings_json));
+        seal_backup(&passphrase, build_archive(&files)?)
+    }).await.map_err(|e| e.to_string())??;
+
+    let prefix = profile_prefix(&config, &profile);
+    let key = format!("{}{}{}", prefix, chrono::Utc::now().format("%Y%m%dT%H%M%SZ"), OBJECT_EXTENSION);
+    let size = sealed.len() as u64;
+    s3.put(&key, sealed).await?;
+    info!(key = %key, size, "Uploaded cloud backup");
+
+    let mut removed = Vec::new();
+    let backups: Vec<CloudBackup> = s3.list(&prefix).await?.into_iter().filter(|b| b.key.ends_with(OBJECT_EXTENSION)).collect();
+    let excess = backups.len().saturating_sub(config.retention.max(1) as usize);
+    for old in &backups[..excess] {
+        match s3.delete(&old.key).await {
+            Ok(()) => removed.push(old.key.clone()),
+            // The new backup is safe; a failed prune is retried on the next run
+            Err(e) => warn!(key = %old.key, "Failed to remove old backup: {}", e),
+        }
+    }
+    Ok(BackupResult { backup: CloudBackup { key, size, last_modified: None }, removed })
+}
+
+/// Replace the active profile's databases and settings with a backup (the newest without `key`).
+/// The current cloud backup settings are kept so later backups go to the same place.
+#[tauri::command]
+pub async fn restore_from_cloud(app: AppHandle, key: Option<String>) -> Result<CloudBackup, String> {
+    settings::ensure_online(&app)?;
+    let config = backup_settings(&app);
+    let s3 = S3::new(http::client(&app), config.clone())?;
+    let backup = match key {
+        Some(key) => CloudBackup { key, size: 0, last_modified: None },
+        None => s3.list(&profile_prefix(&config, &profiles::active_profile_id(&app)))
+            .await?
+            .into_iter()
+            .rfind(|b| b.key.ends_with(OBJECT_EXTENSION))
+            .ok_or("No backups found for this profile")?,
+    };
+    let sealed = s3.get(&backup.key).await?;
+    let size = sealed.len() as u64;
+    let passphrase = config.passphrase.clone();
+
+    let mut entries = tauri::async_runtime::spawn_blocking(move || -> Result<Vec<(String, Vec<u8>)>, String> {
+        let archive = open_backup(&passphrase, &sealed)?;
+        let mut zip = zip::ZipArchive::new(Cursor::new(archive)).map_err(|e| format!("Backup archive is damaged: {}", e))?;
+        let mut entries = Vec::new();
+        for index in 0..zip.len() {
+            let mut file = zip.by_index(index).map_err(|e| e.to_string())?;
+            let mut contents = Vec::new();
+            file.read_to_end(&mut contents).map_err(|e| e.to_string())?;
+            entries.push((file.name().to_string(), contents));
+        }
+        Ok(entries)
+    }).await.map_err(|e| e.to_string())??;
+
+    if !entries.iter().any(|(name, _)| name == MANIFEST_ENTRY) {
+        return Err("Backup has no manifest".to_string());
+    }
+    // Settings first: if they don't parse, nothing has been touched yet
+    let restored_settings = match entries.iter().position(|(name, _)| name == SETTINGS_ENTRY) {
+        Some(index) => {
+            let mut restored: AppSettings = serde_json::from_slice(&entries.remove(index).1)
+                .map_err(|e| format!("Backup settings are invalid: {}", e))?;
+            restored.cloud_backup = config;
+            Some(restored)
+        }
+        None => None,
+    };
+    for (name, contents) in &entries {
+        match name.as_str() {
+            PIPELINE_DB_ENTRY => replace_file(&profiles::active_db_path(&app), contents)?,
+            APP_DB_ENTRY => replace_file(&profiles::active_app_db_path(&app), contents)?,
+            _ => {}
+        }
+    }
+    if let Some(restored) = restored_settings {
+        {
+            let state = app.state::<SettingsState>();
+            let mut store = state.write().await;
+            store.replace(restored.clone());
+            store.save()?;
+        }
+        let _ = app.emit("settings-changed", &restored);
+    }
+    info!(key = %backup.key, "Restored cloud backup");
+    Ok(CloudBackup { size, ..backup })
+}
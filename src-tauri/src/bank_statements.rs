@@ -0,0 +1,750 @@
+// Bank Statements - account statements read in the `bank_statement` analysis mode (CSV, Excel
+// or PDF) into a `bank_transactions` ledger, each transaction put in a category by keyword rules,
+// with cash in and out summed per month
+use chrono::NaiveDate;
+use regex::Regex;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+use tracing::info;
+
+use crate::activity::{self, ActivityKind};
+use crate::corporate_events::normalize_date;
+use crate::db;
+use crate::dividends::period_key;
+use crate::error::AppError;
+use crate::gst_invoices;
+use crate::numbers;
+use crate::portfolio;
+use crate::python_bridge::PythonResponse;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS bank_transactions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account TEXT,                  -- account number as printed, often masked
+    txn_date TEXT NOT NULL,        -- YYYY-MM-DD
+    description TEXT NOT NULL,
+    reference TEXT,                -- cheque or UTR number
+    amount REAL NOT NULL,          -- credits positive, debits negative
+    balance REAL,
+    category TEXT,
+    category_manual INTEGER NOT NULL DEFAULT 0,
+    file_name TEXT NOT NULL,
+    source_path TEXT NOT NULL,
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_bank_transactions_date ON bank_transactions(account, txn_date);
+CREATE INDEX IF NOT EXISTS idx_bank_transactions_source ON bank_transactions(source_path);
+
+CREATE TABLE IF NOT EXISTS bank_category_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    keyword TEXT NOT NULL,         -- lowercase; matched anywhere in the description
+    category TEXT NOT NULL,
+    direction TEXT NOT NULL,       -- 'any', 'debit', 'credit'
+    priority INTEGER NOT NULL DEFAULT 0,
+    created_at INTEGER NOT NULL
+);
+";
+
+/// `options.document_type` that selects this mode in `run_python_analysis`
+pub const DOCUMENT_TYPE: &str = "bank_statement";
+/// Category of transactions no rule matches
+pub const UNCATEGORIZED: &str = "Uncategorized";
+
+// Rows searched for the header; statements open with the account holder's details
+const HEADER_SEARCH_ROWS: usize = 40;
+// Balances carried in the statement may be off by rounding
+const BALANCE_TOLERANCE: f64 = 0.01;
+
+const DATE_HEADERS: &[&str] = &["date", "txn date", "transaction date", "tran date", "value date", "posting date", "value dt"];
+const DESCRIPTION_HEADERS: &[&str] = &["description", "narration", "particulars", "remarks", "transaction details", "transaction remarks", "details"];
+const REFERENCE_HEADERS: &[&str] = &["chq/ref no", "chq / ref no", "ref no", "reference", "reference no", "cheque no", "chq no", "chq/ref number", "ref no/cheque no", "utr"];
+const DEBIT_HEADERS: &[&str] = &["debit", "withdrawal", "withdrawals", "withdrawal amt", "withdrawal amount", "debit amount", "dr", "debits", "withdrawal (dr)"];
+const CREDIT_HEADERS: &[&str] = &["credit", "deposit", "deposits", "deposit amt", "deposit amount", "credit amount", "cr", "credits", "deposit (cr)"];
+const AMOUNT_HEADERS: &[&str] = &["amount", "transaction amount", "amount (inr)", "amt"];
+const DIRECTION_HEADERS: &[&str] = &["dr/cr", "cr/dr", "type", "debit/credit", "transaction type"];
+const BALANCE_HEADERS: &[&str] = &["balance", "closing balance", "running balance", "available balance", "balance (inr)"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleDirection {
+    Any,
+    /// Money out
+    Debit,
+    /// Money in
+    Credit,
+}
+
+impl RuleDirection {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RuleDirection::Any => "any",
+            RuleDirection::Debit => "debit",
+            RuleDirection::Credit => "credit",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "any" => Some(RuleDirection::Any),
+            "debit" => Some(RuleDirection::Debit),
+            "credit" => Some(RuleDirection::Credit),
+            _ => None,
+        }
+    }
+
+    fn applies(&self, amount: f64) -> bool {
+        match self {
+            RuleDirection::Any => true,
+            RuleDirection::Debit => amount < 0.0,
+            RuleDirection::Credit => amount > 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryRule {
+    pub id: i64,
+    /// Matched case-insensitively anywhere in the description, e.g. "swiggy" or "neft-salary"
+    pub keyword: String,
+    pub category: String,
+    pub direction: RuleDirection,
+    /// Higher first; ties go to the older rule
+    pub priority: i64,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankTransaction {
+    /// `None` until stored
+    pub id: Option<i64>,
+    pub account: Option<String>,
+    pub txn_date: String,
+    pub description: String,
+    pub reference: Option<String>,
+    /// Credits positive, debits negative
+    pub amount: f64,
+    pub balance: Option<f64>,
+    /// `None` when no rule matches
+    pub category: Option<String>,
+    /// Set by hand; rules leave it alone
+    pub category_manual: bool,
+    pub file_name: String,
+    pub source_path: String,
+    pub created_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BankStatement {
+    pub file_name: String,
+    pub account: Option<String>,
+    pub transactions: Vec<BankTransaction>,
+    pub opening_balance: Option<f64>,
+    pub closing_balance: Option<f64>,
+    /// Rows skipped, directions guessed and balances that don't run on
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryFlow {
+    pub category: String,
+    pub cash_in: f64,
+    pub cash_out: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CashFlowPeriod {
+    /// "2026-10", "2026-Q3", "2026" or "FY2026-27"
+    pub period: String,
+    pub transactions: usize,
+    pub cash_in: f64,
+    /// Positive, like `cash_in`
+    pub cash_out: f64,
+    pub net: f64,
+    /// Largest outflow first
+    pub categories: Vec<CategoryFlow>,
+}
+
+struct Columns {
+    date: usize,
+    description: usize,
+    reference: Option<usize>,
+    debit: Option<usize>,
+    credit: Option<usize>,
+    amount: Option<usize>,
+    direction: Option<usize>,
+    balance: Option<usize>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Whether `options` ask for the bank statement mode rather than a financial statement parse.
+pub fn requested(options: Option<&serde_json::Value>) -> bool {
+    options
+        .and_then(|o| o.get("document_type"))
+        .and_then(|v| v.as_str())
+        .is_some_and(|t| t.eq_ignore_ascii_case(DOCUMENT_TYPE))
+}
+
+/// An amount as banks print it: "1,234.50", "1,234.50 Cr", "(500.00)"; `None` for blanks.
+/// A trailing "Cr"/"Dr" is a direction here, not crores.
+fn parse_amount(raw: &str) -> Option<(f64, Option<bool>)> {
+    let text = raw.trim();
+    let lower = text.to_lowercase();
+    let (text, credit) = if let Some(rest) = lower.strip_suffix("cr").or_else(|| lower.strip_suffix("cr.")) {
+        (rest.trim().to_string(), Some(true))
+    } else if let Some(rest) = lower.strip_suffix("dr").or_else(|| lower.strip_suffix("dr.")) {
+        (rest.trim().to_string(), Some(false))
+    } else {
+        (lower, None)
+    };
+    let value = numbers::normalize(&text).value?;
+    Some((value, credit))
+}
+
+fn normalize_header(header: &str) -> String {
+    header.to_lowercase().replace(['_', '.'], " ").split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn find_header(header: &[String], names: &[&str]) -> Option<usize> {
+    header.iter().position(|h| names.contains(&normalize_header(h).as_str()))
+}
+
+/// The header row and its columns: the first row naming a date, a description and either
+/// debit/credit columns or an amount.
+fn locate_columns(rows: &[Vec<String>]) -> Option<(usize, Columns)> {
+    rows.iter().enumerate().take(HEADER_SEARCH_ROWS).find_map(|(index, header)| {
+        let date = find_header(header, DATE_HEADERS)?;
+        let description = find_header(header, DESCRIPTION_HEADERS)?;
+        let (debit, credit, amount) = (
+            find_header(header, DEBIT_HEADERS),
+            find_header(header, CREDIT_HEADERS),
+            find_header(header, AMOUNT_HEADERS),
+        );
+        if (debit.is_none() || credit.is_none()) && amount.is_none() {
+            return None;
+        }
+        Some((index, Columns {
+            date,
+            description,
+            reference: find_header(header, REFERENCE_HEADERS),
+            debit,
+            credit,
+            amount,
+            direction: find_header(header, DIRECTION_HEADERS),
+            balance: find_header(header, BALANCE_HEADERS),
+        }))
+    })
+}
+
+/// Signed amount from a row's debit/credit pair, or from a single amount column with its
+/// Dr/Cr marker. `None` for rows that move no money.
+fn row_amount(cell: &dyn Fn(Option<usize>) -> String, columns: &Columns) -> Option<f64> {
+    if let (Some(debit), Some(credit)) = (columns.debit, columns.credit) {
+        let debit = parse_amount(&cell(Some(debit))).map_or(0.0, |(v, _)| v.abs());
+        let credit = parse_amount(&cell(Some(credit))).map_or(0.0, |(v, _)| v.abs());
+        return (debit != 0.0 || credit != 0.0).then_some(credit - debit);
+    }
+    let (value, marker) = parse_amount(&cell(columns.amount))?;
+    let direction = cell(columns.direction).trim().to_lowercase();
+    let credit = match direction.as_str() {
+        "cr" | "credit" | "c" | "deposit" => Some(true),
+        "dr" | "debit" | "d" | "withdrawal" => Some(false),
+        _ => marker,
+    };
+    Some(match credit {
+        Some(true) => value.abs(),
+        Some(false) => -value.abs(),
+        None => value,
+    })
+}
+
+fn parse_rows(rows: &[Vec<String>], file_name: &str, source_path: &str, account: Option<&str>) -> Result<BankStatement, String> {
+    let (header_index, columns) = locate_columns(rows)
+        .ok_or("No transaction table found: expected columns for date, description and debit/credit or amount")?;
+    let mut transactions = Vec::new();
+    let mut warnings = Vec::new();
+    for (index, row) in rows.iter().enumerate().skip(header_index + 1) {
+        let cell = |column: Option<usize>| column.and_then(|c| row.get(c)).map(|c| c.trim().to_string()).unwrap_or_default();
+        let raw_date = cell(Some(columns.date));
+        let description = cell(Some(columns.description));
+        if raw_date.is_empty() && description.is_empty() {
+            continue;
+        }
+        // Footers ("Opening balance", totals) have no date
+        let Some(txn_date) = portfolio::parse_trade_date(&raw_date) else { continue };
+        let Some(amount) = row_amount(&cell, &columns) else {
+            warnings.push(format!("Row {}: no debit or credit amount", index + 1));
+            continue;
+        };
+        transactions.push(BankTransaction {
+            id: None,
+            account: account.map(str::to_string),
+            txn_date,
+            description,
+            reference: Some(cell(columns.reference)).filter(|r| !r.is_empty()),
+            amount,
+            balance: parse_amount(&cell(columns.balance)).map(|(v, marker)| if marker == Some(false) { -v.abs() } else { v }),
+            category: None,
+            category_manual: false,
+            file_name: file_name.to_string(),
+            source_path: source_path.to_string(),
+            created_at: None,
+        });
+    }
+    Ok(finish(file_name, account, transactions, None, warnings))
+}
+
+/// Statement lines from a PDF's text layer: each transaction starts with its date, carries the
+/// amount and then the running balance, and may wrap its narration onto the lines below.
+fn parse_text(text: &str, file_name: &str, source_path: &str, account: Option<&str>) -> BankStatement {
+    let start = Regex::new(r"^\s*(\d{1,2}[/\-.]\d{1,2}[/\-.]\d{2,4}|\d{1,2}[\s\-][A-Za-z]{3}[\s\-]\d{2,4}|\d{4}-\d{2}-\d{2})\s+(.*)$")
+        .expect("valid pattern");
+    let amount = Regex::new(r"\(?-?\d[\d,]*\.\d{2}\)?(?:\s*(?i:cr|dr)\b\.?)?").expect("valid pattern");
+    let opening = Regex::new(r"(?i)opening\s+balance[^\d\-(]*(\(?-?\d[\d,]*\.\d{2}\)?(?:\s*(?i:cr|dr))?)").expect("valid pattern");
+
+    let opening_balance = opening.captures(text).and_then(|c| parse_amount(&c[1])).map(|(v, marker)| {
+        if marker == Some(false) { -v.abs() } else { v }
+    });
+    let mut warnings = Vec::new();
+    let mut transactions: Vec<BankTransaction> = Vec::new();
+    let mut previous_balance = opening_balance;
+    let mut continuing = false;
+    for line in text.lines() {
+        let Some(caps) = start.captures(line) else {
+            // Wrapped narration belongs to the transaction above
+            let line = line.trim();
+            if continuing && !line.is_empty() && !amount.is_match(line) {
+                if let Some(last) = transactions.last_mut() {
+                    last.description = format!("{} {}", last.description, line);
+                }
+            } else {
+                continuing = false;
+            }
+            continue;
+        };
+        let Some(txn_date) = portfolio::parse_trade_date(&caps[1].replace(' ', "-")) else { continue };
+        let rest = &caps[2];
+        let figures: Vec<(usize, f64, Option<bool>)> = amount.find_iter(rest)
+            .filter_map(|m| parse_amount(m.as_str()).map(|(v, marker)| (m.start(), v, marker)))
+            .collect();
+        let (description_end, value, marker, balance) = match figures.as_slice() {
+            [] => {
+                continuing = false;
+                continue;
+            }
+            [(at, value, marker)] => (*at, *value, *marker, None),
+            [.., (at, value, marker), (_, balance, balance_marker)] => {
+                let balance = if *balance_marker == Some(false) { -balance.abs() } else { *balance };
+                (*at, *value, *marker, Some(balance))
+            }
+        };
+        // The value date and a reference number often sit between the date and the narration
+        let description = rest[..description_end].trim().to_string();
+        let value = value.abs();
+        let signed = match (marker, previous_balance, balance) {
+            (Some(true), _, _) => value,
+            (Some(false), _, _) => -value,
+            (None, Some(before), Some(after)) if (before + value - after).abs() <= BALANCE_TOLERANCE => value,
+            (None, Some(before), Some(after)) if (before - value - after).abs() <= BALANCE_TOLERANCE => -value,
+            _ => {
+                warnings.push(format!("{} {}: direction guessed as a debit", txn_date, description));
+                -value
+            }
+        };
+        if balance.is_some() {
+            previous_balance = balance;
+        }
+        transactions.push(BankTransaction {
+            id: None,
+            account: account.map(str::to_string),
+            txn_date,
+            description,
+            reference: None,
+            amount: signed,
+            balance,
+            category: None,
+            category_manual: false,
+            file_name: file_name.to_string(),
+            source_path: source_path.to_string(),
+            created_at: None,
+        });
+        continuing = true;
+    }
+    finish(file_name, account, transactions, opening_balance, warnings)
+}
+
+/// Fill in the balances and check that each one follows from the one before.
+fn finish(
+    file_name: &str,
+    account: Option<&str>,
+    transactions: Vec<BankTransaction>,
+    opening_balance: Option<f64>,
+    mut warnings: Vec<String>,
+) -> BankStatement {
+    let opening_balance = opening_balance.or_else(|| {
+        transactions.first().and_then(|t| t.balance.map(|b| b - t.amount))
+    });
+    let mut running = opening_balance;
+    for transaction in &transactions {
+        if let (Some(before), Some(after)) = (running, transaction.balance) {
+            if (before + transaction.amount - after).abs() > BALANCE_TOLERANCE {
+                warnings.push(format!(
+                    "{} {}: balance {:.2} does not follow from {:.2}",
+                    transaction.txn_date, transaction.description, after, before
+                ));
+            }
+        }
+        running = transaction.balance.or(running.map(|r| r + transaction.amount));
+    }
+    BankStatement {
+        file_name: file_name.to_string(),
+        account: account.map(str::to_string),
+        closing_balance: transactions.last().and_then(|t| t.balance),
+        opening_balance,
+        transactions,
+        warnings,
+    }
+}
+
+/// The account number from the statement's heading: "Account No: XXXXXX1234".
+fn account_number(text: &str) -> Option<String> {
+    let account = Regex::new(r"(?i)\b(?:a/?c|account)\s*(?:no|number|#)?\.?\s*[:\-]?\s*([X*\d][X*\d\-\s]{5,}\d)").expect("valid pattern");
+    account.captures(text).map(|c| c[1].split_whitespace().collect::<String>())
+}
+
+fn load_rules(conn: &Connection) -> Result<Vec<CategoryRule>, String> {
+    let mut stmt = conn.prepare(
+        "SELECT id, keyword, category, direction, priority, created_at FROM bank_category_rules ORDER BY priority DESC, id"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], |row| {
+        let direction: String = row.get(3)?;
+        Ok(CategoryRule {
+            id: row.get(0)?,
+            keyword: row.get(1)?,
+            category: row.get(2)?,
+            direction: RuleDirection::parse(&direction).unwrap_or(RuleDirection::Any),
+            priority: row.get(4)?,
+            created_at: row.get(5)?,
+        })
+    }).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// The first rule, in priority order, whose keyword is in `description`.
+fn categorize(rules: &[CategoryRule], description: &str, amount: f64) -> Option<String> {
+    let description = description.to_lowercase();
+    rules.iter()
+        .find(|rule| rule.direction.applies(amount) && description.contains(&rule.keyword))
+        .map(|rule| rule.category.clone())
+}
+
+/// Re-run the rules over every transaction not categorized by hand; returns how many changed.
+fn recategorize(conn: &Connection) -> Result<usize, String> {
+    let rules = load_rules(conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, description, amount, category FROM bank_transactions WHERE category_manual = 0"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, f64>(2)?, row.get::<_, Option<String>>(3)?))
+    }).map_err(|e| e.to_string())?;
+    let rows = rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?;
+    let mut changed = 0;
+    for (id, description, amount, current) in rows {
+        let category = categorize(&rules, &description, amount);
+        if category != current {
+            conn.execute("UPDATE bank_transactions SET category = ?1 WHERE id = ?2", params![category, id])
+                .map_err(|e| e.to_string())?;
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+/// Transactions in `account` (every account when `None`) dated within `from`..=`to`, oldest first.
+fn load_transactions(
+    conn: &Connection,
+    account: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+    category: Option<&str>,
+) -> Result<Vec<BankTransaction>, String> {
+    let mut stmt = conn.prepare(
+        "SELECT id, account, txn_date, description, reference, amount, balance, category, category_manual,
+                file_name, source_path, created_at
+         FROM bank_transactions
+         WHERE (?1 IS NULL OR account = ?1) AND (?2 IS NULL OR txn_date >= ?2) AND (?3 IS NULL OR txn_date <= ?3)
+           AND (?4 IS NULL OR IFNULL(category, ?5) = ?4)
+         ORDER BY txn_date, id"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![account, from, to, category, UNCATEGORIZED], |row| {
+        Ok(BankTransaction {
+            id: row.get(0)?,
+            account: row.get(1)?,
+            txn_date: row.get(2)?,
+            description: row.get(3)?,
+            reference: row.get(4)?,
+            amount: row.get(5)?,
+            balance: row.get(6)?,
+            category: row.get(7)?,
+            category_manual: row.get::<_, i64>(8)? != 0,
+            file_name: row.get(9)?,
+            source_path: row.get(10)?,
+            created_at: row.get(11)?,
+        })
+    }).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Categorize and store `statement`, replacing an earlier import of the same file.
+fn store(app: &AppHandle, statement: &mut BankStatement) -> Result<(), String> {
+    let mut conn = db::open_app_db(app)?;
+    let rules = load_rules(&conn)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    if let Some(first) = statement.transactions.first() {
+        tx.execute("DELETE FROM bank_transactions WHERE source_path = ?1", params![first.source_path])
+            .map_err(|e| e.to_string())?;
+    }
+    let now = now_secs();
+    for transaction in &mut statement.transactions {
+        transaction.category = categorize(&rules, &transaction.description, transaction.amount);
+        tx.execute(
+            "INSERT INTO bank_transactions (account, txn_date, description, reference, amount, balance, category,
+                category_manual, file_name, source_path, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 0, ?8, ?9, ?10)",
+            params![
+                transaction.account,
+                transaction.txn_date,
+                transaction.description,
+                transaction.reference,
+                transaction.amount,
+                transaction.balance,
+                transaction.category,
+                transaction.file_name,
+                transaction.source_path,
+                now,
+            ],
+        ).map_err(|e| e.to_string())?;
+        transaction.id = Some(tx.last_insert_rowid());
+        transaction.created_at = Some(now);
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// The `bank_statement` mode of `run_python_analysis`: read, categorize and store one
+/// statement. The response carries it as `extractedData.statement`.
+pub async fn analyze(
+    app: &AppHandle,
+    file_path: &str,
+    inline: bool,
+    file_name: Option<&str>,
+    options: Option<&serde_json::Value>,
+) -> Result<PythonResponse, AppError> {
+    if inline || !Path::new(file_path).exists() {
+        return Err(AppError::InvalidInput("Bank statement mode needs a file on disk (file_path)".to_string()));
+    }
+    let (password, ocr_language) = gst_invoices::text_options(options);
+    let account = options.and_then(|o| o.get("account")).and_then(|v| v.as_str())
+        .map(str::trim).filter(|a| !a.is_empty()).map(str::to_string);
+    let name = file_name.map(str::to_string).unwrap_or_else(|| {
+        Path::new(file_path).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| file_path.to_string())
+    });
+
+    let (handle, path, stored_name) = (app.clone(), file_path.to_string(), name.clone());
+    let statement = tauri::async_runtime::spawn_blocking(move || -> Result<BankStatement, String> {
+        let extension = Path::new(&path).extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+        let mut statement = if matches!(extension.as_str(), "pdf" | "txt") {
+            let text = gst_invoices::document_text(&handle, &path, password.as_deref(), ocr_language.as_deref())?;
+            if text.trim().is_empty() {
+                return Err("The statement has no text; try again with OCR".to_string());
+            }
+            let account = account.or_else(|| account_number(&text));
+            parse_text(&text, &stored_name, &path, account.as_deref())
+        } else {
+            let rows = portfolio::read_rows(&path)?;
+            let heading = rows.iter().take(HEADER_SEARCH_ROWS).map(|r| r.join(" ")).collect::<Vec<_>>().join("\n");
+            let account = account.or_else(|| account_number(&heading));
+            parse_rows(&rows, &stored_name, &path, account.as_deref())?
+        };
+        if statement.transactions.is_empty() {
+            return Err("No transactions found in the statement".to_string());
+        }
+        store(&handle, &mut statement)?;
+        Ok(statement)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    info!(
+        transactions = statement.transactions.len(),
+        warnings = statement.warnings.len(),
+        "Stored bank statement from {}", name
+    );
+    let summary = format!("Imported {} bank transactions from {}", statement.transactions.len(), name);
+    activity::record(app, ActivityKind::DocumentImported, None, &summary, None);
+    Ok(PythonResponse {
+        status: "success".to_string(),
+        extracted_data: Some(serde_json::json!({ "documentType": DOCUMENT_TYPE, "statement": statement })),
+        metrics: None,
+        metadata: None,
+        message: None,
+        error: None,
+        code: None,
+        cache_hit: None,
+        doc_id: None,
+    })
+}
+
+fn date_range(from: Option<String>, to: Option<String>) -> Result<(Option<String>, Option<String>), String> {
+    let parse = |raw: &str| normalize_date(raw).ok_or_else(|| format!("Invalid date: {}", raw));
+    Ok((from.as_deref().map(parse).transpose()?, to.as_deref().map(parse).transpose()?))
+}
+
+// Tauri Commands
+/// Stored transactions, optionally only one account's, one category's ("Uncategorized" for
+/// those no rule matched) or those dated within `from`..=`to`.
+#[tauri::command]
+pub fn list_bank_transactions(
+    app: AppHandle,
+    account: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    category: Option<String>,
+) -> Result<Vec<BankTransaction>, String> {
+    let (from, to) = date_range(from, to)?;
+    let conn = db::open_app_db(&app)?;
+    load_transactions(&conn, account.as_deref(), from.as_deref(), to.as_deref(), category.as_deref())
+}
+
+/// Put one transaction in `category` by hand, or with `None` hand it back to the rules.
+#[tauri::command]
+pub fn set_bank_transaction_category(app: AppHandle, id: i64, category: Option<String>) -> Result<(), String> {
+    let conn = db::open_app_db(&app)?;
+    let category = category.map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
+    let updated = match &category {
+        Some(category) => conn.execute(
+            "UPDATE bank_transactions SET category = ?1, category_manual = 1 WHERE id = ?2",
+            params![category, id],
+        ),
+        None => conn.execute("UPDATE bank_transactions SET category_manual = 0 WHERE id = ?1", params![id]),
+    }.map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Unknown bank transaction: {}", id));
+    }
+    if category.is_none() {
+        recategorize(&conn)?;
+    }
+    Ok(())
+}
+
+/// Remove every transaction imported from `source_path`; returns how many went.
+#[tauri::command]
+pub fn delete_bank_statement(app: AppHandle, source_path: String) -> Result<usize, String> {
+    let conn = db::open_app_db(&app)?;
+    conn.execute("DELETE FROM bank_transactions WHERE source_path = ?1", params![source_path])
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_bank_category_rules(app: AppHandle) -> Result<Vec<CategoryRule>, String> {
+    let conn = db::open_app_db(&app)?;
+    load_rules(&conn)
+}
+
+/// Add a keyword rule and re-run the rules over the stored transactions.
+#[tauri::command]
+pub fn create_bank_category_rule(
+    app: AppHandle,
+    keyword: String,
+    category: String,
+    direction: Option<RuleDirection>,
+    priority: Option<i64>,
+) -> Result<CategoryRule, String> {
+    let keyword = keyword.trim().to_lowercase();
+    let category = category.trim().to_string();
+    if keyword.is_empty() {
+        return Err("Keyword must not be empty".to_string());
+    }
+    if category.is_empty() {
+        return Err("Category must not be empty".to_string());
+    }
+    let direction = direction.unwrap_or(RuleDirection::Any);
+    let priority = priority.unwrap_or(0);
+
+    let created_at = now_secs();
+    let conn = db::open_app_db(&app)?;
+    conn.execute(
+        "INSERT INTO bank_category_rules (keyword, category, direction, priority, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![keyword, category, direction.as_str(), priority, created_at],
+    ).map_err(|e| e.to_string())?;
+    let id = conn.last_insert_rowid();
+    let changed = recategorize(&conn)?;
+    info!(id, changed, "Added bank category rule \"{}\" -> {}", keyword, category);
+
+    Ok(CategoryRule { id, keyword, category, direction, priority, created_at })
+}
+
+/// Remove a rule; transactions it categorized fall to the next matching rule or none.
+#[tauri::command]
+pub fn delete_bank_category_rule(app: AppHandle, id: i64) -> Result<(), String> {
+    let conn = db::open_app_db(&app)?;
+    conn.execute("DELETE FROM bank_category_rules WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    recategorize(&conn)?;
+    Ok(())
+}
+
+/// Cash in and out of `account` (all accounts when unset), bucketed by `period` ("month",
+/// "quarter", "year" or "fy", default "month") with a per-category split, optionally within
+/// `from`..=`to`.
+#[tauri::command]
+pub fn get_cash_flow_summary(
+    app: AppHandle,
+    account: Option<String>,
+    period: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Vec<CashFlowPeriod>, String> {
+    let period = period.unwrap_or_else(|| "month".to_string()).to_lowercase();
+    let (from, to) = date_range(from, to)?;
+    let conn = db::open_app_db(&app)?;
+
+    let mut periods: BTreeMap<String, (CashFlowPeriod, BTreeMap<String, CategoryFlow>)> = BTreeMap::new();
+    for transaction in load_transactions(&conn, account.as_deref(), from.as_deref(), to.as_deref(), None)? {
+        let day = NaiveDate::parse_from_str(&transaction.txn_date, "%Y-%m-%d").map_err(|e| e.to_string())?;
+        let key = period_key(day, &period)?;
+        let (entry, categories) = periods.entry(key.clone()).or_insert_with(|| (
+            CashFlowPeriod { period: key, transactions: 0, cash_in: 0.0, cash_out: 0.0, net: 0.0, categories: Vec::new() },
+            BTreeMap::new(),
+        ));
+        let category = transaction.category.unwrap_or_else(|| UNCATEGORIZED.to_string());
+        let flow = categories.entry(category.clone()).or_insert(CategoryFlow { category, cash_in: 0.0, cash_out: 0.0 });
+        entry.transactions += 1;
+        entry.net += transaction.amount;
+        if transaction.amount >= 0.0 {
+            entry.cash_in += transaction.amount;
+            flow.cash_in += transaction.amount;
+        } else {
+            entry.cash_out -= transaction.amount;
+            flow.cash_out -= transaction.amount;
+        }
+    }
+    Ok(periods.into_values().map(|(mut entry, categories)| {
+        entry.categories = categories.into_values().collect();
+        entry.categories.sort_by(|a, b| b.cash_out.total_cmp(&a.cash_out).then(b.cash_in.total_cmp(&a.cash_in)));
+        entry
+    }).collect())
+}
@@ -0,0 +1,164 @@
+// Benchmarks - indicative industry ratios for listed Indian companies by sector, bundled with the
+// app, and a comparison that flags where a company sits materially away from its peers
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::ratios;
+use crate::statements;
+
+// Deviations within this share of the industry figure are in line with peers
+const DEFAULT_TOLERANCE_PERCENT: f64 = 25.0;
+
+/// Ratio keys in the order of each sector's figures.
+const BENCHMARK_KEYS: [&str; 9] = [
+    "current_ratio",
+    "quick_ratio",
+    "debt_to_equity",
+    "interest_coverage",
+    "net_profit_margin",
+    "return_on_equity",
+    "return_on_assets",
+    "asset_turnover",
+    "cash_conversion",
+];
+
+// Ratios where a higher figure is worse than peers
+const LOWER_IS_BETTER: &[&str] = &["debt_to_equity"];
+
+// (key, name, figures in BENCHMARK_KEYS order); medians, rounded. Banks and NBFCs are left
+// out: the ratios here don't describe a lender's balance sheet.
+const SECTORS: &[(&str, &str, [f64; 9])] = &[
+    ("it_services", "IT Services", [2.5, 2.4, 0.1, 40.0, 18.0, 25.0, 16.0, 1.1, 0.9]),
+    ("fmcg", "FMCG", [1.3, 0.9, 0.1, 30.0, 13.0, 35.0, 18.0, 1.5, 1.0]),
+    ("pharmaceuticals", "Pharmaceuticals", [2.0, 1.4, 0.3, 12.0, 12.0, 15.0, 9.0, 0.8, 0.9]),
+    ("automobiles", "Automobiles & Components", [1.2, 0.9, 0.5, 10.0, 7.0, 15.0, 7.0, 1.2, 1.2]),
+    ("cement", "Cement", [1.3, 0.9, 0.4, 8.0, 9.0, 11.0, 6.0, 0.7, 1.4]),
+    ("metals", "Metals & Mining", [1.2, 0.7, 0.8, 5.0, 7.0, 12.0, 5.0, 0.8, 1.5]),
+    ("power", "Power & Utilities", [1.1, 1.0, 1.5, 2.5, 12.0, 11.0, 3.5, 0.25, 1.8]),
+    ("chemicals", "Chemicals", [1.8, 1.2, 0.3, 14.0, 11.0, 16.0, 9.0, 0.9, 0.9]),
+    ("telecom", "Telecom", [0.6, 0.6, 1.8, 2.0, 5.0, 6.0, 1.5, 0.3, 3.0]),
+    ("textiles", "Textiles", [1.4, 0.8, 0.7, 4.0, 5.0, 9.0, 4.0, 1.0, 1.1]),
+    ("real_estate", "Real Estate", [1.8, 0.6, 0.6, 4.0, 15.0, 8.0, 3.0, 0.2, 0.8]),
+    ("capital_goods", "Capital Goods", [1.3, 1.0, 0.4, 8.0, 8.0, 14.0, 5.0, 0.8, 1.0]),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sector {
+    pub key: String,
+    pub name: String,
+    /// Ratio key -> industry median
+    pub ratios: Vec<(String, f64)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Standing {
+    InLine,
+    Better,
+    Worse,
+    /// The company's ratio couldn't be computed
+    NotAvailable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkRow {
+    pub key: String,
+    pub name: String,
+    pub unit: String,
+    pub company: Option<f64>,
+    pub industry: f64,
+    /// Company against industry, as a percent of the industry figure
+    pub deviation_percent: Option<f64>,
+    pub standing: Standing,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkComparison {
+    pub document_id: i64,
+    pub filename: String,
+    pub sector: String,
+    pub sector_name: String,
+    pub tolerance_percent: f64,
+    pub rows: Vec<BenchmarkRow>,
+    /// Rows better or worse than peers
+    pub flagged: usize,
+}
+
+fn sector(wanted: &str) -> Result<&'static (&'static str, &'static str, [f64; 9]), String> {
+    let wanted = wanted.trim().to_lowercase().replace([' ', '-'], "_");
+    SECTORS.iter()
+        .find(|(key, name, _)| *key == wanted || name.to_lowercase().replace([' ', '-'], "_") == wanted)
+        .ok_or_else(|| {
+            let known: Vec<&str> = SECTORS.iter().map(|(key, _, _)| *key).collect();
+            format!("Unknown sector \"{}\"; known sectors: {}", wanted, known.join(", "))
+        })
+}
+
+fn standing(key: &str, deviation: f64, tolerance: f64) -> Standing {
+    if deviation.abs() <= tolerance {
+        return Standing::InLine;
+    }
+    let higher = deviation > 0.0;
+    if higher != LOWER_IS_BETTER.contains(&key) { Standing::Better } else { Standing::Worse }
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn list_industry_benchmarks() -> Vec<Sector> {
+    SECTORS.iter()
+        .map(|(key, name, figures)| Sector {
+            key: key.to_string(),
+            name: name.to_string(),
+            ratios: BENCHMARK_KEYS.iter().zip(figures).map(|(k, v)| (k.to_string(), *v)).collect(),
+        })
+        .collect()
+}
+
+/// The document's ratios against the sector's medians; a ratio more than `tolerance_percent`
+/// (default 25%) away from the median is flagged better or worse than peers.
+#[tauri::command]
+pub fn benchmark_against_industry(
+    app: AppHandle,
+    document_id: i64,
+    sector: String,
+    tolerance_percent: Option<f64>,
+) -> Result<BenchmarkComparison, String> {
+    let (sector_key, sector_name, figures) = self::sector(&sector)?;
+    let tolerance = tolerance_percent.unwrap_or(DEFAULT_TOLERANCE_PERCENT);
+    if !tolerance.is_finite() || tolerance < 0.0 {
+        return Err("Tolerance must be a positive percentage".to_string());
+    }
+    let document = statements::load_document(&app, document_id)?;
+    let computed = ratios::compute(&document.items);
+
+    let rows: Vec<BenchmarkRow> = BENCHMARK_KEYS.iter().zip(figures)
+        .map(|(key, industry)| {
+            let ratio = computed.iter().find(|r| r.key == *key);
+            let company = ratio.and_then(|r| r.current);
+            let deviation = company.map(|c| (c - industry) / industry.abs() * 100.0);
+            BenchmarkRow {
+                key: key.to_string(),
+                name: ratio.map(|r| r.name.clone()).unwrap_or_else(|| key.replace('_', " ")),
+                unit: ratio.map(|r| r.unit.clone()).unwrap_or_default(),
+                company,
+                industry: *industry,
+                deviation_percent: deviation,
+                standing: deviation.map_or(Standing::NotAvailable, |d| standing(key, d, tolerance)),
+            }
+        })
+        .collect();
+    let flagged = rows.iter().filter(|row| matches!(row.standing, Standing::Better | Standing::Worse)).count();
+
+    Ok(BenchmarkComparison {
+        document_id,
+        filename: document.filename,
+        sector: sector_key.to_string(),
+        sector_name: sector_name.to_string(),
+        tolerance_percent: tolerance,
+        rows,
+        flagged,
+    })
+}
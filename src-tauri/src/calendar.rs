@@ -0,0 +1,133 @@
+// Calendar - upcoming IPOs and scheduled quarterly results from NSE, cached daily
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::corporate_events::{normalize_date, str_field};
+use crate::market_cache::MarketCache;
+use crate::scraper::{self, NativeScraper, NSE_BASE};
+use crate::settings;
+
+// Listings and board-meeting schedules change at most daily
+const CALENDAR_TTL_SECS: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CalendarEventKind {
+    Ipo,
+    Results,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarEntry {
+    pub kind: CalendarEventKind,
+    pub date: String,                  // YYYY-MM-DD; IPO open date
+    pub end_date: Option<String>,      // IPO close date
+    pub symbol: Option<String>,
+    pub name: String,
+    pub details: Option<String>,       // price band / meeting purpose
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketCalendar {
+    /// Soonest first
+    pub entries: Vec<CalendarEntry>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cached: Option<bool>,
+}
+
+fn ipo_entry(row: &serde_json::Value) -> Option<CalendarEntry> {
+    Some(CalendarEntry {
+        kind: CalendarEventKind::Ipo,
+        date: str_field(row, "issueStartDate").and_then(|d| normalize_date(&d))?,
+        end_date: str_field(row, "issueEndDate").and_then(|d| normalize_date(&d)),
+        symbol: str_field(row, "symbol"),
+        name: str_field(row, "companyName").or_else(|| str_field(row, "symbol"))?,
+        details: str_field(row, "issuePrice").or_else(|| str_field(row, "priceBand")),
+    })
+}
+
+fn results_entry(row: &serde_json::Value) -> Option<CalendarEntry> {
+    let purpose = str_field(row, "purpose")?;
+    if !purpose.to_lowercase().contains("result") {
+        return None;
+    }
+    Some(CalendarEntry {
+        kind: CalendarEventKind::Results,
+        date: str_field(row, "date").and_then(|d| normalize_date(&d))?,
+        end_date: None,
+        symbol: str_field(row, "symbol"),
+        name: str_field(row, "company").or_else(|| str_field(row, "symbol"))?,
+        details: Some(purpose),
+    })
+}
+
+async fn fetch_calendar(native: &NativeScraper, app: &AppHandle) -> Result<Vec<CalendarEntry>, String> {
+    let timeout = scraper::request_timeout(app);
+    let ipo_referer = format!("{}/market-data/all-upcoming-issues-ipo", NSE_BASE);
+    let events_referer = format!("{}/companies-listing/corporate-filings-event-calendar", NSE_BASE);
+
+    // Current issues are still open for bidding; upcoming ones haven't opened yet
+    let (current, upcoming, events) = futures_util::join!(
+        native.nse_get_json("/api/ipo-current-issue", &ipo_referer, timeout),
+        native.nse_get_json("/api/all-upcoming-issues?category=ipo", &ipo_referer, timeout),
+        native.nse_get_json("/api/event-calendar", &events_referer, timeout),
+    );
+    if current.is_err() && upcoming.is_err() && events.is_err() {
+        return Err(events.err().unwrap_or_default());
+    }
+
+    let rows = |result: Result<serde_json::Value, String>, label: &str| match result {
+        Ok(data) => data.as_array().cloned().unwrap_or_default(),
+        Err(e) => {
+            eprintln!("[Calendar] {} unavailable: {}", label, e);
+            Vec::new()
+        }
+    };
+
+    let mut entries: Vec<CalendarEntry> = rows(current, "Current IPOs").iter()
+        .chain(rows(upcoming, "Upcoming IPOs").iter())
+        .filter_map(ipo_entry)
+        .collect();
+    // The same issue can appear in both IPO lists around its open date
+    let mut seen = std::collections::HashSet::new();
+    entries.retain(|e| seen.insert((e.name.clone(), e.date.clone())));
+    entries.extend(rows(events, "Event calendar").iter().filter_map(results_entry));
+    Ok(entries)
+}
+
+// Tauri Commands
+#[tauri::command]
+pub async fn get_market_calendar(
+    app: AppHandle,
+    native: tauri::State<'_, NativeScraper>,
+    cache: tauri::State<'_, MarketCache>,
+    force_refresh: Option<bool>,
+) -> Result<MarketCalendar, String> {
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    // Open IPOs started in the past but are still relevant until they close
+    let is_upcoming = |e: &CalendarEntry| e.end_date.as_deref().unwrap_or(&e.date) >= today.as_str();
+
+    if !force_refresh.unwrap_or(false) {
+        if let Some(payload) = cache.get(&app, "CALENDAR", "ALL", "NSE", CALENDAR_TTL_SECS) {
+            if let Ok(mut calendar) = serde_json::from_value::<MarketCalendar>(payload) {
+                calendar.entries.retain(is_upcoming);
+                calendar.cached = Some(true);
+                return Ok(calendar);
+            }
+        }
+    }
+
+    settings::ensure_online(&app)?;
+    let mut entries = fetch_calendar(&native, &app).await?;
+    entries.retain(is_upcoming);
+    entries.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.name.cmp(&b.name)));
+
+    let calendar = MarketCalendar { entries, cached: None };
+    if let Ok(payload) = serde_json::to_value(&calendar) {
+        cache.put(&app, "CALENDAR", "ALL", "NSE", &payload);
+    }
+    Ok(calendar)
+}
@@ -0,0 +1,184 @@
+// Chat references - turns a request's `references` and @item:, @doc: and @page: mentions in the
+// user's message into context for the model, so "explain @item:trade_receivables trend" is
+// answered from the stored figures rather than from memory
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use tauri::AppHandle;
+use tracing::debug;
+
+use crate::ollama::{ChatMessage, ChatRequest};
+use crate::ratios;
+use crate::report;
+use crate::statements::{self, StoredDocument};
+
+const MAX_ITEMS: usize = 50;
+const MAX_PAGE_CHARS: usize = 4000;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatReferences {
+    /// Item ids, or label slugs such as "trade_receivables"
+    #[serde(default)]
+    pub item_ids: Vec<String>,
+    #[serde(default)]
+    pub document_ids: Vec<i64>,
+    /// Page numbers of the referenced document
+    #[serde(default)]
+    pub pages: Vec<u32>,
+    /// Document that items and pages refer to; defaults to the first of `document_ids`, then the latest
+    pub document_id: Option<i64>,
+}
+
+/// "Trade Receivables (net)" -> "trade_receivables_net"
+fn slug(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn item_page(item: &serde_json::Value) -> Option<u32> {
+    match &item["sourcePage"] {
+        serde_json::Value::Number(page) => page.as_u64().map(|p| p as u32),
+        serde_json::Value::String(page) => page.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn format_value(value: Option<f64>) -> String {
+    value.map(|v| format!("{:.2}", v)).unwrap_or_else(|| "-".to_string())
+}
+
+/// Collect @kind:value mentions from `text` into `references`.
+fn add_mentions(text: &str, references: &mut ChatReferences) {
+    for word in text.split_whitespace() {
+        let Some((kind, value)) = word.strip_prefix('@').and_then(|w| w.split_once(':')) else { continue };
+        let value = value.trim_end_matches(|c: char| !c.is_ascii_alphanumeric() && c != '_' && c != '-');
+        if value.is_empty() {
+            continue;
+        }
+        match kind {
+            "item" => references.item_ids.push(value.to_string()),
+            "doc" | "document" => references.document_ids.extend(value.parse::<i64>().ok()),
+            "page" => references.pages.extend(value.parse::<u32>().ok()),
+            _ => {}
+        }
+    }
+}
+
+/// By id first, then by label slug, then the first label containing it.
+fn find_item<'a>(document: &'a StoredDocument, reference: &str) -> Option<&'a serde_json::Value> {
+    let wanted = slug(reference);
+    let items = || document.items.iter().filter(|item| !item["isHeader"].as_bool().unwrap_or(false));
+    items().find(|item| item["id"].as_str() == Some(reference))
+        .or_else(|| items().find(|item| slug(item["label"].as_str().unwrap_or("")) == wanted))
+        .or_else(|| items().find(|item| slug(item["label"].as_str().unwrap_or("")).contains(&wanted)))
+}
+
+fn describe_item(out: &mut String, item: &serde_json::Value) {
+    let mut source = report::statement_of(item).replace('_', " ");
+    if let Some(page) = item_page(item) {
+        let _ = write!(source, ", page {}", page);
+    }
+    let _ = write!(
+        out,
+        "- {} [{}]: current {}, previous {}",
+        item["label"].as_str().unwrap_or(""),
+        source,
+        format_value(item["currentYear"].as_f64()),
+        format_value(item["previousYear"].as_f64())
+    );
+    if let Some(years) = item["allYears"].as_object().filter(|years| years.len() > 2) {
+        let years: BTreeMap<&String, String> = years.iter().map(|(year, v)| (year, format_value(v.as_f64()))).collect();
+        let years: Vec<String> = years.into_iter().map(|(year, v)| format!("{}: {}", year, v)).collect();
+        let _ = write!(out, "; by year {}", years.join(", "));
+    }
+    out.push('\n');
+}
+
+fn describe_document(out: &mut String, document: &StoredDocument) {
+    let _ = writeln!(out, "Document \"{}\" (id {}), {} line items. Key ratios (current / previous):", document.filename, document.id, document.items.len());
+    for ratio in ratios::for_document(document) {
+        let _ = writeln!(out, "- {}: {} / {} {}", ratio.name, format_value(ratio.current), format_value(ratio.previous), ratio.unit);
+    }
+}
+
+fn describe_page(app: &AppHandle, out: &mut String, document: &StoredDocument, page: u32) -> Result<(), String> {
+    let _ = writeln!(out, "Page {} of \"{}\":", page, document.filename);
+    let items: Vec<&serde_json::Value> = document.items.iter().filter(|item| item_page(item) == Some(page)).take(MAX_ITEMS).collect();
+    for item in &items {
+        describe_item(out, item);
+    }
+    let text = statements::page_text(app, document.id, page)?.join("\n");
+    if !text.trim().is_empty() {
+        let text: String = text.chars().take(MAX_PAGE_CHARS).collect();
+        let _ = writeln!(out, "Page text:\n{}", text);
+    } else if items.is_empty() {
+        out.push_str("(nothing stored for this page)\n");
+    }
+    Ok(())
+}
+
+/// Context text for `references`, or None when there is nothing to resolve.
+pub fn resolve(app: &AppHandle, references: &ChatReferences) -> Result<Option<String>, String> {
+    if references.item_ids.is_empty() && references.document_ids.is_empty() && references.pages.is_empty() {
+        return Ok(None);
+    }
+    let mut out = String::from("Referenced data from the user's documents. Base your answer on these figures.\n\n");
+
+    // Mention order matters: the first document is the default for items and pages
+    let mut document_ids: Vec<i64> = Vec::new();
+    for id in &references.document_ids {
+        if !document_ids.contains(id) {
+            document_ids.push(*id);
+        }
+    }
+    for id in &document_ids {
+        match statements::load_document(app, *id) {
+            Ok(document) => describe_document(&mut out, &document),
+            Err(e) => { let _ = writeln!(out, "@doc:{} could not be loaded: {}", id, e); }
+        }
+        out.push('\n');
+    }
+
+    if !references.item_ids.is_empty() || !references.pages.is_empty() {
+        let document_id = match references.document_id.or(document_ids.first().copied()) {
+            Some(id) => id,
+            None => statements::latest_document_id(app)?,
+        };
+        let document = statements::load_document(app, document_id)?;
+        if !references.item_ids.is_empty() {
+            let _ = writeln!(out, "Items from \"{}\":", document.filename);
+            for reference in references.item_ids.iter().take(MAX_ITEMS) {
+                match find_item(&document, reference) {
+                    Some(item) => describe_item(&mut out, item),
+                    None => { let _ = writeln!(out, "- {}: not found in this document", reference); }
+                }
+            }
+            out.push('\n');
+        }
+        for page in &references.pages {
+            describe_page(app, &mut out, &document, *page)?;
+            out.push('\n');
+        }
+    }
+    Ok(Some(out.trim_end().to_string()))
+}
+
+/// Resolve the request's references plus mentions in its last user message, and put the
+/// result in a system message just before that message.
+pub fn ground(app: &AppHandle, mut request: ChatRequest) -> Result<ChatRequest, String> {
+    let mut references = request.references.take().unwrap_or_default();
+    let last_user = request.messages.iter().rposition(|m| m.role == "user");
+    if let Some(index) = last_user {
+        add_mentions(&request.messages[index].content, &mut references);
+    }
+    if let Some(context) = resolve(app, &references)? {
+        debug!(items = references.item_ids.len(), documents = references.document_ids.len(), pages = references.pages.len(), "Grounding chat request");
+        let message = ChatMessage { role: "system".to_string(), content: context, images: None };
+        request.messages.insert(last_user.unwrap_or(request.messages.len()), message);
+    }
+    Ok(request)
+}
@@ -0,0 +1,663 @@
+// Chat sessions - keeps each session's turns in chat_history/<id>.json, folds old turns into an
+// LLM-written summary once a conversation nears the model's context window, keeps a full-text
+// index of every message for search, and holds per-session titles and model overrides
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{debug, info, warn};
+
+use crate::db;
+use crate::formatting::Formatter;
+use crate::http;
+use crate::ollama::{self, ChatMessage, ChatRequest};
+use crate::profiles::{self, ProfileManager};
+use crate::settings::SettingsState;
+use crate::timeouts::{self, Operation};
+use crate::usage::{self, UsageKind};
+use crate::windows;
+
+pub const SCHEMA: &str = "
+CREATE VIRTUAL TABLE IF NOT EXISTS chat_search USING fts5(
+    content,
+    session_id UNINDEXED,
+    role UNINDEXED,
+    position UNINDEXED,
+    created_at UNINDEXED,       -- first time the message was indexed
+    tokenize = 'porter unicode61'
+);
+CREATE TABLE IF NOT EXISTS chat_search_files (
+    session_id TEXT PRIMARY KEY,
+    modified INTEGER NOT NULL   -- history file mtime when it was indexed
+);
+CREATE TABLE IF NOT EXISTS chat_sessions (
+    session_id TEXT PRIMARY KEY,
+    title TEXT,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS chat_replies (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id TEXT NOT NULL,
+    content TEXT NOT NULL,
+    model TEXT NOT NULL,
+    prompt_tokens INTEGER,
+    completion_tokens INTEGER,
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_chat_replies_session ON chat_replies(session_id);
+CREATE TABLE IF NOT EXISTS chat_session_config (
+    session_id TEXT PRIMARY KEY,
+    model TEXT,
+    temperature REAL,
+    system_prompt TEXT,
+    updated_at INTEGER NOT NULL
+);
+";
+
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+/// Start of every summary message, so a later compaction can tell them apart
+pub const SUMMARY_PREFIX: &str = "Summary of the earlier conversation:";
+// Compact once the estimate passes this share of the context window
+const COMPACT_AT: f64 = 0.8;
+// Most recent messages always kept verbatim
+const KEEP_RECENT: usize = 6;
+
+const MAX_TITLE_CHARS: usize = 60;
+
+const TITLE_INSTRUCTION: &str = "Write a title of at most six words for the conversation below. \
+Reply with the title only, no quotes.";
+
+const SUMMARY_INSTRUCTION: &str = "Summarize the conversation below for your own later reference. \
+Keep every company name, figure, ratio, period and conclusion that was discussed, the user's goals \
+and any open questions. Write compact bullet points, no preamble.";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    pub session_id: String,
+    pub summary: String,
+    pub messages_before: usize,
+    pub messages_after: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatSession {
+    pub session_id: String,
+    pub title: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+/// One message of an exported transcript
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TranscriptMessage {
+    role: String,
+    content: String,
+    created_at: Option<i64>,
+    model: Option<String>,
+    prompt_tokens: Option<i64>,
+    completion_tokens: Option<i64>,
+}
+
+/// Per-session overrides; unset fields fall back to what the request carries
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionConfig {
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    pub system_prompt: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatSearchHit {
+    pub session_id: String,
+    pub role: String,
+    /// Index of the message in the session's history
+    pub position: usize,
+    pub created_at: i64,
+    /// Matching excerpt with the hits in [brackets]
+    pub snippet: String,
+    pub content: String,
+}
+
+/// Rough token count (about four characters per token plus per-message overhead).
+fn estimate_tokens(messages: &[ChatMessage]) -> usize {
+    messages.iter().map(|m| m.content.len() / 4 + 4).sum()
+}
+
+fn load(app: &AppHandle, session_id: &str) -> Result<Vec<ChatMessage>, String> {
+    let path = ollama::chat_history_file(&app.state::<Mutex<ProfileManager>>(), session_id)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    Ok(serde_json::from_str(&content).unwrap_or_default())
+}
+
+fn save(app: &AppHandle, session_id: &str, messages: &[ChatMessage]) -> Result<(), String> {
+    let path = ollama::chat_history_file(&app.state::<Mutex<ProfileManager>>(), session_id)?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(messages).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| e.to_string())?;
+    if let Err(e) = db::open_app_db(app).and_then(|mut conn| index_session(&mut conn, &path, messages, now_secs())) {
+        warn!(session = session_id, "Failed to index chat history: {}", e);
+    }
+    Ok(())
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn modified_secs(path: &Path) -> i64 {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Replace a session's rows in the search index. Messages already indexed keep their
+/// timestamp; new ones get `now`.
+fn index_session(conn: &mut Connection, path: &Path, messages: &[ChatMessage], now: i64) -> Result<(), String> {
+    let Some(session_id) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else { return Ok(()) };
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let known: HashMap<String, i64> = {
+        let mut stmt = tx.prepare("SELECT content, created_at FROM chat_search WHERE session_id = ?1").map_err(|e| e.to_string())?;
+        let rows = stmt.query_map(params![session_id], |row| Ok((row.get(0)?, row.get(1)?))).map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+    };
+    tx.execute("DELETE FROM chat_search WHERE session_id = ?1", params![session_id]).map_err(|e| e.to_string())?;
+    for (position, message) in messages.iter().enumerate() {
+        let created_at = known.get(&message.content).copied().unwrap_or(now);
+        tx.execute(
+            "INSERT INTO chat_search (content, session_id, role, position, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![message.content, session_id, message.role, position as i64, created_at],
+        ).map_err(|e| e.to_string())?;
+    }
+    tx.execute(
+        "INSERT OR REPLACE INTO chat_search_files (session_id, modified) VALUES (?1, ?2)",
+        params![session_id, modified_secs(path)],
+    ).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Bring the index in line with the history directory: picks up histories written before the
+/// index existed or brought in by a workspace import, and drops cleared sessions.
+fn refresh_index(app: &AppHandle, conn: &mut Connection) -> Result<(), String> {
+    let indexed: HashMap<String, i64> = {
+        let mut stmt = conn.prepare("SELECT session_id, modified FROM chat_search_files").map_err(|e| e.to_string())?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+    };
+    let mut present = HashSet::new();
+    if let Ok(entries) = std::fs::read_dir(profiles::active_chat_history_dir(app)) {
+        for path in entries.filter_map(|e| e.ok().map(|e| e.path())) {
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let Some(session_id) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else { continue };
+            let modified = modified_secs(&path);
+            if indexed.get(&session_id) != Some(&modified) {
+                let messages: Vec<ChatMessage> = std::fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|content| serde_json::from_str(&content).ok())
+                    .unwrap_or_default();
+                debug!(session = %session_id, messages = messages.len(), "Indexing chat history");
+                index_session(conn, &path, &messages, modified)?;
+            }
+            present.insert(session_id);
+        }
+    }
+    for session_id in indexed.keys().filter(|id| !present.contains(*id)) {
+        conn.execute("DELETE FROM chat_search WHERE session_id = ?1", params![session_id]).map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM chat_search_files WHERE session_id = ?1", params![session_id]).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// The session's messages with what is known about each: when it was first stored and, for
+/// replies, the model and token counts.
+fn transcript_messages(app: &AppHandle, session_id: &str) -> Result<Vec<TranscriptMessage>, String> {
+    let messages = load(app, session_id)?;
+    if messages.is_empty() {
+        return Err(format!("No chat history for session {}", session_id));
+    }
+    let key = session_key(app, session_id)?;
+    let mut conn = db::open_app_db(app)?;
+    refresh_index(app, &mut conn)?;
+    let created: HashMap<String, i64> = {
+        let mut stmt = conn.prepare("SELECT content, created_at FROM chat_search WHERE session_id = ?1").map_err(|e| e.to_string())?;
+        let rows = stmt.query_map(params![key], |row| Ok((row.get(0)?, row.get(1)?))).map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+    };
+    // Later rows win, so a regenerated identical reply reports its latest run
+    let replies: HashMap<String, (String, Option<i64>, Option<i64>)> = {
+        let mut stmt = conn.prepare(
+            "SELECT content, model, prompt_tokens, completion_tokens FROM chat_replies WHERE session_id = ?1 ORDER BY id"
+        ).map_err(|e| e.to_string())?;
+        let rows = stmt.query_map(params![key], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?, row.get(3)?)))).map_err(|e| e.to_string())?;
+        rows.collect::<Result<_, _>>().map_err(|e| e.to_string())?
+    };
+    Ok(messages.into_iter().map(|message| {
+        let reply = if message.role == "assistant" { replies.get(&message.content).cloned() } else { None };
+        TranscriptMessage {
+            created_at: created.get(&message.content).copied(),
+            model: reply.as_ref().map(|r| r.0.clone()).filter(|m| !m.is_empty()),
+            prompt_tokens: reply.as_ref().and_then(|r| r.1),
+            completion_tokens: reply.as_ref().and_then(|r| r.2),
+            role: message.role,
+            content: message.content,
+        }
+    }).collect())
+}
+
+fn transcript_markdown(title: &str, messages: &[TranscriptMessage], fmt: &Formatter) -> String {
+    let mut out = format!("# {}\n\nExported {}\n", title, fmt.timestamp(now_secs()));
+    for message in messages {
+        let mut heading = match message.role.as_str() {
+            "user" => "User".to_string(),
+            "assistant" => "Assistant".to_string(),
+            "system" if message.content.starts_with(SUMMARY_PREFIX) => "Summary".to_string(),
+            "system" => "System".to_string(),
+            other => other.to_string(),
+        };
+        if let Some(model) = &message.model {
+            heading.push_str(&format!(" ({})", model));
+        }
+        let mut details = Vec::new();
+        if let Some(created_at) = message.created_at {
+            details.push(fmt.timestamp(created_at));
+        }
+        if let (Some(prompt), Some(completion)) = (message.prompt_tokens, message.completion_tokens) {
+            details.push(format!("{} prompt / {} completion tokens", prompt, completion));
+        }
+        out.push_str(&format!("\n## {}\n", heading));
+        if !details.is_empty() {
+            out.push_str(&format!("\n_{}_\n", details.join(" · ")));
+        }
+        out.push_str(&format!("\n{}\n", message.content.trim()));
+    }
+    out
+}
+
+fn write_transcript(app: &AppHandle, session_id: &str, format: &str, path: &str) -> Result<String, String> {
+    let messages = transcript_messages(app, session_id)?;
+    let key = session_key(app, session_id)?;
+    let title = load_title(app, session_id)?;
+    let content = match format.to_lowercase().as_str() {
+        "json" => serde_json::to_string_pretty(&serde_json::json!({
+            "sessionId": key,
+            "title": title,
+            "exportedAt": now_secs(),
+            "messages": messages,
+        })).map_err(|e| e.to_string())?,
+        "markdown" | "md" => transcript_markdown(title.as_deref().unwrap_or(&key), &messages, &Formatter::for_app(app)),
+        other => return Err(format!("Unsupported export format: {}", other)),
+    };
+    std::fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    info!(session = %key, path, format, messages = messages.len(), "Exported chat transcript");
+    Ok(path.to_string())
+}
+
+/// Each word as a quoted FTS term, so punctuation in the query can't break the syntax.
+fn match_expression(query: &str) -> String {
+    query.split_whitespace()
+        .map(|word| word.replace('"', ""))
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{}\"", word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The id a session is stored under (the history file name), so "a/b" and "ab" agree.
+fn session_key(app: &AppHandle, session_id: &str) -> Result<String, String> {
+    let path = ollama::chat_history_file(&app.state::<Mutex<ProfileManager>>(), session_id)?;
+    Ok(path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default())
+}
+
+fn load_config(app: &AppHandle, session_id: &str) -> Result<Option<SessionConfig>, String> {
+    let key = session_key(app, session_id)?;
+    let conn = db::open_app_db(app)?;
+    conn.query_row(
+        "SELECT model, temperature, system_prompt FROM chat_session_config WHERE session_id = ?1",
+        params![key],
+        |row| Ok(SessionConfig {
+            model: row.get(0)?,
+            temperature: row.get::<_, Option<f64>>(1)?.map(|t| t as f32),
+            system_prompt: row.get(2)?,
+        }),
+    ).optional().map_err(|e| e.to_string())
+}
+
+/// Apply the session's stored model, temperature and system prompt over the request's own.
+pub fn apply_config(app: &AppHandle, mut request: ChatRequest) -> Result<ChatRequest, String> {
+    let Some(session_id) = request.session_id.as_deref() else { return Ok(request) };
+    let Some(config) = load_config(app, session_id)? else { return Ok(request) };
+    debug!(session = session_id, model = ?config.model, temperature = ?config.temperature, "Applying session config");
+    if config.model.is_some() {
+        request.model = config.model;
+    }
+    if config.temperature.is_some() {
+        request.temperature = config.temperature;
+    }
+    if let Some(system_prompt) = config.system_prompt {
+        let message = ChatMessage { role: "system".to_string(), content: system_prompt, images: None };
+        match request.messages.first_mut() {
+            // Replace the client's system prompt, never a compaction summary
+            Some(first) if first.role == "system" && !first.content.starts_with(SUMMARY_PREFIX) => *first = message,
+            _ => request.messages.insert(0, message),
+        }
+    }
+    Ok(request)
+}
+
+fn log_reply(app: &AppHandle, session_id: &str, model: &str, reply: &serde_json::Value) -> Result<(), String> {
+    let key = session_key(app, session_id)?;
+    db::open_app_db(app)?.execute(
+        "INSERT INTO chat_replies (session_id, content, model, prompt_tokens, completion_tokens, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            key,
+            reply["message"]["content"].as_str().unwrap_or(""),
+            model,
+            reply["prompt_eval_count"].as_i64(),
+            reply["eval_count"].as_i64(),
+            now_secs()
+        ],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Store the conversation as sent plus the model's reply (an Ollama /api/chat response), when
+/// the request names a session.
+pub fn record(app: &AppHandle, request: &ChatRequest, reply: &serde_json::Value) {
+    let Some(session_id) = request.session_id.as_deref() else { return };
+    let Some(content) = reply["message"]["content"].as_str() else { return };
+    let mut messages = request.messages.clone();
+    messages.push(ChatMessage { role: "assistant".to_string(), content: content.to_string(), images: None });
+    if let Err(e) = save(app, session_id, &messages) {
+        warn!(session = session_id, "Failed to save chat history: {}", e);
+    }
+    let model = reply["model"].as_str().or(request.model.as_deref()).unwrap_or("");
+    if let Err(e) = log_reply(app, session_id, model, reply) {
+        warn!(session = session_id, "Failed to log chat reply: {}", e);
+    }
+    // Title after the first exchange, in the background so the reply isn't held up
+    let first_exchange = messages.iter().filter(|m| m.role == "user").count() == 1;
+    if first_exchange && load_title(app, session_id).is_ok_and(|title| title.is_none()) {
+        let (app, session_id, model) = (app.clone(), session_id.to_string(), request.model.clone());
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = title_session(app, session_id.clone(), model, messages).await {
+                warn!(session = %session_id, "Failed to title chat session: {}", e);
+            }
+        });
+    }
+}
+
+/// One non-streaming answer from the model to `instruction` + `input`.
+async fn complete(app: &AppHandle, model: Option<&str>, instruction: &str, input: &str, temperature: f32) -> Result<String, String> {
+    let state = app.state::<SettingsState>();
+    let bridge_url = ollama::get_base_url(&state).await?;
+    let llm = state.read().await.get().llm.clone();
+    let request = http::client(app).post(format!("{}/api/chat", bridge_url))
+        .json(&serde_json::json!({
+            "model": model.unwrap_or(&llm.selected_model),
+            "stream": false,
+            "keep_alive": llm.keep_alive,
+            "messages": [
+                { "role": "system", "content": instruction },
+                { "role": "user", "content": input },
+            ],
+            "options": { "num_ctx": llm.context_window, "temperature": temperature },
+        }));
+    let res = timeouts::run(app, Operation::OllamaGeneration, async {
+        request.send()
+            .await
+            .map_err(|e| e.to_string())?
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| e.to_string())
+    }).await?;
+    if let Some(error) = res["error"].as_str() {
+        return Err(error.to_string());
+    }
+    res["message"]["content"].as_str()
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| "The model returned an empty answer".to_string())
+}
+
+fn transcript(messages: &[ChatMessage]) -> String {
+    messages.iter()
+        .map(|m| format!("{}: {}", m.role, m.content))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Ask the model for a summary of `messages`.
+async fn summarize(app: &AppHandle, model: Option<&str>, messages: &[ChatMessage]) -> Result<String, String> {
+    complete(app, model, SUMMARY_INSTRUCTION, &transcript(messages), 0.2).await
+}
+
+/// First line of the model's answer, without quotes or a trailing full stop.
+fn clean_title(raw: &str) -> String {
+    let line = raw.lines().map(str::trim).find(|l| !l.is_empty()).unwrap_or("");
+    let line = line.trim_start_matches("Title:").trim().trim_matches(|c| c == '"' || c == '\'' || c == '*').trim_end_matches('.').trim();
+    line.chars().take(MAX_TITLE_CHARS).collect()
+}
+
+/// Title a session from its first exchange and tell the UI.
+async fn title_session(app: AppHandle, session_id: String, model: Option<String>, messages: Vec<ChatMessage>) -> Result<(), String> {
+    let exchange: Vec<ChatMessage> = messages.into_iter().filter(|m| m.role != "system").collect();
+    let title = clean_title(&complete(&app, model.as_deref(), TITLE_INSTRUCTION, &transcript(&exchange), 0.3).await?);
+    if title.is_empty() {
+        return Ok(());
+    }
+    let key = session_key(&app, &session_id)?;
+    let now = now_secs();
+    db::open_app_db(&app)?.execute(
+        "INSERT INTO chat_sessions (session_id, title, created_at, updated_at) VALUES (?1, ?2, ?3, ?3)
+         ON CONFLICT(session_id) DO UPDATE SET title = COALESCE(chat_sessions.title, excluded.title), updated_at = excluded.updated_at",
+        params![key, title, now],
+    ).map_err(|e| e.to_string())?;
+    info!(session = %key, title = %title, "Titled chat session");
+    let _ = app.emit("session-titled", serde_json::json!({ "sessionId": session_id, "title": title }));
+    Ok(())
+}
+
+fn load_title(app: &AppHandle, session_id: &str) -> Result<Option<String>, String> {
+    let key = session_key(app, session_id)?;
+    let title: Option<String> = db::open_app_db(app)?
+        .query_row("SELECT title FROM chat_sessions WHERE session_id = ?1", params![key], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?
+        .flatten();
+    Ok(title)
+}
+
+/// Replace everything but the leading system prompt and the last few messages with one summary.
+/// Returns the summary, or None when there is too little to fold.
+async fn fold(app: &AppHandle, model: Option<&str>, messages: &mut Vec<ChatMessage>) -> Result<Option<String>, String> {
+    let start = messages.iter()
+        .take_while(|m| m.role == "system" && !m.content.starts_with(SUMMARY_PREFIX))
+        .count();
+    let end = messages.len().saturating_sub(KEEP_RECENT);
+    if end <= start + 1 {
+        return Ok(None);
+    }
+    let summary = summarize(app, model, &messages[start..end]).await?;
+    let message = ChatMessage { role: "system".to_string(), content: format!("{}\n{}", SUMMARY_PREFIX, summary), images: None };
+    messages.splice(start..end, [message]);
+    Ok(Some(summary))
+}
+
+/// Compact the request if it is close to the context window, saving the compacted history
+/// and telling the window so its transcript can follow.
+pub async fn compact_if_needed(app: &AppHandle, window: Option<&str>, mut request: ChatRequest) -> Result<ChatRequest, String> {
+    let context_window = match request.num_ctx {
+        Some(n) => n,
+        None => app.state::<SettingsState>().read().await.get().llm.context_window,
+    };
+    let estimate = estimate_tokens(&request.messages);
+    if (estimate as f64) < context_window as f64 * COMPACT_AT {
+        return Ok(request);
+    }
+    let before = request.messages.len();
+    let Some(summary) = fold(app, request.model.as_deref(), &mut request.messages).await? else {
+        return Ok(request);
+    };
+    info!(before, after = request.messages.len(), estimate, context_window, "Compacted chat history");
+    if let Some(session_id) = request.session_id.as_deref() {
+        save(app, session_id, &request.messages)?;
+    }
+    let payload = serde_json::json!({
+        "sessionId": request.session_id,
+        "summary": summary,
+        "messages": request.messages,
+    });
+    let _ = match window {
+        Some(window) => app.emit_to(window, &windows::scoped_event(window, "chat-compacted"), payload),
+        None => app.emit("chat-compacted", payload),
+    };
+    Ok(request)
+}
+
+/// Remove a session's history file with its title, replies, settings and search entries.
+pub fn delete_session(app: &AppHandle, session_id: &str) -> Result<(), String> {
+    let path = profiles::active_chat_history_dir(app).join(format!("{}.json", session_id));
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+    }
+    let conn = db::open_app_db(app)?;
+    for table in ["chat_sessions", "chat_replies", "chat_session_config", "chat_search", "chat_search_files"] {
+        conn.execute(&format!("DELETE FROM {} WHERE session_id = ?1", table), params![session_id])
+            .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// Tauri Commands
+/// Summarize a stored session and fold its older turns into that summary.
+#[tauri::command]
+pub async fn summarize_session(app: AppHandle, session_id: String) -> Result<SessionSummary, String> {
+    let mut messages = load(&app, &session_id)?;
+    if messages.is_empty() {
+        return Err(format!("No chat history for session {}", session_id));
+    }
+    let before = messages.len();
+    let summary = match fold(&app, None, &mut messages).await? {
+        Some(summary) => {
+            save(&app, &session_id, &messages)?;
+            summary
+        }
+        // Short conversation: summarize it whole but leave the history alone
+        None => summarize(&app, None, &messages).await?,
+    };
+    Ok(SessionSummary { session_id, summary, messages_before: before, messages_after: messages.len() })
+}
+
+/// Full-text search over stored chat messages, best matches first. All words must match;
+/// `since` and `until` are unix seconds.
+#[tauri::command]
+pub fn search_chat_history(
+    app: AppHandle,
+    query: String,
+    session_id: Option<String>,
+    since: Option<i64>,
+    until: Option<i64>,
+    limit: Option<usize>,
+) -> Result<Vec<ChatSearchHit>, String> {
+    let expression = match_expression(&query);
+    if expression.is_empty() {
+        return Err("Enter something to search for".to_string());
+    }
+    let session_id = session_id.map(|id| session_key(&app, &id)).transpose()?;
+    let mut conn = db::open_app_db(&app)?;
+    refresh_index(&app, &mut conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT session_id, role, position, created_at, snippet(chat_search, 0, '[', ']', '…', 16), content
+         FROM chat_search
+         WHERE chat_search MATCH ?1
+           AND (?2 IS NULL OR session_id = ?2)
+           AND (?3 IS NULL OR created_at >= ?3)
+           AND (?4 IS NULL OR created_at <= ?4)
+         ORDER BY rank LIMIT ?5"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(
+        params![expression, session_id, since, until, limit.unwrap_or(DEFAULT_SEARCH_LIMIT) as i64],
+        |row| Ok(ChatSearchHit {
+            session_id: row.get(0)?,
+            role: row.get(1)?,
+            position: row.get::<_, i64>(2)? as usize,
+            created_at: row.get(3)?,
+            snippet: row.get(4)?,
+            content: row.get(5)?,
+        }),
+    ).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// The session's stored overrides (all unset when it has none).
+#[tauri::command]
+pub fn get_session_config(app: AppHandle, session_id: String) -> Result<SessionConfig, String> {
+    Ok(load_config(&app, &session_id)?.unwrap_or_default())
+}
+
+/// Store overrides for a session; `chat` and `chat_stream` apply them to every request that
+/// names it. Setting everything unset removes them.
+#[tauri::command]
+pub fn set_session_config(app: AppHandle, session_id: String, config: SessionConfig) -> Result<SessionConfig, String> {
+    let key = session_key(&app, &session_id)?;
+    let conn = db::open_app_db(&app)?;
+    let config = SessionConfig {
+        model: config.model.filter(|m| !m.trim().is_empty()),
+        temperature: config.temperature,
+        system_prompt: config.system_prompt.filter(|p| !p.trim().is_empty()),
+    };
+    if config == SessionConfig::default() {
+        conn.execute("DELETE FROM chat_session_config WHERE session_id = ?1", params![key]).map_err(|e| e.to_string())?;
+    } else {
+        conn.execute(
+            "INSERT OR REPLACE INTO chat_session_config (session_id, model, temperature, system_prompt, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![key, config.model, config.temperature.map(|t| t as f64), config.system_prompt, now_secs()],
+        ).map_err(|e| e.to_string())?;
+    }
+    Ok(config)
+}
+
+/// Titled sessions, most recently titled first.
+#[tauri::command]
+pub fn list_chat_sessions(app: AppHandle) -> Result<Vec<ChatSession>, String> {
+    let conn = db::open_app_db(&app)?;
+    let mut stmt = conn.prepare(
+        "SELECT session_id, title, created_at, updated_at FROM chat_sessions ORDER BY updated_at DESC"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], |row| Ok(ChatSession {
+        session_id: row.get(0)?,
+        title: row.get(1)?,
+        created_at: row.get(2)?,
+        updated_at: row.get(3)?,
+    })).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Write a session's transcript to `path` as "markdown" or "json", with timestamps and, for
+/// replies, the model and token counts.
+#[tauri::command]
+pub async fn export_chat(app: AppHandle, session_id: String, format: String, path: String) -> Result<String, String> {
+    usage::measure(&app, UsageKind::Export, || write_transcript(&app, &session_id, &format, &path))
+}
@@ -0,0 +1,291 @@
+// Chunks - a document's text cut into passages with an embedding vector each, stored beside the
+// items in the pipeline DB as the store semantic search and the RAG chat read from
+use regex::Regex;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+use crate::db;
+use crate::http;
+use crate::ollama;
+use crate::settings::SettingsState;
+use crate::statements;
+use crate::timeouts::{self, Operation};
+
+const CHUNKS_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS chunks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    doc_id INTEGER NOT NULL,
+    chunk_index INTEGER NOT NULL,
+    page_num INTEGER,
+    content TEXT NOT NULL,
+    embedding BLOB,                -- little-endian f32s, NULL until embedded
+    dimensions INTEGER,
+    model TEXT,
+    metadata TEXT,                 -- JSON: filename, character offsets
+    created_at INTEGER NOT NULL,
+    UNIQUE(doc_id, chunk_index),
+    FOREIGN KEY(doc_id) REFERENCES documents(id)
+);
+CREATE INDEX IF NOT EXISTS idx_chunks_doc ON chunks(doc_id);
+";
+// Long enough to hold a note to the accounts, short enough for small embedding models
+const CHUNK_CHARS: usize = 1200;
+// Carried into the next chunk so a sentence cut at the boundary is whole in one of them
+const CHUNK_OVERLAP: usize = 150;
+// Passages per embedding request
+const EMBED_BATCH: usize = 16;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Chunk {
+    pub id: i64,
+    pub document_id: i64,
+    pub chunk_index: i64,
+    pub page: Option<u32>,
+    pub content: String,
+    pub model: Option<String>,
+    pub dimensions: Option<usize>,
+    /// Only when asked for; vectors are large
+    pub embedding: Option<Vec<f32>>,
+    pub metadata: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkIndex {
+    pub document_id: i64,
+    pub chunks: usize,
+    pub embedded: usize,
+    pub model: Option<String>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+pub fn open(app: &AppHandle) -> Result<Connection, String> {
+    let conn = db::open_pipeline_db(app)?;
+    conn.execute_batch(CHUNKS_SCHEMA).map_err(|e| e.to_string())?;
+    Ok(conn)
+}
+
+pub fn encode(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|value| value.to_le_bytes()).collect()
+}
+
+pub fn decode(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4).map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])).collect()
+}
+
+/// The text the parser kept for a document, in order (python/database.py `text_chunks`).
+fn document_text(conn: &Connection, doc_id: i64) -> Result<String, String> {
+    // Missing until the Python pipeline or `statements::save_text_chunks` has created it
+    let Ok(mut stmt) = conn.prepare("SELECT content FROM text_chunks WHERE doc_id = ?1 ORDER BY chunk_index, id") else {
+        return Ok(String::new());
+    };
+    let parts = stmt.query_map(params![doc_id], |row| row.get::<_, Option<String>>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(parts.into_iter().flatten().collect())
+}
+
+/// Cut `text` into passages of about `CHUNK_CHARS`, ending at a paragraph or line break where
+/// there is one. Returns (start offset in chars, passage, page) with the page taken from the
+/// last "--- Page N ---" marker before it.
+fn split(text: &str) -> Vec<(usize, String, Option<u32>)> {
+    let marker = Regex::new(r"--- Page (\d+) ---").expect("valid pattern");
+    let chars: Vec<char> = text.chars().collect();
+    let pages: Vec<(usize, u32)> = marker.captures_iter(text)
+        .filter_map(|caps| {
+            let whole = caps.get(0)?;
+            Some((text[..whole.start()].chars().count(), caps[1].parse().ok()?))
+        })
+        .collect();
+    let page_at = |offset: usize| pages.iter().take_while(|(start, _)| *start <= offset).last().map(|(_, page)| *page);
+
+    let mut passages = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = (start + CHUNK_CHARS).min(chars.len());
+        if end < chars.len() {
+            let window = &chars[start + CHUNK_CHARS / 2..end];
+            let window_text: String = window.iter().collect();
+            let cut = window_text.rfind("\n\n").or_else(|| window_text.rfind('\n'));
+            if let Some(cut) = cut {
+                end = start + CHUNK_CHARS / 2 + window_text[..cut].chars().count();
+            }
+        }
+        let passage: String = chars[start..end].iter().collect();
+        if !passage.trim().is_empty() {
+            passages.push((start, passage.trim().to_string(), page_at(start)));
+        }
+        if end >= chars.len() {
+            break;
+        }
+        start = end.saturating_sub(CHUNK_OVERLAP).max(start + 1);
+    }
+    passages
+}
+
+/// Rebuild a document's chunks from its stored text; embeddings are cleared until
+/// `embed_pending` fills them again. Returns the number of chunks.
+pub fn rebuild(app: &AppHandle, doc_id: i64) -> Result<usize, String> {
+    let filename = statements::load_stored_document(app, doc_id)?.filename;
+    let mut conn = open(app)?;
+    let passages = split(&document_text(&conn, doc_id)?);
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM chunks WHERE doc_id = ?1", params![doc_id]).map_err(|e| e.to_string())?;
+    let now = now_secs();
+    for (index, (offset, content, page)) in passages.iter().enumerate() {
+        let metadata = serde_json::json!({ "filename": filename, "offset": offset, "length": content.chars().count() });
+        tx.execute(
+            "INSERT INTO chunks (doc_id, chunk_index, page_num, content, metadata, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![doc_id, index as i64, page, content, metadata.to_string(), now],
+        ).map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(passages.len())
+}
+
+/// The Ollama model chunks are embedded with, from the LLM settings.
+pub async fn embedding_model(app: &AppHandle) -> String {
+    let state = app.state::<SettingsState>();
+    let store = state.read().await;
+    store.get().llm.embedding_model.trim().to_string()
+}
+
+/// One vector per input, in order.
+pub async fn embed(app: &AppHandle, model: &str, inputs: &[String]) -> Result<Vec<Vec<f32>>, String> {
+    let base_url = ollama::get_base_url(&app.state::<SettingsState>()).await?;
+    let request = http::client(app)
+        .post(format!("{}/api/embed", base_url))
+        .json(&serde_json::json!({ "model": model, "input": inputs }));
+    let response: serde_json::Value = timeouts::run(app, Operation::OllamaGeneration, async {
+        request.send()
+            .await
+            .map_err(|e| format!("Ollama unavailable: {}", e))?
+            .error_for_status()
+            .map_err(|e| format!("Embedding with {} failed: {}", model, e))?
+            .json()
+            .await
+            .map_err(|e| e.to_string())
+    }).await?;
+    let embeddings: Vec<Vec<f32>> = serde_json::from_value(response["embeddings"].clone())
+        .map_err(|e| format!("Unexpected embedding response: {}", e))?;
+    if embeddings.len() != inputs.len() {
+        return Err(format!("Asked for {} embeddings, got {}", inputs.len(), embeddings.len()));
+    }
+    Ok(embeddings)
+}
+
+/// Embed the document's chunks that have no vector yet, or one from another model.
+pub async fn embed_pending(app: &AppHandle, doc_id: i64) -> Result<ChunkIndex, String> {
+    let model = embedding_model(app).await;
+    if model.is_empty() {
+        return Err("No embedding model configured".to_string());
+    }
+    let pending: Vec<(i64, String)> = {
+        let conn = open(app)?;
+        let mut stmt = conn.prepare(
+            "SELECT id, content FROM chunks WHERE doc_id = ?1 AND (embedding IS NULL OR model IS NOT ?2) ORDER BY chunk_index",
+        ).map_err(|e| e.to_string())?;
+        let rows = stmt.query_map(params![doc_id, model], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| e.to_string())?;
+        rows
+    };
+
+    for batch in pending.chunks(EMBED_BATCH) {
+        let inputs: Vec<String> = batch.iter().map(|(_, content)| content.clone()).collect();
+        let vectors = embed(app, &model, &inputs).await?;
+        let conn = open(app)?;
+        for ((id, _), vector) in batch.iter().zip(&vectors) {
+            conn.execute(
+                "UPDATE chunks SET embedding = ?2, dimensions = ?3, model = ?4 WHERE id = ?1",
+                params![id, encode(vector), vector.len() as i64, model],
+            ).map_err(|e| e.to_string())?;
+        }
+    }
+    let index = summary(app, doc_id)?;
+    info!(document_id = doc_id, chunks = index.chunks, embedded = pending.len(), model = %model, "Embedded document chunks");
+    Ok(index)
+}
+
+fn summary(app: &AppHandle, doc_id: i64) -> Result<ChunkIndex, String> {
+    let conn = open(app)?;
+    let (chunks, embedded, model) = conn.query_row(
+        "SELECT COUNT(*), COUNT(embedding), MAX(model) FROM chunks WHERE doc_id = ?1",
+        params![doc_id],
+        |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, Option<String>>(2)?)),
+    ).map_err(|e| e.to_string())?;
+    Ok(ChunkIndex { document_id: doc_id, chunks: chunks as usize, embedded: embedded as usize, model })
+}
+
+/// Chunk and embed a freshly analyzed document off the analysis path. Without Ollama the
+/// chunks are kept unembedded for `embed_document_chunks` to finish later; emits
+/// `chunks-indexed` when done.
+pub fn index_in_background(app: &AppHandle, doc_id: i64) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match rebuild(&app, doc_id) {
+            Ok(0) => return,
+            Ok(_) => {}
+            Err(e) => {
+                warn!(document_id = doc_id, "Chunking failed: {}", e);
+                return;
+            }
+        }
+        match embed_pending(&app, doc_id).await {
+            Ok(index) => {
+                let _ = app.emit("chunks-indexed", &index);
+            }
+            Err(e) => warn!(document_id = doc_id, "Chunks stored without embeddings: {}", e),
+        }
+    });
+}
+
+// Tauri Commands
+/// A document's chunks in order; vectors only with `include_embeddings`.
+#[tauri::command]
+pub fn get_document_chunks(app: AppHandle, document_id: i64, include_embeddings: Option<bool>) -> Result<Vec<Chunk>, String> {
+    let conn = open(&app)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, chunk_index, page_num, content, model, dimensions, embedding, metadata FROM chunks
+         WHERE doc_id = ?1 ORDER BY chunk_index",
+    ).map_err(|e| e.to_string())?;
+    let with_vectors = include_embeddings.unwrap_or(false);
+    let rows = stmt.query_map(params![document_id], |row| {
+        let embedding: Option<Vec<u8>> = row.get(6)?;
+        let metadata: Option<String> = row.get(7)?;
+        Ok(Chunk {
+            id: row.get(0)?,
+            document_id,
+            chunk_index: row.get(1)?,
+            page: row.get(2)?,
+            content: row.get(3)?,
+            model: row.get(4)?,
+            dimensions: row.get::<_, Option<i64>>(5)?.map(|d| d as usize),
+            embedding: embedding.filter(|_| with_vectors).map(|blob| decode(&blob)),
+            metadata: metadata.and_then(|m| serde_json::from_str(&m).ok()).unwrap_or(serde_json::Value::Null),
+        })
+    }).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Chunk the document again if it has none (or `rebuild` is set), then embed whatever isn't
+/// embedded with the configured model.
+#[tauri::command]
+pub async fn embed_document_chunks(app: AppHandle, document_id: i64, rebuild: Option<bool>) -> Result<ChunkIndex, String> {
+    if rebuild.unwrap_or(false) || summary(&app, document_id)?.chunks == 0 {
+        self::rebuild(&app, document_id)?;
+    }
+    embed_pending(&app, document_id).await
+}
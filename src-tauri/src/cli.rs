@@ -0,0 +1,162 @@
+// CLI - headless mode for scripts and CI: `--analyze <file> [--export <file>] --no-gui`
+// imports the document, prints its ratios, optionally exports, and exits with a status code.
+// `--mcp` instead serves MCP tools on stdio for agent hosts.
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+
+use crate::csv_import;
+use crate::excel;
+use crate::formatting::Formatter;
+use crate::export;
+use crate::jobs::DocumentKind;
+use crate::pptx;
+use crate::python_bridge;
+use crate::ratios;
+use crate::report;
+use crate::statements;
+
+const EXIT_OK: i32 = 0;
+const EXIT_ANALYSIS_FAILED: i32 = 1;
+const EXIT_EXPORT_FAILED: i32 = 2;
+// sysexits.h EX_USAGE
+const EXIT_USAGE: i32 = 64;
+
+const USAGE: &str = "Usage: financial-calculator --analyze <file> [--export <file>] --no-gui
+       financial-calculator --mcp
+
+  --analyze <file>   PDF, Excel (.xlsx/.xls/.ods) or CSV filing to import
+  --export <file>    Write the analysis; the format follows the extension:
+                     .xlsx .json .csv .md .html .pdf .pptx
+  --no-gui           Run without a window and exit when done
+  --mcp              Serve Model Context Protocol tools on stdin/stdout, without a window
+  --help             Show this message
+
+Exit status: 0 success, 1 analysis failed, 2 export failed, 64 bad arguments";
+
+pub struct CliArgs {
+    pub analyze: PathBuf,
+    pub export: Option<PathBuf>,
+}
+
+pub enum Invocation {
+    Gui,
+    Headless(CliArgs),
+    /// MCP server on stdio; exits when the client disconnects
+    Mcp,
+    /// Help or a usage error; nothing to run
+    Exit(i32),
+}
+
+/// Only `--no-gui` and `--mcp` switch to headless mode; otherwise any file arguments are opened in the app.
+pub fn parse(args: &[String]) -> Invocation {
+    let mut analyze = None;
+    let mut export = None;
+    let mut no_gui = false;
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--analyze" => analyze = iter.next().map(PathBuf::from),
+            "--export" => export = iter.next().map(PathBuf::from),
+            "--no-gui" => no_gui = true,
+            "--mcp" => return Invocation::Mcp,
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                return Invocation::Exit(EXIT_OK);
+            }
+            _ => {}
+        }
+    }
+    if !no_gui {
+        return Invocation::Gui;
+    }
+    match analyze {
+        Some(analyze) => Invocation::Headless(CliArgs { analyze, export }),
+        None => {
+            eprintln!("--no-gui needs --analyze <file>\n\n{}", USAGE);
+            Invocation::Exit(EXIT_USAGE)
+        }
+    }
+}
+
+/// Import the file the same way the job queue would; returns the stored document id.
+pub async fn import(app: &AppHandle, path: &Path) -> Result<i64, String> {
+    let file_path = path.to_string_lossy().to_string();
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string());
+    match DocumentKind::detect(path) {
+        Some(DocumentKind::Pdf) => {
+            // A replay from the parse cache stores no document, and the caller needs one
+            let options = serde_json::json!({ "no_cache": true });
+            let response = python_bridge::run_python_analysis(app.clone(), file_path, None, file_name, Some(options)).await?;
+            if response.status != "success" {
+                return Err(response.message.or(response.error).unwrap_or_else(|| "Analysis failed".to_string()));
+            }
+            response.doc_id.ok_or_else(|| "The analysis stored no document".to_string())
+        }
+        Some(DocumentKind::Excel) => Ok(excel::parse_excel(app.clone(), file_path, None, None).await?.doc_id),
+        Some(DocumentKind::Csv) => Ok(csv_import::parse_csv(app.clone(), file_path, None).await?.doc_id),
+        _ => Err(format!("Unsupported file type: {}", file_path)),
+    }
+}
+
+async fn export_to(app: &AppHandle, document_id: i64, path: &Path) -> Result<String, String> {
+    let target = path.to_string_lossy().to_string();
+    let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    match extension.as_str() {
+        "xlsx" => export::export_analysis_xlsx(app.clone(), document_id, target, None).await,
+        "json" | "csv" | "md" | "markdown" | "html" => export::export_analysis(app.clone(), document_id, extension, target, None).await,
+        // No AI summary: scripts shouldn't depend on a running model
+        "pptx" => pptx::export_analysis_pptx(app.clone(), document_id, target, Some(false)).await,
+        "pdf" => {
+            let rendered = report::render_document_report(app, document_id, None)?;
+            std::fs::copy(&rendered, path).map_err(|e| format!("Failed to write {}: {}", target, e))?;
+            Ok(target)
+        }
+        other => Err(format!("Unsupported export format: .{}", other)),
+    }
+}
+
+async fn pipeline(app: &AppHandle, args: &CliArgs) -> i32 {
+    if !args.analyze.is_file() {
+        eprintln!("File not found: {}", args.analyze.display());
+        return EXIT_USAGE;
+    }
+    let document_id = match import(app, &args.analyze).await {
+        Ok(id) => id,
+        Err(e) => {
+            eprintln!("Analysis failed: {}", e);
+            return EXIT_ANALYSIS_FAILED;
+        }
+    };
+    let document = match statements::load_document(app, document_id) {
+        Ok(document) => document,
+        Err(e) => {
+            eprintln!("Analysis failed: {}", e);
+            return EXIT_ANALYSIS_FAILED;
+        }
+    };
+
+    println!("{}: {} items (document {})", document.filename, document.items.len(), document_id);
+    let fmt = Formatter::for_app(app);
+    for ratio in ratios::for_document(&document) {
+        println!("  {:<24} {:>12} {:>12}", ratio.name, fmt.ratio(ratio.current, &ratio.unit), fmt.ratio(ratio.previous, &ratio.unit));
+    }
+
+    if let Some(export) = &args.export {
+        match export_to(app, document_id, export).await {
+            Ok(path) => println!("Exported {}", path),
+            Err(e) => {
+                eprintln!("Export failed: {}", e);
+                return EXIT_EXPORT_FAILED;
+            }
+        }
+    }
+    EXIT_OK
+}
+
+/// Run the pipeline in the background of a window-less app, then exit with its status.
+pub fn run(app: AppHandle, args: CliArgs) {
+    tauri::async_runtime::spawn(async move {
+        let code = pipeline(&app, &args).await;
+        app.exit(code);
+    });
+}
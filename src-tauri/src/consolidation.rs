@@ -0,0 +1,453 @@
+// Consolidation - sums the statements of several entities (a parent and its subsidiaries) line
+// by line, less the intercompany eliminations the user gives, into a new consolidated document;
+// foreign subsidiaries are translated at each period's historical rates first
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use tauri::AppHandle;
+use tracing::info;
+
+use crate::fx_rates::{self, PeriodRates};
+use crate::periods::{self, Period};
+use crate::report;
+use crate::statements::{self, LineItem, StoredDocument};
+use crate::units;
+
+// Equity stays at the rate it was contributed at while the net assets behind it move with the
+// closing rate; the gap between the two is the translation reserve
+const EQUITY_TOTALS: &[&str] = &["total equity", "equity attributable to owners of the company", "shareholders' funds", "total shareholders' equity"];
+const EQUITY_LINES: &[&str] = &[
+    "equity share capital",
+    "share capital",
+    "other equity",
+    "reserves and surplus",
+    "retained earnings",
+    "securities premium",
+    "general reserve",
+];
+const RESERVE_LABEL: &str = "Foreign currency translation reserve";
+const TRANSLATION_OCI_LABEL: &str = "Exchange differences on translation of foreign operations";
+
+/// An intercompany amount to take out of one consolidated line, e.g. sales to a subsidiary
+/// from revenue, or a balance owed by one from receivables.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Elimination {
+    pub label: String,
+    /// Narrows the match when a label appears in more than one statement
+    pub statement_type: Option<String>,
+    /// Period ("FY2024", "Q3-FY24", "2024") -> amount to subtract, in the configured currency
+    /// and scale
+    pub amounts: BTreeMap<String, f64>,
+    pub note: Option<String>,
+}
+
+/// How a foreign subsidiary's statements were brought into the presentation currency.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Translation {
+    pub document_id: i64,
+    pub filename: String,
+    pub currency: String,
+    /// Presentation units per unit of `currency` that equity was translated at
+    pub historical_rate: f64,
+    /// Closing rates for the balance sheet, average rates for the other statements
+    pub rates: Vec<PeriodRates>,
+    /// Period -> translation reserve at its end, in the presentation currency
+    pub reserve: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsolidatedStatements {
+    pub doc_id: i64,
+    pub filename: String,
+    /// Oldest first; only periods every entity reports
+    pub periods: Vec<String>,
+    pub item_count: usize,
+    pub items: Vec<serde_json::Value>,
+    /// Empty unless foreign subsidiaries were translated
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub translations: Vec<Translation>,
+}
+
+fn line_key(statement: &str, label: &str) -> (String, String) {
+    (statement.to_lowercase(), label.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase())
+}
+
+/// The periods an item's amounts are keyed by; items without known periods use the
+/// document-wide ones for their year columns.
+fn item_amounts(item: &serde_json::Value, current: Period, previous: Period) -> BTreeMap<Period, f64> {
+    if item["currentPeriod"].is_string() {
+        return item["periods"].as_object().into_iter().flatten()
+            .filter_map(|(period, value)| Some((Period::parse(period)?, value.as_f64()?)))
+            .collect();
+    }
+    [(current, "currentYear"), (previous, "previousYear")].into_iter()
+        .filter_map(|(period, column)| Some((period, item[column].as_f64()?)))
+        .collect()
+}
+
+/// (current, previous) for the consolidation. Entities with a known period must agree on it;
+/// the year columns of the others are taken to be for the same periods.
+fn common_periods(documents: &[StoredDocument]) -> Result<(Period, Period), String> {
+    let known: Vec<(&StoredDocument, Period, Option<Period>)> = documents.iter()
+        .filter_map(|document| {
+            let periods = &document.metadata["periods"];
+            let current = periods["current"].as_str().and_then(Period::parse)?;
+            Some((document, current, periods["previous"].as_str().and_then(Period::parse)))
+        })
+        .collect();
+    let Some((_, current, previous)) = known.first().copied() else {
+        return Err("None of the documents has a known period; set one with set_document_periods first".to_string());
+    };
+    if let Some((document, other, _)) = known.iter().find(|(_, other, _)| *other != current) {
+        return Err(format!("{} is for {}, the others for {}; consolidate one period at a time", document.filename, other, current));
+    }
+    Ok((current, previous.unwrap_or(current.prior())))
+}
+
+/// Sum every line across `documents`, matched on statement and label, keeping periods all of
+/// them report. Lines come out in the order they are first seen.
+fn sum_lines(documents: &[StoredDocument], current: Period, previous: Period) -> (Vec<LineItem>, Vec<Period>) {
+    let mut lines: Vec<LineItem> = Vec::new();
+    let mut sums: Vec<BTreeMap<Period, f64>> = Vec::new();
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+    let mut shared: Option<BTreeSet<Period>> = None;
+
+    for document in documents {
+        let mut reported = BTreeSet::new();
+        for item in document.items.iter().filter(|item| !item["isHeader"].as_bool().unwrap_or(false)) {
+            let statement = report::statement_of(item);
+            let label = item["label"].as_str().unwrap_or("").trim().to_string();
+            if label.is_empty() {
+                continue;
+            }
+            let position = *index.entry(line_key(&statement, &label)).or_insert_with(|| {
+                lines.push(LineItem {
+                    key: format!("line{}", lines.len()),
+                    label: label.clone(),
+                    statement: statement.clone(),
+                    row_index: lines.len(),
+                    values: BTreeMap::new(),
+                    is_total: item["isTotal"].as_bool().unwrap_or(false),
+                    is_header: false,
+                });
+                sums.push(BTreeMap::new());
+                lines.len() - 1
+            });
+            for (period, amount) in item_amounts(item, current, previous) {
+                *sums[position].entry(period).or_default() += amount;
+                reported.insert(period);
+            }
+        }
+        shared = Some(match shared {
+            Some(shared) => shared.intersection(&reported).copied().collect(),
+            None => reported,
+        });
+    }
+
+    let periods: Vec<Period> = shared.unwrap_or_default().into_iter().collect();
+    for (line, sum) in lines.iter_mut().zip(sums) {
+        line.values = sum.into_iter()
+            .filter(|(period, _)| periods.contains(period))
+            .map(|(period, amount)| (period.to_string(), amount))
+            .collect();
+    }
+    (lines, periods)
+}
+
+fn eliminate(lines: &mut [LineItem], elimination: &Elimination) -> Result<(), String> {
+    let wanted = line_key(elimination.statement_type.as_deref().unwrap_or(""), &elimination.label);
+    let line = lines.iter_mut()
+        .find(|line| {
+            let key = line_key(&line.statement, &line.label);
+            key.1 == wanted.1 && (elimination.statement_type.is_none() || key.0 == wanted.0)
+        })
+        .ok_or_else(|| format!("No consolidated line \"{}\" to eliminate from", elimination.label))?;
+    for (period, amount) in &elimination.amounts {
+        let period = Period::parse(period).ok_or_else(|| format!("Unknown period in elimination: {}", period))?;
+        let value = line.values.get_mut(&period.to_string())
+            .ok_or_else(|| format!("\"{}\" has no {} amount to eliminate from", line.label, period))?;
+        *value -= amount;
+    }
+    Ok(())
+}
+
+/// The periods any of the document's items report an amount for.
+fn reported_periods(document: &StoredDocument, current: Period, previous: Period) -> BTreeSet<Period> {
+    document.items.iter()
+        .filter(|item| !item["isHeader"].as_bool().unwrap_or(false))
+        .flat_map(|item| item_amounts(item, current, previous).into_keys())
+        .collect()
+}
+
+fn is_equity(item: &serde_json::Value, labels: &[&str]) -> bool {
+    let (statement, label) = line_key(&report::statement_of(item), item["label"].as_str().unwrap_or(""));
+    statement == "balance_sheet" && labels.contains(&label.as_str())
+}
+
+/// Period -> total equity in the document's own currency: its total line, else the sum of the
+/// components.
+fn local_equity(document: &StoredDocument, current: Period, previous: Period) -> BTreeMap<Period, f64> {
+    if let Some(total) = document.items.iter().find(|item| is_equity(item, EQUITY_TOTALS)) {
+        return item_amounts(total, current, previous);
+    }
+    let mut equity: BTreeMap<Period, f64> = BTreeMap::new();
+    for item in document.items.iter().filter(|item| is_equity(item, EQUITY_LINES)) {
+        for (period, amount) in item_amounts(item, current, previous) {
+            *equity.entry(period).or_default() += amount;
+        }
+    }
+    equity
+}
+
+/// Multiply each of the item's amounts by `rate` for its period; amounts for periods without
+/// a rate are dropped.
+fn translate_item(item: &mut serde_json::Value, current: Period, previous: Period, rate: impl Fn(Period) -> Option<f64>) {
+    if item["currentPeriod"].is_string() {
+        if let Some(amounts) = item["periods"].as_object_mut() {
+            for (period, value) in amounts.iter_mut() {
+                *value = match (value.as_f64(), Period::parse(period).and_then(&rate)) {
+                    (Some(amount), Some(rate)) => (amount * rate).into(),
+                    _ => serde_json::Value::Null,
+                };
+            }
+        }
+        return;
+    }
+    for (period, column) in [(current, "currentYear"), (previous, "previousYear")] {
+        if let Some(amount) = item[column].as_f64() {
+            item[column] = rate(period).map_or(serde_json::Value::Null, |rate| (amount * rate).into());
+        }
+    }
+}
+
+/// Translate a subsidiary in place: the balance sheet at closing rates except equity, at
+/// `historical_rate`, and the other statements at average rates. Returns the reserve that
+/// leaves, period by period.
+fn translate(
+    document: &mut StoredDocument,
+    rates: &BTreeMap<Period, PeriodRates>,
+    historical_rate: f64,
+    current: Period,
+    previous: Period,
+) -> BTreeMap<String, f64> {
+    let reserve = local_equity(document, current, previous).into_iter()
+        .filter_map(|(period, equity)| Some((period.to_string(), equity * (rates.get(&period)?.closing - historical_rate))))
+        .collect();
+    for item in &mut document.items {
+        let rate: Box<dyn Fn(Period) -> Option<f64>> = if is_equity(item, EQUITY_TOTALS) || is_equity(item, EQUITY_LINES) {
+            Box::new(|period| rates.contains_key(&period).then_some(historical_rate))
+        } else if report::statement_of(item) == "balance_sheet" {
+            Box::new(|period| rates.get(&period).map(|r| r.closing))
+        } else {
+            Box::new(|period| rates.get(&period).map(|r| r.average))
+        };
+        translate_item(item, current, previous, rate);
+    }
+    reserve
+}
+
+/// Put the subsidiaries' reserves on the consolidated balance sheet, within total equity, and
+/// their movement through other comprehensive income.
+fn add_reserve(lines: &mut Vec<LineItem>, periods: &[Period], translations: &[Translation]) {
+    let mut reserve: BTreeMap<String, f64> = BTreeMap::new();
+    for translation in translations {
+        for (period, amount) in &translation.reserve {
+            *reserve.entry(period.clone()).or_default() += amount;
+        }
+    }
+    reserve.retain(|period, _| periods.iter().any(|p| p.to_string() == *period));
+    if reserve.is_empty() {
+        return;
+    }
+    for line in lines.iter_mut().filter(|line| {
+        let (statement, label) = line_key(&line.statement, &line.label);
+        statement == "balance_sheet" && EQUITY_TOTALS.contains(&label.as_str())
+    }) {
+        for (period, amount) in &reserve {
+            if let Some(value) = line.values.get_mut(period) {
+                *value += amount;
+            }
+        }
+    }
+    // The first period's movement would need the reserve before it
+    let movement: BTreeMap<String, f64> = periods.iter()
+        .filter_map(|period| {
+            let closing = reserve.get(&period.to_string())?;
+            let opening = reserve.get(&period.prior().to_string())?;
+            Some((period.to_string(), closing - opening))
+        })
+        .collect();
+    for (label, statement, values) in [(RESERVE_LABEL, "balance_sheet", reserve), (TRANSLATION_OCI_LABEL, "income_statement", movement)] {
+        if values.is_empty() {
+            continue;
+        }
+        lines.push(LineItem {
+            key: format!("line{}", lines.len()),
+            label: label.to_string(),
+            statement: statement.to_string(),
+            row_index: lines.len(),
+            values,
+            is_total: false,
+            is_header: false,
+        });
+    }
+}
+
+/// Eliminate, then store the summed `(lines, periods)` as a new document, in `currency` and the
+/// scale the documents were brought to.
+fn save(
+    app: &AppHandle,
+    documents: &[StoredDocument],
+    (mut lines, periods): (Vec<LineItem>, Vec<Period>),
+    current: Period,
+    eliminations: &[Elimination],
+    currency: &str,
+    translations: Vec<Translation>,
+) -> Result<ConsolidatedStatements, String> {
+    if periods.is_empty() {
+        return Err("The documents share no reporting period".to_string());
+    }
+    let scale = units::parse_scale(documents[0].metadata["units"]["scale"].as_str().unwrap_or(""))?;
+    for elimination in eliminations {
+        eliminate(&mut lines, elimination)?;
+    }
+
+    let period_labels: Vec<String> = periods.iter().map(Period::to_string).collect();
+    let filename = format!("Consolidated {} ({} entities)", current, documents.len());
+    let mut metadata = serde_json::json!({
+        "fileName": filename,
+        "source": "consolidation",
+        "basis": "consolidated",
+        "fiscalYears": period_labels,
+        "currency": currency,
+        "parser": "native_consolidation",
+        "consolidatedFrom": documents.iter()
+            .map(|document| serde_json::json!({ "documentId": document.id, "filename": document.filename }))
+            .collect::<Vec<_>>(),
+        "eliminations": eliminations,
+    });
+    if !translations.is_empty() {
+        metadata["translations"] = serde_json::to_value(&translations).map_err(|e| e.to_string())?;
+    }
+    let (doc_id, items) = statements::save_document(app, &filename, &metadata, &lines, &period_labels, currency, "consolidation")?;
+    // Figures are already in the configured scale; don't let detection guess otherwise
+    units::record(app, doc_id, currency, scale)?;
+
+    info!(doc_id, entities = documents.len(), eliminations = eliminations.len(), translated = translations.len(), "Stored consolidated statements");
+    Ok(ConsolidatedStatements {
+        doc_id,
+        filename,
+        periods: period_labels,
+        item_count: items.len(),
+        items,
+        translations,
+    })
+}
+
+fn dedup_ids(mut document_ids: Vec<i64>) -> Result<Vec<i64>, String> {
+    document_ids.sort_unstable();
+    document_ids.dedup();
+    if document_ids.len() < 2 {
+        return Err("Consolidation needs at least two documents".to_string());
+    }
+    Ok(document_ids)
+}
+
+// Tauri Commands
+/// Consolidate `document_ids` into a new document, in the configured currency and scale.
+#[tauri::command]
+pub fn consolidate_documents(
+    app: AppHandle,
+    document_ids: Vec<i64>,
+    eliminations: Vec<Elimination>,
+) -> Result<ConsolidatedStatements, String> {
+    let document_ids = dedup_ids(document_ids)?;
+    let documents = document_ids.iter()
+        .map(|id| statements::load_document(&app, *id))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // `load_document` converted each to the configured units, unless an FX rate was missing
+    let currency = documents[0].metadata["units"]["currency"].as_str().unwrap_or("").to_string();
+    if let Some(document) = documents.iter().find(|d| d.metadata["units"]["currency"].as_str() != Some(currency.as_str())) {
+        return Err(format!(
+            "{} reports in {}, not {}; add an FX rate for it to the units settings",
+            document.filename,
+            document.metadata["units"]["currency"].as_str().unwrap_or("another currency"),
+            currency
+        ));
+    }
+
+    let (current, previous) = common_periods(&documents)?;
+    let consolidated = sum_lines(&documents, current, previous);
+    save(&app, &documents, consolidated, current, &eliminations, &currency, Vec::new())
+}
+
+/// Consolidate a group whose subsidiaries report in other currencies into
+/// `presentation_currency` (the configured one by default). Each foreign subsidiary is
+/// translated at the closing and average rates of every period (see `fx_rates`), its equity at
+/// `historical_rates[document_id]` (else the earliest period's closing rate), and the
+/// difference is carried as a translation reserve.
+#[tauri::command]
+pub async fn consolidate_foreign_operations(
+    app: AppHandle,
+    document_ids: Vec<i64>,
+    eliminations: Vec<Elimination>,
+    presentation_currency: Option<String>,
+    historical_rates: Option<HashMap<i64, f64>>,
+) -> Result<ConsolidatedStatements, String> {
+    let document_ids = dedup_ids(document_ids)?;
+    let presentation = fx_rates::resolve_presentation(&app, presentation_currency.as_deref())?;
+    let historical_rates = historical_rates.unwrap_or_default();
+    if historical_rates.values().any(|rate| !(*rate > 0.0 && rate.is_finite())) {
+        return Err("Historical rates must be positive".to_string());
+    }
+    // In the configured scale but still in their own currencies
+    let mut documents = document_ids.iter()
+        .map(|id| {
+            let mut document = statements::load_stored_document(&app, *id)?;
+            units::normalize_scale(&app, &mut document)?;
+            periods::apply(&app, &mut document)?;
+            Ok(document)
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let (current, previous) = common_periods(&documents)?;
+    let shared = documents.iter()
+        .map(|document| reported_periods(document, current, previous))
+        .reduce(|shared, reported| shared.intersection(&reported).copied().collect())
+        .unwrap_or_default();
+    if shared.is_empty() {
+        return Err("The documents share no reporting period".to_string());
+    }
+
+    let mut translations = Vec::new();
+    for document in &mut documents {
+        let currency = document.metadata["units"]["currency"].as_str().unwrap_or(&presentation).to_uppercase();
+        if currency == presentation {
+            continue;
+        }
+        let mut rates = BTreeMap::new();
+        for period in &shared {
+            rates.insert(*period, fx_rates::period_rates(&app, &currency, &presentation, *period).await?);
+        }
+        let historical_rate = match historical_rates.get(&document.id) {
+            Some(rate) => *rate,
+            None => rates.values().next().map(|r| r.closing).ok_or("No rates to translate at")?,
+        };
+        let reserve = translate(document, &rates, historical_rate, current, previous);
+        translations.push(Translation {
+            document_id: document.id,
+            filename: document.filename.clone(),
+            currency,
+            historical_rate,
+            rates: rates.into_values().collect(),
+            reserve,
+        });
+    }
+
+    let (mut lines, periods) = sum_lines(&documents, current, previous);
+    add_reserve(&mut lines, &periods, &translations);
+    save(&app, &documents, (lines, periods), current, &eliminations, &presentation, translations)
+}
@@ -0,0 +1,433 @@
+// Corporate Actions - splits, bonuses and rights issues, entered by hand or taken from NSE.
+// Splits and bonuses multiply the shares held when holdings and P&L are replayed and scale down
+// the stored price history before the ex-date; a subscribed rights issue is a buy in the ledger
+use chrono::{Local, NaiveDate};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::info;
+
+use crate::corporate_events::{normalize_date, str_field};
+use crate::db;
+use crate::portfolio::{self, NewTrade, TradeSide};
+use crate::scraper::{self, NativeScraper, NSE_BASE};
+use crate::settings;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS corporate_actions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    kind TEXT NOT NULL,            -- 'split', 'bonus', 'rights'
+    ex_date TEXT NOT NULL,         -- YYYY-MM-DD
+    new_shares REAL NOT NULL,      -- new_shares for every held_shares
+    held_shares REAL NOT NULL,
+    price REAL,                    -- rights issue price
+    quantity REAL,                 -- rights shares subscribed
+    description TEXT,
+    source TEXT NOT NULL,          -- 'manual', 'nse'
+    adjusted_intervals TEXT NOT NULL DEFAULT '', -- price_history intervals scaled, comma-separated
+    created_at INTEGER NOT NULL,
+    UNIQUE (symbol, exchange, kind, ex_date)
+);
+";
+
+// IST is UTC+5:30; an ex-date starts at its midnight there
+const IST_OFFSET_SECS: i64 = 5 * 3600 + 30 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CorporateActionKind {
+    Split,
+    Bonus,
+    Rights,
+}
+
+impl CorporateActionKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            CorporateActionKind::Split => "split",
+            CorporateActionKind::Bonus => "bonus",
+            CorporateActionKind::Rights => "rights",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "split" => Some(CorporateActionKind::Split),
+            "bonus" => Some(CorporateActionKind::Bonus),
+            "rights" => Some(CorporateActionKind::Rights),
+            _ => None,
+        }
+    }
+
+    /// What each share held before the ex-date is afterwards: a 1-for-5 split (5 new for every
+    /// 1 held) is 5, a 1:2 bonus (1 new for every 2 held) is 1.5. Rights shares are bought, so 1.
+    fn factor(self, new_shares: f64, held_shares: f64) -> f64 {
+        match self {
+            CorporateActionKind::Split => new_shares / held_shares,
+            CorporateActionKind::Bonus => (held_shares + new_shares) / held_shares,
+            CorporateActionKind::Rights => 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorporateActionInput {
+    pub symbol: String,
+    pub exchange: Option<String>,
+    pub kind: CorporateActionKind,
+    pub ex_date: String,
+    /// `new_shares` for every `held_shares`: 5 for 1 in a split to a fifth of the face value,
+    /// 1 for 2 in a 1:2 bonus or rights issue
+    pub new_shares: f64,
+    pub held_shares: f64,
+    /// Rights issue price per share
+    pub price: Option<f64>,
+    /// Rights shares subscribed; the full entitlement when omitted
+    pub quantity: Option<f64>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorporateAction {
+    pub id: i64,
+    pub symbol: String,
+    pub exchange: String,
+    pub kind: CorporateActionKind,
+    pub ex_date: String,
+    pub new_shares: f64,
+    pub held_shares: f64,
+    pub factor: f64,
+    pub price: Option<f64>,
+    pub quantity: Option<f64>,
+    pub description: Option<String>,
+    pub source: String,
+    /// Price history intervals that were scaled for this action
+    pub adjusted_intervals: Vec<String>,
+    pub created_at: i64,
+}
+
+/// A split or bonus as the portfolio replay applies it.
+#[derive(Debug, Clone)]
+pub struct ShareAdjustment {
+    pub ex_date: String,
+    pub factor: f64,
+}
+
+/// Splits and bonuses per (symbol, exchange), oldest first.
+pub type Adjustments = BTreeMap<(String, String), Vec<ShareAdjustment>>;
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+/// The rupee amounts in order in an exchange's corporate-action text, e.g. [10.0, 2.0] in "Face
+/// Value Split From Rs 10/- Per Share To Rs 2/- Per Share".
+pub fn rupee_amounts(text: &str) -> Vec<f64> {
+    let lower = text.to_lowercase();
+    let tokens: Vec<&str> = lower.split_whitespace().collect();
+    tokens.iter().enumerate().filter_map(|(i, token)| {
+        let rest = ["rs.", "rs", "re.", "re", "inr", "₹"].iter().find_map(|prefix| token.strip_prefix(prefix))?;
+        let number = if rest.is_empty() { tokens.get(i + 1)? } else { rest };
+        let digits: String = number.chars().take_while(|c| c.is_ascii_digit() || *c == '.').collect();
+        digits.trim_end_matches('.').parse::<f64>().ok()
+    }).collect()
+}
+
+/// (subject, ex-date) of each of NSE's corporate actions for `symbol`.
+pub async fn fetch_nse_actions(app: &AppHandle, symbol: &str) -> Result<Vec<(String, String)>, String> {
+    let native = app.state::<NativeScraper>();
+    let encoded = scraper::url_encode(symbol);
+    let referer = format!("{}/get-quotes/equity?symbol={}", NSE_BASE, encoded);
+    let data = native.nse_get_json(
+        &format!("/api/corporates-corporateActions?index=equities&symbol={}", encoded), &referer, scraper::request_timeout(app),
+    ).await?;
+    Ok(data.as_array().cloned().unwrap_or_default().iter()
+        .filter_map(|action| Some((str_field(action, "subject")?, str_field(action, "exDate").and_then(|d| normalize_date(&d))?)))
+        .collect())
+}
+
+/// A split or bonus in an NSE subject. Rights aren't taken from NSE: whether and how much to
+/// subscribe is the holder's call, so they're entered by hand.
+fn parse_nse_action(symbol: &str, subject: &str, ex_date: String) -> Option<CorporateActionInput> {
+    let lower = subject.to_lowercase();
+    let (kind, new_shares, held_shares) = if lower.contains("split") || lower.contains("sub-division") || lower.contains("subdivision") {
+        // Face value from Rs X to Rs Y: every share becomes X / Y
+        let amounts = rupee_amounts(&lower);
+        let (from, to) = (*amounts.first()?, *amounts.get(1)?);
+        (to > 0.0 && from > to).then_some((CorporateActionKind::Split, from / to, 1.0))?
+    } else if lower.contains("bonus") {
+        let ratio = lower.split_whitespace().find(|token| token.contains(':'))?;
+        let (new, held) = ratio.split_once(':')?;
+        let held: String = held.chars().take_while(|c| c.is_ascii_digit() || *c == '.').collect();
+        (CorporateActionKind::Bonus, new.trim().parse().ok()?, held.parse().ok()?)
+    } else {
+        return None;
+    };
+    Some(CorporateActionInput {
+        symbol: symbol.to_string(),
+        exchange: Some("NSE".to_string()),
+        kind,
+        ex_date,
+        new_shares,
+        held_shares,
+        price: None,
+        quantity: None,
+        description: Some(subject.to_string()),
+    })
+}
+
+fn action_from_row(row: &Row) -> rusqlite::Result<CorporateAction> {
+    let kind: String = row.get(3)?;
+    let kind = CorporateActionKind::parse(&kind).unwrap_or(CorporateActionKind::Split);
+    let new_shares: f64 = row.get(5)?;
+    let held_shares: f64 = row.get(6)?;
+    let adjusted: String = row.get(11)?;
+    Ok(CorporateAction {
+        id: row.get(0)?,
+        symbol: row.get(1)?,
+        exchange: row.get(2)?,
+        kind,
+        ex_date: row.get(4)?,
+        new_shares,
+        held_shares,
+        factor: kind.factor(new_shares, held_shares),
+        price: row.get(7)?,
+        quantity: row.get(8)?,
+        description: row.get(9)?,
+        source: row.get(10)?,
+        adjusted_intervals: adjusted.split(',').filter(|i| !i.is_empty()).map(str::to_string).collect(),
+        created_at: row.get(12)?,
+    })
+}
+
+const SELECT_ACTIONS: &str = "SELECT id, symbol, exchange, kind, ex_date, new_shares, held_shares, price, quantity, description,
+    source, adjusted_intervals, created_at FROM corporate_actions";
+
+fn load_actions(conn: &Connection, symbol: Option<&str>) -> Result<Vec<CorporateAction>, String> {
+    let mut stmt = conn.prepare(&format!("{} WHERE ?1 IS NULL OR symbol = ?1 ORDER BY ex_date DESC, id DESC", SELECT_ACTIONS))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![symbol], action_from_row).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Every split and bonus that has gone ex, for replaying the ledger.
+pub fn load_adjustments(conn: &Connection) -> Result<Adjustments, String> {
+    let mut adjustments = Adjustments::new();
+    for action in load_actions(conn, None)?.into_iter().rev() {
+        if action.kind == CorporateActionKind::Rights || action.ex_date > today() {
+            continue;
+        }
+        adjustments.entry((action.symbol, action.exchange)).or_default()
+            .push(ShareAdjustment { ex_date: action.ex_date, factor: action.factor });
+    }
+    Ok(adjustments)
+}
+
+fn ex_date_start(ex_date: &str) -> Result<i64, String> {
+    let date = NaiveDate::parse_from_str(ex_date, "%Y-%m-%d").map_err(|e| e.to_string())?;
+    Ok(date.and_hms_opt(0, 0, 0).map(|d| d.and_utc().timestamp()).unwrap_or(0) - IST_OFFSET_SECS)
+}
+
+fn scale_candles(conn: &Connection, symbol: &str, exchange: &str, interval: &str, before: &str, factor: f64) -> Result<(), String> {
+    conn.execute(
+        "UPDATE price_history SET open = open / ?1, high = high / ?1, low = low / ?1, close = close / ?1, volume = volume * ?1
+         WHERE symbol = ?2 AND exchange = ?3 AND interval = ?4 AND date < ?5",
+        params![factor, symbol, exchange, interval, before],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Scale candles before the ex-date in every stored series downloaded before it; the providers
+/// already return split-adjusted prices for anything downloaded later. Returns the intervals scaled.
+fn adjust_price_history(conn: &Connection, symbol: &str, exchange: &str, ex_date: &str, factor: f64) -> Result<Vec<String>, String> {
+    let mut stmt = conn.prepare(
+        "SELECT interval FROM price_history_meta WHERE symbol = ?1 AND exchange = ?2 AND fetched_at < ?3"
+    ).map_err(|e| e.to_string())?;
+    let intervals = stmt.query_map(params![symbol, exchange, ex_date_start(ex_date)?], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    for interval in &intervals {
+        scale_candles(conn, symbol, exchange, interval, ex_date, factor)?;
+    }
+    Ok(intervals)
+}
+
+/// Save the action and apply it to price history or the ledger; None when it was already recorded.
+fn record(conn: &mut Connection, input: &CorporateActionInput, source: &str) -> Result<Option<i64>, String> {
+    let exchange = input.exchange.as_deref().unwrap_or("NSE");
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let inserted = tx.execute(
+        "INSERT OR IGNORE INTO corporate_actions (symbol, exchange, kind, ex_date, new_shares, held_shares, price, description, source, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            input.symbol, exchange, input.kind.as_str(), input.ex_date, input.new_shares, input.held_shares,
+            input.price, input.description, source, now_secs(),
+        ],
+    ).map_err(|e| e.to_string())?;
+    if inserted == 0 {
+        return Ok(None);
+    }
+    let id = tx.last_insert_rowid();
+
+    match input.kind {
+        CorporateActionKind::Split | CorporateActionKind::Bonus => {
+            let factor = input.kind.factor(input.new_shares, input.held_shares);
+            let intervals = adjust_price_history(&tx, &input.symbol, exchange, &input.ex_date, factor)?;
+            tx.execute("UPDATE corporate_actions SET adjusted_intervals = ?1 WHERE id = ?2", params![intervals.join(","), id])
+                .map_err(|e| e.to_string())?;
+        }
+        CorporateActionKind::Rights => {
+            let quantity = match input.quantity {
+                Some(quantity) => quantity,
+                None => {
+                    // Fractional entitlements aren't allotted
+                    let transactions = portfolio::load_transactions(&tx, Some(&input.symbol), Some(exchange))?;
+                    let held = portfolio::quantity_held(&transactions, &load_adjustments(&tx)?, &input.symbol, exchange, &input.ex_date);
+                    (held * input.new_shares / input.held_shares).floor()
+                }
+            };
+            if quantity > 0.0 {
+                portfolio::insert_trade(&tx, &NewTrade {
+                    symbol: input.symbol.clone(),
+                    exchange: exchange.to_string(),
+                    side: TradeSide::Buy,
+                    quantity,
+                    price: input.price.unwrap_or(0.0),
+                    charges: 0.0,
+                    trade_date: input.ex_date.clone(),
+                    trade_id: Some(format!("rights-{}", id)),
+                    import_key: None,
+                }, Some("Rights issue"), None)?;
+            }
+            tx.execute("UPDATE corporate_actions SET quantity = ?1 WHERE id = ?2", params![quantity, id])
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(Some(id))
+}
+
+fn holdings_changed(app: &AppHandle, conn: &mut Connection) -> Result<(), String> {
+    portfolio::rebuild_holdings(conn)?;
+    let holdings = portfolio::load_holdings(conn, false)?;
+    let _ = app.emit("holdings-changed", &holdings);
+    Ok(())
+}
+
+// Tauri Commands
+/// Record a split, bonus or rights issue that has gone ex and apply it to holdings and stored
+/// price history.
+#[tauri::command]
+pub fn record_corporate_action(app: AppHandle, input: CorporateActionInput) -> Result<CorporateAction, String> {
+    let input = CorporateActionInput {
+        symbol: input.symbol.trim().to_uppercase(),
+        exchange: Some(input.exchange.map(|e| e.trim().to_uppercase()).filter(|e| !e.is_empty()).unwrap_or_else(|| "NSE".to_string())),
+        ex_date: normalize_date(&input.ex_date).ok_or_else(|| format!("Invalid date: {}", input.ex_date))?,
+        ..input
+    };
+    if input.symbol.is_empty() {
+        return Err("Symbol is required".to_string());
+    }
+    if !(input.new_shares.is_finite() && input.held_shares.is_finite()) || input.new_shares <= 0.0 || input.held_shares <= 0.0 {
+        return Err("The ratio needs positive new and held share counts".to_string());
+    }
+    if input.kind == CorporateActionKind::Rights && input.price.is_none_or(|price| price < 0.0) {
+        return Err("A rights issue needs its issue price".to_string());
+    }
+    // Until the ex-date, prices and holdings are still in the old shares
+    if input.ex_date > today() {
+        return Err(format!("Record this {} on or after its ex-date, {}", input.kind.as_str(), input.ex_date));
+    }
+
+    let mut conn = db::open_app_db(&app)?;
+    let id = record(&mut conn, &input, "manual")?
+        .ok_or_else(|| format!("A {} for {} on {} is already recorded", input.kind.as_str(), input.symbol, input.ex_date))?;
+    holdings_changed(&app, &mut conn)?;
+    conn.query_row(&format!("{} WHERE id = ?1", SELECT_ACTIONS), params![id], action_from_row).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn list_corporate_actions(app: AppHandle, symbol: Option<String>) -> Result<Vec<CorporateAction>, String> {
+    let conn = db::open_app_db(&app)?;
+    let symbol = symbol.map(|s| s.trim().to_uppercase());
+    load_actions(&conn, symbol.as_deref())
+}
+
+/// Undo an action: its rights purchase leaves the ledger and scaled price history is restored,
+/// unless that series has been downloaded again since.
+#[tauri::command]
+pub fn delete_corporate_action(app: AppHandle, id: i64) -> Result<(), String> {
+    let mut conn = db::open_app_db(&app)?;
+    let action = conn.query_row(&format!("{} WHERE id = ?1", SELECT_ACTIONS), params![id], action_from_row)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Corporate action not found: {}", id))?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for interval in &action.adjusted_intervals {
+        let fetched_at: Option<i64> = tx.query_row(
+            "SELECT fetched_at FROM price_history_meta WHERE symbol = ?1 AND exchange = ?2 AND interval = ?3",
+            params![action.symbol, action.exchange, interval],
+            |row| row.get(0),
+        ).optional().map_err(|e| e.to_string())?;
+        if fetched_at.is_some_and(|fetched_at| fetched_at < action.created_at) {
+            scale_candles(&tx, &action.symbol, &action.exchange, interval, &action.ex_date, 1.0 / action.factor)?;
+        }
+    }
+    tx.execute("DELETE FROM transactions WHERE trade_id = ?1", params![format!("rights-{}", id)]).map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM corporate_actions WHERE id = ?1", params![id]).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    holdings_changed(&app, &mut conn)
+}
+
+/// Record NSE's splits and bonuses that have gone ex for `symbol`, or for every NSE stock the
+/// ledger has traded. Returns how many weren't already recorded.
+#[tauri::command]
+pub async fn fetch_corporate_actions(app: AppHandle, symbol: Option<String>) -> Result<usize, String> {
+    settings::ensure_online(&app)?;
+    let symbols: Vec<String> = match symbol {
+        Some(symbol) => vec![symbol.trim().to_uppercase()],
+        None => {
+            let conn = db::open_app_db(&app)?;
+            portfolio::load_holdings(&conn, true)?.into_iter()
+                .filter(|holding| holding.exchange == "NSE")
+                .map(|holding| holding.symbol)
+                .collect()
+        }
+    };
+
+    let today = today();
+    let mut added = 0;
+    for symbol in &symbols {
+        let actions: Vec<CorporateActionInput> = fetch_nse_actions(&app, symbol).await?.into_iter()
+            .filter(|(_, ex_date)| *ex_date <= today)
+            .filter_map(|(subject, ex_date)| parse_nse_action(symbol, &subject, ex_date))
+            .collect();
+        let mut conn = db::open_app_db(&app)?;
+        for action in &actions {
+            if record(&mut conn, action, "nse")?.is_some() {
+                added += 1;
+            }
+        }
+    }
+    if added > 0 {
+        let mut conn = db::open_app_db(&app)?;
+        holdings_changed(&app, &mut conn)?;
+    }
+    info!(symbols = symbols.len(), added, "Fetched corporate actions");
+    Ok(added)
+}
@@ -0,0 +1,185 @@
+// Corporate Events - announcements, board meetings and results dates from NSE/BSE
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::scraper::{self, NativeScraper, NSE_BASE};
+use crate::settings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CorporateEventKind {
+    Announcement,
+    BoardMeeting,
+    Results,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorporateEvent {
+    pub kind: CorporateEventKind,
+    pub symbol: String,
+    pub exchange: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub date: Option<String>,          // YYYY-MM-DD
+    pub attachment_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorporateEvents {
+    pub symbol: String,
+    pub exchange: String,
+    pub announcements: Vec<CorporateEvent>,
+    /// Board meetings / results dates from today onwards, soonest first
+    pub upcoming: Vec<CorporateEvent>,
+}
+
+pub fn str_field(value: &serde_json::Value, key: &str) -> Option<String> {
+    value.get(key)
+        .and_then(|v| v.as_str())
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty() && s != "-")
+}
+
+/// Normalise the exchanges' date formats ("15-Oct-2026 18:30:00", "15-Oct-2026",
+/// "2026-10-15T18:30:00.12") to YYYY-MM-DD.
+pub fn normalize_date(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let parsed = NaiveDateTime::parse_from_str(raw, "%d-%b-%Y %H:%M:%S").map(|d| d.date())
+        .or_else(|_| NaiveDate::parse_from_str(raw, "%d-%b-%Y"))
+        .or_else(|_| NaiveDate::parse_from_str(raw.get(..10).unwrap_or(raw), "%Y-%m-%d"))
+        .ok()?;
+    Some(parsed.format("%Y-%m-%d").to_string())
+}
+
+fn meeting_kind(purpose: &str) -> CorporateEventKind {
+    if purpose.to_lowercase().contains("result") {
+        CorporateEventKind::Results
+    } else {
+        CorporateEventKind::BoardMeeting
+    }
+}
+
+async fn fetch_nse(native: &NativeScraper, symbol: &str, app: &AppHandle) -> Result<(Vec<CorporateEvent>, Vec<CorporateEvent>), String> {
+    let timeout = scraper::request_timeout(app);
+    let encoded = scraper::url_encode(symbol);
+    let referer = format!("{}/get-quotes/equity?symbol={}", NSE_BASE, encoded);
+
+    let announcements = native.nse_get_json(
+        &format!("/api/corporate-announcements?index=equities&symbol={}", encoded), &referer, timeout,
+    ).await?;
+    let meetings = native.nse_get_json(
+        &format!("/api/corporate-board-meetings?index=equities&symbol={}", encoded), &referer, timeout,
+    ).await?;
+
+    let announcements = announcements.as_array().cloned().unwrap_or_default().iter()
+        .map(|a| CorporateEvent {
+            kind: CorporateEventKind::Announcement,
+            symbol: symbol.to_string(),
+            exchange: "NSE".to_string(),
+            title: str_field(a, "desc").unwrap_or_else(|| "Announcement".to_string()),
+            description: str_field(a, "attchmntText"),
+            date: str_field(a, "an_dt").or_else(|| str_field(a, "dt")).and_then(|d| normalize_date(&d)),
+            attachment_url: str_field(a, "attchmntFile"),
+        })
+        .collect();
+
+    let meetings = meetings.as_array().cloned().unwrap_or_default().iter()
+        .map(|m| {
+            let purpose = str_field(m, "bm_purpose").unwrap_or_else(|| "Board Meeting".to_string());
+            CorporateEvent {
+                kind: meeting_kind(&purpose),
+                symbol: symbol.to_string(),
+                exchange: "NSE".to_string(),
+                title: purpose,
+                description: str_field(m, "bm_desc"),
+                date: str_field(m, "bm_date").and_then(|d| normalize_date(&d)),
+                attachment_url: str_field(m, "attachment"),
+            }
+        })
+        .collect();
+
+    Ok((announcements, meetings))
+}
+
+/// BSE keys everything by scrip code, so `symbol` must be numeric here.
+async fn fetch_bse(native: &NativeScraper, scrip_code: &str, app: &AppHandle) -> Result<(Vec<CorporateEvent>, Vec<CorporateEvent>), String> {
+    if !scrip_code.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("BSE events need a numeric scrip code, got: {}", scrip_code));
+    }
+    let timeout = scraper::request_timeout(app);
+    let today = Local::now().date_naive();
+    let from = today - chrono::Duration::days(90);
+
+    let announcements = native.bse_get_json(&format!(
+        "/AnnSubCategoryGetData/w?pageno=1&strCat=-1&strPrevDate={}&strScrip={}&strSearch=P&strToDate={}&strType=C",
+        from.format("%Y%m%d"), scrip_code, today.format("%Y%m%d"),
+    ), timeout).await?;
+    let meetings = native.bse_get_json(&format!(
+        "/BoardMeeting/w?scripcode={}", scrip_code,
+    ), timeout).await.unwrap_or(serde_json::Value::Null);
+
+    let announcements = announcements.get("Table").and_then(|t| t.as_array()).cloned().unwrap_or_default().iter()
+        .map(|a| CorporateEvent {
+            kind: CorporateEventKind::Announcement,
+            symbol: scrip_code.to_string(),
+            exchange: "BSE".to_string(),
+            title: str_field(a, "NEWSSUB").or_else(|| str_field(a, "HEADLINE")).unwrap_or_else(|| "Announcement".to_string()),
+            description: str_field(a, "HEADLINE"),
+            date: str_field(a, "NEWS_DT").and_then(|d| normalize_date(&d)),
+            attachment_url: str_field(a, "ATTACHMENTNAME")
+                .map(|f| format!("https://www.bseindia.com/xml-data/corpfiling/AttachLive/{}", f)),
+        })
+        .collect();
+
+    let meetings = meetings.get("Table").and_then(|t| t.as_array()).cloned().unwrap_or_default().iter()
+        .map(|m| {
+            let purpose = str_field(m, "Purpose").unwrap_or_else(|| "Board Meeting".to_string());
+            CorporateEvent {
+                kind: meeting_kind(&purpose),
+                symbol: scrip_code.to_string(),
+                exchange: "BSE".to_string(),
+                title: purpose,
+                description: None,
+                date: str_field(m, "Meeting_Date").and_then(|d| normalize_date(&d)),
+                attachment_url: None,
+            }
+        })
+        .collect();
+
+    Ok((announcements, meetings))
+}
+
+// Tauri Commands
+#[tauri::command]
+pub async fn get_corporate_events(
+    app: AppHandle,
+    native: tauri::State<'_, NativeScraper>,
+    symbol: String,
+    exchange: String,
+    limit: Option<usize>,
+) -> Result<CorporateEvents, String> {
+    settings::ensure_online(&app)?;
+    let symbol = symbol.trim().to_uppercase();
+    let exchange = exchange.trim().to_uppercase();
+
+    let (mut announcements, meetings) = match exchange.as_str() {
+        "NSE" => fetch_nse(&native, &symbol, &app).await?,
+        "BSE" => fetch_bse(&native, &symbol, &app).await?,
+        other => return Err(format!("Corporate events not supported for exchange: {}", other)),
+    };
+
+    // Newest announcements first
+    announcements.sort_by(|a, b| b.date.cmp(&a.date));
+    announcements.truncate(limit.unwrap_or(25));
+
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    let mut upcoming: Vec<CorporateEvent> = meetings.into_iter()
+        .filter(|m| m.date.as_deref().is_some_and(|d| d >= today.as_str()))
+        .collect();
+    upcoming.sort_by(|a, b| a.date.cmp(&b.date));
+
+    Ok(CorporateEvents { symbol, exchange, announcements, upcoming })
+}
@@ -0,0 +1,308 @@
+// Credit - a borrower's debt-servicing capacity from its extracted statements: interest
+// coverage, DSCR and net debt to EBITDA for every fiscal year on file, their trends, and a
+// credit band summing them up for screening
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+
+use crate::periods::Period;
+use crate::ratio_alerts;
+use crate::ratios;
+use crate::statements::{self, StoredDocument};
+
+const CASH: &[&str] = &["cash and cash equivalents", "cash and bank balances", "cash & cash equivalents", "cash and cash equivalent"];
+const OTHER_BANK_BALANCES: &[&str] = &["bank balances other than cash and cash equivalents", "other bank balances"];
+// Principal repaid in the year, from the cash flow statement
+const REPAYMENTS: &[&str] = &[
+    "repayment of long-term borrowings",
+    "repayment of long term borrowings",
+    "repayment of non-current borrowings",
+    "repayment of borrowings",
+    "repayment of term loans",
+];
+// Falls back to the principal falling due within a year, from the balance sheet
+const CURRENT_MATURITIES: &[&str] = &["current maturities of long-term borrowings", "current maturities of long term debt"];
+
+// Changes within this share of the first value are stable
+const TREND_TOLERANCE: f64 = 0.05;
+const DEFAULT_TREND_YEARS: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CreditBand {
+    Strong,
+    Adequate,
+    Weak,
+    Distressed,
+}
+
+impl CreditBand {
+    fn from_score(score: f64) -> Self {
+        if score >= 2.5 {
+            CreditBand::Strong
+        } else if score >= 1.75 {
+            CreditBand::Adequate
+        } else if score >= 1.0 {
+            CreditBand::Weak
+        } else {
+            CreditBand::Distressed
+        }
+    }
+
+    fn lower(self) -> Self {
+        match self {
+            CreditBand::Strong => CreditBand::Adequate,
+            CreditBand::Adequate => CreditBand::Weak,
+            _ => CreditBand::Distressed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrendDirection {
+    Improving,
+    Stable,
+    Deteriorating,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreditPeriod {
+    pub period: Period,
+    pub ebit: Option<f64>,
+    pub ebitda: Option<f64>,
+    pub interest: Option<f64>,
+    pub debt: Option<f64>,
+    pub cash: Option<f64>,
+    /// Debt less cash and bank balances; negative for net cash
+    pub net_debt: Option<f64>,
+    /// Interest plus principal repaid (or falling due) in the year
+    pub debt_service: Option<f64>,
+    /// EBIT over interest
+    pub interest_coverage: Option<f64>,
+    /// EBITDA over debt service
+    pub dscr: Option<f64>,
+    pub net_debt_to_ebitda: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricTrend {
+    pub key: String,
+    pub name: String,
+    pub first_period: Period,
+    pub first: f64,
+    pub latest_period: Period,
+    pub latest: f64,
+    pub direction: TrendDirection,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreditAnalysis {
+    pub document_id: i64,
+    pub filename: String,
+    /// Oldest first
+    pub periods: Vec<CreditPeriod>,
+    pub trends: Vec<MetricTrend>,
+    /// 0 (worst) to 3, averaged over the latest year's metrics
+    pub score: Option<f64>,
+    /// `None` when no metric could be computed for the latest year
+    pub band: Option<CreditBand>,
+    /// What set the band, metric by metric
+    pub drivers: Vec<String>,
+}
+
+fn divide(numerator: Option<f64>, denominator: Option<f64>) -> Option<f64> {
+    match (numerator, denominator) {
+        (Some(n), Some(d)) if d != 0.0 => Some(n / d),
+        _ => None,
+    }
+}
+
+fn current(pair: Option<(f64, f64)>) -> Option<f64> {
+    pair.map(|(current, _)| current)
+}
+
+fn credit_period(items: &[serde_json::Value], period: Period) -> CreditPeriod {
+    let bs = Some("balance_sheet");
+    let ebit = current(ratios::mapped(items, "ebit"));
+    let ebitda = current(ratios::mapped(items, "ebitda"));
+    let interest = current(ratios::mapped(items, "interest"));
+    let debt = current(ratios::mapped(items, "debt"));
+    let cash = match (current(ratios::find(items, CASH, bs)), current(ratios::find(items, OTHER_BANK_BALANCES, bs))) {
+        (None, None) => None,
+        (cash, other) => Some(cash.unwrap_or(0.0) + other.unwrap_or(0.0)),
+    };
+    let net_debt = debt.map(|debt| debt - cash.unwrap_or(0.0));
+    let principal = current(ratios::find(items, REPAYMENTS, Some("cash_flow")))
+        .or_else(|| current(ratios::find(items, CURRENT_MATURITIES, bs)))
+        .map(f64::abs);
+    let debt_service = match (interest, principal) {
+        (None, None) => None,
+        (interest, principal) => Some(interest.unwrap_or(0.0) + principal.unwrap_or(0.0)),
+    };
+    CreditPeriod {
+        period,
+        ebit,
+        ebitda,
+        interest,
+        debt,
+        cash,
+        net_debt,
+        debt_service,
+        interest_coverage: divide(ebit, interest),
+        dscr: divide(ebitda, debt_service),
+        // Against negative EBITDA the multiple means nothing
+        net_debt_to_ebitda: divide(net_debt, ebitda.filter(|e| *e > 0.0)),
+    }
+}
+
+/// One `CreditPeriod` per fiscal year across `documents` (oldest first), later documents
+/// winning where they restate a year. Quarters are left out: they'd set a quarter's EBITDA
+/// against a year-end debt.
+fn company_periods(documents: &[StoredDocument]) -> Vec<CreditPeriod> {
+    let mut periods: BTreeMap<Period, CreditPeriod> = BTreeMap::new();
+    for document in documents {
+        for period in ratio_alerts::document_periods(document).into_iter().filter(|p| p.quarter.is_none()) {
+            let credit = credit_period(&ratio_alerts::items_at(document, period), period);
+            if credit.ebitda.is_some() || credit.debt.is_some() {
+                periods.insert(period, credit);
+            }
+        }
+    }
+    periods.into_values().collect()
+}
+
+/// (key, name, value, whether higher is better) for each metric of `period`.
+fn metrics(period: &CreditPeriod) -> [(&'static str, &'static str, Option<f64>, bool); 3] {
+    [
+        ("interest_coverage", "Interest Coverage", period.interest_coverage, true),
+        ("dscr", "DSCR", period.dscr, true),
+        ("net_debt_to_ebitda", "Net Debt to EBITDA", period.net_debt_to_ebitda, false),
+    ]
+}
+
+fn trend(periods: &[CreditPeriod], index: usize) -> Option<MetricTrend> {
+    let values: Vec<(Period, f64, &str, &str, bool)> = periods.iter()
+        .filter_map(|period| {
+            let (key, name, value, higher_is_better) = metrics(period)[index];
+            value.map(|value| (period.period, value, key, name, higher_is_better))
+        })
+        .collect();
+    let (first_period, first, key, name, higher_is_better) = *values.first()?;
+    let (latest_period, latest, _, _, _) = *values.last()?;
+    if first_period == latest_period {
+        return None;
+    }
+    let change = if first != 0.0 { (latest - first) / first.abs() } else { latest.signum() };
+    let direction = if change.abs() <= TREND_TOLERANCE {
+        TrendDirection::Stable
+    } else if (change > 0.0) == higher_is_better {
+        TrendDirection::Improving
+    } else {
+        TrendDirection::Deteriorating
+    };
+    Some(MetricTrend { key: key.to_string(), name: name.to_string(), first_period, first, latest_period, latest, direction })
+}
+
+/// Points from 0 to 3 for one metric, with the reason.
+fn points(key: &str, value: f64, period: &CreditPeriod) -> (f64, String) {
+    let (points, verdict) = match key {
+        "interest_coverage" => match value {
+            v if v >= 5.0 => (3.0, "comfortable"),
+            v if v >= 3.0 => (2.0, "adequate"),
+            v if v >= 1.5 => (1.0, "thin"),
+            _ => (0.0, "insufficient"),
+        },
+        "dscr" => match value {
+            v if v >= 2.0 => (3.0, "comfortable"),
+            v if v >= 1.5 => (2.0, "adequate"),
+            v if v >= 1.2 => (1.0, "thin"),
+            _ => (0.0, "insufficient"),
+        },
+        _ => match value {
+            v if v <= 1.0 => (3.0, "low leverage"),
+            v if v <= 2.5 => (2.0, "moderate leverage"),
+            v if v <= 4.0 => (1.0, "high leverage"),
+            _ => (0.0, "excessive leverage"),
+        },
+    };
+    let name = metrics(period).iter().find(|(k, ..)| *k == key).map_or(key, |(_, name, ..)| *name);
+    (points, format!("{} {:.2}x in {}: {}", name, value, period.period, verdict))
+}
+
+fn analyze(document: &StoredDocument, documents: &[StoredDocument], years: usize) -> CreditAnalysis {
+    let mut periods = company_periods(documents);
+    if periods.len() > years {
+        periods.drain(..periods.len() - years);
+    }
+    let trends: Vec<MetricTrend> = (0..3).filter_map(|index| trend(&periods, index)).collect();
+
+    let mut drivers = Vec::new();
+    let (mut score, mut band) = (None, None);
+    if let Some(latest) = periods.last() {
+        let mut scored = Vec::new();
+        for (key, _, value, _) in metrics(latest) {
+            if let Some(value) = value {
+                let (earned, reason) = points(key, value, latest);
+                scored.push(earned);
+                drivers.push(reason);
+            }
+        }
+        // Negative EBITDA has no multiple, but is the worst case for leverage
+        if latest.net_debt_to_ebitda.is_none() && latest.ebitda.is_some_and(|e| e <= 0.0) {
+            scored.push(0.0);
+            drivers.push(format!("EBITDA is not positive in {}", latest.period));
+        }
+        if !scored.is_empty() {
+            let average = scored.iter().sum::<f64>() / scored.len() as f64;
+            let mut result = CreditBand::from_score(average);
+            let deteriorating: Vec<&str> = trends.iter()
+                .filter(|t| t.direction == TrendDirection::Deteriorating)
+                .map(|t| t.name.as_str())
+                .collect();
+            if deteriorating.len() >= 2 {
+                result = result.lower();
+                drivers.push(format!("Band lowered one notch: {} deteriorating", deteriorating.join(" and ")));
+            }
+            score = Some(average);
+            band = Some(result);
+        }
+    }
+
+    CreditAnalysis {
+        document_id: document.id,
+        filename: document.filename.clone(),
+        periods,
+        trends,
+        score,
+        band,
+        drivers,
+    }
+}
+
+// Tauri Commands
+/// Coverage and leverage for the borrower `document_id` belongs to, over its last `years`
+/// fiscal years on file (default 5) across all of the company's documents.
+#[tauri::command]
+pub fn get_credit_analysis(app: AppHandle, document_id: i64, years: Option<usize>) -> Result<CreditAnalysis, String> {
+    let document = statements::load_document(&app, document_id)?;
+    let documents = ratio_alerts::company_documents(&app, &document)?;
+    Ok(analyze(&document, &documents, years.unwrap_or(DEFAULT_TREND_YEARS).max(1)))
+}
+
+/// Credit analyses for several borrowers, strongest first; borrowers without a band last.
+#[tauri::command]
+pub fn screen_borrowers(app: AppHandle, document_ids: Vec<i64>, years: Option<usize>) -> Result<Vec<CreditAnalysis>, String> {
+    let years = years.unwrap_or(DEFAULT_TREND_YEARS).max(1);
+    let mut analyses = Vec::new();
+    for document_id in document_ids {
+        let document = statements::load_document(&app, document_id)?;
+        let documents = ratio_alerts::company_documents(&app, &document)?;
+        analyses.push(analyze(&document, &documents, years));
+    }
+    analyses.sort_by(|a, b| b.score.unwrap_or(-1.0).total_cmp(&a.score.unwrap_or(-1.0)));
+    Ok(analyses)
+}
@@ -0,0 +1,179 @@
+// CSV Import - statement CSVs (Tally and accounting exports) into financial_items
+use calamine::{Data, Range};
+use encoding_rs::{Encoding, UTF_8, WINDOWS_1252};
+use serde::Deserialize;
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::activity::{self, ActivityKind};
+use crate::excel::{self, ExcelOptions, ParsedSpreadsheet};
+use crate::integrity;
+use crate::statements;
+
+const DELIMITERS: [char; 4] = [',', ';', '\t', '|'];
+
+// Enough lines to see the table's shape past any title rows
+const SNIFF_LINES: usize = 20;
+
+/// A column picked by 0-based index or by its header text.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum CsvColumn {
+    Index(usize),
+    Name(String),
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CsvColumnMapping {
+    pub label: Option<CsvColumn>,
+    pub current_year: Option<CsvColumn>,
+    pub previous_year: Option<CsvColumn>,
+    /// Whether the first row is a header; detected when absent
+    pub has_header: Option<bool>,
+    pub statement_type: Option<String>,
+    pub unit: Option<String>,
+}
+
+/// BOM first, then strict UTF-8, then Windows-1252 (Tally's default "ANSI" export).
+pub fn decode(bytes: &[u8]) -> (String, &'static str) {
+    let (encoding, body): (&'static Encoding, &[u8]) = match Encoding::for_bom(bytes) {
+        Some((encoding, bom_len)) => (encoding, &bytes[bom_len..]),
+        None if std::str::from_utf8(bytes).is_ok() => (UTF_8, bytes),
+        None => (WINDOWS_1252, bytes),
+    };
+    let (text, _) = encoding.decode_without_bom_handling(body);
+    (text.into_owned(), encoding.name())
+}
+
+/// Split one line on `delimiter`, honouring double quotes and `""` escapes.
+pub fn split_line(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(std::mem::take(&mut current)),
+            c => current.push(c),
+        }
+    }
+    fields.push(current);
+    fields.into_iter().map(|f| f.trim().to_string()).collect()
+}
+
+/// The delimiter that splits the most sample lines into the same number (>1) of fields.
+pub fn detect_delimiter(lines: &[&str]) -> char {
+    DELIMITERS.iter()
+        .copied()
+        .max_by_key(|&delimiter| {
+            let mut counts = std::collections::HashMap::new();
+            for line in lines.iter().take(SNIFF_LINES) {
+                let fields = split_line(line, delimiter).len();
+                if fields > 1 {
+                    *counts.entry(fields).or_insert(0usize) += 1;
+                }
+            }
+            // Most consistent field count, then the widest split
+            counts.into_iter().map(|(fields, lines)| (lines, fields)).max().unwrap_or((0, 0))
+        })
+        .unwrap_or(',')
+}
+
+pub fn resolve_column(column: &CsvColumn, header: &[String]) -> Result<usize, String> {
+    match column {
+        CsvColumn::Index(index) => Ok(*index),
+        CsvColumn::Name(name) => header.iter()
+            .position(|h| h.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| format!("Column not found in CSV header: {}", name)),
+    }
+}
+
+// Tauri Commands
+#[tauri::command]
+pub async fn parse_csv(
+    app: AppHandle,
+    file_path: String,
+    column_mapping: Option<CsvColumnMapping>,
+) -> Result<ParsedSpreadsheet, String> {
+    let mapping = column_mapping.unwrap_or_default();
+    let bytes = std::fs::read(&file_path).map_err(|e| format!("Failed to read CSV: {}", e))?;
+    let (text, encoding) = decode(&bytes);
+
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    if lines.is_empty() {
+        return Err("CSV file is empty".to_string());
+    }
+    let delimiter = detect_delimiter(&lines);
+    let rows: Vec<Vec<String>> = lines.iter().map(|l| split_line(l, delimiter)).collect();
+    let width = rows.iter().map(Vec::len).max().unwrap_or(1);
+
+    let mut range = Range::new((0, 0), (rows.len() as u32 - 1, width as u32 - 1));
+    for (r, row) in rows.iter().enumerate() {
+        for (c, field) in row.iter().enumerate().filter(|(_, f)| !f.is_empty()) {
+            range.set_value((r as u32, c as u32), Data::String(field.clone()));
+        }
+    }
+
+    // Naming a column means the first row is its header
+    let names_columns = [&mapping.label, &mapping.current_year, &mapping.previous_year]
+        .iter()
+        .any(|c| matches!(c, Some(CsvColumn::Name(_))));
+    let header_row = match mapping.has_header {
+        Some(true) => Some(0),
+        _ if names_columns => Some(0),
+        _ => None,
+    };
+    let value_columns = match &mapping.current_year {
+        Some(current) => {
+            let mut cols = vec![resolve_column(current, &rows[0])?];
+            if let Some(previous) = &mapping.previous_year {
+                cols.push(resolve_column(previous, &rows[0])?);
+            }
+            Some(cols)
+        }
+        None => None,
+    };
+    let options = ExcelOptions {
+        header_row,
+        label_column: mapping.label.as_ref().map(|c| resolve_column(c, &rows[0])).transpose()?,
+        value_columns,
+        statement_type: mapping.statement_type.clone(),
+        unit: mapping.unit.clone(),
+    };
+
+    let filename = Path::new(&file_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| file_path.clone());
+    let (lines, periods) = excel::read_sheet(&range, Some(&filename), &options)?;
+    let unit = options.unit.unwrap_or_else(|| "INR".to_string());
+    let metadata = serde_json::json!({
+        "fileName": filename,
+        "source": "csv",
+        "encoding": encoding,
+        "delimiter": delimiter.to_string(),
+        "fiscalYears": periods,
+        "currency": unit,
+        "parser": "native_csv",
+    });
+
+    let (doc_id, items) = statements::save_document(&app, &filename, &metadata, &lines, &periods, &unit, "csv")?;
+    eprintln!("[CSV] Imported {} rows from {} ({}, {:?}) as document {}", items.len(), filename, encoding, delimiter, doc_id);
+    statements::document_stored(&app, doc_id);
+    integrity::record(&app, doc_id, &file_path);
+    activity::record(&app, ActivityKind::DocumentImported, Some(doc_id), &format!("Imported {}", filename), None);
+    Ok(ParsedSpreadsheet {
+        doc_id,
+        filename,
+        sheet: None,
+        periods,
+        item_count: items.len(),
+        items,
+    })
+}
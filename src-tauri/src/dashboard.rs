@@ -0,0 +1,199 @@
+// Dashboard - the KPIs the user pinned, and everything the dashboard shows for them (latest
+// value, a sparkline across the company's periods, alert state) gathered in one call
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use tracing::info;
+
+use crate::db;
+use crate::derived;
+use crate::metric_formulas;
+use crate::ratio_alerts::{self, RatioFinding};
+use crate::ratios;
+use crate::statements;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS pinned_metrics (
+    metric_id TEXT PRIMARY KEY,    -- a ratios::KEYS entry or a metric formula name
+    position INTEGER NOT NULL,
+    pinned_at INTEGER NOT NULL
+);
+";
+// Points in a sparkline; older periods are dropped
+const SPARKLINE_POINTS: usize = 8;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PinnedMetric {
+    pub metric_id: String,
+    pub position: i64,
+    pub pinned_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SparkPoint {
+    pub period: String,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricAlert {
+    /// Active ratio rules watching the metric
+    pub rules: usize,
+    /// Findings those rules raised on the dashboard's document
+    pub findings: Vec<RatioFinding>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardMetric {
+    pub metric_id: String,
+    pub name: String,
+    pub category: String,
+    pub unit: String,
+    /// The document's current and previous year values; None where the items don't support it
+    pub latest: Option<f64>,
+    pub previous: Option<f64>,
+    pub change_percent: Option<f64>,
+    /// Oldest first, across the company's stored documents
+    pub sparkline: Vec<SparkPoint>,
+    pub alert: MetricAlert,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardData {
+    /// None when nothing has been analyzed yet
+    pub document_id: Option<i64>,
+    pub filename: Option<String>,
+    pub company: Option<String>,
+    pub period: Option<String>,
+    pub metrics: Vec<DashboardMetric>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn load_pins(app: &AppHandle) -> Result<Vec<PinnedMetric>, String> {
+    let conn = db::open_app_db(app)?;
+    let mut stmt = conn.prepare("SELECT metric_id, position, pinned_at FROM pinned_metrics ORDER BY position, pinned_at")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], |row| {
+        Ok(PinnedMetric { metric_id: row.get(0)?, position: row.get(1)?, pinned_at: row.get(2)? })
+    }).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// A built-in ratio key or a saved formula's name, lowercased.
+fn known_metric(app: &AppHandle, metric_id: &str) -> Result<String, String> {
+    let metric_id = metric_id.trim().to_lowercase();
+    if ratios::KEYS.contains(&metric_id.as_str()) {
+        return Ok(metric_id);
+    }
+    let formulas = metric_formulas::list_metric_formulas(app.clone())?;
+    if formulas.iter().any(|formula| formula.name == metric_id) {
+        return Ok(metric_id);
+    }
+    Err(format!("Unknown metric: {}", metric_id))
+}
+
+fn change_percent(latest: Option<f64>, previous: Option<f64>) -> Option<f64> {
+    match (latest, previous) {
+        (Some(latest), Some(previous)) if previous != 0.0 => Some((latest - previous) / previous.abs() * 100.0),
+        _ => None,
+    }
+}
+
+// Tauri Commands
+/// Pin a metric to the end of the dashboard; pinning one already there leaves it in place.
+#[tauri::command]
+pub fn pin_metric(app: AppHandle, metric_id: String) -> Result<Vec<PinnedMetric>, String> {
+    let metric_id = known_metric(&app, &metric_id)?;
+    let conn = db::open_app_db(&app)?;
+    conn.execute(
+        "INSERT OR IGNORE INTO pinned_metrics (metric_id, position, pinned_at)
+         VALUES (?1, (SELECT IFNULL(MAX(position), -1) + 1 FROM pinned_metrics), ?2)",
+        params![metric_id, now_secs()],
+    ).map_err(|e| e.to_string())?;
+    info!(metric = %metric_id, "Pinned metric");
+    let pins = load_pins(&app)?;
+    let _ = app.emit("pinned-metrics-changed", &pins);
+    Ok(pins)
+}
+
+#[tauri::command]
+pub fn unpin_metric(app: AppHandle, metric_id: String) -> Result<Vec<PinnedMetric>, String> {
+    let conn = db::open_app_db(&app)?;
+    conn.execute("DELETE FROM pinned_metrics WHERE metric_id = ?1", params![metric_id.trim().to_lowercase()])
+        .map_err(|e| e.to_string())?;
+    let pins = load_pins(&app)?;
+    let _ = app.emit("pinned-metrics-changed", &pins);
+    Ok(pins)
+}
+
+#[tauri::command]
+pub fn list_pinned_metrics(app: AppHandle) -> Result<Vec<PinnedMetric>, String> {
+    load_pins(&app)
+}
+
+/// Every pinned metric for `document_id`, else the latest document: its values, a sparkline
+/// over the company's periods and the ratio alerts on it.
+#[tauri::command]
+pub fn get_dashboard_data(app: AppHandle, document_id: Option<i64>) -> Result<DashboardData, String> {
+    let pins = load_pins(&app)?;
+    let document_id = match document_id {
+        Some(id) => Some(id),
+        None => statements::latest_document_id(&app).ok(),
+    };
+    let Some(document_id) = document_id else {
+        return Ok(DashboardData { document_id: None, filename: None, company: None, period: None, metrics: Vec::new() });
+    };
+
+    // Precomputed when the document was stored; see `derived`
+    let document = derived::document(&app, document_id)?;
+    let values = &document.ratios;
+    let series = if pins.is_empty() { Default::default() } else { derived::company_series(&app, document_id)? };
+    let rules = ratio_alerts::load_rules(&app, true)?;
+    let findings = ratio_alerts::get_ratio_findings(app.clone(), Some(document_id), None)?;
+
+    let metrics = pins.iter()
+        .map(|pin| {
+            let ratio = values.iter().find(|ratio| ratio.key == pin.metric_id);
+            // Formula metrics are derived at each document's own two years, so they have history too
+            let sparkline: Vec<SparkPoint> = series.get(&pin.metric_id)
+                .map(|points| points.iter().map(|(period, value)| SparkPoint { period: period.to_string(), value: *value }).collect())
+                .unwrap_or_default();
+            let skip = sparkline.len().saturating_sub(SPARKLINE_POINTS);
+            let watching: Vec<i64> = rules.iter().filter(|rule| rule.ratio_key == pin.metric_id).map(|rule| rule.id).collect();
+            DashboardMetric {
+                metric_id: pin.metric_id.clone(),
+                name: ratio.map_or_else(|| pin.metric_id.clone(), |ratio| ratio.name.clone()),
+                category: ratio.map(|ratio| ratio.category.clone()).unwrap_or_default(),
+                unit: ratio.map(|ratio| ratio.unit.clone()).unwrap_or_default(),
+                latest: ratio.and_then(|ratio| ratio.current),
+                previous: ratio.and_then(|ratio| ratio.previous),
+                change_percent: change_percent(ratio.and_then(|ratio| ratio.current), ratio.and_then(|ratio| ratio.previous)),
+                sparkline: sparkline.into_iter().skip(skip).collect(),
+                alert: MetricAlert {
+                    rules: watching.len(),
+                    findings: findings.iter().filter(|finding| watching.contains(&finding.rule_id)).cloned().collect(),
+                },
+            }
+        })
+        .collect();
+
+    Ok(DashboardData {
+        document_id: Some(document_id),
+        filename: Some(document.filename.clone()),
+        company: document.company.clone(),
+        period: document.current_period.clone(),
+        metrics,
+    })
+}
@@ -0,0 +1,119 @@
+// Database - Rust-side SQLite access for the active profile
+use rusqlite::Connection;
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+use crate::activity;
+use crate::ai_analysis;
+use crate::alerts;
+use crate::app_lock;
+use crate::bank_statements;
+use crate::chat_sessions;
+use crate::corporate_actions;
+use crate::dashboard;
+use crate::dividends;
+use crate::fx_rates;
+use crate::gst_invoices;
+use crate::inflation;
+use crate::integrity;
+use crate::mapping_history;
+use crate::market_cache;
+use crate::metric_formulas;
+use crate::mutual_funds;
+use crate::notes;
+use crate::oauth;
+use crate::parse_cache;
+use crate::partial_results;
+use crate::portfolio;
+use crate::price_history;
+use crate::profiles;
+use crate::ratio_alerts;
+use crate::realtime;
+use crate::recent_files;
+use crate::report_templates;
+use crate::scenarios;
+use crate::scheduler;
+use crate::scraper_usage;
+use crate::schedules;
+use crate::shareholding;
+use crate::snapshots;
+use crate::symbols;
+use crate::sync;
+use crate::terminology;
+use crate::trading_calendar;
+use crate::usage;
+use crate::watchlist;
+
+/// Open the profile's app database, creating any missing tables.
+pub fn open_app_db(app: &AppHandle) -> Result<Connection, String> {
+    // Sealed while locked; opening it would only create an empty one
+    if app_lock::is_locked(app) {
+        return Err(app_lock::LOCKED_ERROR.to_string());
+    }
+    let conn = Connection::open(profiles::active_app_db_path(app))
+        .map_err(|e| format!("Failed to open app database: {}", e))?;
+    conn.busy_timeout(std::time::Duration::from_secs(5)).map_err(|e| e.to_string())?;
+    migrate(&conn)?;
+    Ok(conn)
+}
+
+/// The profile's pipeline database (extracted_data.db), for opening here or handing to Python
+/// as `FINCALC_DB_PATH`. Refused while locked: it's sealed, and whatever opened the path would
+/// leave an empty database there that unlocking then overwrites.
+pub fn pipeline_db_path(app: &AppHandle) -> Result<PathBuf, String> {
+    if app_lock::is_locked(app) {
+        return Err(app_lock::LOCKED_ERROR.to_string());
+    }
+    Ok(profiles::active_db_path(app))
+}
+
+pub fn open_pipeline_db(app: &AppHandle) -> Result<Connection, String> {
+    Connection::open(pipeline_db_path(app)?).map_err(|e| e.to_string())
+}
+
+fn migrate(conn: &Connection) -> Result<(), String> {
+    let schemas = [
+        market_cache::SCHEMA,
+        watchlist::SCHEMA,
+        alerts::SCHEMA,
+        price_history::SCHEMA,
+        mutual_funds::SCHEMA,
+        symbols::SCHEMA,
+        shareholding::SCHEMA,
+        parse_cache::SCHEMA,
+        schedules::SCHEMA,
+        report_templates::SCHEMA,
+        snapshots::SCHEMA,
+        usage::SCHEMA,
+        sync::SCHEMA,
+        ai_analysis::SCHEMA,
+        chat_sessions::SCHEMA,
+        partial_results::SCHEMA,
+        ratio_alerts::SCHEMA,
+        metric_formulas::SCHEMA,
+        scenarios::SCHEMA,
+        recent_files::SCHEMA,
+        mapping_history::SCHEMA,
+        terminology::SCHEMA,
+        notes::SCHEMA,
+        dashboard::SCHEMA,
+        oauth::SCHEMA,
+        trading_calendar::SCHEMA,
+        scheduler::SCHEMA,
+        scraper_usage::SCHEMA,
+        portfolio::SCHEMA,
+        dividends::SCHEMA,
+        corporate_actions::SCHEMA,
+        realtime::SCHEMA,
+        integrity::SCHEMA,
+        activity::SCHEMA,
+        gst_invoices::SCHEMA,
+        bank_statements::SCHEMA,
+        inflation::SCHEMA,
+        fx_rates::SCHEMA,
+    ];
+    for schema in schemas {
+        conn.execute_batch(schema).map_err(|e| format!("Database migration failed: {}", e))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,129 @@
+// DCF - discounted cash flow valuation from a base free cash flow (given or taken from a document)
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::inflation;
+use crate::ratios;
+use crate::statements;
+
+const DEFAULT_YEARS: u32 = 5;
+const MAX_YEARS: u32 = 30;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DcfInputs {
+    /// Take the base free cash flow (operating cash flow less capex) from this document
+    pub document_id: Option<i64>,
+    /// Explicit base free cash flow; wins over `document_id`
+    pub base_cash_flow: Option<f64>,
+    /// Yearly growth during the projection, in percent
+    pub growth_rate: f64,
+    /// Discount rate (WACC), in percent
+    pub discount_rate: f64,
+    /// Perpetual growth after the projection, in percent
+    pub terminal_growth_rate: f64,
+    pub years: Option<u32>,
+    /// Debt less cash; subtracted from enterprise value
+    pub net_debt: Option<f64>,
+    pub shares_outstanding: Option<f64>,
+    /// Yearly inflation, in percent, to restate projections in today's money
+    /// (`get_average_inflation` gives one from CPI data)
+    pub inflation: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DcfYear {
+    pub year: u32,
+    pub cash_flow: f64,
+    pub present_value: f64,
+    /// `cash_flow` in today's money, with `inflation`
+    pub real_cash_flow: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DcfResult {
+    pub base_cash_flow: f64,
+    /// "input" or the document's filename
+    pub base_source: String,
+    pub projections: Vec<DcfYear>,
+    pub terminal_value: f64,
+    pub terminal_present_value: f64,
+    /// `terminal_value` in today's money, with `inflation`
+    pub real_terminal_value: Option<f64>,
+    pub enterprise_value: f64,
+    pub equity_value: f64,
+    pub value_per_share: Option<f64>,
+}
+
+/// Gordon growth terminal value on the last projected year, everything discounted to today.
+pub fn value(base_cash_flow: f64, inputs: &DcfInputs) -> Result<Vec<DcfYear>, String> {
+    let years = inputs.years.unwrap_or(DEFAULT_YEARS);
+    if years == 0 || years > MAX_YEARS {
+        return Err(format!("Projection years must be between 1 and {}", MAX_YEARS));
+    }
+    if inputs.discount_rate <= inputs.terminal_growth_rate {
+        return Err("Discount rate must be above the terminal growth rate".to_string());
+    }
+    if inputs.inflation.is_some_and(|inflation| !inflation.is_finite() || inflation <= -100.0) {
+        return Err("Inflation must be a number above -100%".to_string());
+    }
+    let (growth, discount) = (inputs.growth_rate / 100.0, inputs.discount_rate / 100.0);
+    Ok((1..=years)
+        .map(|year| {
+            let cash_flow = base_cash_flow * (1.0 + growth).powi(year as i32);
+            DcfYear {
+                year,
+                cash_flow,
+                present_value: cash_flow / (1.0 + discount).powi(year as i32),
+                real_cash_flow: inputs.inflation.map(|inflation| inflation::deflate(cash_flow, inflation, year as f64)),
+            }
+        })
+        .collect())
+}
+
+pub fn run(app: &AppHandle, inputs: &DcfInputs) -> Result<DcfResult, String> {
+    let (base_cash_flow, base_source) = match (inputs.base_cash_flow, inputs.document_id) {
+        (Some(base), _) => (base, "input".to_string()),
+        (None, Some(document_id)) => {
+            let document = statements::load_document(app, document_id)?;
+            let (current, _) = ratios::free_cash_flow(&document.items)
+                .ok_or_else(|| format!("No operating cash flow found in {}", document.filename))?;
+            (current, document.filename)
+        }
+        (None, None) => return Err("Pass a base cash flow or a document to take it from".to_string()),
+    };
+    valuation(base_cash_flow, base_source, inputs)
+}
+
+/// Projections, terminal value and equity value for a known base free cash flow.
+pub fn valuation(base_cash_flow: f64, base_source: String, inputs: &DcfInputs) -> Result<DcfResult, String> {
+    let projections = value(base_cash_flow, inputs)?;
+    let last = projections.last().expect("at least one projected year");
+    let (discount, terminal_growth) = (inputs.discount_rate / 100.0, inputs.terminal_growth_rate / 100.0);
+    let terminal_value = last.cash_flow * (1.0 + terminal_growth) / (discount - terminal_growth);
+    let terminal_present_value = terminal_value / (1.0 + discount).powi(last.year as i32);
+    let real_terminal_value = inputs.inflation.map(|inflation| inflation::deflate(terminal_value, inflation, last.year as f64));
+    let enterprise_value = projections.iter().map(|y| y.present_value).sum::<f64>() + terminal_present_value;
+    let equity_value = enterprise_value - inputs.net_debt.unwrap_or(0.0);
+    let value_per_share = inputs.shares_outstanding.filter(|s| *s > 0.0).map(|shares| equity_value / shares);
+
+    Ok(DcfResult {
+        base_cash_flow,
+        base_source,
+        projections,
+        terminal_value,
+        terminal_present_value,
+        real_terminal_value,
+        enterprise_value,
+        equity_value,
+        value_per_share,
+    })
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn run_dcf(app: AppHandle, inputs: DcfInputs) -> Result<DcfResult, String> {
+    run(&app, &inputs)
+}
@@ -0,0 +1,386 @@
+// Derived Tables - ratios per period and common-size statements kept precomputed in
+// extracted_data.db. Triggers mark a document stale whenever its items or metadata change,
+// whether Rust or the Python pipeline wrote them, and stale documents are recomputed before
+// anything reads them, so views query stored results instead of recomputing on every load
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+use crate::db;
+use crate::duplicates;
+use crate::periods::{self, Period};
+use crate::ratio_alerts;
+use crate::ratios::{self, Ratio};
+use crate::statements;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS derived_state (
+    doc_id INTEGER PRIMARY KEY,
+    filename TEXT NOT NULL,
+    company TEXT,
+    current_period TEXT,
+    previous_period TEXT,
+    stale INTEGER NOT NULL DEFAULT 0,
+    computed_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS derived_ratios (
+    doc_id INTEGER NOT NULL,
+    ratio_key TEXT NOT NULL,
+    name TEXT NOT NULL,
+    category TEXT NOT NULL,
+    unit TEXT NOT NULL,
+    period TEXT NOT NULL,          -- 'FY2024', 'Q3-FY24'; 'current'/'previous' when undetected
+    value REAL NOT NULL,
+    PRIMARY KEY (doc_id, ratio_key, period)
+);
+CREATE TABLE IF NOT EXISTS derived_common_size (
+    doc_id INTEGER NOT NULL,
+    item_id TEXT NOT NULL,
+    row_index INTEGER NOT NULL,
+    statement TEXT NOT NULL,       -- 'income_statement', 'balance_sheet'
+    label TEXT NOT NULL,
+    base TEXT NOT NULL,            -- 'revenue', 'total_assets'
+    value_current REAL,
+    value_previous REAL,
+    percent_current REAL,
+    percent_previous REAL,
+    PRIMARY KEY (doc_id, item_id)
+);
+CREATE TRIGGER IF NOT EXISTS derived_stale_item_insert AFTER INSERT ON financial_items
+BEGIN
+    UPDATE derived_state SET stale = 1 WHERE doc_id = NEW.doc_id;
+END;
+CREATE TRIGGER IF NOT EXISTS derived_stale_item_update AFTER UPDATE ON financial_items
+BEGIN
+    UPDATE derived_state SET stale = 1 WHERE doc_id IN (OLD.doc_id, NEW.doc_id);
+END;
+CREATE TRIGGER IF NOT EXISTS derived_stale_item_delete AFTER DELETE ON financial_items
+BEGIN
+    UPDATE derived_state SET stale = 1 WHERE doc_id = OLD.doc_id;
+END;
+CREATE TRIGGER IF NOT EXISTS derived_stale_document_update AFTER UPDATE ON documents
+BEGIN
+    UPDATE derived_state SET stale = 1 WHERE doc_id = NEW.id;
+END;
+CREATE TRIGGER IF NOT EXISTS derived_document_delete AFTER DELETE ON documents
+BEGIN
+    DELETE FROM derived_ratios WHERE doc_id = OLD.id;
+    DELETE FROM derived_common_size WHERE doc_id = OLD.id;
+    DELETE FROM derived_state WHERE doc_id = OLD.id;
+END;
+";
+
+/// A document's derived ratios with the periods they're keyed by.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DerivedDocument {
+    pub document_id: i64,
+    pub filename: String,
+    pub company: Option<String>,
+    pub current_period: Option<String>,
+    pub previous_period: Option<String>,
+    /// Current and previous values, the user's formula metrics included
+    pub ratios: Vec<Ratio>,
+    pub computed_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatioPoint {
+    pub key: String,
+    pub name: String,
+    pub category: String,
+    pub unit: String,
+    pub period: String,
+    pub value: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommonSizeLine {
+    pub item_id: String,
+    pub statement: String,
+    pub label: String,
+    /// What the percentages are of: revenue or total assets
+    pub base: String,
+    pub value_current: Option<f64>,
+    pub value_previous: Option<f64>,
+    pub percent_current: Option<f64>,
+    pub percent_previous: Option<f64>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn open(app: &AppHandle) -> Result<Connection, String> {
+    let conn = db::open_pipeline_db(app)?;
+    conn.execute_batch(statements::PIPELINE_SCHEMA).map_err(|e| e.to_string())?;
+    conn.execute_batch(SCHEMA).map_err(|e| e.to_string())?;
+    Ok(conn)
+}
+
+fn percent(value: Option<f64>, base: f64) -> Option<f64> {
+    value.filter(|_| base != 0.0).map(|value| value / base * 100.0)
+}
+
+/// Income statement lines as a percentage of revenue, balance sheet lines of total assets.
+fn common_size(items: &[serde_json::Value]) -> Vec<(i64, CommonSizeLine)> {
+    let revenue = ratios::mapped(items, "revenue");
+    let total_assets = ratios::mapped(items, "total_assets");
+    items.iter().enumerate()
+        .filter(|(_, item)| !item["isHeader"].as_bool().unwrap_or(false))
+        .filter_map(|(index, item)| {
+            let statement = ratios::statement_of(item);
+            let (base, (base_current, base_previous)) = match statement.as_str() {
+                "income_statement" => ("revenue", revenue?),
+                "balance_sheet" => ("total_assets", total_assets?),
+                _ => return None,
+            };
+            let (current, previous) = periods::amounts(item);
+            Some((item["rowIndex"].as_i64().unwrap_or(index as i64), CommonSizeLine {
+                item_id: item["id"].as_str().map(str::to_string).unwrap_or_else(|| index.to_string()),
+                statement,
+                label: item["label"].as_str().unwrap_or("").to_string(),
+                base: base.to_string(),
+                value_current: current,
+                value_previous: previous,
+                percent_current: percent(current, base_current),
+                percent_previous: percent(previous, base_previous),
+            }))
+        })
+        .collect()
+}
+
+/// Recompute everything derived from one document and mark it fresh.
+fn compute(app: &AppHandle, conn: &mut Connection, doc_id: i64) -> Result<(), String> {
+    let document = statements::load_document(app, doc_id)?;
+    let resolved = periods::resolve(app, &document)?;
+    let current = resolved.current.map(|p| p.to_string());
+    let previous = resolved.previous.map(|p| p.to_string());
+    let values = ratios::for_document(&document);
+    let series = ratio_alerts::document_series(&document);
+    let lines = common_size(&document.items);
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM derived_ratios WHERE doc_id = ?1", params![doc_id]).map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM derived_common_size WHERE doc_id = ?1", params![doc_id]).map_err(|e| e.to_string())?;
+
+    let insert = |ratio: &Ratio, period: &str, value: f64| {
+        tx.execute(
+            "INSERT OR REPLACE INTO derived_ratios (doc_id, ratio_key, name, category, unit, period, value)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![doc_id, ratio.key, ratio.name, ratio.category, ratio.unit, period, value],
+        ).map(|_| ()).map_err(|e| e.to_string())
+    };
+    for ratio in &values {
+        if let Some(value) = ratio.current {
+            insert(ratio, current.as_deref().unwrap_or("current"), value)?;
+        }
+        if let Some(value) = ratio.previous {
+            insert(ratio, previous.as_deref().unwrap_or("previous"), value)?;
+        }
+    }
+    // Every other period the items carry, for the built-in ratios
+    for (key, points) in &series {
+        let Some(ratio) = values.iter().find(|ratio| ratio.key == *key) else { continue };
+        for (period, value) in points {
+            insert(ratio, &period.to_string(), *value)?;
+        }
+    }
+
+    for (row_index, line) in &lines {
+        tx.execute(
+            "INSERT OR REPLACE INTO derived_common_size (doc_id, item_id, row_index, statement, label, base,
+                 value_current, value_previous, percent_current, percent_previous)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![
+                doc_id, line.item_id, row_index, line.statement, line.label, line.base,
+                line.value_current, line.value_previous, line.percent_current, line.percent_previous,
+            ],
+        ).map_err(|e| e.to_string())?;
+    }
+    tx.execute(
+        "INSERT OR REPLACE INTO derived_state (doc_id, filename, company, current_period, previous_period, stale, computed_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, 0, ?6)",
+        params![doc_id, document.filename, duplicates::company(&document), current, previous, now_secs()],
+    ).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Recompute `doc_id` now, or every document that's stale or never computed. Returns how many
+/// were recomputed.
+pub fn refresh(app: &AppHandle, doc_id: Option<i64>) -> Result<usize, String> {
+    let mut conn = open(app)?;
+    let due: Vec<i64> = match doc_id {
+        Some(doc_id) => vec![doc_id],
+        None => {
+            let mut stmt = conn.prepare(
+                "SELECT id FROM documents WHERE id NOT IN (SELECT doc_id FROM derived_state WHERE stale = 0) ORDER BY id"
+            ).map_err(|e| e.to_string())?;
+            let ids = stmt.query_map([], |row| row.get(0)).map_err(|e| e.to_string())?;
+            ids.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?
+        }
+    };
+    let mut refreshed = 0;
+    for id in &due {
+        match compute(app, &mut conn, *id) {
+            Ok(()) => refreshed += 1,
+            // One unreadable document shouldn't hold up the others
+            Err(e) if doc_id.is_none() => warn!(document_id = id, "Failed to compute derived tables: {}", e),
+            Err(e) => return Err(e),
+        }
+    }
+    if refreshed > 0 {
+        info!(documents = refreshed, "Refreshed derived tables");
+    }
+    Ok(refreshed)
+}
+
+/// After a document is stored; failures are logged, the next read retries.
+pub fn document_stored(app: &AppHandle, doc_id: i64) {
+    if let Err(e) = refresh(app, Some(doc_id)) {
+        warn!(document_id = doc_id, "Failed to refresh derived tables: {}", e);
+    }
+}
+
+/// For changes the triggers can't see (period and unit overrides, formulas): `doc_id`, or every
+/// document, is recomputed on its next read.
+pub fn mark_stale(app: &AppHandle, doc_id: Option<i64>) {
+    let result = open(app).and_then(|conn| {
+        conn.execute("UPDATE derived_state SET stale = 1 WHERE ?1 IS NULL OR doc_id = ?1", params![doc_id])
+            .map_err(|e| e.to_string())
+    });
+    if let Err(e) = result {
+        warn!("Failed to mark derived tables stale: {}", e);
+    }
+}
+
+/// The document's precomputed ratios at its current and previous periods.
+pub fn document(app: &AppHandle, doc_id: i64) -> Result<DerivedDocument, String> {
+    refresh(app, None)?;
+    let conn = open(app)?;
+    let (filename, company, current_period, previous_period, computed_at) = conn.query_row(
+        "SELECT filename, company, current_period, previous_period, computed_at FROM derived_state WHERE doc_id = ?1",
+        params![doc_id],
+        |row| Ok((row.get::<_, String>(0)?, row.get(1)?, row.get::<_, Option<String>>(2)?, row.get::<_, Option<String>>(3)?, row.get(4)?)),
+    ).optional().map_err(|e| e.to_string())?.ok_or_else(|| format!("Document {} not found", doc_id))?;
+
+    let current = current_period.clone().unwrap_or_else(|| "current".to_string());
+    let previous = previous_period.clone().unwrap_or_else(|| "previous".to_string());
+    let mut ratios: Vec<Ratio> = Vec::new();
+    for point in load_points(&conn, doc_id)? {
+        if point.period != current && point.period != previous {
+            continue;
+        }
+        let index = match ratios.iter().position(|ratio| ratio.key == point.key) {
+            Some(index) => index,
+            None => {
+                ratios.push(Ratio { key: point.key, name: point.name, category: point.category, unit: point.unit, current: None, previous: None });
+                ratios.len() - 1
+            }
+        };
+        if point.period == current {
+            ratios[index].current = Some(point.value);
+        } else {
+            ratios[index].previous = Some(point.value);
+        }
+    }
+    Ok(DerivedDocument { document_id: doc_id, filename, company, current_period, previous_period, ratios, computed_at })
+}
+
+fn load_points(conn: &Connection, doc_id: i64) -> Result<Vec<RatioPoint>, String> {
+    let mut stmt = conn.prepare(
+        "SELECT ratio_key, name, category, unit, period, value FROM derived_ratios WHERE doc_id = ?1 ORDER BY rowid"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![doc_id], |row| {
+        Ok(RatioPoint {
+            key: row.get(0)?,
+            name: row.get(1)?,
+            category: row.get(2)?,
+            unit: row.get(3)?,
+            period: row.get(4)?,
+            value: row.get(5)?,
+        })
+    }).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// `ratio_alerts::company_series` from the derived tables: every ratio at every period across
+/// the company's documents, later documents winning where they restate a period.
+pub fn company_series(app: &AppHandle, doc_id: i64) -> Result<BTreeMap<String, BTreeMap<Period, f64>>, String> {
+    refresh(app, None)?;
+    let conn = open(app)?;
+    let mut stmt = conn.prepare(
+        "SELECT r.ratio_key, r.period, r.value FROM derived_ratios r JOIN derived_state s ON s.doc_id = r.doc_id
+         WHERE r.doc_id = ?1 OR s.company = (SELECT company FROM derived_state WHERE doc_id = ?1)
+         ORDER BY r.doc_id"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![doc_id], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, f64>(2)?)))
+        .map_err(|e| e.to_string())?;
+
+    let mut series: BTreeMap<String, BTreeMap<Period, f64>> = BTreeMap::new();
+    for row in rows {
+        let (key, period, value) = row.map_err(|e| e.to_string())?;
+        if let Some(period) = Period::parse(&period) {
+            series.entry(key).or_default().insert(period, value);
+        }
+    }
+    Ok(series)
+}
+
+// Tauri Commands
+/// Every ratio at every period of a document, oldest period first within each ratio.
+#[tauri::command]
+pub fn get_ratio_table(app: AppHandle, document_id: i64) -> Result<Vec<RatioPoint>, String> {
+    refresh(&app, None)?;
+    let conn = open(&app)?;
+    let mut points = load_points(&conn, document_id)?;
+    points.sort_by(|a, b| {
+        a.key.cmp(&b.key).then_with(|| match (Period::parse(&a.period), Period::parse(&b.period)) {
+            (Some(x), Some(y)) => x.cmp(&y),
+            _ => b.period.cmp(&a.period),
+        })
+    });
+    Ok(points)
+}
+
+/// The document's income statement as a percentage of revenue and balance sheet as a
+/// percentage of total assets, in document order; `statement` picks one.
+#[tauri::command]
+pub fn get_common_size(app: AppHandle, document_id: i64, statement: Option<String>) -> Result<Vec<CommonSizeLine>, String> {
+    refresh(&app, None)?;
+    let conn = open(&app)?;
+    let mut stmt = conn.prepare(
+        "SELECT item_id, statement, label, base, value_current, value_previous, percent_current, percent_previous
+         FROM derived_common_size WHERE doc_id = ?1 AND (?2 IS NULL OR statement = ?2) ORDER BY row_index, rowid"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![document_id, statement.map(|s| s.trim().to_lowercase())], |row| {
+        Ok(CommonSizeLine {
+            item_id: row.get(0)?,
+            statement: row.get(1)?,
+            label: row.get(2)?,
+            base: row.get(3)?,
+            value_current: row.get(4)?,
+            value_previous: row.get(5)?,
+            percent_current: row.get(6)?,
+            percent_previous: row.get(7)?,
+        })
+    }).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Recompute a document's derived tables, or every document's, regardless of staleness.
+#[tauri::command]
+pub fn refresh_derived_tables(app: AppHandle, document_id: Option<i64>) -> Result<usize, String> {
+    if document_id.is_none() {
+        mark_stale(&app, None);
+    }
+    refresh(&app, document_id)
+}
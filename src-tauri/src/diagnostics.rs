@@ -0,0 +1,178 @@
+// Diagnostics - crash reports written by a panic hook, and a zip bundle users can attach to issues
+use serde_json::Value;
+use std::fs;
+use std::io::Write;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use zip::write::SimpleFileOptions;
+
+use crate::jobs::JobManager;
+use crate::logging;
+use crate::settings::SettingsState;
+use crate::tasks::TaskRegistry;
+
+const CRASH_LOG_LINES: usize = 200;
+pub const REDACTED: &str = "[redacted]";
+// Settings whose name contains one of these hold credentials (apiKeys, anonKey, tokens, proxy logins...)
+const SECRET_MARKERS: [&str; 7] = ["key", "token", "secret", "password", "passphrase", "credential", "proxy"];
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn data_subdir(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?.join(name);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+fn is_secret(key: &str) -> bool {
+    let key = key.to_lowercase();
+    SECRET_MARKERS.iter().any(|marker| key.contains(marker))
+}
+
+/// Blank every non-empty string under a credential-like key, however deep.
+pub fn redact(value: Value, secret: bool) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    let secret = secret || is_secret(&key);
+                    (key, redact(value, secret))
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(|v| redact(v, secret)).collect()),
+        Value::String(text) if secret && !text.is_empty() => Value::String(REDACTED.to_string()),
+        other => other,
+    }
+}
+
+/// Settings with secrets removed. `try_read` because a panic may happen while the store is being written.
+fn redacted_settings(app: &AppHandle) -> Value {
+    let settings = app.try_state::<SettingsState>()
+        .and_then(|state| state.try_read().ok().and_then(|store| serde_json::to_value(store.get()).ok()))
+        .unwrap_or(Value::Null);
+    redact(settings, false)
+}
+
+fn system_info(app: &AppHandle) -> Value {
+    serde_json::json!({
+        "appVersion": app.package_info().version.to_string(),
+        "os": std::env::consts::OS,
+        "family": std::env::consts::FAMILY,
+        "arch": std::env::consts::ARCH,
+        "generatedAt": chrono::Local::now().to_rfc3339(),
+    })
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    let payload = info.payload();
+    payload.downcast_ref::<&str>().map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".to_string())
+}
+
+fn write_crash_report(app: &AppHandle, info: &PanicHookInfo<'_>) -> Result<PathBuf, String> {
+    let thread = std::thread::current();
+    let location = info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column())).unwrap_or_default();
+    let log_lines = logging::log_dir(app)
+        .map(|dir| logging::tail_lines(&dir, CRASH_LOG_LINES))
+        .unwrap_or_default();
+
+    let report = format!(
+        "Financial Calculator crash report\n\
+         Time: {}\nVersion: {}\nOS: {} {}\nThread: {}\nPanic: {}\nLocation: {}\n\n\
+         Backtrace:\n{}\n\n\
+         Settings (secrets redacted):\n{}\n\n\
+         Last {} log lines:\n{}\n",
+        chrono::Local::now().to_rfc3339(),
+        app.package_info().version,
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        thread.name().unwrap_or("unnamed"),
+        panic_message(info),
+        location,
+        std::backtrace::Backtrace::force_capture(),
+        serde_json::to_string_pretty(&redacted_settings(app)).unwrap_or_default(),
+        log_lines.len(),
+        log_lines.join("\n"),
+    );
+
+    let path = data_subdir(app, "crash-reports")?.join(format!("crash-{}.txt", now_secs()));
+    fs::write(&path, report).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+/// Write a crash report for every panic, then fall through to the default hook.
+pub fn install_panic_hook(app: &AppHandle) {
+    let app = app.clone();
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        match write_crash_report(&app, info) {
+            Ok(path) => {
+                tracing::error!("Panic: {} (crash report {})", panic_message(info), path.display());
+                eprintln!("Crash report written to {}", path.display());
+            }
+            Err(e) => eprintln!("Failed to write crash report: {}", e),
+        }
+        default_hook(info);
+    }));
+}
+
+pub fn add_file<W: Write + std::io::Seek>(zip: &mut zip::ZipWriter<W>, name: &str, contents: &[u8]) -> Result<(), String> {
+    zip.start_file(name, SimpleFileOptions::default()).map_err(|e| e.to_string())?;
+    zip.write_all(contents).map_err(|e| e.to_string())
+}
+
+fn add_dir<W: Write + std::io::Seek>(zip: &mut zip::ZipWriter<W>, prefix: &str, files: &[PathBuf]) -> Result<(), String> {
+    for path in files {
+        let Some(name) = path.file_name().map(|n| n.to_string_lossy().to_string()) else { continue };
+        // A log file being written right now can still be read; skip anything that can't
+        if let Ok(contents) = fs::read(path) {
+            add_file(zip, &format!("{}/{}", prefix, name), &contents)?;
+        }
+    }
+    Ok(())
+}
+
+fn files_in(dir: &Path) -> Vec<PathBuf> {
+    fs::read_dir(dir)
+        .map(|entries| entries.filter_map(|e| e.ok().map(|e| e.path())).filter(|p| p.is_file()).collect())
+        .unwrap_or_default()
+}
+
+// Tauri Commands
+/// Zip system info, redacted settings, job/task state, logs and crash reports.
+/// Without `path` the bundle goes to the app data `diagnostics` folder.
+#[tauri::command]
+pub fn create_diagnostics_bundle(app: AppHandle, path: Option<String>) -> Result<String, String> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => data_subdir(&app, "diagnostics")?.join(format!("diagnostics-{}.zip", now_secs())),
+    };
+    let file = fs::File::create(&path).map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+
+    let pretty = |value: &Value| serde_json::to_vec_pretty(value).unwrap_or_default();
+    add_file(&mut zip, "system.json", &pretty(&system_info(&app)))?;
+    add_file(&mut zip, "settings.json", &pretty(&redacted_settings(&app)))?;
+    let jobs = app.try_state::<JobManager>().map(|manager| manager.list()).unwrap_or_default();
+    add_file(&mut zip, "jobs.json", &pretty(&serde_json::to_value(jobs).unwrap_or_default()))?;
+    let tasks = app.try_state::<TaskRegistry>().map(|registry| registry.list()).unwrap_or_default();
+    add_file(&mut zip, "tasks.json", &pretty(&serde_json::to_value(tasks).unwrap_or_default()))?;
+
+    if let Ok(dir) = logging::log_dir(&app) {
+        add_dir(&mut zip, "logs", &logging::log_files(&dir))?;
+    }
+    add_dir(&mut zip, "crash-reports", &files_in(&data_subdir(&app, "crash-reports")?))?;
+
+    zip.finish().map_err(|e| e.to_string())?;
+    tracing::info!("Diagnostics bundle written to {}", path.display());
+    Ok(path.to_string_lossy().to_string())
+}
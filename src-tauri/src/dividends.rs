@@ -0,0 +1,347 @@
+// Dividends - per-share dividends entered by hand or taken from NSE's corporate actions, paid
+// out on the shares the transactions ledger held before each ex-date, with income per period,
+// yield on cost and projected annual income for the portfolio
+use chrono::{Datelike, Duration as ChronoDuration, Local, NaiveDate};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+use tracing::info;
+
+use crate::corporate_actions;
+use crate::corporate_events::normalize_date;
+use crate::db;
+use crate::portfolio;
+use crate::scraper;
+use crate::settings;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS dividends (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    ex_date TEXT NOT NULL,         -- YYYY-MM-DD
+    payment_date TEXT,
+    amount_per_share REAL NOT NULL,
+    quantity REAL,                 -- NULL: the shares held before the ex-date
+    description TEXT,
+    source TEXT NOT NULL,          -- 'manual', 'nse'
+    created_at INTEGER NOT NULL,
+    UNIQUE (symbol, exchange, ex_date, amount_per_share)
+);
+";
+
+// Trailing window whose dividends are projected forward a year
+const TRAILING_DAYS: i64 = 365;
+// Quotes younger than this are used for current yield without re-fetching
+const QUOTE_MAX_AGE_SECS: u64 = 300;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DividendInput {
+    pub symbol: String,
+    pub exchange: Option<String>,
+    pub ex_date: String,
+    pub payment_date: Option<String>,
+    pub amount_per_share: f64,
+    /// Shares paid on, when the ledger doesn't hold the position
+    pub quantity: Option<f64>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Dividend {
+    pub id: i64,
+    pub symbol: String,
+    pub exchange: String,
+    pub ex_date: String,
+    pub payment_date: Option<String>,
+    pub amount_per_share: f64,
+    /// As entered, or held per the ledger before the ex-date
+    pub quantity: f64,
+    pub amount: f64,
+    pub description: Option<String>,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DividendPeriod {
+    /// "2026-10", "2026-Q3", "2026" or "FY2026-27"
+    pub period: String,
+    pub amount: f64,
+    pub payments: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoldingYield {
+    pub symbol: String,
+    pub exchange: String,
+    pub quantity: f64,
+    pub average_cost: f64,
+    /// Per share, ex-dates in the last year
+    pub trailing_dividend: f64,
+    /// Percent of average cost
+    pub yield_on_cost: Option<f64>,
+    pub last_price: Option<f64>,
+    pub current_yield: Option<f64>,
+    pub projected_income: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DividendSummary {
+    pub holdings: Vec<HoldingYield>,
+    pub invested: f64,
+    pub trailing_income: f64,
+    pub projected_annual_income: f64,
+    pub yield_on_cost: Option<f64>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn parse_date(raw: &str) -> Result<String, String> {
+    normalize_date(raw).ok_or_else(|| format!("Invalid date: {}", raw))
+}
+
+fn load_dividends(conn: &Connection, symbol: Option<&str>) -> Result<Vec<Dividend>, String> {
+    let transactions = portfolio::load_transactions(conn, symbol, None)?;
+    let adjustments = corporate_actions::load_adjustments(conn)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, symbol, exchange, ex_date, payment_date, amount_per_share, quantity, description, source
+         FROM dividends WHERE ?1 IS NULL OR symbol = ?1 ORDER BY ex_date DESC, symbol"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![symbol], |row| {
+        Ok((
+            row.get::<_, i64>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, Option<String>>(4)?,
+            row.get::<_, f64>(5)?,
+            row.get::<_, Option<f64>>(6)?,
+            row.get::<_, Option<String>>(7)?,
+            row.get::<_, String>(8)?,
+        ))
+    }).map_err(|e| e.to_string())?;
+
+    let mut dividends = Vec::new();
+    for row in rows {
+        let (id, symbol, exchange, ex_date, payment_date, amount_per_share, quantity, description, source) = row.map_err(|e| e.to_string())?;
+        let quantity = quantity.unwrap_or_else(|| portfolio::quantity_held(&transactions, &adjustments, &symbol, &exchange, &ex_date));
+        dividends.push(Dividend {
+            id,
+            amount: amount_per_share * quantity,
+            symbol,
+            exchange,
+            ex_date,
+            payment_date,
+            amount_per_share,
+            quantity,
+            description,
+            source,
+        });
+    }
+    Ok(dividends)
+}
+
+pub fn period_key(date: NaiveDate, period: &str) -> Result<String, String> {
+    Ok(match period {
+        "month" => date.format("%Y-%m").to_string(),
+        "quarter" => format!("{}-Q{}", date.year(), (date.month() - 1) / 3 + 1),
+        "year" => date.year().to_string(),
+        // Indian financial year, April to March
+        "fy" => {
+            let start = if date.month() >= 4 { date.year() } else { date.year() - 1 };
+            format!("FY{}-{:02}", start, (start + 1) % 100)
+        }
+        other => return Err(format!("Unsupported period: {} (expected month, quarter, year or fy)", other)),
+    })
+}
+
+/// Rupees per share in an NSE corporate-action subject; "Interim Dividend - Rs 3 Per Share /
+/// Special Dividend - Rs 2 Per Share" is 5. None when it isn't a dividend.
+fn dividend_per_share(subject: &str) -> Option<f64> {
+    let lower = subject.to_lowercase();
+    let total: f64 = lower.split('/')
+        .filter(|part| part.contains("dividend"))
+        .filter_map(|part| corporate_actions::rupee_amounts(part).first().copied())
+        .sum();
+    (total > 0.0).then_some(total)
+}
+
+/// Dividends from NSE's corporate actions for `symbol`.
+async fn fetch_nse_dividends(app: &AppHandle, symbol: &str) -> Result<Vec<DividendInput>, String> {
+    Ok(corporate_actions::fetch_nse_actions(app, symbol).await?.into_iter()
+        .filter_map(|(subject, ex_date)| Some(DividendInput {
+            symbol: symbol.to_string(),
+            exchange: Some("NSE".to_string()),
+            ex_date,
+            payment_date: None,
+            amount_per_share: dividend_per_share(&subject)?,
+            quantity: None,
+            description: Some(subject),
+        }))
+        .collect())
+}
+
+fn insert(conn: &Connection, input: &DividendInput, source: &str) -> rusqlite::Result<usize> {
+    conn.execute(
+        "INSERT OR IGNORE INTO dividends (symbol, exchange, ex_date, payment_date, amount_per_share, quantity, description, source, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            input.symbol, input.exchange.as_deref().unwrap_or("NSE"), input.ex_date, input.payment_date,
+            input.amount_per_share, input.quantity, input.description, source, now_secs(),
+        ],
+    )
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn add_dividend(app: AppHandle, input: DividendInput) -> Result<Dividend, String> {
+    if !input.amount_per_share.is_finite() || input.amount_per_share <= 0.0 {
+        return Err("Dividend per share must be positive".to_string());
+    }
+    let input = DividendInput {
+        symbol: input.symbol.trim().to_uppercase(),
+        exchange: Some(input.exchange.map(|e| e.trim().to_uppercase()).filter(|e| !e.is_empty()).unwrap_or_else(|| "NSE".to_string())),
+        ex_date: parse_date(&input.ex_date)?,
+        payment_date: input.payment_date.as_deref().map(parse_date).transpose()?,
+        ..input
+    };
+    if input.symbol.is_empty() {
+        return Err("Symbol is required".to_string());
+    }
+    let conn = db::open_app_db(&app)?;
+    if insert(&conn, &input, "manual").map_err(|e| e.to_string())? == 0 {
+        return Err(format!("A {} dividend of {} on {} is already recorded", input.symbol, input.amount_per_share, input.ex_date));
+    }
+    let id = conn.last_insert_rowid();
+    load_dividends(&conn, Some(&input.symbol))?.into_iter()
+        .find(|dividend| dividend.id == id)
+        .ok_or_else(|| "Dividend not found after saving".to_string())
+}
+
+#[tauri::command]
+pub fn delete_dividend(app: AppHandle, id: i64) -> Result<(), String> {
+    let conn = db::open_app_db(&app)?;
+    conn.execute("DELETE FROM dividends WHERE id = ?1", params![id]).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Recorded dividends, newest ex-date first, with what each paid on the shares held.
+#[tauri::command]
+pub fn list_dividends(app: AppHandle, symbol: Option<String>) -> Result<Vec<Dividend>, String> {
+    let conn = db::open_app_db(&app)?;
+    let symbol = symbol.map(|s| s.trim().to_uppercase());
+    load_dividends(&conn, symbol.as_deref())
+}
+
+/// Record NSE's announced dividends for `symbol`, or for every NSE stock the ledger has traded.
+/// Returns how many weren't already recorded.
+#[tauri::command]
+pub async fn fetch_dividends(app: AppHandle, symbol: Option<String>) -> Result<usize, String> {
+    settings::ensure_online(&app)?;
+    let symbols: Vec<String> = match symbol {
+        Some(symbol) => vec![symbol.trim().to_uppercase()],
+        None => {
+            let conn = db::open_app_db(&app)?;
+            portfolio::load_holdings(&conn, true)?.into_iter()
+                .filter(|holding| holding.exchange == "NSE")
+                .map(|holding| holding.symbol)
+                .collect()
+        }
+    };
+
+    let mut added = 0;
+    for symbol in &symbols {
+        let dividends = fetch_nse_dividends(&app, symbol).await?;
+        let conn = db::open_app_db(&app)?;
+        for dividend in &dividends {
+            added += insert(&conn, dividend, "nse").map_err(|e| e.to_string())?;
+        }
+    }
+    info!(symbols = symbols.len(), added, "Fetched dividends");
+    Ok(added)
+}
+
+/// Dividend income bucketed by `period` ("month", "quarter", "year" or "fy", default "fy"),
+/// dated by payment date where known, else ex-date, optionally within `from`..=`to`.
+#[tauri::command]
+pub fn get_dividend_income(
+    app: AppHandle,
+    period: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Vec<DividendPeriod>, String> {
+    let period = period.unwrap_or_else(|| "fy".to_string()).to_lowercase();
+    let from = from.as_deref().map(parse_date).transpose()?;
+    let to = to.as_deref().map(parse_date).transpose()?;
+    let conn = db::open_app_db(&app)?;
+
+    let mut periods: BTreeMap<String, DividendPeriod> = BTreeMap::new();
+    for dividend in load_dividends(&conn, None)?.into_iter().filter(|d| d.amount > 0.0) {
+        let date = dividend.payment_date.clone().unwrap_or(dividend.ex_date);
+        if from.as_ref().is_some_and(|from| date < *from) || to.as_ref().is_some_and(|to| date > *to) {
+            continue;
+        }
+        let day = NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| e.to_string())?;
+        let key = period_key(day, &period)?;
+        let entry = periods.entry(key.clone()).or_insert(DividendPeriod { period: key, amount: 0.0, payments: 0 });
+        entry.amount += dividend.amount;
+        entry.payments += 1;
+    }
+    Ok(periods.into_values().collect())
+}
+
+/// Yield on cost and current yield per open holding from the last year's dividends, and the
+/// income they'd pay over the next year at the current quantity.
+#[tauri::command]
+pub async fn get_dividend_yield(app: AppHandle, max_quote_age_secs: Option<u64>) -> Result<DividendSummary, String> {
+    let (holdings, dividends) = {
+        let conn = db::open_app_db(&app)?;
+        (portfolio::load_holdings(&conn, false)?, load_dividends(&conn, None)?)
+    };
+    let since = (Local::now().date_naive() - ChronoDuration::days(TRAILING_DAYS)).format("%Y-%m-%d").to_string();
+    let trailing: Vec<&Dividend> = dividends.iter().filter(|d| d.ex_date >= since).collect();
+    let max_age = max_quote_age_secs.unwrap_or(QUOTE_MAX_AGE_SECS);
+
+    let mut yields = Vec::new();
+    for holding in holdings.into_iter().filter(|h| h.quantity > 0.0) {
+        let trailing_dividend: f64 = trailing.iter()
+            .filter(|d| d.symbol == holding.symbol && d.exchange == holding.exchange)
+            .map(|d| d.amount_per_share)
+            .sum();
+        let last_price = scraper::get_quote(&app, &holding.symbol, &holding.exchange, max_age).await.ok().and_then(|q| q.price);
+        yields.push(HoldingYield {
+            yield_on_cost: (holding.average_cost > 0.0).then(|| trailing_dividend / holding.average_cost * 100.0),
+            current_yield: last_price.filter(|p| *p > 0.0).map(|p| trailing_dividend / p * 100.0),
+            projected_income: trailing_dividend * holding.quantity,
+            symbol: holding.symbol,
+            exchange: holding.exchange,
+            quantity: holding.quantity,
+            average_cost: holding.average_cost,
+            trailing_dividend,
+            last_price,
+        });
+    }
+    yields.sort_by(|a, b| b.projected_income.total_cmp(&a.projected_income));
+
+    let invested: f64 = yields.iter().map(|h| h.quantity * h.average_cost).sum();
+    let projected_annual_income: f64 = yields.iter().map(|h| h.projected_income).sum();
+    Ok(DividendSummary {
+        trailing_income: trailing.iter().map(|d| d.amount).sum(),
+        yield_on_cost: (invested > 0.0).then(|| projected_annual_income / invested * 100.0),
+        holdings: yields,
+        invested,
+        projected_annual_income,
+    })
+}
@@ -0,0 +1,58 @@
+// Document locks - one analysis per document at a time, so two parses of the same file never
+// write into the pipeline DB side by side; the second waits and then usually hits the parse cache
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard};
+use tracing::info;
+
+use crate::error::AppError;
+use crate::tasks::TaskHandle;
+
+// How often a waiting analysis looks for cancellation
+const CANCEL_POLL: Duration = Duration::from_millis(250);
+
+/// Held for the whole analysis; dropping it lets the next one for the document start.
+pub type DocumentGuard = OwnedMutexGuard<()>;
+
+#[derive(Default)]
+pub struct DocumentLocks {
+    /// Document key (file hash, or path when it can't be hashed) -> its lock
+    locks: Mutex<HashMap<String, Arc<AsyncMutex<()>>>>,
+}
+
+impl DocumentLocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock_for(&self, key: &str) -> Arc<AsyncMutex<()>> {
+        let mut locks = self.locks.lock().unwrap_or_else(|e| e.into_inner());
+        // Nobody holds or waits for a lock only the map refers to
+        locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+        locks.entry(key.to_string()).or_default().clone()
+    }
+
+    /// Wait until no other analysis holds `key`; gives up if `task` is cancelled meanwhile.
+    pub async fn acquire(&self, key: &str, task: &TaskHandle) -> Result<DocumentGuard, AppError> {
+        let lock = self.lock_for(key);
+        if let Ok(guard) = lock.clone().try_lock_owned() {
+            return Ok(guard);
+        }
+        info!(document = key, "Waiting for another analysis of the same document");
+        task.progress(0.0, "Waiting for another analysis of this document");
+        let acquire = lock.lock_owned();
+        tokio::pin!(acquire);
+        let mut cancel_check = tokio::time::interval(CANCEL_POLL);
+        loop {
+            tokio::select! {
+                guard = &mut acquire => return Ok(guard),
+                _ = cancel_check.tick() => {
+                    if task.is_cancelled() {
+                        return Err(AppError::Cancelled("Analysis cancelled".to_string()));
+                    }
+                }
+            }
+        }
+    }
+}
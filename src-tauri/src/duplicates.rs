@@ -0,0 +1,244 @@
+// Duplicates - the same report stored twice, or a revised version of one, found by a hash of
+// the extracted figures or by company and period, and folded together on request
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn};
+
+use crate::periods::{self, Basis, Period};
+use crate::report;
+use crate::statements::{self, StoredDocument};
+use crate::units;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateKind {
+    /// The same figures, e.g. one report imported twice
+    Identical,
+    /// Same company, period and basis with other figures, e.g. a revised report
+    SamePeriod,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fingerprint {
+    /// blake3 of the extracted figures
+    pub content_hash: String,
+    pub item_count: usize,
+    pub company: Option<String>,
+    pub period: Option<Period>,
+    pub basis: Option<Basis>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateDocument {
+    pub document_id: i64,
+    pub filename: String,
+    pub processed_at: Option<String>,
+    pub kind: DuplicateKind,
+    pub company: Option<String>,
+    pub period: Option<Period>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeResult {
+    pub document_id: i64,
+    pub removed_document_id: i64,
+    /// Source items the target did not have
+    pub added_items: usize,
+    /// Year values filled in on items both had
+    pub filled_values: usize,
+}
+
+/// (statement, label, occurrence); repeated labels such as "Others" pair up in order.
+fn item_keys(items: &[serde_json::Value]) -> Vec<(String, String, usize)> {
+    let mut seen: HashMap<(String, String), usize> = HashMap::new();
+    items.iter()
+        .map(|item| {
+            let label = item["label"].as_str().unwrap_or("").split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase();
+            let key = (report::statement_of(item), label);
+            let occurrence = seen.entry(key.clone()).or_default();
+            *occurrence += 1;
+            (key.0, key.1, *occurrence)
+        })
+        .collect()
+}
+
+/// Over what was extracted rather than the filename or item ids, so the same report imported
+/// under another name, or from a spreadsheet of it, still matches.
+fn content_hash(document: &StoredDocument) -> String {
+    let mut hasher = blake3::Hasher::new();
+    for ((statement, label, _), item) in item_keys(&document.items).iter().zip(&document.items) {
+        let line = format!("{}|{}|{:?}|{:?}\n", statement, label, item["currentYear"].as_f64(), item["previousYear"].as_f64());
+        hasher.update(line.as_bytes());
+    }
+    hasher.finalize().to_hex().to_string()
+}
+
+/// Company name or symbol from the metadata, lowercased for comparison.
+pub fn company(document: &StoredDocument) -> Option<String> {
+    ["companyName", "symbol", "company"].iter()
+        .filter_map(|key| document.metadata[*key].as_str())
+        .map(|name| name.trim().to_lowercase())
+        .find(|name| !name.is_empty())
+}
+
+pub fn fingerprint(app: &AppHandle, document: &StoredDocument) -> Result<Fingerprint, String> {
+    let periods = periods::resolve(app, document)?;
+    Ok(Fingerprint {
+        content_hash: content_hash(document),
+        item_count: document.items.len(),
+        company: company(document),
+        period: periods.current,
+        basis: periods.basis,
+    })
+}
+
+fn duplicate_kind(a: &Fingerprint, b: &Fingerprint) -> Option<DuplicateKind> {
+    if a.item_count > 0 && a.content_hash == b.content_hash {
+        return Some(DuplicateKind::Identical);
+    }
+    let same_period = a.company.is_some() && a.company == b.company && a.period.is_some() && a.period == b.period && a.basis == b.basis;
+    same_period.then_some(DuplicateKind::SamePeriod)
+}
+
+/// Other stored documents that duplicate `document_id`, newest first.
+pub fn find(app: &AppHandle, document_id: i64) -> Result<Vec<DuplicateDocument>, String> {
+    let own = fingerprint(app, &statements::load_stored_document(app, document_id)?)?;
+    let mut duplicates = Vec::new();
+    for summary in statements::list_documents(app)? {
+        if summary.id == document_id {
+            continue;
+        }
+        let other = fingerprint(app, &statements::load_stored_document(app, summary.id)?)?;
+        if let Some(kind) = duplicate_kind(&own, &other) {
+            duplicates.push(DuplicateDocument {
+                document_id: summary.id,
+                filename: summary.filename,
+                processed_at: summary.processed_at,
+                kind,
+                company: other.company,
+                period: other.period,
+            });
+        }
+    }
+    Ok(duplicates)
+}
+
+/// Emit `duplicate-document` when a document just stored duplicates others, so the frontend
+/// can offer to merge or replace. Never fails the import that stored it.
+pub fn notify(app: &AppHandle, document_id: i64) {
+    match find(app, document_id) {
+        Ok(duplicates) if !duplicates.is_empty() => {
+            info!(document_id, duplicates = duplicates.len(), "Stored document duplicates others");
+            let _ = app.emit("duplicate-document", serde_json::json!({ "documentId": document_id, "duplicates": duplicates }));
+        }
+        Ok(_) => {}
+        Err(e) => warn!(document_id, "Duplicate check failed: {}", e),
+    }
+}
+
+/// Add `source` items `target` lacks, and fill in year values missing on items both have.
+/// Returns (items added, values filled).
+fn merge_items(target: &mut Vec<serde_json::Value>, source: &[serde_json::Value]) -> (usize, usize) {
+    let index: HashMap<(String, String, usize), usize> = item_keys(target).into_iter().enumerate().map(|(i, key)| (key, i)).collect();
+    let mut ids: HashSet<String> = target.iter().filter_map(|item| item["id"].as_str().map(str::to_string)).collect();
+    let (mut added, mut filled) = (0, 0);
+    for (key, item) in item_keys(source).into_iter().zip(source) {
+        let Some(&i) = index.get(&key) else {
+            let mut item = item.clone();
+            // Both documents number their items the same way
+            if let Some(id) = item["id"].as_str().map(str::to_string).filter(|id| ids.contains(id)) {
+                item["id"] = format!("{}-merged", id).into();
+            }
+            if let Some(id) = item["id"].as_str() {
+                ids.insert(id.to_string());
+            }
+            target.push(item);
+            added += 1;
+            continue;
+        };
+        let Some(fields) = target[i].as_object_mut() else { continue };
+        for field in ["currentYear", "previousYear"] {
+            if fields.get(field).and_then(|v| v.as_f64()).is_none() {
+                if let Some(value) = item[field].as_f64() {
+                    fields.insert(field.to_string(), value.into());
+                    filled += 1;
+                }
+            }
+        }
+        if let Some(years) = item["allYears"].as_object() {
+            let target_years = fields.entry("allYears").or_insert_with(|| serde_json::json!({}));
+            if let Some(target_years) = target_years.as_object_mut() {
+                for (period, value) in years {
+                    if !target_years.contains_key(period) {
+                        target_years.insert(period.clone(), value.clone());
+                        filled += 1;
+                    }
+                }
+            }
+        }
+    }
+    (added, filled)
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn find_duplicate_documents(app: AppHandle, document_id: i64) -> Result<Vec<DuplicateDocument>, String> {
+    find(&app, document_id)
+}
+
+/// Fold `source_id` into `target_id` and remove it. The target keeps its own figures; the
+/// source only adds lines and years the target is missing, converted to the target's scale.
+#[tauri::command]
+pub fn merge_documents(app: AppHandle, target_id: i64, source_id: i64) -> Result<MergeResult, String> {
+    if target_id == source_id {
+        return Err("Cannot merge a document into itself".to_string());
+    }
+    let mut target = statements::load_stored_document(&app, target_id)?;
+    let mut source = statements::load_stored_document(&app, source_id)?;
+    let (target_units, source_units) = (units::resolve(&app, &target)?, units::resolve(&app, &source)?);
+    if !target_units.currency.eq_ignore_ascii_case(&source_units.currency) {
+        return Err(format!(
+            "The documents report in different currencies ({} and {}); correct their units first",
+            target_units.currency, source_units.currency
+        ));
+    }
+    if target_units.scale != source_units.scale {
+        units::rescale(&mut source, &source_units, &target_units);
+    }
+
+    let (added_items, filled_values) = merge_items(&mut target.items, &source.items);
+    if !target.metadata.is_object() {
+        target.metadata = serde_json::json!({});
+    }
+    let merged_from = target.metadata.as_object_mut().expect("metadata is an object")
+        .entry("mergedFrom")
+        .or_insert_with(|| serde_json::json!([]));
+    if let Some(merged_from) = merged_from.as_array_mut() {
+        merged_from.push(serde_json::json!({ "documentId": source_id, "filename": source.filename }));
+    }
+    statements::replace_document(&app, target_id, &target.filename, &target.metadata, &target.items)?;
+    statements::delete_document(&app, source_id)?;
+    info!(target_id, source_id, added_items, filled_values, "Merged documents");
+    Ok(MergeResult { document_id: target_id, removed_document_id: source_id, added_items, filled_values })
+}
+
+/// Put `replacement_id` (e.g. a revised report) in place of `document_id`: the document keeps
+/// its id, so snapshots, overrides and sync records still point at it, but takes the
+/// replacement's filename, metadata, items and text. The replacement is then removed.
+#[tauri::command]
+pub fn replace_document(app: AppHandle, document_id: i64, replacement_id: i64) -> Result<i64, String> {
+    if document_id == replacement_id {
+        return Err("Cannot replace a document with itself".to_string());
+    }
+    statements::load_stored_document(&app, document_id)?;
+    let replacement = statements::load_stored_document(&app, replacement_id)?;
+    statements::replace_document(&app, document_id, &replacement.filename, &replacement.metadata, &replacement.items)?;
+    statements::move_text_chunks(&app, replacement_id, document_id)?;
+    statements::delete_document(&app, replacement_id)?;
+    info!(document_id, replacement_id, "Replaced document");
+    Ok(document_id)
+}
@@ -0,0 +1,316 @@
+// EDGAR - SEC company lookup and 10-K/10-Q XBRL facts normalised into the items schema
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+
+use crate::activity::{self, ActivityKind};
+use crate::market_cache::MarketCache;
+use crate::scraper::{self, NativeScraper};
+use crate::settings;
+use crate::statements::{self, ImportedStatements, Series, StatementLayout, StatementLine};
+
+const SEC_TICKERS_URL: &str = "https://www.sec.gov/files/company_tickers.json";
+const SEC_FACTS_BASE: &str = "https://data.sec.gov/api/xbrl/companyfacts";
+
+// The ticker list changes rarely; refresh it daily
+const TICKERS_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Line key -> us-gaap concepts to try in order (filers pick different tags for the same figure).
+const CONCEPTS: &[(&str, &[&str])] = &[
+    ("Revenue", &["Revenues", "RevenueFromContractWithCustomerExcludingAssessedTax", "SalesRevenueNet"]),
+    ("CostOfRevenue", &["CostOfRevenue", "CostOfGoodsAndServicesSold"]),
+    ("GrossProfit", &["GrossProfit"]),
+    ("OperatingExpenses", &["OperatingExpenses"]),
+    ("OperatingIncome", &["OperatingIncomeLoss"]),
+    ("InterestExpense", &["InterestExpense"]),
+    ("Depreciation", &["DepreciationDepletionAndAmortization", "DepreciationAndAmortization"]),
+    ("PretaxIncome", &[
+        "IncomeLossFromContinuingOperationsBeforeIncomeTaxesExtraordinaryItemsNoncontrollingInterest",
+        "IncomeLossFromContinuingOperationsBeforeIncomeTaxesMinorityInterestAndIncomeLossFromEquityMethodInvestments",
+    ]),
+    ("IncomeTax", &["IncomeTaxExpenseBenefit"]),
+    ("NetIncome", &["NetIncomeLoss"]),
+    ("BasicEPS", &["EarningsPerShareBasic"]),
+    ("DilutedEPS", &["EarningsPerShareDiluted"]),
+    ("PPE", &["PropertyPlantAndEquipmentNet"]),
+    ("Goodwill", &["Goodwill"]),
+    ("Inventory", &["InventoryNet"]),
+    ("Receivables", &["AccountsReceivableNetCurrent"]),
+    ("Cash", &["CashAndCashEquivalentsAtCarryingValue"]),
+    ("CurrentAssets", &["AssetsCurrent"]),
+    ("TotalAssets", &["Assets"]),
+    ("Payables", &["AccountsPayableCurrent"]),
+    ("CurrentLiabilities", &["LiabilitiesCurrent"]),
+    ("LongTermDebt", &["LongTermDebtNoncurrent", "LongTermDebt"]),
+    ("TotalLiabilities", &["Liabilities"]),
+    ("RetainedEarnings", &["RetainedEarningsAccumulatedDeficit"]),
+    ("Equity", &["StockholdersEquity", "StockholdersEquityIncludingPortionAttributableToNoncontrollingInterest"]),
+    ("OperatingCashFlow", &["NetCashProvidedByUsedInOperatingActivities"]),
+    ("Capex", &["PaymentsToAcquirePropertyPlantAndEquipment"]),
+    ("InvestingCashFlow", &["NetCashProvidedByUsedInInvestingActivities"]),
+    ("Dividends", &["PaymentsOfDividends", "PaymentsOfDividendsCommonStock"]),
+    ("FinancingCashFlow", &["NetCashProvidedByUsedInFinancingActivities"]),
+];
+
+// Same labels as the listed-company import so metrics work identically
+const INCOME_LINES: &[StatementLine] = &[
+    ("Revenue", "Revenue from Operations", false),
+    ("CostOfRevenue", "Cost of Revenue", false),
+    ("GrossProfit", "Gross Profit", true),
+    ("OperatingExpenses", "Operating Expenses", false),
+    ("OperatingIncome", "Operating Profit", true),
+    ("InterestExpense", "Finance Costs", false),
+    ("Depreciation", "Depreciation and Amortisation Expense", false),
+    ("PretaxIncome", "Profit Before Tax", true),
+    ("IncomeTax", "Tax Expense", false),
+    ("NetIncome", "Profit After Tax", true),
+    ("BasicEPS", "Basic EPS", false),
+    ("DilutedEPS", "Diluted EPS", false),
+];
+
+const BALANCE_LINES: &[StatementLine] = &[
+    ("PPE", "Property, Plant and Equipment", false),
+    ("Goodwill", "Goodwill", false),
+    ("Inventory", "Inventories", false),
+    ("Receivables", "Trade Receivables", false),
+    ("Cash", "Cash and Cash Equivalents", false),
+    ("CurrentAssets", "Current Assets", true),
+    ("TotalAssets", "Total Assets", true),
+    ("Payables", "Trade Payables", false),
+    ("CurrentLiabilities", "Current Liabilities", true),
+    ("LongTermDebt", "Long Term Borrowings", false),
+    ("TotalLiabilities", "Total Liabilities", true),
+    ("RetainedEarnings", "Retained Earnings", false),
+    ("Equity", "Total Equity", true),
+];
+
+const CASH_LINES: &[StatementLine] = &[
+    ("OperatingCashFlow", "Net Cash from Operating Activities", true),
+    ("Capex", "Capital Expenditure", false),
+    ("InvestingCashFlow", "Net Cash used in Investing Activities", true),
+    ("Dividends", "Dividends Paid", false),
+    ("FinancingCashFlow", "Net Cash used in Financing Activities", true),
+];
+
+const STATEMENTS: &StatementLayout = &[
+    ("income_statement", INCOME_LINES),
+    ("balance_sheet", BALANCE_LINES),
+    ("cash_flow", CASH_LINES),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecCompany {
+    pub cik: String,   // zero-padded to 10 digits, as the data.sec.gov URLs expect
+    pub ticker: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Period {
+    Annual,
+    Quarterly,
+}
+
+async fn load_companies(app: &AppHandle, native: &NativeScraper, cache: &MarketCache) -> Result<Vec<SecCompany>, String> {
+    let raw = match cache.get(app, "SEC_TICKERS", "ALL", "SEC", TICKERS_TTL_SECS) {
+        Some(raw) => raw,
+        None => {
+            settings::ensure_online(app)?;
+            let raw = native.sec_get_json(SEC_TICKERS_URL, scraper::request_timeout(app)).await?;
+            cache.put(app, "SEC_TICKERS", "ALL", "SEC", &raw);
+            raw
+        }
+    };
+
+    // {"0": {"cik_str": 320193, "ticker": "AAPL", "title": "Apple Inc."}, ...}
+    let companies = raw.as_object()
+        .map(|entries| entries.values()
+            .filter_map(|c| Some(SecCompany {
+                cik: format!("{:010}", c.get("cik_str")?.as_u64()?),
+                ticker: c.get("ticker")?.as_str()?.to_uppercase(),
+                name: c.get("title")?.as_str()?.to_string(),
+            }))
+            .collect())
+        .unwrap_or_default();
+    Ok(companies)
+}
+
+/// Accepts a ticker ("AAPL") or a CIK with or without padding ("320193").
+async fn resolve_company(app: &AppHandle, native: &NativeScraper, cache: &MarketCache, query: &str) -> Result<SecCompany, String> {
+    let query = query.trim().to_uppercase();
+    let companies = load_companies(app, native, cache).await?;
+
+    let found = if let Ok(cik) = query.parse::<u64>() {
+        let cik = format!("{:010}", cik);
+        companies.into_iter().find(|c| c.cik == cik)
+    } else {
+        companies.into_iter().find(|c| c.ticker == query)
+    };
+    found.ok_or_else(|| format!("No SEC registrant found for {}", query))
+}
+
+fn days_between(start: &str, end: &str) -> Option<i64> {
+    let start = NaiveDate::parse_from_str(start, "%Y-%m-%d").ok()?;
+    let end = NaiveDate::parse_from_str(end, "%Y-%m-%d").ok()?;
+    Some((end - start).num_days())
+}
+
+/// Period label -> value for one concept, keeping the most recently filed figure
+/// when a period is reported more than once (comparatives, amendments).
+fn concept_values(concept: &serde_json::Value, period: Period) -> BTreeMap<String, f64> {
+    let Some(units) = concept.get("units").and_then(|u| u.as_object()) else { return BTreeMap::new() };
+    let facts = units.get("USD")
+        .or_else(|| units.get("USD/shares"))
+        .or_else(|| units.values().next())
+        .and_then(|f| f.as_array())
+        .cloned()
+        .unwrap_or_default();
+
+    let mut best: BTreeMap<String, (String, f64)> = BTreeMap::new();
+    for fact in &facts {
+        let (Some(end), Some(val), Some(form)) = (
+            fact.get("end").and_then(|v| v.as_str()),
+            fact.get("val").and_then(|v| v.as_f64()),
+            fact.get("form").and_then(|v| v.as_str()),
+        ) else { continue };
+        let filed = fact.get("filed").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+
+        let form_ok = match period {
+            Period::Annual => form.starts_with("10-K") || form.starts_with("20-F"),
+            Period::Quarterly => form.starts_with("10-Q"),
+        };
+        // Flow facts carry a start date; skip YTD/9-month figures that aren't one period long
+        let duration_ok = match fact.get("start").and_then(|v| v.as_str()).and_then(|s| days_between(s, end)) {
+            None => true,
+            Some(days) => match period {
+                Period::Annual => (330..=380).contains(&days),
+                Period::Quarterly => (80..=100).contains(&days),
+            },
+        };
+        if !form_ok || !duration_ok {
+            continue;
+        }
+
+        let label = match period {
+            Period::Annual => end.get(..4).unwrap_or(end).to_string(),
+            Period::Quarterly => end.to_string(),
+        };
+        if best.get(&label).is_none_or(|(prev_filed, _)| filed > *prev_filed) {
+            best.insert(label, (filed, val));
+        }
+    }
+    best.into_iter().map(|(label, (_, val))| (label, val)).collect()
+}
+
+fn collect_series(facts: &serde_json::Value, period: Period) -> Series {
+    let null = serde_json::Value::Null;
+    let gaap = facts.pointer("/facts/us-gaap").unwrap_or(&null);
+
+    let mut series = Series::new();
+    for (key, concepts) in CONCEPTS {
+        let values = concepts.iter()
+            .filter_map(|name| gaap.get(*name))
+            .map(|concept| concept_values(concept, period))
+            .find(|values| !values.is_empty());
+        if let Some(values) = values {
+            series.insert(key.to_string(), values);
+        }
+    }
+    series
+}
+
+// Tauri Commands
+#[tauri::command]
+pub async fn search_sec_companies(
+    app: AppHandle,
+    native: tauri::State<'_, NativeScraper>,
+    cache: tauri::State<'_, MarketCache>,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<SecCompany>, String> {
+    let query = query.trim().to_uppercase();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    let companies = load_companies(&app, &native, &cache).await?;
+    let cik = query.parse::<u64>().ok().map(|c| format!("{:010}", c));
+
+    // Exact ticker / CIK matches first, then ticker prefixes, then name matches
+    let mut ranked: Vec<(u8, SecCompany)> = companies.into_iter()
+        .filter_map(|c| {
+            let rank = if c.ticker == query || cik.as_ref() == Some(&c.cik) {
+                0
+            } else if c.ticker.starts_with(&query) {
+                1
+            } else if c.name.to_uppercase().contains(&query) {
+                2
+            } else {
+                return None;
+            };
+            Some((rank, c))
+        })
+        .collect();
+    ranked.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.ticker.cmp(&b.1.ticker)));
+    ranked.truncate(limit.unwrap_or(25));
+    Ok(ranked.into_iter().map(|(_, c)| c).collect())
+}
+
+#[tauri::command]
+pub async fn import_sec_financials(
+    app: AppHandle,
+    native: tauri::State<'_, NativeScraper>,
+    cache: tauri::State<'_, MarketCache>,
+    company: String,
+    period: Option<String>,
+    periods: Option<usize>,
+) -> Result<ImportedStatements, String> {
+    let period = match period.as_deref().unwrap_or("annual").to_lowercase().as_str() {
+        "annual" | "10-k" => Period::Annual,
+        "quarterly" | "10-q" => Period::Quarterly,
+        other => return Err(format!("Unsupported period: {}", other)),
+    };
+    let company = resolve_company(&app, &native, &cache, &company).await?;
+    settings::ensure_online(&app)?;
+
+    eprintln!("[Edgar] Fetching company facts for {} (CIK {})", company.ticker, company.cik);
+    let facts = native.sec_get_json(
+        &format!("{}/CIK{}.json", SEC_FACTS_BASE, company.cik), scraper::request_timeout(&app),
+    ).await?;
+
+    let (series, labels) = statements::latest_periods(collect_series(&facts, period), periods);
+    if labels.is_empty() {
+        return Err(format!("No 10-K/10-Q financial data found for {}", company.ticker));
+    }
+
+    let form = if period == Period::Annual { "10-K" } else { "10-Q" };
+    let filename = format!("{} ({}) - SEC EDGAR", company.ticker, form);
+    let metadata = serde_json::json!({
+        "fileName": filename,
+        "source": "sec_edgar",
+        "symbol": company.ticker,
+        "exchange": "SEC",
+        "cik": company.cik,
+        "companyName": company.name,
+        "form": form,
+        "fiscalYears": labels,
+        "currency": "USD",
+        "parser": "sec_edgar_import",
+    });
+
+    let (doc_id, items) = statements::save_statements(&app, &filename, &metadata, STATEMENTS, &series, &labels, "USD")?;
+
+    eprintln!("[Edgar] Imported {} items for {} as document {}", items.len(), company.ticker, doc_id);
+    statements::document_stored(&app, doc_id);
+    activity::record(&app, ActivityKind::DocumentImported, Some(doc_id), &format!("Imported {}", filename), None);
+    Ok(ImportedStatements {
+        doc_id,
+        filename,
+        symbol: company.ticker,
+        exchange: "SEC".to_string(),
+        fiscal_years: labels,
+        item_count: items.len(),
+        items,
+    })
+}
@@ -0,0 +1,142 @@
+// Error - typed command errors: a stable `code` the frontend can branch on, the message to show
+// and, where there is one, a hint on how to fix it
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+use crate::python_setup::MIN_PYTHON_VERSION;
+use crate::settings::OFFLINE_ERROR;
+use crate::timeouts::TimeoutHit;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppError {
+    /// No interpreter of at least `MIN_PYTHON_VERSION` was found
+    PythonNotFound,
+    /// python/api.py is not next to the app
+    ScriptNotFound(String),
+    /// python/api.py speaks another bridge protocol than this build
+    BridgeOutdated(String),
+    /// Python started but failed, crashed or never answered
+    Python(String),
+    Timeout(String),
+    /// A configurable limit (see `timeouts`) ran out
+    TimedOut(TimeoutHit),
+    Cancelled(String),
+    /// A network-dependent command while offline mode is on
+    Offline,
+    /// Ollama did not answer at the configured host
+    OllamaUnavailable(String),
+    InvalidInput(String),
+    NotFound(String),
+    /// Reading or writing a file failed
+    Io(String),
+    Internal(String),
+}
+
+impl AppError {
+    /// Stable identifier; never change an existing one, the frontend matches on it.
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::PythonNotFound => "python_not_found",
+            AppError::ScriptNotFound(_) => "script_not_found",
+            AppError::BridgeOutdated(_) => "bridge_outdated",
+            AppError::Python(_) => "python_failed",
+            AppError::Timeout(_) | AppError::TimedOut(_) => "timeout",
+            AppError::Cancelled(_) => "cancelled",
+            AppError::Offline => "offline",
+            AppError::OllamaUnavailable(_) => "ollama_unavailable",
+            AppError::InvalidInput(_) => "invalid_input",
+            AppError::NotFound(_) => "not_found",
+            AppError::Io(_) => "io",
+            AppError::Internal(_) => "internal",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            AppError::PythonNotFound => format!(
+                "Python {}.{} or newer not found",
+                MIN_PYTHON_VERSION.0, MIN_PYTHON_VERSION.1
+            ),
+            AppError::Offline => OFFLINE_ERROR.to_string(),
+            AppError::TimedOut(hit) => hit.message(),
+            AppError::ScriptNotFound(message)
+            | AppError::BridgeOutdated(message)
+            | AppError::Python(message)
+            | AppError::Timeout(message)
+            | AppError::Cancelled(message)
+            | AppError::OllamaUnavailable(message)
+            | AppError::InvalidInput(message)
+            | AppError::NotFound(message)
+            | AppError::Io(message)
+            | AppError::Internal(message) => message.clone(),
+        }
+    }
+
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            AppError::PythonNotFound => Some("Install a current Python 3 or set its path under Settings > Python; the setup check lists what was found."),
+            AppError::ScriptNotFound(_) => Some("Reinstall the app; its python/ folder is missing."),
+            AppError::BridgeOutdated(_) => Some("Replace the python/ folder with the one shipped with this release, then restart."),
+            AppError::Python(_) => Some("The log has the Python error; run the health check to see missing modules."),
+            AppError::Timeout(_) => Some("Raise the timeout under Settings > Python, or split the document."),
+            AppError::TimedOut(hit) => Some(hit.operation.hint()),
+            AppError::Offline => Some("Turn off offline mode in Settings."),
+            AppError::OllamaUnavailable(_) => Some("Start Ollama, or check its host and port under Settings > LLM."),
+            AppError::Cancelled(_)
+            | AppError::InvalidInput(_)
+            | AppError::NotFound(_)
+            | AppError::Io(_)
+            | AppError::Internal(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+/// `{ code, message, hint, timeout }`, with `hint` null when there is none and `timeout` (which
+/// limit was hit) null unless a configurable timeout ran out.
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let timeout = match self {
+            AppError::TimedOut(hit) => Some(hit),
+            _ => None,
+        };
+        let mut error = serializer.serialize_struct("AppError", 4)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &self.message())?;
+        error.serialize_field("hint", &self.hint())?;
+        error.serialize_field("timeout", &timeout)?;
+        error.end()
+    }
+}
+
+/// Errors from helpers that still return strings; the offline prefix keeps its own code.
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        if message == OFFLINE_ERROR {
+            AppError::Offline
+        } else {
+            AppError::Internal(message)
+        }
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        AppError::from(message.to_string())
+    }
+}
+
+/// Lets code that still works in strings call the migrated commands with `?`.
+impl From<AppError> for String {
+    fn from(error: AppError) -> Self {
+        error.message()
+    }
+}
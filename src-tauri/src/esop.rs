@@ -0,0 +1,209 @@
+// ESOP - fair value of employee stock option grants by Black-Scholes, each vesting tranche
+// valued as a grant of its own (graded vesting under Ind AS 102 / IFRS 2), and the expense
+// those tranches put through profit and loss year by year for the share-based payment note
+use chrono::{Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::corporate_events::normalize_date;
+
+const MAX_VESTING_YEARS: f64 = 20.0;
+// Tranche percentages may be off by this much from 100 after rounding
+const PERCENT_TOLERANCE: f64 = 0.01;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VestingTranche {
+    /// Years from grant until the tranche vests
+    pub vesting_years: f64,
+    /// Share of the options granted vesting in this tranche, in percent
+    pub percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EsopInputs {
+    pub options_granted: f64,
+    pub exercise_price: f64,
+    /// Share price on the grant date
+    pub share_price: f64,
+    /// Yearly, in percent
+    pub volatility: f64,
+    /// Yearly, in percent
+    pub risk_free_rate: f64,
+    /// Yearly, in percent
+    pub dividend_yield: Option<f64>,
+    /// Years after vesting the options can still be exercised in; each tranche is expected to
+    /// be exercised halfway through it
+    pub exercise_period: f64,
+    pub vesting_schedule: Vec<VestingTranche>,
+    /// Employees expected to leave each year before vesting, in percent
+    pub forfeiture_rate: Option<f64>,
+    /// Dates the expense periods when given
+    pub grant_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrancheValuation {
+    pub vesting_years: f64,
+    pub percent: f64,
+    pub options: f64,
+    /// Vesting plus half the exercise period, in years
+    pub expected_life: f64,
+    pub fair_value_per_option: f64,
+    /// Options still held by employees at vesting after expected forfeiture
+    pub expected_to_vest: f64,
+    /// Fair value of the options expected to vest, spread straight-line to vesting
+    pub total_expense: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpensePeriod {
+    /// Years from grant, starting at 1
+    pub year: u32,
+    /// Last day of the year when the grant date is known
+    pub end_date: Option<String>,
+    /// In order of the tranches
+    pub tranche_expense: Vec<f64>,
+    pub expense: f64,
+    pub cumulative_expense: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EsopValuation {
+    /// Averaged over the tranches by options
+    pub fair_value_per_option: f64,
+    /// Fair value of every option granted
+    pub total_fair_value: f64,
+    pub expected_to_vest: f64,
+    /// What the grant costs after expected forfeiture
+    pub total_expense: f64,
+    pub tranches: Vec<TrancheValuation>,
+    pub schedule: Vec<ExpensePeriod>,
+}
+
+/// Standard normal cumulative distribution, via the Abramowitz and Stegun approximation of
+/// erf (7.1.26; error below 1.5e-7).
+fn normal_cdf(x: f64) -> f64 {
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * z);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-z * z).exp();
+    if x >= 0.0 { 0.5 * (1.0 + erf) } else { 0.5 * (1.0 - erf) }
+}
+
+/// Black-Scholes value of a European call with a continuous dividend yield; rates as fractions.
+pub fn black_scholes_call(spot: f64, strike: f64, years: f64, rate: f64, volatility: f64, dividend_yield: f64) -> f64 {
+    let carried_spot = spot * (-dividend_yield * years).exp();
+    let discounted_strike = strike * (-rate * years).exp();
+    if years <= 0.0 || volatility <= 0.0 {
+        return (carried_spot - discounted_strike).max(0.0);
+    }
+    let spread = volatility * years.sqrt();
+    let d1 = ((spot / strike).ln() + (rate - dividend_yield + volatility * volatility / 2.0) * years) / spread;
+    let d2 = d1 - spread;
+    carried_spot * normal_cdf(d1) - discounted_strike * normal_cdf(d2)
+}
+
+fn validate(inputs: &EsopInputs) -> Result<(), String> {
+    if !(inputs.options_granted > 0.0 && inputs.share_price > 0.0 && inputs.exercise_price > 0.0) {
+        return Err("Options granted, share price and exercise price must be positive".to_string());
+    }
+    if !(inputs.volatility > 0.0 && inputs.volatility.is_finite()) {
+        return Err("Volatility must be positive".to_string());
+    }
+    if !inputs.risk_free_rate.is_finite() || inputs.dividend_yield.is_some_and(|d| !d.is_finite() || d < 0.0) {
+        return Err("Risk-free rate and dividend yield must be numbers; the yield not negative".to_string());
+    }
+    if !(inputs.exercise_period >= 0.0 && inputs.exercise_period.is_finite()) {
+        return Err("Exercise period must not be negative".to_string());
+    }
+    if inputs.forfeiture_rate.is_some_and(|f| !(0.0..100.0).contains(&f)) {
+        return Err("Forfeiture rate must be at least 0% and below 100%".to_string());
+    }
+    if inputs.vesting_schedule.is_empty() {
+        return Err("The vesting schedule needs at least one tranche".to_string());
+    }
+    if inputs.vesting_schedule.iter().any(|t| !(t.vesting_years > 0.0 && t.vesting_years <= MAX_VESTING_YEARS) || !(t.percent > 0.0 && t.percent.is_finite())) {
+        return Err(format!("Tranches must vest within {} years and carry a positive share", MAX_VESTING_YEARS));
+    }
+    let total: f64 = inputs.vesting_schedule.iter().map(|t| t.percent).sum();
+    if (total - 100.0).abs() > PERCENT_TOLERANCE {
+        return Err(format!("Tranche percentages add up to {:.2}%, not 100%", total));
+    }
+    Ok(())
+}
+
+pub fn value(inputs: &EsopInputs) -> Result<EsopValuation, String> {
+    validate(inputs)?;
+    let grant_date = inputs.grant_date.as_deref()
+        .map(|raw| normalize_date(raw).ok_or_else(|| format!("Invalid date: {}", raw)))
+        .transpose()?
+        .map(|date| NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| e.to_string()))
+        .transpose()?;
+    let retention = 1.0 - inputs.forfeiture_rate.unwrap_or(0.0) / 100.0;
+
+    let tranches: Vec<TrancheValuation> = inputs.vesting_schedule.iter()
+        .map(|tranche| {
+            let options = inputs.options_granted * tranche.percent / 100.0;
+            let expected_life = tranche.vesting_years + inputs.exercise_period / 2.0;
+            let fair_value_per_option = black_scholes_call(
+                inputs.share_price,
+                inputs.exercise_price,
+                expected_life,
+                inputs.risk_free_rate / 100.0,
+                inputs.volatility / 100.0,
+                inputs.dividend_yield.unwrap_or(0.0) / 100.0,
+            );
+            let expected_to_vest = options * retention.powf(tranche.vesting_years);
+            TrancheValuation {
+                vesting_years: tranche.vesting_years,
+                percent: tranche.percent,
+                options,
+                expected_life,
+                fair_value_per_option,
+                expected_to_vest,
+                total_expense: expected_to_vest * fair_value_per_option,
+            }
+        })
+        .collect();
+
+    // Each tranche is expensed evenly from grant to its vesting, so a year takes the share of
+    // the vesting period that falls within it
+    let years = tranches.iter().map(|t| t.vesting_years.ceil() as u32).max().unwrap_or(0);
+    let mut cumulative_expense = 0.0;
+    let schedule = (1..=years)
+        .map(|year| {
+            let (start, end) = ((year - 1) as f64, year as f64);
+            let tranche_expense: Vec<f64> = tranches.iter()
+                .map(|t| t.total_expense * (end.min(t.vesting_years) - start).max(0.0) / t.vesting_years)
+                .collect();
+            let expense: f64 = tranche_expense.iter().sum();
+            cumulative_expense += expense;
+            let end_date = grant_date
+                .and_then(|date| date.checked_add_months(Months::new(year * 12)))
+                .and_then(|date| date.pred_opt())
+                .map(|date| date.format("%Y-%m-%d").to_string());
+            ExpensePeriod { year, end_date, tranche_expense, expense, cumulative_expense }
+        })
+        .collect();
+
+    let total_fair_value: f64 = tranches.iter().map(|t| t.options * t.fair_value_per_option).sum();
+    Ok(EsopValuation {
+        fair_value_per_option: total_fair_value / inputs.options_granted,
+        total_fair_value,
+        expected_to_vest: tranches.iter().map(|t| t.expected_to_vest).sum(),
+        total_expense: tranches.iter().map(|t| t.total_expense).sum(),
+        tranches,
+        schedule,
+    })
+}
+
+// Tauri Commands
+/// Grant-date fair value of an option grant and its yearly expense under graded vesting.
+#[tauri::command]
+pub fn value_esop_grant(inputs: EsopInputs) -> Result<EsopValuation, String> {
+    value(&inputs)
+}
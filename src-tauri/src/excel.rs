@@ -0,0 +1,287 @@
+// Excel - native XLSX/XLS/ODS statement parsing into financial_items, no Python involved
+use calamine::{open_workbook_auto, Data, Range, Reader};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tauri::AppHandle;
+
+use crate::activity::{self, ActivityKind};
+use crate::integrity;
+use crate::numbers;
+use crate::statements::{self, LineItem};
+
+// Headers sit near the top; don't scan a whole 10k-row ledger looking for one
+const HEADER_SCAN_ROWS: usize = 30;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExcelOptions {
+    /// 0-based row holding the period headers; detected when absent
+    pub header_row: Option<usize>,
+    /// 0-based column holding line item labels; detected when absent
+    pub label_column: Option<usize>,
+    /// 0-based columns to read values from; defaults to every period column
+    pub value_columns: Option<Vec<usize>>,
+    /// Statement type for rows before any recognisable section heading
+    pub statement_type: Option<String>,
+    pub unit: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedSpreadsheet {
+    pub doc_id: i64,
+    pub filename: String,
+    pub sheet: Option<String>,
+    pub periods: Vec<String>,
+    pub item_count: usize,
+    pub items: Vec<serde_json::Value>,
+}
+
+/// Parse an amount as accountants write it: "1,23,456.50", "(1,234)", "₹ 12.3 Cr", "-".
+pub fn parse_amount(raw: &str) -> Option<f64> {
+    numbers::normalize(raw).value
+}
+
+/// Fiscal year named by a column header: "2024", "FY 2023-24", "Mar-24", "31.03.2024" -> "2024".
+pub fn period_label(header: &str) -> Option<String> {
+    let lower = header.to_lowercase();
+    let runs: Vec<&str> = lower.split(|c: char| !c.is_ascii_digit()).filter(|r| !r.is_empty()).collect();
+
+    for (i, run) in runs.iter().enumerate() {
+        let Ok(year) = run.parse::<u32>() else { continue };
+        if run.len() == 4 && (1990..=2099).contains(&year) {
+            // "2023-24": the fiscal year is named after the year it ends in
+            return match runs.get(i + 1).filter(|next| next.len() == 2) {
+                Some(next) if lower.contains(&format!("{}-{}", run, next)) || lower.contains(&format!("{}/{}", run, next)) => {
+                    Some(format!("{}{}", &run[..2], next))
+                }
+                _ => Some(run.to_string()),
+            };
+        }
+    }
+
+    const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+    let names_period = lower.contains("fy") || MONTHS.iter().any(|m| lower.contains(m));
+    runs.last()
+        .filter(|run| names_period && run.len() == 2)
+        .map(|run| format!("20{}", run))
+}
+
+/// Statement type named by a section heading or sheet name, if any.
+pub fn statement_for_heading(text: &str) -> Option<&'static str> {
+    let lower = text.to_lowercase();
+    if lower.contains("balance sheet") || lower.contains("financial position") {
+        Some("balance_sheet")
+    } else if lower.contains("cash flow") || lower.contains("cashflow") {
+        Some("cash_flow")
+    } else if ["profit and loss", "profit & loss", "income statement", "statement of profit", "p&l", "p & l"]
+        .iter().any(|k| lower.contains(k))
+    {
+        Some("income_statement")
+    } else if lower.starts_with("note") {
+        Some("notes")
+    } else {
+        None
+    }
+}
+
+/// Totals are what the metrics engine keys ratios off, so flag them.
+pub fn is_total_label(label: &str) -> bool {
+    let lower = label.to_lowercase();
+    lower.starts_with("total") || lower.contains(" total") || lower.starts_with("net ")
+}
+
+pub fn cell_text(cell: &Data) -> Option<String> {
+    let text = match cell {
+        Data::String(s) => s.trim().to_string(),
+        Data::Int(i) => i.to_string(),
+        Data::Float(f) => f.to_string(),
+        Data::DateTime(d) => d.as_datetime()?.format("%Y-%m-%d").to_string(),
+        Data::DateTimeIso(s) => s.clone(),
+        _ => return None,
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+fn cell_amount(cell: &Data) -> Option<f64> {
+    match cell {
+        Data::Int(i) => Some(*i as f64),
+        Data::Float(f) => Some(*f),
+        Data::String(s) => parse_amount(s),
+        _ => None,
+    }
+}
+
+/// A header cell naming a period. Bare numbers only count when they look like years,
+/// so a data row full of amounts isn't mistaken for a header.
+fn header_period(cell: &Data) -> Option<String> {
+    match cell {
+        Data::Int(_) | Data::Float(_) => cell_amount(cell)
+            .filter(|v| v.fract() == 0.0 && (1990.0..=2099.0).contains(v))
+            .map(|v| (v as i64).to_string()),
+        _ => period_label(&cell_text(cell)?),
+    }
+}
+
+fn detect_header_row(rows: &[&[Data]]) -> Option<usize> {
+    rows.iter().take(HEADER_SCAN_ROWS).position(|row| {
+        let periods = row.iter().filter_map(header_period).count();
+        let has_text_period = row.iter().any(|c| !matches!(c, Data::Int(_) | Data::Float(_)) && header_period(c).is_some());
+        periods >= 2 || (periods == 1 && has_text_period)
+    })
+}
+
+/// The column with the most text that isn't an amount.
+fn detect_label_column(rows: &[&[Data]], width: usize) -> usize {
+    (0..width.min(5))
+        .max_by_key(|&col| {
+            let score = rows.iter()
+                .filter_map(|row| row.get(col))
+                .filter(|c| matches!(c, Data::String(s) if parse_amount(s).is_none() && !s.trim().is_empty()))
+                .count();
+            // Prefer the leftmost column on ties
+            (score, usize::MAX - col)
+        })
+        .unwrap_or(0)
+}
+
+pub fn read_sheet(range: &Range<Data>, sheet: Option<&str>, options: &ExcelOptions) -> Result<(Vec<LineItem>, Vec<String>), String> {
+    let rows: Vec<&[Data]> = range.rows().collect();
+    if rows.is_empty() {
+        return Err("Sheet is empty".to_string());
+    }
+    let width = range.width();
+
+    let header_row = options.header_row.or_else(|| detect_header_row(&rows));
+    if let Some(h) = header_row.filter(|h| *h >= rows.len()) {
+        return Err(format!("Header row {} is past the end of the sheet ({} rows)", h, rows.len()));
+    }
+    let data_start = header_row.map_or(0, |h| h + 1);
+    let label_column = options.label_column.unwrap_or_else(|| detect_label_column(&rows[data_start..], width));
+
+    // Columns named by position, leftmost = most recent as Indian statements print them
+    let positional = |cols: Vec<usize>| {
+        let count = cols.len();
+        cols.into_iter().enumerate()
+            .map(|(i, col)| (col, format!("Period {:02}", count - i)))
+            .collect::<Vec<(usize, String)>>()
+    };
+    let columns: Vec<(usize, String)> = match (header_row, &options.value_columns) {
+        // Explicit columns keep their header years only if every one of them has a distinct year
+        (Some(h), Some(cols)) => {
+            let named: Vec<(usize, String)> = cols.iter()
+                .filter_map(|&col| Some((col, rows[h].get(col).and_then(header_period)?)))
+                .collect();
+            let distinct: std::collections::HashSet<&String> = named.iter().map(|(_, p)| p).collect();
+            if named.len() == cols.len() && distinct.len() == cols.len() { named } else { positional(cols.clone()) }
+        }
+        (Some(h), None) => {
+            let mut named: Vec<(usize, String)> = Vec::new();
+            for col in (0..width).filter(|c| *c != label_column) {
+                let Some(period) = rows[h].get(col).and_then(header_period) else { continue };
+                // Standalone and consolidated side by side repeat years; keep the first
+                if !named.iter().any(|(_, p)| *p == period) {
+                    named.push((col, period));
+                }
+            }
+            named
+        }
+        (None, Some(cols)) => positional(cols.clone()),
+        (None, None) => positional((0..width)
+            .filter(|&col| col != label_column && rows.iter().any(|row| row.get(col).and_then(cell_amount).is_some()))
+            .collect()),
+    };
+    if columns.is_empty() {
+        return Err("No value columns found; set headerRow or valueColumns".to_string());
+    }
+
+    let mut statement = options.statement_type.clone()
+        .or_else(|| sheet.and_then(statement_for_heading).map(str::to_string))
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let mut lines = Vec::new();
+    for (row_number, row) in rows.iter().enumerate().skip(data_start) {
+        let Some(label) = row.get(label_column).and_then(cell_text) else { continue };
+        if parse_amount(&label).is_some() {
+            continue;
+        }
+        let values: BTreeMap<String, f64> = columns.iter()
+            .filter_map(|(col, period)| Some((period.clone(), row.get(*col).and_then(cell_amount)?)))
+            .collect();
+
+        let is_header = values.is_empty();
+        if is_header {
+            if let Some(kind) = statement_for_heading(&label) {
+                statement = kind.to_string();
+            }
+        }
+        lines.push(LineItem {
+            key: format!("row{}", row_number),
+            is_total: !is_header && is_total_label(&label),
+            label,
+            statement: statement.clone(),
+            row_index: row_number,
+            values,
+            is_header,
+        });
+    }
+    if lines.iter().all(|l| l.is_header) {
+        return Err("No labelled rows with values found".to_string());
+    }
+
+    let mut periods: Vec<String> = columns.into_iter().map(|(_, p)| p).collect();
+    periods.sort();
+    Ok((lines, periods))
+}
+
+// Tauri Commands
+#[tauri::command]
+pub async fn parse_excel(
+    app: AppHandle,
+    file_path: String,
+    sheet: Option<String>,
+    options: Option<ExcelOptions>,
+) -> Result<ParsedSpreadsheet, String> {
+    let options = options.unwrap_or_default();
+    let mut workbook = open_workbook_auto(&file_path).map_err(|e| format!("Failed to open workbook: {}", e))?;
+
+    let sheet_name = match sheet {
+        Some(name) => name,
+        // First sheet with anything in it
+        None => workbook.sheet_names().into_iter()
+            .find(|name| workbook.worksheet_range(name).is_ok_and(|r| !r.is_empty()))
+            .ok_or("Workbook has no non-empty sheets")?,
+    };
+    let range = workbook.worksheet_range(&sheet_name)
+        .map_err(|e| format!("Failed to read sheet {}: {}", sheet_name, e))?;
+
+    let (lines, periods) = read_sheet(&range, Some(&sheet_name), &options)?;
+    let filename = Path::new(&file_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| file_path.clone());
+    let unit = options.unit.unwrap_or_else(|| "INR".to_string());
+    let metadata = serde_json::json!({
+        "fileName": filename,
+        "source": "excel",
+        "sheet": sheet_name,
+        "fiscalYears": periods,
+        "currency": unit,
+        "parser": "native_excel",
+    });
+
+    let (doc_id, items) = statements::save_document(&app, &filename, &metadata, &lines, &periods, &unit, "excel")?;
+    eprintln!("[Excel] Imported {} rows from {} ({}) as document {}", items.len(), filename, sheet_name, doc_id);
+    statements::document_stored(&app, doc_id);
+    integrity::record(&app, doc_id, &file_path);
+    activity::record(&app, ActivityKind::DocumentImported, Some(doc_id), &format!("Imported {}", filename), None);
+    Ok(ParsedSpreadsheet {
+        doc_id,
+        filename,
+        sheet: Some(sheet_name),
+        periods,
+        item_count: items.len(),
+        items,
+    })
+}
@@ -0,0 +1,651 @@
+// Export - analysis workbooks and files built from a stored document
+use rust_xlsxwriter::{Format, FormatAlign, Workbook, Worksheet, XlsxError};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use tauri::AppHandle;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+use crate::activity::{self, ActivityKind};
+use crate::formatting::{Formatter, NumberStyle};
+use crate::item_edits;
+use crate::notes::{self, Note};
+use crate::periods;
+use crate::ratios::{self, Ratio};
+use crate::report;
+use crate::report_templates::{self, TemplateRef};
+use crate::statements::{self, StoredDocument};
+use crate::usage::{self, UsageKind};
+
+struct Formats {
+    header: Format,
+    plain: Format,
+    amount: Format,
+    total_amount: Format,
+    bold: Format,
+    percent: Format,
+    multiple: Format,
+}
+
+impl Formats {
+    fn new(fmt: &Formatter) -> Self {
+        Self {
+            header: Format::new().set_bold().set_background_color("#DCE6F1").set_align(FormatAlign::Center),
+            plain: Format::new(),
+            amount: Format::new().set_num_format(fmt.excel_amount_format()),
+            total_amount: Format::new().set_bold().set_num_format(fmt.excel_amount_format()),
+            bold: Format::new().set_bold(),
+            percent: Format::new().set_num_format("0.0%"),
+            multiple: Format::new().set_num_format("0.00\"x\""),
+        }
+    }
+}
+
+fn f64_field(item: &serde_json::Value, key: &str) -> f64 {
+    item[key].as_f64().unwrap_or(0.0)
+}
+
+fn write_headers(sheet: &mut Worksheet, headers: &[&str], format: &Format) -> Result<(), XlsxError> {
+    for (col, header) in headers.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, *header, format)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    Ok(())
+}
+
+/// Every item, with a column per reported period after the current/previous pair.
+fn write_items(sheet: &mut Worksheet, items: &[serde_json::Value], formats: &Formats) -> Result<(), XlsxError> {
+    let periods: Vec<String> = items.iter()
+        .filter_map(|item| item["allYears"].as_object())
+        .flat_map(|years| years.keys().cloned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let mut headers = vec!["Statement", "Label", "Current Year", "Previous Year", "Source Page", "Confidence", "Method", "Source", "Set By"];
+    headers.extend(periods.iter().map(String::as_str));
+    write_headers(sheet, &headers, &formats.header)?;
+
+    for (i, item) in items.iter().enumerate() {
+        let row = i as u32 + 1;
+        let is_header = item["isHeader"].as_bool().unwrap_or(false);
+        let is_total = item["isTotal"].as_bool().unwrap_or(false);
+        let label_format = if is_header || is_total { &formats.bold } else { &formats.plain };
+        let amount_format = if is_total { &formats.total_amount } else { &formats.amount };
+
+        sheet.write_string(row, 0, item["statementType"].as_str().unwrap_or(""))?;
+        sheet.write_string_with_format(row, 1, item["label"].as_str().unwrap_or(""), label_format)?;
+        if !is_header {
+            sheet.write_number_with_format(row, 2, f64_field(item, "currentYear"), amount_format)?;
+            sheet.write_number_with_format(row, 3, f64_field(item, "previousYear"), amount_format)?;
+        }
+        match &item["sourcePage"] {
+            serde_json::Value::Number(page) => { sheet.write_number(row, 4, page.as_f64().unwrap_or(0.0))?; }
+            serde_json::Value::String(page) if !page.is_empty() => { sheet.write_string(row, 4, page)?; }
+            _ => {}
+        }
+        sheet.write_number(row, 5, f64_field(item, "confidence"))?;
+        sheet.write_string(row, 6, item["extractionMethod"].as_str().unwrap_or(""))?;
+        if item_edits::is_manual(item) {
+            sheet.write_string(row, 7, "Manual")?;
+            sheet.write_string(row, 8, item["provenance"]["setBy"].as_str().unwrap_or(""))?;
+        } else {
+            sheet.write_string(row, 7, "Extracted")?;
+        }
+        for (offset, period) in periods.iter().enumerate() {
+            if let Some(value) = item["allYears"][period].as_f64() {
+                sheet.write_number_with_format(row, 9 + offset as u16, value, amount_format)?;
+            }
+        }
+    }
+    sheet.set_column_width(0, 18)?;
+    sheet.set_column_width(1, 48)?;
+    for col in 2..(9 + periods.len() as u16) {
+        sheet.set_column_width(col, 16)?;
+    }
+    Ok(())
+}
+
+fn write_ratios(sheet: &mut Worksheet, ratios: &[Ratio], formats: &Formats) -> Result<(), XlsxError> {
+    write_headers(sheet, &["Ratio", "Category", "Current Year", "Previous Year", "Change"], &formats.header)?;
+    for (i, ratio) in ratios.iter().enumerate() {
+        let row = i as u32 + 1;
+        // Percent ratios are stored x100; Excel's % format wants the fraction
+        let (scale, format) = if ratio.unit == "%" { (0.01, &formats.percent) } else { (1.0, &formats.multiple) };
+        sheet.write_string(row, 0, &ratio.name)?;
+        sheet.write_string(row, 1, ratio.category.replace('_', " "))?;
+        if let Some(current) = ratio.current {
+            sheet.write_number_with_format(row, 2, current * scale, format)?;
+        }
+        if let Some(previous) = ratio.previous {
+            sheet.write_number_with_format(row, 3, previous * scale, format)?;
+        }
+        if let (Some(current), Some(previous)) = (ratio.current, ratio.previous) {
+            sheet.write_number_with_format(row, 4, (current - previous) * scale, format)?;
+        }
+    }
+    sheet.set_column_width(0, 36)?;
+    sheet.set_column_width(1, 16)?;
+    for col in 2..5 {
+        sheet.set_column_width(col, 16)?;
+    }
+    Ok(())
+}
+
+/// Movement between each valued line's current and previous periods, largest relative change first.
+fn write_variance(sheet: &mut Worksheet, items: &[serde_json::Value], formats: &Formats) -> Result<(), XlsxError> {
+    write_headers(sheet, &["Label", "Statement", "Current Year", "Previous Year", "Change", "Change %", "Periods"], &formats.header)?;
+    // (item, current, previous, change, change %)
+    let mut lines: Vec<(&serde_json::Value, f64, f64, f64, Option<f64>)> = items.iter()
+        .filter(|item| !item["isHeader"].as_bool().unwrap_or(false))
+        .filter_map(|item| {
+            let (current, previous) = periods::amounts(item);
+            let (current, previous) = (current.unwrap_or(0.0), previous.unwrap_or(0.0));
+            if current == 0.0 && previous == 0.0 {
+                return None;
+            }
+            let change = current - previous;
+            let percent = (previous != 0.0).then(|| change / previous.abs());
+            Some((item, current, previous, change, percent))
+        })
+        .collect();
+    lines.sort_by(|a, b| {
+        let magnitude = |p: Option<f64>| p.map(f64::abs).unwrap_or(f64::INFINITY);
+        magnitude(b.4).total_cmp(&magnitude(a.4))
+    });
+
+    for (i, (item, current, previous, change, percent)) in lines.iter().enumerate() {
+        let row = i as u32 + 1;
+        sheet.write_string(row, 0, item["label"].as_str().unwrap_or(""))?;
+        sheet.write_string(row, 1, item["statementType"].as_str().unwrap_or(""))?;
+        sheet.write_number_with_format(row, 2, *current, &formats.amount)?;
+        sheet.write_number_with_format(row, 3, *previous, &formats.amount)?;
+        sheet.write_number_with_format(row, 4, *change, &formats.amount)?;
+        if let Some(percent) = percent {
+            sheet.write_number_with_format(row, 5, *percent, &formats.percent)?;
+        }
+        if let (Some(current), Some(previous)) = (item["currentPeriod"].as_str(), item["previousPeriod"].as_str()) {
+            sheet.write_string(row, 6, format!("{} vs {}", current, previous))?;
+        }
+    }
+    sheet.set_column_width(0, 48)?;
+    sheet.set_column_width(1, 18)?;
+    for col in 2..7 {
+        sheet.set_column_width(col, 16)?;
+    }
+    Ok(())
+}
+
+fn write_metadata(sheet: &mut Worksheet, document: &StoredDocument, formats: &Formats) -> Result<(), XlsxError> {
+    write_headers(sheet, &["Field", "Value"], &formats.header)?;
+    let mut rows: Vec<(String, String)> = vec![
+        ("Document ID".to_string(), document.id.to_string()),
+        ("File Name".to_string(), document.filename.clone()),
+        ("Processed At".to_string(), document.processed_at.clone().unwrap_or_default()),
+        ("Item Count".to_string(), document.items.len().to_string()),
+        ("Exported At".to_string(), chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string()),
+    ];
+    if let Some(metadata) = document.metadata.as_object() {
+        for (key, value) in metadata {
+            let value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            rows.push((key.clone(), value));
+        }
+    }
+    for (i, (field, value)) in rows.iter().enumerate() {
+        sheet.write_string_with_format(i as u32 + 1, 0, field, &formats.bold)?;
+        sheet.write_string(i as u32 + 1, 1, value)?;
+    }
+    sheet.set_column_width(0, 24)?;
+    sheet.set_column_width(1, 60)?;
+    Ok(())
+}
+
+fn build_workbook(document: &StoredDocument, fmt: &Formatter, path: &str) -> Result<(), XlsxError> {
+    let formats = Formats::new(fmt);
+    let ratios = ratios::for_document(document);
+    let mut workbook = Workbook::new();
+    write_items(workbook.add_worksheet().set_name("Items")?, &document.items, &formats)?;
+    write_ratios(workbook.add_worksheet().set_name("Ratios")?, &ratios, &formats)?;
+    write_variance(workbook.add_worksheet().set_name("Variance")?, &document.items, &formats)?;
+    write_metadata(workbook.add_worksheet().set_name("Metadata")?, document, &formats)?;
+    workbook.save(path)
+}
+
+/// Everything known about a document in one self-describing object.
+fn analysis_json(document: &StoredDocument, notes: &[Note]) -> serde_json::Value {
+    let mut json = serde_json::json!({
+        "formatVersion": 1,
+        "exportedAt": chrono::Local::now().to_rfc3339(),
+        "document": {
+            "id": document.id,
+            "filename": document.filename,
+            "processedAt": document.processed_at,
+            "metadata": document.metadata,
+        },
+        "items": document.items,
+        "ratios": ratios::for_document(document),
+    });
+    if !notes.is_empty() {
+        json["notes"] = serde_json::json!(notes);
+    }
+    json
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn csv_number(value: Option<f64>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+/// One flat table: a `record_type` column tells metadata, item and ratio rows apart.
+fn analysis_csv(document: &StoredDocument) -> String {
+    let mut rows: Vec<[String; 8]> = vec![[
+        "record_type", "statement", "label", "current_year", "previous_year", "unit", "is_total", "value",
+    ].map(String::from)];
+
+    let mut metadata = vec![
+        ("document_id".to_string(), document.id.to_string()),
+        ("filename".to_string(), document.filename.clone()),
+        ("processed_at".to_string(), document.processed_at.clone().unwrap_or_default()),
+    ];
+    if let Some(fields) = document.metadata.as_object() {
+        for (key, value) in fields {
+            let value = match value {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            metadata.push((key.clone(), value));
+        }
+    }
+    for (key, value) in metadata {
+        rows.push(["metadata".into(), String::new(), key, String::new(), String::new(), String::new(), String::new(), value]);
+    }
+
+    for item in &document.items {
+        let text = |key: &str| item[key].as_str().unwrap_or("").to_string();
+        rows.push([
+            "item".into(),
+            text("statementType"),
+            text("label"),
+            csv_number(item["currentYear"].as_f64()),
+            csv_number(item["previousYear"].as_f64()),
+            text("unit"),
+            item["isTotal"].as_bool().unwrap_or(false).to_string(),
+            String::new(),
+        ]);
+    }
+
+    for ratio in ratios::for_document(document) {
+        rows.push([
+            "ratio".into(),
+            ratio.category,
+            ratio.name,
+            csv_number(ratio.current),
+            csv_number(ratio.previous),
+            ratio.unit,
+            String::new(),
+            String::new(),
+        ]);
+    }
+
+    rows.iter()
+        .map(|row| row.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(","))
+        .collect::<Vec<_>>()
+        .join("\n")
+        + "\n"
+}
+
+/// Chart series for the key figures, embedded so readers can re-plot them.
+fn chart_data(document: &StoredDocument) -> serde_json::Value {
+    let figures = report::chart_figures(&document.items);
+    serde_json::json!({
+        "type": "bar",
+        "labels": figures.iter().map(|(name, _, _)| name).collect::<Vec<_>>(),
+        "series": [
+            { "name": "Previous Year", "values": figures.iter().map(|(_, _, previous)| previous).collect::<Vec<_>>() },
+            { "name": "Current Year", "values": figures.iter().map(|(_, current, _)| current).collect::<Vec<_>>() },
+        ],
+    })
+}
+
+fn statement_sections(document: &StoredDocument) -> Vec<(&'static str, Vec<&serde_json::Value>)> {
+    report::STATEMENTS.iter()
+        .map(|(statement, title)| {
+            let items = document.items.iter().filter(|item| report::statement_of(item) == *statement).collect();
+            (*title, items)
+        })
+        .filter(|(_, items): &(_, Vec<_>)| !items.is_empty())
+        .collect()
+}
+
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn analysis_markdown(document: &StoredDocument, fmt: &Formatter, notes: &[Note]) -> String {
+    let mut out = format!("# Financial Analysis: {}\n\n", markdown_cell(&document.filename));
+    out += &format!(
+        "Generated {} | {} line items\n\n",
+        fmt.date(&chrono::Local::now()),
+        document.items.len()
+    );
+
+    let ratios = ratios::for_document(document);
+    if !ratios.is_empty() {
+        out += "## Key Ratios\n\n| Ratio | Category | Current Year | Previous Year |\n|---|---|---:|---:|\n";
+        for ratio in &ratios {
+            out += &format!(
+                "| {} | {} | {} | {} |\n",
+                ratio.name,
+                ratio.category.replace('_', " "),
+                fmt.ratio(ratio.current, &ratio.unit),
+                fmt.ratio(ratio.previous, &ratio.unit),
+            );
+        }
+        out += "\n";
+    }
+
+    for (title, items) in statement_sections(document) {
+        out += &format!(
+            "## {}\n\n| Particulars | {} | {} |\n|---|---:|---:|\n",
+            title,
+            fmt.amount_heading("Current Year"),
+            fmt.amount_heading("Previous Year"),
+        );
+        for item in items {
+            let label = markdown_cell(&item_edits::display_label(item));
+            if item["isHeader"].as_bool().unwrap_or(false) {
+                out += &format!("| **{}** | | |\n", label);
+                continue;
+            }
+            let (current, previous) = (
+                fmt.amount(item["currentYear"].as_f64().unwrap_or(0.0)),
+                fmt.amount(item["previousYear"].as_f64().unwrap_or(0.0)),
+            );
+            if item["isTotal"].as_bool().unwrap_or(false) {
+                out += &format!("| **{}** | **{}** | **{}** |\n", label, current, previous);
+            } else {
+                out += &format!("| {} | {} | {} |\n", label, current, previous);
+            }
+        }
+        out += "\n";
+    }
+    if document.items.iter().any(item_edits::is_manual) {
+        // Escaped, or the leading asterisk starts a list
+        out += &format!("{}\n\n", item_edits::MANUAL_FOOTNOTE.replace('*', "\\*"));
+    }
+    if !notes.is_empty() {
+        out += "## Notes\n\n";
+        for note in notes {
+            let about = notes::item_label(note, &document.items).unwrap_or_else(|| "Document".to_string());
+            out += &format!("- **{}**: {}\n", markdown_cell(&about), note.body.replace('\n', " "));
+        }
+        out += "\n";
+    }
+
+    let chart = serde_json::to_string_pretty(&chart_data(document)).unwrap_or_default();
+    out += &format!("## Chart Data\n\n```json\n{}\n```\n", chart);
+    out
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+const HTML_STYLE: &str = "
+body { font-family: -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; color: #21262f; max-width: 960px; margin: 40px auto; padding: 0 24px; }
+h1 { color: #2a579e; margin-bottom: 4px; }
+h2 { color: #2a579e; border-bottom: 2px solid #2a579e; padding-bottom: 4px; margin-top: 36px; }
+.meta { color: #737a87; }
+table { border-collapse: collapse; width: 100%; font-size: 14px; }
+th { text-align: left; color: #737a87; font-weight: 600; padding: 6px 8px; border-bottom: 1px solid #d0d5dd; }
+td { padding: 5px 8px; border-bottom: 1px solid #eef0f3; }
+td.num, th.num { text-align: right; font-variant-numeric: tabular-nums; }
+tr.header td { font-weight: 600; background: #f6f7f9; }
+tr.total td { font-weight: 600; background: #eff2f7; }
+.chart { display: flex; align-items: flex-end; gap: 24px; height: 200px; border-bottom: 1px solid #737a87; padding-top: 12px; }
+.figure { flex: 1; display: flex; flex-direction: column; align-items: center; height: 100%; justify-content: flex-end; }
+.bars { display: flex; align-items: flex-end; gap: 4px; height: 100%; }
+.bar { width: 28px; }
+.bar.previous { background: #9ebae0; }
+.bar.current { background: #2a579e; }
+.labels { display: flex; gap: 24px; font-size: 12px; margin-top: 6px; }
+.labels div { flex: 1; text-align: center; }
+";
+
+fn analysis_html(document: &StoredDocument, fmt: &Formatter, notes: &[Note]) -> String {
+    let title = html_escape(&document.filename);
+    let mut body = format!(
+        "<h1>Financial Analysis Report</h1>\n<p class=\"meta\">{} &middot; Generated {} &middot; {} line items</p>\n",
+        title,
+        fmt.date(&chrono::Local::now()),
+        document.items.len()
+    );
+
+    let ratios = ratios::for_document(document);
+    if !ratios.is_empty() {
+        body += "<h2>Key Ratios</h2>\n<table>\n<tr><th>Ratio</th><th>Category</th><th class=\"num\">Current Year</th><th class=\"num\">Previous Year</th></tr>\n";
+        for ratio in &ratios {
+            body += &format!(
+                "<tr><td>{}</td><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>\n",
+                ratio.name,
+                ratio.category.replace('_', " "),
+                fmt.ratio(ratio.current, &ratio.unit),
+                fmt.ratio(ratio.previous, &ratio.unit),
+            );
+        }
+        body += "</table>\n";
+    }
+
+    let figures = report::chart_figures(&document.items);
+    if !figures.is_empty() {
+        let max = figures.iter()
+            .flat_map(|(_, current, previous)| [current.abs(), previous.abs()])
+            .fold(0.0_f64, f64::max)
+            .max(1.0);
+        body += "<h2>Key Figures</h2>\n<div class=\"chart\">\n";
+        for (_, current, previous) in &figures {
+            body += &format!(
+                "<div class=\"figure\"><div class=\"bars\"><div class=\"bar previous\" style=\"height:{:.1}%\" title=\"Previous year: {}\"></div><div class=\"bar current\" style=\"height:{:.1}%\" title=\"Current year: {}\"></div></div></div>\n",
+                previous.abs() / max * 100.0,
+                fmt.figure(*previous),
+                current.abs() / max * 100.0,
+                fmt.figure(*current),
+            );
+        }
+        body += "</div>\n<div class=\"labels\">";
+        for (name, current, _) in &figures {
+            body += &format!("<div><strong>{}</strong><br>{}</div>", name, html_escape(&fmt.figure(*current)));
+        }
+        body += "</div>\n";
+    }
+
+    for (title, items) in statement_sections(document) {
+        body += &format!(
+            "<h2>{}</h2>\n<table>\n<tr><th>Particulars</th><th class=\"num\">{}</th><th class=\"num\">{}</th></tr>\n",
+            title,
+            html_escape(&fmt.amount_heading("Current Year")),
+            html_escape(&fmt.amount_heading("Previous Year")),
+        );
+        for item in items {
+            let label = html_escape(&item_edits::display_label(item));
+            if item["isHeader"].as_bool().unwrap_or(false) {
+                body += &format!("<tr class=\"header\"><td colspan=\"3\">{}</td></tr>\n", label);
+                continue;
+            }
+            let class = if item["isTotal"].as_bool().unwrap_or(false) { " class=\"total\"" } else { "" };
+            body += &format!(
+                "<tr{}><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>\n",
+                class,
+                label,
+                fmt.amount(item["currentYear"].as_f64().unwrap_or(0.0)),
+                fmt.amount(item["previousYear"].as_f64().unwrap_or(0.0)),
+            );
+        }
+        body += "</table>\n";
+    }
+    if document.items.iter().any(item_edits::is_manual) {
+        body += &format!("<p class=\"meta\">{}</p>\n", html_escape(item_edits::MANUAL_FOOTNOTE));
+    }
+    if !notes.is_empty() {
+        body += "<h2>Notes</h2>\n<ul>\n";
+        for note in notes {
+            let about = notes::item_label(note, &document.items).unwrap_or_else(|| "Document".to_string());
+            body += &format!("<li><strong>{}</strong>: {}</li>\n", html_escape(&about), html_escape(&note.body).replace('\n', "<br>"));
+        }
+        body += "</ul>\n";
+    }
+
+    // `</` can't appear inside a script element
+    let chart = chart_data(document).to_string().replace("</", "<\\/");
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n{}<script type=\"application/json\" id=\"chart-data\">{}</script>\n</body>\n</html>\n",
+        title, HTML_STYLE, body, chart
+    )
+}
+
+/// What to copy: a document's items (optionally only some ids or one statement), or its ratios.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "table", rename_all = "snake_case")]
+pub enum ClipboardTable {
+    #[serde(rename_all = "camelCase")]
+    Items {
+        document_id: i64,
+        #[serde(default)]
+        item_ids: Vec<String>,
+        statement_type: Option<String>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Ratios { document_id: i64 },
+}
+
+/// Header plus rows; TSV keeps raw numbers so Excel parses them, Markdown formats them.
+fn clipboard_rows(app: &AppHandle, table: &ClipboardTable, raw_numbers: bool) -> Result<(Vec<&'static str>, Vec<Vec<String>>), String> {
+    let fmt = Formatter::for_app(app);
+    let amount = |value: Option<f64>| match value {
+        Some(v) if raw_numbers => v.to_string(),
+        Some(v) => fmt.amount(v),
+        None => String::new(),
+    };
+    match table {
+        ClipboardTable::Items { document_id, item_ids, statement_type } => {
+            let document = statements::load_document(app, *document_id)?;
+            let rows = document.items.iter()
+                .filter(|item| item_ids.is_empty() || item_ids.iter().any(|id| item["id"].as_str() == Some(id)))
+                .filter(|item| statement_type.as_deref().is_none_or(|s| report::statement_of(item) == s.to_lowercase()))
+                .map(|item| vec![
+                    item["label"].as_str().unwrap_or("").to_string(),
+                    amount(item["currentYear"].as_f64()),
+                    amount(item["previousYear"].as_f64()),
+                    item["statementType"].as_str().unwrap_or("").to_string(),
+                ])
+                .collect();
+            Ok((vec!["Particulars", "Current Year", "Previous Year", "Statement"], rows))
+        }
+        ClipboardTable::Ratios { document_id } => {
+            let document = statements::load_document(app, *document_id)?;
+            let rows = ratios::for_document(&document).into_iter()
+                .map(|ratio| {
+                    let value = |v: Option<f64>| if raw_numbers { v.map(|v| v.to_string()).unwrap_or_default() } else { fmt.ratio(v, &ratio.unit) };
+                    vec![ratio.name.clone(), value(ratio.current), value(ratio.previous), ratio.unit.clone()]
+                })
+                .collect();
+            Ok((vec!["Ratio", "Current Year", "Previous Year", "Unit"], rows))
+        }
+    }
+}
+
+fn render_tsv(header: &[&str], rows: &[Vec<String>]) -> String {
+    // Tabs and newlines inside a cell would split it across columns or rows
+    let clean = |cell: &str| cell.replace(['\t', '\n', '\r'], " ");
+    std::iter::once(header.join("\t"))
+        .chain(rows.iter().map(|row| row.iter().map(|c| clean(c)).collect::<Vec<_>>().join("\t")))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn render_markdown_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    // First column is text, the rest are right-aligned figures
+    let alignment: Vec<&str> = (0..header.len()).map(|i| if i == 0 { "---" } else { "---:" }).collect();
+    let mut out = format!("| {} |\n|{}|\n", header.join(" | "), alignment.join("|"));
+    for row in rows {
+        out += &format!("| {} |\n", row.iter().map(|c| markdown_cell(c)).collect::<Vec<_>>().join(" | "));
+    }
+    out
+}
+
+fn write_xlsx(app: &AppHandle, document_id: i64, path: String, template: Option<&TemplateRef>) -> Result<String, String> {
+    let template = report_templates::resolve(app, template)?;
+    let mut document = statements::load_document(app, document_id)?;
+    if document.items.is_empty() {
+        return Err(format!("Document {} has no extracted items to export", document_id));
+    }
+    let fmt = Formatter::for_document(app, &mut document, &template.number_style)?;
+    build_workbook(&document, &fmt, &path).map_err(|e| format!("Failed to write workbook: {}", e))?;
+    eprintln!("[Export] Wrote {} ({} items)", path, document.items.len());
+    Ok(path)
+}
+
+fn write_export(app: &AppHandle, document_id: i64, format: String, path: String, include_notes: bool) -> Result<String, String> {
+    let mut document = statements::load_document(app, document_id)?;
+    let fmt = Formatter::for_document(app, &mut document, &NumberStyle::default())?;
+    let notes = if include_notes { notes::for_document(app, document_id)? } else { Vec::new() };
+    let content = match format.to_lowercase().as_str() {
+        "json" => serde_json::to_string_pretty(&analysis_json(&document, &notes)).map_err(|e| e.to_string())?,
+        "csv" => analysis_csv(&document),
+        "markdown" | "md" => analysis_markdown(&document, &fmt, &notes),
+        "html" => analysis_html(&document, &fmt, &notes),
+        other => return Err(format!("Unsupported export format: {}", other)),
+    };
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    eprintln!("[Export] Wrote {} as {} ({} items)", path, format, document.items.len());
+    Ok(path)
+}
+
+// Tauri Commands
+/// `template` picks the number style (grouping, scale, unit suffix); its sections are not used.
+#[tauri::command]
+pub async fn export_analysis_xlsx(
+    app: AppHandle,
+    document_id: i64,
+    path: String,
+    template: Option<TemplateRef>,
+) -> Result<String, String> {
+    let path = usage::measure(&app, UsageKind::Export, || write_xlsx(&app, document_id, path, template.as_ref()))?;
+    activity::record(&app, ActivityKind::ReportGenerated, Some(document_id), &format!("Exported {}", path), None);
+    Ok(path)
+}
+
+/// `include_notes` adds the user's notes on the document and its items (JSON, Markdown, HTML).
+#[tauri::command]
+pub async fn export_analysis(
+    app: AppHandle,
+    document_id: i64,
+    format: String,
+    path: String,
+    include_notes: Option<bool>,
+) -> Result<String, String> {
+    let path = usage::measure(&app, UsageKind::Export, || write_export(&app, document_id, format, path, include_notes.unwrap_or(false)))?;
+    activity::record(&app, ActivityKind::ReportGenerated, Some(document_id), &format!("Exported {}", path), None);
+    Ok(path)
+}
+
+#[tauri::command]
+pub async fn copy_table_to_clipboard(app: AppHandle, table: ClipboardTable, format: Option<String>) -> Result<usize, String> {
+    let format = format.unwrap_or_else(|| "tsv".to_string()).to_lowercase();
+    let (header, rows) = clipboard_rows(&app, &table, format == "tsv")?;
+    let text = match format.as_str() {
+        "tsv" => render_tsv(&header, &rows),
+        "markdown" | "md" => render_markdown_table(&header, &rows),
+        other => return Err(format!("Unsupported clipboard format: {}", other)),
+    };
+    app.clipboard().write_text(text).map_err(|e| format!("Failed to write clipboard: {}", e))?;
+    Ok(rows.len())
+}
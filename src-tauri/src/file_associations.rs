@@ -0,0 +1,229 @@
+// File Associations - optional per-user registration that lists the app under "Open with" for
+// PDFs and spreadsheets; the opened files reach the job queue through `open_with`
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::settings::{self, SettingsState};
+
+/// Extensions offered to the OS, with their MIME types for the Linux desktop entry.
+const FILE_TYPES: &[(&str, &str)] = &[
+    ("pdf", "application/pdf"),
+    ("xlsx", "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+    ("xls", "application/vnd.ms-excel"),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssociationStatus {
+    /// The user's choice, kept in settings
+    pub enabled: bool,
+    /// Whether the OS currently lists this executable
+    pub registered: bool,
+    /// False where the installer owns the association (macOS reads it from the app bundle)
+    pub supported: bool,
+    pub extensions: Vec<String>,
+}
+
+/// The launcher the OS should run: on Linux an AppImage's own path, not its mounted binary.
+fn executable() -> Result<PathBuf, String> {
+    if let Some(appimage) = std::env::var_os("APPIMAGE").filter(|p| !p.is_empty()) {
+        return Ok(PathBuf::from(appimage));
+    }
+    std::env::current_exe().map_err(|e| format!("Cannot find the app executable: {}", e))
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+
+    const DESKTOP_FILE: &str = "financial-calculator-open-with.desktop";
+
+    fn desktop_file(app: &AppHandle) -> Result<PathBuf, String> {
+        let data_dir = app.path().data_dir().map_err(|e| format!("Failed to get data dir: {}", e))?;
+        Ok(data_dir.join("applications").join(DESKTOP_FILE))
+    }
+
+    fn entry(exe: &Path) -> String {
+        let mime_types: String = FILE_TYPES.iter().map(|(_, mime)| format!("{};", mime)).collect();
+        format!(
+            "[Desktop Entry]\nType=Application\nName=Financial Calculator\nExec=\"{}\" %F\nMimeType={}\nNoDisplay=true\nTerminal=false\n",
+            exe.display(),
+            mime_types
+        )
+    }
+
+    /// Lets file managers pick up the new entry; they rescan eventually without it.
+    fn refresh_database(dir: &Path) {
+        if let Err(e) = std::process::Command::new("update-desktop-database").arg(dir).status() {
+            warn!("update-desktop-database not run: {}", e);
+        }
+    }
+
+    pub fn register(app: &AppHandle, exe: &Path) -> Result<(), String> {
+        let path = desktop_file(app)?;
+        let dir = path.parent().ok_or("Invalid applications directory")?;
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        settings::write_atomic(&path, entry(exe).as_bytes())?;
+        refresh_database(dir);
+        Ok(())
+    }
+
+    pub fn unregister(app: &AppHandle) -> Result<(), String> {
+        let path = desktop_file(app)?;
+        if path.exists() {
+            std::fs::remove_file(&path).map_err(|e| e.to_string())?;
+        }
+        if let Some(dir) = path.parent() {
+            refresh_database(dir);
+        }
+        Ok(())
+    }
+
+    pub fn is_registered(app: &AppHandle, exe: &Path) -> bool {
+        desktop_file(app)
+            .and_then(|path| std::fs::read_to_string(path).map_err(|e| e.to_string()))
+            .is_ok_and(|contents| contents == entry(exe))
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::*;
+    use std::os::windows::process::CommandExt;
+
+    const PROG_ID: &str = "FinancialCalculator.Document";
+    // Keeps reg.exe from flashing a console window
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    fn reg(args: &[&str]) -> Result<bool, String> {
+        std::process::Command::new("reg")
+            .args(args)
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .map(|output| output.status.success())
+            .map_err(|e| format!("Failed to run reg: {}", e))
+    }
+
+    fn command_key() -> String {
+        format!(r"HKCU\Software\Classes\{}\shell\open\command", PROG_ID)
+    }
+
+    fn command(exe: &Path) -> String {
+        format!("\"{}\" \"%1\"", exe.display())
+    }
+
+    pub fn register(_app: &AppHandle, exe: &Path) -> Result<(), String> {
+        let prog_key = format!(r"HKCU\Software\Classes\{}", PROG_ID);
+        let command = command(exe);
+        let mut steps = vec![
+            vec!["add".to_string(), prog_key, "/ve".into(), "/d".into(), "Financial report".into(), "/f".into()],
+            vec!["add".to_string(), command_key(), "/ve".into(), "/d".into(), command, "/f".into()],
+        ];
+        // OpenWithProgids adds an entry to "Open with" without taking over the default app
+        for (ext, _) in FILE_TYPES {
+            steps.push(vec![
+                "add".to_string(),
+                format!(r"HKCU\Software\Classes\.{}\OpenWithProgids", ext),
+                "/v".into(),
+                PROG_ID.into(),
+                "/t".into(),
+                "REG_NONE".into(),
+                "/f".into(),
+            ]);
+        }
+        for step in steps {
+            let args: Vec<&str> = step.iter().map(String::as_str).collect();
+            if !reg(&args)? {
+                return Err(format!("Failed to write registry key {}", step[1]));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn unregister(_app: &AppHandle) -> Result<(), String> {
+        for (ext, _) in FILE_TYPES {
+            let key = format!(r"HKCU\Software\Classes\.{}\OpenWithProgids", ext);
+            reg(&["delete", &key, "/v", PROG_ID, "/f"])?;
+        }
+        reg(&["delete", &format!(r"HKCU\Software\Classes\{}", PROG_ID), "/f"])?;
+        Ok(())
+    }
+
+    pub fn is_registered(_app: &AppHandle, exe: &Path) -> bool {
+        std::process::Command::new("reg")
+            .args(["query", &command_key(), "/ve"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .is_ok_and(|output| output.status.success() && String::from_utf8_lossy(&output.stdout).contains(&command(exe)))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+mod platform {
+    use super::*;
+
+    pub fn register(_app: &AppHandle, _exe: &Path) -> Result<(), String> {
+        Err("File types on this platform come from the installed app bundle".to_string())
+    }
+
+    pub fn unregister(app: &AppHandle) -> Result<(), String> {
+        register(app, Path::new(""))
+    }
+
+    pub fn is_registered(_app: &AppHandle, _exe: &Path) -> bool {
+        true
+    }
+}
+
+fn enabled(app: &AppHandle) -> bool {
+    let state = app.state::<SettingsState>();
+    let enabled = settings::blocking_read(&state).get().file_associations;
+    enabled
+}
+
+/// Re-register at startup when enabled, so the entry follows the executable after an update or
+/// a move.
+pub fn refresh_if_enabled(app: &AppHandle) {
+    if !cfg!(any(target_os = "linux", target_os = "windows")) || !enabled(app) {
+        return;
+    }
+    let refreshed = executable().and_then(|exe| {
+        if platform::is_registered(app, &exe) { Ok(()) } else { platform::register(app, &exe) }
+    });
+    if let Err(e) = refreshed {
+        warn!("Failed to refresh file associations: {}", e);
+    }
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn get_file_associations(app: AppHandle) -> Result<AssociationStatus, String> {
+    let exe = executable()?;
+    Ok(AssociationStatus {
+        enabled: enabled(&app),
+        registered: platform::is_registered(&app, &exe),
+        supported: cfg!(any(target_os = "linux", target_os = "windows")),
+        extensions: FILE_TYPES.iter().map(|(ext, _)| ext.to_string()).collect(),
+    })
+}
+
+/// List the app under "Open with" for PDFs and spreadsheets, or remove it. Registration is per
+/// user and never changes the default application.
+#[tauri::command]
+pub async fn set_file_associations(app: AppHandle, enabled: bool) -> Result<AssociationStatus, String> {
+    if enabled {
+        platform::register(&app, &executable()?)?;
+    } else {
+        platform::unregister(&app)?;
+    }
+    {
+        let state = app.state::<SettingsState>();
+        let mut store = state.write().await;
+        store.set_file_associations(enabled);
+        store.save()?;
+    }
+    info!(enabled, "File associations changed");
+    get_file_associations(app)
+}
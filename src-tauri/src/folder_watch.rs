@@ -0,0 +1,162 @@
+// Folder Watch - auto-import PDFs and spreadsheets dropped into watched folders
+use notify::event::{EventKind, ModifyKind};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Manager};
+
+use crate::app_lock;
+use crate::jobs::{self, DocumentKind, JobSource};
+use crate::settings::{self, SettingsState};
+
+// A copy in progress keeps growing; wait this long between size checks
+const SETTLE_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_SETTLE_CHECKS: u32 = 120;
+
+pub struct FolderWatcher {
+    watchers: Mutex<HashMap<PathBuf, RecommendedWatcher>>,
+}
+
+impl FolderWatcher {
+    pub fn new() -> Self {
+        Self { watchers: Mutex::new(HashMap::new()) }
+    }
+}
+
+/// Wait until the file stops growing so we don't import half-copied documents.
+fn wait_until_settled(path: &Path) -> bool {
+    let mut last_size = None;
+    for _ in 0..MAX_SETTLE_CHECKS {
+        let size = std::fs::metadata(path).map(|m| m.len()).ok();
+        if size.is_none() {
+            return false;
+        }
+        if size == last_size && size != Some(0) {
+            return true;
+        }
+        last_size = size;
+        std::thread::sleep(SETTLE_INTERVAL);
+    }
+    false
+}
+
+fn start_watching(app: &AppHandle, folder: &Path) -> Result<RecommendedWatcher, String> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(|e| e.to_string())?;
+    watcher.watch(folder, RecursiveMode::NonRecursive).map_err(|e| e.to_string())?;
+
+    let app = app.clone();
+    let folder_name = folder.display().to_string();
+    std::thread::spawn(move || {
+        // Create and rename-in both fire, sometimes more than once; import each version once
+        let mut imported: HashSet<(PathBuf, Option<SystemTime>)> = HashSet::new();
+
+        // Ends when the watcher is dropped by unwatch_folder
+        while let Ok(event) = rx.recv() {
+            let Ok(event) = event else { continue };
+            if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(_))) {
+                continue;
+            }
+            for path in event.paths {
+                if DocumentKind::detect(&path).is_none() || !path.is_file() || !wait_until_settled(&path) {
+                    continue;
+                }
+                let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+                if !imported.insert((path.clone(), modified)) {
+                    continue;
+                }
+                // Files dropped in while locked are queued once it's unlocked
+                app_lock::block_until_unlocked(&app);
+                if let Err(e) = jobs::enqueue(&app, &path.to_string_lossy(), JobSource::WatchedFolder) {
+                    eprintln!("[FolderWatch] Could not queue {}: {}", path.display(), e);
+                }
+            }
+        }
+        eprintln!("[FolderWatch] Stopped watching {}", folder_name);
+    });
+
+    Ok(watcher)
+}
+
+fn save_watched(app: &AppHandle, folders: Vec<String>) -> Result<(), String> {
+    let state = app.state::<SettingsState>();
+    let mut store = settings::blocking_write(&state);
+    store.set_watched_folders(folders);
+    store.save()
+}
+
+fn watched_list(watcher: &FolderWatcher) -> Result<Vec<String>, String> {
+    let watchers = watcher.watchers.lock().map_err(|e| e.to_string())?;
+    let mut folders: Vec<String> = watchers.keys().map(|p| p.display().to_string()).collect();
+    folders.sort();
+    Ok(folders)
+}
+
+/// Resume watching the folders saved in settings.
+pub fn restore(app: &AppHandle) {
+    let folders = {
+        let state = app.state::<SettingsState>();
+        let store = settings::blocking_read(&state);
+        store.get().watched_folders.clone()
+    };
+    let watcher = app.state::<FolderWatcher>();
+    for folder in folders {
+        let path = PathBuf::from(&folder);
+        match start_watching(app, &path) {
+            Ok(w) => {
+                if let Ok(mut watchers) = watcher.watchers.lock() {
+                    watchers.insert(path, w);
+                }
+                eprintln!("[FolderWatch] Watching {}", folder);
+            }
+            Err(e) => eprintln!("[FolderWatch] Cannot watch {}: {}", folder, e),
+        }
+    }
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn watch_folder(
+    app: AppHandle,
+    watcher: tauri::State<'_, FolderWatcher>,
+    path: String,
+) -> Result<Vec<String>, String> {
+    let folder = std::fs::canonicalize(&path).map_err(|e| format!("Cannot watch {}: {}", path, e))?;
+    if !folder.is_dir() {
+        return Err(format!("Not a folder: {}", path));
+    }
+    {
+        let mut watchers = watcher.watchers.lock().map_err(|e| e.to_string())?;
+        if !watchers.contains_key(&folder) {
+            watchers.insert(folder.clone(), start_watching(&app, &folder)?);
+            eprintln!("[FolderWatch] Watching {}", folder.display());
+        }
+    }
+    let folders = watched_list(&watcher)?;
+    save_watched(&app, folders.clone())?;
+    Ok(folders)
+}
+
+#[tauri::command]
+pub fn unwatch_folder(
+    app: AppHandle,
+    watcher: tauri::State<'_, FolderWatcher>,
+    path: String,
+) -> Result<Vec<String>, String> {
+    let folder = std::fs::canonicalize(&path).unwrap_or_else(|_| PathBuf::from(&path));
+    {
+        let mut watchers = watcher.watchers.lock().map_err(|e| e.to_string())?;
+        // Dropping the watcher closes its channel, which ends the event thread
+        watchers.remove(&folder);
+    }
+    let folders = watched_list(&watcher)?;
+    save_watched(&app, folders.clone())?;
+    Ok(folders)
+}
+
+#[tauri::command]
+pub fn list_watched_folders(watcher: tauri::State<'_, FolderWatcher>) -> Result<Vec<String>, String> {
+    watched_list(&watcher)
+}
@@ -0,0 +1,244 @@
+// Formatting - numbers and dates for exports and reports in the user's conventions: Indian
+// (1,23,45,678) or Western (12,345,678) grouping from `number_format`, separators and date order
+// from `language`, and for document figures the currency and scale they are in ("₹ 12.50 Cr")
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::numbers::Unit;
+use crate::settings::{self, SettingsState};
+use crate::statements::StoredDocument;
+use crate::units;
+
+// Languages written in India; "auto" groups their numbers in lakhs and crores
+const INDIAN_LANGUAGES: &[&str] = &["hi", "mr", "gu", "ta", "te", "kn", "bn", "ml", "pa", "or", "as", "ur"];
+// "1.234.567,89"
+const DOT_GROUPING: &[&str] = &["de", "es", "it", "pt", "nl", "id", "tr", "da"];
+// "1 234 567,89"
+const SPACE_GROUPING: &[&str] = &["fr", "ru", "pl", "sv", "nb", "fi", "cs", "uk"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Grouping {
+    /// Thousands, then every two digits: 1,23,45,678
+    Indian,
+    /// Every three digits: 12,345,678
+    Western,
+}
+
+/// How a report template writes amounts; anything unset follows the settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NumberStyle {
+    /// "indian", "western" or "auto", as `number_format` in settings
+    #[serde(default)]
+    pub number_format: Option<String>,
+    /// Scale to present figures in ("lakh", "crore", "million", "units"); the configured
+    /// `units.scale` if unset
+    #[serde(default)]
+    pub scale: Option<String>,
+    /// Mark figures with their currency and scale ("₹ Cr"); on with Indian grouping if unset
+    #[serde(default)]
+    pub unit_suffix: Option<bool>,
+}
+
+impl NumberStyle {
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(format) = self.number_format.as_deref() {
+            if !matches!(format, "auto" | "indian" | "western") {
+                return Err(format!("Unknown number format: {}", format));
+            }
+        }
+        if let Some(scale) = self.scale.as_deref() {
+            units::parse_scale(scale)?;
+        }
+        Ok(())
+    }
+}
+
+fn currency_symbol(currency: &str) -> String {
+    match currency.to_uppercase().as_str() {
+        "INR" => "₹".to_string(),
+        "USD" => "$".to_string(),
+        "EUR" => "€".to_string(),
+        "GBP" => "£".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Formatter {
+    grouping: Grouping,
+    group_separator: &'static str,
+    decimal_separator: char,
+    date_format: &'static str,
+    datetime_format: &'static str,
+    /// Figures are in lakhs, crores, ...; amounts keep two decimals
+    scale: Option<Unit>,
+    /// Currency symbol and scale abbreviation amounts are marked with, when the style asks for it
+    unit_label: Option<(String, Option<&'static str>)>,
+}
+
+impl Formatter {
+    /// `language` as a tag ("en", "en-IN", "de"); `number_format` is "indian", "western" or
+    /// "auto", which groups the Indian way for Indian languages and regions.
+    pub fn new(language: &str, number_format: &str) -> Self {
+        let language = language.trim().to_lowercase().replace('_', "-");
+        let (base, region) = language.split_once('-').unwrap_or((&language, ""));
+        let grouping = match number_format.trim().to_lowercase().as_str() {
+            "indian" => Grouping::Indian,
+            "western" => Grouping::Western,
+            _ if region == "in" || INDIAN_LANGUAGES.contains(&base) => Grouping::Indian,
+            _ => Grouping::Western,
+        };
+        let (group_separator, decimal_separator) = if DOT_GROUPING.contains(&base) {
+            (".", ',')
+        } else if SPACE_GROUPING.contains(&base) {
+            (" ", ',')
+        } else {
+            (",", '.')
+        };
+        // Month names from chrono are English, so other languages get numeric dates
+        let (date_format, datetime_format) = match (base, region) {
+            ("en", "us") => ("%b %d, %Y", "%b %d, %Y %I:%M %p"),
+            ("en", _) if grouping == Grouping::Indian => ("%d %b %Y", "%d %b %Y %H:%M"),
+            ("en", _) => ("%d %B %Y", "%d %B %Y %H:%M"),
+            ("de" | "ru" | "pl" | "fi" | "cs" | "uk" | "tr" | "da" | "nb", _) => ("%d.%m.%Y", "%d.%m.%Y %H:%M"),
+            ("ja" | "zh" | "ko" | "sv", _) => ("%Y-%m-%d", "%Y-%m-%d %H:%M"),
+            _ => ("%d/%m/%Y", "%d/%m/%Y %H:%M"),
+        };
+        Self { grouping, group_separator, decimal_separator, date_format, datetime_format, scale: None, unit_label: None }
+    }
+
+    pub fn for_app(app: &AppHandle) -> Self {
+        let state = app.state::<SettingsState>();
+        let store = settings::blocking_read(&state);
+        Self::new(&store.get().language, &store.get().number_format)
+    }
+
+    /// For a loaded document's figures under `style`: rescales the document when the style picks
+    /// another scale, and labels amounts with the currency and scale they end up in.
+    pub fn for_document(app: &AppHandle, document: &mut StoredDocument, style: &NumberStyle) -> Result<Self, String> {
+        let (language, number_format) = {
+            let state = app.state::<SettingsState>();
+            let store = settings::blocking_read(&state);
+            (store.get().language.clone(), store.get().number_format.clone())
+        };
+        let mut fmt = Self::new(&language, style.number_format.as_deref().unwrap_or(&number_format));
+        if let Some(scale) = style.scale.as_deref() {
+            units::present_in(document, units::parse_scale(scale)?);
+        }
+        let (currency, scale) = units::presented(document);
+        fmt.scale = scale;
+        if style.unit_suffix.unwrap_or(fmt.grouping == Grouping::Indian) {
+            fmt.unit_label = Some((currency_symbol(&currency), scale.map(Unit::abbreviation)));
+        }
+        Ok(fmt)
+    }
+
+    fn group(&self, whole: &str) -> String {
+        let digits: Vec<char> = whole.chars().collect();
+        let mut grouped = String::new();
+        for (i, c) in digits.iter().enumerate() {
+            let remaining = digits.len() - i;
+            let boundary = match self.grouping {
+                Grouping::Western => remaining.is_multiple_of(3),
+                Grouping::Indian => remaining == 3 || (remaining > 3 && (remaining - 3).is_multiple_of(2)),
+            };
+            if i > 0 && boundary {
+                grouped.push_str(self.group_separator);
+            }
+            grouped.push(*c);
+        }
+        grouped
+    }
+
+    /// The magnitude of `value`, grouped, to `decimals` places.
+    fn magnitude(&self, value: f64, decimals: usize) -> String {
+        let formatted = format!("{:.*}", decimals, value.abs());
+        match formatted.split_once('.') {
+            Some((whole, fraction)) => format!("{}{}{}", self.group(whole), self.decimal_separator, fraction),
+            None => self.group(&formatted),
+        }
+    }
+
+    /// Grouped, with a leading minus for negatives.
+    pub fn number(&self, value: f64, decimals: usize) -> String {
+        let magnitude = self.magnitude(value, decimals);
+        // Rounding can leave "-0.00"
+        if value < 0.0 && magnitude.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            format!("-{}", magnitude)
+        } else {
+            magnitude
+        }
+    }
+
+    /// Statement amounts: whole numbers from a thousand up (two decimals in lakhs, crores and
+    /// the like), accounting-style negatives.
+    pub fn amount(&self, value: f64) -> String {
+        let decimals = if self.scale.is_some_and(|s| s != Unit::Thousand) || (value.abs() < 1000.0 && value.fract() != 0.0) { 2 } else { 0 };
+        let magnitude = self.magnitude(value, decimals);
+        if value < 0.0 { format!("({})", magnitude) } else { magnitude }
+    }
+
+    /// An amount standing on its own, marked with its units when the style asks: "₹ 1,234.50 Cr".
+    pub fn figure(&self, value: f64) -> String {
+        match &self.unit_label {
+            Some((symbol, Some(suffix))) => format!("{} {} {}", symbol, self.amount(value), suffix),
+            Some((symbol, None)) => format!("{} {}", symbol, self.amount(value)),
+            None => self.amount(value),
+        }
+    }
+
+    /// A column heading over amounts, with their units when the style asks: "Current Year (₹ Cr)".
+    pub fn amount_heading(&self, heading: &str) -> String {
+        match &self.unit_label {
+            Some((symbol, Some(suffix))) => format!("{} ({} {})", heading, symbol, suffix),
+            Some((symbol, None)) => format!("{} ({})", heading, symbol),
+            None => heading.to_string(),
+        }
+    }
+
+    pub fn ratio(&self, value: Option<f64>, unit: &str) -> String {
+        match value {
+            Some(v) if unit == "%" => format!("{}%", self.number(v, 1)),
+            Some(v) => format!("{}x", self.number(v, 2)),
+            None => "-".to_string(),
+        }
+    }
+
+    pub fn date(&self, time: &DateTime<Local>) -> String {
+        time.format(self.date_format).to_string()
+    }
+
+    pub fn datetime(&self, time: &DateTime<Local>) -> String {
+        time.format(self.datetime_format).to_string()
+    }
+
+    /// A Unix timestamp in local time; "-" when it's out of range.
+    pub fn timestamp(&self, secs: i64) -> String {
+        DateTime::from_timestamp(secs, 0)
+            .map(|t| self.datetime(&t.with_timezone(&Local)))
+            .unwrap_or_else(|| "-".to_string())
+    }
+
+    /// Excel number format for amounts. Excel draws the separators from the reader's own locale,
+    /// so only the grouping is chosen here; Indian grouping needs conditional sections, which
+    /// leaves negatives with a minus sign rather than parentheses. Every section carries the unit
+    /// label when the style asks for one.
+    pub fn excel_amount_format(&self) -> String {
+        let (prefix, suffix) = match &self.unit_label {
+            Some((symbol, suffix)) => (format!("\"{} \"", symbol), suffix.map(|s| format!("\" {}\"", s)).unwrap_or_default()),
+            None => (String::new(), String::new()),
+        };
+        let mark = |pattern: &str| format!("{}{}{}", prefix, pattern, suffix);
+        match self.grouping {
+            Grouping::Western => format!("{};({})", mark("#,##0.00"), mark("#,##0.00")),
+            Grouping::Indian => format!(
+                "[>=10000000]{};[>=100000]{};{}",
+                mark("##\\,##\\,##\\,##0.00"),
+                mark("##\\,##\\,##0.00"),
+                mark("##,##0.00"),
+            ),
+        }
+    }
+}
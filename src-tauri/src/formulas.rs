@@ -0,0 +1,253 @@
+// Formulas - user-authored metric formulas and post-processors compiled to WebAssembly and run
+// in a wasmtime sandbox. A formula sees only the host API below: no files, network or clock.
+//
+// Imports (module "fincalc"):
+//   item_count() -> i32
+//   find_item(label_ptr: i32, label_len: i32) -> i32      index, or -1 (case-insensitive label)
+//   item_current(index: i32) -> f64                        NaN when missing
+//   item_previous(index: i32) -> f64                       NaN when missing
+//   emit(key_ptr: i32, key_len: i32, current: f64, previous: f64)   write a derived value
+// Exports: `memory` and `run()`.
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+use wasmtime::{Caller, Config, Engine, Extern, Linker, Module, Store, StoreLimits, StoreLimitsBuilder};
+
+use crate::periods;
+use crate::statements;
+
+const HOST_MODULE: &str = "fincalc";
+// Roughly a few hundred million simple instructions; plenty for arithmetic over a few hundred items
+const FUEL: u64 = 200_000_000;
+const MAX_MEMORY_BYTES: usize = 16 << 20;
+const MAX_OUTPUTS: usize = 1_000;
+const MAX_KEY_LEN: usize = 128;
+const MAX_LABEL_LEN: usize = 512;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormulaInfo {
+    pub name: String,
+    /// "wat" (text) or "wasm" (binary)
+    pub format: String,
+    pub size: u64,
+    pub modified_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DerivedValue {
+    pub key: String,
+    pub current: Option<f64>,
+    pub previous: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormulaResult {
+    pub name: String,
+    pub document_id: i64,
+    pub values: Vec<DerivedValue>,
+    pub fuel_used: u64,
+}
+
+struct FormulaItem {
+    label: String,
+    current: Option<f64>,
+    previous: Option<f64>,
+}
+
+struct Sandbox {
+    items: Vec<FormulaItem>,
+    outputs: Vec<DerivedValue>,
+    limits: StoreLimits,
+}
+
+fn formulas_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?.join("formulas");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+fn valid_name(name: &str) -> Result<&str, String> {
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(format!("Formula name \"{}\" may only use letters, digits, '-' and '_'", name));
+    }
+    Ok(name)
+}
+
+/// The stored module for `name`; text takes precedence over binary if both exist.
+fn formula_path(app: &AppHandle, name: &str) -> Result<PathBuf, String> {
+    let dir = formulas_dir(app)?;
+    let name = valid_name(name)?;
+    ["wat", "wasm"]
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", name, ext)))
+        .find(|path| path.is_file())
+        .ok_or_else(|| format!("No formula {}", name))
+}
+
+fn engine() -> Result<Engine, String> {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    Engine::new(&config).map_err(|e| e.to_string())
+}
+
+fn normalize(label: &str) -> String {
+    label.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn finite(value: f64) -> Option<f64> {
+    value.is_finite().then_some(value)
+}
+
+fn read_str(caller: &mut Caller<'_, Sandbox>, ptr: i32, len: i32) -> wasmtime::Result<String> {
+    let Some(Extern::Memory(memory)) = caller.get_export("memory") else {
+        return Err(wasmtime::format_err!("formula must export its memory"));
+    };
+    let (start, len) = (ptr as u32 as usize, len as u32 as usize);
+    if len > MAX_LABEL_LEN {
+        return Err(wasmtime::format_err!("string of {} bytes is too long", len));
+    }
+    let bytes = memory
+        .data(&caller)
+        .get(start..start + len)
+        .ok_or_else(|| wasmtime::format_err!("string out of bounds"))?;
+    Ok(String::from_utf8_lossy(bytes).to_string())
+}
+
+fn host_api(engine: &Engine) -> wasmtime::Result<Linker<Sandbox>> {
+    let mut linker = Linker::new(engine);
+    linker.func_wrap(HOST_MODULE, "item_count", |caller: Caller<'_, Sandbox>| caller.data().items.len() as i32)?;
+    linker.func_wrap(HOST_MODULE, "find_item", |mut caller: Caller<'_, Sandbox>, ptr: i32, len: i32| -> wasmtime::Result<i32> {
+        let label = normalize(&read_str(&mut caller, ptr, len)?);
+        let items = &caller.data().items;
+        let index = items.iter().position(|item| item.label == label)
+            .or_else(|| items.iter().position(|item| item.label.starts_with(&label)));
+        Ok(index.map(|i| i as i32).unwrap_or(-1))
+    })?;
+    linker.func_wrap(HOST_MODULE, "item_current", |caller: Caller<'_, Sandbox>, index: i32| {
+        caller.data().items.get(index as usize).and_then(|item| item.current).unwrap_or(f64::NAN)
+    })?;
+    linker.func_wrap(HOST_MODULE, "item_previous", |caller: Caller<'_, Sandbox>, index: i32| {
+        caller.data().items.get(index as usize).and_then(|item| item.previous).unwrap_or(f64::NAN)
+    })?;
+    linker.func_wrap(HOST_MODULE, "emit", |mut caller: Caller<'_, Sandbox>, ptr: i32, len: i32, current: f64, previous: f64| -> wasmtime::Result<()> {
+        let key = read_str(&mut caller, ptr, len)?;
+        if key.is_empty() || key.len() > MAX_KEY_LEN {
+            return Err(wasmtime::format_err!("output key must be 1-{} bytes", MAX_KEY_LEN));
+        }
+        let outputs = &mut caller.data_mut().outputs;
+        if outputs.len() >= MAX_OUTPUTS {
+            return Err(wasmtime::format_err!("more than {} outputs", MAX_OUTPUTS));
+        }
+        // Emitting a key again replaces the earlier value
+        outputs.retain(|o| o.key != key);
+        outputs.push(DerivedValue { key, current: finite(current), previous: finite(previous) });
+        Ok(())
+    })?;
+    Ok(linker)
+}
+
+/// Compile `source` (WAT text or a wasm binary) and run it over `items`.
+pub fn evaluate(source: &[u8], items: &[serde_json::Value]) -> Result<(Vec<DerivedValue>, u64), String> {
+    let engine = engine()?;
+    let module = Module::new(&engine, source).map_err(|e| format!("Invalid formula: {}", e))?;
+    let items = items.iter()
+        .filter(|item| !item["isHeader"].as_bool().unwrap_or(false))
+        .map(|item| {
+            let (current, previous) = periods::amounts(item);
+            FormulaItem { label: normalize(item["label"].as_str().unwrap_or("")), current, previous }
+        })
+        .collect();
+    let limits = StoreLimitsBuilder::new()
+        .memory_size(MAX_MEMORY_BYTES)
+        .instances(1)
+        .memories(1)
+        .tables(1)
+        .build();
+    let mut store = Store::new(&engine, Sandbox { items, outputs: Vec::new(), limits });
+    store.limiter(|sandbox| &mut sandbox.limits);
+    store.set_fuel(FUEL).map_err(|e| e.to_string())?;
+
+    let linker = host_api(&engine).map_err(|e| e.to_string())?;
+    let instance = linker.instantiate(&mut store, &module).map_err(|e| format!("Formula failed to start: {}", e))?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")
+        .map_err(|_| "Formula must export a `run` function taking no arguments".to_string())?;
+    run.call(&mut store, ()).map_err(|e| format!("Formula failed: {}", e))?;
+
+    let fuel_used = FUEL - store.get_fuel().unwrap_or(0);
+    Ok((store.into_data().outputs, fuel_used))
+}
+
+fn modified_secs(metadata: &fs::Metadata) -> i64 {
+    metadata.modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0))
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn list_formulas(app: AppHandle) -> Result<Vec<FormulaInfo>, String> {
+    let dir = formulas_dir(&app)?;
+    let mut formulas: Vec<FormulaInfo> = fs::read_dir(&dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let format = path.extension()?.to_str()?.to_lowercase();
+            if format != "wat" && format != "wasm" {
+                return None;
+            }
+            let metadata = entry.metadata().ok()?;
+            Some(FormulaInfo {
+                name: path.file_stem()?.to_string_lossy().to_string(),
+                format,
+                size: metadata.len(),
+                modified_at: modified_secs(&metadata),
+            })
+        })
+        .collect();
+    formulas.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(formulas)
+}
+
+/// Save WAT source; it must compile, so mistakes surface here rather than on first run.
+#[tauri::command]
+pub fn save_formula(app: AppHandle, name: String, source: String) -> Result<FormulaInfo, String> {
+    let name = valid_name(&name)?.to_string();
+    Module::new(&engine()?, source.as_bytes()).map_err(|e| format!("Invalid formula: {}", e))?;
+    let dir = formulas_dir(&app)?;
+    let path = dir.join(format!("{}.wat", name));
+    fs::write(&path, &source).map_err(|e| e.to_string())?;
+    // A stale binary of the same name would otherwise be shadowed silently
+    let _ = fs::remove_file(dir.join(format!("{}.wasm", name)));
+    info!(formula = %name, "Saved formula");
+    let metadata = fs::metadata(&path).map_err(|e| e.to_string())?;
+    Ok(FormulaInfo { name, format: "wat".to_string(), size: metadata.len(), modified_at: modified_secs(&metadata) })
+}
+
+#[tauri::command]
+pub fn delete_formula(app: AppHandle, name: String) -> Result<(), String> {
+    let path = formula_path(&app, &name)?;
+    fs::remove_file(path).map_err(|e| e.to_string())
+}
+
+/// Run a saved formula over a document's items and return the values it derived.
+#[tauri::command]
+pub async fn run_formula(app: AppHandle, name: String, document_id: i64) -> Result<FormulaResult, String> {
+    let path = formula_path(&app, &name)?;
+    let source = fs::read(&path).map_err(|e| e.to_string())?;
+    let document = statements::load_document(&app, document_id)?;
+    let (values, fuel_used) = tauri::async_runtime::spawn_blocking(move || evaluate(&source, &document.items))
+        .await
+        .map_err(|e| e.to_string())?
+        .inspect_err(|e| warn!(formula = %name, "{}", e))?;
+    Ok(FormulaResult { name, document_id, values, fuel_used })
+}
@@ -0,0 +1,209 @@
+// FX rates - closing and average exchange rates for each fiscal period, downloaded from the ECB
+// reference rates or set by hand, for translating foreign statements at the rates of the
+// periods they report rather than at today's
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use tracing::info;
+
+use crate::db;
+use crate::periods::Period;
+use crate::scraper::{self, NativeScraper};
+use crate::settings::{self, SettingsState};
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS fx_period_rates (
+    currency TEXT NOT NULL,        -- ISO 4217, the currency translated from
+    presentation TEXT NOT NULL,    -- ISO 4217, the currency translated into
+    period TEXT NOT NULL,          -- 'FY2024', 'Q3-FY24'
+    closing REAL NOT NULL,         -- presentation units per unit of currency at period end
+    average REAL NOT NULL,         -- the same, averaged over the period's trading days
+    source TEXT NOT NULL,          -- 'manual' or where it was downloaded from
+    updated_at INTEGER NOT NULL,
+    PRIMARY KEY (currency, presentation, period)
+);
+";
+
+// Frankfurter serves the ECB's daily reference rates: a day, or every day in a range
+const FRANKFURTER_URL: &str = "https://api.frankfurter.app";
+pub const SOURCE: &str = "ECB reference rates (Frankfurter)";
+pub const MANUAL: &str = "manual";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeriodRates {
+    pub currency: String,
+    pub presentation_currency: String,
+    pub period: Period,
+    /// Presentation units per unit of `currency` on the period's last day
+    pub closing: f64,
+    /// Over the period
+    pub average: f64,
+    pub source: String,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn normalize_currency(currency: &str) -> Result<String, String> {
+    let currency = currency.trim().to_uppercase();
+    if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(format!("Currency must be an ISO code such as USD or INR, not {}", currency));
+    }
+    Ok(currency)
+}
+
+/// `presentation`, else the currency documents are converted to in the units settings.
+pub fn resolve_presentation(app: &AppHandle, presentation: Option<&str>) -> Result<String, String> {
+    match presentation {
+        Some(currency) => normalize_currency(currency),
+        None => {
+            let state = app.state::<SettingsState>();
+            let currency = settings::blocking_read(&state).get().units.currency.clone();
+            normalize_currency(&currency)
+        }
+    }
+}
+
+fn stored(conn: &Connection, currency: &str, presentation: &str, period: Period) -> Result<Option<PeriodRates>, String> {
+    conn.query_row(
+        "SELECT closing, average, source FROM fx_period_rates WHERE currency = ?1 AND presentation = ?2 AND period = ?3",
+        params![currency, presentation, period.to_string()],
+        |row| Ok(PeriodRates {
+            currency: currency.to_string(),
+            presentation_currency: presentation.to_string(),
+            period,
+            closing: row.get(0)?,
+            average: row.get(1)?,
+            source: row.get(2)?,
+        }),
+    ).optional().map_err(|e| e.to_string())
+}
+
+fn store(conn: &Connection, rates: &PeriodRates) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO fx_period_rates (currency, presentation, period, closing, average, source, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![rates.currency, rates.presentation_currency, rates.period.to_string(), rates.closing, rates.average, rates.source, now_secs()],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// The rate into `presentation` from a Frankfurter `{"rates": {...}}` body, for one day or
+/// every day of a range.
+fn parse_rates(body: &serde_json::Value, presentation: &str) -> Vec<f64> {
+    let rates = &body["rates"];
+    if let Some(rate) = rates[presentation].as_f64() {
+        return vec![rate];
+    }
+    rates.as_object().into_iter().flatten()
+        .filter_map(|(_, day)| day[presentation].as_f64())
+        .collect()
+}
+
+async fn download(app: &AppHandle, currency: &str, presentation: &str, period: Period) -> Result<PeriodRates, String> {
+    let (start, end) = period.dates();
+    if end >= chrono::Local::now().date_naive() {
+        return Err(format!("{} hasn't ended yet; set its {} rates by hand", period, currency));
+    }
+    let timeout = scraper::request_timeout(app);
+    let client = app.state::<NativeScraper>();
+    let range = format!("{}/{}..{}?from={}&to={}", FRANKFURTER_URL, start, end, currency, presentation);
+    let days = parse_rates(&client.get_json(&range, timeout).await?, presentation);
+    // The single-day endpoint falls back to the last business day on or before the date
+    let last_day = format!("{}/{}?from={}&to={}", FRANKFURTER_URL, end, currency, presentation);
+    let closing = parse_rates(&client.get_json(&last_day, timeout).await?, presentation).first().copied();
+    let (Some(closing), false) = (closing, days.is_empty()) else {
+        return Err(format!("No ECB rates from {} to {} for {}", currency, presentation, period));
+    };
+    Ok(PeriodRates {
+        currency: currency.to_string(),
+        presentation_currency: presentation.to_string(),
+        period,
+        closing,
+        average: days.iter().sum::<f64>() / days.len() as f64,
+        source: SOURCE.to_string(),
+    })
+}
+
+/// Closing and average rates from `currency` into `presentation` for `period`: the stored ones,
+/// else downloaded and stored. Past periods' rates don't change, so stored ones never go stale.
+pub async fn period_rates(app: &AppHandle, currency: &str, presentation: &str, period: Period) -> Result<PeriodRates, String> {
+    let (currency, presentation) = (normalize_currency(currency)?, normalize_currency(presentation)?);
+    if currency == presentation {
+        return Ok(PeriodRates { currency, presentation_currency: presentation, period, closing: 1.0, average: 1.0, source: "identity".to_string() });
+    }
+    if let Some(rates) = stored(&db::open_app_db(app)?, &currency, &presentation, period)? {
+        return Ok(rates);
+    }
+    settings::ensure_online(app)?;
+    let rates = download(app, &currency, &presentation, period).await?;
+    store(&db::open_app_db(app)?, &rates)?;
+    info!(currency, presentation, period = %period, closing = rates.closing, average = rates.average, "Cached FX rates");
+    Ok(rates)
+}
+
+fn parse_period(period: &str) -> Result<Period, String> {
+    Period::parse(period).ok_or_else(|| format!("Unknown period: {}", period))
+}
+
+// Tauri Commands
+/// Rates from `currency` into the presentation currency (the configured one by default) for
+/// each of `periods`, downloading those not yet stored.
+#[tauri::command]
+pub async fn get_fx_rates(
+    app: AppHandle,
+    currency: String,
+    presentation_currency: Option<String>,
+    periods: Vec<String>,
+) -> Result<Vec<PeriodRates>, String> {
+    let presentation = resolve_presentation(&app, presentation_currency.as_deref())?;
+    let mut rates = Vec::new();
+    for period in periods {
+        rates.push(period_rates(&app, &currency, &presentation, parse_period(&period)?).await?);
+    }
+    Ok(rates)
+}
+
+/// Set a period's rates by hand, e.g. for a currency the ECB doesn't publish or a period not
+/// yet ended. They take the place of any downloaded ones.
+#[tauri::command]
+pub fn set_fx_rates(
+    app: AppHandle,
+    currency: String,
+    presentation_currency: Option<String>,
+    period: String,
+    closing: f64,
+    average: f64,
+) -> Result<PeriodRates, String> {
+    if !(closing > 0.0 && average > 0.0 && closing.is_finite() && average.is_finite()) {
+        return Err("Rates must be positive".to_string());
+    }
+    let rates = PeriodRates {
+        currency: normalize_currency(&currency)?,
+        presentation_currency: resolve_presentation(&app, presentation_currency.as_deref())?,
+        period: parse_period(&period)?,
+        closing,
+        average,
+        source: MANUAL.to_string(),
+    };
+    store(&db::open_app_db(&app)?, &rates)?;
+    info!(currency = rates.currency, period = %rates.period, "Set FX rates");
+    Ok(rates)
+}
+
+/// Forget a period's rates; downloaded ones are fetched again when next needed.
+#[tauri::command]
+pub fn delete_fx_rates(app: AppHandle, currency: String, presentation_currency: Option<String>, period: String) -> Result<(), String> {
+    let presentation = resolve_presentation(&app, presentation_currency.as_deref())?;
+    db::open_app_db(&app)?.execute(
+        "DELETE FROM fx_period_rates WHERE currency = ?1 AND presentation = ?2 AND period = ?3",
+        params![normalize_currency(&currency)?, presentation, parse_period(&period)?.to_string()],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
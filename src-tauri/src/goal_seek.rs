@@ -0,0 +1,216 @@
+// Goal Seek - solves for the line item change or DCF input that brings a metric (a ratio, a
+// metric formula, free cash flow or a DCF value) to a target, by bracketing then bisection
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tracing::info;
+
+use crate::dcf::DcfInputs;
+use crate::scenarios::{self, Adjustment};
+use crate::sensitivity::{Input, Output};
+use crate::statements::{self, StoredDocument};
+
+// Points tried across the bounds when looking for a bracket
+const GRID: usize = 60;
+const MAX_ITERATIONS: usize = 100;
+const TOLERANCE: f64 = 1e-9;
+// How near the target, relative to it, the answer must land; a sign change across a pole
+// (a denominator passing through zero) narrows to a bracket without getting any nearer
+const CONVERGENCE: f64 = 1e-6;
+
+/// What the solver may change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Variable {
+    /// Percent change to a line's current amount, carried to totals and profit as in a scenario
+    #[serde(rename_all = "camelCase")]
+    LineItem { label: String, statement_type: Option<String> },
+    /// A DCF input, in its own units
+    DcfInput { input: Input },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GoalSeekResult {
+    pub document_id: i64,
+    pub target_metric: String,
+    pub target_value: f64,
+    pub variable: Variable,
+    /// The variable as reported: 0 for a line item's percent change
+    pub base_variable: f64,
+    pub base_metric: Option<f64>,
+    pub solution: f64,
+    /// The metric at `solution`; matches the target to within float noise
+    pub achieved: f64,
+    pub iterations: usize,
+}
+
+fn output(metric: &str) -> Option<Output> {
+    match metric {
+        "enterprise_value" => Some(Output::EnterpriseValue),
+        "equity_value" => Some(Output::EquityValue),
+        "value_per_share" => Some(Output::ValuePerShare),
+        _ => None,
+    }
+}
+
+/// Bounds searched when none are given: -100% to +500% for a line item, plausible ranges for
+/// rates, and ten times either side of the base for amounts.
+fn default_bounds(variable: &Variable, base: f64) -> Result<(f64, f64), String> {
+    Ok(match variable {
+        Variable::LineItem { .. } => (-100.0, 500.0),
+        Variable::DcfInput { input: Input::GrowthRate } => (-50.0, 100.0),
+        Variable::DcfInput { input: Input::DiscountRate } => (0.0, 100.0),
+        Variable::DcfInput { input: Input::TerminalGrowthRate } => (-10.0, 20.0),
+        Variable::DcfInput { input } if base == 0.0 => return Err(format!("Give bounds to search {:?} in", input)),
+        Variable::DcfInput { .. } => (base - 10.0 * base.abs(), base + 10.0 * base.abs()),
+    })
+}
+
+struct Problem<'a> {
+    app: &'a AppHandle,
+    document: &'a StoredDocument,
+    metric: &'a str,
+    variable: &'a Variable,
+    dcf: Option<&'a DcfInputs>,
+}
+
+impl Problem<'_> {
+    /// The metric with the variable at `x`; None where it isn't defined there.
+    fn evaluate(&self, x: f64) -> Result<Option<f64>, String> {
+        let mut inputs = self.dcf.cloned();
+        let adjusted;
+        let document = match self.variable {
+            Variable::LineItem { label, statement_type } => {
+                let adjustment = Adjustment {
+                    label: label.clone(),
+                    statement_type: statement_type.clone(),
+                    percent: Some(x),
+                    amount: None,
+                };
+                let (items, _, _) = scenarios::adjust(self.document, &[adjustment])?;
+                adjusted = StoredDocument { items, ..self.document.clone() };
+                &adjusted
+            }
+            Variable::DcfInput { input } => {
+                if let Some(inputs) = inputs.as_mut() {
+                    input.set(inputs, x);
+                }
+                self.document
+            }
+        };
+
+        if let Some(output) = output(self.metric) {
+            // An invalid DCF at this point (e.g. discount at or below terminal growth) is a gap
+            // in the curve, not a failure of the search
+            return Ok(scenarios::valuation(self.app, document, inputs.as_ref()).ok().flatten().and_then(|v| output.of(&v)));
+        }
+        Ok(scenarios::metrics(self.app, document)?.into_iter().find(|m| m.key == self.metric).and_then(|m| m.current))
+    }
+}
+
+// Tauri Commands
+/// Find the value of `variable` at which `target_metric` equals `target_value`. Metrics are the
+/// ratio and metric formula keys, "free_cash_flow", or, given `dcf` inputs, "enterprise_value",
+/// "equity_value" and "value_per_share". Where several values hit the target, the one closest
+/// to the document as reported is returned.
+#[tauri::command]
+pub async fn goal_seek(
+    app: AppHandle,
+    document_id: i64,
+    target_metric: String,
+    target_value: f64,
+    variable: Variable,
+    dcf: Option<DcfInputs>,
+    bounds: Option<(f64, f64)>,
+) -> Result<GoalSeekResult, String> {
+    if !target_value.is_finite() {
+        return Err("Target value must be a number".to_string());
+    }
+    let metric = target_metric.trim().to_lowercase();
+    if output(&metric).is_some() && dcf.is_none() {
+        return Err(format!("Seeking {} needs DCF inputs", metric));
+    }
+    if matches!(variable, Variable::DcfInput { .. }) && (output(&metric).is_none() || dcf.is_none()) {
+        return Err("A DCF input only moves enterprise value, equity value or value per share".to_string());
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let document = statements::load_document(&app, document_id)?;
+        let problem = Problem { app: &app, document: &document, metric: &metric, variable: &variable, dcf: dcf.as_ref() };
+        let base_variable = match &variable {
+            Variable::LineItem { .. } => 0.0,
+            Variable::DcfInput { input: Input::BaseCashFlow } if dcf.as_ref().is_some_and(|d| d.base_cash_flow.is_none()) => {
+                scenarios::valuation(&app, &document, dcf.as_ref())?.map(|v| v.base_cash_flow).unwrap_or(0.0)
+            }
+            Variable::DcfInput { input } => dcf.as_ref().map(|d| input.get(d)).unwrap_or(0.0),
+        };
+        let base_metric = problem.evaluate(base_variable)?;
+        let (low, high) = match bounds {
+            Some((low, high)) if low.is_finite() && high.is_finite() && low < high => (low, high),
+            Some(_) => return Err("Bounds must run from low to high".to_string()),
+            None => default_bounds(&variable, base_variable)?,
+        };
+
+        // Bracket: adjacent grid points either side of the target, nearest the base
+        let mut points = Vec::with_capacity(GRID + 1);
+        for i in 0..=GRID {
+            let x = low + (high - low) * i as f64 / GRID as f64;
+            points.push((x, problem.evaluate(x)?.map(|v| v - target_value)));
+        }
+        let bracket = points.windows(2)
+            .filter_map(|pair| match (pair[0], pair[1]) {
+                ((a, Some(fa)), (b, Some(fb))) if fa.signum() != fb.signum() || fa == 0.0 => Some((a, fa, b)),
+                _ => None,
+            })
+            .min_by(|x, y| ((x.0 + x.2) / 2.0 - base_variable).abs().total_cmp(&((y.0 + y.2) / 2.0 - base_variable).abs()));
+        let Some((mut a, mut fa, mut b)) = bracket else {
+            let reached: Vec<f64> = points.iter().filter_map(|(_, v)| v.map(|v| v + target_value)).collect();
+            let (min, max) = reached.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| (lo.min(*v), hi.max(*v)));
+            return Err(if reached.is_empty() {
+                format!("{} could not be computed between {} and {}", metric, low, high)
+            } else {
+                format!("{} only ranges from {:.4} to {:.4} between {} and {}; widen the bounds", metric, min, max, low, high)
+            });
+        };
+
+        let mut iterations = 0;
+        let (mut solution, mut achieved) = (a, fa + target_value);
+        while iterations < MAX_ITERATIONS && fa != 0.0 {
+            iterations += 1;
+            let mid = (a + b) / 2.0;
+            let Some(fm) = problem.evaluate(mid)? else { break };
+            (solution, achieved) = (mid, fm + target_value);
+            if fm.abs() <= TOLERANCE * (1.0 + target_value.abs()) || (b - a).abs() <= TOLERANCE * (1.0 + mid.abs()) {
+                break;
+            }
+            if fm.signum() == fa.signum() {
+                (a, fa) = (mid, fm);
+            } else {
+                b = mid;
+            }
+        }
+
+        let converged = (achieved - target_value).abs() <= CONVERGENCE * (1.0 + target_value.abs());
+        if !converged {
+            return Err(format!(
+                "The search did not converge: {} reaches {:.4} at {}, not {}; the metric may jump across the target there",
+                metric, achieved, solution, target_value
+            ));
+        }
+
+        info!(document_id, metric = %metric, solution, iterations, "Goal seek solved");
+        Ok(GoalSeekResult {
+            document_id,
+            target_metric: metric,
+            target_value,
+            variable,
+            base_variable,
+            base_metric,
+            solution,
+            achieved,
+            iterations,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
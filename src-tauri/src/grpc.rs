@@ -0,0 +1,255 @@
+// gRPC - optional tonic service (proto/fincalc.proto) mirroring the core commands for
+// pipelines that embed the calculator; shares the import, item and ratio code with the UI
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+use tracing::{error, info, warn};
+
+use crate::api_server;
+use crate::app_lock;
+use crate::cli;
+use crate::plugins;
+use crate::ratios::{self, Ratio};
+use crate::report;
+use crate::settings::{self, GrpcSettings, SettingsState};
+use crate::statements::{self, StoredDocument};
+
+pub mod proto {
+    tonic::include_proto!("fincalc.v1");
+}
+
+use proto::financial_calculator_server::{FinancialCalculator, FinancialCalculatorServer};
+
+struct Service {
+    app: AppHandle,
+}
+
+struct Running {
+    port: u16,
+    shutdown: oneshot::Sender<()>,
+}
+
+pub struct GrpcServer {
+    running: Mutex<Option<Running>>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GrpcStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub port: u16,
+    /// e.g. "127.0.0.1:50051" while running
+    pub address: Option<String>,
+}
+
+fn settings(app: &AppHandle) -> GrpcSettings {
+    let state = app.state::<SettingsState>();
+    let grpc = settings::blocking_read(&state).get().grpc.clone();
+    grpc
+}
+
+fn to_proto(ratio: Ratio) -> proto::Ratio {
+    proto::Ratio {
+        key: ratio.key,
+        name: ratio.name,
+        category: ratio.category,
+        unit: ratio.unit,
+        current: ratio.current,
+        previous: ratio.previous,
+    }
+}
+
+impl Service {
+    fn document(&self, document_id: Option<i64>) -> Result<StoredDocument, Status> {
+        let document_id = match document_id {
+            Some(id) => id,
+            None => statements::latest_document_id(&self.app).map_err(Status::not_found)?,
+        };
+        statements::load_document(&self.app, document_id).map_err(Status::not_found)
+    }
+}
+
+#[tonic::async_trait]
+impl FinancialCalculator for Service {
+    async fn analyze(&self, request: Request<proto::AnalyzeRequest>) -> Result<Response<proto::AnalyzeReply>, Status> {
+        let path = std::path::PathBuf::from(request.into_inner().path.trim());
+        if !path.is_file() {
+            return Err(Status::invalid_argument(format!("File not found: {}", path.display())));
+        }
+        info!(path = %path.display(), "gRPC analyze request");
+        let document_id = cli::import(&self.app, &path).await.map_err(Status::failed_precondition)?;
+        let document = self.document(Some(document_id))?;
+        Ok(Response::new(proto::AnalyzeReply {
+            document_id,
+            item_count: document.items.len() as u32,
+            ratios: ratios::for_document(&document).into_iter().map(to_proto).collect(),
+            filename: document.filename,
+        }))
+    }
+
+    async fn list_documents(&self, _request: Request<proto::ListDocumentsRequest>) -> Result<Response<proto::ListDocumentsReply>, Status> {
+        let documents = statements::list_documents(&self.app).map_err(Status::internal)?;
+        Ok(Response::new(proto::ListDocumentsReply {
+            documents: documents.into_iter()
+                .map(|d| proto::Document { id: d.id, filename: d.filename, processed_at: d.processed_at, item_count: d.item_count as u32 })
+                .collect(),
+        }))
+    }
+
+    async fn query(&self, request: Request<proto::QueryRequest>) -> Result<Response<proto::QueryReply>, Status> {
+        let request = request.into_inner();
+        let document = self.document(request.document_id)?;
+        let statement = request.statement.map(|s| s.to_lowercase());
+        let search = request.search.map(|s| s.to_lowercase());
+        let items = document.items.iter()
+            .filter(|item| statement.as_ref().is_none_or(|s| report::statement_of(item) == *s))
+            .filter(|item| search.as_ref().is_none_or(|s| item["label"].as_str().unwrap_or("").to_lowercase().contains(s)))
+            .map(|item| proto::Item {
+                label: item["label"].as_str().unwrap_or("").to_string(),
+                statement: report::statement_of(item),
+                current: item["currentYear"].as_f64(),
+                previous: item["previousYear"].as_f64(),
+                is_header: item["isHeader"].as_bool().unwrap_or(false),
+                json: item.to_string(),
+            })
+            .collect();
+        Ok(Response::new(proto::QueryReply { document_id: document.id, filename: document.filename, items }))
+    }
+
+    async fn calculate(&self, request: Request<proto::CalculateRequest>) -> Result<Response<proto::CalculateReply>, Status> {
+        let request = request.into_inner();
+        let document = self.document(request.document_id)?;
+        let mut ratios = ratios::for_document(&document);
+        if request.include_plugins {
+            let app = self.app.clone();
+            let items = document.items.clone();
+            let extra = tauri::async_runtime::spawn_blocking(move || plugins::compute_metrics(&app, &items))
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?;
+            ratios.extend(extra);
+        }
+        Ok(Response::new(proto::CalculateReply {
+            document_id: document.id,
+            filename: document.filename,
+            ratios: ratios.into_iter().map(to_proto).collect(),
+        }))
+    }
+}
+
+impl GrpcServer {
+    pub fn new() -> Self {
+        Self { running: Mutex::new(None) }
+    }
+
+    fn port(&self) -> Option<u16> {
+        self.running.lock().ok().and_then(|running| running.as_ref().map(|r| r.port))
+    }
+
+    pub async fn start(&self, app: &AppHandle, port: u16) -> Result<(), String> {
+        self.stop();
+        let listener = TcpListener::bind(("127.0.0.1", port))
+            .await
+            .map_err(|e| format!("Could not listen on 127.0.0.1:{}: {}", port, e))?;
+        let (shutdown, stopped) = oneshot::channel::<()>();
+
+        let auth_app = app.clone();
+        let service = FinancialCalculatorServer::with_interceptor(Service { app: app.clone() }, move |request: Request<()>| {
+            let given = request.metadata()
+                .get("authorization")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.strip_prefix("Bearer "))
+                .unwrap_or_default();
+            if api_server::token_accepted(&auth_app, given) {
+                Ok(request)
+            } else {
+                warn!("Rejected gRPC call with a missing or wrong token");
+                Err(Status::unauthenticated("Missing or invalid API token"))
+            }
+        });
+
+        tauri::async_runtime::spawn(async move {
+            let served = Server::builder()
+                .add_service(service)
+                .serve_with_incoming_shutdown(TcpIncoming::from(listener), async {
+                    let _ = stopped.await;
+                })
+                .await;
+            if let Err(e) = served {
+                error!("gRPC server stopped: {}", e);
+            }
+        });
+        if let Ok(mut running) = self.running.lock() {
+            *running = Some(Running { port, shutdown });
+        }
+        info!(port, "gRPC server listening on 127.0.0.1");
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        let running = self.running.lock().ok().and_then(|mut running| running.take());
+        if let Some(running) = running {
+            let _ = running.shutdown.send(());
+            info!(port = running.port, "gRPC server stopped");
+        }
+    }
+}
+
+/// Start the service at launch, or on unlocking, when it was left enabled.
+pub fn start_if_enabled(app: AppHandle) {
+    let config = settings(&app);
+    if !config.enabled || app_lock::is_locked(&app) {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = app.state::<GrpcServer>().start(&app, config.port).await {
+            error!("{}", e);
+        }
+    });
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn get_grpc_status(app: AppHandle, server: tauri::State<'_, GrpcServer>) -> GrpcStatus {
+    let config = settings(&app);
+    let running = server.port();
+    GrpcStatus {
+        enabled: config.enabled,
+        running: running.is_some(),
+        port: running.unwrap_or(config.port),
+        address: running.map(|port| format!("127.0.0.1:{}", port)),
+    }
+}
+
+/// Turn the gRPC service on or off and remember the choice. Clients authenticate with the
+/// local API token (`get_api_server_status`), which is generated here if it doesn't exist yet.
+#[tauri::command]
+pub async fn set_grpc_enabled(
+    app: AppHandle,
+    server: tauri::State<'_, GrpcServer>,
+    enabled: bool,
+    port: Option<u16>,
+) -> Result<GrpcStatus, String> {
+    api_server::ensure_token(&app)?;
+    let mut config = settings(&app);
+    config.enabled = enabled;
+    if let Some(port) = port {
+        config.port = port;
+    }
+    if enabled {
+        server.start(&app, config.port).await?;
+    } else {
+        server.stop();
+    }
+    {
+        let state = app.state::<SettingsState>();
+        let mut store = state.write().await;
+        store.set_grpc(config);
+        store.save()?;
+    }
+    Ok(get_grpc_status(app.clone(), server))
+}
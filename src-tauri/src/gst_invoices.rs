@@ -0,0 +1,594 @@
+// GST Invoices - tax invoices read in the `gst_invoice` analysis mode: GSTINs, invoice number
+// and date, taxable value, CGST/SGST/IGST/cess and HSN lines, kept in their own tables, with the
+// input tax credit they carry summed per period
+use chrono::NaiveDate;
+use lopdf::Document;
+use regex::Regex;
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+use tracing::info;
+
+use crate::activity::{self, ActivityKind};
+use crate::corporate_events::normalize_date;
+use crate::db;
+use crate::dividends::period_key;
+use crate::error::AppError;
+use crate::numbers;
+use crate::ocr;
+use crate::python_bridge::{PythonResponse, PDF_DECRYPTION_FAILED};
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS gst_invoices (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    file_name TEXT NOT NULL,
+    source_path TEXT NOT NULL,
+    invoice_number TEXT,
+    invoice_date TEXT,             -- YYYY-MM-DD
+    supplier_gstin TEXT,
+    recipient_gstin TEXT,
+    place_of_supply TEXT,          -- two-digit state code
+    taxable_value REAL NOT NULL,
+    cgst REAL NOT NULL,
+    sgst REAL NOT NULL,            -- SGST or UTGST
+    igst REAL NOT NULL,
+    cess REAL NOT NULL,
+    total REAL,
+    warnings TEXT NOT NULL,        -- JSON array of strings
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_gst_invoices_recipient ON gst_invoices(recipient_gstin, invoice_date);
+
+CREATE TABLE IF NOT EXISTS gst_invoice_lines (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    invoice_id INTEGER NOT NULL,
+    description TEXT NOT NULL,
+    hsn_code TEXT NOT NULL,
+    taxable_value REAL,
+    gst_rate REAL                  -- percent
+);
+CREATE INDEX IF NOT EXISTS idx_gst_invoice_lines_invoice ON gst_invoice_lines(invoice_id);
+";
+
+/// `options.document_type` that selects this mode in `run_python_analysis`
+pub const DOCUMENT_TYPE: &str = "gst_invoice";
+
+// Charged tax and the invoice total may differ by rounding off
+const TOTAL_TOLERANCE: f64 = 1.0;
+const GSTIN_CHARSET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvoiceLine {
+    pub description: String,
+    /// HSN for goods, SAC for services
+    pub hsn_code: String,
+    pub taxable_value: Option<f64>,
+    pub gst_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GstInvoice {
+    /// `None` until stored
+    pub id: Option<i64>,
+    pub file_name: String,
+    pub source_path: String,
+    pub invoice_number: Option<String>,
+    pub invoice_date: Option<String>,
+    pub supplier_gstin: Option<String>,
+    pub recipient_gstin: Option<String>,
+    /// State code; the recipient's when the invoice doesn't say
+    pub place_of_supply: Option<String>,
+    pub taxable_value: f64,
+    pub cgst: f64,
+    pub sgst: f64,
+    pub igst: f64,
+    pub cess: f64,
+    pub total: Option<f64>,
+    /// What didn't add up or couldn't be found; worth a look before claiming the credit
+    pub warnings: Vec<String>,
+    pub lines: Vec<InvoiceLine>,
+    pub created_at: Option<i64>,
+}
+
+impl GstInvoice {
+    pub fn tax(&self) -> f64 {
+        self.cgst + self.sgst + self.igst + self.cess
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputCreditPeriod {
+    /// "2026-10", "2026-Q3", "2026" or "FY2026-27"
+    pub period: String,
+    pub invoices: usize,
+    pub taxable_value: f64,
+    pub cgst: f64,
+    pub sgst: f64,
+    pub igst: f64,
+    pub cess: f64,
+    pub total_credit: f64,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Whether `options` ask for the invoice mode rather than a financial statement parse.
+pub fn requested(options: Option<&serde_json::Value>) -> bool {
+    options
+        .and_then(|o| o.get("document_type"))
+        .and_then(|v| v.as_str())
+        .is_some_and(|t| t.eq_ignore_ascii_case(DOCUMENT_TYPE))
+}
+
+/// The last character of a GSTIN is a base-36 check digit over the first fourteen.
+pub fn gstin_valid(gstin: &str) -> bool {
+    let bytes = gstin.as_bytes();
+    if bytes.len() != 15 {
+        return false;
+    }
+    let mut sum = 0;
+    for (i, c) in bytes[..14].iter().enumerate() {
+        let Some(value) = GSTIN_CHARSET.iter().position(|x| x == c) else { return false };
+        let product = value * if i % 2 == 0 { 1 } else { 2 };
+        sum += product / 36 + product % 36;
+    }
+    GSTIN_CHARSET[(36 - sum % 36) % 36] == bytes[14]
+}
+
+/// "1,23,456.00" style figures; whole numbers are left out so rates, quantities and HSN codes
+/// aren't read as amounts.
+fn amounts(text: &str) -> Vec<f64> {
+    let amount = Regex::new(r"\(?-?\d[\d,]*\.\d{2}\b\)?").expect("valid pattern");
+    amount.find_iter(text).filter_map(|m| numbers::normalize(m.as_str()).value).collect()
+}
+
+/// The figure on the lines labelled `label`: a "total" line when there is one, otherwise the
+/// lines added up (an invoice lists CGST once per rate).
+fn labelled_amount(lines: &[&str], label: &str) -> Option<f64> {
+    let label = Regex::new(&format!(r"(?i)\b(?:{})\b", label)).expect("valid pattern");
+    let found: Vec<(bool, f64)> = lines.iter()
+        .filter_map(|line| {
+            let at = label.find(line)?;
+            let value = *amounts(&line[at.end()..]).last()?;
+            Some((line.to_lowercase().contains("total"), value))
+        })
+        .collect();
+    match found.iter().rev().find(|(total, _)| *total) {
+        Some((_, value)) => Some(*value),
+        None if found.is_empty() => None,
+        None => Some(found.iter().map(|(_, value)| value).sum()),
+    }
+}
+
+fn parse_invoice_date(raw: &str) -> Option<String> {
+    let cleaned: String = raw.trim().replace(['.', '/', ','], "-").split_whitespace().collect::<Vec<_>>().join("-");
+    let cleaned = cleaned.replace("--", "-");
+    ["%d-%m-%Y", "%d-%m-%y", "%d-%b-%Y", "%d-%B-%Y", "%Y-%m-%d"].iter()
+        .find_map(|format| NaiveDate::parse_from_str(&cleaned, format).ok())
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .or_else(|| normalize_date(raw))
+}
+
+/// GSTINs as (supplier, recipient); one near "buyer", "bill to" and the like is the recipient.
+fn parties(lines: &[&str]) -> (Option<String>, Option<String>) {
+    let gstin = Regex::new(r"\b\d{2}[A-Z]{5}\d{4}[A-Z][1-9A-Z]Z[0-9A-Z]\b").expect("valid pattern");
+    let recipient_label = Regex::new(r"(?i)\b(buyer|recipient|bill(?:ed)?\s+to|ship(?:ped)?\s+to|consignee|customer|party)\b")
+        .expect("valid pattern");
+    let (mut supplier, mut recipient) = (None, None);
+    for (i, line) in lines.iter().enumerate() {
+        for found in gstin.find_iter(line) {
+            let context = format!("{} {}", if i > 0 { lines[i - 1] } else { "" }, &line[..found.start()]);
+            let slot = if recipient_label.is_match(&context) { &mut recipient } else { &mut supplier };
+            if slot.is_none() {
+                *slot = Some(found.as_str().to_string());
+            } else if recipient.is_none() && supplier.as_deref() != Some(found.as_str()) {
+                recipient = Some(found.as_str().to_string());
+            }
+        }
+    }
+    (supplier, recipient)
+}
+
+/// Table rows that carry an HSN/SAC code: a description, a 4, 6 or 8 digit code and amounts.
+fn invoice_lines(lines: &[&str]) -> Vec<InvoiceLine> {
+    let row = Regex::new(r"^\s*(?:\d{1,3}[.)]?\s+)?(.*?[A-Za-z].*?)\s+(\d{4}|\d{6}|\d{8})\s+(.*\d\.\d{2}.*)$").expect("valid pattern");
+    let rate = Regex::new(r"(\d{1,2}(?:\.\d+)?)\s*%").expect("valid pattern");
+    let labelled = Regex::new(r"(?i)\b(?:hsn|sac)(?:\s*/\s*sac)?(?:\s+code)?\s*[:\-]?\s*(\d{4}|\d{6}|\d{8})\b").expect("valid pattern");
+    let mut found: Vec<InvoiceLine> = lines.iter()
+        .filter_map(|line| {
+            let caps = row.captures(line)?;
+            let rest = &caps[3];
+            let gst_rate = rate.captures(rest).and_then(|c| c[1].parse().ok());
+            // Amounts after the rate are tax, so the taxable value is the last one before it
+            let before_rate = rate.find(rest).map_or(rest, |m| &rest[..m.start()]);
+            let taxable_value = amounts(before_rate).last().or(amounts(rest).last()).copied();
+            Some(InvoiceLine {
+                description: caps[1].trim().to_string(),
+                hsn_code: caps[2].to_string(),
+                taxable_value,
+                gst_rate,
+            })
+        })
+        .collect();
+    if found.is_empty() {
+        // Single-item invoices often give the code as "HSN/SAC: 998314" outside a table
+        found = lines.iter()
+            .filter_map(|line| labelled.captures(line))
+            .map(|caps| InvoiceLine { description: String::new(), hsn_code: caps[1].to_string(), taxable_value: None, gst_rate: None })
+            .collect();
+    }
+    found
+}
+
+/// Read an invoice from its text. Figures that can't be found are zero and noted in `warnings`.
+pub fn parse(text: &str, file_name: &str, source_path: &str) -> GstInvoice {
+    let lines: Vec<&str> = text.lines().map(str::trim_end).filter(|l| !l.trim().is_empty()).collect();
+    let mut warnings = Vec::new();
+
+    let number = Regex::new(r"(?i)\b(?:tax\s+)?inv(?:oice)?\.?\s*(?:no\b|number|#)\.?\s*[:\-]?\s*([A-Za-z0-9][A-Za-z0-9/\-]*)").expect("valid pattern");
+    let invoice_number = number.captures(text).map(|c| c[1].to_string());
+    let date = Regex::new(
+        r"(?i)\b(?:invoice\s+)?dated?\s*[:\-]?\s*(\d{1,2}[./\-]\d{1,2}[./\-]\d{2,4}|\d{1,2}[\s\-]*[A-Za-z]{3,9}[\s\-,]*\d{4}|\d{4}-\d{2}-\d{2})"
+    ).expect("valid pattern");
+    let invoice_date = date.captures(text).and_then(|c| parse_invoice_date(&c[1]));
+    let (supplier_gstin, recipient_gstin) = parties(&lines);
+    let place = Regex::new(r"(?i)place\s+of\s+supply[^\n]*?\b(\d{2})\b").expect("valid pattern");
+    let place_of_supply = place.captures(text)
+        .map(|c| c[1].to_string())
+        .or_else(|| recipient_gstin.as_ref().map(|g| g[..2].to_string()));
+
+    let cgst = labelled_amount(&lines, "cgst").unwrap_or(0.0);
+    let sgst = labelled_amount(&lines, "sgst|utgst").unwrap_or(0.0);
+    let igst = labelled_amount(&lines, "igst").unwrap_or(0.0);
+    let cess = labelled_amount(&lines, "cess").unwrap_or(0.0);
+    let tax = cgst + sgst + igst + cess;
+    let total = labelled_amount(&lines, r"grand\s+total|invoice\s+(?:total|value|amount)|total\s+amount|amount\s+payable|net\s+payable");
+    let taxable_value = labelled_amount(&lines, r"taxable\s+(?:value|amount)|sub\s*-?\s*total")
+        .or_else(|| total.map(|total| total - tax))
+        .unwrap_or(0.0);
+
+    if invoice_number.is_none() {
+        warnings.push("Invoice number not found".to_string());
+    }
+    if invoice_date.is_none() {
+        warnings.push("Invoice date not found; it is left out of the period totals".to_string());
+    }
+    match &supplier_gstin {
+        None => warnings.push("Supplier GSTIN not found".to_string()),
+        Some(gstin) if !gstin_valid(gstin) => warnings.push(format!("Supplier GSTIN {} fails its check digit", gstin)),
+        Some(_) => {}
+    }
+    match &recipient_gstin {
+        None => warnings.push("Recipient GSTIN not found; no input credit can be claimed without it".to_string()),
+        Some(gstin) if !gstin_valid(gstin) => warnings.push(format!("Recipient GSTIN {} fails its check digit", gstin)),
+        Some(_) => {}
+    }
+    if tax == 0.0 {
+        warnings.push("No CGST, SGST or IGST found".to_string());
+    }
+    if igst > 0.0 && cgst + sgst > 0.0 {
+        warnings.push("Both IGST and CGST/SGST are charged".to_string());
+    }
+    if (cgst - sgst).abs() > TOTAL_TOLERANCE {
+        warnings.push(format!("CGST ({:.2}) and SGST ({:.2}) differ", cgst, sgst));
+    }
+    if let (Some(supplier), Some(place)) = (&supplier_gstin, &place_of_supply) {
+        let intra_state = supplier[..2] == *place;
+        if intra_state && igst > 0.0 {
+            warnings.push("IGST charged on an intra-state supply".to_string());
+        } else if !intra_state && cgst + sgst > 0.0 {
+            warnings.push("CGST/SGST charged on an inter-state supply".to_string());
+        }
+    }
+    if let Some(total) = total {
+        if (taxable_value + tax - total).abs() > TOTAL_TOLERANCE {
+            warnings.push(format!("Taxable value plus tax ({:.2}) does not match the invoice total ({:.2})", taxable_value + tax, total));
+        }
+    }
+
+    GstInvoice {
+        id: None,
+        file_name: file_name.to_string(),
+        source_path: source_path.to_string(),
+        invoice_number,
+        invoice_date,
+        supplier_gstin,
+        recipient_gstin,
+        place_of_supply,
+        taxable_value,
+        cgst,
+        sgst,
+        igst,
+        cess,
+        total,
+        warnings,
+        lines: invoice_lines(&lines),
+        created_at: None,
+    }
+}
+
+/// Poppler's layout mode keeps table rows on one line; lopdf is the fallback when it isn't installed.
+fn pdf_text(app: &AppHandle, path: &str, password: Option<&str>) -> Result<String, String> {
+    if let Some(pdftotext) = ocr::find_tool(app, "pdftotext") {
+        let mut args = ocr::password_args(password);
+        args.extend(["-layout", path, "-"]);
+        return ocr::run(&pdftotext, &args);
+    }
+    let doc = match password {
+        Some(password) => Document::load_with_password(path, password).map_err(|e| match e {
+            lopdf::Error::InvalidPassword => format!("{}: Incorrect PDF password", PDF_DECRYPTION_FAILED),
+            e => format!("Failed to open PDF: {}", e),
+        })?,
+        None => Document::load(path).map_err(|e| format!("Failed to open PDF: {}", e))?,
+    };
+    let pages: Vec<u32> = doc.get_pages().into_keys().collect();
+    doc.extract_text(&pages).map_err(|e| format!("Failed to read PDF text: {}", e))
+}
+
+/// A short document's text (an invoice, a bank statement): text files as they are, PDFs through
+/// their text layer or, with `ocr_language`, Tesseract.
+pub fn document_text(app: &AppHandle, path: &str, password: Option<&str>, ocr_language: Option<&str>) -> Result<String, String> {
+    if !path.to_lowercase().ends_with(".pdf") {
+        return std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e));
+    }
+    match ocr_language {
+        Some(language) => {
+            let document = ocr::ocr_pdf(app, path, language, password)?;
+            // The searchable copy is decrypted
+            pdf_text(app, &document.path.to_string_lossy(), None)
+        }
+        None => pdf_text(app, path, password),
+    }
+}
+
+/// The PDF password and, when `ocr` is set, the OCR language from analysis options.
+pub fn text_options(options: Option<&serde_json::Value>) -> (Option<String>, Option<String>) {
+    let option = |key: &str| options.and_then(|o| o.get(key));
+    let password = option("password").and_then(|v| v.as_str()).filter(|p| !p.is_empty()).map(str::to_string);
+    let ocr_language = option("ocr").and_then(|v| v.as_bool()).unwrap_or(false).then(|| {
+        option("ocr_language").and_then(|v| v.as_str()).unwrap_or("eng").to_string()
+    });
+    (password, ocr_language)
+}
+
+fn load_lines(conn: &Connection, invoice_id: i64) -> Result<Vec<InvoiceLine>, String> {
+    let mut stmt = conn.prepare(
+        "SELECT description, hsn_code, taxable_value, gst_rate FROM gst_invoice_lines WHERE invoice_id = ?1 ORDER BY id"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![invoice_id], |row| {
+        Ok(InvoiceLine {
+            description: row.get(0)?,
+            hsn_code: row.get(1)?,
+            taxable_value: row.get(2)?,
+            gst_rate: row.get(3)?,
+        })
+    }).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Invoices billed to `gstin` (every invoice when `None`), dated within `from`..=`to`, newest first.
+fn load_invoices(conn: &Connection, id: Option<i64>, gstin: Option<&str>, from: Option<&str>, to: Option<&str>) -> Result<Vec<GstInvoice>, String> {
+    let mut stmt = conn.prepare(
+        "SELECT id, file_name, source_path, invoice_number, invoice_date, supplier_gstin, recipient_gstin, place_of_supply,
+                taxable_value, cgst, sgst, igst, cess, total, warnings, created_at
+         FROM gst_invoices
+         WHERE (?1 IS NULL OR id = ?1) AND (?2 IS NULL OR recipient_gstin = ?2)
+           AND (?3 IS NULL OR invoice_date >= ?3) AND (?4 IS NULL OR invoice_date <= ?4)
+         ORDER BY invoice_date DESC, id DESC"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![id, gstin, from, to], |row| {
+        let warnings: String = row.get(14)?;
+        Ok(GstInvoice {
+            id: row.get(0)?,
+            file_name: row.get(1)?,
+            source_path: row.get(2)?,
+            invoice_number: row.get(3)?,
+            invoice_date: row.get(4)?,
+            supplier_gstin: row.get(5)?,
+            recipient_gstin: row.get(6)?,
+            place_of_supply: row.get(7)?,
+            taxable_value: row.get(8)?,
+            cgst: row.get(9)?,
+            sgst: row.get(10)?,
+            igst: row.get(11)?,
+            cess: row.get(12)?,
+            total: row.get(13)?,
+            warnings: serde_json::from_str(&warnings).unwrap_or_default(),
+            lines: Vec::new(),
+            created_at: row.get(15)?,
+        })
+    }).map_err(|e| e.to_string())?;
+    let mut invoices = rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?;
+    for invoice in &mut invoices {
+        invoice.lines = load_lines(conn, invoice.id.unwrap_or_default())?;
+    }
+    Ok(invoices)
+}
+
+/// Store `invoice`, replacing an earlier import of the same supplier's invoice number.
+fn store(app: &AppHandle, invoice: &mut GstInvoice) -> Result<(), String> {
+    let mut conn = db::open_app_db(app)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    if let (Some(supplier), Some(number)) = (&invoice.supplier_gstin, &invoice.invoice_number) {
+        tx.execute(
+            "DELETE FROM gst_invoice_lines WHERE invoice_id IN
+                (SELECT id FROM gst_invoices WHERE supplier_gstin = ?1 AND invoice_number = ?2)",
+            params![supplier, number],
+        ).map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM gst_invoices WHERE supplier_gstin = ?1 AND invoice_number = ?2", params![supplier, number])
+            .map_err(|e| e.to_string())?;
+    }
+    let now = now_secs();
+    tx.execute(
+        "INSERT INTO gst_invoices (file_name, source_path, invoice_number, invoice_date, supplier_gstin, recipient_gstin,
+            place_of_supply, taxable_value, cgst, sgst, igst, cess, total, warnings, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+        params![
+            invoice.file_name,
+            invoice.source_path,
+            invoice.invoice_number,
+            invoice.invoice_date,
+            invoice.supplier_gstin,
+            invoice.recipient_gstin,
+            invoice.place_of_supply,
+            invoice.taxable_value,
+            invoice.cgst,
+            invoice.sgst,
+            invoice.igst,
+            invoice.cess,
+            invoice.total,
+            serde_json::to_string(&invoice.warnings).map_err(|e| e.to_string())?,
+            now,
+        ],
+    ).map_err(|e| e.to_string())?;
+    let id = tx.last_insert_rowid();
+    for line in &invoice.lines {
+        tx.execute(
+            "INSERT INTO gst_invoice_lines (invoice_id, description, hsn_code, taxable_value, gst_rate) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![id, line.description, line.hsn_code, line.taxable_value, line.gst_rate],
+        ).map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    invoice.id = Some(id);
+    invoice.created_at = Some(now);
+    Ok(())
+}
+
+/// The `gst_invoice` mode of `run_python_analysis`: read, check and store one invoice. The
+/// response carries it as `extractedData.invoice`.
+pub async fn analyze(
+    app: &AppHandle,
+    file_path: &str,
+    inline: bool,
+    file_name: Option<&str>,
+    options: Option<&serde_json::Value>,
+) -> Result<PythonResponse, AppError> {
+    if inline || !Path::new(file_path).exists() {
+        return Err(AppError::InvalidInput("Invoice mode needs a file on disk (file_path)".to_string()));
+    }
+    let (password, ocr_language) = text_options(options);
+    let name = file_name.map(str::to_string).unwrap_or_else(|| {
+        Path::new(file_path).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| file_path.to_string())
+    });
+
+    let (handle, path, stored_name) = (app.clone(), file_path.to_string(), name.clone());
+    let invoice = tauri::async_runtime::spawn_blocking(move || -> Result<GstInvoice, String> {
+        let text = document_text(&handle, &path, password.as_deref(), ocr_language.as_deref())?;
+        if text.trim().is_empty() {
+            return Err("The invoice has no text; try again with OCR".to_string());
+        }
+        let mut invoice = parse(&text, &stored_name, &path);
+        store(&handle, &mut invoice)?;
+        Ok(invoice)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    info!(
+        invoice = invoice.invoice_number.as_deref().unwrap_or("-"),
+        warnings = invoice.warnings.len(),
+        "Stored GST invoice from {}", name
+    );
+    let summary = format!("Imported GST invoice {} from {}", invoice.invoice_number.as_deref().unwrap_or("(no number)"), name);
+    activity::record(app, ActivityKind::DocumentImported, None, &summary, None);
+    Ok(PythonResponse {
+        status: "success".to_string(),
+        extracted_data: Some(serde_json::json!({ "documentType": DOCUMENT_TYPE, "invoice": invoice })),
+        metrics: None,
+        metadata: None,
+        message: None,
+        error: None,
+        code: None,
+        cache_hit: None,
+        doc_id: None,
+    })
+}
+
+// Tauri Commands
+/// Stored invoices billed to `gstin` (all when unset), optionally dated within `from`..=`to`.
+#[tauri::command]
+pub fn list_gst_invoices(
+    app: AppHandle,
+    gstin: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Vec<GstInvoice>, String> {
+    let conn = db::open_app_db(&app)?;
+    load_invoices(&conn, None, gstin.as_deref(), from.as_deref(), to.as_deref())
+}
+
+#[tauri::command]
+pub fn get_gst_invoice(app: AppHandle, id: i64) -> Result<GstInvoice, String> {
+    let conn = db::open_app_db(&app)?;
+    load_invoices(&conn, Some(id), None, None, None)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("Unknown GST invoice: {}", id))
+}
+
+#[tauri::command]
+pub fn delete_gst_invoice(app: AppHandle, id: i64) -> Result<(), String> {
+    let conn = db::open_app_db(&app)?;
+    conn.execute("DELETE FROM gst_invoice_lines WHERE invoice_id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM gst_invoices WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Input tax credit on invoices billed to `gstin`, bucketed by `period` ("month", "quarter",
+/// "year" or "fy", default "month" as returns are filed), optionally within `from`..=`to`.
+/// Undated invoices are left out.
+#[tauri::command]
+pub fn get_input_credit_summary(
+    app: AppHandle,
+    gstin: String,
+    period: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<Vec<InputCreditPeriod>, String> {
+    let gstin = gstin.trim().to_uppercase();
+    if !gstin_valid(&gstin) {
+        return Err(format!("Invalid GSTIN: {}", gstin));
+    }
+    let period = period.unwrap_or_else(|| "month".to_string()).to_lowercase();
+    let parse = |raw: &str| normalize_date(raw).ok_or_else(|| format!("Invalid date: {}", raw));
+    let from = from.as_deref().map(parse).transpose()?;
+    let to = to.as_deref().map(parse).transpose()?;
+    let conn = db::open_app_db(&app)?;
+
+    let mut periods: BTreeMap<String, InputCreditPeriod> = BTreeMap::new();
+    for invoice in load_invoices(&conn, None, Some(&gstin), from.as_deref(), to.as_deref())? {
+        let Some(date) = invoice.invoice_date.as_deref() else { continue };
+        let day = NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|e| e.to_string())?;
+        let key = period_key(day, &period)?;
+        let entry = periods.entry(key.clone()).or_insert(InputCreditPeriod {
+            period: key,
+            invoices: 0,
+            taxable_value: 0.0,
+            cgst: 0.0,
+            sgst: 0.0,
+            igst: 0.0,
+            cess: 0.0,
+            total_credit: 0.0,
+        });
+        entry.invoices += 1;
+        entry.taxable_value += invoice.taxable_value;
+        entry.cgst += invoice.cgst;
+        entry.sgst += invoice.sgst;
+        entry.igst += invoice.igst;
+        entry.cess += invoice.cess;
+        entry.total_credit += invoice.tax();
+    }
+    Ok(periods.into_values().collect())
+}
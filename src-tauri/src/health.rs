@@ -0,0 +1,259 @@
+// Health - one call that probes everything the app leans on (Python and its modules, Ollama and
+// the selected model, both databases, the market data hosts) for a diagnostics panel
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use tracing::info;
+
+use crate::db;
+use crate::error::AppError;
+use crate::http;
+use crate::ollama;
+use crate::profiles;
+use crate::python_bridge;
+use crate::python_setup;
+use crate::scraper;
+use crate::settings::{self, SettingsState};
+
+const OLLAMA_TIMEOUT: Duration = Duration::from_secs(5);
+const SCRAPER_HOSTS: &[(&str, &str)] = &[
+    ("NSE", scraper::NSE_BASE),
+    ("BSE", "https://api.bseindia.com"),
+    ("Yahoo Finance", "https://query1.finance.yahoo.com"),
+];
+
+/// Ordered from best to worst, so the overall status is the maximum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Skipped,
+    Ok,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentHealth {
+    pub component: String,
+    pub status: HealthStatus,
+    pub message: String,
+    pub details: serde_json::Value,
+    pub millis: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub checked_at: i64,
+    pub components: Vec<ComponentHealth>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+type Probe = (HealthStatus, String, serde_json::Value);
+
+fn component(name: &str, elapsed: Duration, (status, message, details): Probe) -> ComponentHealth {
+    ComponentHealth {
+        component: name.to_string(),
+        status,
+        message,
+        details,
+        millis: elapsed.as_millis() as u64,
+    }
+}
+
+async fn timed<T>(probe: impl std::future::Future<Output = T>) -> (T, Duration) {
+    let started = Instant::now();
+    let result = probe.await;
+    (result, started.elapsed())
+}
+
+async fn check_python(app: &AppHandle) -> Probe {
+    match python_bridge::get_bridge_version(app.clone()).await {
+        Ok(version) if version.compatible => (
+            HealthStatus::Ok,
+            format!("Python {} with bridge protocol {}", version.python_version.as_deref().unwrap_or("?"), version.expected),
+            serde_json::to_value(&version).unwrap_or_default(),
+        ),
+        Ok(version) => (
+            HealthStatus::Error,
+            "python/api.py speaks a different bridge protocol than this app".to_string(),
+            serde_json::to_value(&version).unwrap_or_default(),
+        ),
+        Err(e) => (HealthStatus::Error, e.to_string(), serde_json::Value::Null),
+    }
+}
+
+async fn check_python_modules(app: &AppHandle) -> Probe {
+    let Some(python) = python_bridge::find_python(&python_bridge::python_settings(app).interpreter_path) else {
+        return (HealthStatus::Error, AppError::PythonNotFound.to_string(), serde_json::Value::Null);
+    };
+    let modules = match python_setup::check_modules(app, &python).await {
+        Ok(modules) => modules,
+        Err(e) => return (HealthStatus::Error, e.to_string(), serde_json::Value::Null),
+    };
+    let missing = |required: bool| -> Vec<&str> {
+        modules.iter()
+            .filter(|module| module.required == required && !module.installed)
+            .map(|module| module.name.as_str())
+            .collect()
+    };
+    let (missing_required, missing_optional) = (missing(true), missing(false));
+    let status = if !missing_required.is_empty() {
+        HealthStatus::Error
+    } else if !missing_optional.is_empty() {
+        HealthStatus::Warning
+    } else {
+        HealthStatus::Ok
+    };
+    let message = match (missing_required.is_empty(), missing_optional.is_empty()) {
+        (true, true) => "All modules import".to_string(),
+        (true, false) => format!("Optional modules missing: {}", missing_optional.join(", ")),
+        _ => format!("Required modules missing: {}", missing_required.join(", ")),
+    };
+    let details = modules.iter().map(|module| (module.name.clone(), module.error.clone().into())).collect::<serde_json::Map<_, _>>();
+    (status, message, details.into())
+}
+
+fn model_unchecked() -> Probe {
+    (HealthStatus::Skipped, "Ollama is not reachable".to_string(), serde_json::Value::Null)
+}
+
+/// Reachability and the selected model as two components.
+async fn check_ollama(app: &AppHandle) -> (Probe, Probe) {
+    let state = app.state::<SettingsState>();
+    let selected = state.read().await.get().llm.selected_model.clone();
+    let bridge_url = match ollama::get_base_url(&state).await {
+        Ok(url) => url,
+        Err(e) => {
+            return ((HealthStatus::Error, e.to_string(), serde_json::Value::Null), model_unchecked());
+        }
+    };
+    let tags = http::client(app).get(format!("{}/api/tags", bridge_url))
+        .timeout(OLLAMA_TIMEOUT)
+        .send()
+        .await;
+    let tags = match tags {
+        Ok(res) if res.status().is_success() => res.json::<serde_json::Value>().await.unwrap_or_default(),
+        Ok(res) => {
+            return ((HealthStatus::Error, format!("Ollama at {} answered {}", bridge_url, res.status()), serde_json::Value::Null), model_unchecked());
+        }
+        Err(e) => {
+            return ((HealthStatus::Error, format!("Ollama not reachable at {}: {}", bridge_url, e), serde_json::Value::Null), model_unchecked());
+        }
+    };
+
+    let models: Vec<&str> = tags["models"].as_array()
+        .map(|models| models.iter().filter_map(|m| m["name"].as_str()).collect())
+        .unwrap_or_default();
+    let reachable = (
+        HealthStatus::Ok,
+        format!("Reachable at {} with {} models", bridge_url, models.len()),
+        serde_json::json!({ "url": bridge_url, "models": models }),
+    );
+    // Ollama lists untagged pulls as "name:latest"
+    let installed = models.iter().any(|name| *name == selected || *name == format!("{}:latest", selected));
+    let model = if selected.is_empty() {
+        (HealthStatus::Warning, "No model selected".to_string(), serde_json::Value::Null)
+    } else if installed {
+        (HealthStatus::Ok, format!("{} is installed", selected), serde_json::json!({ "model": selected }))
+    } else {
+        (HealthStatus::Error, format!("{} is not installed; pull it first", selected), serde_json::json!({ "model": selected }))
+    };
+    (reachable, model)
+}
+
+fn check_app_database(app: &AppHandle) -> Probe {
+    let path = profiles::active_app_db_path(app);
+    let details = serde_json::json!({ "path": path });
+    match db::open_app_db(app) {
+        Ok(_) => (HealthStatus::Ok, "Opens and is migrated".to_string(), details),
+        Err(e) => (HealthStatus::Error, e, details),
+    }
+}
+
+fn check_pipeline_database(app: &AppHandle) -> Probe {
+    let path = profiles::active_db_path(app);
+    let size = std::fs::metadata(&path).map(|m| m.len()).ok();
+    let details = serde_json::json!({ "path": path, "sizeBytes": size });
+    if size.is_none() {
+        return (HealthStatus::Ok, "Not created yet; the first analysis creates it".to_string(), details);
+    }
+    let tables = Connection::open(&path).and_then(|conn| {
+        conn.query_row("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'", [], |row| row.get::<_, i64>(0))
+    });
+    match tables {
+        Ok(tables) => (HealthStatus::Ok, format!("Opens with {} tables", tables), details),
+        Err(e) => (HealthStatus::Error, e.to_string(), details),
+    }
+}
+
+/// Any HTTP answer means the host is reachable; refusals (403/429) are a warning because
+/// the scraper usually gets through after backing off.
+async fn check_scrapers(app: &AppHandle) -> Probe {
+    if settings::is_offline(app) {
+        return (HealthStatus::Skipped, "Offline mode is on".to_string(), serde_json::Value::Null);
+    }
+    let client = http::client(app);
+    let timeout = scraper::request_timeout(app);
+    let probes = SCRAPER_HOSTS.iter().map(|(name, url)| {
+        let client = client.clone();
+        async move {
+            let result = client.get(*url).timeout(timeout).send().await;
+            let (status, detail) = match result {
+                Ok(res) if res.status().is_success() || res.status().is_redirection() => (HealthStatus::Ok, res.status().to_string()),
+                Ok(res) => (HealthStatus::Warning, res.status().to_string()),
+                Err(e) => (HealthStatus::Error, e.to_string()),
+            };
+            (*name, status, detail)
+        }
+    });
+    let results = futures_util::future::join_all(probes).await;
+
+    let status = results.iter().map(|(_, status, _)| *status).max().unwrap_or(HealthStatus::Ok);
+    let failing: Vec<&str> = results.iter().filter(|(_, status, _)| *status != HealthStatus::Ok).map(|(name, _, _)| *name).collect();
+    let message = if failing.is_empty() {
+        "All market data hosts reachable".to_string()
+    } else {
+        format!("Problems reaching: {}", failing.join(", "))
+    };
+    let details: serde_json::Map<String, serde_json::Value> = results.into_iter()
+        .map(|(name, status, detail)| (name.to_string(), serde_json::json!({ "status": status, "detail": detail })))
+        .collect();
+    (status, message, details.into())
+}
+
+// Tauri Commands
+/// Probe every component at once; each reports its own status, so one failure never hides
+/// the others.
+#[tauri::command]
+pub async fn run_health_check(app: AppHandle) -> Result<HealthReport, String> {
+    let (python, modules, ((ollama, ollama_model), ollama_time), scrapers, app_database, pipeline_database) = tokio::join!(
+        timed(check_python(&app)),
+        timed(check_python_modules(&app)),
+        timed(check_ollama(&app)),
+        timed(check_scrapers(&app)),
+        timed(async { check_app_database(&app) }),
+        timed(async { check_pipeline_database(&app) }),
+    );
+    let components = vec![
+        component("python", python.1, python.0),
+        component("python_modules", modules.1, modules.0),
+        component("ollama", ollama_time, ollama),
+        component("ollama_model", ollama_time, ollama_model),
+        component("app_database", app_database.1, app_database.0),
+        component("pipeline_database", pipeline_database.1, pipeline_database.0),
+        component("scrapers", scrapers.1, scrapers.0),
+    ];
+    let status = components.iter().map(|c| c.status).max().unwrap_or(HealthStatus::Ok);
+    info!(status = ?status, "Health check finished");
+    Ok(HealthReport { status, checked_at: now_secs(), components })
+}
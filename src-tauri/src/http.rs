@@ -0,0 +1,57 @@
+// HTTP - one pooled reqwest client shared by Ollama, provider and scraper calls, so connections
+// and TLS sessions are reused; rebuilt when the network settings change
+use reqwest::{Client, Proxy};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+use tracing::{info, warn};
+
+use crate::settings::{self, NetworkSettings, SettingsState};
+
+const TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+/// The client and the settings it was built from
+pub struct HttpClient {
+    inner: Mutex<Option<(NetworkSettings, Client)>>,
+}
+
+impl HttpClient {
+    pub fn new() -> Self {
+        Self { inner: Mutex::new(None) }
+    }
+}
+
+// No overall timeout: chat streams and model pulls run for minutes, so callers set
+// per-request timeouts where they want one
+fn build(settings: &NetworkSettings) -> Result<Client, String> {
+    let mut builder = Client::builder()
+        .cookie_store(true)
+        .connect_timeout(Duration::from_secs(settings.connect_timeout_secs))
+        .pool_idle_timeout(Duration::from_secs(settings.pool_idle_timeout_secs))
+        .pool_max_idle_per_host(settings.pool_max_idle_per_host)
+        .tcp_keepalive(TCP_KEEPALIVE);
+    let proxy = settings.proxy.trim();
+    if !proxy.is_empty() {
+        builder = builder.proxy(Proxy::all(proxy).map_err(|e| format!("Invalid proxy {}: {}", proxy, e))?);
+    }
+    builder.build().map_err(|e| e.to_string())
+}
+
+/// The shared client for the current network settings. Clones share the connection pool.
+pub fn client<R: Runtime>(app: &AppHandle<R>) -> Client {
+    let settings = settings::blocking_read(&app.state::<SettingsState>()).get().network.clone();
+    let state = app.state::<HttpClient>();
+    let mut inner = state.inner.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((built_for, client)) = inner.as_ref() {
+        if *built_for == settings {
+            return client.clone();
+        }
+    }
+    let client = build(&settings).unwrap_or_else(|e| {
+        warn!("{}; using default network settings", e);
+        build(&NetworkSettings::default()).unwrap_or_default()
+    });
+    info!(proxy = !settings.proxy.trim().is_empty(), pool_max_idle_per_host = settings.pool_max_idle_per_host, "Built HTTP client");
+    *inner = Some((settings, client.clone()));
+    client
+}
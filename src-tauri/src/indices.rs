@@ -0,0 +1,193 @@
+// Indices - NIFTY family from NSE, SENSEX from Yahoo, with a constituents summary
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::market_cache::MarketCache;
+use crate::scraper::{self, parse_number, NativeScraper, NSE_BASE};
+use crate::settings::{self, SettingsState};
+
+// Gainers/losers listed in the constituents summary
+const TOP_MOVERS: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexConstituent {
+    pub symbol: String,
+    pub price: Option<f64>,
+    pub change_percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConstituentsSummary {
+    pub count: usize,
+    pub advances: Option<u32>,
+    pub declines: Option<u32>,
+    pub unchanged: Option<u32>,
+    pub top_gainers: Vec<IndexConstituent>,
+    pub top_losers: Vec<IndexConstituent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexQuote {
+    pub name: String,
+    pub exchange: String,
+    pub level: Option<f64>,
+    pub change: Option<f64>,
+    pub change_percent: Option<f64>,
+    pub open: Option<f64>,
+    pub high: Option<f64>,
+    pub low: Option<f64>,
+    pub previous_close: Option<f64>,
+    /// Not published for SENSEX by any keyless source, so `None` there
+    pub constituents: Option<ConstituentsSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cached: Option<bool>,
+}
+
+enum IndexSource {
+    Nse(String),
+    Yahoo(&'static str, &'static str),
+}
+
+/// Map common spellings ("nifty", "BANKNIFTY", "S&P BSE SENSEX") to a source and its index name.
+fn resolve_index(index_name: &str) -> Result<IndexSource, String> {
+    let normalized = index_name.trim().to_uppercase().replace(['_', '-'], " ");
+    let compact = normalized.replace(' ', "");
+    match compact.as_str() {
+        "SENSEX" | "BSESENSEX" | "S&PBSESENSEX" => Ok(IndexSource::Yahoo("^BSESN", "SENSEX")),
+        "NIFTY" | "NIFTY50" => Ok(IndexSource::Nse("NIFTY 50".to_string())),
+        "BANKNIFTY" | "NIFTYBANK" => Ok(IndexSource::Nse("NIFTY BANK".to_string())),
+        "NIFTYNEXT50" => Ok(IndexSource::Nse("NIFTY NEXT 50".to_string())),
+        _ if compact.starts_with("NIFTY") => Ok(IndexSource::Nse(normalized)),
+        _ => Err(format!("Unsupported index: {}", index_name)),
+    }
+}
+
+fn constituent(row: &serde_json::Value) -> Option<IndexConstituent> {
+    Some(IndexConstituent {
+        symbol: row.get("symbol")?.as_str()?.to_string(),
+        price: row.get("lastPrice").and_then(parse_number),
+        change_percent: row.get("pChange").and_then(parse_number),
+    })
+}
+
+async fn fetch_nse_index(native: &NativeScraper, name: &str, app: &AppHandle) -> Result<IndexQuote, String> {
+    let encoded = scraper::url_encode(name);
+    let referer = format!("{}/market-data/live-equity-market?symbol={}", NSE_BASE, encoded);
+    let data = native.nse_get_json(
+        &format!("/api/equity-stockIndices?index={}", encoded), &referer, scraper::request_timeout(app),
+    ).await?;
+
+    let rows = data.get("data").and_then(|d| d.as_array()).cloned().unwrap_or_default();
+    // The index itself is the priority-1 row; everything else is a constituent
+    let index_row = rows.iter()
+        .find(|r| r.get("priority").and_then(|p| p.as_i64()) == Some(1))
+        .ok_or_else(|| format!("No data for index {} on NSE", name))?;
+
+    let mut members: Vec<IndexConstituent> = rows.iter()
+        .filter(|r| r.get("priority").and_then(|p| p.as_i64()) != Some(1))
+        .filter_map(constituent)
+        .collect();
+    members.sort_by(|a, b| b.change_percent.unwrap_or(0.0).total_cmp(&a.change_percent.unwrap_or(0.0)));
+
+    let count_field = |key: &str| data.pointer(&format!("/advance/{}", key))
+        .and_then(parse_number)
+        .map(|n| n as u32);
+
+    let constituents = ConstituentsSummary {
+        count: members.len(),
+        advances: count_field("advances"),
+        declines: count_field("declines"),
+        unchanged: count_field("unchanged"),
+        top_gainers: members.iter()
+            .filter(|m| m.change_percent.is_some_and(|p| p > 0.0))
+            .take(TOP_MOVERS).cloned().collect(),
+        top_losers: members.iter().rev()
+            .filter(|m| m.change_percent.is_some_and(|p| p < 0.0))
+            .take(TOP_MOVERS).cloned().collect(),
+    };
+
+    Ok(IndexQuote {
+        name: name.to_string(),
+        exchange: "NSE".to_string(),
+        level: index_row.get("lastPrice").and_then(parse_number),
+        change: index_row.get("change").and_then(parse_number),
+        change_percent: index_row.get("pChange").and_then(parse_number),
+        open: index_row.get("open").and_then(parse_number),
+        high: index_row.get("dayHigh").and_then(parse_number),
+        low: index_row.get("dayLow").and_then(parse_number),
+        previous_close: index_row.get("previousClose").and_then(parse_number),
+        constituents: Some(constituents),
+        cached: None,
+    })
+}
+
+async fn fetch_yahoo_index(native: &NativeScraper, ticker: &str, name: &str, app: &AppHandle) -> Result<IndexQuote, String> {
+    let chart = native.yahoo_chart(ticker, "1d", "1d", scraper::request_timeout(app)).await?;
+    let meta = chart.get("meta").ok_or_else(|| format!("No data for index {}", name))?;
+    let day = |key: &str| chart.pointer(&format!("/indicators/quote/0/{}/0", key)).and_then(|v| v.as_f64());
+
+    let level = meta.get("regularMarketPrice").and_then(|v| v.as_f64());
+    let previous_close = meta.get("chartPreviousClose").and_then(|v| v.as_f64())
+        .or_else(|| meta.get("previousClose").and_then(|v| v.as_f64()));
+    let change = level.zip(previous_close).map(|(l, p)| l - p);
+    let change_percent = change.zip(previous_close)
+        .filter(|(_, p)| *p != 0.0)
+        .map(|(c, p)| c / p * 100.0);
+
+    Ok(IndexQuote {
+        name: name.to_string(),
+        exchange: "BSE".to_string(),
+        level,
+        change,
+        change_percent,
+        open: day("open"),
+        high: meta.get("regularMarketDayHigh").and_then(|v| v.as_f64()).or_else(|| day("high")),
+        low: meta.get("regularMarketDayLow").and_then(|v| v.as_f64()).or_else(|| day("low")),
+        previous_close,
+        constituents: None,
+        cached: None,
+    })
+}
+
+// Tauri Commands
+#[tauri::command]
+pub async fn get_index_quote(
+    app: AppHandle,
+    native: tauri::State<'_, NativeScraper>,
+    cache: tauri::State<'_, MarketCache>,
+    settings: tauri::State<'_, SettingsState>,
+    index_name: String,
+    force_refresh: Option<bool>,
+) -> Result<IndexQuote, String> {
+    let source = resolve_index(&index_name)?;
+    let (name, exchange) = match &source {
+        IndexSource::Nse(name) => (name.clone(), "NSE"),
+        IndexSource::Yahoo(_, name) => (name.to_string(), "BSE"),
+    };
+
+    if !force_refresh.unwrap_or(false) {
+        let ttl = {
+            let store = settings.read().await;
+            store.get().scraper.cache_ttl_secs
+        };
+        if let Some(payload) = cache.get(&app, "INDEX", &name, exchange, ttl) {
+            if let Ok(mut quote) = serde_json::from_value::<IndexQuote>(payload) {
+                quote.cached = Some(true);
+                return Ok(quote);
+            }
+        }
+    }
+
+    settings::ensure_online(&app)?;
+    let quote = match source {
+        IndexSource::Nse(name) => fetch_nse_index(&native, &name, &app).await?,
+        IndexSource::Yahoo(ticker, name) => fetch_yahoo_index(&native, ticker, name, &app).await?,
+    };
+    if let Ok(payload) = serde_json::to_value(&quote) {
+        cache.put(&app, "INDEX", &name, exchange, &payload);
+    }
+    Ok(quote)
+}
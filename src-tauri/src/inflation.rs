@@ -0,0 +1,309 @@
+// Inflation - yearly consumer price index series from the World Bank, cached in SQLite, for
+// turning nominal returns and past amounts into real terms and for the inflation assumption in
+// retirement plans and DCF projections
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::db;
+use crate::scraper::{self, NativeScraper};
+use crate::settings;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS cpi_index (
+    country TEXT NOT NULL,         -- ISO 3166 alpha-3, e.g. 'IND'
+    year INTEGER NOT NULL,
+    value REAL NOT NULL,           -- 2010 = 100
+    PRIMARY KEY (country, year)
+);
+CREATE TABLE IF NOT EXISTS cpi_meta (
+    country TEXT PRIMARY KEY,
+    fetched_at INTEGER NOT NULL
+);
+";
+
+// World Bank indicator FP.CPI.TOTL: consumer prices, 2010 = 100, one figure a year
+const WORLD_BANK_CPI_URL: &str = "https://api.worldbank.org/v2/country/{country}/indicator/FP.CPI.TOTL?format=json&per_page=200";
+pub const SOURCE: &str = "World Bank FP.CPI.TOTL";
+pub const DEFAULT_COUNTRY: &str = "IND";
+/// Years averaged when no period is given
+pub const DEFAULT_AVERAGE_YEARS: u32 = 10;
+
+// The series gains a year once a year; a week old is fresh enough
+const CPI_STALE_AFTER_SECS: i64 = 7 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CpiPoint {
+    pub year: i32,
+    pub index: f64,
+    /// Change on the year before, in percent
+    pub inflation: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CpiSeries {
+    pub country: String,
+    pub source: String,
+    /// Oldest first
+    pub points: Vec<CpiPoint>,
+    pub fetched_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RealReturn {
+    /// In percent, like the rest
+    pub nominal_return: f64,
+    pub inflation: f64,
+    /// (1 + nominal) / (1 + inflation) - 1
+    pub real_return: f64,
+    /// "input", or the CPI years the inflation was taken over
+    pub inflation_source: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AdjustedAmount {
+    pub amount: f64,
+    pub from_year: i32,
+    pub to_year: i32,
+    /// `amount` in `to_year` money
+    pub adjusted_amount: f64,
+    /// Price rise between the two years, in percent
+    pub cumulative_inflation: f64,
+    /// The same as a yearly rate, in percent
+    pub annual_inflation: f64,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn normalize_country(country: Option<&str>) -> Result<String, String> {
+    let country = country.map(str::trim).filter(|c| !c.is_empty()).unwrap_or(DEFAULT_COUNTRY).to_uppercase();
+    if country.len() != 3 || !country.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(format!("Country must be an ISO alpha-3 code such as IND or USA, not {}", country));
+    }
+    Ok(country)
+}
+
+/// `(year, index)` pairs from the World Bank's `[paging, rows]` response; years without a figure
+/// are left out.
+fn parse_world_bank(body: &serde_json::Value) -> Result<Vec<(i32, f64)>, String> {
+    let rows = body.get(1).and_then(|r| r.as_array()).ok_or_else(|| {
+        let message = body.get(0).and_then(|p| p.pointer("/message/0/value")).and_then(|m| m.as_str());
+        format!("World Bank returned no CPI data{}", message.map(|m| format!(": {}", m)).unwrap_or_default())
+    })?;
+    Ok(rows.iter()
+        .filter_map(|row| {
+            let year = row.get("date")?.as_str()?.parse().ok()?;
+            Some((year, row.get("value")?.as_f64()?))
+        })
+        .collect())
+}
+
+fn store_series(conn: &mut Connection, country: &str, series: &[(i32, f64)]) -> Result<(), String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for (year, value) in series {
+        tx.execute(
+            "INSERT OR REPLACE INTO cpi_index (country, year, value) VALUES (?1, ?2, ?3)",
+            params![country, year, value],
+        ).map_err(|e| e.to_string())?;
+    }
+    tx.execute(
+        "INSERT OR REPLACE INTO cpi_meta (country, fetched_at) VALUES (?1, ?2)",
+        params![country, now_secs()],
+    ).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+fn fetched_at(conn: &Connection, country: &str) -> Result<Option<i64>, String> {
+    conn.query_row("SELECT fetched_at FROM cpi_meta WHERE country = ?1", params![country], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+/// Download the series if the local copy is missing or stale. A failed refresh falls back to the
+/// stale copy when there is one.
+async fn ensure_series(app: &AppHandle, country: &str, force: bool) -> Result<(), String> {
+    let fetched_at = fetched_at(&db::open_app_db(app)?, country)?;
+    if !force && fetched_at.is_some_and(|t| now_secs() - t < CPI_STALE_AFTER_SECS) {
+        return Ok(());
+    }
+    if settings::is_offline(app) {
+        return if fetched_at.is_some() { Ok(()) } else { Err(settings::OFFLINE_ERROR.to_string()) };
+    }
+
+    let url = WORLD_BANK_CPI_URL.replace("{country}", country);
+    let fetched = app.state::<NativeScraper>().get_json(&url, scraper::request_timeout(app)).await
+        .and_then(|body| parse_world_bank(&body));
+    let series = match fetched {
+        Ok(series) if !series.is_empty() => series,
+        Ok(_) if fetched_at.is_some() => return Ok(()),
+        Ok(_) => return Err(format!("World Bank has no CPI figures for {}", country)),
+        Err(e) if fetched_at.is_some() => {
+            warn!(country, "CPI refresh failed, using the cached series: {}", e);
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    };
+    let mut conn = db::open_app_db(app)?;
+    store_series(&mut conn, country, &series)?;
+    info!(country, years = series.len(), "Cached CPI series");
+    Ok(())
+}
+
+fn load_series(conn: &Connection, country: &str) -> Result<Vec<(i32, f64)>, String> {
+    let mut stmt = conn.prepare("SELECT year, value FROM cpi_index WHERE country = ?1 ORDER BY year")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![country], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// The cached series for `country`, downloading it first when needed.
+async fn series(app: &AppHandle, country: &str) -> Result<Vec<(i32, f64)>, String> {
+    ensure_series(app, country, false).await?;
+    let series = load_series(&db::open_app_db(app)?, country)?;
+    if series.is_empty() {
+        return Err(format!("No CPI figures for {}", country));
+    }
+    Ok(series)
+}
+
+fn index_at(series: &[(i32, f64)], year: i32) -> Result<f64, String> {
+    series.iter().find(|(y, _)| *y == year).map(|(_, value)| *value).ok_or_else(|| {
+        let (first, last) = (series[0].0, series[series.len() - 1].0);
+        format!("No CPI figure for {} (the series covers {}-{})", year, first, last)
+    })
+}
+
+/// Compound yearly inflation between two years, in percent.
+fn annualized(series: &[(i32, f64)], from_year: i32, to_year: i32) -> Result<f64, String> {
+    if to_year <= from_year {
+        return Err("The end year must be after the start year".to_string());
+    }
+    let ratio = index_at(series, to_year)? / index_at(series, from_year)?;
+    Ok((ratio.powf(1.0 / (to_year - from_year) as f64) - 1.0) * 100.0)
+}
+
+/// Yearly inflation over the last `years` years of `country`'s series (India by default), in
+/// percent: the assumption plans fall back to when they aren't given one.
+pub async fn average_inflation(app: &AppHandle, country: Option<&str>, years: u32) -> Result<(f64, String), String> {
+    let country = normalize_country(country)?;
+    let series = series(app, &country).await?;
+    let last = series[series.len() - 1].0;
+    let first = (last - years.max(1) as i32).max(series[0].0);
+    if first == last {
+        return Err(format!("The CPI series for {} has a single year", country));
+    }
+    let rate = annualized(&series, first, last)?;
+    Ok((rate, format!("{} CPI {}-{}", country, first, last)))
+}
+
+/// Restate `amount` in the money of `years` years earlier.
+pub fn deflate(amount: f64, inflation: f64, years: f64) -> f64 {
+    amount / (1.0 + inflation / 100.0).powf(years)
+}
+
+// Tauri Commands
+#[tauri::command]
+pub async fn get_cpi_series(app: AppHandle, country: Option<String>, refresh: Option<bool>) -> Result<CpiSeries, String> {
+    let country = normalize_country(country.as_deref())?;
+    ensure_series(&app, &country, refresh.unwrap_or(false)).await?;
+    let conn = db::open_app_db(&app)?;
+    let series = load_series(&conn, &country)?;
+    let points = series.iter().enumerate()
+        .map(|(i, (year, index))| CpiPoint {
+            year: *year,
+            index: *index,
+            inflation: i.checked_sub(1)
+                .map(|previous| series[previous])
+                .filter(|(previous_year, _)| *previous_year == year - 1)
+                .map(|(_, previous)| (index / previous - 1.0) * 100.0),
+        })
+        .collect();
+    Ok(CpiSeries {
+        fetched_at: fetched_at(&conn, &country)?,
+        country,
+        source: SOURCE.to_string(),
+        points,
+    })
+}
+
+/// Yearly inflation over the last `years` years (default 10), in percent.
+#[tauri::command]
+pub async fn get_average_inflation(app: AppHandle, country: Option<String>, years: Option<u32>) -> Result<f64, String> {
+    let (rate, _) = average_inflation(&app, country.as_deref(), years.unwrap_or(DEFAULT_AVERAGE_YEARS)).await?;
+    Ok(rate)
+}
+
+/// Real return for a nominal one, both in percent. Inflation is `inflation` when given, else
+/// the CPI's between `from_year` and `to_year`, else the last ten years' average.
+#[tauri::command]
+pub async fn calculate_real_return(
+    app: AppHandle,
+    nominal_return: f64,
+    inflation: Option<f64>,
+    from_year: Option<i32>,
+    to_year: Option<i32>,
+    country: Option<String>,
+) -> Result<RealReturn, String> {
+    if !nominal_return.is_finite() || nominal_return <= -100.0 {
+        return Err("Nominal return must be a number above -100%".to_string());
+    }
+    let (inflation, inflation_source) = match (inflation, from_year, to_year) {
+        (Some(inflation), _, _) if inflation.is_finite() && inflation > -100.0 => (inflation, "input".to_string()),
+        (Some(_), _, _) => return Err("Inflation must be a number above -100%".to_string()),
+        (None, Some(from_year), Some(to_year)) => {
+            let country = normalize_country(country.as_deref())?;
+            let series = series(&app, &country).await?;
+            (annualized(&series, from_year, to_year)?, format!("{} CPI {}-{}", country, from_year, to_year))
+        }
+        (None, None, None) => average_inflation(&app, country.as_deref(), DEFAULT_AVERAGE_YEARS).await?,
+        (None, _, _) => return Err("Give both the start and the end year".to_string()),
+    };
+    Ok(RealReturn {
+        nominal_return,
+        inflation,
+        real_return: ((1.0 + nominal_return / 100.0) / (1.0 + inflation / 100.0) - 1.0) * 100.0,
+        inflation_source,
+    })
+}
+
+/// `amount` in `from_year` money restated in `to_year` money (the latest CPI year by default).
+#[tauri::command]
+pub async fn adjust_for_inflation(
+    app: AppHandle,
+    amount: f64,
+    from_year: i32,
+    to_year: Option<i32>,
+    country: Option<String>,
+) -> Result<AdjustedAmount, String> {
+    let country = normalize_country(country.as_deref())?;
+    let series = series(&app, &country).await?;
+    let to_year = to_year.unwrap_or(series[series.len() - 1].0);
+    let ratio = index_at(&series, to_year)? / index_at(&series, from_year)?;
+    // Going back in time the rate is still the one prices rose at
+    let annual_inflation = match to_year.cmp(&from_year) {
+        Ordering::Equal => 0.0,
+        Ordering::Greater => annualized(&series, from_year, to_year)?,
+        Ordering::Less => annualized(&series, to_year, from_year)?,
+    };
+    Ok(AdjustedAmount {
+        amount,
+        from_year,
+        to_year,
+        adjusted_amount: amount * ratio,
+        cumulative_inflation: (ratio - 1.0) * 100.0,
+        annual_inflation,
+    })
+}
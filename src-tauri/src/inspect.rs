@@ -0,0 +1,111 @@
+// Inspect - quick document facts (pages, encryption, scanned or not) before committing to a parse
+use lopdf::Document;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::python_bridge::PDF_DECRYPTION_FAILED;
+
+// Pages checked for a text layer; evenly spread so a scanned annexure doesn't decide it
+const SCAN_SAMPLE_PAGES: usize = 12;
+
+// Rough throughput of the Python pipeline and the OCR pass on a typical laptop
+const STARTUP_SECS: f64 = 5.0;
+const PARSE_SECS_PER_PAGE: f64 = 1.2;
+const OCR_SECS_PER_PAGE: f64 = 4.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentInfo {
+    pub file_name: String,
+    pub file_size: u64,
+    /// "pdf", "excel", "csv", "xbrl" or "other"
+    pub format: String,
+    pub page_count: Option<u32>,
+    pub encrypted: bool,
+    /// Still locked after loading (with the supplied password, if any)
+    pub requires_password: bool,
+    /// No text layer on any sampled page; `None` when it couldn't be checked
+    pub scanned: Option<bool>,
+    pub estimated_seconds: Option<u64>,
+}
+
+fn format_of(path: &Path) -> &'static str {
+    match path.extension().map(|e| e.to_string_lossy().to_lowercase()).as_deref() {
+        Some("pdf") => "pdf",
+        Some("xlsx" | "xlsm" | "xls" | "ods") => "excel",
+        Some("csv" | "tsv" | "txt") => "csv",
+        Some("xml" | "xbrl") => "xbrl",
+        _ => "other",
+    }
+}
+
+/// A page counts as scanned when it draws images but has no fonts to draw text with.
+fn looks_scanned(doc: &Document) -> Option<bool> {
+    let pages: Vec<_> = doc.get_pages().into_values().collect();
+    if pages.is_empty() {
+        return None;
+    }
+    let step = pages.len().div_ceil(SCAN_SAMPLE_PAGES);
+    let sampled: Vec<bool> = pages.iter()
+        .step_by(step)
+        .map(|&page| {
+            let has_fonts = doc.get_page_fonts(page).map(|f| !f.is_empty()).unwrap_or(false);
+            let has_images = doc.get_page_images(page).map(|i| !i.is_empty()).unwrap_or(false);
+            has_images && !has_fonts
+        })
+        .collect();
+    Some(sampled.iter().all(|scanned| *scanned))
+}
+
+fn estimate_seconds(pages: u32, scanned: bool) -> u64 {
+    let per_page = if scanned { PARSE_SECS_PER_PAGE + OCR_SECS_PER_PAGE } else { PARSE_SECS_PER_PAGE };
+    (STARTUP_SECS + per_page * pages as f64).round() as u64
+}
+
+// Tauri Commands
+#[tauri::command]
+pub async fn inspect_document(file_path: String, password: Option<String>) -> Result<DocumentInfo, String> {
+    let path = Path::new(&file_path);
+    let file_size = std::fs::metadata(path)
+        .map_err(|e| format!("Cannot read {}: {}", file_path, e))?
+        .len();
+    let format = format_of(path);
+    let mut info = DocumentInfo {
+        file_name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| file_path.clone()),
+        file_size,
+        format: format.to_string(),
+        page_count: None,
+        encrypted: false,
+        requires_password: false,
+        scanned: None,
+        estimated_seconds: None,
+    };
+    if format != "pdf" {
+        // Spreadsheets and XBRL parse natively in seconds
+        info.estimated_seconds = Some(STARTUP_SECS as u64);
+        return Ok(info);
+    }
+
+    let doc = match password.as_deref().filter(|p| !p.is_empty()) {
+        Some(password) => Document::load_with_password(path, password).map_err(|e| match e {
+            lopdf::Error::InvalidPassword => format!("{}: Incorrect PDF password", PDF_DECRYPTION_FAILED),
+            e => format!("Failed to open PDF: {}", e),
+        })?,
+        None => Document::load(path).map_err(|e| format!("Failed to open PDF: {}", e))?,
+    };
+    let page_count = doc.get_pages().len() as u32;
+    // Loading decrypts PDFs with an empty user password; the rest stay encrypted
+    info.requires_password = doc.is_encrypted() && !doc.was_encrypted();
+    info.encrypted = doc.was_encrypted() || info.requires_password;
+    info.page_count = Some(page_count);
+    if !info.requires_password {
+        info.scanned = looks_scanned(&doc);
+    }
+    info.estimated_seconds = Some(estimate_seconds(page_count, info.scanned.unwrap_or(false)));
+
+    eprintln!(
+        "[Inspect] {}: {} pages, encrypted={}, scanned={:?}",
+        info.file_name, page_count, info.encrypted, info.scanned
+    );
+    Ok(info)
+}
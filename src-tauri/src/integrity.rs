@@ -0,0 +1,137 @@
+// Integrity - a ledger of the source files documents were extracted from (blake3, size, when),
+// so a report's figures can be shown to come from a specific, unmodified file. Inline uploads
+// have no file to check later, so only files read from disk are recorded.
+use rusqlite::{params, Row};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+use crate::db;
+
+// Kept in the app DB, so entries outlive the pipeline DB being wiped for a new analysis
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS file_ledger (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    document_id INTEGER NOT NULL,  -- pipeline DB documents.id
+    file_name TEXT NOT NULL,
+    source_path TEXT NOT NULL,
+    blake3 TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    imported_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_file_ledger_document ON file_ledger(document_id);
+";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntry {
+    pub id: i64,
+    pub document_id: i64,
+    pub file_name: String,
+    pub source_path: String,
+    pub blake3: String,
+    pub size: u64,
+    pub imported_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityCheck {
+    pub document_id: i64,
+    /// What was recorded when the document was extracted
+    pub recorded: LedgerEntry,
+    pub current_path: String,
+    pub current_blake3: String,
+    pub current_size: u64,
+    /// Byte-for-byte the file the document was extracted from
+    pub verified: bool,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// blake3 and size of a file, read in one pass.
+fn hash_file(path: &Path) -> Result<(String, u64), String> {
+    let mut file = File::open(path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    let mut hasher = blake3::Hasher::new();
+    let size = std::io::copy(&mut file, &mut hasher).map_err(|e| format!("Failed to hash {}: {}", path.display(), e))?;
+    Ok((hasher.finalize().to_hex().to_string(), size))
+}
+
+fn entry_from_row(row: &Row) -> rusqlite::Result<LedgerEntry> {
+    Ok(LedgerEntry {
+        id: row.get(0)?,
+        document_id: row.get(1)?,
+        file_name: row.get(2)?,
+        source_path: row.get(3)?,
+        blake3: row.get(4)?,
+        size: row.get::<_, i64>(5)? as u64,
+        imported_at: row.get(6)?,
+    })
+}
+
+fn load(app: &AppHandle, document_id: Option<i64>) -> Result<Vec<LedgerEntry>, String> {
+    let conn = db::open_app_db(app)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, document_id, file_name, source_path, blake3, size, imported_at FROM file_ledger
+         WHERE ?1 IS NULL OR document_id = ?1 ORDER BY imported_at DESC, id DESC"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![document_id], entry_from_row).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Record the file `document_id` was just extracted from. Failures are logged, never fatal to
+/// the import.
+pub fn record(app: &AppHandle, document_id: i64, source_path: &str) {
+    let path = Path::new(source_path);
+    let result = hash_file(path).and_then(|(hash, size)| {
+        let file_name = path.file_name().map_or_else(|| source_path.to_string(), |n| n.to_string_lossy().to_string());
+        let conn = db::open_app_db(app)?;
+        conn.execute(
+            "INSERT INTO file_ledger (document_id, file_name, source_path, blake3, size, imported_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![document_id, file_name, source_path, hash, size as i64, now_secs()],
+        ).map_err(|e| e.to_string())?;
+        info!(document_id, blake3 = %hash, size, "Recorded source file");
+        Ok(())
+    });
+    if let Err(e) = result {
+        warn!(document_id, "Failed to record source file: {}", e);
+    }
+}
+
+// Tauri Commands
+/// Ledger entries for a document, or for every document, newest first.
+#[tauri::command]
+pub fn get_file_ledger(app: AppHandle, document_id: Option<i64>) -> Result<Vec<LedgerEntry>, String> {
+    load(&app, document_id)
+}
+
+/// Hash the file at `current_path` and compare it with the one `document_id` was last extracted
+/// from.
+#[tauri::command]
+pub async fn verify_document_integrity(app: AppHandle, document_id: i64, current_path: String) -> Result<IntegrityCheck, String> {
+    let recorded = load(&app, Some(document_id))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("No source file was recorded for document {}", document_id))?;
+    let path = current_path.clone();
+    let (current_blake3, current_size) = tauri::async_runtime::spawn_blocking(move || hash_file(Path::new(&path)))
+        .await
+        .map_err(|e| e.to_string())??;
+    Ok(IntegrityCheck {
+        document_id,
+        verified: current_blake3 == recorded.blake3 && current_size == recorded.size,
+        recorded,
+        current_path,
+        current_blake3,
+        current_size,
+    })
+}
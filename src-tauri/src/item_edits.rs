@@ -0,0 +1,471 @@
+// Item Edits - figures corrected or added by hand, with who set them, when and what the parser
+// had found, so exports and reports can tell them apart from raw extraction output
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use tracing::info;
+
+use crate::activity::{self, ActivityKind};
+use crate::db;
+use crate::realtime;
+use crate::statements;
+
+// In the pipeline DB, next to the items it describes
+const EDITS_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS item_edits (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    doc_id INTEGER NOT NULL,
+    item_id TEXT NOT NULL,         -- financial_items.id
+    field TEXT NOT NULL,           -- the item field set, e.g. currentYear; \"item\" when added
+    old_value TEXT,                -- JSON
+    new_value TEXT,                -- JSON
+    edited_by TEXT NOT NULL,
+    note TEXT,
+    edited_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_item_edits_item ON item_edits(item_id);
+";
+
+/// Appended to the labels of hand-set items in reports, with a footnote saying what it means
+pub const MANUAL_MARK: &str = " *";
+pub const MANUAL_FOOTNOTE: &str = "* Entered or corrected by hand, not extracted";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueSource {
+    Extracted,
+    Manual,
+}
+
+/// The fields `update_financial_item` can set; those left None stay as they are.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ItemFields {
+    pub label: Option<String>,
+    pub current_year: Option<f64>,
+    pub previous_year: Option<f64>,
+    pub statement_type: Option<String>,
+    /// Period label -> amount, merged into what the item has
+    pub all_years: Option<BTreeMap<String, f64>>,
+    pub is_header: Option<bool>,
+    pub is_total: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemEdit {
+    pub field: String,
+    pub old_value: Option<Value>,
+    pub new_value: Option<Value>,
+    pub edited_by: String,
+    pub note: Option<String>,
+    pub edited_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemProvenance {
+    pub item_id: String,
+    pub document_id: i64,
+    pub source: ValueSource,
+    /// How the parser produced the item, for extracted ones
+    pub extraction_method: Option<String>,
+    /// Oldest first
+    pub edits: Vec<ItemEdit>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn open(app: &AppHandle) -> Result<Connection, String> {
+    let conn = db::open_pipeline_db(app)?;
+    conn.execute_batch(EDITS_SCHEMA).map_err(|e| e.to_string())?;
+    Ok(conn)
+}
+
+/// The named editor, else the OS account running the app.
+pub fn editor(edited_by: Option<String>) -> String {
+    edited_by
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .or_else(|| std::env::var("USER").ok())
+        .or_else(|| std::env::var("USERNAME").ok())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Whether an item's figures were set by hand rather than extracted.
+pub fn is_manual(item: &Value) -> bool {
+    item["provenance"]["source"] == "manual"
+}
+
+/// The label to print for an item, marked when it was set by hand.
+pub fn display_label(item: &Value) -> String {
+    let label = item["label"].as_str().unwrap_or("");
+    if is_manual(item) { format!("{}{}", label, MANUAL_MARK) } else { label.to_string() }
+}
+
+/// A stored item by its row id, or by the id in its JSON within `document_id`. Returns the row
+/// id, document id and item.
+pub fn find_item(conn: &Connection, id: &str, document_id: Option<i64>) -> Result<(String, i64, Value), String> {
+    let mut stmt = conn.prepare(
+        "SELECT id, doc_id, original_json FROM financial_items
+         WHERE (id = ?1 OR json_extract(original_json, '$.id') = ?1) AND (?2 IS NULL OR doc_id = ?2)
+         LIMIT 2",
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![id, document_id], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, Option<i64>>(1)?, row.get::<_, Option<String>>(2)?))
+    }).map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    match rows.as_slice() {
+        [] => Err(format!("Item {} not found", id)),
+        [(row_id, doc_id, json)] => {
+            let doc_id = doc_id.ok_or_else(|| format!("Item {} belongs to no document", id))?;
+            let item = json.as_deref().and_then(|json| serde_json::from_str(json).ok()).unwrap_or_else(|| json!({ "id": id }));
+            Ok((row_id.clone(), doc_id, item))
+        }
+        _ => Err(format!("Item id {} is used in several documents; pass the document id", id)),
+    }
+}
+
+/// Write an item back, keeping the row's columns in step with its JSON.
+fn store(conn: &Connection, row_id: &str, item: &Value) -> Result<(), String> {
+    conn.execute(
+        "UPDATE financial_items SET label = ?2, value_current = ?3, value_previous = ?4, statement_type = ?5,
+                is_header = ?6, original_json = ?7
+         WHERE id = ?1",
+        params![
+            row_id,
+            item["label"].as_str(),
+            item["currentYear"].as_f64(),
+            item["previousYear"].as_f64(),
+            item["statementType"].as_str().map(|s| s.to_uppercase()),
+            item["isHeader"].as_bool(),
+            item.to_string(),
+        ],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn record(conn: &Connection, doc_id: i64, item_id: &str, edit: &ItemEdit) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO item_edits (doc_id, item_id, field, old_value, new_value, edited_by, note, edited_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            doc_id,
+            item_id,
+            edit.field,
+            edit.old_value.as_ref().map(Value::to_string),
+            edit.new_value.as_ref().map(Value::to_string),
+            edit.edited_by,
+            edit.note,
+            edit.edited_at,
+        ],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Variation fields follow the amounts, as the parser computes them.
+fn refresh_variation(item: &mut Value) {
+    let current = item["currentYear"].as_f64().unwrap_or(0.0);
+    let previous = item["previousYear"].as_f64().unwrap_or(0.0);
+    let variation = current - previous;
+    item["variation"] = json!(variation);
+    item["variationPercent"] = json!(if previous != 0.0 { variation / previous.abs() * 100.0 } else { 0.0 });
+    item["isNegative"] = json!(current < 0.0);
+}
+
+/// Mark an item as set by hand. The first time one of its fields is changed, the extracted value
+/// is kept under `provenance.extractedValues`; later changes are only in the edit history.
+fn stamp(item: &mut Value, changed: &[(String, Option<Value>)], edited_by: &str, now: i64) {
+    let mut provenance = item["provenance"].as_object().cloned().unwrap_or_default();
+    let mut extracted = provenance.get("extractedValues").and_then(Value::as_object).cloned().unwrap_or_default();
+    let mut fields: Vec<String> = provenance.get("fields")
+        .and_then(Value::as_array)
+        .map(|fields| fields.iter().filter_map(|f| f.as_str().map(str::to_string)).collect())
+        .unwrap_or_default();
+    // Items added by hand have no parser values to keep
+    let added = fields.iter().any(|field| field == "item");
+    for (field, old) in changed {
+        if !fields.contains(field) {
+            fields.push(field.clone());
+            if !added {
+                extracted.insert(field.clone(), old.clone().unwrap_or(Value::Null));
+            }
+        }
+    }
+    provenance.insert("source".to_string(), json!("manual"));
+    provenance.insert("setBy".to_string(), json!(edited_by));
+    provenance.insert("setAt".to_string(), json!(now));
+    provenance.insert("fields".to_string(), json!(fields));
+    provenance.insert("extractedValues".to_string(), Value::Object(extracted));
+    item["provenance"] = Value::Object(provenance);
+}
+
+/// Write an item as another device last saved it into `document_id`, over the item with the same
+/// id or as a new row at its `rowIndex`. Not published back to the realtime channel.
+pub fn store_synced(app: &AppHandle, document_id: i64, item: &Value) -> Result<(), String> {
+    let key = item["id"].as_str().ok_or("A synced item needs an id")?;
+    let conn = open(app)?;
+    let row_id = match find_item(&conn, key, Some(document_id)) {
+        Ok((row_id, _, _)) => {
+            store(&conn, &row_id, item)?;
+            row_id
+        }
+        Err(_) => {
+            let row_id = format!("{}:{}", document_id, key);
+            conn.execute(
+                "INSERT INTO financial_items (
+                    id, doc_id, label, value_current, value_previous, row_index, statement_type,
+                    is_header, source_page, source_line_text, confidence, original_json
+                 ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 0, '', ?9, ?10)",
+                params![
+                    row_id,
+                    document_id,
+                    item["label"].as_str(),
+                    item["currentYear"].as_f64(),
+                    item["previousYear"].as_f64(),
+                    item["rowIndex"].as_i64().unwrap_or(0),
+                    item["statementType"].as_str().map(|s| s.to_uppercase()),
+                    item["isHeader"].as_bool(),
+                    item["confidence"].as_f64().unwrap_or(1.0),
+                    item.to_string(),
+                ],
+            ).map_err(|e| e.to_string())?;
+            row_id
+        }
+    };
+    let _ = app.emit("financial-item-changed", json!({ "documentId": document_id, "itemId": row_id, "item": item }));
+    statements::document_stored(app, document_id);
+    Ok(())
+}
+
+// Tauri Commands
+/// Correct a stored item. `id` is the item's row id, or its own id when `document_id` is given
+/// too. Every changed field is recorded with who changed it; returns the updated item.
+#[tauri::command]
+pub fn update_financial_item(
+    app: AppHandle,
+    id: String,
+    fields: ItemFields,
+    document_id: Option<i64>,
+    edited_by: Option<String>,
+    note: Option<String>,
+) -> Result<Value, String> {
+    let conn = open(&app)?;
+    let (row_id, doc_id, mut item) = find_item(&conn, &id, document_id)?;
+    let edited_by = editor(edited_by);
+    let now = now_secs();
+
+    let mut updates: Vec<(&str, Value)> = Vec::new();
+    if let Some(label) = fields.label.map(|l| l.trim().to_string()).filter(|l| !l.is_empty()) {
+        updates.push(("label", json!(label)));
+    }
+    if let Some(value) = fields.current_year {
+        updates.push(("currentYear", json!(value)));
+    }
+    if let Some(value) = fields.previous_year {
+        updates.push(("previousYear", json!(value)));
+    }
+    if let Some(statement) = fields.statement_type {
+        updates.push(("statementType", json!(statement)));
+    }
+    if let Some(years) = fields.all_years {
+        let mut merged = item["allYears"].as_object().cloned().unwrap_or_default();
+        for (period, amount) in years {
+            merged.insert(period, json!(amount));
+        }
+        updates.push(("allYears", Value::Object(merged)));
+    }
+    if let Some(flag) = fields.is_header {
+        updates.push(("isHeader", json!(flag)));
+    }
+    if let Some(flag) = fields.is_total {
+        updates.push(("isTotal", json!(flag)));
+    }
+
+    let mut changed = Vec::new();
+    for (field, value) in updates {
+        let old = item.get(field).cloned().filter(|old| !old.is_null());
+        if old.as_ref() == Some(&value) {
+            continue;
+        }
+        record(&conn, doc_id, &row_id, &ItemEdit {
+            field: field.to_string(),
+            old_value: old.clone(),
+            new_value: Some(value.clone()),
+            edited_by: edited_by.clone(),
+            note: note.clone(),
+            edited_at: now,
+        })?;
+        item[field] = value;
+        changed.push((field.to_string(), old));
+    }
+    if changed.is_empty() {
+        return Ok(item);
+    }
+    refresh_variation(&mut item);
+    stamp(&mut item, &changed, &edited_by, now);
+    store(&conn, &row_id, &item)?;
+
+    info!(item = %row_id, document_id = doc_id, fields = changed.len(), "Edited financial item");
+    let fields = changed.iter().map(|(field, _)| field.as_str()).collect::<Vec<_>>().join(", ");
+    let summary = format!("Edited {} ({})", item["label"].as_str().unwrap_or(&row_id), fields);
+    activity::record(&app, ActivityKind::ItemsEdited, Some(doc_id), &summary, Some(&edited_by));
+    let _ = app.emit("financial-item-changed", json!({ "documentId": doc_id, "itemId": row_id, "item": &item }));
+    statements::document_stored(&app, doc_id);
+    realtime::item_saved(&app, doc_id, &item);
+    Ok(item)
+}
+
+/// Add an item the parser missed to a stored document, placed after `after_id` (a row id or the
+/// item's own id) or at the end. Returns the new item.
+#[tauri::command]
+pub fn add_financial_item(
+    app: AppHandle,
+    document_id: i64,
+    fields: ItemFields,
+    after_id: Option<String>,
+    edited_by: Option<String>,
+    note: Option<String>,
+) -> Result<Value, String> {
+    let label = fields.label.as_deref().map(str::trim).filter(|l| !l.is_empty())
+        .ok_or("A new item needs a label")?
+        .to_string();
+    let conn = open(&app)?;
+    let exists: bool = conn.query_row("SELECT EXISTS(SELECT 1 FROM documents WHERE id = ?1)", params![document_id], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    if !exists {
+        return Err(format!("Document {} not found", document_id));
+    }
+
+    // Rows after the anchor move down one to make room
+    let row_index = match after_id {
+        Some(after) => {
+            let (anchor, _, _) = find_item(&conn, &after, Some(document_id))?;
+            let index: Option<i64> = conn.query_row("SELECT row_index FROM financial_items WHERE id = ?1", params![anchor], |row| row.get(0))
+                .map_err(|e| e.to_string())?;
+            let index = index.unwrap_or(0) + 1;
+            conn.execute(
+                "UPDATE financial_items SET row_index = row_index + 1 WHERE doc_id = ?1 AND row_index >= ?2",
+                params![document_id, index],
+            ).map_err(|e| e.to_string())?;
+            index
+        }
+        None => conn.query_row(
+            "SELECT IFNULL(MAX(row_index), -1) + 1 FROM financial_items WHERE doc_id = ?1",
+            params![document_id],
+            |row| row.get(0),
+        ).map_err(|e| e.to_string())?,
+    };
+
+    let edited_by = editor(edited_by);
+    let now = now_secs();
+    let mut suffix = 0;
+    let key = loop {
+        let key = format!("manual-{}-{}", now, suffix);
+        let taken: Option<String> = conn.query_row(
+            "SELECT id FROM financial_items WHERE id = ?1",
+            params![format!("{}:{}", document_id, key)],
+            |row| row.get(0),
+        ).optional().map_err(|e| e.to_string())?;
+        if taken.is_none() {
+            break key;
+        }
+        suffix += 1;
+    };
+    let row_id = format!("{}:{}", document_id, key);
+
+    let mut item = json!({
+        "id": key,
+        "label": label,
+        "normalizedLabel": label.to_lowercase(),
+        "currentYear": fields.current_year.unwrap_or(0.0),
+        "previousYear": fields.previous_year.unwrap_or(0.0),
+        "allYears": fields.all_years.unwrap_or_default(),
+        "sourcePage": "",
+        "statementType": fields.statement_type.unwrap_or_else(|| "other".to_string()),
+        "confidence": 1.0,
+        "extractionMethod": "manual",
+        "rowIndex": row_index,
+        "isHeader": fields.is_header.unwrap_or(false),
+        "isTotal": fields.is_total.unwrap_or(false),
+        "provenance": {
+            "source": "manual",
+            "setBy": edited_by,
+            "setAt": now,
+            "fields": ["item"],
+            "extractedValues": {},
+        },
+    });
+    refresh_variation(&mut item);
+    conn.execute(
+        "INSERT INTO financial_items (
+            id, doc_id, label, value_current, value_previous, row_index, statement_type,
+            is_header, source_page, source_line_text, confidence, original_json
+         ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 0, '', 1.0, ?9)",
+        params![
+            row_id,
+            document_id,
+            item["label"].as_str(),
+            item["currentYear"].as_f64(),
+            item["previousYear"].as_f64(),
+            row_index,
+            item["statementType"].as_str().map(|s| s.to_uppercase()),
+            item["isHeader"].as_bool(),
+            item.to_string(),
+        ],
+    ).map_err(|e| e.to_string())?;
+    record(&conn, document_id, &row_id, &ItemEdit {
+        field: "item".to_string(),
+        old_value: None,
+        new_value: Some(item.clone()),
+        edited_by: edited_by.clone(),
+        note,
+        edited_at: now,
+    })?;
+
+    info!(item = %row_id, document_id, "Added financial item");
+    activity::record(&app, ActivityKind::ItemsEdited, Some(document_id), &format!("Added {}", label), Some(&edited_by));
+    let _ = app.emit("financial-item-changed", json!({ "documentId": document_id, "itemId": row_id, "item": &item }));
+    statements::document_stored(&app, document_id);
+    realtime::item_saved(&app, document_id, &item);
+    Ok(item)
+}
+
+/// Whether an item's figures are extracted or set by hand, with every edit made to it.
+#[tauri::command]
+pub fn get_item_provenance(app: AppHandle, id: String, document_id: Option<i64>) -> Result<ItemProvenance, String> {
+    let conn = open(&app)?;
+    let (row_id, doc_id, item) = find_item(&conn, &id, document_id)?;
+    let mut stmt = conn.prepare(
+        "SELECT field, old_value, new_value, edited_by, note, edited_at FROM item_edits WHERE item_id = ?1 ORDER BY id",
+    ).map_err(|e| e.to_string())?;
+    let parse = |json: Option<String>| json.and_then(|json| serde_json::from_str(&json).ok());
+    let edits = stmt.query_map(params![row_id], |row| {
+        Ok(ItemEdit {
+            field: row.get(0)?,
+            old_value: parse(row.get(1)?),
+            new_value: parse(row.get(2)?),
+            edited_by: row.get(3)?,
+            note: row.get(4)?,
+            edited_at: row.get(5)?,
+        })
+    }).map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(ItemProvenance {
+        item_id: row_id,
+        document_id: doc_id,
+        source: if is_manual(&item) { ValueSource::Manual } else { ValueSource::Extracted },
+        extraction_method: item["extractionMethod"].as_str().filter(|m| *m != "manual").map(str::to_string),
+        edits,
+    })
+}
@@ -0,0 +1,75 @@
+// Item Sources - where in the original PDF each extracted item was read from (page, the row's
+// box and its text), for jumping from a figure back to the statement it came from
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::AppHandle;
+
+use crate::db;
+use crate::item_edits;
+use crate::page_ranges;
+use crate::recent_files;
+
+/// The row an item was read from, in PDF points from the page's top-left corner.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceBox {
+    pub x0: f64,
+    pub y0: f64,
+    pub x1: f64,
+    pub y1: f64,
+    pub page_width: f64,
+    pub page_height: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemSource {
+    pub item_id: String,
+    pub document_id: i64,
+    pub label: String,
+    /// 1-based; None when the parser didn't say which page
+    pub page: Option<u32>,
+    /// None for items not located on the page, e.g. from XBRL or entered by hand
+    pub bbox: Option<SourceBox>,
+    /// The line of text the item was read from, else its label
+    pub snippet: String,
+    /// The file last analyzed into the document, and whether it's still there to open
+    pub file_path: Option<String>,
+    pub file_exists: bool,
+}
+
+fn source_box(item: &Value) -> Option<SourceBox> {
+    let bbox = item.get("sourceBox")?;
+    let coord = |key: &str| bbox.get(key).and_then(Value::as_f64);
+    Some(SourceBox {
+        x0: coord("x0")?,
+        y0: coord("y0")?,
+        x1: coord("x1")?,
+        y1: coord("y1")?,
+        page_width: coord("pageWidth")?,
+        page_height: coord("pageHeight")?,
+    })
+}
+
+// Tauri Commands
+/// Where an item came from, for "jump to source". `id` is the stored row id, or the item's own id
+/// within `document_id`.
+#[tauri::command]
+pub fn get_item_source(app: AppHandle, id: String, document_id: Option<i64>) -> Result<ItemSource, String> {
+    let conn = db::open_pipeline_db(&app)?;
+    let (row_id, doc_id, item) = item_edits::find_item(&conn, &id, document_id)?;
+    let label = item["label"].as_str().unwrap_or("").to_string();
+    let snippet = item["rawLine"].as_str().map(str::trim).filter(|line| !line.is_empty())
+        .map_or_else(|| label.clone(), str::to_string);
+    let file_path = recent_files::path_for_document(&app, doc_id);
+    Ok(ItemSource {
+        item_id: row_id,
+        document_id: doc_id,
+        label,
+        page: page_ranges::item_page(&item),
+        bbox: source_box(&item),
+        snippet,
+        file_exists: file_path.as_deref().is_some_and(|path| std::path::Path::new(path).is_file()),
+        file_path,
+    })
+}
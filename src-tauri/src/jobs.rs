@@ -0,0 +1,400 @@
+// Jobs - background queue for document imports, bounded by python.max_concurrent_jobs. The same
+// slots bound every other Python interpreter the app starts, so queued and direct work never run
+// more interpreters between them than configured.
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::app_lock;
+use crate::csv_import;
+use crate::excel;
+use crate::notifications;
+use crate::python_bridge;
+use crate::tray;
+
+// Finished jobs kept for the jobs list; older ones are dropped
+const MAX_JOB_HISTORY: usize = 200;
+
+tokio::task_local! {
+    // Set while the current task holds a slot, so the interpreters it starts don't wait for another
+    static SLOT_HELD: ();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobSource {
+    Manual,
+    WatchedFolder,
+    Schedule,
+    /// Double-clicked or "Open With" in the OS
+    OpenWith,
+    /// A fincalc:// link
+    DeepLink,
+    /// Dropped onto the app window
+    DragDrop,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentKind {
+    Pdf,
+    Excel,
+    Csv,
+    /// A generated report rather than an imported document
+    Report,
+}
+
+impl DocumentKind {
+    pub fn detect(path: &Path) -> Option<Self> {
+        match path.extension()?.to_string_lossy().to_lowercase().as_str() {
+            "pdf" => Some(Self::Pdf),
+            "xlsx" | "xlsm" | "xls" | "ods" => Some(Self::Excel),
+            "csv" | "tsv" => Some(Self::Csv),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Job {
+    pub id: u64,
+    pub file_path: String,
+    pub file_name: String,
+    pub kind: DocumentKind,
+    pub source: JobSource,
+    pub status: JobStatus,
+    pub error: Option<String>,
+    pub item_count: Option<usize>,
+    pub created_at: i64,
+    pub started_at: Option<i64>,
+    pub finished_at: Option<i64>,
+}
+
+/// One file from a drop: queued as `job`, or turned away with `error`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DroppedFile {
+    pub file_path: String,
+    pub kind: Option<DocumentKind>,
+    pub job: Option<Job>,
+    pub error: Option<String>,
+}
+
+pub struct JobManager {
+    jobs: Mutex<VecDeque<Job>>,
+    next_id: AtomicU64,
+    slots: Arc<Semaphore>,
+    limit: AtomicUsize,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+impl JobManager {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            jobs: Mutex::new(VecDeque::new()),
+            next_id: AtomicU64::new(1),
+            slots: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            limit: AtomicUsize::new(max_concurrent.max(1)),
+        }
+    }
+
+    /// Apply a changed `max_concurrent_jobs`. Running jobs keep their slots; a lower limit takes
+    /// effect as they finish.
+    pub fn set_limit(&self, max_concurrent: usize) {
+        let limit = max_concurrent.max(1);
+        let previous = self.limit.swap(limit, Ordering::SeqCst);
+        if limit > previous {
+            self.slots.add_permits(limit - previous);
+        } else if limit < previous {
+            let slots = self.slots.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Ok(permits) = slots.acquire_many_owned((previous - limit) as u32).await {
+                    permits.forget();
+                }
+            });
+        }
+    }
+
+    pub fn list(&self) -> Vec<Job> {
+        self.jobs.lock().map(|jobs| jobs.iter().cloned().collect()).unwrap_or_default()
+    }
+
+    /// Apply `change` to a job and announce it with `job-updated`.
+    fn update(&self, app: &AppHandle, id: u64, change: impl FnOnce(&mut Job)) -> Option<Job> {
+        let job = {
+            let mut jobs = self.jobs.lock().ok()?;
+            let job = jobs.iter_mut().find(|j| j.id == id)?;
+            change(job);
+            job.clone()
+        };
+        let _ = app.emit("job-updated", &job);
+        tray::refresh(app);
+        Some(job)
+    }
+
+    /// Add a queued job to the list and announce it.
+    fn push(&self, app: &AppHandle, file_path: &str, file_name: String, kind: DocumentKind, source: JobSource) -> Result<Job, String> {
+        let job = Job {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            file_path: file_path.to_string(),
+            file_name,
+            kind,
+            source,
+            status: JobStatus::Queued,
+            error: None,
+            item_count: None,
+            created_at: now_secs(),
+            started_at: None,
+            finished_at: None,
+        };
+        {
+            let mut jobs = self.jobs.lock().map_err(|e| e.to_string())?;
+            jobs.push_back(job.clone());
+            while jobs.len() > MAX_JOB_HISTORY {
+                // Never drop a job that's still waiting or running
+                match jobs.iter().position(|j| matches!(j.status, JobStatus::Completed | JobStatus::Failed)) {
+                    Some(index) => { jobs.remove(index); }
+                    None => break,
+                }
+            }
+        }
+        let _ = app.emit("job-updated", &job);
+        tray::refresh(app);
+        eprintln!("[Jobs] Queued #{} {} ({:?})", job.id, job.file_name, job.kind);
+        Ok(job)
+    }
+}
+
+/// Queue a document for import; it starts as soon as a slot is free.
+pub fn enqueue(app: &AppHandle, file_path: &str, source: JobSource) -> Result<Job, String> {
+    let path = Path::new(file_path);
+    let kind = DocumentKind::detect(path).ok_or_else(|| format!("Unsupported file type: {}", file_path))?;
+    if !path.is_file() {
+        return Err(format!("File not found: {}", file_path));
+    }
+
+    let manager = app.state::<JobManager>();
+    let file_name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let job = manager.push(app, file_path, file_name, kind, source)?;
+
+    tauri::async_runtime::spawn(run_job(app.clone(), job.clone()));
+    Ok(job)
+}
+
+/// Queue every file of a drop, announcing each with `drop-file`. Files the importers can't read
+/// are reported rather than stopping the rest; the jobs then wait for free slots like any other,
+/// so a large drop never runs more imports at once than configured.
+pub fn enqueue_dropped(app: &AppHandle, paths: &[PathBuf]) -> Vec<DroppedFile> {
+    let mut seen = HashSet::new();
+    paths.iter()
+        .filter(|path| seen.insert(path.as_path()))
+        .map(|path| {
+            let file_path = path.to_string_lossy().to_string();
+            let kind = DocumentKind::detect(path);
+            let dropped = match enqueue(app, &file_path, JobSource::DragDrop) {
+                Ok(job) => DroppedFile { file_path, kind, job: Some(job), error: None },
+                Err(e) => DroppedFile { file_path, kind, job: None, error: Some(e) },
+            };
+            let _ = app.emit("drop-file", &dropped);
+            dropped
+        })
+        .collect()
+}
+
+async fn run_job(app: AppHandle, job: Job) {
+    let manager = app.state::<JobManager>();
+    // Queued jobs stay queued while the workspace is locked, and don't hold a slot meanwhile
+    let _permit = loop {
+        app_lock::unlocked(&app).await;
+        let Ok(permit) = manager.slots.clone().acquire_owned().await else { return };
+        if !app_lock::is_locked(&app) {
+            break permit;
+        }
+    };
+    manager.update(&app, job.id, |j| {
+        j.status = JobStatus::Running;
+        j.started_at = Some(now_secs());
+    });
+
+    let result = SLOT_HELD.scope((), execute(&app, &job)).await;
+    let finished = manager.update(&app, job.id, |j| {
+        j.finished_at = Some(now_secs());
+        match &result {
+            Ok(item_count) => {
+                j.status = JobStatus::Completed;
+                j.item_count = *item_count;
+            }
+            Err(e) => {
+                j.status = JobStatus::Failed;
+                j.error = Some(e.clone());
+            }
+        }
+    });
+
+    let Some(finished) = finished else { return };
+    match &result {
+        Ok(_) => eprintln!("[Jobs] #{} {} completed", finished.id, finished.file_name),
+        Err(e) => eprintln!("[Jobs] #{} {} failed: {}", finished.id, finished.file_name, e),
+    }
+    // PDF imports are announced by their analysis task
+    if finished.kind != DocumentKind::Pdf {
+        match &result {
+            Ok(_) => notifications::notify_user(&app, "Import complete", format!("{} imported", finished.file_name)),
+            Err(e) => notifications::notify_user(&app, "Import failed", format!("{}: {}", finished.file_name, e)),
+        }
+    }
+    if finished.source == JobSource::WatchedFolder && finished.status == JobStatus::Completed {
+        let _ = app.emit("folder-import-completed", &finished);
+    }
+}
+
+/// Run work that isn't a document import (e.g. a scheduled report) as a tracked job,
+/// sharing the import slots. `work` resolves to the output file path.
+pub async fn run_tracked<F>(app: &AppHandle, name: &str, kind: DocumentKind, source: JobSource, work: F) -> Result<Job, String>
+where
+    F: Future<Output = Result<String, String>>,
+{
+    let manager = app.state::<JobManager>();
+    let job = manager.push(app, "", name.to_string(), kind, source)?;
+    let _permit = manager.slots.clone().acquire_owned().await.map_err(|e| e.to_string())?;
+    manager.update(app, job.id, |j| {
+        j.status = JobStatus::Running;
+        j.started_at = Some(now_secs());
+    });
+
+    let result = SLOT_HELD.scope((), work).await;
+    let finished = manager.update(app, job.id, |j| {
+        j.finished_at = Some(now_secs());
+        match &result {
+            Ok(path) => {
+                j.status = JobStatus::Completed;
+                j.file_path = path.clone();
+            }
+            Err(e) => {
+                j.status = JobStatus::Failed;
+                j.error = Some(e.clone());
+            }
+        }
+    }).ok_or_else(|| "Job disappeared from the list".to_string())?;
+    match &result {
+        Ok(path) => eprintln!("[Jobs] #{} {} completed: {}", finished.id, finished.file_name, path),
+        Err(e) => eprintln!("[Jobs] #{} {} failed: {}", finished.id, finished.file_name, e),
+    }
+    Ok(finished)
+}
+
+/// A slot for one Python interpreter, held until dropped; waits while all are taken. None when
+/// the current task already holds one (a queued job or a `run_python_job`).
+pub async fn python_slot(app: &AppHandle) -> Option<OwnedSemaphorePermit> {
+    if SLOT_HELD.try_with(|_| ()).is_ok() {
+        return None;
+    }
+    let manager = app.state::<JobManager>();
+    manager.slots.clone().acquire_owned().await.ok()
+}
+
+/// Run a Python analysis started outside the queue while holding a slot. When none is free it
+/// waits in the jobs list as a queued `source` job, like an enqueued document would.
+pub async fn run_python_job<T, E, F>(app: &AppHandle, file_path: &str, name: &str, source: JobSource, work: F) -> Result<T, E>
+where
+    E: ToString,
+    F: Future<Output = Result<T, E>>,
+{
+    if SLOT_HELD.try_with(|_| ()).is_ok() {
+        return work.await;
+    }
+    let manager = app.state::<JobManager>();
+    if let Ok(_permit) = manager.slots.clone().try_acquire_owned() {
+        return SLOT_HELD.scope((), work).await;
+    }
+
+    // Without a job to show, the wait just runs unqueued rather than failing the analysis
+    let Ok(job) = manager.push(app, file_path, name.to_string(), DocumentKind::Pdf, source) else {
+        return SLOT_HELD.scope((), work).await;
+    };
+    let _permit = manager.slots.clone().acquire_owned().await;
+    manager.update(app, job.id, |j| {
+        j.status = JobStatus::Running;
+        j.started_at = Some(now_secs());
+    });
+    let result = SLOT_HELD.scope((), work).await;
+    manager.update(app, job.id, |j| {
+        j.finished_at = Some(now_secs());
+        match &result {
+            Ok(_) => j.status = JobStatus::Completed,
+            Err(e) => {
+                j.status = JobStatus::Failed;
+                j.error = Some(e.to_string());
+            }
+        }
+    });
+    result
+}
+
+/// Run the importer for the job's document type; returns the extracted item count.
+async fn execute(app: &AppHandle, job: &Job) -> Result<Option<usize>, String> {
+    match job.kind {
+        DocumentKind::Pdf => {
+            let response = python_bridge::run_python_analysis(
+                app.clone(), job.file_path.clone(), None, Some(job.file_name.clone()), None,
+            ).await?;
+            if response.status != "success" {
+                return Err(response.message.or(response.error).unwrap_or_else(|| "Analysis failed".to_string()));
+            }
+            Ok(response.extracted_data
+                .as_ref()
+                .and_then(|data| data.get("items"))
+                .and_then(|items| items.as_array())
+                .map(Vec::len))
+        }
+        DocumentKind::Excel => {
+            let parsed = excel::parse_excel(app.clone(), job.file_path.clone(), None, None).await?;
+            Ok(Some(parsed.item_count))
+        }
+        DocumentKind::Csv => {
+            let parsed = csv_import::parse_csv(app.clone(), job.file_path.clone(), None).await?;
+            Ok(Some(parsed.item_count))
+        }
+        DocumentKind::Report => Err("Reports are generated through run_tracked, not imported".to_string()),
+    }
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn list_jobs(manager: tauri::State<'_, JobManager>) -> Vec<Job> {
+    manager.list()
+}
+
+#[tauri::command]
+pub fn enqueue_document(app: AppHandle, file_path: String) -> Result<Job, String> {
+    enqueue(&app, &file_path, JobSource::Manual)
+}
+
+/// Queue several files at once, as a drop onto the window does.
+#[tauri::command]
+pub fn enqueue_documents(app: AppHandle, file_paths: Vec<String>) -> Vec<DroppedFile> {
+    let paths: Vec<PathBuf> = file_paths.into_iter().map(PathBuf::from).collect();
+    enqueue_dropped(&app, &paths)
+}
@@ -0,0 +1,132 @@
+// Keychain - secrets kept in the OS credential store rather than settings.json: the macOS
+// Keychain, the Secret Service (GNOME Keyring, KWallet) through secret-tool on Linux, and
+// files sealed with the user's DPAPI key on Windows. Secrets only ever reach the helper tools
+// on stdin, never on their command line where other processes could read them, and the tools
+// run on the blocking pool so async commands don't stall the runtime
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use tauri::{AppHandle, Manager};
+
+const SERVICE: &str = "financial-calculator";
+
+struct Output {
+    success: bool,
+    stdout: String,
+    stderr: String,
+}
+
+fn run(program: &str, args: &[&str], input: Option<&str>) -> Result<Output, String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("The credential store is unavailable ({}: {})", program, e))?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin.write_all(input.as_bytes()).map_err(|e| e.to_string())?;
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    Ok(Output {
+        success: output.status.success(),
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+    })
+}
+
+/// Where Windows keeps an account's sealed secret; DPAPI ties it to the signed-in user.
+fn sealed_path(app: &AppHandle, account: &str) -> Result<PathBuf, String> {
+    let name: String = account.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect();
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?.join("credentials");
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create credentials folder: {}", e))?;
+    Ok(dir.join(format!("{}.dpapi", name)))
+}
+
+fn powershell(script: &str, input: &str) -> Result<String, String> {
+    let output = run("powershell", &["-NoProfile", "-NonInteractive", "-Command", script], Some(input))?;
+    if !output.success {
+        return Err(format!("Windows credential protection failed: {}", output.stderr));
+    }
+    Ok(output.stdout.trim().to_string())
+}
+
+fn set_blocking(app: &AppHandle, account: &str, secret: &str) -> Result<(), String> {
+    if cfg!(target_os = "macos") {
+        // -U updates an existing entry instead of failing on it; -w last with no value makes
+        // security prompt for the password and its confirmation, answered from stdin
+        let input = format!("{}\n{}\n", secret, secret);
+        let output = run("security", &["add-generic-password", "-U", "-s", SERVICE, "-a", account, "-w"], Some(&input))?;
+        if !output.success {
+            return Err(format!("Keychain refused the secret: {}", output.stderr));
+        }
+    } else if cfg!(windows) {
+        let sealed = powershell("[Console]::In.ReadToEnd() | ConvertTo-SecureString -AsPlainText -Force | ConvertFrom-SecureString", secret)?;
+        std::fs::write(sealed_path(app, account)?, sealed).map_err(|e| format!("Failed to save credential: {}", e))?;
+    } else {
+        let label = format!("Financial Calculator ({})", account);
+        let output = run("secret-tool", &["store", "--label", &label, "service", SERVICE, "account", account], Some(secret))
+            .map_err(|e| format!("{}; install libsecret-tools", e))?;
+        if !output.success {
+            return Err(format!("Secret Service refused the secret: {}", output.stderr));
+        }
+    }
+    Ok(())
+}
+
+fn get_blocking(app: &AppHandle, account: &str) -> Result<Option<String>, String> {
+    if cfg!(target_os = "macos") {
+        let output = run("security", &["find-generic-password", "-s", SERVICE, "-a", account, "-w"], None)?;
+        if !output.success {
+            return if output.stderr.contains("could not be found") { Ok(None) } else { Err(format!("Keychain read failed: {}", output.stderr)) };
+        }
+        Ok(Some(output.stdout.trim_end_matches('\n').to_string()))
+    } else if cfg!(windows) {
+        let Ok(sealed) = std::fs::read_to_string(sealed_path(app, account)?) else { return Ok(None) };
+        let script = "$s = [Console]::In.ReadToEnd().Trim() | ConvertTo-SecureString; [System.Net.NetworkCredential]::new('', $s).Password";
+        powershell(script, &sealed).map(Some)
+    } else {
+        let output = run("secret-tool", &["lookup", "service", SERVICE, "account", account], None)?;
+        // secret-tool exits 1 with no output when there is no such secret
+        if !output.success && output.stderr.is_empty() {
+            return Ok(None);
+        }
+        if !output.success {
+            return Err(format!("Secret Service read failed: {}", output.stderr));
+        }
+        Ok(Some(output.stdout))
+    }
+}
+
+fn delete_blocking(app: &AppHandle, account: &str) -> Result<(), String> {
+    if cfg!(target_os = "macos") {
+        let output = run("security", &["delete-generic-password", "-s", SERVICE, "-a", account], None)?;
+        if !output.success && !output.stderr.contains("could not be found") {
+            return Err(format!("Keychain delete failed: {}", output.stderr));
+        }
+    } else if cfg!(windows) {
+        let path = sealed_path(app, account)?;
+        if path.exists() {
+            std::fs::remove_file(&path).map_err(|e| format!("Failed to remove credential: {}", e))?;
+        }
+    } else {
+        run("secret-tool", &["clear", "service", SERVICE, "account", account], None)?;
+    }
+    Ok(())
+}
+
+pub async fn set(app: &AppHandle, account: &str, secret: &str) -> Result<(), String> {
+    let (app, account, secret) = (app.clone(), account.to_string(), secret.to_string());
+    tauri::async_runtime::spawn_blocking(move || set_blocking(&app, &account, &secret)).await.map_err(|e| e.to_string())?
+}
+
+/// None when nothing is stored for `account`.
+pub async fn get(app: &AppHandle, account: &str) -> Result<Option<String>, String> {
+    let (app, account) = (app.clone(), account.to_string());
+    tauri::async_runtime::spawn_blocking(move || get_blocking(&app, &account)).await.map_err(|e| e.to_string())?
+}
+
+pub async fn delete(app: &AppHandle, account: &str) -> Result<(), String> {
+    let (app, account) = (app.clone(), account.to_string());
+    tauri::async_runtime::spawn_blocking(move || delete_blocking(&app, &account)).await.map_err(|e| e.to_string())?
+}
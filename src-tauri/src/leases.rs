@@ -0,0 +1,229 @@
+// Leases - Ind AS 116 / IFRS 16 lessee accounting: the lease liability at the present value of
+// the payments, the right-of-use asset depreciated straight-line, and what each period puts
+// through profit and loss and leaves on the balance sheet
+use chrono::{Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::corporate_events::normalize_date;
+
+const MAX_PERIODS: u32 = 1200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaymentFrequency {
+    Monthly,
+    Quarterly,
+    HalfYearly,
+    Yearly,
+}
+
+impl PaymentFrequency {
+    fn per_year(self) -> u32 {
+        match self {
+            PaymentFrequency::Monthly => 12,
+            PaymentFrequency::Quarterly => 4,
+            PaymentFrequency::HalfYearly => 2,
+            PaymentFrequency::Yearly => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaseInputs {
+    /// Fixed payment per period in the first year
+    pub payment: f64,
+    /// Lease term in payment periods
+    pub periods: u32,
+    pub frequency: PaymentFrequency,
+    /// Paid at the start of each period (rent is usually) rather than the end
+    #[serde(default)]
+    pub in_advance: bool,
+    /// Incremental borrowing rate, yearly effective, in percent
+    pub borrowing_rate: f64,
+    /// Yearly escalation of the payment, in percent, applied on each lease anniversary
+    pub escalation: Option<f64>,
+    pub initial_direct_costs: Option<f64>,
+    /// Incentives received from the lessor; reduce the asset
+    pub lease_incentives: Option<f64>,
+    /// Present value of dismantling and restoration; added to the asset
+    pub restoration_costs: Option<f64>,
+    /// Dates the periods when given
+    pub commencement_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeasePeriod {
+    pub period: u32,
+    /// Last day of the period
+    pub end_date: Option<String>,
+    pub opening_liability: f64,
+    pub payment: f64,
+    pub interest: f64,
+    /// The part of the payment that reduces the liability
+    pub principal: f64,
+    pub closing_liability: f64,
+    pub depreciation: f64,
+    pub closing_asset: f64,
+    /// Interest plus depreciation
+    pub profit_and_loss: f64,
+    /// Payments spread evenly, as an operating lease was expensed before Ind AS 116
+    pub straight_line_rent: f64,
+    /// Liability settled within twelve months of the period end
+    pub current_liability: f64,
+    pub non_current_liability: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaseYear {
+    /// Lease year, from 1
+    pub year: u32,
+    pub payments: f64,
+    pub interest: f64,
+    pub depreciation: f64,
+    pub profit_and_loss: f64,
+    pub straight_line_rent: f64,
+    pub closing_liability: f64,
+    pub closing_asset: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LeaseSchedule {
+    /// Periodic rate equivalent to the yearly borrowing rate, in percent
+    pub periodic_rate: f64,
+    /// Present value of the payments not yet made at commencement
+    pub initial_liability: f64,
+    /// Liability plus payments made at commencement, direct costs and restoration, less
+    /// incentives
+    pub initial_asset: f64,
+    pub total_payments: f64,
+    pub total_interest: f64,
+    pub periods: Vec<LeasePeriod>,
+    pub years: Vec<LeaseYear>,
+}
+
+fn validate(inputs: &LeaseInputs) -> Result<(), String> {
+    if inputs.periods == 0 || inputs.periods > MAX_PERIODS {
+        return Err(format!("The lease term must be between 1 and {} periods", MAX_PERIODS));
+    }
+    if !inputs.borrowing_rate.is_finite() || inputs.borrowing_rate < 0.0 {
+        return Err("Borrowing rate must not be negative".to_string());
+    }
+    let amounts = [Some(inputs.payment), inputs.initial_direct_costs, inputs.lease_incentives, inputs.restoration_costs];
+    if amounts.iter().flatten().any(|a| !a.is_finite() || *a < 0.0) {
+        return Err("Payments and costs must not be negative".to_string());
+    }
+    if inputs.escalation.is_some_and(|e| !e.is_finite() || e <= -100.0) {
+        return Err("Escalation must be a number above -100%".to_string());
+    }
+    Ok(())
+}
+
+pub fn schedule(inputs: &LeaseInputs) -> Result<LeaseSchedule, String> {
+    validate(inputs)?;
+    let per_year = inputs.frequency.per_year();
+    let rate = (1.0 + inputs.borrowing_rate / 100.0).powf(1.0 / per_year as f64) - 1.0;
+    let escalation = inputs.escalation.unwrap_or(0.0) / 100.0;
+    let commencement = inputs.commencement_date.as_deref()
+        .map(|raw| normalize_date(raw).ok_or_else(|| format!("Invalid date: {}", raw)))
+        .transpose()?
+        .map(|date| NaiveDate::parse_from_str(&date, "%Y-%m-%d").map_err(|e| e.to_string()))
+        .transpose()?;
+
+    let payments: Vec<f64> = (0..inputs.periods)
+        .map(|i| inputs.payment * (1.0 + escalation).powi((i / per_year) as i32))
+        .collect();
+    // Payment i falls at time i (in advance) or i + 1 (in arrears), in periods from commencement
+    let offset = if inputs.in_advance { 0 } else { 1 };
+    let paid_at_commencement = if inputs.in_advance { payments[0] } else { 0.0 };
+    let initial_liability: f64 = payments.iter().enumerate()
+        .filter(|(i, _)| i + offset > 0)
+        .map(|(i, payment)| payment / (1.0 + rate).powi((i + offset) as i32))
+        .sum();
+    let initial_asset = initial_liability + paid_at_commencement
+        + inputs.initial_direct_costs.unwrap_or(0.0)
+        + inputs.restoration_costs.unwrap_or(0.0)
+        - inputs.lease_incentives.unwrap_or(0.0);
+    let depreciation = initial_asset / inputs.periods as f64;
+    let total_payments: f64 = payments.iter().sum();
+    let straight_line_rent = total_payments / inputs.periods as f64;
+
+    let mut periods = Vec::new();
+    let mut liability = initial_liability;
+    let mut asset = initial_asset;
+    for index in 0..inputs.periods as usize {
+        // In advance, the period's payment goes out at its start (the first at commencement)
+        // and the next one is what's paid within it
+        let payment = if inputs.in_advance { payments.get(index + 1).copied().unwrap_or(0.0) } else { payments[index] };
+        let opening_liability = liability;
+        let interest = opening_liability * rate;
+        liability = (opening_liability + interest - payment).max(0.0);
+        if index + 1 == inputs.periods as usize {
+            // Rounding leaves crumbs on the last period
+            liability = 0.0;
+        }
+        asset = (asset - depreciation).max(0.0);
+        let end_date = commencement
+            .and_then(|date| date.checked_add_months(Months::new((index as u32 + 1) * 12 / per_year)))
+            .and_then(|date| date.pred_opt())
+            .map(|date| date.format("%Y-%m-%d").to_string());
+        periods.push(LeasePeriod {
+            period: index as u32 + 1,
+            end_date,
+            opening_liability,
+            payment,
+            interest,
+            principal: payment - interest,
+            closing_liability: liability,
+            depreciation,
+            closing_asset: asset,
+            profit_and_loss: interest + depreciation,
+            straight_line_rent,
+            current_liability: 0.0,
+            non_current_liability: liability,
+        });
+    }
+    // What's still owed a year on is non-current; the rest falls due within the year
+    for index in 0..periods.len() {
+        let year_on = periods.get(index + per_year as usize).map_or(0.0, |p| p.closing_liability);
+        let period = &mut periods[index];
+        period.current_liability = period.closing_liability - year_on;
+        period.non_current_liability = year_on;
+    }
+
+    let years = periods.chunks(per_year as usize).enumerate()
+        .map(|(i, chunk)| {
+            let last = chunk.last().expect("chunks are never empty");
+            LeaseYear {
+                year: i as u32 + 1,
+                payments: chunk.iter().map(|p| p.payment).sum::<f64>() + if i == 0 { paid_at_commencement } else { 0.0 },
+                interest: chunk.iter().map(|p| p.interest).sum(),
+                depreciation: chunk.iter().map(|p| p.depreciation).sum(),
+                profit_and_loss: chunk.iter().map(|p| p.profit_and_loss).sum(),
+                straight_line_rent: chunk.iter().map(|p| p.straight_line_rent).sum(),
+                closing_liability: last.closing_liability,
+                closing_asset: last.closing_asset,
+            }
+        })
+        .collect();
+
+    Ok(LeaseSchedule {
+        periodic_rate: rate * 100.0,
+        initial_liability,
+        initial_asset,
+        total_payments,
+        total_interest: periods.iter().map(|p| p.interest).sum(),
+        periods,
+        years,
+    })
+}
+
+// Tauri Commands
+/// Liability and right-of-use asset schedules for a lease, period by period and by lease year.
+#[tauri::command]
+pub fn calculate_lease_schedule(inputs: LeaseInputs) -> Result<LeaseSchedule, String> {
+    schedule(&inputs)
+}
@@ -0,0 +1,389 @@
+// Loans - EMI schedules for competing loan offers compared on everything they cost (interest,
+// fees, prepayment charges), and whether refinancing an existing loan pays and from when
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::formatting::Formatter;
+
+const MAX_TENURE_MONTHS: u32 = 600;
+// Balances under a paisa are paid off
+const PAID_OFF: f64 = 0.005;
+// Bisection on the monthly rate for the effective annual cost
+const RATE_ITERATIONS: usize = 200;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoanOffer {
+    pub name: String,
+    /// Yearly interest rate, in percent, reducing balance
+    pub annual_rate: f64,
+    pub tenure_months: u32,
+    /// Flat processing fee
+    pub processing_fee: Option<f64>,
+    /// Processing fee as a percent of the principal, on top of the flat one
+    pub processing_fee_percent: Option<f64>,
+    /// Legal, valuation, insurance and other one-off charges
+    pub other_fees: Option<f64>,
+    /// Charged on each amount prepaid, in percent
+    pub prepayment_penalty_percent: Option<f64>,
+    /// Months before prepayment is allowed; earlier prepayments wait until then
+    pub prepayment_lock_in_months: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Prepayment {
+    /// Month of the loan it's paid in, after that month's EMI; from 1
+    pub month: u32,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoanComparisonInputs {
+    pub principal: f64,
+    pub offers: Vec<LoanOffer>,
+    /// Part prepayments planned whichever offer is taken; they shorten the tenure, the EMI stays
+    #[serde(default)]
+    pub prepayments: Vec<Prepayment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoanYear {
+    pub year: u32,
+    pub principal_paid: f64,
+    pub interest_paid: f64,
+    pub prepaid: f64,
+    pub closing_balance: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OfferCost {
+    pub name: String,
+    pub emi: f64,
+    /// Months until paid off, after prepayments
+    pub months: u32,
+    pub total_interest: f64,
+    pub fees: f64,
+    pub prepayment_penalties: f64,
+    /// Interest, fees and penalties: what the loan costs over the principal
+    pub total_cost: f64,
+    /// Yearly rate at which the money received (principal less fees) equals the payments, in
+    /// percent
+    pub effective_annual_rate: f64,
+    pub schedule: Vec<LoanYear>,
+    /// The calculation step by step, for showing alongside the figures
+    pub workings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoanComparison {
+    pub principal: f64,
+    /// Cheapest first
+    pub offers: Vec<OfferCost>,
+    pub recommended: String,
+    /// How much more each of the others costs, and why the recommended one wins
+    pub reasoning: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefinanceInputs {
+    pub outstanding_principal: f64,
+    /// The current loan's yearly rate, in percent
+    pub current_rate: f64,
+    pub remaining_months: u32,
+    /// The new lender's yearly rate, in percent
+    pub new_rate: f64,
+    /// Defaults to the months remaining
+    pub new_tenure_months: Option<u32>,
+    /// Foreclosure charge on the current loan, in percent of the outstanding principal
+    pub foreclosure_penalty_percent: Option<f64>,
+    /// New lender's processing fee as a percent of the principal
+    pub processing_fee_percent: Option<f64>,
+    /// Legal, valuation, stamp duty and other one-off charges of switching
+    pub other_costs: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefinanceAnalysis {
+    pub current_emi: f64,
+    pub new_emi: f64,
+    /// Current EMI less the new one; negative when the new tenure is shorter
+    pub monthly_saving: f64,
+    pub current_remaining_interest: f64,
+    pub new_total_interest: f64,
+    pub switching_costs: f64,
+    /// Payments saved over both loans' lives, less the switching costs
+    pub net_saving: f64,
+    /// The month from which the savings cover the switching costs for good
+    pub break_even_month: Option<u32>,
+    pub recommend_refinance: bool,
+    pub workings: Vec<String>,
+}
+
+struct Run {
+    months: u32,
+    interest: f64,
+    penalties: f64,
+    schedule: Vec<LoanYear>,
+    /// (month, amount paid) after the loan is taken
+    payments: Vec<(u32, f64)>,
+}
+
+/// EMI on a reducing balance: P·r·(1+r)^n / ((1+r)^n − 1), with r the monthly rate.
+pub fn emi(principal: f64, annual_rate: f64, months: u32) -> f64 {
+    let rate = annual_rate / 1200.0;
+    if rate.abs() < 1e-12 {
+        return principal / months as f64;
+    }
+    let growth = (1.0 + rate).powi(months as i32);
+    principal * rate * growth / (growth - 1.0)
+}
+
+/// Month by month until paid off: EMIs, then any prepayment due that month and its penalty.
+fn amortize(principal: f64, annual_rate: f64, installment: f64, prepayments: &[Prepayment], offer: Option<&LoanOffer>) -> Run {
+    let rate = annual_rate / 1200.0;
+    let lock_in = offer.and_then(|o| o.prepayment_lock_in_months).unwrap_or(0);
+    let penalty_rate = offer.and_then(|o| o.prepayment_penalty_percent).unwrap_or(0.0) / 100.0;
+    let mut run = Run { months: 0, interest: 0.0, penalties: 0.0, schedule: Vec::new(), payments: Vec::new() };
+    let mut balance = principal;
+    let mut year = LoanYear { year: 1, principal_paid: 0.0, interest_paid: 0.0, prepaid: 0.0, closing_balance: principal };
+    let mut month = 0;
+    while balance > PAID_OFF && month < MAX_TENURE_MONTHS {
+        month += 1;
+        let interest = balance * rate;
+        let payment = installment.min(balance + interest);
+        balance -= payment - interest;
+        let due: f64 = prepayments.iter().filter(|p| p.month.max(lock_in) == month).map(|p| p.amount).sum();
+        let prepaid = due.min(balance.max(0.0));
+        let penalty = prepaid * penalty_rate;
+        balance -= prepaid;
+
+        run.interest += interest;
+        run.penalties += penalty;
+        run.payments.push((month, payment + prepaid + penalty));
+        year.principal_paid += payment - interest;
+        year.interest_paid += interest;
+        year.prepaid += prepaid;
+        year.closing_balance = balance.max(0.0);
+        if month % 12 == 0 || balance <= PAID_OFF {
+            let next = LoanYear { year: year.year + 1, principal_paid: 0.0, interest_paid: 0.0, prepaid: 0.0, closing_balance: year.closing_balance };
+            run.schedule.push(std::mem::replace(&mut year, next));
+        }
+    }
+    run.months = month;
+    run
+}
+
+/// Monthly rate at which `received` now equals `payments` later, as a yearly effective rate in
+/// percent.
+fn effective_annual_rate(received: f64, payments: &[(u32, f64)]) -> f64 {
+    let present_value = |rate: f64| payments.iter().map(|(month, amount)| amount / (1.0 + rate).powi(*month as i32)).sum::<f64>();
+    let (mut low, mut high) = (0.0, 1.0);
+    if present_value(low) <= received {
+        return 0.0;
+    }
+    for _ in 0..RATE_ITERATIONS {
+        let mid = (low + high) / 2.0;
+        if present_value(mid) > received { low = mid } else { high = mid }
+    }
+    ((1.0 + (low + high) / 2.0).powi(12) - 1.0) * 100.0
+}
+
+fn validate_offer(offer: &LoanOffer) -> Result<(), String> {
+    if offer.tenure_months == 0 || offer.tenure_months > MAX_TENURE_MONTHS {
+        return Err(format!("{}: tenure must be between 1 and {} months", offer.name, MAX_TENURE_MONTHS));
+    }
+    let figures = [Some(offer.annual_rate), offer.processing_fee, offer.processing_fee_percent, offer.other_fees, offer.prepayment_penalty_percent];
+    if figures.iter().flatten().any(|f| !f.is_finite() || *f < 0.0) {
+        return Err(format!("{}: rates and fees must not be negative", offer.name));
+    }
+    Ok(())
+}
+
+fn cost_offer(fmt: &Formatter, principal: f64, offer: &LoanOffer, prepayments: &[Prepayment]) -> Result<OfferCost, String> {
+    validate_offer(offer)?;
+    let installment = emi(principal, offer.annual_rate, offer.tenure_months);
+    let fees = offer.processing_fee.unwrap_or(0.0)
+        + principal * offer.processing_fee_percent.unwrap_or(0.0) / 100.0
+        + offer.other_fees.unwrap_or(0.0);
+    let run = amortize(principal, offer.annual_rate, installment, prepayments, Some(offer));
+    let total_cost = run.interest + fees + run.penalties;
+    let effective_annual_rate = effective_annual_rate(principal - fees, &run.payments);
+
+    let mut workings = vec![
+        format!(
+            "Monthly rate r = {}% / 12 = {:.5}%",
+            fmt.number(offer.annual_rate, 2), offer.annual_rate / 12.0
+        ),
+        format!(
+            "EMI = P·r·(1+r)^n / ((1+r)^n − 1) with P = {}, n = {} months: {}",
+            fmt.amount(principal), offer.tenure_months, fmt.number(installment, 2)
+        ),
+        format!("Fees: {}", fmt.amount(fees)),
+    ];
+    if run.months < offer.tenure_months {
+        workings.push(format!("Prepayments close the loan in {} months instead of {}", run.months, offer.tenure_months));
+    }
+    if run.penalties > 0.0 {
+        workings.push(format!("Prepayment charges: {}", fmt.amount(run.penalties)));
+    }
+    workings.push(format!(
+        "Total cost = interest {} + fees {} + charges {} = {}",
+        fmt.amount(run.interest), fmt.amount(fees), fmt.amount(run.penalties), fmt.amount(total_cost)
+    ));
+    workings.push(format!(
+        "Effective annual rate, fees included: {}%",
+        fmt.number(effective_annual_rate, 2)
+    ));
+
+    Ok(OfferCost {
+        name: offer.name.clone(),
+        emi: installment,
+        months: run.months,
+        total_interest: run.interest,
+        fees,
+        prepayment_penalties: run.penalties,
+        total_cost,
+        effective_annual_rate,
+        schedule: run.schedule,
+        workings,
+    })
+}
+
+pub fn compare(fmt: &Formatter, inputs: &LoanComparisonInputs) -> Result<LoanComparison, String> {
+    if !inputs.principal.is_finite() || inputs.principal <= 0.0 {
+        return Err("Principal must be a positive amount".to_string());
+    }
+    if inputs.offers.is_empty() {
+        return Err("Give at least one loan offer".to_string());
+    }
+    if inputs.prepayments.iter().any(|p| p.month == 0 || !p.amount.is_finite() || p.amount < 0.0) {
+        return Err("Prepayments need a month from 1 and a positive amount".to_string());
+    }
+    let mut offers = inputs.offers.iter()
+        .map(|offer| cost_offer(fmt, inputs.principal, offer, &inputs.prepayments))
+        .collect::<Result<Vec<_>, _>>()?;
+    offers.sort_by(|a, b| a.total_cost.total_cmp(&b.total_cost));
+
+    let best = &offers[0];
+    let mut reasoning = vec![format!(
+        "{} costs least: {} over the principal, at an effective {}% a year",
+        best.name, fmt.amount(best.total_cost), fmt.number(best.effective_annual_rate, 2)
+    )];
+    for other in &offers[1..] {
+        let mut line = format!("{} costs {} more", other.name, fmt.amount(other.total_cost - best.total_cost));
+        if other.emi < best.emi {
+            line.push_str(&format!(", though its EMI is {} lower", fmt.number(best.emi - other.emi, 2)));
+        }
+        reasoning.push(line);
+    }
+
+    Ok(LoanComparison {
+        principal: inputs.principal,
+        recommended: best.name.clone(),
+        offers,
+        reasoning,
+    })
+}
+
+pub fn refinance(fmt: &Formatter, inputs: &RefinanceInputs) -> Result<RefinanceAnalysis, String> {
+    let principal = inputs.outstanding_principal;
+    if !principal.is_finite() || principal <= 0.0 {
+        return Err("Outstanding principal must be a positive amount".to_string());
+    }
+    let new_months = inputs.new_tenure_months.unwrap_or(inputs.remaining_months);
+    for months in [inputs.remaining_months, new_months] {
+        if months == 0 || months > MAX_TENURE_MONTHS {
+            return Err(format!("Tenures must be between 1 and {} months", MAX_TENURE_MONTHS));
+        }
+    }
+    let figures = [Some(inputs.current_rate), Some(inputs.new_rate), inputs.foreclosure_penalty_percent, inputs.processing_fee_percent, inputs.other_costs];
+    if figures.iter().flatten().any(|f| !f.is_finite() || *f < 0.0) {
+        return Err("Rates and costs must not be negative".to_string());
+    }
+
+    let current_emi = emi(principal, inputs.current_rate, inputs.remaining_months);
+    let new_emi = emi(principal, inputs.new_rate, new_months);
+    let current = amortize(principal, inputs.current_rate, current_emi, &[], None);
+    let new = amortize(principal, inputs.new_rate, new_emi, &[], None);
+    let foreclosure = principal * inputs.foreclosure_penalty_percent.unwrap_or(0.0) / 100.0;
+    let processing = principal * inputs.processing_fee_percent.unwrap_or(0.0) / 100.0;
+    let switching_costs = foreclosure + processing + inputs.other_costs.unwrap_or(0.0);
+
+    // Month by month, what staying would have cost over what switching does
+    let paid = |run: &Run, month: u32| run.payments.get(month as usize - 1).map_or(0.0, |(_, amount)| *amount);
+    let horizon = current.months.max(new.months);
+    let mut cumulative = -switching_costs;
+    let mut break_even_month = (switching_costs <= 0.0).then_some(0);
+    for month in 1..=horizon {
+        cumulative += paid(&current, month) - paid(&new, month);
+        // A later run of dearer months can undo the break-even; only the last crossing holds
+        if cumulative < 0.0 {
+            break_even_month = None;
+        } else if break_even_month.is_none() {
+            break_even_month = Some(month);
+        }
+    }
+    let net_saving = cumulative;
+    let recommend_refinance = net_saving > 0.0;
+
+    let mut workings = vec![
+        format!(
+            "Current EMI on {} at {}% over {} months: {}",
+            fmt.amount(principal), fmt.number(inputs.current_rate, 2), inputs.remaining_months, fmt.number(current_emi, 2)
+        ),
+        format!(
+            "New EMI at {}% over {} months: {}",
+            fmt.number(inputs.new_rate, 2), new_months, fmt.number(new_emi, 2)
+        ),
+        format!(
+            "Switching costs = foreclosure {} + processing {} + other {} = {}",
+            fmt.amount(foreclosure), fmt.amount(processing), fmt.amount(inputs.other_costs.unwrap_or(0.0)), fmt.amount(switching_costs)
+        ),
+        format!(
+            "Interest left on the current loan {} against {} on the new one",
+            fmt.amount(current.interest), fmt.amount(new.interest)
+        ),
+        format!(
+            "Net saving = payments saved {} − switching costs {} = {}",
+            fmt.amount(net_saving + switching_costs), fmt.amount(switching_costs), fmt.amount(net_saving)
+        ),
+    ];
+    workings.push(match break_even_month {
+        Some(0) => "Nothing to recover: switching costs nothing".to_string(),
+        Some(month) => format!("The savings cover the switching costs in month {}", month),
+        None => "The savings never cover the switching costs".to_string(),
+    });
+
+    Ok(RefinanceAnalysis {
+        current_emi,
+        new_emi,
+        monthly_saving: current_emi - new_emi,
+        current_remaining_interest: current.interest,
+        new_total_interest: new.interest,
+        switching_costs,
+        net_saving,
+        break_even_month,
+        recommend_refinance,
+        workings,
+    })
+}
+
+// Tauri Commands
+/// Every offer's EMI, yearly schedule and total cost on the same principal and prepayments,
+/// cheapest first, with the recommended one.
+#[tauri::command]
+pub fn compare_loans(app: AppHandle, inputs: LoanComparisonInputs) -> Result<LoanComparison, String> {
+    compare(&Formatter::for_app(&app), &inputs)
+}
+
+#[tauri::command]
+pub fn analyze_refinance(app: AppHandle, inputs: RefinanceInputs) -> Result<RefinanceAnalysis, String> {
+    refinance(&Formatter::for_app(&app), &inputs)
+}
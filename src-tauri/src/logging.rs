@@ -0,0 +1,185 @@
+// Logging - tracing subscriber writing JSON lines to a rotating file, plus a viewer for bug reports
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+use tracing::level_filters::LevelFilter;
+use tracing::Level;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{Builder as RollingBuilder, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, Layer, Registry};
+
+use crate::settings::SettingsState;
+
+const LOG_PREFIX: &str = "financial-calculator";
+const LOG_SUFFIX: &str = "log";
+// One file per day; a week is plenty to cover "it broke yesterday"
+const MAX_LOG_FILES: usize = 7;
+const DEFAULT_LIMIT: usize = 200;
+
+pub struct LogState {
+    dir: PathBuf,
+    filter: reload::Handle<LevelFilter, Registry>,
+    // Dropping the guard flushes and stops the background writer, so it lives until `flush`
+    guard: Mutex<Option<WorkerGuard>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    /// Structured fields recorded alongside the message
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    LevelFilter::from_str(level.trim()).map_err(|_| format!("Unknown log level: {}", level))
+}
+
+pub fn log_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?.join("logs");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// Install the global subscriber: JSON lines to the rotating file, readable text to stderr.
+pub fn init(app: &AppHandle, level: &str) -> Result<LogState, String> {
+    let dir = log_dir(app)?;
+    let appender = RollingBuilder::new()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_PREFIX)
+        .filename_suffix(LOG_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(&dir)
+        .map_err(|e| e.to_string())?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+
+    let level = parse_level(level).unwrap_or(LevelFilter::INFO);
+    let (filter, handle) = reload::Layer::new(level);
+    let file_layer = fmt::layer()
+        .json()
+        .flatten_event(true)
+        .with_ansi(false)
+        .with_writer(writer);
+    let stderr_layer = fmt::layer().with_writer(std::io::stderr);
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(file_layer.and_then(stderr_layer))
+        .try_init()
+        .map_err(|e| e.to_string())?;
+
+    Ok(LogState { dir, filter: handle, guard: Mutex::new(Some(guard)) })
+}
+
+impl LogState {
+    /// Write out buffered lines before the process exits; later lines only reach stderr.
+    pub fn flush(&self) {
+        if let Ok(mut guard) = self.guard.lock() {
+            guard.take();
+        }
+    }
+}
+
+/// Log files, newest first; the date suffix sorts chronologically.
+pub fn log_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok().map(|e| e.path()))
+                .filter(|p| {
+                    p.file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with(LOG_PREFIX) && n.ends_with(LOG_SUFFIX))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files.reverse();
+    files
+}
+
+/// The last `count` raw lines across the log files, oldest first.
+pub fn tail_lines(dir: &Path, count: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for path in log_files(dir) {
+        let Ok(file) = fs::File::open(&path) else { continue };
+        let mut day: Vec<String> = BufReader::new(file).lines().map_while(Result::ok).collect();
+        day.append(&mut lines);
+        lines = day;
+        if lines.len() >= count {
+            break;
+        }
+    }
+    let skip = lines.len().saturating_sub(count);
+    lines.split_off(skip)
+}
+
+fn parse_entry(line: &str) -> Option<LogEntry> {
+    let mut value: serde_json::Map<String, serde_json::Value> = serde_json::from_str(line).ok()?;
+    let mut take = |key: &str| value.remove(key).and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default();
+    let timestamp = take("timestamp");
+    let level = take("level");
+    let target = take("target");
+    let message = take("message");
+    Some(LogEntry { timestamp, level, target, message, fields: value })
+}
+
+// Tauri Commands
+/// The most recent entries at `level` or more severe, newest first.
+#[tauri::command]
+pub fn get_recent_logs(
+    state: tauri::State<'_, LogState>,
+    level: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<LogEntry>, String> {
+    let threshold = match level.as_deref() {
+        Some(level) => parse_level(level)?,
+        None => LevelFilter::TRACE,
+    };
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+
+    let mut entries = Vec::new();
+    for path in log_files(&state.dir) {
+        let file = fs::File::open(&path).map_err(|e| e.to_string())?;
+        let mut day: Vec<LogEntry> = BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| parse_entry(&line))
+            .filter(|entry| Level::from_str(&entry.level).is_ok_and(|l| threshold >= l))
+            .collect();
+        day.reverse();
+        entries.extend(day.into_iter().take(limit - entries.len()));
+        if entries.len() >= limit {
+            break;
+        }
+    }
+    Ok(entries)
+}
+
+/// Change the level immediately and remember it for the next launch.
+#[tauri::command]
+pub async fn set_log_level(
+    state: tauri::State<'_, LogState>,
+    settings: tauri::State<'_, SettingsState>,
+    level: String,
+) -> Result<(), String> {
+    let filter = parse_level(&level)?;
+    state.filter
+        .modify(|current| *current = filter)
+        .map_err(|e| e.to_string())?;
+    tracing::info!(level = %filter, "Log level changed");
+
+    let mut store = settings.write().await;
+    store.set_log_level(filter.to_string().to_lowercase());
+    store.save()
+}
@@ -1,51 +1,309 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod error;
 mod settings;
 mod ollama;
 mod python_bridge;
+mod python_setup;
+mod profiles;
+mod scraper;
+mod db;
+mod market_cache;
+mod market_providers;
+mod metric_cache;
+mod watchlist;
+mod alerts;
+mod price_history;
+mod corporate_events;
+mod quote_stream;
+mod indices;
+mod screener;
+mod statements;
+mod mutual_funds;
+mod edgar;
+mod symbols;
+mod news;
+mod shareholding;
+mod calendar;
+mod excel;
+mod numbers;
+mod units;
+mod periods;
+mod duplicates;
+mod consolidation;
+mod ratio_alerts;
+mod metric_formulas;
+mod scenarios;
+mod monte_carlo;
+mod sensitivity;
+mod benchmarks;
+mod goal_seek;
+mod shortcuts;
+mod recent_files;
+mod file_associations;
+mod formatting;
+mod theme;
+mod session;
+mod mapping_history;
+mod terminology;
+mod item_edits;
+mod item_sources;
+mod anomalies;
+mod chunks;
+mod vector_index;
+mod notes;
+mod dashboard;
+mod keychain;
+mod oauth;
+mod trading_calendar;
+mod scheduler;
+mod scraper_usage;
+mod portfolio;
+mod dividends;
+mod corporate_actions;
+mod sql_console;
+mod derived;
+mod realtime;
+mod app_lock;
+mod retention;
+mod integrity;
+mod activity;
+mod preflight;
+mod timeouts;
+mod gst_invoices;
+mod bank_statements;
+mod csv_import;
+mod ocr;
+mod page_ranges;
+mod page_render;
+mod pipeline_profile;
+mod shutdown;
+mod partial_results;
+mod document_locks;
+mod inspect;
+mod parse_cache;
+mod jobs;
+mod folder_watch;
+mod ratios;
+mod report;
+mod report_templates;
+mod export;
+mod pptx;
+mod snapshots;
+mod schedules;
+mod logging;
+mod tasks;
+mod tray;
+mod notifications;
+mod open_with;
+mod updater;
+mod cli;
+mod diagnostics;
+mod health;
+mod usage;
+mod windows;
+mod api_server;
+mod dcf;
+mod payroll;
+mod retirement;
+mod inflation;
+mod fx_rates;
+mod loans;
+mod credit;
+mod leases;
+mod esop;
+mod mcp;
+mod plugins;
+mod formulas;
+mod grpc;
+mod sync;
+mod backup;
+mod workspace;
+mod ai_analysis;
+mod chat_references;
+mod chat_sessions;
+mod prompt_pipeline;
+mod http;
 
 use tauri::Manager;
+use tauri_plugin_deep_link::DeepLinkExt;
 
 fn main() {
-    tauri::Builder::default()
+    let args: Vec<String> = std::env::args().collect();
+    let invocation = match cli::parse(&args) {
+        cli::Invocation::Exit(code) => std::process::exit(code),
+        invocation => invocation,
+    };
+    let headless = !matches!(invocation, cli::Invocation::Gui);
+    let mut context = tauri::generate_context!();
+    if headless {
+        // Headless runs never open the main window
+        context.config_mut().app.windows.clear();
+    }
+
+    let mut builder = tauri::Builder::default();
+    if !headless {
+        // Must come first: a second launch hands its arguments to this instance and exits.
+        // Headless runs skip it so scripts work while the app is open.
+        builder = builder.plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+            tray::show_main_window(app);
+            open_with::open_args(app, &argv, std::path::Path::new(&cwd));
+        }));
+    }
+    builder
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
-        .setup(|app| {
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
+        .plugin(tauri_plugin_updater::Builder::new().build())
+        .plugin(shortcuts::plugin())
+        .setup(move |app| {
             let app_handle = app.handle().clone();
-            let settings_store = settings::SettingsStore::new(&app_handle)
+            let profile_manager = profiles::ProfileManager::new(&app_handle)
+                .expect("Failed to initialize profiles");
+            let settings_store = settings::SettingsStore::new(profile_manager.settings_path())
                 .expect("Failed to initialize settings store");
 
-            app.manage(std::sync::Mutex::new(settings_store));
+            let max_concurrent_jobs = settings_store.get().python.max_concurrent_jobs;
+            match logging::init(&app_handle, &settings_store.get().log_level) {
+                Ok(log_state) => {
+                    app.manage(log_state);
+                }
+                Err(e) => eprintln!("Failed to initialize logging: {}", e),
+            }
+            diagnostics::install_panic_hook(&app_handle);
+
+            app.manage(std::sync::Mutex::new(profile_manager));
+            app.manage(settings::SettingsState::new(settings_store));
+            app.manage(app_lock::AppLock::new(&app_handle));
+            app.manage(http::HttpClient::new());
+            app.manage(scraper::NativeScraper::new(app_handle.clone()));
+            app.manage(market_cache::MarketCache::new());
+            app.manage(document_locks::DocumentLocks::new());
+            app.manage(metric_cache::MetricCache::new());
+            app.manage(vector_index::VectorIndex::new());
+            app.manage(pipeline_profile::LastAnalysisProfile::new());
+            app.manage(quote_stream::QuoteStreamer::new());
+            app.manage(jobs::JobManager::new(max_concurrent_jobs));
+            app.manage(folder_watch::FolderWatcher::new());
+            app.manage(tasks::TaskRegistry::new());
+            app.manage(ollama::OllamaBridge::new());
+            app.manage(updater::PendingUpdate::new());
+            app.manage(api_server::ApiServer::new());
+            app.manage(grpc::GrpcServer::new());
+            app.manage(sync::SyncEngine::new());
+            app.manage(realtime::Realtime::new());
+            app.manage(plugins::PluginRegistry::new());
+            app.manage(theme::ThemeState::new());
+            app.manage(preflight::Preflight::new());
+            plugins::load(&app_handle);
+
+            match invocation {
+                cli::Invocation::Headless(cli_args) => {
+                    cli::run(app_handle, cli_args);
+                    return Ok(());
+                }
+                cli::Invocation::Mcp => {
+                    mcp::serve_stdio(app_handle);
+                    return Ok(());
+                }
+                _ => {}
+            }
+
+            watchlist::start_refresh_task(app_handle.clone());
+            alerts::start_alert_task(app_handle.clone());
+            folder_watch::restore(&app_handle);
+            partial_results::mark_interrupted(&app_handle);
+            schedules::start_schedule_task(app_handle.clone());
+            scheduler::start_scheduler_task(app_handle.clone());
+            updater::start_update_check(app_handle.clone());
+            api_server::bridge_events(&app_handle);
+            api_server::start_if_enabled(app_handle.clone());
+            grpc::start_if_enabled(app_handle.clone());
+            realtime::start_task(app_handle.clone());
+            app_lock::start_idle_task(app_handle.clone());
+            retention::start_cleanup_task(app_handle.clone());
+
+            if let Err(e) = settings::watch_settings_file(app_handle.clone()) {
+                eprintln!("Failed to watch settings file: {}", e);
+            }
 
             // Start Ollama bridge on app start if configured
             let handle_for_async = app_handle.clone();
             tauri::async_runtime::spawn(async move {
-                let state = handle_for_async.state::<std::sync::Mutex<settings::SettingsStore>>();
+                let state = handle_for_async.state::<settings::SettingsState>();
                 let should_start = {
-                    let store = state.lock().unwrap();
+                    let store = state.read().await;
                     store.get().auto_start_ollama
                 };
 
                 if should_start {
-                    let service = ollama::OllamaBridge::new();
+                    let service = handle_for_async.state::<ollama::OllamaBridge>();
                     if let Err(e) = service.start(&handle_for_async).await {
-                        eprintln!("Failed to start Ollama bridge: {}", e);
+                        tracing::error!("Failed to start Ollama bridge: {}", e);
                     }
-                    // In Tauri v2, you usually manage state on the app/handle during setup
-                    handle_for_async.manage(service);
+                    tray::refresh(&handle_for_async);
                 }
+                // After the bridge start, so the model preload finds Ollama up
+                preflight::run(&handle_for_async).await;
             });
 
+            if let Err(e) = tray::create(&app_handle) {
+                tracing::error!("Failed to create tray icon: {}", e);
+            }
+            shortcuts::register_all(&app_handle);
+            file_associations::refresh_if_enabled(&app_handle);
+            theme::apply(&app_handle);
+            app.manage(session::SessionStore::new());
+
+            // Links and files this instance was launched with, then links arriving later
+            #[cfg(any(windows, target_os = "linux"))]
+            if let Err(e) = app.deep_link().register_all() {
+                tracing::warn!("Failed to register fincalc:// links: {}", e);
+            }
+            let link_handle = app_handle.clone();
+            app.deep_link().on_open_url(move |event| open_with::open_urls(&link_handle, event.urls()));
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                open_with::open_urls(&app_handle, urls);
+            }
+            open_with::open_args(&app_handle, &args, &std::env::current_dir().unwrap_or_default());
 
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
+        .on_window_event(|window, event| {
+            // Closing the main window keeps the app running in the tray; "Quit" in the tray exits
+            match event {
+                tauri::WindowEvent::CloseRequested { api, .. }
+                    if window.label() == windows::MAIN_WINDOW && window.app_handle().try_state::<tray::TrayState>().is_some() =>
+                {
+                    let _ = window.hide();
+                    api.prevent_close();
+                }
+                tauri::WindowEvent::Destroyed => shutdown::window_destroyed(window.app_handle(), window.label()),
+                tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_) => session::track(window.app_handle(), window.label()),
+                tauri::WindowEvent::ThemeChanged(os_theme) => theme::os_theme_changed(window.app_handle(), *os_theme),
+                // Dropped documents are queued here, so the import slots bound them however many arrive
+                tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }) if window.label() == windows::MAIN_WINDOW => {
+                    jobs::enqueue_dropped(window.app_handle(), paths);
+                }
+                _ => {}
+            }
+        })
+        .invoke_handler(app_lock::guarded(tauri::generate_handler![
             // Settings commands
             settings::get_settings,
             settings::update_llm_settings,
+            settings::update_scraper_settings,
+            settings::update_python_settings,
             settings::update_setting,
+            settings::reset_settings,
+            // Profile commands
+            profiles::list_profiles,
+            profiles::create_profile,
+            profiles::switch_profile,
             // Ollama commands
             ollama::start_ollama_bridge,
             ollama::stop_ollama_bridge,
@@ -60,11 +318,25 @@ fn main() {
             ollama::generate_completion,
             ollama::get_chat_history,
             ollama::clear_chat_history,
+            chat_sessions::summarize_session,
+            chat_sessions::search_chat_history,
+            chat_sessions::get_session_config,
+            chat_sessions::set_session_config,
+            chat_sessions::list_chat_sessions,
+            chat_sessions::export_chat,
             // Python bridge commands
             python_bridge::run_python_analysis,
-            python_bridge::update_terminology_mapping,
+            python_bridge::resume_analysis,
+            python_bridge::reanalyze_pages,
+            pipeline_profile::profile_last_analysis,
+            partial_results::list_partial_analyses,
+            partial_results::discard_partial_analysis,
+            python_bridge::get_bridge_version,
+            python_setup::check_python_setup,
             python_bridge::calculate_metrics,
+            metric_cache::clear_metric_cache,
             python_bridge::get_db_data,
+            python_bridge::stream_db_data,
             // Database streaming commands
             python_bridge::start_db_streaming,
             python_bridge::stop_db_streaming,
@@ -72,9 +344,280 @@ fn main() {
             python_bridge::search_companies,
             python_bridge::get_company_details,
             python_bridge::get_stock_quote,
+            scraper::get_stock_quotes,
+            quote_stream::start_quote_streaming,
+            quote_stream::stop_quote_streaming,
             python_bridge::search_web,
             python_bridge::get_scraper_status,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+            // Watchlist commands
+            watchlist::add_to_watchlist,
+            watchlist::remove_from_watchlist,
+            watchlist::get_watchlist,
+            // Price alert commands
+            alerts::create_price_alert,
+            alerts::list_price_alerts,
+            alerts::delete_price_alert,
+            alerts::get_alert_history,
+            // Market data commands
+            price_history::get_historical_prices,
+            corporate_events::get_corporate_events,
+            indices::get_index_quote,
+            screener::screen_companies,
+            statements::import_listed_financials,
+            statements::get_items_after,
+            statements::list_low_confidence_items,
+            item_edits::update_financial_item,
+            item_edits::add_financial_item,
+            item_edits::get_item_provenance,
+            item_sources::get_item_source,
+            page_render::render_pdf_page,
+            units::get_document_units,
+            units::set_document_units,
+            periods::get_document_periods,
+            periods::set_document_periods,
+            periods::set_item_periods,
+            periods::get_item_trend,
+            periods::get_item_timeseries,
+            duplicates::find_duplicate_documents,
+            duplicates::merge_documents,
+            duplicates::replace_document,
+            consolidation::consolidate_documents,
+            consolidation::consolidate_foreign_operations,
+            ratio_alerts::create_ratio_rule,
+            ratio_alerts::list_ratio_rules,
+            ratio_alerts::delete_ratio_rule,
+            ratio_alerts::evaluate_ratio_rules,
+            anomalies::get_document_anomalies,
+            chunks::get_document_chunks,
+            chunks::embed_document_chunks,
+            vector_index::semantic_search,
+            notes::add_note,
+            notes::list_notes,
+            notes::delete_note,
+            dashboard::pin_metric,
+            dashboard::unpin_metric,
+            dashboard::list_pinned_metrics,
+            dashboard::get_dashboard_data,
+            oauth::save_oauth_provider,
+            oauth::list_oauth_providers,
+            oauth::delete_oauth_provider,
+            oauth::connect_provider,
+            oauth::disconnect_provider,
+            oauth::refresh_provider_token,
+            oauth::get_provider_token,
+            trading_calendar::is_market_open,
+            trading_calendar::get_market_holidays,
+            trading_calendar::update_market_holidays,
+            scheduler::list_schedules,
+            scheduler::create_schedule,
+            scheduler::run_schedule_now,
+            scheduler::set_schedule_enabled,
+            scheduler::delete_schedule,
+            portfolio::import_trades,
+            portfolio::list_holdings,
+            portfolio::list_transactions,
+            portfolio::delete_transaction,
+            portfolio::get_realized_gains,
+            portfolio::get_portfolio_pnl,
+            dividends::add_dividend,
+            dividends::delete_dividend,
+            dividends::list_dividends,
+            dividends::fetch_dividends,
+            dividends::get_dividend_income,
+            dividends::get_dividend_yield,
+            corporate_actions::record_corporate_action,
+            corporate_actions::list_corporate_actions,
+            corporate_actions::delete_corporate_action,
+            corporate_actions::fetch_corporate_actions,
+            sql_console::run_readonly_query,
+            derived::get_ratio_table,
+            derived::get_common_size,
+            derived::refresh_derived_tables,
+            ratio_alerts::get_ratio_findings,
+            metric_formulas::list_metric_formulas,
+            metric_formulas::save_metric_formula,
+            metric_formulas::delete_metric_formula,
+            metric_formulas::evaluate_metric_formula,
+            scenarios::create_scenario,
+            scenarios::list_scenarios,
+            scenarios::get_scenario,
+            scenarios::delete_scenario,
+            scenarios::compare_scenarios,
+            monte_carlo::run_monte_carlo,
+            sensitivity::run_sensitivity,
+            benchmarks::list_industry_benchmarks,
+            benchmarks::benchmark_against_industry,
+            goal_seek::goal_seek,
+            shortcuts::get_shortcuts,
+            shortcuts::set_shortcut,
+            recent_files::get_recent_files,
+            recent_files::pin_file,
+            recent_files::clear_recent,
+            file_associations::get_file_associations,
+            file_associations::set_file_associations,
+            theme::get_effective_theme,
+            session::get_last_session,
+            session::restore_session,
+            session::update_session,
+            mapping_history::get_mapping_history,
+            mapping_history::revert_mapping,
+            terminology::list_mappings,
+            terminology::add_mapping,
+            terminology::delete_mapping,
+            terminology::update_terminology_mapping,
+            terminology::preview_mapping_match,
+            mutual_funds::search_mutual_funds,
+            mutual_funds::get_fund_nav,
+            edgar::search_sec_companies,
+            edgar::import_sec_financials,
+            symbols::resolve_symbol,
+            news::get_company_news,
+            shareholding::get_shareholding_pattern,
+            calendar::get_market_calendar,
+            excel::parse_excel,
+            csv_import::parse_csv,
+            inspect::inspect_document,
+            jobs::list_jobs,
+            jobs::enqueue_document,
+            jobs::enqueue_documents,
+            folder_watch::watch_folder,
+            folder_watch::unwatch_folder,
+            folder_watch::list_watched_folders,
+            report::generate_report,
+            export::export_analysis_xlsx,
+            export::export_analysis,
+            schedules::create_report_schedule,
+            schedules::list_report_schedules,
+            schedules::set_report_schedule_enabled,
+            schedules::delete_report_schedule,
+            report_templates::list_report_templates,
+            report_templates::create_report_template,
+            report_templates::update_report_template,
+            report_templates::delete_report_template,
+            export::copy_table_to_clipboard,
+            pptx::export_analysis_pptx,
+            snapshots::create_snapshot,
+            snapshots::list_snapshots,
+            snapshots::delete_snapshot,
+            snapshots::diff_snapshots,
+            // Logging commands
+            logging::get_recent_logs,
+            logging::set_log_level,
+            // Background task commands
+            tasks::list_background_tasks,
+            tasks::cancel_task,
+            // Tray commands
+            tray::set_background_refresh_paused,
+            tray::is_background_refresh_paused,
+            alerts::mark_alerts_read,
+            // Updater commands
+            updater::check_for_updates,
+            updater::install_update,
+            // Diagnostics commands
+            diagnostics::create_diagnostics_bundle,
+            health::run_health_check,
+            // Usage statistics commands
+            usage::get_app_statistics,
+            usage::clear_app_statistics,
+            // Window commands
+            windows::open_window,
+            windows::list_windows,
+            windows::focus_window,
+            windows::close_window,
+            // API server commands
+            api_server::get_api_server_status,
+            api_server::set_api_server_enabled,
+            api_server::regenerate_api_token,
+            // Valuation commands
+            dcf::run_dcf,
+            // Payroll commands
+            payroll::calculate_salary_breakdown,
+            // Retirement commands
+            retirement::plan_retirement,
+            // Inflation commands
+            inflation::get_cpi_series,
+            inflation::get_average_inflation,
+            inflation::calculate_real_return,
+            inflation::adjust_for_inflation,
+            // FX rate commands
+            fx_rates::get_fx_rates,
+            fx_rates::set_fx_rates,
+            fx_rates::delete_fx_rates,
+            // Loan commands
+            loans::compare_loans,
+            loans::analyze_refinance,
+            // Credit analysis commands
+            credit::get_credit_analysis,
+            credit::screen_borrowers,
+            // Lease commands
+            leases::calculate_lease_schedule,
+            // ESOP commands
+            esop::value_esop_grant,
+            // Plugin commands
+            plugins::list_plugins,
+            plugins::reload_plugins,
+            plugins::run_plugin_command,
+            plugins::compute_plugin_metrics,
+            // Formula commands
+            formulas::list_formulas,
+            formulas::save_formula,
+            formulas::delete_formula,
+            formulas::run_formula,
+            // gRPC commands
+            grpc::get_grpc_status,
+            grpc::set_grpc_enabled,
+            // Sync commands
+            sync::sync_now,
+            sync::get_sync_status,
+            realtime::get_realtime_status,
+            // Cloud backup commands
+            backup::backup_to_cloud,
+            backup::restore_from_cloud,
+            backup::list_cloud_backups,
+            // Workspace commands
+            workspace::export_workspace,
+            workspace::import_workspace,
+            // App lock commands
+            app_lock::enable_app_lock,
+            app_lock::disable_app_lock,
+            app_lock::set_app_lock_idle_minutes,
+            app_lock::lock_workspace,
+            app_lock::unlock_workspace,
+            app_lock::record_activity,
+            app_lock::get_app_lock_status,
+            // Retention commands
+            retention::preview_cleanup,
+            retention::run_cleanup,
+            // Integrity commands
+            integrity::get_file_ledger,
+            integrity::verify_document_integrity,
+            // Activity commands
+            activity::get_activity_timeline,
+            // Preflight commands
+            preflight::run_preflight,
+            preflight::get_preflight_status,
+            // GST invoice commands
+            gst_invoices::list_gst_invoices,
+            gst_invoices::get_gst_invoice,
+            gst_invoices::delete_gst_invoice,
+            gst_invoices::get_input_credit_summary,
+            // Bank statement commands
+            bank_statements::list_bank_transactions,
+            bank_statements::set_bank_transaction_category,
+            bank_statements::delete_bank_statement,
+            bank_statements::list_bank_category_rules,
+            bank_statements::create_bank_category_rule,
+            bank_statements::delete_bank_category_rule,
+            bank_statements::get_cash_flow_summary,
+            // AI analysis commands
+            ai_analysis::ai_analyze_document,
+            ai_analysis::list_ai_analyses,
+            ai_analysis::delete_ai_analysis,
+        ]))
+        .build(context)
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            shutdown::handle_run_event(app, &event);
+            open_with::handle_run_event(app, event);
+        });
 }
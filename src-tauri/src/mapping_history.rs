@@ -0,0 +1,129 @@
+// Mapping History - every set of terminology mappings saved, kept as numbered versions
+// so a bad bulk edit of label mappings can be rolled back
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use tracing::info;
+
+use crate::db;
+use crate::terminology::{self, TermMapping};
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS mapping_versions (
+    version INTEGER PRIMARY KEY AUTOINCREMENT,
+    mappings TEXT NOT NULL,        -- JSON, the full set of mappings in effect
+    entry_count INTEGER NOT NULL,
+    reverted_from INTEGER,         -- the version restored, for reverts
+    created_at INTEGER NOT NULL
+);
+";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MappingVersion {
+    pub version: i64,
+    pub entry_count: usize,
+    pub reverted_from: Option<i64>,
+    pub created_at: i64,
+    /// Keys added, removed or edited since the version before
+    pub changed_keys: Vec<String>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Mappings by key. The store keeps a list of `{key, label, keywords_*}` entries; an object keyed by
+/// label key is read the same way.
+fn entries(mappings: &Value) -> BTreeMap<String, &Value> {
+    match mappings {
+        Value::Array(items) => items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let key = item.get("key").and_then(Value::as_str).map_or_else(|| format!("#{}", i), str::to_string);
+                (key, item)
+            })
+            .collect(),
+        Value::Object(map) => map.iter().map(|(key, item)| (key.clone(), item)).collect(),
+        _ => BTreeMap::new(),
+    }
+}
+
+fn changed_keys(previous: Option<&Value>, current: &Value) -> Vec<String> {
+    let before = previous.map(entries).unwrap_or_default();
+    let after = entries(current);
+    let mut keys: Vec<String> = after
+        .iter()
+        .filter(|(key, item)| before.get(*key) != Some(*item))
+        .map(|(key, _)| key.clone())
+        .collect();
+    keys.extend(before.keys().filter(|key| !after.contains_key(*key)).cloned());
+    keys.sort();
+    keys
+}
+
+/// Keep `mappings` as the newest version. Returns its number.
+pub fn record(app: &AppHandle, mappings: &Value, reverted_from: Option<i64>) -> Result<i64, String> {
+    let conn = db::open_app_db(app)?;
+    conn.execute(
+        "INSERT INTO mapping_versions (mappings, entry_count, reverted_from, created_at) VALUES (?1, ?2, ?3, ?4)",
+        params![mappings.to_string(), entries(mappings).len() as i64, reverted_from, now_secs()],
+    ).map_err(|e| e.to_string())?;
+    let version = conn.last_insert_rowid();
+    info!(version, entries = entries(mappings).len(), "Recorded terminology mapping version");
+    Ok(version)
+}
+
+fn load(app: &AppHandle, version: i64) -> Result<Value, String> {
+    let conn = db::open_app_db(app)?;
+    let json: Option<String> = conn
+        .query_row("SELECT mappings FROM mapping_versions WHERE version = ?1", params![version], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let json = json.ok_or_else(|| format!("Mapping version {} not found", version))?;
+    serde_json::from_str(&json).map_err(|e| format!("Mapping version {} is unreadable: {}", version, e))
+}
+
+// Tauri Commands
+/// Newest first, each with the keys it changed.
+#[tauri::command]
+pub fn get_mapping_history(app: AppHandle, limit: Option<usize>) -> Result<Vec<MappingVersion>, String> {
+    let conn = db::open_app_db(&app)?;
+    let mut stmt = conn.prepare(
+        "SELECT version, mappings, reverted_from, created_at FROM mapping_versions ORDER BY version DESC LIMIT ?1",
+    ).map_err(|e| e.to_string())?;
+    // One extra row so the oldest listed version can be compared with the one before it
+    let limit = limit.map_or(-1, |l| l as i64 + 1);
+    let rows = stmt.query_map(params![limit], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, Option<i64>>(2)?, row.get::<_, i64>(3)?))
+    }).map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    let parsed: Vec<Value> = rows.iter().map(|(_, json, _, _)| serde_json::from_str(json).unwrap_or(Value::Null)).collect();
+    let shown = if limit < 0 { rows.len() } else { rows.len().min(limit as usize - 1) };
+    Ok(rows.iter().take(shown).enumerate().map(|(i, (version, _, reverted_from, created_at))| MappingVersion {
+        version: *version,
+        entry_count: entries(&parsed[i]).len(),
+        reverted_from: *reverted_from,
+        created_at: *created_at,
+        changed_keys: changed_keys(parsed.get(i + 1), &parsed[i]),
+    }).collect())
+}
+
+/// Put an earlier version's mappings back in effect and record them as a new version, so the
+/// revert itself can be undone. Returns the restored mappings, also sent as `mapping-reverted`.
+#[tauri::command]
+pub fn revert_mapping(app: AppHandle, version: i64) -> Result<Vec<TermMapping>, String> {
+    let (new_version, mappings) = terminology::restore(&app, &load(&app, version)?, version)?;
+    info!(version, new_version, "Reverted terminology mapping");
+    let _ = app.emit("mapping-reverted", serde_json::json!({ "version": new_version, "revertedFrom": version, "mappings": &mappings }));
+    Ok(mappings)
+}
@@ -0,0 +1,133 @@
+// Market Cache - TTL cache for company details and quotes (memory + SQLite)
+use rusqlite::{params, OptionalExtension};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+use crate::db;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS market_cache (
+    kind TEXT NOT NULL,        -- 'DETAILS', 'QUOTE'
+    symbol TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    payload TEXT NOT NULL,     -- JSON
+    fetched_at INTEGER NOT NULL,
+    PRIMARY KEY (kind, symbol, exchange)
+);
+";
+
+type CacheKey = (String, String, String);
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn key(kind: &str, symbol: &str, exchange: &str) -> CacheKey {
+    (kind.to_string(), symbol.trim().to_uppercase(), exchange.trim().to_uppercase())
+}
+
+/// In-memory layer in front of the `market_cache` table. SQLite keeps entries
+/// across restarts; memory avoids hitting the disk for hot symbols.
+#[derive(Default)]
+pub struct MarketCache {
+    entries: Mutex<HashMap<CacheKey, (u64, serde_json::Value)>>,
+}
+
+impl MarketCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, app: &AppHandle, kind: &str, symbol: &str, exchange: &str, ttl_secs: u64) -> Option<serde_json::Value> {
+        let key = key(kind, symbol, exchange);
+        let now = now_secs();
+
+        if let Some((fetched_at, payload)) = self.entries.lock().ok()?.get(&key) {
+            if now.saturating_sub(*fetched_at) < ttl_secs {
+                return Some(payload.clone());
+            }
+        }
+
+        let conn = db::open_app_db(app).ok()?;
+        let row: Option<(String, i64)> = conn.query_row(
+            "SELECT payload, fetched_at FROM market_cache WHERE kind = ?1 AND symbol = ?2 AND exchange = ?3",
+            params![key.0, key.1, key.2],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).optional().ok()?;
+
+        let (payload, fetched_at) = row?;
+        let fetched_at = fetched_at as u64;
+        if now.saturating_sub(fetched_at) >= ttl_secs {
+            return None;
+        }
+
+        let payload: serde_json::Value = serde_json::from_str(&payload).ok()?;
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(key, (fetched_at, payload.clone()));
+        }
+        Some(payload)
+    }
+
+    /// Forget the in-memory entries, e.g. on a profile switch: they were fetched under the old
+    /// profile's settings, and its database no longer backs them.
+    pub fn clear_memory(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+
+    pub fn put(&self, app: &AppHandle, kind: &str, symbol: &str, exchange: &str, payload: &serde_json::Value) {
+        let key = key(kind, symbol, exchange);
+        let now = now_secs();
+
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(key.clone(), (now, payload.clone()));
+        }
+
+        let result = db::open_app_db(app).and_then(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO market_cache (kind, symbol, exchange, payload, fetched_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![key.0, key.1, key.2, payload.to_string(), now as i64],
+            ).map_err(|e| e.to_string())
+        });
+        if let Err(e) = result {
+            eprintln!("[MarketCache] Failed to persist {} {}: {}", kind, key.1, e);
+        }
+    }
+
+    /// Drop entries fetched more than `max_age_secs` ago, in memory and on disk; returns how many
+    /// rows went.
+    pub fn prune(&self, app: &AppHandle, max_age_secs: u64) -> Result<usize, String> {
+        let cutoff = now_secs().saturating_sub(max_age_secs);
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.retain(|_, (fetched_at, _)| *fetched_at >= cutoff);
+        }
+        let conn = db::open_app_db(app)?;
+        conn.execute("DELETE FROM market_cache WHERE fetched_at < ?1", params![cutoff as i64])
+            .map_err(|e| e.to_string())
+    }
+
+    /// Every persisted entry of one kind regardless of age, as (symbol, exchange, payload).
+    pub fn all(&self, app: &AppHandle, kind: &str) -> Result<Vec<(String, String, serde_json::Value)>, String> {
+        let conn = db::open_app_db(app)?;
+        let mut stmt = conn.prepare("SELECT symbol, exchange, payload FROM market_cache WHERE kind = ?1")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt.query_map(params![kind], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+        }).map_err(|e| e.to_string())?;
+
+        let mut entries = Vec::new();
+        for row in rows {
+            let (symbol, exchange, payload) = row.map_err(|e| e.to_string())?;
+            if let Ok(payload) = serde_json::from_str(&payload) {
+                entries.push((symbol, exchange, payload));
+            }
+        }
+        Ok(entries)
+    }
+}
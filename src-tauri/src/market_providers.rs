@@ -0,0 +1,185 @@
+// Market Providers - quotes and price history from Yahoo Finance or Alpha Vantage, for listings
+// outside India or when the NSE/BSE scraper is being blocked
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::scraper::{self, parse_number, Candle, NativeScraper, Quote};
+use crate::settings::{self, MarketDataProvider, SettingsState};
+use crate::symbols;
+
+const ALPHA_VANTAGE_BASE: &str = "https://www.alphavantage.co/query";
+
+impl MarketDataProvider {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MarketDataProvider::Native => "native",
+            MarketDataProvider::Yahoo => "yahoo",
+            MarketDataProvider::AlphaVantage => "alpha_vantage",
+        }
+    }
+}
+
+/// `requested` when a call names one, else the provider chosen in the scraper settings.
+pub fn resolve(app: &AppHandle, requested: Option<MarketDataProvider>) -> MarketDataProvider {
+    requested.unwrap_or_else(|| {
+        let state = app.state::<SettingsState>();
+        let provider = settings::blocking_read(&state).get().scraper.market_data_provider;
+        provider
+    })
+}
+
+/// Yahoo's ticker: `.NS`/`.BO` suffixes for Indian listings; other exchanges take the symbol as
+/// Yahoo lists it (AAPL, VOD.L, 7203.T).
+fn yahoo_ticker(app: &AppHandle, symbol: &str, exchange: &str) -> String {
+    match exchange.trim().to_uppercase().as_str() {
+        "NSE" | "BSE" => {
+            let native = symbols::exchange_identifier(app, symbol, exchange);
+            scraper::yahoo_ticker(&native, exchange).unwrap_or_else(|_| symbol.trim().to_uppercase())
+        }
+        _ => symbol.trim().to_uppercase(),
+    }
+}
+
+/// Alpha Vantage carries Indian equities under BSE tickers only (RELIANCE.BSE).
+fn alpha_vantage_symbol(symbol: &str, exchange: &str) -> String {
+    let symbol = symbol.trim().to_uppercase();
+    match exchange.trim().to_uppercase().as_str() {
+        "NSE" | "BSE" if !symbol.contains('.') => format!("{}.BSE", symbol),
+        _ => symbol,
+    }
+}
+
+async fn alpha_vantage_key(app: &AppHandle) -> Result<String, String> {
+    let state = app.state::<SettingsState>();
+    let key = state.read().await.get().financial_data_apis.alpha_vantage.trim().to_string();
+    if key.is_empty() {
+        return Err("Alpha Vantage needs an API key under Financial Data APIs in settings".to_string());
+    }
+    Ok(key)
+}
+
+/// Alpha Vantage answers errors and rate limits with HTTP 200 and a message field.
+async fn alpha_vantage(app: &AppHandle, params: &[(&str, &str)], timeout: Duration) -> Result<serde_json::Value, String> {
+    let key = alpha_vantage_key(app).await?;
+    let mut url = reqwest::Url::parse(ALPHA_VANTAGE_BASE).map_err(|e| e.to_string())?;
+    url.query_pairs_mut().extend_pairs(params).append_pair("apikey", &key);
+    let data = app.state::<NativeScraper>().get_json(url.as_str(), timeout).await?;
+    for field in ["Error Message", "Note", "Information"] {
+        if let Some(message) = data.get(field).and_then(|m| m.as_str()) {
+            return Err(format!("Alpha Vantage: {}", message));
+        }
+    }
+    Ok(data)
+}
+
+async fn yahoo_quote(app: &AppHandle, symbol: &str, exchange: &str, timeout: Duration) -> Result<Quote, String> {
+    let ticker = yahoo_ticker(app, symbol, exchange);
+    let chart = app.state::<NativeScraper>().yahoo_chart(&ticker, "1d", "1d", timeout).await?;
+    let meta = chart.get("meta").ok_or_else(|| format!("No quote for {} on Yahoo", ticker))?;
+    let field = |name: &str| meta.get(name).and_then(parse_number);
+    let price = field("regularMarketPrice");
+    let previous_close = field("chartPreviousClose").or_else(|| field("previousClose"));
+    let change = price.zip(previous_close).map(|(price, previous)| price - previous);
+    Ok(Quote {
+        symbol: symbol.trim().to_uppercase(),
+        company_name: meta.get("longName").or_else(|| meta.get("shortName")).and_then(|n| n.as_str()).map(str::to_string),
+        price,
+        change,
+        change_percent: change.zip(previous_close).filter(|(_, previous)| *previous != 0.0).map(|(change, previous)| change / previous * 100.0),
+        open: chart.pointer("/indicators/quote/0/open/0").and_then(parse_number),
+        high: field("regularMarketDayHigh"),
+        low: field("regularMarketDayLow"),
+        previous_close,
+        volume: field("regularMarketVolume"),
+        exchange: exchange.trim().to_uppercase(),
+    })
+}
+
+async fn alpha_vantage_quote(app: &AppHandle, symbol: &str, exchange: &str, timeout: Duration) -> Result<Quote, String> {
+    let av_symbol = alpha_vantage_symbol(symbol, exchange);
+    let data = alpha_vantage(app, &[("function", "GLOBAL_QUOTE"), ("symbol", av_symbol.as_str())], timeout).await?;
+    let quote = data.get("Global Quote")
+        .filter(|q| q.as_object().is_some_and(|q| !q.is_empty()))
+        .ok_or_else(|| format!("No quote for {} on Alpha Vantage", av_symbol))?;
+    let field = |name: &str| quote.get(name).and_then(parse_number);
+    Ok(Quote {
+        symbol: symbol.trim().to_uppercase(),
+        company_name: None,
+        price: field("05. price"),
+        change: field("09. change"),
+        // "1.2345%"
+        change_percent: quote.get("10. change percent").and_then(|p| p.as_str()).and_then(|p| p.trim_end_matches('%').trim().parse().ok()),
+        open: field("02. open"),
+        high: field("03. high"),
+        low: field("04. low"),
+        previous_close: field("08. previous close"),
+        volume: field("06. volume"),
+        exchange: exchange.trim().to_uppercase(),
+    })
+}
+
+async fn alpha_vantage_history(app: &AppHandle, symbol: &str, exchange: &str, range: &str, interval: &str, timeout: Duration) -> Result<Vec<Candle>, String> {
+    let (function, series) = match interval {
+        "1wk" => ("TIME_SERIES_WEEKLY", "Weekly Time Series"),
+        "1mo" => ("TIME_SERIES_MONTHLY", "Monthly Time Series"),
+        _ => ("TIME_SERIES_DAILY", "Time Series (Daily)"),
+    };
+    // "compact" is the last 100 points, enough for up to three months of days
+    let output_size = if matches!(range, "1mo" | "3mo") { "compact" } else { "full" };
+    let av_symbol = alpha_vantage_symbol(symbol, exchange);
+    let data = alpha_vantage(app, &[("function", function), ("symbol", av_symbol.as_str()), ("outputsize", output_size)], timeout).await?;
+    let points = data.get(series).and_then(|s| s.as_object())
+        .ok_or_else(|| format!("No price history for {} on Alpha Vantage", av_symbol))?;
+
+    let mut candles: Vec<Candle> = points.iter()
+        .filter_map(|(date, point)| {
+            let field = |name: &str| point.get(name).and_then(parse_number);
+            let timestamp = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?.and_hms_opt(0, 0, 0)?.and_utc().timestamp();
+            Some(Candle {
+                date: date.clone(),
+                timestamp,
+                open: field("1. open")?,
+                high: field("2. high")?,
+                low: field("3. low")?,
+                close: field("4. close")?,
+                volume: field("5. volume"),
+            })
+        })
+        .collect();
+    candles.sort_by(|a, b| a.date.cmp(&b.date));
+    Ok(candles)
+}
+
+/// A quote from `provider`. The native provider reads NSE/BSE directly and leaves fallback to
+/// its callers.
+pub async fn fetch_quote(app: &AppHandle, provider: MarketDataProvider, symbol: &str, exchange: &str, timeout: Duration) -> Result<Quote, String> {
+    match provider {
+        MarketDataProvider::Native => {
+            let native_symbol = symbols::exchange_identifier(app, symbol, exchange);
+            app.state::<NativeScraper>().fetch_quote(&native_symbol, exchange, timeout).await
+        }
+        MarketDataProvider::Yahoo => yahoo_quote(app, symbol, exchange, timeout).await,
+        MarketDataProvider::AlphaVantage => alpha_vantage_quote(app, symbol, exchange, timeout).await,
+    }
+}
+
+/// Candles oldest first; `range` and `interval` in Yahoo's vocabulary ("1y", "1d", "1wk", "1mo").
+/// Native history already comes from Yahoo, so the two differ only in which tickers they accept.
+pub async fn fetch_history(
+    app: &AppHandle,
+    provider: MarketDataProvider,
+    symbol: &str,
+    exchange: &str,
+    range: &str,
+    interval: &str,
+) -> Result<Vec<Candle>, String> {
+    let timeout = scraper::request_timeout(app);
+    match provider {
+        MarketDataProvider::Native => app.state::<NativeScraper>().fetch_history(symbol, exchange, range, interval, timeout).await,
+        MarketDataProvider::Yahoo => {
+            let ticker = yahoo_ticker(app, symbol, exchange);
+            app.state::<NativeScraper>().yahoo_candles(&ticker, range, interval, timeout).await
+        }
+        MarketDataProvider::AlphaVantage => alpha_vantage_history(app, symbol, exchange, range, interval, timeout).await,
+    }
+}
@@ -0,0 +1,215 @@
+// MCP - Model Context Protocol server so external agents can use the app as a financial
+// data backend. Served over stdio (`--mcp`) and over HTTP at the API server's /mcp endpoint.
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use tauri::AppHandle;
+use tracing::{debug, info, warn};
+
+use crate::dcf::{self, DcfInputs};
+use crate::ratios;
+use crate::report;
+use crate::scraper;
+use crate::statements;
+
+const SERVER_NAME: &str = "financial-calculator";
+// Newest first; we answer with the client's version when we know it
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+const DEFAULT_ITEM_LIMIT: usize = 200;
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+fn tool_definitions() -> Value {
+    let document_id = json!({ "type": "integer", "description": "Document id from list_documents; defaults to the most recent" });
+    json!([
+        {
+            "name": "list_documents",
+            "description": "List imported financial documents (annual reports, spreadsheets), newest first.",
+            "inputSchema": { "type": "object", "properties": {} }
+        },
+        {
+            "name": "query_items",
+            "description": "Extracted line items of a document with current and previous year values.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "documentId": document_id,
+                    "statement": { "type": "string", "description": "balance_sheet, profit_loss or cash_flow" },
+                    "search": { "type": "string", "description": "Case-insensitive substring of the item label" },
+                    "limit": { "type": "integer", "description": "Maximum items to return (default 200)" }
+                }
+            }
+        },
+        {
+            "name": "compute_ratios",
+            "description": "Key liquidity, leverage, profitability, efficiency and cash flow ratios of a document.",
+            "inputSchema": { "type": "object", "properties": { "documentId": document_id } }
+        },
+        {
+            "name": "get_quotes",
+            "description": "Latest market quotes for listed Indian companies.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "symbols": { "type": "array", "items": { "type": "string" }, "description": "Ticker symbols, e.g. RELIANCE" },
+                    "exchange": { "type": "string", "enum": ["NSE", "BSE"], "description": "Defaults to NSE" }
+                },
+                "required": ["symbols"]
+            }
+        },
+        {
+            "name": "run_dcf",
+            "description": "Discounted cash flow valuation. Uses baseCashFlow, or the free cash flow of documentId.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "documentId": document_id,
+                    "baseCashFlow": { "type": "number" },
+                    "growthRate": { "type": "number", "description": "Yearly growth during the projection, percent" },
+                    "discountRate": { "type": "number", "description": "WACC, percent" },
+                    "terminalGrowthRate": { "type": "number", "description": "Perpetual growth, percent" },
+                    "years": { "type": "integer", "description": "Projection years (default 5)" },
+                    "netDebt": { "type": "number" },
+                    "sharesOutstanding": { "type": "number" }
+                },
+                "required": ["growthRate", "discountRate", "terminalGrowthRate"]
+            }
+        }
+    ])
+}
+
+fn document_id(app: &AppHandle, args: &Value) -> Result<i64, String> {
+    match args["documentId"].as_i64() {
+        Some(id) => Ok(id),
+        None => statements::latest_document_id(app),
+    }
+}
+
+async fn call_tool(app: &AppHandle, name: &str, args: &Value) -> Result<Value, String> {
+    match name {
+        "list_documents" => serde_json::to_value(statements::list_documents(app)?).map_err(|e| e.to_string()),
+        "query_items" => {
+            let document = statements::load_document(app, document_id(app, args)?)?;
+            let statement = args["statement"].as_str().map(str::to_lowercase);
+            let search = args["search"].as_str().map(str::to_lowercase);
+            let limit = args["limit"].as_u64().map(|l| l as usize).unwrap_or(DEFAULT_ITEM_LIMIT);
+            let items: Vec<&Value> = document.items.iter()
+                .filter(|item| statement.as_ref().is_none_or(|s| report::statement_of(item) == *s))
+                .filter(|item| search.as_ref().is_none_or(|s| item["label"].as_str().unwrap_or("").to_lowercase().contains(s)))
+                .take(limit)
+                .collect();
+            Ok(json!({ "documentId": document.id, "filename": document.filename, "items": items }))
+        }
+        "compute_ratios" => {
+            let document = statements::load_document(app, document_id(app, args)?)?;
+            Ok(json!({ "documentId": document.id, "filename": document.filename, "ratios": ratios::for_document(&document) }))
+        }
+        "get_quotes" => {
+            let symbols: Vec<String> = args["symbols"].as_array()
+                .map(|symbols| symbols.iter().filter_map(|s| s.as_str()).map(|s| s.trim().to_uppercase()).filter(|s| !s.is_empty()).collect())
+                .unwrap_or_default();
+            if symbols.is_empty() || symbols.len() > scraper::MAX_QUOTE_LIST {
+                return Err(format!("Pass between 1 and {} symbols", scraper::MAX_QUOTE_LIST));
+            }
+            let exchange = args["exchange"].as_str().unwrap_or("NSE").to_uppercase();
+            serde_json::to_value(scraper::quote_list(app, symbols, &exchange).await).map_err(|e| e.to_string())
+        }
+        "run_dcf" => {
+            let inputs: DcfInputs = serde_json::from_value(args.clone()).map_err(|e| format!("Invalid DCF inputs: {}", e))?;
+            serde_json::to_value(dcf::run(app, &inputs)?).map_err(|e| e.to_string())
+        }
+        other => Err(format!("Unknown tool: {}", other)),
+    }
+}
+
+fn success(id: &Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+fn failure(id: &Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// Handle one JSON-RPC message; notifications get no reply.
+pub async fn handle(app: &AppHandle, message: &Value) -> Option<Value> {
+    let id = message.get("id").cloned();
+    let Some(method) = message["method"].as_str() else {
+        return Some(failure(&id.unwrap_or(Value::Null), INVALID_REQUEST, "Missing method"));
+    };
+    let Some(id) = id else {
+        debug!(method, "MCP notification");
+        return None;
+    };
+    let params = &message["params"];
+
+    let reply = match method {
+        "initialize" => {
+            let requested = params["protocolVersion"].as_str().unwrap_or_default();
+            let version = PROTOCOL_VERSIONS.iter().find(|v| **v == requested).unwrap_or(&PROTOCOL_VERSIONS[0]);
+            info!(client = %params["clientInfo"]["name"].as_str().unwrap_or("unknown"), version, "MCP client connected");
+            success(&id, json!({
+                "protocolVersion": version,
+                "capabilities": { "tools": { "listChanged": false } },
+                "serverInfo": { "name": SERVER_NAME, "version": env!("CARGO_PKG_VERSION") },
+                "instructions": "Financial statements imported into Financial Calculator. Call list_documents first to find a documentId."
+            }))
+        }
+        "ping" => success(&id, json!({})),
+        "tools/list" => success(&id, json!({ "tools": tool_definitions() })),
+        "tools/call" => match params["name"].as_str() {
+            Some(name) => {
+                let empty = json!({});
+                let args = if params["arguments"].is_object() { &params["arguments"] } else { &empty };
+                // Tool failures are results the model can read, not protocol errors
+                let result = match call_tool(app, name, args).await {
+                    Ok(value) => {
+                        let mut result = json!({ "content": [{ "type": "text", "text": value.to_string() }], "isError": false });
+                        // Structured content has to be an object; lists only go out as text
+                        if value.is_object() {
+                            result["structuredContent"] = value;
+                        }
+                        result
+                    }
+                    Err(e) => {
+                        warn!(tool = name, "MCP tool failed: {}", e);
+                        json!({ "content": [{ "type": "text", "text": e }], "isError": true })
+                    }
+                };
+                success(&id, result)
+            }
+            None => failure(&id, INVALID_PARAMS, "Missing tool name"),
+        },
+        other => failure(&id, METHOD_NOT_FOUND, &format!("Method not found: {}", other)),
+    };
+    Some(reply)
+}
+
+/// Serve newline-delimited JSON-RPC on stdin/stdout until stdin closes, then exit.
+/// Logs go to stderr and the log file, so stdout only ever carries protocol messages.
+pub fn serve_stdio(app: AppHandle) {
+    std::thread::spawn(move || {
+        info!("MCP server listening on stdio");
+        let stdin = std::io::stdin();
+        let mut stdout = std::io::stdout();
+        for line in stdin.lock().lines() {
+            let Ok(line) = line else { break };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let reply = match serde_json::from_str::<Value>(&line) {
+                Ok(message) => tauri::async_runtime::block_on(handle(&app, &message)),
+                Err(e) => Some(failure(&Value::Null, PARSE_ERROR, &e.to_string())),
+            };
+            if let Some(reply) = reply {
+                if writeln!(stdout, "{}", reply).and_then(|_| stdout.flush()).is_err() {
+                    break;
+                }
+            }
+        }
+        info!("MCP client disconnected");
+        app.exit(0);
+    });
+}
@@ -0,0 +1,121 @@
+// Metric cache - computed metrics kept in memory per (document set, metric set, data version),
+// least recently used dropped first, so switching views doesn't recompute them
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+use tauri::AppHandle;
+use tracing::info;
+
+use crate::profiles;
+
+// Enough for every view of a handful of open documents
+const MAX_ENTRIES: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct MetricKey {
+    /// blake3 of the items the metrics were computed from
+    documents: String,
+    metrics: String,
+    version: String,
+}
+
+#[derive(Default)]
+struct Entries {
+    version: String,
+    tick: u64,
+    /// key -> (last use, metrics)
+    map: HashMap<MetricKey, (u64, serde_json::Value)>,
+}
+
+#[derive(Default)]
+pub struct MetricCache {
+    entries: Mutex<Entries>,
+}
+
+fn file_stamp(path: &Path) -> String {
+    std::fs::metadata(path)
+        .map(|meta| {
+            let modified = meta.modified().ok()
+                .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_nanos());
+            format!("{}:{}", meta.len(), modified)
+        })
+        .unwrap_or_default()
+}
+
+/// Changes whenever the active pipeline DB is written, by Rust, Python or a restore, and when
+/// another profile becomes active. The WAL file counts too, as writes land there first.
+fn data_version(app: &AppHandle) -> String {
+    let db_path = profiles::active_db_path(app);
+    let mut wal_path = db_path.clone().into_os_string();
+    wal_path.push("-wal");
+    format!("{}|{}|{}", db_path.display(), file_stamp(&db_path), file_stamp(Path::new(&wal_path)))
+}
+
+impl MetricCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn key(app: &AppHandle, documents: &str, metrics: &str) -> MetricKey {
+        MetricKey {
+            documents: blake3::hash(documents.as_bytes()).to_hex().to_string(),
+            metrics: metrics.to_string(),
+            version: data_version(app),
+        }
+    }
+
+    /// Entries from an older data version can never be hit again, so they go as soon as the
+    /// version moves on.
+    fn sync_version(entries: &mut Entries, version: &str) {
+        if entries.version != version {
+            entries.map.clear();
+            entries.version = version.to_string();
+        }
+    }
+
+    /// Metrics `metrics` computed from `documents` (serialized items), if still current.
+    pub fn get(&self, app: &AppHandle, documents: &str, metrics: &str) -> Option<serde_json::Value> {
+        let key = Self::key(app, documents, metrics);
+        let mut entries = self.entries.lock().ok()?;
+        Self::sync_version(&mut entries, &key.version);
+        entries.tick += 1;
+        let tick = entries.tick;
+        let (last_used, value) = entries.map.get_mut(&key)?;
+        *last_used = tick;
+        Some(value.clone())
+    }
+
+    pub fn put(&self, app: &AppHandle, documents: &str, metrics: &str, value: &serde_json::Value) {
+        let key = Self::key(app, documents, metrics);
+        let Ok(mut entries) = self.entries.lock() else { return };
+        Self::sync_version(&mut entries, &key.version);
+        if entries.map.len() >= MAX_ENTRIES && !entries.map.contains_key(&key) {
+            let oldest = entries.map.iter().min_by_key(|(_, (last_used, _))| *last_used).map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.map.remove(&oldest);
+            }
+        }
+        entries.tick += 1;
+        let tick = entries.tick;
+        entries.map.insert(key, (tick, value.clone()));
+    }
+
+    /// Drop everything; returns how many entries there were.
+    pub fn clear(&self) -> usize {
+        let Ok(mut entries) = self.entries.lock() else { return 0 };
+        let count = entries.map.len();
+        entries.map.clear();
+        count
+    }
+}
+
+// Tauri Commands
+/// Forget every cached metric, e.g. to check that a fresh computation gives the same result.
+#[tauri::command]
+pub fn clear_metric_cache(cache: tauri::State<'_, MetricCache>) -> Result<usize, String> {
+    let count = cache.clear();
+    info!(count, "Cleared metric cache");
+    Ok(count)
+}
@@ -0,0 +1,432 @@
+// Metric Formulas - user-defined metrics written as expressions over line items, e.g.
+// `(ebitda - capex) / interest`, stored in the app DB and computed alongside the built-in ratios.
+//
+// Grammar: numbers, + - * / ^, parentheses, unary minus, abs(x), min(a, b, ...), max(a, b, ...).
+// Names are the mapped items in `ratios::MAPPED_ITEMS` ("revenue", "capex", ...) or any item
+// label with underscores for spaces (`trade_receivables`); `[Trade Receivables]` quotes a label.
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+use crate::db;
+use crate::derived;
+use crate::ratios::{self, Ratio};
+use crate::statements::{self, StoredDocument};
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS metric_formulas (
+    name TEXT PRIMARY KEY,
+    expression TEXT NOT NULL,
+    unit TEXT NOT NULL DEFAULT 'x',
+    description TEXT,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+";
+const MAX_EXPRESSION_LEN: usize = 1_000;
+// Deep enough for any sane formula, shallow enough not to overflow the stack
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricFormula {
+    /// Also the metric's key; letters, digits and '_'
+    pub name: String,
+    pub expression: String,
+    /// "x", "%" or a currency, as for ratios
+    pub unit: String,
+    pub description: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Function {
+    Abs,
+    Min,
+    Max,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(f64),
+    /// An item name or quoted label
+    Item(String),
+    Neg(Box<Expr>),
+    Binary(Op, Box<Expr>, Box<Expr>),
+    Call(Function, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Label(String),
+    Op(char),
+    Open,
+    Close,
+    Comma,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn tokenize(expression: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = expression.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '0'..='9' | '.' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                let text: String = chars[start..i].iter().collect();
+                let number = text.parse().map_err(|_| format!("Bad number \"{}\" at {}", text, start + 1))?;
+                tokens.push(Token::Number(number));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                tokens.push(Token::Name(chars[start..i].iter().collect()));
+            }
+            '[' => {
+                let end = chars[i..].iter().position(|c| *c == ']').map(|p| i + p)
+                    .ok_or_else(|| format!("Unclosed [ at {}", i + 1))?;
+                tokens.push(Token::Label(chars[i + 1..end].iter().collect()));
+                i = end + 1;
+            }
+            '+' | '-' | '*' | '/' | '^' => {
+                tokens.push(Token::Op(c));
+                i += 1;
+            }
+            '(' => {
+                tokens.push(Token::Open);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::Close);
+                i += 1;
+            }
+            ',' => {
+                tokens.push(Token::Comma);
+                i += 1;
+            }
+            other => return Err(format!("Unexpected \"{}\" at {}", other, i + 1)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Recursive descent, lowest precedence first: sum, product, unary, power (right associative), primary.
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, token: Token, what: &str) -> Result<(), String> {
+        match self.next() {
+            Some(found) if found == token => Ok(()),
+            _ => Err(format!("Expected {}", what)),
+        }
+    }
+
+    fn sum(&mut self) -> Result<Expr, String> {
+        let mut left = self.product()?;
+        while let Some(Token::Op(c @ ('+' | '-'))) = self.peek().cloned() {
+            self.position += 1;
+            let op = if c == '+' { Op::Add } else { Op::Sub };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.product()?));
+        }
+        Ok(left)
+    }
+
+    fn product(&mut self) -> Result<Expr, String> {
+        let mut left = self.unary()?;
+        while let Some(Token::Op(c @ ('*' | '/'))) = self.peek().cloned() {
+            self.position += 1;
+            let op = if c == '*' { Op::Mul } else { Op::Div };
+            left = Expr::Binary(op, Box::new(left), Box::new(self.unary()?));
+        }
+        Ok(left)
+    }
+
+    /// Signs bind looser than `^`, so `-2^2` is -(2^2).
+    fn unary(&mut self) -> Result<Expr, String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err("Formula is nested too deeply".to_string());
+        }
+        let expr = match self.peek() {
+            Some(Token::Op('-')) => {
+                self.position += 1;
+                Expr::Neg(Box::new(self.unary()?))
+            }
+            Some(Token::Op('+')) => {
+                self.position += 1;
+                self.unary()?
+            }
+            _ => self.power()?,
+        };
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    /// Right-associative; the exponent may carry its own sign, as in `2^-1`.
+    fn power(&mut self) -> Result<Expr, String> {
+        let base = self.primary()?;
+        if self.peek() == Some(&Token::Op('^')) {
+            self.position += 1;
+            return Ok(Expr::Binary(Op::Pow, Box::new(base), Box::new(self.unary()?)));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let expr = match self.next() {
+            Some(Token::Number(n)) => Expr::Number(n),
+            Some(Token::Label(label)) => Expr::Item(label),
+            Some(Token::Name(name)) if self.peek() == Some(&Token::Open) => {
+                let function = match name.to_lowercase().as_str() {
+                    "abs" => Function::Abs,
+                    "min" => Function::Min,
+                    "max" => Function::Max,
+                    _ => return Err(format!("Unknown function {}", name)),
+                };
+                self.position += 1;
+                let mut args = vec![self.sum()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.position += 1;
+                    args.push(self.sum()?);
+                }
+                self.expect(Token::Close, "\")\" after arguments")?;
+                if function == Function::Abs && args.len() != 1 {
+                    return Err("abs takes one argument".to_string());
+                }
+                Expr::Call(function, args)
+            }
+            Some(Token::Name(name)) => Expr::Item(name),
+            Some(Token::Open) => {
+                let inner = self.sum()?;
+                self.expect(Token::Close, "\")\"")?;
+                inner
+            }
+            Some(token) => return Err(format!("Unexpected {:?}", token)),
+            None => return Err("Formula ends too early".to_string()),
+        };
+        Ok(expr)
+    }
+}
+
+fn parse(expression: &str) -> Result<Expr, String> {
+    if expression.trim().is_empty() {
+        return Err("Formula is empty".to_string());
+    }
+    if expression.len() > MAX_EXPRESSION_LEN {
+        return Err(format!("Formula is longer than {} characters", MAX_EXPRESSION_LEN));
+    }
+    let mut parser = Parser { tokens: tokenize(expression)?, position: 0, depth: 0 };
+    let expr = parser.sum()?;
+    if parser.position < parser.tokens.len() {
+        return Err(format!("Unexpected {:?} after the formula", parser.tokens[parser.position]));
+    }
+    Ok(expr)
+}
+
+/// (current, previous) for a name: a mapped item, else an item with that label.
+fn item_values(items: &[serde_json::Value], name: &str) -> Option<(f64, f64)> {
+    let name = name.trim().to_lowercase();
+    if ratios::MAPPED_ITEMS.contains(&name.as_str()) {
+        return ratios::mapped(items, &name);
+    }
+    let label = name.replace('_', " ");
+    ratios::find(items, &[label.as_str()], None)
+}
+
+/// Value at the current (`previous` false) or previous period; None when an item is missing
+/// or the result isn't a finite number.
+fn evaluate(expr: &Expr, items: &[serde_json::Value], previous: bool) -> Option<f64> {
+    let value = match expr {
+        Expr::Number(n) => *n,
+        Expr::Item(name) => item_values(items, name).map(|(c, p)| if previous { p } else { c })?,
+        Expr::Neg(inner) => -evaluate(inner, items, previous)?,
+        Expr::Binary(op, left, right) => {
+            let (a, b) = (evaluate(left, items, previous)?, evaluate(right, items, previous)?);
+            match op {
+                Op::Add => a + b,
+                Op::Sub => a - b,
+                Op::Mul => a * b,
+                Op::Div if b == 0.0 => return None,
+                Op::Div => a / b,
+                Op::Pow => a.powf(b),
+            }
+        }
+        Expr::Call(function, args) => {
+            let values = args.iter().map(|arg| evaluate(arg, items, previous)).collect::<Option<Vec<f64>>>()?;
+            match function {
+                Function::Abs => values[0].abs(),
+                Function::Min => values.into_iter().fold(f64::INFINITY, f64::min),
+                Function::Max => values.into_iter().fold(f64::NEG_INFINITY, f64::max),
+            }
+        }
+    };
+    value.is_finite().then_some(value)
+}
+
+fn to_ratio(formula: &MetricFormula, expr: &Expr, items: &[serde_json::Value]) -> Ratio {
+    Ratio {
+        key: formula.name.clone(),
+        name: formula.description.clone().filter(|d| !d.trim().is_empty()).unwrap_or_else(|| formula.name.replace('_', " ")),
+        category: "custom".to_string(),
+        unit: formula.unit.clone(),
+        current: evaluate(expr, items, false),
+        previous: evaluate(expr, items, true),
+    }
+}
+
+fn load_formulas(app: &AppHandle) -> Result<Vec<MetricFormula>, String> {
+    let conn = db::open_app_db(app)?;
+    let mut stmt = conn.prepare(
+        "SELECT name, expression, unit, description, created_at, updated_at FROM metric_formulas ORDER BY name"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], |row| {
+        Ok(MetricFormula {
+            name: row.get(0)?,
+            expression: row.get(1)?,
+            unit: row.get(2)?,
+            description: row.get(3)?,
+            created_at: row.get(4)?,
+            updated_at: row.get(5)?,
+        })
+    }).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Compute every saved formula over a freshly loaded document and keep the results in
+/// `metadata.customMetrics`, where `ratios::for_document` picks them up.
+pub fn apply(app: &AppHandle, document: &mut StoredDocument) -> Result<(), String> {
+    let formulas = load_formulas(app)?;
+    if formulas.is_empty() {
+        return Ok(());
+    }
+    let metrics: Vec<Ratio> = formulas.iter()
+        .filter_map(|formula| match parse(&formula.expression) {
+            Ok(expr) => Some(to_ratio(formula, &expr, &document.items)),
+            // Saved formulas were validated; only a hand-edited database gets here
+            Err(e) => {
+                warn!(formula = %formula.name, "Skipping formula: {}", e);
+                None
+            }
+        })
+        .filter(|ratio| ratio.current.is_some() || ratio.previous.is_some())
+        .collect();
+    if !document.metadata.is_object() {
+        document.metadata = serde_json::json!({});
+    }
+    document.metadata["customMetrics"] = serde_json::json!(metrics);
+    Ok(())
+}
+
+fn valid_name(name: &str) -> Result<String, String> {
+    let name = name.trim().to_lowercase();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(format!("Metric name \"{}\" may only use letters, digits and '_'", name));
+    }
+    if ratios::KEYS.contains(&name.as_str()) {
+        return Err(format!("{} is a built-in ratio", name));
+    }
+    Ok(name)
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn list_metric_formulas(app: AppHandle) -> Result<Vec<MetricFormula>, String> {
+    load_formulas(&app)
+}
+
+/// Create or replace a formula; it must parse, so mistakes surface here rather than as a
+/// missing metric later.
+#[tauri::command]
+pub fn save_metric_formula(
+    app: AppHandle,
+    name: String,
+    expression: String,
+    unit: Option<String>,
+    description: Option<String>,
+) -> Result<MetricFormula, String> {
+    let name = valid_name(&name)?;
+    let expression = expression.trim().to_string();
+    parse(&expression).map_err(|e| format!("Invalid formula: {}", e))?;
+    let unit = unit.map(|u| u.trim().to_string()).filter(|u| !u.is_empty()).unwrap_or_else(|| "x".to_string());
+
+    let now = now_secs();
+    let conn = db::open_app_db(&app)?;
+    conn.execute(
+        "INSERT INTO metric_formulas (name, expression, unit, description, created_at, updated_at) VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+         ON CONFLICT(name) DO UPDATE SET expression = excluded.expression, unit = excluded.unit,
+             description = excluded.description, updated_at = excluded.updated_at",
+        params![name, expression, unit, description, now],
+    ).map_err(|e| e.to_string())?;
+    let created_at = conn.query_row("SELECT created_at FROM metric_formulas WHERE name = ?1", params![name], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    info!(metric = %name, "Saved metric formula");
+    derived::mark_stale(&app, None);
+    Ok(MetricFormula { name, expression, unit, description, created_at, updated_at: now })
+}
+
+#[tauri::command]
+pub fn delete_metric_formula(app: AppHandle, name: String) -> Result<(), String> {
+    let conn = db::open_app_db(&app)?;
+    conn.execute("DELETE FROM metric_formulas WHERE name = ?1", params![name.trim().to_lowercase()])
+        .map_err(|e| e.to_string())?;
+    derived::mark_stale(&app, None);
+    Ok(())
+}
+
+/// Try an expression against a document without saving it.
+#[tauri::command]
+pub fn evaluate_metric_formula(app: AppHandle, expression: String, document_id: i64) -> Result<Ratio, String> {
+    let expr = parse(&expression).map_err(|e| format!("Invalid formula: {}", e))?;
+    let document = statements::load_document(&app, document_id)?;
+    let formula = MetricFormula {
+        name: "preview".to_string(),
+        expression,
+        unit: "x".to_string(),
+        description: None,
+        created_at: 0,
+        updated_at: 0,
+    };
+    Ok(to_ratio(&formula, &expr, &document.items))
+}
@@ -0,0 +1,258 @@
+// Monte Carlo - DCF valuations over many draws of the key drivers (growth, free cash flow
+// margin, discount rate, terminal growth), summarized as percentile bands
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tracing::info;
+
+use crate::dcf::{self, DcfInputs};
+use crate::ratios;
+use crate::statements;
+
+const DEFAULT_ITERATIONS: usize = 10_000;
+const MAX_ITERATIONS: usize = 100_000;
+// Draws per RNG stream; each chunk is seeded from the run's seed, so a seed reproduces a run
+// whatever the thread count
+const CHUNK: usize = 1_000;
+
+/// How a driver varies between draws, in percent.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Distribution {
+    Fixed { value: f64 },
+    #[serde(rename_all = "camelCase")]
+    Normal { mean: f64, std_dev: f64 },
+    Uniform { min: f64, max: f64 },
+    Triangular { min: f64, mode: f64, max: f64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationInputs {
+    /// Take the base revenue from this document
+    pub document_id: Option<i64>,
+    /// Explicit base revenue; wins over `document_id`
+    pub base_revenue: Option<f64>,
+    /// Yearly revenue growth during the projection
+    pub growth_rate: Distribution,
+    /// Free cash flow as a share of revenue
+    pub fcf_margin: Distribution,
+    pub discount_rate: Distribution,
+    pub terminal_growth_rate: Distribution,
+    pub years: Option<u32>,
+    /// Debt less cash; subtracted from enterprise value
+    pub net_debt: Option<f64>,
+    pub shares_outstanding: Option<f64>,
+    pub iterations: Option<usize>,
+    /// Repeats an earlier run when given; otherwise a random one is returned with the result
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Band {
+    pub p5: f64,
+    pub p10: f64,
+    pub p25: f64,
+    pub p50: f64,
+    pub p75: f64,
+    pub p90: f64,
+    pub p95: f64,
+    pub mean: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YearBand {
+    pub year: u32,
+    pub cash_flow: Band,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationResult {
+    pub base_revenue: f64,
+    /// "input" or the document's filename
+    pub base_source: String,
+    pub iterations: usize,
+    /// Draws left out because the discount rate wasn't above terminal growth
+    pub discarded: usize,
+    pub seed: u64,
+    pub free_cash_flow: Vec<YearBand>,
+    pub enterprise_value: Band,
+    pub equity_value: Band,
+    pub value_per_share: Option<Band>,
+}
+
+/// One draw's valuation, without the per-year present values the bands don't need.
+struct Outcome {
+    cash_flows: Vec<f64>,
+    enterprise_value: f64,
+    equity_value: f64,
+    value_per_share: Option<f64>,
+}
+
+impl Distribution {
+    fn validate(&self, name: &str) -> Result<(), String> {
+        let (values, ordered) = match *self {
+            Distribution::Fixed { value } => (vec![value], true),
+            Distribution::Normal { mean, std_dev } => (vec![mean, std_dev], std_dev >= 0.0),
+            Distribution::Uniform { min, max } => (vec![min, max], min <= max),
+            Distribution::Triangular { min, mode, max } => (vec![min, mode, max], min <= mode && mode <= max && min < max),
+        };
+        if values.iter().any(|v| !v.is_finite()) {
+            return Err(format!("{} has a value that is not a number", name));
+        }
+        if !ordered {
+            return Err(format!("{} is not a valid distribution: {:?}", name, self));
+        }
+        Ok(())
+    }
+
+    fn sample(&self, rng: &mut StdRng) -> f64 {
+        match *self {
+            Distribution::Fixed { value } => value,
+            Distribution::Normal { mean, std_dev } => {
+                // Box-Muller; 1 - u keeps the logarithm's argument above zero
+                let (u1, u2): (f64, f64) = (1.0 - rng.random::<f64>(), rng.random());
+                mean + std_dev * (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+            }
+            Distribution::Uniform { min, max } => min + (max - min) * rng.random::<f64>(),
+            Distribution::Triangular { min, mode, max } => {
+                // Inverse of the triangular CDF
+                let u: f64 = rng.random();
+                let split = (mode - min) / (max - min);
+                if u < split {
+                    min + (u * (max - min) * (mode - min)).sqrt()
+                } else {
+                    max - ((1.0 - u) * (max - min) * (max - mode)).sqrt()
+                }
+            }
+        }
+    }
+}
+
+fn draw(rng: &mut StdRng, inputs: &SimulationInputs, base_revenue: f64) -> Option<Outcome> {
+    let dcf_inputs = DcfInputs {
+        document_id: None,
+        base_cash_flow: None,
+        growth_rate: inputs.growth_rate.sample(rng),
+        discount_rate: inputs.discount_rate.sample(rng),
+        terminal_growth_rate: inputs.terminal_growth_rate.sample(rng),
+        years: inputs.years,
+        net_debt: inputs.net_debt,
+        shares_outstanding: inputs.shares_outstanding,
+        inflation: None,
+    };
+    // Free cash flow is a share of revenue, so it grows with revenue from this year's margin
+    let base_cash_flow = base_revenue * inputs.fcf_margin.sample(rng) / 100.0;
+    let result = dcf::valuation(base_cash_flow, String::new(), &dcf_inputs).ok()?;
+    Some(Outcome {
+        cash_flows: result.projections.iter().map(|year| year.cash_flow).collect(),
+        enterprise_value: result.enterprise_value,
+        equity_value: result.equity_value,
+        value_per_share: result.value_per_share,
+    })
+}
+
+/// Linear interpolation between the closest ranks of sorted `values`.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p / 100.0 * (sorted.len() - 1) as f64;
+    let (low, high) = (rank.floor() as usize, rank.ceil() as usize);
+    sorted[low] + (sorted[high] - sorted[low]) * (rank - low as f64)
+}
+
+fn band(mut values: Vec<f64>) -> Band {
+    values.sort_by(f64::total_cmp);
+    Band {
+        p5: percentile(&values, 5.0),
+        p10: percentile(&values, 10.0),
+        p25: percentile(&values, 25.0),
+        p50: percentile(&values, 50.0),
+        p75: percentile(&values, 75.0),
+        p90: percentile(&values, 90.0),
+        p95: percentile(&values, 95.0),
+        mean: values.iter().sum::<f64>() / values.len() as f64,
+    }
+}
+
+/// Run the simulation on rayon's pool; call off the async runtime.
+pub fn simulate(inputs: &SimulationInputs, base_revenue: f64, base_source: String) -> Result<SimulationResult, String> {
+    let iterations = inputs.iterations.unwrap_or(DEFAULT_ITERATIONS);
+    if iterations == 0 || iterations > MAX_ITERATIONS {
+        return Err(format!("Iterations must be between 1 and {}", MAX_ITERATIONS));
+    }
+    for (name, distribution) in [
+        ("Growth rate", &inputs.growth_rate),
+        ("Free cash flow margin", &inputs.fcf_margin),
+        ("Discount rate", &inputs.discount_rate),
+        ("Terminal growth rate", &inputs.terminal_growth_rate),
+    ] {
+        distribution.validate(name)?;
+    }
+    // Fails the same way for every draw, so report it rather than discard everything
+    dcf::value(0.0, &DcfInputs {
+        document_id: None,
+        base_cash_flow: None,
+        growth_rate: 0.0,
+        discount_rate: 1.0,
+        terminal_growth_rate: 0.0,
+        years: inputs.years,
+        net_debt: None,
+        shares_outstanding: None,
+        inflation: None,
+    })?;
+
+    let seed = inputs.seed.unwrap_or_else(rand::random);
+    let outcomes: Vec<Outcome> = (0..iterations.div_ceil(CHUNK))
+        .into_par_iter()
+        .flat_map_iter(|chunk| {
+            let mut rng = StdRng::seed_from_u64(seed.wrapping_add(chunk as u64));
+            let count = CHUNK.min(iterations - chunk * CHUNK);
+            (0..count).filter_map(move |_| draw(&mut rng, inputs, base_revenue))
+        })
+        .collect();
+    if outcomes.is_empty() {
+        return Err("Every draw had a discount rate at or below terminal growth; narrow the distributions".to_string());
+    }
+
+    let years = outcomes[0].cash_flows.len();
+    let free_cash_flow = (0..years)
+        .map(|i| YearBand { year: i as u32 + 1, cash_flow: band(outcomes.iter().map(|o| o.cash_flows[i]).collect()) })
+        .collect();
+    let per_share: Vec<f64> = outcomes.iter().filter_map(|o| o.value_per_share).collect();
+    Ok(SimulationResult {
+        base_revenue,
+        base_source,
+        iterations,
+        discarded: iterations - outcomes.len(),
+        seed,
+        free_cash_flow,
+        enterprise_value: band(outcomes.iter().map(|o| o.enterprise_value).collect()),
+        equity_value: band(outcomes.iter().map(|o| o.equity_value).collect()),
+        value_per_share: (!per_share.is_empty()).then(|| band(per_share)),
+    })
+}
+
+// Tauri Commands
+#[tauri::command]
+pub async fn run_monte_carlo(app: AppHandle, inputs: SimulationInputs) -> Result<SimulationResult, String> {
+    let (base_revenue, base_source) = match (inputs.base_revenue, inputs.document_id) {
+        (Some(base), _) => (base, "input".to_string()),
+        (None, Some(document_id)) => {
+            let document = statements::load_document(&app, document_id)?;
+            let (current, _) = ratios::mapped(&document.items, "revenue")
+                .ok_or_else(|| format!("No revenue found in {}", document.filename))?;
+            (current, document.filename)
+        }
+        (None, None) => return Err("Pass a base revenue or a document to take it from".to_string()),
+    };
+
+    let result = tauri::async_runtime::spawn_blocking(move || simulate(&inputs, base_revenue, base_source))
+        .await
+        .map_err(|e| e.to_string())??;
+    info!(iterations = result.iterations, discarded = result.discarded, seed = result.seed, "Ran Monte Carlo simulation");
+    Ok(result)
+}
@@ -0,0 +1,232 @@
+// Mutual Funds - AMFI NAV feed parsed in Rust and cached in SQLite
+use chrono::NaiveDate;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::db;
+use crate::scraper::NativeScraper;
+use crate::settings::{self, SettingsState};
+
+const AMFI_NAV_URL: &str = "https://www.amfiindia.com/spages/NAVAll.txt";
+
+// AMFI publishes once a day (late evening IST); twice a day is plenty
+const NAV_STALE_AFTER_SECS: i64 = 12 * 60 * 60;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS mutual_fund_nav (
+    scheme_code TEXT PRIMARY KEY,
+    scheme_name TEXT NOT NULL,
+    fund_house TEXT,
+    category TEXT,
+    isin_growth TEXT,
+    isin_reinvestment TEXT,
+    nav REAL,
+    nav_date TEXT              -- YYYY-MM-DD
+);
+CREATE TABLE IF NOT EXISTS mutual_fund_meta (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    fetched_at INTEGER NOT NULL,
+    scheme_count INTEGER NOT NULL
+);
+";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FundNav {
+    pub scheme_code: String,
+    pub scheme_name: String,
+    pub fund_house: Option<String>,
+    pub category: Option<String>,
+    pub isin_growth: Option<String>,
+    pub isin_reinvestment: Option<String>,
+    pub nav: Option<f64>,
+    pub nav_date: Option<String>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty() && value != "-").then(|| value.to_string())
+}
+
+/// Parse NAVAll.txt. Data rows are `code;isin;isin;name;nav;date`; the lines in
+/// between name the scheme category ("Open Ended Schemes(Equity Scheme - ...)")
+/// and the fund house, and apply to every row that follows.
+fn parse_nav_feed(text: &str) -> Vec<FundNav> {
+    let mut funds = Vec::new();
+    let mut category: Option<String> = None;
+    let mut fund_house: Option<String> = None;
+
+    for line in text.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let fields: Vec<&str> = line.split(';').collect();
+        if fields.len() < 6 {
+            if line.contains("Schemes(") || line.ends_with("Schemes") {
+                category = Some(line.to_string());
+            } else {
+                fund_house = Some(line.to_string());
+            }
+            continue;
+        }
+        // Header row
+        if !fields[0].trim().chars().all(|c| c.is_ascii_digit()) {
+            continue;
+        }
+
+        funds.push(FundNav {
+            scheme_code: fields[0].trim().to_string(),
+            scheme_name: fields[3].trim().to_string(),
+            fund_house: fund_house.clone(),
+            category: category.clone(),
+            isin_growth: non_empty(fields[1]),
+            isin_reinvestment: non_empty(fields[2]),
+            // "N.A." for suspended schemes
+            nav: fields[4].trim().replace(',', "").parse::<f64>().ok(),
+            nav_date: NaiveDate::parse_from_str(fields[5].trim(), "%d-%b-%Y")
+                .ok()
+                .map(|d| d.format("%Y-%m-%d").to_string()),
+        });
+    }
+    funds
+}
+
+fn store_funds(conn: &mut Connection, funds: &[FundNav]) -> Result<(), String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for f in funds {
+        tx.execute(
+            "INSERT OR REPLACE INTO mutual_fund_nav
+                (scheme_code, scheme_name, fund_house, category, isin_growth, isin_reinvestment, nav, nav_date)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![f.scheme_code, f.scheme_name, f.fund_house, f.category, f.isin_growth, f.isin_reinvestment, f.nav, f.nav_date],
+        ).map_err(|e| e.to_string())?;
+    }
+    tx.execute(
+        "INSERT OR REPLACE INTO mutual_fund_meta (id, fetched_at, scheme_count) VALUES (1, ?1, ?2)",
+        params![now_secs(), funds.len() as i64],
+    ).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Download the feed if the local copy is missing or stale. A failed refresh
+/// falls back to the stale copy when there is one.
+async fn ensure_nav_data(app: &AppHandle, force: bool) -> Result<(), String> {
+    let fetched_at: Option<i64> = {
+        let conn = db::open_app_db(app)?;
+        conn.query_row("SELECT fetched_at FROM mutual_fund_meta WHERE id = 1", [], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?
+    };
+    if !force && fetched_at.is_some_and(|t| now_secs() - t < NAV_STALE_AFTER_SECS) {
+        return Ok(());
+    }
+    // Offline, a stale copy beats no copy
+    if settings::is_offline(app) {
+        return if fetched_at.is_some() { Ok(()) } else { Err(settings::OFFLINE_ERROR.to_string()) };
+    }
+
+    // The feed is a few MB, so allow the longer search timeout
+    let timeout = {
+        let state = app.state::<SettingsState>();
+        let secs = state.read().await.get().scraper.search_timeout_secs;
+        Duration::from_secs(secs)
+    };
+
+    eprintln!("[MutualFunds] Downloading AMFI NAV feed");
+    let text = match app.state::<NativeScraper>().get_text(AMFI_NAV_URL, timeout).await {
+        Ok(text) => text,
+        Err(e) if fetched_at.is_some() => {
+            eprintln!("[MutualFunds] Refresh failed, using cached NAVs: {}", e);
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    };
+
+    let funds = parse_nav_feed(&text);
+    if funds.is_empty() {
+        return Err("AMFI NAV feed contained no schemes".to_string());
+    }
+    let mut conn = db::open_app_db(app)?;
+    store_funds(&mut conn, &funds)?;
+    eprintln!("[MutualFunds] Cached {} schemes", funds.len());
+    Ok(())
+}
+
+fn row_to_fund(row: &rusqlite::Row) -> rusqlite::Result<FundNav> {
+    Ok(FundNav {
+        scheme_code: row.get(0)?,
+        scheme_name: row.get(1)?,
+        fund_house: row.get(2)?,
+        category: row.get(3)?,
+        isin_growth: row.get(4)?,
+        isin_reinvestment: row.get(5)?,
+        nav: row.get(6)?,
+        nav_date: row.get(7)?,
+    })
+}
+
+const FUND_COLUMNS: &str = "scheme_code, scheme_name, fund_house, category, isin_growth, isin_reinvestment, nav, nav_date";
+
+// Tauri Commands
+#[tauri::command]
+pub async fn search_mutual_funds(
+    app: AppHandle,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<FundNav>, String> {
+    let query = query.trim().to_string();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    ensure_nav_data(&app, false).await?;
+
+    let conn = db::open_app_db(&app)?;
+    // Exact code / ISIN lookups first, then every word must appear in the name or fund house
+    let exact = conn.query_row(
+        &format!("SELECT {} FROM mutual_fund_nav WHERE scheme_code = ?1 OR isin_growth = ?2 OR isin_reinvestment = ?2", FUND_COLUMNS),
+        params![query, query.to_uppercase()],
+        row_to_fund,
+    ).optional().map_err(|e| e.to_string())?;
+    if let Some(fund) = exact {
+        return Ok(vec![fund]);
+    }
+
+    let words: Vec<String> = query.split_whitespace().map(|w| format!("%{}%", w.to_lowercase())).collect();
+    let conditions = (1..=words.len())
+        .map(|i| format!("LOWER(scheme_name || ' ' || IFNULL(fund_house, '')) LIKE ?{}", i))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    let sql = format!(
+        "SELECT {} FROM mutual_fund_nav WHERE {} ORDER BY scheme_name LIMIT {}",
+        FUND_COLUMNS, conditions, limit.unwrap_or(50)
+    );
+
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(rusqlite::params_from_iter(words.iter()), row_to_fund)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_fund_nav(
+    app: AppHandle,
+    scheme_code: String,
+    force_refresh: Option<bool>,
+) -> Result<FundNav, String> {
+    ensure_nav_data(&app, force_refresh.unwrap_or(false)).await?;
+
+    let conn = db::open_app_db(&app)?;
+    conn.query_row(
+        &format!("SELECT {} FROM mutual_fund_nav WHERE scheme_code = ?1", FUND_COLUMNS),
+        params![scheme_code.trim()],
+        row_to_fund,
+    ).optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Unknown scheme code: {}", scheme_code))
+}
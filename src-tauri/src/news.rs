@@ -0,0 +1,172 @@
+// News - recent company headlines from Google News and Yahoo Finance RSS, deduplicated
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tauri::AppHandle;
+
+use crate::market_cache::MarketCache;
+use crate::scraper::{self, NativeScraper};
+use crate::settings::{self, SettingsState};
+use crate::symbols;
+
+const GOOGLE_NEWS_RSS: &str = "https://news.google.com/rss/search";
+const YAHOO_HEADLINES_RSS: &str = "https://feeds.finance.yahoo.com/rss/2.0/headline";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewsHeadline {
+    pub title: String,
+    pub source: Option<String>,
+    pub published_at: Option<String>,  // RFC 3339, UTC
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompanyNews {
+    pub symbol: String,
+    pub query: String,
+    pub headlines: Vec<NewsHeadline>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cached: Option<bool>,
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// Text content of the first `<tag ...>...</tag>` in `xml`, with CDATA unwrapped.
+fn tag_text(xml: &str, tag: &str) -> Option<String> {
+    let open = xml.find(&format!("<{}", tag))?;
+    let body_start = open + xml[open..].find('>')? + 1;
+    let body_end = body_start + xml[body_start..].find(&format!("</{}>", tag))?;
+    let body = xml[body_start..body_end].trim();
+    let body = body.strip_prefix("<![CDATA[")
+        .and_then(|b| b.strip_suffix("]]>"))
+        .unwrap_or(body);
+    let text = decode_entities(body.trim());
+    (!text.is_empty()).then_some(text)
+}
+
+/// Pull `<item>` entries out of an RSS 2.0 feed. Feeds here are simple enough
+/// that string scanning beats pulling in an XML parser.
+fn parse_rss(xml: &str, default_source: &str) -> Vec<NewsHeadline> {
+    xml.split("<item>")
+        .skip(1)
+        .filter_map(|chunk| {
+            let item = chunk.split("</item>").next()?;
+            let mut title = tag_text(item, "title")?;
+            let url = tag_text(item, "link")?;
+            let mut source = tag_text(item, "source");
+
+            // Google News appends " - Publisher" to every title
+            if let Some(publisher) = &source {
+                if let Some(stripped) = title.strip_suffix(&format!(" - {}", publisher)) {
+                    title = stripped.to_string();
+                }
+            } else {
+                source = Some(default_source.to_string());
+            }
+
+            let published_at = tag_text(item, "pubDate")
+                .and_then(|d| DateTime::parse_from_rfc2822(&d).ok())
+                .map(|d| d.with_timezone(&Utc).to_rfc3339());
+
+            Some(NewsHeadline { title, source, published_at, url })
+        })
+        .collect()
+}
+
+/// Dedup key: the same story syndicated across outlets differs only in
+/// punctuation and case.
+fn title_key(title: &str) -> String {
+    title.chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+async fn fetch_feed(native: &NativeScraper, url: &str, source: &str, app: &AppHandle) -> Vec<NewsHeadline> {
+    match native.get_text(url, scraper::request_timeout(app)).await {
+        Ok(xml) => parse_rss(&xml, source),
+        Err(e) => {
+            eprintln!("[News] {} feed unavailable: {}", source, e);
+            Vec::new()
+        }
+    }
+}
+
+// Tauri Commands
+#[tauri::command]
+pub async fn get_company_news(
+    app: AppHandle,
+    native: tauri::State<'_, NativeScraper>,
+    cache: tauri::State<'_, MarketCache>,
+    settings: tauri::State<'_, SettingsState>,
+    symbol: String,
+    limit: Option<usize>,
+) -> Result<CompanyNews, String> {
+    let symbol = symbol.trim().to_uppercase();
+    if symbol.is_empty() {
+        return Err("Symbol is required".to_string());
+    }
+    let limit = limit.unwrap_or(20);
+
+    let ttl = {
+        let store = settings.read().await;
+        store.get().scraper.cache_ttl_secs
+    };
+    if let Some(payload) = cache.get(&app, "NEWS", &symbol, "ALL", ttl) {
+        if let Ok(mut news) = serde_json::from_value::<CompanyNews>(payload) {
+            news.headlines.truncate(limit);
+            news.cached = Some(true);
+            return Ok(news);
+        }
+    }
+
+    settings::ensure_online(&app)?;
+
+    // Search by company name when the symbol map knows it; bare tickers match too much noise
+    let query = symbols::company_name(&app, &symbol).unwrap_or_else(|| symbol.clone());
+    // Numeric identifiers are BSE scrip codes
+    let exchange = if symbol.chars().all(|c| c.is_ascii_digit()) { "BSE" } else { "NSE" };
+    let ticker = scraper::yahoo_ticker(&symbol, exchange)?;
+
+    let google_url = format!(
+        "{}?q={}&hl=en-IN&gl=IN&ceid=IN:en",
+        GOOGLE_NEWS_RSS, scraper::url_encode(&format!("\"{}\" stock", query)),
+    );
+    let yahoo_url = format!("{}?s={}&region=IN&lang=en-IN", YAHOO_HEADLINES_RSS, scraper::url_encode(&ticker));
+    let (google, yahoo) = futures_util::join!(
+        fetch_feed(&native, &google_url, "Google News", &app),
+        fetch_feed(&native, &yahoo_url, "Yahoo Finance", &app),
+    );
+    if google.is_empty() && yahoo.is_empty() {
+        return Err(format!("No news found for {}", symbol));
+    }
+
+    let mut seen_titles = HashSet::new();
+    let mut seen_urls = HashSet::new();
+    let mut headlines: Vec<NewsHeadline> = google.into_iter()
+        .chain(yahoo)
+        .filter(|h| seen_urls.insert(h.url.clone()) && seen_titles.insert(title_key(&h.title)))
+        .collect();
+    // UTC RFC 3339 strings sort chronologically; undated items go last
+    headlines.sort_by(|a, b| b.published_at.cmp(&a.published_at));
+
+    let mut news = CompanyNews { symbol, query, headlines, cached: None };
+    if let Ok(payload) = serde_json::to_value(&news) {
+        cache.put(&app, "NEWS", &news.symbol, "ALL", &payload);
+    }
+    news.headlines.truncate(limit);
+    Ok(news)
+}
@@ -0,0 +1,229 @@
+// Notes - the user's free-text annotations on a document, one of its items or a chat message,
+// listed beside what they annotate and optionally printed in exports and reports
+use rusqlite::{params, Row};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use tracing::info;
+
+use crate::db;
+use crate::realtime;
+use crate::statements;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS notes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    target TEXT NOT NULL,          -- 'document', 'item', 'chat_message'
+    document_id INTEGER,
+    item_id TEXT,
+    session_id TEXT,
+    message_index INTEGER,
+    body TEXT NOT NULL,
+    author TEXT,
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_notes_document ON notes(document_id);
+CREATE INDEX IF NOT EXISTS idx_notes_session ON notes(session_id);
+";
+
+/// What a note is attached to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NoteTarget {
+    #[serde(rename_all = "camelCase")]
+    Document { document_id: i64 },
+    /// `item_id` is the item's own id within the document
+    #[serde(rename_all = "camelCase")]
+    Item { document_id: i64, item_id: String },
+    /// `message_index` is the message's position in the session's history
+    #[serde(rename_all = "camelCase")]
+    ChatMessage { session_id: String, message_index: i64 },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Note {
+    pub id: i64,
+    pub target: NoteTarget,
+    pub body: String,
+    pub author: Option<String>,
+    pub created_at: i64,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+const COLUMNS: &str = "id, target, document_id, item_id, session_id, message_index, body, author, created_at";
+
+fn note_from_row(row: &Row) -> rusqlite::Result<Option<Note>> {
+    let target: String = row.get(1)?;
+    let target = match target.as_str() {
+        "document" => row.get::<_, Option<i64>>(2)?.map(|document_id| NoteTarget::Document { document_id }),
+        "item" => match (row.get::<_, Option<i64>>(2)?, row.get::<_, Option<String>>(3)?) {
+            (Some(document_id), Some(item_id)) => Some(NoteTarget::Item { document_id, item_id }),
+            _ => None,
+        },
+        "chat_message" => match (row.get::<_, Option<String>>(4)?, row.get::<_, Option<i64>>(5)?) {
+            (Some(session_id), Some(message_index)) => Some(NoteTarget::ChatMessage { session_id, message_index }),
+            _ => None,
+        },
+        // Written by a newer version
+        _ => None,
+    };
+    let (id, body, author, created_at) = (row.get(0)?, row.get(6)?, row.get(7)?, row.get(8)?);
+    Ok(target.map(|target| Note { id, target, body, author, created_at }))
+}
+
+fn query(app: &AppHandle, filter: &str, args: &[&dyn rusqlite::ToSql]) -> Result<Vec<Note>, String> {
+    let conn = db::open_app_db(app)?;
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM notes WHERE {} ORDER BY created_at, id", COLUMNS, filter))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(args, note_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows.into_iter().flatten().collect())
+}
+
+/// A document's notes and its items' notes, oldest first, for exports and reports.
+pub fn for_document(app: &AppHandle, document_id: i64) -> Result<Vec<Note>, String> {
+    query(app, "document_id = ?1 AND target IN ('document', 'item')", &[&document_id])
+}
+
+/// The label a note's item had in `items`, or its id when the item is gone.
+pub fn item_label(note: &Note, items: &[serde_json::Value]) -> Option<String> {
+    let NoteTarget::Item { item_id, .. } = &note.target else { return None };
+    let label = items.iter()
+        .find(|item| item["id"].as_str() == Some(item_id.as_str()))
+        .and_then(|item| item["label"].as_str());
+    Some(label.map_or_else(|| format!("Item {}", item_id), str::to_string))
+}
+
+fn target_columns(target: &NoteTarget) -> (&'static str, Option<i64>, Option<&str>, Option<&str>, Option<i64>) {
+    match target {
+        NoteTarget::Document { document_id } => ("document", Some(*document_id), None, None, None),
+        NoteTarget::Item { document_id, item_id } => ("item", Some(*document_id), Some(item_id), None, None),
+        NoteTarget::ChatMessage { session_id, message_index } => ("chat_message", None, None, Some(session_id), Some(*message_index)),
+    }
+}
+
+fn remove(app: &AppHandle, id: i64) -> Result<Option<Note>, String> {
+    let note = query(app, "id = ?1", &[&id])?.into_iter().next();
+    let conn = db::open_app_db(app)?;
+    conn.execute("DELETE FROM notes WHERE id = ?1", params![id]).map_err(|e| e.to_string())?;
+    if let Some(note) = &note {
+        let _ = app.emit("notes-changed", &note.target);
+    }
+    Ok(note)
+}
+
+/// Write a note another device made, over note `id` when that is still here. Unlike `add_note`
+/// it is not published back to the realtime channel.
+pub fn store_synced(
+    app: &AppHandle,
+    id: Option<i64>,
+    target: NoteTarget,
+    body: String,
+    author: Option<String>,
+    created_at: i64,
+) -> Result<Note, String> {
+    let (kind, document_id, item_id, session_id, message_index) = target_columns(&target);
+    let conn = db::open_app_db(app)?;
+    let updated = match id {
+        Some(id) => conn.execute(
+            "UPDATE notes SET target = ?2, document_id = ?3, item_id = ?4, session_id = ?5, message_index = ?6,
+                    body = ?7, author = ?8, created_at = ?9
+             WHERE id = ?1",
+            params![id, kind, document_id, item_id, session_id, message_index, body, author, created_at],
+        ).map_err(|e| e.to_string())?,
+        None => 0,
+    };
+    let id = match (id, updated) {
+        (Some(id), 1) => id,
+        _ => {
+            conn.execute(
+                "INSERT INTO notes (target, document_id, item_id, session_id, message_index, body, author, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![kind, document_id, item_id, session_id, message_index, body, author, created_at],
+            ).map_err(|e| e.to_string())?;
+            conn.last_insert_rowid()
+        }
+    };
+    let note = Note { id, target, body, author, created_at };
+    let _ = app.emit("notes-changed", &note.target);
+    Ok(note)
+}
+
+/// Remove a note another device deleted, without publishing the deletion back.
+pub fn remove_synced(app: &AppHandle, id: i64) -> Result<(), String> {
+    remove(app, id).map(|_| ())
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn add_note(app: AppHandle, target: NoteTarget, body: String, author: Option<String>) -> Result<Note, String> {
+    let body = body.trim().to_string();
+    if body.is_empty() {
+        return Err("A note needs some text".to_string());
+    }
+    match &target {
+        NoteTarget::Document { document_id } => {
+            statements::load_stored_document(&app, *document_id)?;
+        }
+        NoteTarget::Item { document_id, item_id } => {
+            let document = statements::load_stored_document(&app, *document_id)?;
+            if !document.items.iter().any(|item| item["id"].as_str() == Some(item_id.as_str())) {
+                return Err(format!("Item {} not found in document {}", item_id, document_id));
+            }
+        }
+        NoteTarget::ChatMessage { session_id, message_index } => {
+            if session_id.trim().is_empty() || *message_index < 0 {
+                return Err("A chat note needs a session and a message position".to_string());
+            }
+        }
+    }
+    let (kind, document_id, item_id, session_id, message_index) = target_columns(&target);
+    let author = author.map(|a| a.trim().to_string()).filter(|a| !a.is_empty());
+    let created_at = now_secs();
+    let conn = db::open_app_db(&app)?;
+    conn.execute(
+        "INSERT INTO notes (target, document_id, item_id, session_id, message_index, body, author, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![kind, document_id, item_id, session_id, message_index, body, author, created_at],
+    ).map_err(|e| e.to_string())?;
+    let note = Note { id: conn.last_insert_rowid(), target, body, author, created_at };
+    info!(note = note.id, target = kind, "Added note");
+    let _ = app.emit("notes-changed", &note.target);
+    realtime::note_saved(&app, &note);
+    Ok(note)
+}
+
+/// Notes oldest first: on a document (with `item_id`, on that item only), in a chat session,
+/// or with neither every note.
+#[tauri::command]
+pub fn list_notes(
+    app: AppHandle,
+    document_id: Option<i64>,
+    item_id: Option<String>,
+    session_id: Option<String>,
+) -> Result<Vec<Note>, String> {
+    query(
+        &app,
+        "(?1 IS NULL OR document_id = ?1) AND (?2 IS NULL OR item_id = ?2) AND (?3 IS NULL OR session_id = ?3)",
+        &[&document_id, &item_id, &session_id],
+    )
+}
+
+/// Returns whether there was such a note.
+#[tauri::command]
+pub fn delete_note(app: AppHandle, id: i64) -> Result<bool, String> {
+    let deleted = remove(&app, id)?.is_some();
+    if deleted {
+        realtime::note_deleted(&app, id);
+    }
+    Ok(deleted)
+}
@@ -0,0 +1,42 @@
+// Notifications - OS notifications for long-running work that finishes while the user is elsewhere
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+use tracing::warn;
+
+use crate::settings::{self, SettingsState};
+use crate::tasks::{BackgroundTask, TaskKind, TaskStatus};
+
+fn muted(app: &AppHandle) -> bool {
+    app.try_state::<SettingsState>()
+        .is_some_and(|state| settings::blocking_read(&state).get().mute_notifications)
+}
+
+/// The user is looking at the app, so the in-app events are enough.
+fn window_focused(app: &AppHandle) -> bool {
+    app.get_webview_window("main")
+        .is_some_and(|window| window.is_visible().unwrap_or(false) && window.is_focused().unwrap_or(false))
+}
+
+/// Show an OS notification unless they are muted or the main window has focus.
+pub fn notify_user(app: &AppHandle, title: &str, body: impl Into<String>) {
+    if muted(app) || window_focused(app) {
+        return;
+    }
+    if let Err(e) = app.notification().builder().title(title).body(body.into()).show() {
+        warn!("Failed to show notification: {}", e);
+    }
+}
+
+/// Announce a finished analysis or model pull; cancelled tasks and streams stay quiet.
+pub fn task_finished(app: &AppHandle, task: &BackgroundTask) {
+    let error = task.error.as_deref().unwrap_or("unknown error");
+    match (task.kind, task.status) {
+        (TaskKind::Analysis, TaskStatus::Completed) => notify_user(app, "Analysis complete", format!("{} is ready", task.label)),
+        (TaskKind::Analysis, TaskStatus::Failed) => notify_user(app, "Analysis failed", format!("{}: {}", task.label, error)),
+        (TaskKind::ModelPull, TaskStatus::Completed) => notify_user(app, "Model downloaded", format!("{} is ready to use", task.label)),
+        (TaskKind::ModelPull, TaskStatus::Failed) => notify_user(app, "Model download failed", format!("{}: {}", task.label, error)),
+        (TaskKind::AiAnalysis, TaskStatus::Completed) => notify_user(app, "AI analysis ready", task.label.clone()),
+        (TaskKind::AiAnalysis, TaskStatus::Failed) => notify_user(app, "AI analysis failed", format!("{}: {}", task.label, error)),
+        _ => {}
+    }
+}
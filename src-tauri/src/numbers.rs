@@ -0,0 +1,167 @@
+// Numbers - amounts as Indian statements write them ("1,23,456.78", "₹ 12.5 Cr", "(4,500)")
+// normalized to plain values, for every path that stores financial items
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Unit {
+    Thousand,
+    Lakh,
+    Crore,
+    Million,
+    Billion,
+}
+
+impl Unit {
+    pub fn scale(self) -> f64 {
+        match self {
+            Unit::Thousand => 1e3,
+            Unit::Lakh => 1e5,
+            Unit::Crore => 1e7,
+            Unit::Million => 1e6,
+            Unit::Billion => 1e9,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Unit::Thousand => "thousand",
+            Unit::Lakh => "lakh",
+            Unit::Crore => "crore",
+            Unit::Million => "million",
+            Unit::Billion => "billion",
+        }
+    }
+
+    /// Suffix after a figure in reports: "₹ 12.50 Cr"
+    pub fn abbreviation(self) -> &'static str {
+        match self {
+            Unit::Thousand => "'000",
+            Unit::Lakh => "Lakh",
+            Unit::Crore => "Cr",
+            Unit::Million => "Mn",
+            Unit::Billion => "Bn",
+        }
+    }
+
+    /// A suffix or name as written: "Cr", "crores", "lacs", "mn", "'000", ...
+    pub fn parse(suffix: &str) -> Option<Self> {
+        match suffix.trim().to_lowercase().trim_end_matches('.') {
+            "k" | "thousand" | "thousands" | "'000" | "000s" => Some(Unit::Thousand),
+            "l" | "lakh" | "lakhs" | "lac" | "lacs" => Some(Unit::Lakh),
+            "cr" | "crs" | "crore" | "crores" => Some(Unit::Crore),
+            "mn" | "million" | "millions" => Some(Unit::Million),
+            "bn" | "billion" | "billions" => Some(Unit::Billion),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NormalizedNumber {
+    pub raw: String,
+    /// In base units (rupees), the detected unit already applied; None for blanks, "-" and text
+    pub value: Option<f64>,
+    pub unit: Option<Unit>,
+}
+
+// Leading currency markers, longest first so "rs." goes before "rs"
+const CURRENCY: &[&str] = &["₹", "inr", "rs.", "rs", "$", "usd"];
+
+/// Commas must group digits the Indian (1,23,45,678) or western (12,345,678) way; anything
+/// else ("1,2,3") is a list rather than one number.
+fn grouping_ok(integer: &str) -> bool {
+    let groups: Vec<&str> = integer.split(',').collect();
+    let Some((last, middle)) = groups[1..].split_last() else { return true };
+    let first_ok = (1..=3).contains(&groups[0].len());
+    let indian = last.len() == 3 && middle.iter().all(|g| g.len() == 2);
+    let western = groups[1..].iter().all(|g| g.len() == 3);
+    first_ok && (indian || western)
+}
+
+/// Parse one amount: currency symbols, spaces, comma grouping and a unit suffix are accepted,
+/// parentheses or a leading minus make it negative.
+pub fn normalize(raw: &str) -> NormalizedNumber {
+    let mut result = NormalizedNumber { raw: raw.to_string(), value: None, unit: None };
+    let mut text = raw.trim().to_lowercase().replace('\u{2212}', "-");
+
+    let mut negative = false;
+    if text.starts_with('(') && text.ends_with(')') {
+        negative = true;
+        text = text[1..text.len() - 1].trim().to_string();
+    }
+    if let Some(rest) = text.strip_prefix('-') {
+        negative = !negative;
+        text = rest.trim().to_string();
+    }
+    if let Some(currency) = CURRENCY.iter().find(|c| text.starts_with(*c)) {
+        text = text[currency.len()..].trim().to_string();
+    }
+    // "(₹ 1,200)" and "₹ (1,200)" both occur
+    if text.starts_with('(') && text.ends_with(')') {
+        negative = !negative;
+        text = text[1..text.len() - 1].trim().to_string();
+    }
+
+    let number_end = text.find(|c: char| !(c.is_ascii_digit() || c == ',' || c == '.' || c == ' ')).unwrap_or(text.len());
+    let (number, suffix) = text.split_at(number_end);
+    let suffix = suffix.trim();
+    if !suffix.is_empty() {
+        match Unit::parse(suffix) {
+            Some(unit) => result.unit = Some(unit),
+            None => return result,
+        }
+    }
+
+    let number = number.replace(' ', "");
+    let integer = number.split('.').next().unwrap_or("");
+    if number.is_empty() || !grouping_ok(integer) {
+        return result;
+    }
+    let Ok(value) = number.replace(',', "").parse::<f64>() else { return result };
+    let value = value * result.unit.map_or(1.0, Unit::scale);
+    result.value = Some(if negative { -value } else { value });
+    result
+}
+
+/// Numbers pass through; strings are parsed as amounts.
+pub fn normalize_value(value: &serde_json::Value) -> NormalizedNumber {
+    match value {
+        serde_json::Value::Number(n) => NormalizedNumber { raw: n.to_string(), value: n.as_f64(), unit: None },
+        serde_json::Value::String(s) => normalize(s),
+        _ => NormalizedNumber { raw: String::new(), value: None, unit: None },
+    }
+}
+
+/// Normalize an item in the frontend shape before it is stored: `currentYear`, `previousYear`
+/// and `allYears` become numbers, the strings they came from are kept in `rawCurrentYear` and
+/// `rawPreviousYear`, a unit found in them goes in `detectedUnit`, and `confidence` becomes a
+/// number from 0 to 1. Normalizing twice changes nothing.
+pub fn normalize_item(item: &mut serde_json::Value) {
+    let Some(fields) = item.as_object_mut() else { return };
+    let mut detected = None;
+    for (field, raw_field) in [("currentYear", "rawCurrentYear"), ("previousYear", "rawPreviousYear")] {
+        let Some(value) = fields.get(field) else { continue };
+        if !value.is_string() {
+            continue;
+        }
+        let number = normalize_value(value);
+        detected = detected.or(number.unit);
+        fields.insert(raw_field.to_string(), number.raw.into());
+        fields.insert(field.to_string(), number.value.unwrap_or(0.0).into());
+    }
+    if let Some(serde_json::Value::Object(years)) = fields.get_mut("allYears") {
+        for value in years.values_mut().filter(|value| value.is_string()) {
+            *value = normalize_value(value).value.into();
+        }
+    }
+    if let Some(unit) = detected {
+        fields.insert("detectedUnit".to_string(), serde_json::json!(unit));
+    }
+    // Some parsers score confidence in percent
+    if let Some(confidence) = fields.get("confidence").and_then(|c| normalize_value(c).value) {
+        let confidence = if confidence > 1.0 { confidence / 100.0 } else { confidence };
+        fields.insert("confidence".to_string(), confidence.clamp(0.0, 1.0).into());
+    }
+}
@@ -0,0 +1,494 @@
+// OAuth - data providers that want OAuth instead of a static API key: the provider's client
+// registration, connecting through PKCE (browser + localhost callback) or the device-code flow,
+// and access tokens kept in the OS keychain and refreshed before they expire
+use rusqlite::{params, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::process::{Command, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+
+use crate::db;
+use crate::http;
+use crate::keychain;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS oauth_providers (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    flow TEXT NOT NULL,            -- 'pkce', 'device_code'
+    client_id TEXT NOT NULL,
+    authorization_url TEXT,
+    token_url TEXT NOT NULL,
+    device_authorization_url TEXT,
+    scopes TEXT NOT NULL,          -- JSON array
+    redirect_port INTEGER,
+    connected_at INTEGER,
+    expires_at INTEGER,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+";
+// Tokens this close to expiry are refreshed before use
+const REFRESH_MARGIN_SECS: i64 = 60;
+// How long the browser sign-in may take
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(300);
+const DEFAULT_REDIRECT_PORT: u16 = 53682;
+const DEVICE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OAuthFlow {
+    /// Authorization code with PKCE, redirected to a one-shot listener on 127.0.0.1
+    Pkce,
+    /// The user enters a code on another page while the app polls
+    DeviceCode,
+}
+
+impl OAuthFlow {
+    fn as_str(self) -> &'static str {
+        match self {
+            OAuthFlow::Pkce => "pkce",
+            OAuthFlow::DeviceCode => "device_code",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthProviderInput {
+    pub id: String,
+    pub name: String,
+    pub flow: OAuthFlow,
+    pub client_id: String,
+    /// Required for PKCE
+    pub authorization_url: Option<String>,
+    pub token_url: String,
+    /// Required for the device-code flow
+    pub device_authorization_url: Option<String>,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// Must match the redirect registered with the provider: http://127.0.0.1:{port}/callback
+    pub redirect_port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthProvider {
+    pub id: String,
+    pub name: String,
+    pub flow: OAuthFlow,
+    pub client_id: String,
+    pub authorization_url: Option<String>,
+    pub token_url: String,
+    pub device_authorization_url: Option<String>,
+    pub scopes: Vec<String>,
+    pub redirect_port: Option<u16>,
+    pub connected: bool,
+    pub connected_at: Option<i64>,
+    /// When the current access token lapses; None if it doesn't say
+    pub expires_at: Option<i64>,
+    pub created_at: i64,
+}
+
+/// What goes in the keychain for a connected provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokenSet {
+    access_token: String,
+    refresh_token: Option<String>,
+    token_type: Option<String>,
+    scope: Option<String>,
+    expires_at: Option<i64>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn keychain_account(provider_id: &str) -> String {
+    format!("oauth:{}", provider_id)
+}
+
+const COLUMNS: &str = "id, name, flow, client_id, authorization_url, token_url, device_authorization_url, scopes,
+    redirect_port, connected_at, expires_at, created_at";
+
+fn provider_from_row(row: &Row) -> rusqlite::Result<OAuthProvider> {
+    let flow: String = row.get(2)?;
+    let scopes: String = row.get(7)?;
+    let connected_at: Option<i64> = row.get(9)?;
+    Ok(OAuthProvider {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        flow: if flow == "device_code" { OAuthFlow::DeviceCode } else { OAuthFlow::Pkce },
+        client_id: row.get(3)?,
+        authorization_url: row.get(4)?,
+        token_url: row.get(5)?,
+        device_authorization_url: row.get(6)?,
+        scopes: serde_json::from_str(&scopes).unwrap_or_default(),
+        redirect_port: row.get(8)?,
+        connected: connected_at.is_some(),
+        connected_at,
+        expires_at: row.get(10)?,
+        created_at: row.get(11)?,
+    })
+}
+
+fn load_provider(app: &AppHandle, id: &str) -> Result<OAuthProvider, String> {
+    let conn = db::open_app_db(app)?;
+    conn.query_row(&format!("SELECT {} FROM oauth_providers WHERE id = ?1", COLUMNS), params![id], provider_from_row)
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("OAuth provider {} not found", id))
+}
+
+fn mark_connected(app: &AppHandle, id: &str, tokens: Option<&TokenSet>) -> Result<(), String> {
+    let conn = db::open_app_db(app)?;
+    let (connected_at, expires_at) = match tokens {
+        Some(tokens) => (Some(now_secs()), tokens.expires_at),
+        None => (None, None),
+    };
+    conn.execute(
+        "UPDATE oauth_providers SET connected_at = CASE WHEN ?2 IS NULL THEN NULL ELSE IFNULL(connected_at, ?2) END,
+             expires_at = ?3, updated_at = ?4 WHERE id = ?1",
+        params![id, connected_at, expires_at, now_secs()],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn store_tokens(app: &AppHandle, id: &str, tokens: &TokenSet) -> Result<(), String> {
+    let secret = serde_json::to_string(tokens).map_err(|e| e.to_string())?;
+    keychain::set(app, &keychain_account(id), &secret).await
+}
+
+async fn load_tokens(app: &AppHandle, id: &str) -> Result<Option<TokenSet>, String> {
+    keychain::get(app, &keychain_account(id)).await?
+        .map(|secret| serde_json::from_str(&secret).map_err(|e| format!("Stored token for {} is unreadable: {}", id, e)))
+        .transpose()
+}
+
+/// RFC 4648 base64url without padding, as PKCE and state values use.
+fn base64url(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    bytes.chunks(3)
+        .flat_map(|chunk| {
+            let n = chunk.iter().enumerate().fold(0u32, |n, (i, b)| n | (*b as u32) << (16 - 8 * i));
+            (0..=chunk.len()).map(move |i| ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char)
+        })
+        .collect()
+}
+
+fn random_token() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    getrandom::fill(&mut bytes).map_err(|e| format!("No randomness available: {}", e))?;
+    Ok(base64url(&bytes))
+}
+
+fn open_browser(url: &str) -> bool {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg(url);
+        command
+    } else if cfg!(windows) {
+        let mut command = Command::new("rundll32");
+        command.args(["url.dll,FileProtocolHandler", url]);
+        command
+    } else {
+        let mut command = Command::new("xdg-open");
+        command.arg(url);
+        command
+    };
+    command.stdout(Stdio::null()).stderr(Stdio::null()).spawn().is_ok()
+}
+
+/// POST a form to a token endpoint. Error responses come back as JSON with `error` set, so the
+/// body is returned whatever the status.
+async fn post_form(app: &AppHandle, url: &str, pairs: &[(&str, &str)]) -> Result<Value, String> {
+    let mut form = reqwest::Url::parse("http://form.invalid/").map_err(|e| e.to_string())?;
+    form.query_pairs_mut().extend_pairs(pairs);
+    let response = http::client(app)
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+        .header(reqwest::header::ACCEPT, "application/json")
+        .body(form.query().unwrap_or_default().to_string())
+        .send()
+        .await
+        .map_err(|e| format!("Provider unreachable: {}", e))?;
+    let status = response.status();
+    let body = response.text().await.map_err(|e| e.to_string())?;
+    serde_json::from_str(&body).map_err(|_| format!("Provider returned {}: {}", status, body.chars().take(200).collect::<String>()))
+}
+
+fn oauth_error(response: &Value) -> Option<String> {
+    let error = response["error"].as_str()?;
+    Some(response["error_description"].as_str().map_or_else(|| error.to_string(), |d| format!("{}: {}", error, d)))
+}
+
+/// A token response; a refresh that doesn't rotate the refresh token keeps `previous_refresh`.
+fn token_set(response: &Value, previous_refresh: Option<String>) -> Result<TokenSet, String> {
+    if let Some(error) = oauth_error(response) {
+        return Err(format!("Authorization failed ({})", error));
+    }
+    let access_token = response["access_token"].as_str()
+        .ok_or_else(|| "Token response had no access_token".to_string())?;
+    Ok(TokenSet {
+        access_token: access_token.to_string(),
+        refresh_token: response["refresh_token"].as_str().map(str::to_string).or(previous_refresh),
+        token_type: response["token_type"].as_str().map(str::to_string),
+        scope: response["scope"].as_str().map(str::to_string),
+        expires_at: response["expires_in"].as_i64().map(|secs| now_secs() + secs),
+    })
+}
+
+/// The request line's path, e.g. "/callback?code=..&state=..".
+fn request_path(request: &str) -> Option<&str> {
+    let mut parts = request.lines().next()?.split_whitespace();
+    (parts.next()? == "GET").then_some(())?;
+    parts.next()
+}
+
+/// Wait on `listener` for the browser to come back to /callback; other requests (favicon) get a 404.
+async fn await_callback(listener: &TcpListener, state: &str) -> Result<String, String> {
+    loop {
+        let (mut stream, _) = listener.accept().await.map_err(|e| e.to_string())?;
+        let mut buffer = vec![0u8; 8192];
+        let read = stream.read(&mut buffer).await.map_err(|e| e.to_string())?;
+        let request = String::from_utf8_lossy(&buffer[..read]).to_string();
+        let Some(path) = request_path(&request).filter(|path| path.starts_with("/callback")) else {
+            let _ = stream.write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").await;
+            continue;
+        };
+        let url = reqwest::Url::parse(&format!("http://127.0.0.1{}", path)).map_err(|e| e.to_string())?;
+        let param = |name: &str| url.query_pairs().find(|(key, _)| key == name).map(|(_, value)| value.to_string());
+
+        let outcome = match (param("error"), param("code")) {
+            (Some(error), _) => Err(format!("Sign-in was refused: {}", param("error_description").unwrap_or(error))),
+            (None, Some(_)) if param("state").as_deref() != Some(state) => Err("Sign-in response did not match this request".to_string()),
+            (None, Some(code)) => Ok(code),
+            (None, None) => Err("Sign-in response had no authorization code".to_string()),
+        };
+        let message = match &outcome {
+            Ok(_) => "Connected. You can close this window and return to Financial Calculator.",
+            Err(_) => "Sign-in failed. Return to Financial Calculator for details.",
+        };
+        let page = format!("<!doctype html><html><body style=\"font-family:sans-serif\"><p>{}</p></body></html>", message);
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            page.len(), page,
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+        return outcome;
+    }
+}
+
+async fn connect_pkce(app: &AppHandle, provider: &OAuthProvider) -> Result<TokenSet, String> {
+    let authorization_url = provider.authorization_url.as_deref()
+        .ok_or_else(|| format!("{} has no authorization URL", provider.name))?;
+    let verifier = random_token()?;
+    let challenge = base64url(ring::digest::digest(&ring::digest::SHA256, verifier.as_bytes()).as_ref());
+    let state = random_token()?;
+
+    let port = provider.redirect_port.unwrap_or(DEFAULT_REDIRECT_PORT);
+    let listener = TcpListener::bind(("127.0.0.1", port)).await
+        .map_err(|e| format!("Cannot listen for the sign-in callback on port {}: {}", port, e))?;
+    let redirect_uri = format!("http://127.0.0.1:{}/callback", port);
+    let scope = provider.scopes.join(" ");
+    let url = reqwest::Url::parse_with_params(authorization_url, &[
+        ("response_type", "code"),
+        ("client_id", provider.client_id.as_str()),
+        ("redirect_uri", redirect_uri.as_str()),
+        ("scope", scope.as_str()),
+        ("state", state.as_str()),
+        ("code_challenge", challenge.as_str()),
+        ("code_challenge_method", "S256"),
+    ]).map_err(|e| format!("Invalid authorization URL: {}", e))?;
+
+    // The UI shows the link too, for when no browser could be opened
+    let _ = app.emit("oauth-authorize", json!({ "provider": provider.id, "url": url.as_str() }));
+    if !open_browser(url.as_str()) {
+        warn!(provider = %provider.id, "Could not open a browser for sign-in");
+    }
+
+    let code = tokio::time::timeout(CALLBACK_TIMEOUT, await_callback(&listener, &state)).await
+        .map_err(|_| "Timed out waiting for sign-in".to_string())??;
+    let response = post_form(app, &provider.token_url, &[
+        ("grant_type", "authorization_code"),
+        ("code", code.as_str()),
+        ("redirect_uri", redirect_uri.as_str()),
+        ("client_id", provider.client_id.as_str()),
+        ("code_verifier", verifier.as_str()),
+    ]).await?;
+    token_set(&response, None)
+}
+
+async fn connect_device_code(app: &AppHandle, provider: &OAuthProvider) -> Result<TokenSet, String> {
+    let device_url = provider.device_authorization_url.as_deref()
+        .ok_or_else(|| format!("{} has no device authorization URL", provider.name))?;
+    let scope = provider.scopes.join(" ");
+    let device = post_form(app, device_url, &[("client_id", provider.client_id.as_str()), ("scope", scope.as_str())]).await?;
+    if let Some(error) = oauth_error(&device) {
+        return Err(format!("Device authorization failed ({})", error));
+    }
+    let device_code = device["device_code"].as_str().ok_or_else(|| "Response had no device_code".to_string())?;
+    // Some providers still send the draft spec's verification_url
+    let verification_uri = device["verification_uri"].as_str().or(device["verification_url"].as_str()).unwrap_or_default();
+    let complete_uri = device["verification_uri_complete"].as_str();
+    let expires_in = device["expires_in"].as_i64().unwrap_or(900);
+    let mut interval = device["interval"].as_u64().unwrap_or(5).max(1);
+
+    let _ = app.emit("oauth-device-code", json!({
+        "provider": provider.id,
+        "userCode": device["user_code"],
+        "verificationUri": verification_uri,
+        "verificationUriComplete": complete_uri,
+        "expiresIn": expires_in,
+    }));
+    open_browser(complete_uri.unwrap_or(verification_uri));
+
+    let deadline = now_secs() + expires_in;
+    while now_secs() < deadline {
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+        let response = post_form(app, &provider.token_url, &[
+            ("grant_type", DEVICE_GRANT),
+            ("device_code", device_code),
+            ("client_id", provider.client_id.as_str()),
+        ]).await?;
+        match response["error"].as_str() {
+            Some("authorization_pending") => continue,
+            Some("slow_down") => interval += 5,
+            _ => return token_set(&response, None),
+        }
+    }
+    Err("The device code expired before sign-in finished".to_string())
+}
+
+async fn refresh(app: &AppHandle, provider: &OAuthProvider, refresh_token: &str) -> Result<TokenSet, String> {
+    let response = post_form(app, &provider.token_url, &[
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token),
+        ("client_id", provider.client_id.as_str()),
+    ]).await?;
+    let tokens = token_set(&response, Some(refresh_token.to_string()))
+        .map_err(|e| format!("{} needs reconnecting: {}", provider.name, e))?;
+    store_tokens(app, &provider.id, &tokens).await?;
+    mark_connected(app, &provider.id, Some(&tokens))?;
+    info!(provider = %provider.id, "Refreshed OAuth token");
+    Ok(tokens)
+}
+
+/// A usable access token for `provider_id`, refreshed first when it's about to expire. For
+/// integrations to put in their Authorization header.
+pub async fn access_token(app: &AppHandle, provider_id: &str) -> Result<String, String> {
+    let provider = load_provider(app, provider_id)?;
+    let tokens = load_tokens(app, provider_id).await?
+        .ok_or_else(|| format!("{} is not connected", provider.name))?;
+    let expiring = tokens.expires_at.is_some_and(|at| at - REFRESH_MARGIN_SECS <= now_secs());
+    match (&tokens.refresh_token, expiring) {
+        (Some(refresh_token), true) => Ok(refresh(app, &provider, refresh_token).await?.access_token),
+        (None, true) => Err(format!("{}'s token has expired; reconnect it", provider.name)),
+        _ => Ok(tokens.access_token),
+    }
+}
+
+// Tauri Commands
+/// Add or update a provider's client registration. Changing it keeps any stored token.
+#[tauri::command]
+pub fn save_oauth_provider(app: AppHandle, provider: OAuthProviderInput) -> Result<OAuthProvider, String> {
+    let id = provider.id.trim().to_lowercase();
+    if id.is_empty() || provider.client_id.trim().is_empty() || provider.token_url.trim().is_empty() {
+        return Err("A provider needs an id, a client id and a token URL".to_string());
+    }
+    match provider.flow {
+        OAuthFlow::Pkce if provider.authorization_url.as_deref().is_none_or(|url| url.trim().is_empty()) => {
+            return Err("PKCE needs an authorization URL".to_string());
+        }
+        OAuthFlow::DeviceCode if provider.device_authorization_url.as_deref().is_none_or(|url| url.trim().is_empty()) => {
+            return Err("The device-code flow needs a device authorization URL".to_string());
+        }
+        _ => {}
+    }
+    let scopes = serde_json::to_string(&provider.scopes).map_err(|e| e.to_string())?;
+    let name = if provider.name.trim().is_empty() { id.clone() } else { provider.name.trim().to_string() };
+    let now = now_secs();
+    let conn = db::open_app_db(&app)?;
+    conn.execute(
+        "INSERT INTO oauth_providers (id, name, flow, client_id, authorization_url, token_url, device_authorization_url,
+             scopes, redirect_port, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?10)
+         ON CONFLICT(id) DO UPDATE SET name = ?2, flow = ?3, client_id = ?4, authorization_url = ?5, token_url = ?6,
+             device_authorization_url = ?7, scopes = ?8, redirect_port = ?9, updated_at = ?10",
+        params![
+            id, name, provider.flow.as_str(), provider.client_id.trim(), provider.authorization_url, provider.token_url.trim(),
+            provider.device_authorization_url, scopes, provider.redirect_port, now,
+        ],
+    ).map_err(|e| e.to_string())?;
+    load_provider(&app, &id)
+}
+
+#[tauri::command]
+pub fn list_oauth_providers(app: AppHandle) -> Result<Vec<OAuthProvider>, String> {
+    let conn = db::open_app_db(&app)?;
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM oauth_providers ORDER BY name", COLUMNS))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], provider_from_row).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Remove the provider and its stored token.
+#[tauri::command]
+pub async fn delete_oauth_provider(app: AppHandle, provider: String) -> Result<(), String> {
+    keychain::delete(&app, &keychain_account(&provider)).await?;
+    let conn = db::open_app_db(&app)?;
+    conn.execute("DELETE FROM oauth_providers WHERE id = ?1", params![provider]).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Sign in to `provider` with its configured flow and keep the tokens in the OS keychain. PKCE
+/// opens the browser (also sent as "oauth-authorize"); the device-code flow sends the code to
+/// enter as "oauth-device-code". Resolves once the user has finished or it times out.
+#[tauri::command]
+pub async fn connect_provider(app: AppHandle, provider: String) -> Result<OAuthProvider, String> {
+    let provider = load_provider(&app, &provider)?;
+    let tokens = match provider.flow {
+        OAuthFlow::Pkce => connect_pkce(&app, &provider).await?,
+        OAuthFlow::DeviceCode => connect_device_code(&app, &provider).await?,
+    };
+    store_tokens(&app, &provider.id, &tokens).await?;
+    mark_connected(&app, &provider.id, Some(&tokens))?;
+    info!(provider = %provider.id, flow = provider.flow.as_str(), "Connected OAuth provider");
+    let provider = load_provider(&app, &provider.id)?;
+    let _ = app.emit("oauth-provider-connected", &provider);
+    Ok(provider)
+}
+
+#[tauri::command]
+pub async fn disconnect_provider(app: AppHandle, provider: String) -> Result<OAuthProvider, String> {
+    load_provider(&app, &provider)?;
+    keychain::delete(&app, &keychain_account(&provider)).await?;
+    mark_connected(&app, &provider, None)?;
+    load_provider(&app, &provider)
+}
+
+/// Refresh the stored token now rather than on next use.
+#[tauri::command]
+pub async fn refresh_provider_token(app: AppHandle, provider: String) -> Result<OAuthProvider, String> {
+    let record = load_provider(&app, &provider)?;
+    let refresh_token = load_tokens(&app, &provider).await?
+        .and_then(|tokens| tokens.refresh_token)
+        .ok_or_else(|| format!("{} has no refresh token; reconnect it", record.name))?;
+    refresh(&app, &record, &refresh_token).await?;
+    load_provider(&app, &provider)
+}
+
+/// A current access token for `provider`, refreshed if needed, for integrations that call the
+/// provider from the frontend or the Python side.
+#[tauri::command]
+pub async fn get_provider_token(app: AppHandle, provider: String) -> Result<String, String> {
+    access_token(&app, &provider).await
+}
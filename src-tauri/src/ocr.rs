@@ -0,0 +1,130 @@
+// OCR - Tesseract over each page of a scanned PDF, producing a searchable copy for the parser
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::python_bridge::{ProgressUpdate, PDF_DECRYPTION_FAILED, PDF_PASSWORD_REQUIRED};
+
+// Tesseract's accuracy drops sharply below ~300 DPI on small annual-report fonts
+const RENDER_DPI: &str = "300";
+
+/// Pages OCR'd into a temporary directory; removed when dropped.
+pub struct OcrDocument {
+    dir: PathBuf,
+    pub path: PathBuf,
+}
+
+impl Drop for OcrDocument {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// A bundled binary under `<resources>/bin` wins over one on PATH.
+pub fn find_tool(app: &AppHandle, name: &str) -> Option<PathBuf> {
+    let file = if cfg!(windows) { format!("{}.exe", name) } else { name.to_string() };
+    if let Ok(resources) = app.path().resource_dir() {
+        let bundled = resources.join("bin").join(&file);
+        if bundled.exists() {
+            return Some(bundled);
+        }
+    }
+    Command::new(name)
+        .arg("-v")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok()
+        .then(|| PathBuf::from(name))
+}
+
+pub fn run(tool: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(tool)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {}", tool.display(), e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            tool.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Poppler's `-upw` arguments for an encrypted PDF, empty otherwise.
+pub fn password_args(password: Option<&str>) -> Vec<&str> {
+    password.map(|p| vec!["-upw", p]).unwrap_or_default()
+}
+
+pub fn page_count(pdfinfo: &Path, file_path: &str, password: Option<&str>) -> Result<i32, String> {
+    let mut args = password_args(password);
+    args.push(file_path);
+    let info = run(pdfinfo, &args).map_err(|e| {
+        if !e.contains("Incorrect password") {
+            e
+        } else if password.is_some() {
+            format!("{}: Incorrect PDF password", PDF_DECRYPTION_FAILED)
+        } else {
+            format!("{}: PDF is password protected", PDF_PASSWORD_REQUIRED)
+        }
+    })?;
+    info.lines()
+        .find_map(|line| line.strip_prefix("Pages:").and_then(|n| n.trim().parse().ok()))
+        .ok_or_else(|| "Could not read the PDF page count".to_string())
+}
+
+/// OCR every page of `file_path`, emitting `pdf-progress` per page with its text,
+/// and merge the results into one searchable PDF.
+pub fn ocr_pdf(app: &AppHandle, file_path: &str, language: &str, password: Option<&str>) -> Result<OcrDocument, String> {
+    let missing = |tool: &str| format!("OCR needs {} (install Tesseract and poppler-utils)", tool);
+    let tesseract = find_tool(app, "tesseract").ok_or_else(|| missing("tesseract"))?;
+    let pdftoppm = find_tool(app, "pdftoppm").ok_or_else(|| missing("pdftoppm"))?;
+    let pdfinfo = find_tool(app, "pdfinfo").ok_or_else(|| missing("pdfinfo"))?;
+    let pdfunite = find_tool(app, "pdfunite").ok_or_else(|| missing("pdfunite"))?;
+
+    let total_pages = page_count(&pdfinfo, file_path, password)?;
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+    let dir = std::env::temp_dir().join(format!("fincalc-ocr-{}-{}", std::process::id(), stamp));
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create OCR workspace: {}", e))?;
+    // Owns the directory from here so early returns clean up
+    let document = OcrDocument { path: dir.join("ocr.pdf"), dir };
+
+    eprintln!("[OCR] Recognising {} pages of {} ({})", total_pages, file_path, language);
+    let mut page_pdfs = Vec::new();
+    for page in 1..=total_pages {
+        let page_str = page.to_string();
+        let base = document.dir.join(format!("page-{:04}", page));
+        let base_str = base.to_string_lossy().to_string();
+
+        let mut args = vec!["-r", RENDER_DPI, "-f", &page_str, "-l", &page_str, "-png", "-singlefile"];
+        args.extend(password_args(password));
+        args.extend([file_path, base_str.as_str()]);
+        run(&pdftoppm, &args)?;
+        let image = format!("{}.png", base_str);
+        run(&tesseract, &[&image, &base_str, "-l", language, "pdf", "txt"])?;
+        let _ = std::fs::remove_file(&image);
+
+        let text = std::fs::read_to_string(format!("{}.txt", base_str)).unwrap_or_default();
+        let _ = app.emit("pdf-progress", ProgressUpdate {
+            status: "progress".to_string(),
+            current_page: page,
+            total_pages,
+            percentage: page * 100 / total_pages.max(1),
+            message: format!("OCR page {}/{}", page, total_pages),
+            partial_items: None,
+            partial_text: Some(text),
+        });
+        page_pdfs.push(format!("{}.pdf", base_str));
+    }
+
+    let output = document.path.to_string_lossy().to_string();
+    let mut args: Vec<&str> = page_pdfs.iter().map(String::as_str).collect();
+    args.push(&output);
+    run(&pdfunite, &args)?;
+
+    eprintln!("[OCR] Searchable copy written to {}", output);
+    Ok(document)
+}
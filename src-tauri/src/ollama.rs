@@ -1,13 +1,27 @@
-use tauri::{AppHandle, Emitter, Runtime};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
 use serde::{Deserialize, Serialize};
 use reqwest::Client;
 use std::collections::HashMap;
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
 use futures_util::StreamExt;
+use tracing::{debug, info, warn};
 
-use crate::settings::SettingsStore;
+use crate::chat_references::{self, ChatReferences};
+use crate::chat_sessions;
+use crate::error::AppError;
+use crate::http;
+use crate::profiles::ProfileManager;
+use crate::prompt_pipeline;
+use crate::settings::{LLMSettings, SettingsState};
+use crate::tasks::{self, TaskHandle, TaskKind};
+use crate::timeouts::{self, Operation};
+use crate::tray;
+use crate::usage::{self, UsageKind};
+use crate::windows;
 
-fn get_base_url(state: &tauri::State<'_, std::sync::Mutex<SettingsStore>>) -> String {
-    let store = state.lock().unwrap();
+pub async fn get_base_url(state: &SettingsState) -> Result<String, AppError> {
+    let store = state.read().await;
     let settings = store.get();
     let mut host = settings.llm.ollama_host.trim().to_string();
     
@@ -16,8 +30,14 @@ fn get_base_url(state: &tauri::State<'_, std::sync::Mutex<SettingsStore>>) -> St
     if host.is_empty() || host.to_lowercase() == "localhost" {
         host = "127.0.0.1".to_string();
     }
+
+    // A local Ollama keeps working offline; remote/cloud hosts don't
+    let is_local = host == "127.0.0.1" || host == "::1" || host == "[::1]";
+    if settings.offline_mode && !is_local {
+        return Err(AppError::Offline);
+    }
     
-    format!("http://{}:{}", host, settings.llm.ollama_port)
+    Ok(format!("http://{}:{}", host, settings.llm.ollama_port))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,114 +62,222 @@ pub struct ChatRequest {
     pub num_predict: Option<i32>,
     pub repeat_penalty: Option<f32>,
     pub format: Option<String>,
+    #[serde(default)]
+    pub stop: Option<Vec<String>>,
+    #[serde(default)]
+    pub min_p: Option<f32>,
+    #[serde(default)]
+    pub mirostat: Option<u8>,
+    /// Items, documents and pages to resolve into context; never sent to Ollama
+    #[serde(default, skip_serializing)]
+    pub references: Option<ChatReferences>,
+}
+
+impl ChatRequest {
+    /// The /api/chat body: sampling parameters go in `options`, each falling back to the
+    /// configured default when the request leaves it out.
+    fn to_ollama(&self, llm: &LLMSettings) -> serde_json::Value {
+        let mut options = serde_json::Map::new();
+        let mut set = |key: &str, value: serde_json::Value| {
+            if !value.is_null() {
+                options.insert(key.to_string(), value);
+            }
+        };
+        set("num_ctx", self.num_ctx.unwrap_or(llm.context_window).into());
+        set("temperature", self.temperature.unwrap_or(llm.temperature).into());
+        set("top_p", self.top_p.unwrap_or(llm.top_p).into());
+        set("top_k", self.top_k.unwrap_or(llm.top_k).into());
+        set("repeat_penalty", self.repeat_penalty.unwrap_or(llm.repeat_penalty).into());
+        set("seed", self.seed.or(llm.seed).into());
+        set("num_predict", self.num_predict.or(llm.num_predict).into());
+        set("stop", serde_json::json!(self.stop.as_ref().or(llm.stop.as_ref())));
+        set("min_p", self.min_p.or(llm.min_p).into());
+        set("mirostat", self.mirostat.or(llm.mirostat).into());
+        set("num_gpu", llm.num_gpu.into());
+
+        let mut messages = self.messages.clone();
+        if let Some(system) = self.system.as_ref().filter(|_| messages.first().is_none_or(|m| m.role != "system")) {
+            messages.insert(0, ChatMessage { role: "system".to_string(), content: system.clone(), images: None });
+        }
+        let mut body = serde_json::json!({
+            "model": self.model.as_deref().unwrap_or(&llm.selected_model),
+            "messages": messages,
+            "stream": self.stream,
+            "keep_alive": llm.keep_alive,
+            "options": options,
+        });
+        if let Some(format) = self.format.as_ref().or(llm.format.as_ref()) {
+            body["format"] = format.as_str().into();
+        }
+        body
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PullRequest {
     pub model: String,
     pub insecure: bool,
+    pub stream: bool,
 }
 
 pub struct OllamaBridge {
-    // Track if service is running? 
-    // For now we just use HTTP checks
+    // `ollama serve` started by us; an Ollama the user runs themselves is left alone
+    server: Mutex<Option<Child>>,
 }
 
 impl OllamaBridge {
     pub fn new() -> Self {
-        Self {}
+        Self { server: Mutex::new(None) }
     }
 
-    pub async fn start<R: Runtime>(&self, _app: &AppHandle<R>) -> Result<(), String> {
-        // Direct Ollama connection doesn't strictly need a bridge start,
-        // but we keep the method for main.rs compatibility.
-        // If we want to auto-start Ollama itself, we'd add it here.
+    /// Make sure Ollama answers, launching a local `ollama serve` if nothing is listening.
+    pub async fn start<R: Runtime>(&self, app: &AppHandle<R>) -> Result<(), AppError> {
+        let settings = app.state::<SettingsState>();
+        let bridge_url = get_base_url(&settings).await?;
+        if http::client(app).get(&bridge_url).send().await.is_ok() {
+            return Ok(());
+        }
+        if !bridge_url.contains("127.0.0.1") && !bridge_url.contains("[::1]") {
+            return Err(AppError::OllamaUnavailable(format!("Ollama at {} is not reachable and can only be started locally", bridge_url)));
+        }
+
+        let mut server = self.server.lock().map_err(|e| AppError::Internal(e.to_string()))?;
+        if server.as_mut().is_some_and(|child| matches!(child.try_wait(), Ok(None))) {
+            return Ok(());
+        }
+        let child = Command::new("ollama")
+            .arg("serve")
+            .env("OLLAMA_HOST", bridge_url.trim_start_matches("http://"))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| AppError::OllamaUnavailable(format!("Failed to start Ollama: {}", e)))?;
+        info!("Started ollama serve (pid {})", child.id());
+        *server = Some(child);
         Ok(())
     }
+
+    /// Stop the server we launched; returns false if there was none.
+    pub fn stop(&self) -> bool {
+        let Ok(mut server) = self.server.lock() else { return false };
+        match server.take() {
+            Some(mut child) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                info!("Stopped ollama serve");
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn owns_server(&self) -> bool {
+        self.server
+            .lock()
+            .map(|mut server| server.as_mut().is_some_and(|child| matches!(child.try_wait(), Ok(None))))
+            .unwrap_or(false)
+    }
 }
 
 // --- Commands ---
 
 #[tauri::command]
-pub async fn start_ollama_bridge<R: Runtime>(_app: AppHandle<R>, state: tauri::State<'_, OllamaBridge>) -> Result<String, String> {
-    state.start(&_app).await?;
+pub async fn start_ollama_bridge(app: AppHandle, state: tauri::State<'_, OllamaBridge>) -> Result<String, AppError> {
+    state.start(&app).await?;
+    tray::refresh(&app);
     Ok("Bridge ready (Direct connection)".to_string())
 }
 
 #[tauri::command]
-pub async fn stop_ollama_bridge() -> Result<(), String> {
+pub async fn stop_ollama_bridge(app: AppHandle, state: tauri::State<'_, OllamaBridge>) -> Result<(), AppError> {
+    state.stop();
+    tray::refresh(&app);
     Ok(())
 }
 
 #[tauri::command]
-pub async fn get_ollama_status(state: tauri::State<'_, std::sync::Mutex<SettingsStore>>) -> Result<serde_json::Value, String> {
-    let client = Client::new();
-    let bridge_url = get_base_url(&state);
-    let res = client.get(&bridge_url)
-        .send()
-        .await
-        .map_err(|e| e.to_string())?;
+pub async fn get_ollama_status(app: AppHandle, state: tauri::State<'_, SettingsState>) -> Result<serde_json::Value, AppError> {
+    let client = http::client(&app);
+    let bridge_url = get_base_url(&state).await?;
+    let res = timeouts::run(&app, Operation::OllamaRequest, async {
+        client.get(&bridge_url).send().await.map_err(|e| {
+            warn!("Ollama unreachable at {}: {}", bridge_url, e);
+            AppError::OllamaUnavailable(e.to_string())
+        })
+    }).await?;
     
     if res.status().is_success() {
         Ok(serde_json::json!({ "status": "connected" }))
     } else {
-        Err("Ollama unreachable".to_string())
+        warn!("Ollama at {} answered {}", bridge_url, res.status());
+        Err(AppError::OllamaUnavailable("Ollama unreachable".to_string()))
     }
 }
 
 #[tauri::command]
 pub async fn generate_completion(
-    state: tauri::State<'_, std::sync::Mutex<SettingsStore>>,
+    app: AppHandle,
+    state: tauri::State<'_, SettingsState>,
     prompt: String, 
     model: String, 
     context: Vec<i32>
-) -> Result<String, String> {
-    let client = Client::new();
-    let bridge_url = get_base_url(&state);
-    let res = client.post(format!("{}/api/generate", bridge_url))
+) -> Result<String, AppError> {
+    let client = http::client(&app);
+    let bridge_url = get_base_url(&state).await?;
+    debug!(model = %model, prompt_chars = prompt.len(), "Generating completion");
+    let request = client.post(format!("{}/api/generate", bridge_url))
         .json(&serde_json::json!({
             "model": model,
             "prompt": prompt,
             "stream": false,
             "context": if context.is_empty() { None } else { Some(context) }
-        }))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?
-        .json::<serde_json::Value>()
-        .await
-        .map_err(|e| e.to_string())?;
+        }));
+    let res = timeouts::run(&app, Operation::OllamaGeneration, async {
+        request.send()
+            .await
+            .map_err(|e| AppError::OllamaUnavailable(e.to_string()))?
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))
+    }).await?;
 
     res.get("response")
        .and_then(|v| v.as_str())
        .map(|s| s.to_string())
-       .ok_or_else(|| "No response text in output".to_string())
+       .ok_or_else(|| {
+           warn!("Completion from {} had no response text", model);
+           AppError::Internal("No response text in output".to_string())
+       })
 }
 
 #[tauri::command]
-pub async fn list_ollama_models(state: tauri::State<'_, std::sync::Mutex<SettingsStore>>) -> Result<Vec<serde_json::Value>, String> {
-    list_ollama_models_detailed(state).await
+pub async fn list_ollama_models(app: AppHandle, state: tauri::State<'_, SettingsState>) -> Result<Vec<serde_json::Value>, AppError> {
+    list_ollama_models_detailed(app, state).await
 }
 
 #[tauri::command]
-pub async fn list_ollama_models_detailed(state: tauri::State<'_, std::sync::Mutex<SettingsStore>>) -> Result<Vec<serde_json::Value>, String> {
-    let client = reqwest::Client::new();
-    let bridge_url = get_base_url(&state);
+pub async fn list_ollama_models_detailed(app: AppHandle, state: tauri::State<'_, SettingsState>) -> Result<Vec<serde_json::Value>, AppError> {
+    let client = http::client(&app);
+    let bridge_url = get_base_url(&state).await?;
     
     // 1. Get all available models
-    let tags_res = client.get(format!("{}/api/tags", bridge_url))
-        .send()
-        .await
-        .map_err(|e| format!("Ollama not running: {}", e))?
-        .json::<serde_json::Value>()
-        .await
-        .map_err(|e| e.to_string())?;
+    let tags_res = timeouts::run(&app, Operation::OllamaRequest, async {
+        client.get(format!("{}/api/tags", bridge_url))
+            .send()
+            .await
+            .map_err(|e| {
+                warn!("Listing models failed, Ollama not running at {}: {}", bridge_url, e);
+                AppError::OllamaUnavailable(format!("Ollama not running: {}", e))
+            })?
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))
+    }).await?;
 
     // 2. Get currently loaded models (Ollama >= 0.1.34)
-    let ps_res = client.get(format!("{}/api/ps", bridge_url))
-        .send()
-        .await
-        .ok(); // Ignore errors if /api/ps fails (older Ollama)
+    let ps_res = timeouts::run(&app, Operation::OllamaRequest, async {
+        client.get(format!("{}/api/ps", bridge_url)).send().await.map_err(|e| AppError::OllamaUnavailable(e.to_string()))
+    }).await.ok(); // Ignore errors if /api/ps fails (older Ollama)
     
     let loaded_models: HashMap<String, serde_json::Value> = if let Some(resp) = ps_res {
         if resp.status().is_success() {
@@ -216,75 +344,141 @@ pub async fn list_ollama_models_detailed(state: tauri::State<'_, std::sync::Mute
     Ok(result)
 }
 
-#[tauri::command]
-pub async fn pull_model(
-    state: tauri::State<'_, std::sync::Mutex<SettingsStore>>,
-    model: String, 
-    insecure: bool
-) -> Result<serde_json::Value, String> {
-    let client = Client::new();
-    let bridge_url = get_base_url(&state);
-    let payload = PullRequest { model, insecure };
+/// Follow Ollama's NDJSON pull progress; returns the last status line.
+async fn stream_pull(client: &Client, bridge_url: &str, payload: &PullRequest, task: &TaskHandle) -> Result<serde_json::Value, String> {
     let res = client.post(format!("{}/api/pull", bridge_url))
-        .json(&payload)
+        .json(payload)
         .send()
         .await
-        .map_err(|e| e.to_string())?
-        .json::<serde_json::Value>()
-        .await
         .map_err(|e| e.to_string())?;
-    Ok(res)
+
+    let mut stream = res.bytes_stream();
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut last = serde_json::Value::Null;
+    let mut percent = 0.0;
+    while let Some(chunk) = stream.next().await {
+        // Dropping the response closes the connection, which stops the pull in Ollama
+        if task.is_cancelled() {
+            return Err("Model pull cancelled".to_string());
+        }
+        buffer.extend_from_slice(&chunk.map_err(|e| e.to_string())?);
+        while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            let Ok(value) = serde_json::from_slice::<serde_json::Value>(&line) else { continue };
+            if let Some(error) = value.get("error").and_then(|e| e.as_str()) {
+                return Err(error.to_string());
+            }
+            // Layers report bytes; manifest/verify steps keep the last known figure
+            if let (Some(completed), Some(total)) = (value["completed"].as_f64(), value["total"].as_f64()) {
+                if total > 0.0 {
+                    percent = completed / total * 100.0;
+                }
+            }
+            task.progress(percent, value["status"].as_str().unwrap_or(""));
+            last = value;
+        }
+    }
+    Ok(last)
+}
+
+#[tauri::command]
+pub async fn pull_model(
+    app: AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    model: String, 
+    insecure: bool
+) -> Result<serde_json::Value, AppError> {
+    // Pulling always goes out to the model registry
+    if state.read().await.get().offline_mode {
+        return Err(AppError::Offline);
+    }
+    let bridge_url = get_base_url(&state).await?;
+    info!("Pulling model {}", model);
+    let task = tasks::register(&app, TaskKind::ModelPull, &model);
+    let payload = PullRequest { model, insecure, stream: true };
+    let result = stream_pull(&http::client(&app), &bridge_url, &payload, &task).await;
+    if let Err(e) = &result {
+        warn!("Pull of {} stopped: {}", payload.model, e);
+    }
+    task.finish(&result);
+    result.map_err(AppError::from)
 }
 
 #[tauri::command]
 pub async fn delete_model(
-    state: tauri::State<'_, std::sync::Mutex<SettingsStore>>,
+    app: AppHandle,
+    state: tauri::State<'_, SettingsState>,
     model: String
-) -> Result<serde_json::Value, String> {
-    let client = Client::new();
-    let bridge_url = get_base_url(&state);
-    let res = client.post(format!("{}/api/delete", bridge_url))
-        .json(&serde_json::json!({ "name": model }))
-        .send()
-        .await
-        .map_err(|e| e.to_string())?
-        .json::<serde_json::Value>()
-        .await
-        .map_err(|e| e.to_string())?;
-    Ok(res)
+) -> Result<serde_json::Value, AppError> {
+    let client = http::client(&app);
+    let bridge_url = get_base_url(&state).await?;
+    info!("Deleting model {}", model);
+    timeouts::run(&app, Operation::OllamaRequest, async {
+        client.post(format!("{}/api/delete", bridge_url))
+            .json(&serde_json::json!({ "name": model }))
+            .send()
+            .await
+            .map_err(|e| AppError::OllamaUnavailable(e.to_string()))?
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))
+    }).await
 }
 
 #[tauri::command]
 pub async fn unload_model(
-    state: tauri::State<'_, std::sync::Mutex<SettingsStore>>,
+    app: AppHandle,
+    state: tauri::State<'_, SettingsState>,
     model: String
-) -> Result<(), String> {
-    let client = reqwest::Client::new();
-    let bridge_url = get_base_url(&state);
-    let _ = client.post(format!("{}/api/generate", bridge_url))
+) -> Result<(), AppError> {
+    let client = http::client(&app);
+    let bridge_url = get_base_url(&state).await?;
+    let request = client.post(format!("{}/api/generate", bridge_url))
         .json(&serde_json::json!({
             "model": model,
             "prompt": "",
             "stream": false,
             "keep_alive": 0
-        }))
-        .send()
-        .await;
+        }));
+    let unloaded = timeouts::run(&app, Operation::OllamaRequest, async {
+        request.send().await.map_err(|e| AppError::OllamaUnavailable(e.to_string()))
+    }).await;
+    match unloaded {
+        Ok(_) => debug!("Unloaded model {}", model),
+        Err(e) => warn!("Failed to unload model {}: {}", model, e),
+    }
     Ok(())
 }
 
 #[tauri::command]
 pub async fn chat(
-    state: tauri::State<'_, std::sync::Mutex<SettingsStore>>,
+    app: AppHandle,
+    state: tauri::State<'_, SettingsState>,
     request: ChatRequest
-) -> Result<serde_json::Value, String> {
-    let client = Client::new();
-    let bridge_url = get_base_url(&state);
+) -> Result<serde_json::Value, AppError> {
+    let request = chat_sessions::apply_config(&app, request)?;
+    let request = chat_sessions::compact_if_needed(&app, None, request).await?;
+    let grounded = prompt_pipeline::prepare(&app, chat_references::ground(&app, request.clone())?)?;
+    let reply = timeouts::run(&app, Operation::OllamaGeneration, send_chat(&app, &state, grounded));
+    let mut reply = usage::track(&app, UsageKind::Chat, reply).await?;
+    prompt_pipeline::finish(&app, &mut reply)?;
+    chat_sessions::record(&app, &request, &reply);
+    Ok(reply)
+}
+
+async fn send_chat(
+    app: &AppHandle,
+    state: &tauri::State<'_, SettingsState>,
+    request: ChatRequest
+) -> Result<serde_json::Value, AppError> {
+    let client = http::client(app);
+    let bridge_url = get_base_url(state).await?;
+    let llm = state.read().await.get().llm.clone();
     let res = client.post(format!("{}/api/chat", bridge_url))
-        .json(&request)
+        .json(&request.to_ollama(&llm))
         .send()
         .await
-        .map_err(|e| e.to_string())?
+        .map_err(|e| AppError::OllamaUnavailable(e.to_string()))?
         .json::<serde_json::Value>()
         .await
         .map_err(|e| e.to_string())?;
@@ -294,59 +488,193 @@ pub async fn chat(
 #[tauri::command]
 pub async fn chat_stream(
     app: AppHandle, 
-    state: tauri::State<'_, std::sync::Mutex<SettingsStore>>,
+    window: tauri::WebviewWindow,
+    state: tauri::State<'_, SettingsState>,
     request: ChatRequest
-) -> Result<(), String> {
-    let client = Client::new();
+) -> Result<(), AppError> {
+    let request = chat_sessions::apply_config(&app, request)?;
+    let request = chat_sessions::compact_if_needed(&app, Some(window.label()), request).await?;
+    let grounded = prompt_pipeline::prepare(&app, chat_references::ground(&app, request.clone())?)?;
+    let reply = timeouts::run(&app, Operation::OllamaGeneration, stream_chat(&app, window.label(), &state, grounded));
+    let mut reply = usage::track(&app, UsageKind::Chat, reply).await?;
+    // Tokens went out as they arrived; the processed reply follows as one event
+    if prompt_pipeline::finish(&app, &mut reply)? {
+        let _ = app.emit_to(window.label(), &windows::scoped_event(window.label(), "chat-stream-processed"), serde_json::json!({
+            "content": reply["message"]["content"],
+            "json": reply.get("json"),
+        }));
+    }
+    chat_sessions::record(&app, &request, &reply);
+    Ok(())
+}
+
+/// Splits streamed content into thinking and answer around `<think>…</think>`, whose tags can
+/// straddle chunk boundaries.
+#[derive(Default)]
+struct ThinkSplitter {
+    in_think: bool,
+    pending: String,
+}
+
+impl ThinkSplitter {
+    /// (thinking, answer) text that is certain so far
+    fn push(&mut self, text: &str) -> (String, String) {
+        self.pending.push_str(text);
+        let (mut thinking, mut answer) = (String::new(), String::new());
+        loop {
+            let tag = if self.in_think { "</think>" } else { "<think>" };
+            let out = if self.in_think { &mut thinking } else { &mut answer };
+            if let Some(pos) = self.pending.find(tag) {
+                out.extend(self.pending.drain(..pos));
+                self.pending.drain(..tag.len());
+                self.in_think = !self.in_think;
+                continue;
+            }
+            // Hold back what could be the start of the tag
+            let keep = (1..tag.len()).rev().find(|n| self.pending.ends_with(&tag[..*n])).unwrap_or(0);
+            out.extend(self.pending.drain(..self.pending.len() - keep));
+            return (thinking, answer);
+        }
+    }
+
+    /// Whatever was held back, once the stream ends
+    fn finish(&mut self) -> (String, String) {
+        let rest = std::mem::take(&mut self.pending);
+        if self.in_think { (rest, String::new()) } else { (String::new(), rest) }
+    }
+}
+
+/// Streams to the window that asked, under its own event names (see `windows::scoped_event`).
+/// Thinking from reasoning models (a `thinking` field or `<think>` blocks) is sent separately
+/// as `thinking`, or dropped when `llm.hide_thinking` is set, and never kept in the reply.
+/// Returns the final chunk (model, token counts) with the full reply as its message.
+async fn stream_chat(
+    app: &AppHandle,
+    window: &str,
+    state: &tauri::State<'_, SettingsState>,
+    request: ChatRequest
+) -> Result<serde_json::Value, AppError> {
+    let client = http::client(app);
     let mut req = request.clone();
     req.stream = true;
     
-    let bridge_url = get_base_url(&state);
+    let bridge_url = get_base_url(state).await?;
+    let llm = state.read().await.get().llm.clone();
+    let hide_thinking = llm.hide_thinking;
+    debug!(model = ?req.model, messages = req.messages.len(), "Starting chat stream");
+    // Labelled with the window so closing it stops the stream
+    let task = tasks::register(app, TaskKind::ChatStream, window);
     let res = client.post(format!("{}/api/chat", bridge_url))
-        .json(&req)
+        .json(&req.to_ollama(&llm))
         .send()
         .await
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| {
+            warn!("Chat stream request failed: {}", e);
+            AppError::OllamaUnavailable(e.to_string())
+        })?;
 
     let mut stream = res.bytes_stream();
+    let stream_event = windows::scoped_event(window, "chat-stream-event");
+    let error_event = windows::scoped_event(window, "chat-stream-error");
+    let mut reply = String::new();
+    let mut last = serde_json::Value::Null;
+    let mut splitter = ThinkSplitter::default();
     
     while let Some(item) = stream.next().await {
+        if task.is_cancelled() {
+            return Err(AppError::Cancelled("Chat stream cancelled".to_string()));
+        }
         match item {
             Ok(chunk) => {
                 let text = String::from_utf8_lossy(&chunk);
                 for line in text.lines() {
                     if let Ok(val) = serde_json::from_str::<serde_json::Value>(line) {
-                        let content = val.get("message")
+                        let done = val.get("done").and_then(|d| d.as_bool()).unwrap_or(false);
+                        let message = val.get("message");
+                        let mut thinking = message
+                            .and_then(|m| m.get("thinking"))
+                            .and_then(|t| t.as_str())
+                            .unwrap_or("")
+                            .to_string();
+                        let content = message
                             .and_then(|m| m.get("content"))
                             .and_then(|c| c.as_str())
-                            .map(|s| s.to_string());
+                            .map(|text| {
+                                let (mut think, mut answer) = splitter.push(text);
+                                if done {
+                                    let (rest_think, rest_answer) = splitter.finish();
+                                    think.push_str(&rest_think);
+                                    answer.push_str(&rest_answer);
+                                }
+                                thinking.push_str(&think);
+                                answer
+                            });
                         
-                        let done = val.get("done").and_then(|d| d.as_bool()).unwrap_or(false);
+                        if let Some(content) = &content {
+                            reply.push_str(content);
+                        }
+                        if done {
+                            last = val.clone();
+                        }
                         
+                        let thinking = Some(thinking).filter(|t| !hide_thinking && !t.is_empty());
                         let payload = serde_json::json!({
                             "content": content,
+                            "thinking": thinking,
                             "done": done
                         });
                         
-                        let _ = app.emit("chat-stream-event", &payload);
+                        let _ = app.emit_to(window, &stream_event, &payload);
                     }
                 }
             }
             Err(e) => {
-                 let _ = app.emit("chat-stream-error", &(e.to_string()));
+                 warn!("Chat stream interrupted: {}", e);
+                 let _ = app.emit_to(window, &error_event, &(e.to_string()));
             }
         }
     }
     
-    Ok(())
+    if !last.is_object() {
+        last = serde_json::json!({ "model": req.model });
+    }
+    last["message"] = serde_json::json!({ "role": "assistant", "content": reply });
+    task.finish(&Ok::<(), String>(()));
+    Ok(last)
+}
+
+pub fn chat_history_file(
+    profiles: &tauri::State<'_, std::sync::Mutex<ProfileManager>>,
+    session_id: &str,
+) -> Result<std::path::PathBuf, String> {
+    let manager = profiles.lock().map_err(|e| e.to_string())?;
+    let safe_id: String = session_id.chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+    Ok(manager.chat_history_dir().join(format!("{}.json", safe_id)))
 }
 
 #[tauri::command]
-pub async fn get_chat_history(_session_id: String) -> Result<Vec<serde_json::Value>, String> {
-    Ok(vec![])
+pub async fn get_chat_history(
+    profiles: tauri::State<'_, std::sync::Mutex<ProfileManager>>,
+    session_id: String
+) -> Result<Vec<serde_json::Value>, AppError> {
+    let path = chat_history_file(&profiles, &session_id)?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| AppError::Io(e.to_string()))?;
+    Ok(serde_json::from_str(&content).unwrap_or_default())
 }
 
 #[tauri::command]
-pub async fn clear_chat_history(_session_id: String) -> Result<(), String> {
+pub async fn clear_chat_history(
+    profiles: tauri::State<'_, std::sync::Mutex<ProfileManager>>,
+    session_id: String
+) -> Result<(), AppError> {
+    let path = chat_history_file(&profiles, &session_id)?;
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| AppError::Io(e.to_string()))?;
+    }
     Ok(())
 }
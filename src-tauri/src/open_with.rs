@@ -0,0 +1,78 @@
+// Open With - documents handed over by the OS (file associations, fincalc:// links, a second
+// launch) are queued through the job manager of the one running instance
+use std::path::Path;
+use tauri::{AppHandle, Emitter, RunEvent, Url};
+use tracing::{info, warn};
+
+use crate::jobs::{self, JobSource};
+use crate::tray;
+
+const URL_SCHEME: &str = "fincalc";
+
+/// `fincalc://analyze?path=/reports/annual-report.pdf`
+fn path_from_url(url: &Url) -> Result<String, String> {
+    if url.scheme() != URL_SCHEME {
+        return Err(format!("Unsupported link: {}", url));
+    }
+    if url.host_str() != Some("analyze") {
+        return Err(format!("Unknown action in link: {}", url));
+    }
+    url.query_pairs()
+        .find(|(key, _)| key == "path")
+        .map(|(_, value)| value.into_owned())
+        .filter(|path| !path.trim().is_empty())
+        .ok_or_else(|| format!("Link has no path: {}", url))
+}
+
+fn open(app: &AppHandle, path: &str, source: JobSource) {
+    match jobs::enqueue(app, path, source) {
+        Ok(job) => {
+            info!("Opened {} as job #{}", path, job.id);
+            tray::show_main_window(app);
+            let _ = app.emit("document-opened", &job);
+        }
+        Err(e) => {
+            warn!("Cannot open {}: {}", path, e);
+            let _ = app.emit("document-open-failed", serde_json::json!({ "path": path, "error": e }));
+        }
+    }
+}
+
+/// Links delivered by the deep-link plugin; other schemes and actions are logged and ignored.
+pub fn open_urls(app: &AppHandle, urls: Vec<Url>) {
+    for url in urls {
+        match path_from_url(&url) {
+            Ok(path) => open(app, &path, JobSource::DeepLink),
+            Err(e) => warn!("{}", e),
+        }
+    }
+}
+
+/// macOS delivers "Open With" files as a run event rather than launch arguments.
+pub fn handle_run_event(app: &AppHandle, event: RunEvent) {
+    #[cfg(target_os = "macos")]
+    if let RunEvent::Opened { urls } = event {
+        for url in urls.iter().filter(|url| url.scheme() == "file") {
+            match url.to_file_path() {
+                Ok(path) => open(app, &path.to_string_lossy(), JobSource::OpenWith),
+                Err(_) => warn!("Cannot open {}", url),
+            }
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = (app, event);
+}
+
+/// Launch arguments (argv[0] skipped): file paths, relative to `cwd`, become imports.
+/// fincalc:// links are left to the deep-link plugin.
+pub fn open_args(app: &AppHandle, args: &[String], cwd: &Path) {
+    for arg in args.iter().skip(1).filter(|arg| !arg.starts_with('-')) {
+        if Url::parse(arg).is_ok_and(|url| url.scheme() == URL_SCHEME) {
+            continue;
+        }
+        let path = cwd.join(arg);
+        if path.is_file() {
+            open(app, &path.to_string_lossy(), JobSource::OpenWith);
+        }
+    }
+}
@@ -0,0 +1,272 @@
+// Page ranges - splits a large PDF into contiguous page ranges so several parser processes can
+// work on it at once, and merges their results back in page order
+use lopdf::Document;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// One slice of the document: 1-based inclusive pages, the PDF holding just those pages and a
+/// scratch DB for the worker, since python/api.py wipes the DB it is pointed at.
+pub struct PageRange {
+    pub first: u32,
+    pub last: u32,
+    pub path: PathBuf,
+    pub db_path: PathBuf,
+}
+
+impl PageRange {
+    pub fn pages(&self) -> u32 {
+        self.last - self.first + 1
+    }
+}
+
+/// Range PDFs in a temporary directory; removed when dropped.
+pub struct SplitDocument {
+    dir: PathBuf,
+    pub total_pages: u32,
+    pub ranges: Vec<PageRange>,
+}
+
+impl Drop for SplitDocument {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Worker count: one per core, capped by `max_workers` when that is set.
+pub fn worker_count(max_workers: usize) -> usize {
+    let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+    if max_workers == 0 { cores } else { cores.min(max_workers) }
+}
+
+/// Split `file_path` into `workers` ranges when it has more than `threshold` pages. None when the
+/// document is small, encrypted or unreadable by lopdf; the caller then parses it whole.
+pub fn split(file_path: &str, threshold: u32, workers: usize) -> Result<Option<SplitDocument>, String> {
+    if threshold == 0 || workers < 2 {
+        return Ok(None);
+    }
+    let Ok(doc) = Document::load(file_path) else { return Ok(None) };
+    if doc.is_encrypted() || doc.was_encrypted() {
+        return Ok(None);
+    }
+    let total_pages = doc.get_pages().len() as u32;
+    if total_pages <= threshold {
+        return Ok(None);
+    }
+    let per_range = total_pages.div_ceil(workers as u32);
+    let spans = (1..=total_pages).step_by(per_range as usize)
+        .map(|first| (first, (first + per_range - 1).min(total_pages)))
+        .collect();
+    write_ranges(&doc, total_pages, spans).map(Some)
+}
+
+/// Ranges covering the pages of `file_path` not in `done`, each gap cut so the missing pages
+/// are shared out over `workers`.
+pub fn split_missing(file_path: &str, done: &HashSet<u32>, workers: usize) -> Result<SplitDocument, String> {
+    let doc = Document::load(file_path).map_err(|e| format!("Failed to open PDF: {}", e))?;
+    if doc.is_encrypted() || doc.was_encrypted() {
+        return Err("Encrypted PDFs can't be resumed; analyze the file again".to_string());
+    }
+    let total_pages = doc.get_pages().len() as u32;
+    let missing: Vec<u32> = (1..=total_pages).filter(|page| !done.contains(page)).collect();
+    write_ranges(&doc, total_pages, spans(&missing, workers))
+}
+
+/// Ranges covering just `pages` of `file_path`, e.g. to extract a few pages again.
+pub fn split_pages(file_path: &str, pages: &[u32], workers: usize) -> Result<SplitDocument, String> {
+    let doc = Document::load(file_path).map_err(|e| format!("Failed to open PDF: {}", e))?;
+    if doc.is_encrypted() || doc.was_encrypted() {
+        return Err("Pages of encrypted PDFs can't be extracted on their own; analyze the file again".to_string());
+    }
+    let total_pages = doc.get_pages().len() as u32;
+    if let Some(page) = pages.iter().find(|page| **page == 0 || **page > total_pages) {
+        return Err(format!("Page {} is outside the document's {} pages", page, total_pages));
+    }
+    let mut pages = pages.to_vec();
+    pages.sort_unstable();
+    pages.dedup();
+    write_ranges(&doc, total_pages, spans(&pages, workers))
+}
+
+/// Runs of consecutive pages from sorted `pages`, cut so they're shared out over `workers`.
+fn spans(pages: &[u32], workers: usize) -> Vec<(u32, u32)> {
+    let per_range = (pages.len() as u32).div_ceil(workers.max(1) as u32).max(1);
+    let mut spans: Vec<(u32, u32)> = Vec::new();
+    for &page in pages {
+        match spans.last_mut() {
+            Some((first, last)) if *last + 1 == page && page - *first < per_range => *last = page,
+            _ => spans.push((page, page)),
+        }
+    }
+    spans
+}
+
+/// Write one PDF per (first, last) span into a fresh temporary directory.
+fn write_ranges(doc: &Document, total_pages: u32, spans: Vec<(u32, u32)>) -> Result<SplitDocument, String> {
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+    let dir = std::env::temp_dir().join(format!("fincalc-ranges-{}-{}", std::process::id(), stamp));
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create page range workspace: {}", e))?;
+    // Owns the directory from here so early returns clean up
+    let mut split = SplitDocument { dir, total_pages, ranges: Vec::new() };
+
+    for (first, last) in spans {
+        let outside: Vec<u32> = (1..=total_pages).filter(|page| *page < first || *page > last).collect();
+        let mut part = doc.clone();
+        part.delete_pages(&outside);
+        part.prune_objects();
+        let path = split.dir.join(format!("pages-{:04}-{:04}.pdf", first, last));
+        part.save(&path).map_err(|e| format!("Failed to write pages {}-{}: {}", first, last, e))?;
+        let db_path = split.dir.join(format!("pages-{:04}-{:04}.db", first, last));
+        split.ranges.push(PageRange { first, last, path, db_path });
+    }
+    Ok(split)
+}
+
+/// Move a page reference from range-relative to document page numbers: numbers are shifted,
+/// and so is the first number in labels such as "Page 3".
+fn shift_page(value: &mut Value, offset: u32) {
+    match value {
+        Value::Number(page) => {
+            if let Some(page) = page.as_u64() {
+                *value = (page + offset as u64).into();
+            }
+        }
+        Value::String(label) => {
+            let Some(start) = label.find(|c: char| c.is_ascii_digit()) else { return };
+            let end = label[start..].find(|c: char| !c.is_ascii_digit()).map_or(label.len(), |i| start + i);
+            if let Ok(page) = label[start..end].parse::<u64>() {
+                *label = format!("{}{}{}", &label[..start], page + offset as u64, &label[end..]);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Shift an item's page references (`sourcePage`, `stream_page_num`) by `offset` pages.
+pub fn shift_item_pages(item: &mut Value, offset: u32) {
+    for key in ["sourcePage", "stream_page_num"] {
+        if let Some(page) = item.get_mut(key) {
+            shift_page(page, offset);
+        }
+    }
+    if let Some(page) = item.get_mut("sourceBox").and_then(|b| b.get_mut("page")) {
+        shift_page(page, offset);
+    }
+}
+
+/// The document page an item came from: the page of its `sourceBox` when it was located,
+/// `stream_page_num` when the parser streamed it, else the number in `sourcePage` ("Page 3" or 3).
+pub fn item_page(item: &Value) -> Option<u32> {
+    if let Some(page) = item["sourceBox"]["page"].as_u64() {
+        return Some(page as u32);
+    }
+    if let Some(page) = item.get("stream_page_num").and_then(Value::as_u64) {
+        return Some(page as u32);
+    }
+    match item.get("sourcePage")? {
+        Value::Number(page) => page.as_u64().map(|page| page as u32),
+        Value::String(label) => {
+            let digits: String = label.chars().skip_while(|c| !c.is_ascii_digit()).take_while(char::is_ascii_digit).collect();
+            digits.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Swap the items of `pages` in `existing` for `fresh` ones, keeping everything else where it
+/// was; each page's new items go where its old ones started. Kept items keep their ids and new
+/// ones that clash are renamed. Returns the merged items and how many were dropped.
+pub fn replace_pages(existing: Vec<Value>, fresh: Vec<Value>, pages: &HashSet<u32>) -> (Vec<Value>, usize) {
+    let mut taken: HashSet<String> = existing.iter()
+        .filter(|item| !item_page(item).is_some_and(|page| pages.contains(&page)))
+        .filter_map(|item| item["id"].as_str().map(str::to_string))
+        .collect();
+    let mut fresh_by_page: std::collections::BTreeMap<u32, Vec<Value>> = std::collections::BTreeMap::new();
+    for (index, mut item) in fresh.into_iter().enumerate() {
+        if let Some(id) = item["id"].as_str().map(str::to_string) {
+            let mut unique = id.clone();
+            let mut attempt = 0;
+            while !taken.insert(unique.clone()) {
+                attempt += 1;
+                unique = format!("r{}_{}_{}", index, attempt, id);
+            }
+            item["id"] = unique.into();
+        }
+        // Items with no page go after everything else
+        let page = item_page(&item).unwrap_or(u32::MAX);
+        fresh_by_page.entry(page).or_default().push(item);
+    }
+    let mut merged = Vec::new();
+    let mut removed = 0;
+    for item in existing {
+        let page = item_page(&item);
+        // New items for pages up to this one go first, so pages that had no items slot in
+        let due: Vec<u32> = fresh_by_page.keys().copied().take_while(|p| page.is_some_and(|page| *p <= page)).collect();
+        for p in due {
+            merged.extend(fresh_by_page.remove(&p).unwrap_or_default());
+        }
+        if page.is_some_and(|page| pages.contains(&page)) {
+            removed += 1;
+        } else {
+            merged.push(item);
+        }
+    }
+    merged.extend(fresh_by_page.into_values().flatten());
+    (merged, removed)
+}
+
+/// Parsers number their items per run, so ids repeat across ranges; later repeats get their
+/// position as a prefix.
+pub fn unique_ids(items: &mut [Value]) {
+    let mut seen = HashSet::new();
+    for (index, item) in items.iter_mut().enumerate() {
+        let Some(id) = item["id"].as_str().map(str::to_string) else { continue };
+        if !seen.insert(id.clone()) {
+            let unique = format!("p{}_{}", index, id);
+            seen.insert(unique.clone());
+            item["id"] = unique.into();
+        }
+    }
+}
+
+/// Concatenate the per-range `extractedData` in range order, with page references shifted to
+/// document pages and ids made unique.
+pub fn merge(ranges: &[PageRange], results: Vec<Value>, total_pages: u32) -> Value {
+    let mut items = Vec::new();
+    let mut texts = Vec::new();
+    let mut metadata = serde_json::Map::new();
+    for (range, mut data) in ranges.iter().zip(results) {
+        let offset = range.first - 1;
+        if metadata.is_empty() {
+            if let Some(first) = data["metadata"].as_object() {
+                metadata = first.clone();
+            }
+        }
+        if let Value::Array(range_items) = data["items"].take() {
+            for mut item in range_items {
+                shift_item_pages(&mut item, offset);
+                items.push(item);
+            }
+        }
+        if let Some(text) = data["text"].as_str().filter(|text| !text.is_empty()) {
+            texts.push(text.to_string());
+        }
+    }
+    unique_ids(&mut items);
+    metadata.insert("pageCount".to_string(), total_pages.into());
+    metadata.insert("analysisMode".to_string(), "parallel_ranges".into());
+    metadata.insert(
+        "pageRanges".to_string(),
+        ranges.iter().map(|range| serde_json::json!([range.first, range.last])).collect(),
+    );
+    serde_json::json!({
+        "items": items,
+        "text": texts.join("\n"),
+        "metadata": metadata,
+        "standalone": {},
+        "consolidated": {},
+        "validation": { "issues": [] },
+    })
+}
+
@@ -0,0 +1,59 @@
+// Page Render - one page of a PDF as a PNG, for showing the original statement next to its
+// extracted figures and for handing page images to vision models
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::ipc::Response;
+use tauri::AppHandle;
+
+use crate::ocr;
+
+// Sharp on screen without making a statement page several megabytes
+const DEFAULT_DPI: u32 = 150;
+const MIN_DPI: u32 = 36;
+const MAX_DPI: u32 = 600;
+
+/// Render `page` (1-based) of `file_path` with poppler's pdftoppm. Returns the PNG bytes.
+pub fn render_page(app: &AppHandle, file_path: &str, page: u32, dpi: u32, password: Option<&str>) -> Result<Vec<u8>, String> {
+    let missing = |tool: &str| format!("Page previews need {} (install poppler-utils)", tool);
+    let pdftoppm = ocr::find_tool(app, "pdftoppm").ok_or_else(|| missing("pdftoppm"))?;
+    let pdfinfo = ocr::find_tool(app, "pdfinfo").ok_or_else(|| missing("pdfinfo"))?;
+    if !std::path::Path::new(file_path).is_file() {
+        return Err(format!("File not found: {}", file_path));
+    }
+    let total_pages = ocr::page_count(&pdfinfo, file_path, password)?;
+    if page == 0 || page as i32 > total_pages {
+        return Err(format!("Page {} is out of range; the PDF has {} pages", page, total_pages));
+    }
+
+    let stamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+    let base = std::env::temp_dir().join(format!("fincalc-page-{}-{}", std::process::id(), stamp));
+    let base_str = base.to_string_lossy().to_string();
+    let (page_str, dpi_str) = (page.to_string(), dpi.clamp(MIN_DPI, MAX_DPI).to_string());
+    let mut args = vec!["-r", dpi_str.as_str(), "-f", &page_str, "-l", &page_str, "-png", "-singlefile"];
+    args.extend(ocr::password_args(password));
+    args.extend([file_path, base_str.as_str()]);
+    ocr::run(&pdftoppm, &args)?;
+
+    let image = format!("{}.png", base_str);
+    let png = std::fs::read(&image).map_err(|e| format!("Failed to read rendered page: {}", e));
+    let _ = std::fs::remove_file(&image);
+    png
+}
+
+// Tauri Commands
+/// The page as raw PNG bytes (an ArrayBuffer in the frontend). `dpi` defaults to 150 and is
+/// kept within 36-600.
+#[tauri::command]
+pub async fn render_pdf_page(
+    app: AppHandle,
+    file_path: String,
+    page: u32,
+    dpi: Option<u32>,
+    password: Option<String>,
+) -> Result<Response, String> {
+    let png = tauri::async_runtime::spawn_blocking(move || {
+        render_page(&app, &file_path, page, dpi.unwrap_or(DEFAULT_DPI), password.as_deref())
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    Ok(Response::new(png))
+}
@@ -0,0 +1,93 @@
+// Parse Cache - finished analysis results keyed by a blake3 hash of the input file
+use rusqlite::{params, OptionalExtension};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+use crate::db;
+use crate::python_bridge::PythonResponse;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS parse_cache (
+    cache_key TEXT PRIMARY KEY,    -- file hash + parse options
+    file_hash TEXT NOT NULL,
+    file_name TEXT,
+    response TEXT NOT NULL,        -- PythonResponse JSON
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_parse_cache_hash ON parse_cache(file_hash);
+";
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// blake3 of the file on disk, or of the inline base64 content when there's no file.
+pub fn file_hash(file_path: &str, content: Option<&str>) -> Result<String, String> {
+    let mut hasher = blake3::Hasher::new();
+    match content {
+        Some(content) => {
+            hasher.update(content.as_bytes());
+        }
+        None => {
+            let mut file = std::fs::File::open(file_path).map_err(|e| format!("Cannot read {}: {}", file_path, e))?;
+            std::io::copy(&mut file, &mut hasher).map_err(|e| format!("Failed to hash {}: {}", file_path, e))?;
+        }
+    }
+    Ok(hasher.finalize().to_hex().to_string())
+}
+
+/// Options and the user's label mappings change what the parser produces, so they're part of the
+/// key. The password only unlocks the file and must never be stored.
+pub fn cache_key(file_hash: &str, options: Option<&serde_json::Value>, mappings: &serde_json::Value) -> String {
+    let mut options = options.cloned().unwrap_or(serde_json::Value::Null);
+    if let Some(map) = options.as_object_mut() {
+        map.remove("password");
+        map.remove("no_cache");
+    }
+    let mut keyed = options.to_string();
+    // Without user mappings the key stays what it was before they existed
+    if mappings.as_array().is_some_and(|m| !m.is_empty()) {
+        keyed.push_str(&mappings.to_string());
+    }
+    format!("{}:{}", file_hash, blake3::hash(keyed.as_bytes()).to_hex())
+}
+
+pub fn lookup(app: &AppHandle, cache_key: &str) -> Option<PythonResponse> {
+    let conn = db::open_app_db(app).ok()?;
+    let response: Option<String> = conn.query_row(
+        "SELECT response FROM parse_cache WHERE cache_key = ?1",
+        params![cache_key],
+        |row| row.get(0),
+    ).optional().ok()?;
+    serde_json::from_str(&response?).ok()
+}
+
+/// Only successful parses are worth replaying.
+pub fn store(app: &AppHandle, cache_key: &str, file_hash: &str, file_name: Option<&str>, response: &PythonResponse) {
+    if response.status != "success" {
+        return;
+    }
+    let result = serde_json::to_string(response)
+        .map_err(|e| e.to_string())
+        .and_then(|json| {
+            let conn = db::open_app_db(app)?;
+            conn.execute(
+                "INSERT OR REPLACE INTO parse_cache (cache_key, file_hash, file_name, response, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![cache_key, file_hash, file_name, json, now_secs()],
+            ).map_err(|e| e.to_string())
+        });
+    if let Err(e) = result {
+        eprintln!("[ParseCache] Failed to store result: {}", e);
+    }
+}
+
+/// Forget results stored more than `max_age_secs` ago; returns how many went.
+pub fn prune(app: &AppHandle, max_age_secs: u64) -> Result<usize, String> {
+    let conn = db::open_app_db(app)?;
+    conn.execute("DELETE FROM parse_cache WHERE created_at < ?1", params![now_secs() - max_age_secs as i64])
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,277 @@
+// Partial results - items the parser streams are written to the app DB as they arrive, so a
+// crash or timeout part-way through a long report keeps the pages already done and a resumed
+// analysis only has to parse the rest
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use tracing::warn;
+
+use crate::db;
+use crate::page_ranges;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS partial_analyses (
+    document_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    file_path TEXT NOT NULL,
+    file_name TEXT,
+    options TEXT,                  -- parse options JSON, without the password
+    status TEXT NOT NULL,          -- running, failed or interrupted
+    error TEXT,
+    started_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS partial_pages (
+    document_id INTEGER NOT NULL,
+    page INTEGER NOT NULL,         -- every item of this page has been stored
+    PRIMARY KEY (document_id, page)
+);
+CREATE TABLE IF NOT EXISTS partial_items (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    document_id INTEGER NOT NULL,
+    page INTEGER NOT NULL,
+    item TEXT NOT NULL             -- frontend item JSON with document page numbers
+);
+CREATE INDEX IF NOT EXISTS idx_partial_items_document ON partial_items(document_id, page);
+";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartialAnalysis {
+    pub document_id: i64,
+    pub file_path: String,
+    pub file_name: Option<String>,
+    pub options: Option<serde_json::Value>,
+    pub status: String,
+    pub error: Option<String>,
+    pub pages_done: u32,
+    pub item_count: u32,
+    pub started_at: i64,
+    pub updated_at: i64,
+}
+
+const SELECT_PARTIAL: &str = "
+SELECT document_id, file_path, file_name, options, status, error, started_at, updated_at,
+       (SELECT COUNT(*) FROM partial_pages p WHERE p.document_id = a.document_id),
+       (SELECT COUNT(*) FROM partial_items i WHERE i.document_id = a.document_id)
+FROM partial_analyses a";
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn load(conn: &Connection, document_id: i64) -> Result<Option<PartialAnalysis>, String> {
+    conn.query_row(
+        &format!("{} WHERE document_id = ?1", SELECT_PARTIAL),
+        params![document_id],
+        row_to_partial,
+    ).optional().map_err(|e| e.to_string())
+}
+
+fn row_to_partial(row: &rusqlite::Row) -> rusqlite::Result<PartialAnalysis> {
+    let options: Option<String> = row.get(3)?;
+    Ok(PartialAnalysis {
+        document_id: row.get(0)?,
+        file_path: row.get(1)?,
+        file_name: row.get(2)?,
+        options: options.and_then(|o| serde_json::from_str(&o).ok()),
+        status: row.get(4)?,
+        error: row.get(5)?,
+        started_at: row.get(6)?,
+        updated_at: row.get(7)?,
+        pages_done: row.get(8)?,
+        item_count: row.get(9)?,
+    })
+}
+
+/// Runs left "running" by a previous launch were cut off by a crash or a quit.
+pub fn mark_interrupted(app: &AppHandle) {
+    let result = db::open_app_db(app).and_then(|conn| {
+        conn.execute("UPDATE partial_analyses SET status = 'interrupted' WHERE status = 'running'", [])
+            .map_err(|e| e.to_string())
+    });
+    if let Err(e) = result {
+        warn!("Failed to mark interrupted analyses: {}", e);
+    }
+}
+
+/// Writes one analysis's items as they stream in. Failures to write are logged, not returned:
+/// losing the safety net must not fail the analysis itself.
+pub struct Recorder {
+    pub document_id: i64,
+    conn: Mutex<Connection>,
+}
+
+impl Recorder {
+    /// Register a new analysis of `file_path` and announce its id with `partial-analysis-started`.
+    pub fn start(app: &AppHandle, file_path: &str, file_name: Option<&str>, options: Option<&serde_json::Value>) -> Result<Self, String> {
+        let conn = db::open_app_db(app)?;
+        let mut options = options.cloned();
+        if let Some(map) = options.as_mut().and_then(|o| o.as_object_mut()) {
+            map.remove("password");
+        }
+        let now = now_secs();
+        conn.execute(
+            "INSERT INTO partial_analyses (file_path, file_name, options, status, started_at, updated_at)
+             VALUES (?1, ?2, ?3, 'running', ?4, ?4)",
+            params![file_path, file_name, options.map(|o| o.to_string()), now],
+        ).map_err(|e| e.to_string())?;
+        let document_id = conn.last_insert_rowid();
+        let _ = app.emit("partial-analysis-started", serde_json::json!({ "documentId": document_id, "fileName": file_name }));
+        Ok(Self { document_id, conn: Mutex::new(conn) })
+    }
+
+    /// Take up an earlier run again. Items on pages that weren't finished are dropped, since
+    /// those pages are parsed again.
+    pub fn reopen(app: &AppHandle, document_id: i64) -> Result<(Self, PartialAnalysis), String> {
+        let conn = db::open_app_db(app)?;
+        let partial = load(&conn, document_id)?.ok_or_else(|| format!("No partial analysis {}", document_id))?;
+        if partial.status == "running" {
+            return Err(format!("Analysis {} is still running", document_id));
+        }
+        conn.execute(
+            "DELETE FROM partial_items WHERE document_id = ?1
+             AND page NOT IN (SELECT page FROM partial_pages WHERE document_id = ?1)",
+            params![document_id],
+        ).map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE partial_analyses SET status = 'running', error = NULL, updated_at = ?2 WHERE document_id = ?1",
+            params![document_id, now_secs()],
+        ).map_err(|e| e.to_string())?;
+        Ok((Self { document_id, conn: Mutex::new(conn) }, partial))
+    }
+
+    fn insert_items(&self, page: u32, items: &[String]) {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        for item in items {
+            let result = conn.execute(
+                "INSERT INTO partial_items (document_id, page, item) VALUES (?1, ?2, ?3)",
+                params![self.document_id, page, item],
+            );
+            if let Err(e) = result {
+                warn!(document_id = self.document_id, "Failed to store partial result: {}", e);
+                return;
+            }
+        }
+    }
+
+    fn mark_done(&self, first: u32, last: u32) {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        for page in first..=last {
+            let result = conn.execute(
+                "INSERT OR IGNORE INTO partial_pages (document_id, page) VALUES (?1, ?2)",
+                params![self.document_id, page],
+            );
+            if let Err(e) = result {
+                warn!(document_id = self.document_id, "Failed to mark page {} done: {}", page, e);
+                return;
+            }
+        }
+        let _ = conn.execute(
+            "UPDATE partial_analyses SET updated_at = ?2 WHERE document_id = ?1",
+            params![self.document_id, now_secs()],
+        );
+    }
+
+    /// A stream for a parser working on a file whose page 1 is document page `first_page`.
+    pub fn stream(&self, first_page: u32) -> PageStream<'_> {
+        PageStream { recorder: self, offset: first_page - 1, page: first_page, pending: Vec::new() }
+    }
+
+    pub fn done_pages(&self) -> Result<HashSet<u32>, String> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let mut stmt = conn.prepare("SELECT page FROM partial_pages WHERE document_id = ?1").map_err(|e| e.to_string())?;
+        let rows = stmt.query_map(params![self.document_id], |row| row.get::<_, u32>(0)).map_err(|e| e.to_string())?;
+        rows.collect::<Result<HashSet<_>, _>>().map_err(|e| e.to_string())
+    }
+
+    /// Every stored item, in page order and then arrival order.
+    pub fn items(&self) -> Result<Vec<serde_json::Value>, String> {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let mut stmt = conn.prepare("SELECT item FROM partial_items WHERE document_id = ?1 ORDER BY page, id").map_err(|e| e.to_string())?;
+        let rows = stmt.query_map(params![self.document_id], |row| row.get::<_, String>(0)).map_err(|e| e.to_string())?;
+        let items = rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?;
+        Ok(items.iter().filter_map(|item| serde_json::from_str(item).ok()).collect())
+    }
+
+    /// Keep what was stored so the run can be resumed.
+    pub fn fail(&self, error: &str) {
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let _ = conn.execute(
+            "UPDATE partial_analyses SET status = 'failed', error = ?2, updated_at = ?3 WHERE document_id = ?1",
+            params![self.document_id, error, now_secs()],
+        );
+    }
+
+    /// The full result has been stored elsewhere; the partials are no longer needed.
+    pub fn complete(self) {
+        let conn = self.conn.into_inner().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = discard(&conn, self.document_id) {
+            warn!(document_id = self.document_id, "Failed to clear partial results: {}", e);
+        }
+    }
+}
+
+/// Items from one parser process. Pages arrive in order, so reaching a later page means every
+/// page before it is done, including pages that produced no items.
+pub struct PageStream<'a> {
+    recorder: &'a Recorder,
+    offset: u32,
+    page: u32,
+    pending: Vec<String>,
+}
+
+impl PageStream<'_> {
+    /// An item as the parser sent it; its page comes from `stream_page_num`, else the current page.
+    pub fn item(&mut self, mut item: serde_json::Value) {
+        page_ranges::shift_item_pages(&mut item, self.offset);
+        let page = item.get("stream_page_num").and_then(|p| p.as_u64()).map_or(self.page, |p| p as u32);
+        if page > self.page {
+            self.flush();
+            self.recorder.mark_done(self.page, page - 1);
+            self.page = page;
+        }
+        self.pending.push(item.to_string());
+    }
+
+    fn flush(&mut self) {
+        let pending = std::mem::take(&mut self.pending);
+        self.recorder.insert_items(self.page, &pending);
+    }
+
+    /// The parser answered successfully, so everything through `last_page` is done.
+    pub fn finish(mut self, last_page: u32) {
+        self.flush();
+        self.recorder.mark_done(self.page, last_page);
+    }
+}
+
+fn discard(conn: &Connection, document_id: i64) -> Result<(), String> {
+    conn.execute("DELETE FROM partial_items WHERE document_id = ?1", params![document_id]).map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM partial_pages WHERE document_id = ?1", params![document_id]).map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM partial_analyses WHERE document_id = ?1", params![document_id]).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// Tauri Commands
+/// Analyses that stopped before finishing, newest first.
+#[tauri::command]
+pub fn list_partial_analyses(app: AppHandle) -> Result<Vec<PartialAnalysis>, String> {
+    let conn = db::open_app_db(&app)?;
+    let mut stmt = conn.prepare(
+        &format!("{} ORDER BY updated_at DESC", SELECT_PARTIAL),
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], row_to_partial).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn discard_partial_analysis(app: AppHandle, document_id: i64) -> Result<(), String> {
+    let conn = db::open_app_db(&app)?;
+    discard(&conn, document_id)
+}
@@ -0,0 +1,270 @@
+// Payroll - in-hand salary from a CTC: the components it splits into (basic, HRA, PF, gratuity,
+// special allowance), and income tax under both the old and the new regime (FY 2025-26 rules)
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_BASIC_PERCENT: f64 = 40.0;
+// HRA as a share of basic; also the exemption's cap, so 50% in the metros and 40% elsewhere
+const METRO_HRA_PERCENT: f64 = 50.0;
+const NON_METRO_HRA_PERCENT: f64 = 40.0;
+const PF_RATE: f64 = 0.12;
+// Statutory PF wage ceiling, ₹15,000 a month
+const PF_WAGE_CEILING: f64 = 180_000.0;
+const GRATUITY_RATE: f64 = 0.0481;
+// Maharashtra's yearly professional tax; most states charge at most this
+const DEFAULT_PROFESSIONAL_TAX: f64 = 2_500.0;
+const SECTION_80C_LIMIT: f64 = 150_000.0;
+const CESS_RATE: f64 = 0.04;
+
+const OLD_STANDARD_DEDUCTION: f64 = 50_000.0;
+const NEW_STANDARD_DEDUCTION: f64 = 75_000.0;
+// (upper bound of the slab, rate)
+const OLD_SLABS: &[(f64, f64)] = &[(250_000.0, 0.0), (500_000.0, 0.05), (1_000_000.0, 0.20), (f64::INFINITY, 0.30)];
+const NEW_SLABS: &[(f64, f64)] = &[
+    (400_000.0, 0.0), (800_000.0, 0.05), (1_200_000.0, 0.10), (1_600_000.0, 0.15),
+    (2_000_000.0, 0.20), (2_400_000.0, 0.25), (f64::INFINITY, 0.30),
+];
+// Section 87A: tax up to the cap is rebated while taxable income stays within the limit
+const OLD_REBATE: (f64, f64) = (500_000.0, 12_500.0);
+const NEW_REBATE: (f64, f64) = (1_200_000.0, 60_000.0);
+// (income above which it applies, rate); the new regime stops at 25%
+const OLD_SURCHARGE: &[(f64, f64)] = &[(5_000_000.0, 0.10), (10_000_000.0, 0.15), (20_000_000.0, 0.25), (50_000_000.0, 0.37)];
+const NEW_SURCHARGE: &[(f64, f64)] = &[(5_000_000.0, 0.10), (10_000_000.0, 0.15), (20_000_000.0, 0.25)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaxRegime {
+    Old,
+    New,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayrollInputs {
+    /// Yearly cost to company
+    pub ctc: f64,
+    /// Basic salary as a percent of CTC; default 40
+    pub basic_percent: Option<f64>,
+    /// HRA as a percent of basic; default 50 in a metro, 40 elsewhere
+    pub hra_percent: Option<f64>,
+    /// Yearly rent paid, for the HRA exemption
+    pub rent_paid: Option<f64>,
+    /// Living in Delhi, Mumbai, Kolkata or Chennai
+    pub metro: Option<bool>,
+    /// Whether the employer's PF share is part of the CTC; default true
+    pub employer_pf_in_ctc: Option<bool>,
+    /// PF on basic up to ₹15,000 a month rather than on all of it; default false
+    pub pf_wage_capped: Option<bool>,
+    /// Whether gratuity (4.81% of basic) is part of the CTC; default true
+    pub gratuity_in_ctc: Option<bool>,
+    /// Yearly performance pay included in the CTC; taxed, paid out once
+    pub variable_pay: Option<f64>,
+    /// Yearly; default ₹2,500
+    pub professional_tax: Option<f64>,
+    /// 80C investments beyond the employee's PF (ELSS, PPF, life cover, ...)
+    pub deductions_80c: Option<f64>,
+    /// Other old-regime deductions: 80D, 80CCD(1B), home loan interest, ...
+    pub other_deductions: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SalaryComponent {
+    pub name: String,
+    pub annual: f64,
+    pub monthly: f64,
+    /// Paid out in the month's salary, as opposed to PF, gratuity and once-a-year pay
+    pub in_monthly_pay: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegimeTax {
+    pub regime: TaxRegime,
+    /// Standard deduction, and under the old regime HRA, professional tax, 80C and the rest
+    pub deductions: f64,
+    pub taxable_income: f64,
+    /// Slab tax before the rebate
+    pub slab_tax: f64,
+    pub rebate: f64,
+    pub surcharge: f64,
+    pub cess: f64,
+    pub total_tax: f64,
+    /// Total tax over gross salary, in percent
+    pub effective_rate: f64,
+    /// Gross salary less employee PF, professional tax and income tax
+    pub in_hand_annual: f64,
+    /// In-hand without the variable pay, tax spread evenly over the year
+    pub in_hand_monthly: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PayrollBreakdown {
+    pub ctc: f64,
+    pub components: Vec<SalaryComponent>,
+    /// CTC less the employer's PF and gratuity
+    pub gross_salary: f64,
+    pub employee_pf: f64,
+    pub professional_tax: f64,
+    pub hra_exemption: f64,
+    pub old_regime: RegimeTax,
+    pub new_regime: RegimeTax,
+    /// The regime leaving more in hand; the new one on a tie, as it's the default
+    pub better_regime: TaxRegime,
+    pub annual_saving: f64,
+}
+
+fn component(name: &str, annual: f64, in_monthly_pay: bool) -> SalaryComponent {
+    SalaryComponent { name: name.to_string(), annual, monthly: annual / 12.0, in_monthly_pay }
+}
+
+fn slab_tax(income: f64, slabs: &[(f64, f64)]) -> f64 {
+    let mut lower = 0.0;
+    let mut tax = 0.0;
+    for &(upper, rate) in slabs {
+        if income <= lower {
+            break;
+        }
+        tax += (income.min(upper) - lower) * rate;
+        lower = upper;
+    }
+    tax
+}
+
+/// Tax before cess, with the 87A rebate and surcharge and the marginal relief on both: just past
+/// a threshold, the extra tax can't be more than the extra income.
+fn tax_before_cess(income: f64, regime: TaxRegime) -> (f64, f64, f64) {
+    let (slabs, (rebate_limit, rebate_cap), surcharges) = match regime {
+        TaxRegime::Old => (OLD_SLABS, OLD_REBATE, OLD_SURCHARGE),
+        TaxRegime::New => (NEW_SLABS, NEW_REBATE, NEW_SURCHARGE),
+    };
+    let slab = slab_tax(income, slabs);
+    let rebate = if income <= rebate_limit {
+        slab.min(rebate_cap)
+    } else if regime == TaxRegime::New {
+        slab.min(slab - (income - rebate_limit)).max(0.0)
+    } else {
+        0.0
+    };
+    let tax = slab - rebate;
+
+    let Some(position) = surcharges.iter().rposition(|(threshold, _)| income > *threshold) else {
+        return (slab, rebate, 0.0);
+    };
+    let (threshold, rate) = surcharges[position];
+    let below_rate = if position == 0 { 0.0 } else { surcharges[position - 1].1 };
+    let at_threshold = slab_tax(threshold, slabs) * (1.0 + below_rate);
+    let surcharge = (tax * rate).min((at_threshold + income - threshold - tax).max(0.0));
+    (slab, rebate, surcharge)
+}
+
+fn regime_tax(regime: TaxRegime, gross: f64, deductions: f64, take_home_before_tax: f64, variable_pay: f64) -> RegimeTax {
+    let taxable_income = (gross - deductions).max(0.0);
+    let (slab_tax, rebate, surcharge) = tax_before_cess(taxable_income, regime);
+    let cess = (slab_tax - rebate + surcharge) * CESS_RATE;
+    let total_tax = slab_tax - rebate + surcharge + cess;
+    let in_hand_annual = take_home_before_tax - total_tax;
+    RegimeTax {
+        regime,
+        deductions,
+        taxable_income,
+        slab_tax,
+        rebate,
+        surcharge,
+        cess,
+        total_tax,
+        effective_rate: if gross > 0.0 { total_tax / gross * 100.0 } else { 0.0 },
+        in_hand_annual,
+        in_hand_monthly: (in_hand_annual - variable_pay) / 12.0,
+    }
+}
+
+pub fn breakdown(inputs: &PayrollInputs) -> Result<PayrollBreakdown, String> {
+    let ctc = inputs.ctc;
+    if !ctc.is_finite() || ctc <= 0.0 {
+        return Err("CTC must be a positive amount".to_string());
+    }
+    let percent = |value: Option<f64>, default: f64, name: &str| -> Result<f64, String> {
+        let value = value.unwrap_or(default);
+        if !(0.0..=100.0).contains(&value) {
+            return Err(format!("{} must be between 0 and 100 percent", name));
+        }
+        Ok(value / 100.0)
+    };
+    let amount = |value: Option<f64>, name: &str| -> Result<f64, String> {
+        match value {
+            Some(v) if !v.is_finite() || v < 0.0 => Err(format!("{} must not be negative", name)),
+            v => Ok(v.unwrap_or(0.0)),
+        }
+    };
+    let metro = inputs.metro.unwrap_or(false);
+    let basic = ctc * percent(inputs.basic_percent, DEFAULT_BASIC_PERCENT, "Basic")?;
+    let hra_default = if metro { METRO_HRA_PERCENT } else { NON_METRO_HRA_PERCENT };
+    let hra = basic * percent(inputs.hra_percent, hra_default, "HRA")?;
+    let pf_wage = if inputs.pf_wage_capped.unwrap_or(false) { basic.min(PF_WAGE_CEILING) } else { basic };
+    let employee_pf = pf_wage * PF_RATE;
+    let employer_pf = if inputs.employer_pf_in_ctc.unwrap_or(true) { employee_pf } else { 0.0 };
+    let gratuity = if inputs.gratuity_in_ctc.unwrap_or(true) { basic * GRATUITY_RATE } else { 0.0 };
+    let variable_pay = amount(inputs.variable_pay, "Variable pay")?;
+    let professional_tax = amount(Some(inputs.professional_tax.unwrap_or(DEFAULT_PROFESSIONAL_TAX)), "Professional tax")?;
+    let rent_paid = amount(inputs.rent_paid, "Rent paid")?;
+    let deductions_80c = amount(inputs.deductions_80c, "80C deductions")?;
+    let other_deductions = amount(inputs.other_deductions, "Other deductions")?;
+
+    let special_allowance = ctc - basic - hra - employer_pf - gratuity - variable_pay;
+    if special_allowance < 0.0 {
+        return Err(format!(
+            "Basic, HRA, PF, gratuity and variable pay add up to more than the CTC by {:.0}",
+            -special_allowance
+        ));
+    }
+    let gross_salary = ctc - employer_pf - gratuity;
+
+    // Least of the HRA received, rent over 10% of basic, and 50% (40%) of basic
+    let hra_exemption = hra.min((rent_paid - 0.1 * basic).max(0.0)).min(basic * hra_default / 100.0);
+    let old_deductions = OLD_STANDARD_DEDUCTION
+        + hra_exemption
+        + professional_tax
+        + (employee_pf + deductions_80c).min(SECTION_80C_LIMIT)
+        + other_deductions;
+
+    let take_home_before_tax = gross_salary - employee_pf - professional_tax;
+    let old_regime = regime_tax(TaxRegime::Old, gross_salary, old_deductions, take_home_before_tax, variable_pay);
+    let new_regime = regime_tax(TaxRegime::New, gross_salary, NEW_STANDARD_DEDUCTION, take_home_before_tax, variable_pay);
+    let better_regime = if old_regime.total_tax < new_regime.total_tax { TaxRegime::Old } else { TaxRegime::New };
+
+    let mut components = vec![
+        component("Basic", basic, true),
+        component("HRA", hra, true),
+        component("Special allowance", special_allowance, true),
+    ];
+    if variable_pay > 0.0 {
+        components.push(component("Variable pay", variable_pay, false));
+    }
+    if employer_pf > 0.0 {
+        components.push(component("Employer PF", employer_pf, false));
+    }
+    if gratuity > 0.0 {
+        components.push(component("Gratuity", gratuity, false));
+    }
+
+    Ok(PayrollBreakdown {
+        ctc,
+        components,
+        gross_salary,
+        employee_pf,
+        professional_tax,
+        hra_exemption,
+        annual_saving: (old_regime.total_tax - new_regime.total_tax).abs(),
+        old_regime,
+        new_regime,
+        better_regime,
+    })
+}
+
+// Tauri Commands
+/// CTC split into its components, with tax and in-hand pay under both regimes side by side.
+#[tauri::command]
+pub fn calculate_salary_breakdown(inputs: PayrollInputs) -> Result<PayrollBreakdown, String> {
+    breakdown(&inputs)
+}
@@ -0,0 +1,583 @@
+// Periods - the fiscal period a document reports (FY2024, Q3-FY24) and whether it is standalone
+// or consolidated, detected or set by the user, so variances and trends compare like periods
+// rather than whatever sits in the current/previous year columns
+use chrono::{Months, NaiveDate};
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+use tracing::info;
+
+use crate::db;
+use crate::derived;
+use crate::duplicates;
+use crate::excel;
+use crate::statements::{self, StoredDocument};
+
+const PERIODS_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS document_periods (
+    doc_id INTEGER PRIMARY KEY,
+    current_period TEXT,
+    previous_period TEXT,
+    basis TEXT,
+    updated_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS item_periods (
+    doc_id INTEGER NOT NULL,
+    item_id TEXT NOT NULL,
+    current_period TEXT,
+    previous_period TEXT,
+    basis TEXT,
+    updated_at INTEGER NOT NULL,
+    PRIMARY KEY (doc_id, item_id)
+);";
+// "Year ended 31st March, 2024" and "Consolidated" headings sit on the first statement pages
+const HEADING_PAGES: u32 = 6;
+
+/// A fiscal year (April to March, named after the year it ends in) or one of its quarters.
+/// Serialized as its label, "FY2024" or "Q3-FY24".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct Period {
+    pub fiscal_year: i32,
+    /// 1 (April-June) to 4 (January-March); None for the full year
+    pub quarter: Option<u8>,
+}
+
+fn full_year(digits: &str) -> Option<i32> {
+    let year: i32 = digits.parse().ok()?;
+    let year = if digits.len() == 2 { 2000 + year } else { year };
+    (1990..=2099).contains(&year).then_some(year)
+}
+
+impl Period {
+    pub fn year(fiscal_year: i32) -> Self {
+        Period { fiscal_year, quarter: None }
+    }
+
+    /// The period ending in `month` of `year`: March closes the fiscal year, other months
+    /// close the quarter they fall in.
+    fn ending(year: i32, month: u32) -> Option<Self> {
+        let quarter = match month {
+            3 => None,
+            4..=6 => Some(1),
+            7..=9 => Some(2),
+            10..=12 => Some(3),
+            1 | 2 => Some(4),
+            _ => return None,
+        };
+        Some(Period { fiscal_year: if month <= 3 { year } else { year + 1 }, quarter })
+    }
+
+    /// First and last day of the period.
+    pub fn dates(self) -> (NaiveDate, NaiveDate) {
+        let (year, month, months) = match self.quarter {
+            None => (self.fiscal_year - 1, 4, 12),
+            Some(4) => (self.fiscal_year, 1, 3),
+            Some(quarter) => (self.fiscal_year - 1, 1 + quarter as u32 * 3, 3),
+        };
+        let start = NaiveDate::from_ymd_opt(year, month, 1).expect("valid month");
+        let end = start.checked_add_months(Months::new(months)).and_then(|date| date.pred_opt()).expect("date in range");
+        (start, end)
+    }
+
+    /// The same period a year earlier, what a year-on-year variance compares against.
+    pub fn prior(self) -> Self {
+        Period { fiscal_year: self.fiscal_year - 1, ..self }
+    }
+
+    /// A period as written: "FY2024", "FY 2023-24", "2024", "Q3-FY24", "Q3 FY2024",
+    /// "31.12.2023", "2024-03-31", "Mar-24", "31st March, 2024".
+    pub fn parse(text: &str) -> Option<Self> {
+        let lower = text.trim().to_lowercase();
+        let quarter = Regex::new(r"\bq([1-4])\s*[-/ ]?\s*(?:fy)?\s*'?(\d{4}|\d{2})(?:\s*[-/]\s*(\d{4}|\d{2}))?\b").expect("valid pattern");
+        if let Some(caps) = quarter.captures(&lower) {
+            // "Q3 2023-24" is named after the year it ends in, like a fiscal year
+            let year = full_year(caps.get(3).unwrap_or_else(|| caps.get(2).expect("year group")).as_str())?;
+            return Some(Period { fiscal_year: year, quarter: caps[1].parse().ok() });
+        }
+
+        let iso = Regex::new(r"\b(\d{4})-(\d{1,2})-\d{1,2}\b").expect("valid pattern");
+        if let Some(caps) = iso.captures(&lower) {
+            return Period::ending(full_year(&caps[1])?, caps[2].parse().ok()?);
+        }
+        let numeric = Regex::new(r"\b\d{1,2}[./-](\d{1,2})[./-](\d{4}|\d{2})\b").expect("valid pattern");
+        if let Some(caps) = numeric.captures(&lower) {
+            return Period::ending(full_year(&caps[2])?, caps[1].parse().ok()?);
+        }
+        let named = Regex::new(
+            r"\b(jan|feb|mar|apr|may|jun|jul|aug|sep|oct|nov|dec)[a-z]*\.?[\s,'-]*(?:\d{1,2}(?:st|nd|rd|th)?,?\s+)?(\d{4}|\d{2})\b"
+        ).expect("valid pattern");
+        if let Some(caps) = named.captures(&lower) {
+            const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+            let month = MONTHS.iter().position(|m| *m == &caps[1])? as u32 + 1;
+            return Period::ending(full_year(&caps[2])?, month);
+        }
+
+        excel::period_label(&lower).and_then(|year| year.parse().ok()).map(Period::year)
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.quarter {
+            Some(quarter) => write!(f, "Q{}-FY{:02}", quarter, self.fiscal_year.rem_euclid(100)),
+            None => write!(f, "FY{}", self.fiscal_year),
+        }
+    }
+}
+
+impl From<Period> for String {
+    fn from(period: Period) -> Self {
+        period.to_string()
+    }
+}
+
+impl TryFrom<String> for Period {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        Period::parse(&text).ok_or_else(|| format!("Unknown period: {}", text))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Basis {
+    Standalone,
+    Consolidated,
+}
+
+impl Basis {
+    pub fn name(self) -> &'static str {
+        match self {
+            Basis::Standalone => "standalone",
+            Basis::Consolidated => "consolidated",
+        }
+    }
+
+    /// Whichever of the two a text mentions first.
+    pub fn parse(text: &str) -> Option<Self> {
+        let lower = text.to_lowercase();
+        let standalone = ["standalone", "stand-alone", "stand alone"].iter().filter_map(|word| lower.find(word)).min();
+        match (standalone, lower.find("consolidated")) {
+            (Some(s), Some(c)) if c < s => Some(Basis::Consolidated),
+            (Some(_), _) => Some(Basis::Standalone),
+            (None, Some(_)) => Some(Basis::Consolidated),
+            (None, None) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeriodSource {
+    /// Set with `set_document_periods`
+    Override,
+    /// Found in the metadata, the items' year columns or the page text
+    Detected,
+    /// Nothing found; calculations use the current/previous year columns
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentPeriods {
+    /// The period the "current year" column holds
+    pub current: Option<Period>,
+    /// The period the "previous year" column holds
+    pub previous: Option<Period>,
+    pub basis: Option<Basis>,
+    pub source: PeriodSource,
+}
+
+/// A correction for one item; fields left None follow the document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemPeriods {
+    pub item_id: String,
+    pub current: Option<Period>,
+    pub previous: Option<Period>,
+    pub basis: Option<Basis>,
+}
+
+/// One point of a line item's trend across documents.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeriodAmount {
+    pub period: Period,
+    pub amount: f64,
+    /// The newest document reporting the period; restated figures replace earlier ones
+    pub document_id: i64,
+    /// Against the same period a year earlier, when that is in the trend
+    pub change: Option<f64>,
+    pub change_percent: Option<f64>,
+}
+
+/// A line item's trend across a company's documents, ready to chart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemTimeseries {
+    pub normalized_label: String,
+    /// Lowercased as documents are grouped; None when every document was included
+    pub company: Option<String>,
+    pub basis: Option<Basis>,
+    /// The units every value is in, the configured ones
+    pub currency: Option<String>,
+    pub scale: Option<String>,
+    /// Period labels ("FY2023", "Q1-FY24") and the values at them, for the chart's axes
+    pub labels: Vec<String>,
+    pub values: Vec<f64>,
+    pub points: Vec<PeriodAmount>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn open(app: &AppHandle) -> Result<Connection, String> {
+    let conn = db::open_pipeline_db(app)?;
+    conn.execute_batch(PERIODS_SCHEMA).map_err(|e| e.to_string())?;
+    Ok(conn)
+}
+
+fn parse_period(text: Option<String>) -> Result<Option<Period>, String> {
+    text.filter(|text| !text.trim().is_empty()).map(Period::try_from).transpose()
+}
+
+fn parse_basis(text: Option<String>) -> Result<Option<Basis>, String> {
+    text.filter(|text| !text.trim().is_empty())
+        .map(|text| match text.trim().to_lowercase().as_str() {
+            "standalone" => Ok(Basis::Standalone),
+            "consolidated" => Ok(Basis::Consolidated),
+            _ => Err(format!("Unknown basis: {}", text)),
+        })
+        .transpose()
+}
+
+/// Periods named after "year ended", "quarter ended" and the like in page text.
+fn heading_periods(text: &str) -> Vec<Period> {
+    let heading = Regex::new(r"(?i)\b(year|quarter|months|period)\s+ended\s+(?:on\s+)?([^\n|]{1,25})").expect("valid pattern");
+    heading.captures_iter(text)
+        .filter_map(|caps| {
+            let period = Period::parse(&caps[2])?;
+            // A year ended in December is still a year, not the third quarter
+            Some(if caps[1].eq_ignore_ascii_case("year") { Period::year(period.fiscal_year) } else { period })
+        })
+        .collect()
+}
+
+/// Periods and basis as found in the stored document.
+pub fn detect(app: &AppHandle, document: &StoredDocument) -> DocumentPeriods {
+    let metadata = &document.metadata;
+    let mut found: BTreeSet<Period> = metadata["fiscalYears"].as_array().into_iter().flatten()
+        .filter_map(|year| year.as_str().and_then(Period::parse))
+        .collect();
+    found.extend(["period", "fiscalYear"].iter().filter_map(|key| metadata[*key].as_str()).filter_map(Period::parse));
+    if found.is_empty() {
+        found = document.items.iter()
+            .filter_map(|item| item["allYears"].as_object())
+            .flat_map(|years| years.keys())
+            .filter_map(|key| Period::parse(key))
+            .collect();
+    }
+    let mut basis = ["basis", "statementBasis"].iter()
+        .filter_map(|key| metadata[*key].as_str())
+        .find_map(Basis::parse)
+        .or_else(|| Basis::parse(&document.filename));
+
+    if found.is_empty() || basis.is_none() {
+        let pages: Vec<String> = (1..=HEADING_PAGES)
+            .filter_map(|page| statements::page_text(app, document.id, page).ok())
+            .flatten()
+            .collect();
+        if found.is_empty() {
+            found = pages.iter().flat_map(|chunk| heading_periods(chunk)).collect();
+        }
+        basis = basis.or_else(|| pages.iter().find_map(|chunk| Basis::parse(chunk)));
+    }
+
+    let current = found.last().copied();
+    // Quarterly results put the sequential quarter beside the same quarter last year; compare
+    // against the latter when it is there
+    let previous = current.map(|current| {
+        let prior = current.prior();
+        if found.contains(&prior) {
+            prior
+        } else {
+            found.range(..current).next_back().copied().unwrap_or(prior)
+        }
+    });
+    let source = if current.is_some() || basis.is_some() { PeriodSource::Detected } else { PeriodSource::Unknown };
+    DocumentPeriods { current, previous, basis, source }
+}
+
+fn stored_override(conn: &Connection, doc_id: i64) -> Result<Option<DocumentPeriods>, String> {
+    let row: Option<(Option<String>, Option<String>, Option<String>)> = conn.query_row(
+        "SELECT current_period, previous_period, basis FROM document_periods WHERE doc_id = ?1",
+        params![doc_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    ).optional().map_err(|e| e.to_string())?;
+    Ok(row.map(|(current, previous, basis)| DocumentPeriods {
+        current: parse_period(current).unwrap_or(None),
+        previous: parse_period(previous).unwrap_or(None),
+        basis: parse_basis(basis).unwrap_or(None),
+        source: PeriodSource::Override,
+    }))
+}
+
+fn item_overrides(conn: &Connection, doc_id: i64) -> Result<HashMap<String, ItemPeriods>, String> {
+    let mut stmt = conn.prepare("SELECT item_id, current_period, previous_period, basis FROM item_periods WHERE doc_id = ?1")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![doc_id], |row| {
+        Ok(ItemPeriods {
+            item_id: row.get(0)?,
+            current: parse_period(row.get(1)?).unwrap_or(None),
+            previous: parse_period(row.get(2)?).unwrap_or(None),
+            basis: parse_basis(row.get(3)?).unwrap_or(None),
+        })
+    }).map_err(|e| e.to_string())?;
+    let overrides = rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?;
+    Ok(overrides.into_iter().map(|o| (o.item_id.clone(), o)).collect())
+}
+
+/// The user's override if there is one, otherwise what detection finds.
+pub fn resolve(app: &AppHandle, document: &StoredDocument) -> Result<DocumentPeriods, String> {
+    let conn = open(app)?;
+    Ok(stored_override(&conn, document.id)?.unwrap_or_else(|| detect(app, document)))
+}
+
+/// Key every item's amounts by period: `periods` maps labels to amounts (the year columns
+/// placed at the resolved periods, plus `allYears`), and `currentPeriod`, `previousPeriod`
+/// and `basis` say what the columns hold. The document's own go under `metadata.periods`.
+pub fn annotate(document: &mut StoredDocument, periods: &DocumentPeriods, overrides: &HashMap<String, ItemPeriods>) {
+    for item in &mut document.items {
+        let correction = item["id"].as_str().and_then(|id| overrides.get(id));
+        let current = correction.and_then(|c| c.current).or(periods.current);
+        let previous = correction.and_then(|c| c.previous).or(periods.previous);
+        let basis = correction.and_then(|c| c.basis).or(periods.basis);
+        let Some(fields) = item.as_object_mut() else { continue };
+
+        let mut amounts = serde_json::Map::new();
+        if let Some(serde_json::Value::Object(years)) = fields.get("allYears") {
+            for (key, value) in years {
+                if let (Some(period), Some(amount)) = (Period::parse(key), value.as_f64()) {
+                    amounts.insert(period.to_string(), amount.into());
+                }
+            }
+        }
+        for (period, column) in [(previous, "previousYear"), (current, "currentYear")] {
+            if let (Some(period), Some(amount)) = (period, fields.get(column).and_then(|v| v.as_f64())) {
+                amounts.insert(period.to_string(), amount.into());
+            }
+        }
+        fields.insert("periods".to_string(), amounts.into());
+        fields.insert("currentPeriod".to_string(), serde_json::json!(current));
+        fields.insert("previousPeriod".to_string(), serde_json::json!(previous));
+        fields.insert("basis".to_string(), serde_json::json!(basis));
+    }
+
+    if !document.metadata.is_object() {
+        document.metadata = serde_json::json!({});
+    }
+    document.metadata["periods"] = serde_json::json!(periods);
+}
+
+/// Annotate a freshly loaded document with its resolved periods.
+pub fn apply(app: &AppHandle, document: &mut StoredDocument) -> Result<(), String> {
+    let periods = resolve(app, document)?;
+    let overrides = item_overrides(&open(app)?, document.id)?;
+    annotate(document, &periods, &overrides);
+    Ok(())
+}
+
+/// An item's amount for `period`, from the `periods` map `annotate` builds.
+pub fn amount(item: &serde_json::Value, period: Period) -> Option<f64> {
+    item["periods"][period.to_string()].as_f64()
+}
+
+/// (current, previous) amounts at the item's own periods, so a corrected period moves the
+/// comparison with it. Items whose periods are unknown, or that were never annotated, use the
+/// year columns.
+pub fn amounts(item: &serde_json::Value) -> (Option<f64>, Option<f64>) {
+    let at = |period: &str, column: &str| match item[period].as_str().and_then(Period::parse) {
+        Some(period) => amount(item, period),
+        None => item[column].as_f64(),
+    };
+    (at("currentPeriod", "currentYear"), at("previousPeriod", "previousYear"))
+}
+
+fn normalize_label(label: &str) -> String {
+    label.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// The item's amounts from each of `documents`, one point per period. Documents come oldest
+/// first, so a period reported again takes the later (restated) figure.
+pub fn trend(documents: &[StoredDocument], label: &str, basis: Option<Basis>) -> Vec<PeriodAmount> {
+    let label = normalize_label(label);
+    let matches = |item: &serde_json::Value| {
+        ["label", "normalizedLabel"].iter().any(|key| item[*key].as_str().map(normalize_label).as_deref() == Some(label.as_str()))
+    };
+    let mut points: BTreeMap<Period, (f64, i64)> = BTreeMap::new();
+    for document in documents {
+        let item = document.items.iter()
+            .filter(|item| !item["isHeader"].as_bool().unwrap_or(false))
+            .filter(|item| basis.is_none_or(|basis| item["basis"].as_str() == Some(basis.name())))
+            .find(|item| matches(item));
+        let Some(amounts) = item.and_then(|item| item["periods"].as_object()) else { continue };
+        for (period, value) in amounts {
+            if let (Some(period), Some(value)) = (Period::parse(period), value.as_f64()) {
+                points.insert(period, (value, document.id));
+            }
+        }
+    }
+
+    points.iter()
+        .map(|(period, (amount, document_id))| {
+            let change = points.get(&period.prior()).map(|(prior, _)| amount - prior);
+            let change_percent = points.get(&period.prior())
+                .filter(|(prior, _)| *prior != 0.0)
+                .map(|(prior, _)| (amount - prior) / prior.abs() * 100.0);
+            PeriodAmount { period: *period, amount: *amount, document_id: *document_id, change, change_percent }
+        })
+        .collect()
+}
+
+// Tauri Commands
+/// The periods and basis a document's year columns hold.
+#[tauri::command]
+pub fn get_document_periods(app: AppHandle, document_id: i64) -> Result<DocumentPeriods, String> {
+    let document = statements::load_stored_document(&app, document_id)?;
+    resolve(&app, &document)
+}
+
+/// Override what detection found; with no arguments the override is removed. Setting only
+/// `current` compares it with the same period a year earlier. Returns the periods now in effect.
+#[tauri::command]
+pub fn set_document_periods(
+    app: AppHandle,
+    document_id: i64,
+    current: Option<String>,
+    previous: Option<String>,
+    basis: Option<String>,
+) -> Result<DocumentPeriods, String> {
+    let document = statements::load_stored_document(&app, document_id)?;
+    let conn = open(&app)?;
+    derived::mark_stale(&app, Some(document_id));
+    if current.is_none() && previous.is_none() && basis.is_none() {
+        conn.execute("DELETE FROM document_periods WHERE doc_id = ?1", params![document_id]).map_err(|e| e.to_string())?;
+        info!(document_id, "Cleared period override");
+        return resolve(&app, &document);
+    }
+
+    let resolved = resolve(&app, &document)?;
+    let (current, previous, basis) = (parse_period(current)?, parse_period(previous)?, parse_basis(basis)?);
+    let previous = previous.or(current.map(Period::prior)).or(resolved.previous);
+    let current = current.or(resolved.current);
+    let basis = basis.or(resolved.basis);
+    conn.execute(
+        "INSERT OR REPLACE INTO document_periods (doc_id, current_period, previous_period, basis, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![document_id, current.map(String::from), previous.map(String::from), basis.map(Basis::name), now_secs()],
+    ).map_err(|e| e.to_string())?;
+    info!(document_id, current = ?current, previous = ?previous, basis = ?basis, "Set period override");
+    Ok(DocumentPeriods { current, previous, basis, source: PeriodSource::Override })
+}
+
+/// Correct the periods or basis of one item, e.g. a consolidated table in a standalone
+/// report; with no arguments the item follows the document again.
+#[tauri::command]
+pub fn set_item_periods(
+    app: AppHandle,
+    document_id: i64,
+    item_id: String,
+    current: Option<String>,
+    previous: Option<String>,
+    basis: Option<String>,
+) -> Result<ItemPeriods, String> {
+    let document = statements::load_stored_document(&app, document_id)?;
+    if !document.items.iter().any(|item| item["id"].as_str() == Some(item_id.as_str())) {
+        return Err(format!("Item {} not found in document {}", item_id, document_id));
+    }
+    let (current, previous, basis) = (parse_period(current)?, parse_period(previous)?, parse_basis(basis)?);
+    let previous = previous.or(current.map(Period::prior));
+    let conn = open(&app)?;
+    derived::mark_stale(&app, Some(document_id));
+    if current.is_none() && previous.is_none() && basis.is_none() {
+        conn.execute(
+            "DELETE FROM item_periods WHERE doc_id = ?1 AND item_id = ?2",
+            params![document_id, item_id],
+        ).map_err(|e| e.to_string())?;
+        info!(document_id, item_id = %item_id, "Cleared item period override");
+    } else {
+        conn.execute(
+            "INSERT OR REPLACE INTO item_periods (doc_id, item_id, current_period, previous_period, basis, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![document_id, item_id, current.map(String::from), previous.map(String::from), basis.map(Basis::name), now_secs()],
+        ).map_err(|e| e.to_string())?;
+        info!(document_id, item_id = %item_id, current = ?current, basis = ?basis, "Set item period override");
+    }
+    Ok(ItemPeriods { item_id, current, previous, basis })
+}
+
+/// One line item across documents, one point per period, oldest first. Items match on label;
+/// `basis` keeps standalone and consolidated figures apart.
+#[tauri::command]
+pub fn get_item_trend(
+    app: AppHandle,
+    document_ids: Vec<i64>,
+    label: String,
+    basis: Option<String>,
+) -> Result<Vec<PeriodAmount>, String> {
+    let basis = parse_basis(basis)?;
+    let mut document_ids = document_ids;
+    document_ids.sort_unstable();
+    document_ids.dedup();
+    let documents = document_ids.into_iter()
+        .map(|document_id| statements::load_document(&app, document_id))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(trend(&documents, &label, basis))
+}
+
+/// A line item across every stored document of `company` (all documents when None), in the
+/// configured units, shaped for a chart: period labels and values side by side, oldest first.
+#[tauri::command]
+pub fn get_item_timeseries(
+    app: AppHandle,
+    normalized_label: String,
+    company: Option<String>,
+    basis: Option<String>,
+) -> Result<ItemTimeseries, String> {
+    let basis = parse_basis(basis)?;
+    let company = company.map(|name| name.trim().to_lowercase()).filter(|name| !name.is_empty());
+    let mut document_ids: Vec<i64> = statements::list_documents(&app)?.into_iter().map(|d| d.id).collect();
+    document_ids.sort_unstable();
+
+    let mut documents = Vec::new();
+    for document_id in document_ids {
+        let document = statements::load_document(&app, document_id)?;
+        if company.is_none() || duplicates::company(&document) == company {
+            documents.push(document);
+        }
+    }
+    let points = trend(&documents, &normalized_label, basis);
+    // Every document was converted to the configured units on load, so any one of them says which
+    let units = documents.iter()
+        .find(|document| points.iter().any(|point| point.document_id == document.id))
+        .map(|document| &document.metadata["units"]);
+    Ok(ItemTimeseries {
+        normalized_label: normalize_label(&normalized_label),
+        company,
+        basis,
+        currency: units.and_then(|units| units["currency"].as_str()).map(str::to_string),
+        scale: units.and_then(|units| units["scale"].as_str()).map(str::to_string),
+        labels: points.iter().map(|point| point.period.to_string()).collect(),
+        values: points.iter().map(|point| point.amount).collect(),
+        points,
+    })
+}
@@ -0,0 +1,217 @@
+// Pipeline profile - per-stage timings of the latest analysis (process spawn, OCR, each page's
+// extraction, DB writes, metrics), so a slow run shows where its time went
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    CacheLookup,
+    Ocr,
+    Split,
+    /// Python start-up and the bridge version handshake
+    Spawn,
+    /// Page extraction inside python/api.py; per page in `pages`
+    Extraction,
+    /// python/api.py saving the document, items and text chunks
+    PythonDbWrites,
+    /// python/api.py computing metrics after the parse
+    PythonMetrics,
+    /// Whatever python/api.py did after the last page when it reported no further stages
+    PythonFinalize,
+    /// Streamed items written to `partial_items`
+    PartialWrites,
+    /// Merged results written to the pipeline DB from Rust
+    DbWrites,
+    /// Metrics recomputed over merged results
+    Metrics,
+    CacheWrite,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageTiming {
+    pub stage: Stage,
+    /// Summed over every occurrence; parallel workers add up, so this can exceed the wall time
+    pub millis: f64,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageTiming {
+    /// Document page number
+    pub page: u32,
+    pub millis: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisProfile {
+    pub label: String,
+    /// sequential, parallel_ranges, resumed or cache_hit
+    pub mode: String,
+    pub started_at: i64,
+    pub total_millis: f64,
+    pub success: bool,
+    pub error: Option<String>,
+    pub stages: Vec<StageTiming>,
+    /// In the order pages finished
+    pub pages: Vec<PageTiming>,
+}
+
+/// The profile of the most recently finished analysis.
+#[derive(Default)]
+pub struct LastAnalysisProfile {
+    profile: Mutex<Option<AnalysisProfile>>,
+}
+
+impl LastAnalysisProfile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Collects timings for one run; shared by reference between parallel workers.
+pub struct Profiler {
+    started: Instant,
+    profile: Mutex<AnalysisProfile>,
+}
+
+impl Profiler {
+    pub fn new(label: &str) -> Self {
+        Self {
+            started: Instant::now(),
+            profile: Mutex::new(AnalysisProfile {
+                label: label.to_string(),
+                mode: "sequential".to_string(),
+                started_at: now_secs(),
+                total_millis: 0.0,
+                success: false,
+                error: None,
+                stages: Vec::new(),
+                pages: Vec::new(),
+            }),
+        }
+    }
+
+    pub fn set_mode(&self, mode: &str) {
+        self.profile.lock().unwrap_or_else(|e| e.into_inner()).mode = mode.to_string();
+    }
+
+    pub fn add(&self, stage: Stage, duration: Duration) {
+        let mut profile = self.profile.lock().unwrap_or_else(|e| e.into_inner());
+        match profile.stages.iter_mut().find(|timing| timing.stage == stage) {
+            Some(timing) => {
+                timing.millis += millis(duration);
+                timing.count += 1;
+            }
+            None => profile.stages.push(StageTiming { stage, millis: millis(duration), count: 1 }),
+        }
+    }
+
+    /// Record `stage` as having run from `since` until now.
+    pub fn since(&self, stage: Stage, since: Instant) {
+        self.add(stage, since.elapsed());
+    }
+
+    fn page(&self, page: u32, duration: Duration) {
+        self.add(Stage::Extraction, duration);
+        self.profile.lock().unwrap_or_else(|e| e.into_inner()).pages.push(PageTiming { page, millis: millis(duration) });
+    }
+
+    /// A clock for one parser process whose page 1 is document page `first_page`.
+    pub fn parse_clock(&self, first_page: u32) -> ParseClock<'_> {
+        ParseClock { profiler: self, offset: first_page - 1, page: 0, mark: Instant::now(), phase: Phase::Extracting }
+    }
+
+    /// Keep the profile for `profile_last_analysis`.
+    pub fn finish<T, E: std::fmt::Display>(self, app: &AppHandle, result: &Result<T, E>) {
+        let mut profile = self.profile.into_inner().unwrap_or_else(|e| e.into_inner());
+        profile.total_millis = millis(self.started.elapsed());
+        profile.success = result.is_ok();
+        profile.error = result.as_ref().err().map(ToString::to_string);
+        if let Some(state) = app.try_state::<LastAnalysisProfile>() {
+            *state.profile.lock().unwrap_or_else(|e| e.into_inner()) = Some(profile);
+        }
+    }
+}
+
+/// The page in python/api.py progress such as "Extracting (3/120 pages)".
+fn reported_page(message: &str) -> Option<u32> {
+    let end = message.rfind(" pages)")?;
+    let start = message[..end].rfind('(')? + 1;
+    message[start..end].split('/').next()?.parse().ok()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Extracting,
+    Metrics,
+    Done,
+}
+
+/// Turns one parser's progress messages into stage timings: each page runs from the previous
+/// page's report to its own, DB writes from the last page to "Calculating metrics...", and
+/// metrics from there to "Analysis complete!".
+pub struct ParseClock<'a> {
+    profiler: &'a Profiler,
+    offset: u32,
+    page: u32,
+    mark: Instant,
+    phase: Phase,
+}
+
+impl ParseClock<'_> {
+    pub fn progress(&mut self, message: &str) {
+        if message.starts_with("Calculating metrics") {
+            if self.phase == Phase::Extracting {
+                self.profiler.since(Stage::PythonDbWrites, self.mark);
+                self.mark = Instant::now();
+                self.phase = Phase::Metrics;
+            }
+        } else if message.starts_with("Analysis complete") {
+            if self.phase == Phase::Metrics {
+                self.profiler.since(Stage::PythonMetrics, self.mark);
+                self.mark = Instant::now();
+                self.phase = Phase::Done;
+            }
+        } else if let Some(page) = reported_page(message) {
+            if self.phase == Phase::Extracting && page > self.page {
+                self.profiler.page(self.offset + page, self.mark.elapsed());
+                self.page = page;
+                self.mark = Instant::now();
+            }
+        }
+    }
+
+    /// The response arrived; time since the last reported stage goes to the stage in progress.
+    pub fn response(self) {
+        match self.phase {
+            Phase::Extracting => self.profiler.since(Stage::PythonFinalize, self.mark),
+            Phase::Metrics => self.profiler.since(Stage::PythonMetrics, self.mark),
+            Phase::Done => {}
+        }
+    }
+}
+
+// Tauri Commands
+/// Timings of the latest analysis, or None before the first one this session.
+#[tauri::command]
+pub fn profile_last_analysis(state: tauri::State<'_, LastAnalysisProfile>) -> Result<Option<AnalysisProfile>, String> {
+    let profile = state.profile.lock().map_err(|e| e.to_string())?;
+    Ok(profile.clone())
+}
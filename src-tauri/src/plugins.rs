@@ -0,0 +1,335 @@
+// Plugins - third-party analyzers discovered in app-data `plugins/<id>/plugin.json`.
+// A plugin is a Python script or an executable that reads one JSON request on stdin and
+// writes one JSON reply on stdout; it can provide analysis commands and metric calculators.
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::db;
+use crate::jobs;
+use crate::python_bridge;
+use crate::ratios::Ratio;
+use crate::statements;
+
+const MANIFEST_FILE: &str = "plugin.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginRuntime {
+    /// Run the entry with the configured Python interpreter
+    Python,
+    /// Run the entry directly
+    Executable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginCommand {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginMetric {
+    pub key: String,
+    pub name: String,
+    #[serde(default = "default_metric_category")]
+    pub category: String,
+    /// "x" for multiples, "%" for percentages
+    #[serde(default)]
+    pub unit: String,
+}
+
+fn default_metric_category() -> String { "custom".to_string() }
+
+/// `plugin.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginManifest {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    pub runtime: PluginRuntime,
+    /// Relative to the plugin's folder
+    pub entry: String,
+    #[serde(default)]
+    pub commands: Vec<PluginCommand>,
+    #[serde(default)]
+    pub metrics: Vec<PluginMetric>,
+    /// Seconds before a call is killed; the Python metrics timeout when absent
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginInfo {
+    pub id: String,
+    pub dir: String,
+    /// None when the manifest couldn't be loaded; see `error`
+    pub manifest: Option<PluginManifest>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MetricValue {
+    key: String,
+    current: Option<f64>,
+    previous: Option<f64>,
+}
+
+pub struct PluginRegistry {
+    plugins: Mutex<Vec<PluginInfo>>,
+}
+
+pub fn plugins_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?.join("plugins");
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+fn load_manifest(dir: &Path) -> Result<PluginManifest, String> {
+    let text = fs::read_to_string(dir.join(MANIFEST_FILE)).map_err(|e| format!("Cannot read {}: {}", MANIFEST_FILE, e))?;
+    let manifest: PluginManifest = serde_json::from_str(&text).map_err(|e| format!("Invalid {}: {}", MANIFEST_FILE, e))?;
+    let valid_id = !manifest.id.is_empty()
+        && manifest.id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid_id {
+        return Err(format!("Plugin id \"{}\" may only use letters, digits, '-' and '_'", manifest.id));
+    }
+    // The entry has to stay inside the plugin's own folder
+    let entry = dir.join(&manifest.entry).canonicalize().map_err(|_| format!("Entry {} not found", manifest.entry))?;
+    if !entry.starts_with(dir.canonicalize().map_err(|e| e.to_string())?) {
+        return Err(format!("Entry {} is outside the plugin folder", manifest.entry));
+    }
+    Ok(manifest)
+}
+
+/// Every folder under `plugins/` with a manifest; broken ones are listed with their error.
+fn discover(app: &AppHandle) -> Result<Vec<PluginInfo>, String> {
+    let root = plugins_dir(app)?;
+    let mut dirs: Vec<PathBuf> = fs::read_dir(&root)
+        .map_err(|e| e.to_string())?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.join(MANIFEST_FILE).is_file())
+        .collect();
+    dirs.sort();
+
+    let mut plugins: Vec<PluginInfo> = Vec::new();
+    for dir in dirs {
+        let folder = dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let mut info = match load_manifest(&dir) {
+            Ok(manifest) => PluginInfo { id: manifest.id.clone(), dir: dir.to_string_lossy().to_string(), manifest: Some(manifest), error: None },
+            Err(e) => PluginInfo { id: folder, dir: dir.to_string_lossy().to_string(), manifest: None, error: Some(e) },
+        };
+        if plugins.iter().any(|p| p.id == info.id) {
+            info.error = Some(format!("Another plugin already uses the id {}", info.id));
+            info.manifest = None;
+        }
+        match &info.error {
+            Some(e) => warn!(plugin = %info.id, "Plugin not loaded: {}", e),
+            None => info!(plugin = %info.id, "Plugin loaded"),
+        }
+        plugins.push(info);
+    }
+    Ok(plugins)
+}
+
+impl PluginRegistry {
+    pub fn new() -> Self {
+        Self { plugins: Mutex::new(Vec::new()) }
+    }
+
+    pub fn list(&self) -> Vec<PluginInfo> {
+        self.plugins.lock().map(|plugins| plugins.clone()).unwrap_or_default()
+    }
+
+    pub fn reload(&self, app: &AppHandle) -> Result<Vec<PluginInfo>, String> {
+        let plugins = discover(app)?;
+        *self.plugins.lock().map_err(|e| e.to_string())? = plugins.clone();
+        Ok(plugins)
+    }
+
+    fn get(&self, id: &str) -> Result<(PathBuf, PluginManifest), String> {
+        let plugins = self.plugins.lock().map_err(|e| e.to_string())?;
+        let plugin = plugins.iter().find(|p| p.id == id).ok_or_else(|| format!("No plugin {}", id))?;
+        match (&plugin.manifest, &plugin.error) {
+            (Some(manifest), _) => Ok((PathBuf::from(&plugin.dir), manifest.clone())),
+            (None, error) => Err(error.clone().unwrap_or_else(|| format!("Plugin {} is not loaded", id))),
+        }
+    }
+}
+
+/// Load plugins at startup.
+pub fn load(app: &AppHandle) {
+    if let Err(e) = app.state::<PluginRegistry>().reload(app) {
+        warn!("Failed to load plugins: {}", e);
+    }
+}
+
+/// Send one request to the plugin and parse its reply, killing it after the timeout.
+fn call(app: &AppHandle, dir: &Path, manifest: &PluginManifest, request: &serde_json::Value) -> Result<serde_json::Value, String> {
+    let python = python_bridge::python_settings(app);
+    let entry = dir.join(&manifest.entry);
+    let mut command = match manifest.runtime {
+        PluginRuntime::Python => {
+            let interpreter = python_bridge::find_python(&python.interpreter_path).ok_or("Python not found")?;
+            let mut command = Command::new(interpreter);
+            command.arg(&entry);
+            command
+        }
+        PluginRuntime::Executable => Command::new(&entry),
+    };
+    let db_path = db::pipeline_db_path(app)?;
+    // Callers are on blocking threads, so waiting here for a Python slot stalls no async task
+    let _slot = match manifest.runtime {
+        PluginRuntime::Python => tauri::async_runtime::block_on(jobs::python_slot(app)),
+        PluginRuntime::Executable => None,
+    };
+    let mut child = command
+        .current_dir(dir)
+        .env("FINCALC_DB_PATH", db_path)
+        .env("FINCALC_PLUGIN_DIR", dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to start plugin {}: {}", manifest.id, e))?;
+
+    let input = serde_json::to_vec(request).map_err(|e| e.to_string())?;
+    let timeout = Duration::from_secs(manifest.timeout_secs.unwrap_or(python.metrics_timeout_secs));
+    let start = Instant::now();
+    // Drain the pipes while waiting so a chatty plugin can't block on a full buffer
+    let mut stdout = child.stdout.take().ok_or("Plugin stdout unavailable")?;
+    let mut stderr = child.stderr.take().ok_or("Plugin stderr unavailable")?;
+    let out_reader = thread::spawn(move || {
+        let mut text = String::new();
+        let _ = stdout.read_to_string(&mut text);
+        text
+    });
+    let err_reader = thread::spawn(move || {
+        let mut text = String::new();
+        let _ = stderr.read_to_string(&mut text);
+        text
+    });
+    // Written from its own thread, under the timeout, in case the plugin answers before it has
+    // read everything; dropping stdin at the end closes the pipe
+    let mut stdin = child.stdin.take().ok_or("Plugin stdin unavailable")?;
+    let writer = thread::spawn(move || stdin.write_all(&input));
+
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if start.elapsed() > timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("Plugin {} timed out after {}s", manifest.id, timeout.as_secs()));
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(50)),
+            Err(e) => return Err(format!("Error waiting for plugin: {}", e)),
+        }
+    };
+    let output = out_reader.join().unwrap_or_default();
+    let errors = err_reader.join().unwrap_or_default();
+    let sent = writer.join().unwrap_or_else(|_| Err(std::io::Error::other("writer panicked")));
+    if !status.success() {
+        return Err(format!("Plugin {} failed: {}", manifest.id, errors.trim()));
+    }
+    sent.map_err(|e| format!("Failed to send request to plugin: {}", e))?;
+
+    let reply: serde_json::Value = serde_json::from_str(output.trim())
+        .map_err(|e| format!("Plugin {} returned invalid JSON: {}", manifest.id, e))?;
+    if let Some(error) = reply.get("error").and_then(|e| e.as_str()) {
+        return Err(error.to_string());
+    }
+    Ok(reply.get("result").cloned().unwrap_or(reply))
+}
+
+/// Every plugin metric for a document, keyed `<plugin id>:<metric key>`. A failing plugin
+/// is logged and skipped so one bad calculator doesn't hide the others.
+pub fn compute_metrics(app: &AppHandle, items: &[serde_json::Value]) -> Vec<Ratio> {
+    let plugins: Vec<(PathBuf, PluginManifest)> = app.state::<PluginRegistry>().list()
+        .into_iter()
+        .filter_map(|p| p.manifest.map(|m| (PathBuf::from(p.dir), m)))
+        .filter(|(_, m)| !m.metrics.is_empty())
+        .collect();
+
+    let mut ratios = Vec::new();
+    for (dir, manifest) in plugins {
+        let request = serde_json::json!({ "type": "metrics", "items": items });
+        let values: Vec<MetricValue> = match call(app, &dir, &manifest, &request)
+            .and_then(|reply| serde_json::from_value(reply).map_err(|e| e.to_string()))
+        {
+            Ok(values) => values,
+            Err(e) => {
+                warn!(plugin = %manifest.id, "Plugin metrics failed: {}", e);
+                continue;
+            }
+        };
+        for metric in &manifest.metrics {
+            let Some(value) = values.iter().find(|v| v.key == metric.key) else { continue };
+            if value.current.is_none() && value.previous.is_none() {
+                continue;
+            }
+            ratios.push(Ratio {
+                key: format!("{}:{}", manifest.id, metric.key),
+                name: metric.name.clone(),
+                category: metric.category.clone(),
+                unit: metric.unit.clone(),
+                current: value.current,
+                previous: value.previous,
+            });
+        }
+    }
+    ratios
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn list_plugins(registry: tauri::State<'_, PluginRegistry>) -> Vec<PluginInfo> {
+    registry.list()
+}
+
+/// Re-scan the plugins folder, e.g. after installing one.
+#[tauri::command]
+pub fn reload_plugins(app: AppHandle, registry: tauri::State<'_, PluginRegistry>) -> Result<Vec<PluginInfo>, String> {
+    registry.reload(&app)
+}
+
+#[tauri::command]
+pub async fn run_plugin_command(
+    app: AppHandle,
+    plugin_id: String,
+    command: String,
+    args: Option<serde_json::Value>,
+) -> Result<serde_json::Value, String> {
+    let (dir, manifest) = app.state::<PluginRegistry>().get(&plugin_id)?;
+    if !manifest.commands.iter().any(|c| c.name == command) {
+        return Err(format!("Plugin {} has no command {}", plugin_id, command));
+    }
+    let request = serde_json::json!({ "type": "command", "command": command, "args": args.unwrap_or(serde_json::Value::Null) });
+    info!(plugin = %plugin_id, command = %command, "Running plugin command");
+    tauri::async_runtime::spawn_blocking(move || call(&app, &dir, &manifest, &request))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Metrics from every plugin calculator for one document.
+#[tauri::command]
+pub async fn compute_plugin_metrics(app: AppHandle, document_id: i64) -> Result<Vec<Ratio>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let document = statements::load_document(&app, document_id)?;
+        Ok(compute_metrics(&app, &document.items))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
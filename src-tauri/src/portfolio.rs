@@ -0,0 +1,861 @@
+// Portfolio - trades imported from broker tradebooks and contract notes (CSV or Excel) into a
+// transactions ledger, with holdings rebuilt from it at average cost and P&L (FIFO realized,
+// unrealized at market, XIRR) computed from it on request
+use calamine::{open_workbook_auto, Reader};
+use chrono::{Local, NaiveDate};
+use rusqlite::{params, Connection, Row};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use tracing::info;
+
+use crate::corporate_actions::{self, Adjustments};
+use crate::corporate_events::normalize_date;
+use crate::csv_import::{self, CsvColumn};
+use crate::db;
+use crate::excel;
+use crate::scraper;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    side TEXT NOT NULL,            -- 'buy', 'sell'
+    quantity REAL NOT NULL,
+    price REAL NOT NULL,
+    charges REAL NOT NULL DEFAULT 0,
+    trade_date TEXT NOT NULL,      -- YYYY-MM-DD
+    trade_id TEXT,
+    import_key TEXT,               -- hash of the source row and its position, for trades without an id
+    broker TEXT,
+    source_file TEXT,
+    imported_at INTEGER NOT NULL
+);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_transactions_trade_id
+    ON transactions(exchange, trade_id) WHERE trade_id IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_transactions_import_key
+    ON transactions(import_key) WHERE trade_id IS NULL AND import_key IS NOT NULL;
+CREATE TABLE IF NOT EXISTS holdings (
+    symbol TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    quantity REAL NOT NULL,
+    average_cost REAL NOT NULL,
+    invested REAL NOT NULL,
+    realized_pnl REAL NOT NULL,
+    charges REAL NOT NULL,
+    unmatched_sell_quantity REAL NOT NULL DEFAULT 0,
+    first_trade_date TEXT NOT NULL,
+    last_trade_date TEXT NOT NULL,
+    PRIMARY KEY (symbol, exchange)
+);
+";
+
+// Rows searched for the header; contract notes put the broker's letterhead above it
+const HEADER_SEARCH_ROWS: usize = 30;
+// Quantities this close to zero are a closed position
+const QUANTITY_EPSILON: f64 = 1e-9;
+// Sorts after every trade and ex-date, to apply whatever adjustments remain
+const LATEST_DATE: &str = "9999-12-31";
+// Listed equity held longer than this is long-term for capital gains
+const LONG_TERM_DAYS: i64 = 365;
+// Quotes younger than this are used for unrealized P&L without re-fetching
+const QUOTE_MAX_AGE_SECS: u64 = 300;
+const XIRR_ITERATIONS: usize = 100;
+const XIRR_TOLERANCE: f64 = 1e-7;
+
+const SYMBOL_HEADERS: &[&str] = &["symbol", "tradingsymbol", "scrip", "scrip name", "scrip code", "security", "security name", "instrument", "stock", "stock name", "company"];
+const SIDE_HEADERS: &[&str] = &["trade type", "type", "side", "buy/sell", "b/s", "buy sell", "transaction type", "action", "trade side"];
+const QUANTITY_HEADERS: &[&str] = &["quantity", "qty", "shares", "units", "traded qty", "trade quantity"];
+const PRICE_HEADERS: &[&str] = &["price", "rate", "trade price", "net rate", "gross rate", "price per unit", "avg price", "average price", "trade rate"];
+const DATE_HEADERS: &[&str] = &["trade date", "date", "order execution time", "execution time", "trade time", "settlement date"];
+const EXCHANGE_HEADERS: &[&str] = &["exchange", "exch", "segment exchange"];
+// Not order ids: every fill of an order shares one
+const TRADE_ID_HEADERS: &[&str] = &["trade id", "trade no", "trade number", "trade no."];
+// A total-charges column is used on its own; otherwise every itemized one is summed
+const TOTAL_CHARGES_HEADERS: &[&str] = &["charges", "total charges", "total taxes and charges", "taxes and charges"];
+const CHARGE_HEADERS: &[&str] = &["brokerage", "stt", "gst", "igst", "cgst", "sgst", "stamp duty", "exchange charges", "transaction charges", "sebi fees", "sebi turnover fees", "clearing charges"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+impl TradeSide {
+    fn as_str(self) -> &'static str {
+        match self {
+            TradeSide::Buy => "buy",
+            TradeSide::Sell => "sell",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "buy" | "b" | "bought" | "purchase" | "bo" => Some(TradeSide::Buy),
+            "sell" | "s" | "sold" | "sale" | "so" => Some(TradeSide::Sell),
+            _ => None,
+        }
+    }
+}
+
+/// Columns by header text or 0-based index, where the headers aren't ones we recognise.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeColumnMapping {
+    pub symbol: Option<CsvColumn>,
+    pub side: Option<CsvColumn>,
+    pub quantity: Option<CsvColumn>,
+    pub price: Option<CsvColumn>,
+    /// Summed per row
+    pub charges: Option<Vec<CsvColumn>>,
+    pub date: Option<CsvColumn>,
+    pub exchange: Option<CsvColumn>,
+    pub trade_id: Option<CsvColumn>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transaction {
+    pub id: i64,
+    pub symbol: String,
+    pub exchange: String,
+    pub side: TradeSide,
+    pub quantity: f64,
+    pub price: f64,
+    pub charges: f64,
+    pub trade_date: String,
+    pub trade_id: Option<String>,
+    pub broker: Option<String>,
+    pub source_file: Option<String>,
+    pub imported_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Holding {
+    pub symbol: String,
+    pub exchange: String,
+    /// 0 once fully sold
+    pub quantity: f64,
+    /// Per share, charges on buys included
+    pub average_cost: f64,
+    pub invested: f64,
+    /// FIFO, matching `get_portfolio_pnl`
+    pub realized_pnl: f64,
+    pub charges: f64,
+    /// Shares sold beyond the imported buys, left out of the position and realized P&L
+    pub unmatched_sell_quantity: f64,
+    pub first_trade_date: String,
+    pub last_trade_date: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeImport {
+    pub file_name: String,
+    pub imported: usize,
+    /// Trades already in the ledger from an earlier import
+    pub duplicates: usize,
+    /// "Row N: why" for rows that weren't trades
+    pub skipped: Vec<String>,
+    pub holdings: Vec<Holding>,
+}
+
+/// Shares of one sell matched against one buy lot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RealizedGain {
+    pub symbol: String,
+    pub exchange: String,
+    pub quantity: f64,
+    pub buy_date: String,
+    pub sell_date: String,
+    /// Per share, net of each trade's charges
+    pub buy_price: f64,
+    pub sell_price: f64,
+    pub gain: f64,
+    pub holding_days: i64,
+    pub long_term: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RealizedGains {
+    pub gains: Vec<RealizedGain>,
+    pub short_term: f64,
+    pub long_term: f64,
+    pub total: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HoldingPnl {
+    pub symbol: String,
+    pub exchange: String,
+    pub quantity: f64,
+    /// FIFO cost of the shares still held, buy charges included
+    pub cost_basis: f64,
+    pub average_cost: f64,
+    pub last_price: Option<f64>,
+    pub market_value: Option<f64>,
+    pub unrealized_pnl: Option<f64>,
+    pub unrealized_percent: Option<f64>,
+    pub realized_pnl: f64,
+    /// Shares sold beyond the imported buys, left out of realized P&L
+    pub unmatched_sell_quantity: f64,
+    pub quote_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortfolioPnl {
+    pub holdings: Vec<HoldingPnl>,
+    pub invested: f64,
+    pub market_value: f64,
+    pub unrealized_pnl: f64,
+    pub realized_pnl: f64,
+    pub total_pnl: f64,
+    /// Annualised, in percent; None when it can't be solved or a holding has no quote
+    pub xirr: Option<f64>,
+    /// Open holdings without a quote, valued at cost
+    pub unpriced: usize,
+    pub as_of: String,
+}
+
+/// A trade to add to the ledger, before it has an id.
+pub struct NewTrade {
+    pub symbol: String,
+    pub exchange: String,
+    pub side: TradeSide,
+    pub quantity: f64,
+    pub price: f64,
+    pub charges: f64,
+    pub trade_date: String,
+    pub trade_id: Option<String>,
+    /// Identifies the source row when there's no trade id; see `import_key`
+    pub import_key: Option<String>,
+}
+
+struct Columns {
+    symbol: usize,
+    side: Option<usize>,
+    quantity: usize,
+    price: usize,
+    charges: Vec<usize>,
+    date: usize,
+    exchange: Option<usize>,
+    trade_id: Option<usize>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Every row of the file as text: CSVs however they're delimited, or a workbook's first
+/// non-empty sheet.
+pub fn read_rows(file_path: &str) -> Result<Vec<Vec<String>>, String> {
+    let extension = Path::new(file_path).extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    match extension.as_str() {
+        "xlsx" | "xlsm" | "xls" | "xlsb" | "ods" => {
+            let mut workbook = open_workbook_auto(file_path).map_err(|e| format!("Failed to open workbook: {}", e))?;
+            let range = workbook.sheet_names().into_iter()
+                .filter_map(|name| workbook.worksheet_range(&name).ok())
+                .find(|range| !range.is_empty())
+                .ok_or("Workbook has no non-empty sheets")?;
+            Ok(range.rows().map(|row| row.iter().map(|cell| excel::cell_text(cell).unwrap_or_default()).collect()).collect())
+        }
+        "pdf" => Err("PDF contract notes aren't supported; download the contract note or tradebook as CSV or Excel".to_string()),
+        _ => {
+            let bytes = std::fs::read(file_path).map_err(|e| format!("Failed to read {}: {}", file_path, e))?;
+            let (text, _) = csv_import::decode(&bytes);
+            let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+            let delimiter = csv_import::detect_delimiter(&lines);
+            Ok(lines.iter().map(|line| csv_import::split_line(line, delimiter)).collect())
+        }
+    }
+}
+
+fn normalize_header(header: &str) -> String {
+    header.to_lowercase().replace(['_', '.'], " ").split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn find_header(header: &[String], names: &[&str]) -> Option<usize> {
+    header.iter().position(|h| names.contains(&normalize_header(h).as_str()))
+}
+
+/// The mapped column, else the first header that names it.
+fn column(mapped: Option<&CsvColumn>, header: &[String], names: &[&str]) -> Result<Option<usize>, String> {
+    match mapped {
+        Some(mapped) => csv_import::resolve_column(mapped, header).map(Some),
+        None => Ok(find_header(header, names)),
+    }
+}
+
+/// The header row and its columns: the first row naming a symbol, a quantity, a price and a date.
+fn locate_columns(rows: &[Vec<String>], mapping: &TradeColumnMapping) -> Result<(usize, Columns), String> {
+    for (index, header) in rows.iter().enumerate().take(HEADER_SEARCH_ROWS) {
+        let (Ok(Some(symbol)), Ok(Some(quantity)), Ok(Some(price)), Ok(Some(date))) = (
+            column(mapping.symbol.as_ref(), header, SYMBOL_HEADERS),
+            column(mapping.quantity.as_ref(), header, QUANTITY_HEADERS),
+            column(mapping.price.as_ref(), header, PRICE_HEADERS),
+            column(mapping.date.as_ref(), header, DATE_HEADERS),
+        ) else { continue };
+
+        let charges = match &mapping.charges {
+            Some(columns) => columns.iter().map(|c| csv_import::resolve_column(c, header)).collect::<Result<Vec<_>, _>>()?,
+            None => match find_header(header, TOTAL_CHARGES_HEADERS) {
+                Some(total) => vec![total],
+                None => header.iter().enumerate()
+                    .filter(|(_, h)| CHARGE_HEADERS.contains(&normalize_header(h).as_str()))
+                    .map(|(i, _)| i)
+                    .collect(),
+            },
+        };
+        return Ok((index, Columns {
+            symbol,
+            side: column(mapping.side.as_ref(), header, SIDE_HEADERS)?,
+            quantity,
+            price,
+            charges,
+            date,
+            exchange: column(mapping.exchange.as_ref(), header, EXCHANGE_HEADERS)?,
+            trade_id: column(mapping.trade_id.as_ref(), header, TRADE_ID_HEADERS)?,
+        }));
+    }
+    Err("No trade table found: expected columns for symbol, quantity, price and date".to_string())
+}
+
+/// Brokers write dates every way there is; the time of day is dropped.
+pub fn parse_trade_date(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    normalize_date(raw).or_else(|| {
+        let date = raw.split_whitespace().next()?;
+        ["%d-%m-%Y", "%d/%m/%Y", "%d.%m.%Y", "%Y/%m/%d", "%d-%b-%y", "%d %b %Y", "%d-%m-%y", "%d/%m/%y"].iter()
+            .find_map(|format| chrono::NaiveDate::parse_from_str(date, format).ok())
+            .map(|d| d.format("%Y-%m-%d").to_string())
+    })
+}
+
+fn parse_trade(row: &[String], columns: &Columns, default_exchange: &str) -> Result<NewTrade, String> {
+    let cell = |index: usize| row.get(index).map(|c| c.trim()).unwrap_or_default();
+    let symbol = cell(columns.symbol).to_uppercase();
+    if symbol.is_empty() {
+        return Err("no symbol".to_string());
+    }
+    let quantity = excel::parse_amount(cell(columns.quantity)).ok_or("no quantity")?;
+    let price = excel::parse_amount(cell(columns.price)).ok_or("no price")?;
+    // Without a side column, sells are the negative quantities
+    let side = match columns.side {
+        Some(side) => TradeSide::parse(cell(side)).ok_or_else(|| format!("unknown trade type '{}'", cell(side)))?,
+        None if quantity < 0.0 => TradeSide::Sell,
+        None => TradeSide::Buy,
+    };
+    if quantity == 0.0 {
+        return Err("zero quantity".to_string());
+    }
+    let trade_date = parse_trade_date(cell(columns.date)).ok_or_else(|| format!("unreadable date '{}'", cell(columns.date)))?;
+    let exchange = columns.exchange.map(|e| cell(e).to_uppercase()).filter(|e| !e.is_empty()).unwrap_or_else(|| default_exchange.to_string());
+    Ok(NewTrade {
+        symbol,
+        exchange,
+        side,
+        quantity: quantity.abs(),
+        price: price.abs(),
+        charges: columns.charges.iter().filter_map(|c| excel::parse_amount(cell(*c))).map(f64::abs).sum(),
+        trade_date,
+        trade_id: columns.trade_id.map(|t| cell(t).to_string()).filter(|t| !t.is_empty()),
+        import_key: None,
+    })
+}
+
+/// Hash of a row of `file_name` and its position there. Two fills of the same quantity at the
+/// same price on the same day are separate rows, so they're kept apart; importing the file
+/// again gives the same keys.
+fn import_key(file_name: &str, index: usize, row: &[String]) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(file_name.as_bytes());
+    hasher.update(&(index as u64).to_le_bytes());
+    for cell in row {
+        hasher.update(cell.as_bytes());
+        hasher.update(&[0]);
+    }
+    hasher.finalize().to_hex().to_string()
+}
+
+/// Add `trade` to the ledger unless it's already there, by its trade id or else its import
+/// key; returns 1 when it was added. A trade with neither is always added.
+pub fn insert_trade(conn: &Connection, trade: &NewTrade, broker: Option<&str>, source_file: Option<&str>) -> Result<usize, String> {
+    conn.execute(
+        "INSERT OR IGNORE INTO transactions (symbol, exchange, side, quantity, price, charges, trade_date, trade_id, import_key, broker, source_file, imported_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        params![
+            trade.symbol, trade.exchange, trade.side.as_str(), trade.quantity, trade.price, trade.charges,
+            trade.trade_date, trade.trade_id, trade.import_key, broker, source_file, now_secs(),
+        ],
+    ).map_err(|e| e.to_string())
+}
+
+fn transaction_from_row(row: &Row) -> rusqlite::Result<Transaction> {
+    let side: String = row.get(3)?;
+    Ok(Transaction {
+        id: row.get(0)?,
+        symbol: row.get(1)?,
+        exchange: row.get(2)?,
+        side: TradeSide::parse(&side).unwrap_or(TradeSide::Buy),
+        quantity: row.get(4)?,
+        price: row.get(5)?,
+        charges: row.get(6)?,
+        trade_date: row.get(7)?,
+        trade_id: row.get(8)?,
+        broker: row.get(9)?,
+        source_file: row.get(10)?,
+        imported_at: row.get(11)?,
+    })
+}
+
+pub fn load_transactions(conn: &Connection, symbol: Option<&str>, exchange: Option<&str>) -> Result<Vec<Transaction>, String> {
+    let mut stmt = conn.prepare(
+        "SELECT id, symbol, exchange, side, quantity, price, charges, trade_date, trade_id, broker, source_file, imported_at
+         FROM transactions WHERE (?1 IS NULL OR symbol = ?1) AND (?2 IS NULL OR exchange = ?2)
+         ORDER BY symbol, exchange, trade_date, id"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![symbol, exchange], transaction_from_row).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// The combined factor of `key`'s splits and bonuses effective by `date` that `applied` hasn't
+/// counted yet; 1 when there are none.
+fn due_factor(adjustments: &Adjustments, applied: &mut BTreeMap<(String, String), usize>, key: &(String, String), date: &str) -> f64 {
+    let Some(pending) = adjustments.get(key) else { return 1.0 };
+    let done = applied.entry(key.clone()).or_default();
+    let mut factor = 1.0;
+    while let Some(adjustment) = pending.get(*done).filter(|a| a.ex_date.as_str() <= date) {
+        factor *= adjustment.factor;
+        *done += 1;
+    }
+    factor
+}
+
+/// Shares of `symbol` the ledger held before `date`, in that day's units after splits and bonuses.
+pub fn quantity_held(transactions: &[Transaction], adjustments: &Adjustments, symbol: &str, exchange: &str, date: &str) -> f64 {
+    let key = (symbol.to_string(), exchange.to_string());
+    let mut applied = BTreeMap::new();
+    let mut held = 0.0;
+    for trade in transactions.iter().filter(|t| t.symbol == symbol && t.exchange == exchange && t.trade_date.as_str() < date) {
+        held *= due_factor(adjustments, &mut applied, &key, &trade.trade_date);
+        held += match trade.side {
+            TradeSide::Buy => trade.quantity,
+            TradeSide::Sell => -trade.quantity,
+        };
+    }
+    (held * due_factor(adjustments, &mut applied, &key, date)).max(0.0)
+}
+
+/// Replay the ledger into `holdings` at average cost: buys add their cost (charges included),
+/// sells take out the average; shares sold beyond what's held are set aside as unmatched, not
+/// taken below zero. Splits and bonuses multiply the quantity and leave the amount invested
+/// alone. Realized P&L is the FIFO figure, the same one `get_portfolio_pnl` reports.
+pub fn rebuild_holdings(conn: &mut Connection) -> Result<(), String> {
+    let transactions = load_transactions(conn, None, None)?;
+    let adjustments = corporate_actions::load_adjustments(conn)?;
+    let book = match_fifo(&transactions, &adjustments);
+    let mut applied = BTreeMap::new();
+    let mut positions: BTreeMap<(String, String), Holding> = BTreeMap::new();
+    for trade in &transactions {
+        let key = (trade.symbol.clone(), trade.exchange.clone());
+        let factor = due_factor(&adjustments, &mut applied, &key, &trade.trade_date);
+        let holding = positions.entry(key).or_insert_with(|| Holding {
+            symbol: trade.symbol.clone(),
+            exchange: trade.exchange.clone(),
+            quantity: 0.0,
+            average_cost: 0.0,
+            invested: 0.0,
+            realized_pnl: 0.0,
+            charges: 0.0,
+            unmatched_sell_quantity: 0.0,
+            first_trade_date: trade.trade_date.clone(),
+            last_trade_date: trade.trade_date.clone(),
+        });
+        holding.quantity *= factor;
+        match trade.side {
+            TradeSide::Buy => {
+                holding.invested += trade.quantity * trade.price + trade.charges;
+                holding.quantity += trade.quantity;
+            }
+            TradeSide::Sell => {
+                let average = if holding.quantity > QUANTITY_EPSILON { holding.invested / holding.quantity } else { 0.0 };
+                let sold = trade.quantity.min(holding.quantity);
+                holding.invested = (holding.invested - average * sold).max(0.0);
+                holding.quantity -= sold;
+                if trade.quantity - sold > QUANTITY_EPSILON {
+                    holding.unmatched_sell_quantity += trade.quantity - sold;
+                }
+            }
+        }
+        if holding.quantity.abs() < QUANTITY_EPSILON {
+            holding.quantity = 0.0;
+            holding.invested = 0.0;
+        }
+        holding.average_cost = if holding.quantity > 0.0 { holding.invested / holding.quantity } else { 0.0 };
+        holding.charges += trade.charges;
+        holding.last_trade_date = trade.trade_date.clone();
+    }
+    for (key, holding) in positions.iter_mut() {
+        holding.quantity *= due_factor(&adjustments, &mut applied, key, LATEST_DATE);
+        holding.average_cost = if holding.quantity > 0.0 { holding.invested / holding.quantity } else { 0.0 };
+        holding.realized_pnl = book.realized.iter().filter(|g| g.symbol == key.0 && g.exchange == key.1).map(|g| g.gain).sum();
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM holdings", []).map_err(|e| e.to_string())?;
+    for h in positions.values() {
+        tx.execute(
+            "INSERT INTO holdings (symbol, exchange, quantity, average_cost, invested, realized_pnl, charges, unmatched_sell_quantity, first_trade_date, last_trade_date)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![h.symbol, h.exchange, h.quantity, h.average_cost, h.invested, h.realized_pnl, h.charges, h.unmatched_sell_quantity, h.first_trade_date, h.last_trade_date],
+        ).map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+pub fn load_holdings(conn: &Connection, include_closed: bool) -> Result<Vec<Holding>, String> {
+    let mut stmt = conn.prepare(
+        "SELECT symbol, exchange, quantity, average_cost, invested, realized_pnl, charges, unmatched_sell_quantity, first_trade_date, last_trade_date
+         FROM holdings WHERE ?1 OR quantity != 0 ORDER BY invested DESC, symbol"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![include_closed], |row| {
+        Ok(Holding {
+            symbol: row.get(0)?,
+            exchange: row.get(1)?,
+            quantity: row.get(2)?,
+            average_cost: row.get(3)?,
+            invested: row.get(4)?,
+            realized_pnl: row.get(5)?,
+            charges: row.get(6)?,
+            unmatched_sell_quantity: row.get(7)?,
+            first_trade_date: row.get(8)?,
+            last_trade_date: row.get(9)?,
+        })
+    }).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Buy lots still open after FIFO matching, the gains sells realized against them, and sold
+/// quantity no imported buy covers, per (symbol, exchange) and per sell transaction id.
+#[derive(Default)]
+struct FifoBook {
+    open: BTreeMap<(String, String), VecDeque<Lot>>,
+    realized: Vec<RealizedGain>,
+    unmatched: BTreeMap<(String, String), f64>,
+    unmatched_by_sell: BTreeMap<i64, f64>,
+}
+
+struct Lot {
+    date: String,
+    quantity: f64,
+    /// Per share, the buy's charges spread over it
+    unit_cost: f64,
+}
+
+fn days_between(from: &str, to: &str) -> i64 {
+    match (NaiveDate::parse_from_str(from, "%Y-%m-%d"), NaiveDate::parse_from_str(to, "%Y-%m-%d")) {
+        (Ok(from), Ok(to)) => (to - from).num_days(),
+        _ => 0,
+    }
+}
+
+fn split_lots(lots: &mut VecDeque<Lot>, factor: f64) {
+    for lot in lots.iter_mut() {
+        lot.quantity *= factor;
+        lot.unit_cost /= factor;
+    }
+}
+
+/// Match each sell against the oldest open buys of the same symbol, in trade order, with lots
+/// split up by the splits and bonuses in between.
+fn match_fifo(transactions: &[Transaction], adjustments: &Adjustments) -> FifoBook {
+    let mut book = FifoBook::default();
+    let mut applied = BTreeMap::new();
+    for trade in transactions {
+        let key = (trade.symbol.clone(), trade.exchange.clone());
+        let factor = due_factor(adjustments, &mut applied, &key, &trade.trade_date);
+        let lots = book.open.entry(key.clone()).or_default();
+        split_lots(lots, factor);
+        match trade.side {
+            TradeSide::Buy => lots.push_back(Lot {
+                date: trade.trade_date.clone(),
+                quantity: trade.quantity,
+                unit_cost: trade.price + trade.charges / trade.quantity,
+            }),
+            TradeSide::Sell => {
+                let unit_proceeds = trade.price - trade.charges / trade.quantity;
+                let mut remaining = trade.quantity;
+                while remaining > QUANTITY_EPSILON {
+                    let Some(lot) = lots.front_mut() else { break };
+                    let quantity = remaining.min(lot.quantity);
+                    let holding_days = days_between(&lot.date, &trade.trade_date);
+                    book.realized.push(RealizedGain {
+                        symbol: trade.symbol.clone(),
+                        exchange: trade.exchange.clone(),
+                        quantity,
+                        buy_date: lot.date.clone(),
+                        sell_date: trade.trade_date.clone(),
+                        buy_price: lot.unit_cost,
+                        sell_price: unit_proceeds,
+                        gain: quantity * (unit_proceeds - lot.unit_cost),
+                        holding_days,
+                        long_term: holding_days > LONG_TERM_DAYS,
+                    });
+                    lot.quantity -= quantity;
+                    remaining -= quantity;
+                    if lot.quantity <= QUANTITY_EPSILON {
+                        lots.pop_front();
+                    }
+                }
+                if remaining > QUANTITY_EPSILON {
+                    *book.unmatched.entry(key).or_default() += remaining;
+                    book.unmatched_by_sell.insert(trade.id, remaining);
+                }
+            }
+        }
+    }
+    for (key, lots) in book.open.iter_mut() {
+        split_lots(lots, due_factor(adjustments, &mut applied, key, LATEST_DATE));
+    }
+    book
+}
+
+/// Annualised rate at which `flows` (negative out, positive in) net to zero: Newton's method,
+/// falling back to bisection when it wanders off. None without both an outflow and an inflow.
+fn xirr(flows: &[(NaiveDate, f64)]) -> Option<f64> {
+    if !flows.iter().any(|(_, a)| *a < 0.0) || !flows.iter().any(|(_, a)| *a > 0.0) {
+        return None;
+    }
+    let start = flows.iter().map(|(date, _)| *date).min()?;
+    let years: Vec<(f64, f64)> = flows.iter().map(|(date, amount)| ((*date - start).num_days() as f64 / 365.0, *amount)).collect();
+    let npv = |rate: f64| years.iter().map(|(t, a)| a / (1.0 + rate).powf(*t)).sum::<f64>();
+    let slope = |rate: f64| years.iter().map(|(t, a)| -t * a / (1.0 + rate).powf(t + 1.0)).sum::<f64>();
+
+    let mut rate = 0.1;
+    for _ in 0..XIRR_ITERATIONS {
+        let (value, derivative) = (npv(rate), slope(rate));
+        if derivative == 0.0 || !derivative.is_finite() {
+            break;
+        }
+        let next = rate - value / derivative;
+        if !next.is_finite() || next <= -1.0 {
+            break;
+        }
+        if (next - rate).abs() < XIRR_TOLERANCE {
+            return Some(next);
+        }
+        rate = next;
+    }
+
+    let (mut low, mut high) = (-0.9999, 10.0);
+    if npv(low).signum() == npv(high).signum() {
+        return None;
+    }
+    for _ in 0..XIRR_ITERATIONS {
+        let mid = (low + high) / 2.0;
+        if npv(mid).signum() == npv(low).signum() {
+            low = mid;
+        } else {
+            high = mid;
+        }
+        if high - low < XIRR_TOLERANCE {
+            break;
+        }
+    }
+    Some((low + high) / 2.0)
+}
+
+// Tauri Commands
+/// Import the trades in a broker tradebook or contract note (CSV or Excel) into the ledger and
+/// rebuild holdings. Columns are recognised by their usual headers unless `column_mapping`
+/// names them. Trades already recorded are skipped: by trade id where the file has one, else
+/// by the row they came from, so re-importing the same file adds nothing.
+#[tauri::command]
+pub fn import_trades(
+    app: AppHandle,
+    file_path: String,
+    broker: Option<String>,
+    default_exchange: Option<String>,
+    column_mapping: Option<TradeColumnMapping>,
+) -> Result<TradeImport, String> {
+    let rows = read_rows(&file_path)?;
+    let (header_row, columns) = locate_columns(&rows, &column_mapping.unwrap_or_default())?;
+    let default_exchange = default_exchange.map(|e| e.trim().to_uppercase()).filter(|e| !e.is_empty()).unwrap_or_else(|| "NSE".to_string());
+    let broker = broker.map(|b| b.trim().to_string()).filter(|b| !b.is_empty());
+    let file_name = Path::new(&file_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| file_path.clone());
+
+    let mut skipped = Vec::new();
+    let mut trades = Vec::new();
+    for (index, row) in rows.iter().enumerate().skip(header_row + 1) {
+        // Totals and footers leave the symbol or numbers empty
+        match parse_trade(row, &columns, &default_exchange) {
+            Ok(trade) => trades.push(NewTrade { import_key: Some(import_key(&file_name, index, row)), ..trade }),
+            Err(reason) => skipped.push(format!("Row {}: {}", index + 1, reason)),
+        }
+    }
+    if trades.is_empty() {
+        return Err(format!("No trades found in {}", file_name));
+    }
+
+    let mut conn = db::open_app_db(&app)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut imported = 0;
+    for trade in &trades {
+        imported += insert_trade(&tx, trade, broker.as_deref(), Some(&file_name))?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    rebuild_holdings(&mut conn)?;
+    let holdings = load_holdings(&conn, false)?;
+
+    info!(file = %file_name, imported, duplicates = trades.len() - imported, skipped = skipped.len(), "Imported trades");
+    let _ = app.emit("holdings-changed", &holdings);
+    Ok(TradeImport { file_name, imported, duplicates: trades.len() - imported, skipped, holdings })
+}
+
+/// Current positions, largest first; with `include_closed`, fully sold ones too.
+#[tauri::command]
+pub fn list_holdings(app: AppHandle, include_closed: Option<bool>) -> Result<Vec<Holding>, String> {
+    let conn = db::open_app_db(&app)?;
+    load_holdings(&conn, include_closed.unwrap_or(false))
+}
+
+#[tauri::command]
+pub fn list_transactions(app: AppHandle, symbol: Option<String>, exchange: Option<String>) -> Result<Vec<Transaction>, String> {
+    let conn = db::open_app_db(&app)?;
+    let symbol = symbol.map(|s| s.trim().to_uppercase());
+    let exchange = exchange.map(|e| e.trim().to_uppercase());
+    load_transactions(&conn, symbol.as_deref(), exchange.as_deref())
+}
+
+/// Remove a mis-imported trade; holdings are rebuilt without it.
+#[tauri::command]
+pub fn delete_transaction(app: AppHandle, id: i64) -> Result<Vec<Holding>, String> {
+    let mut conn = db::open_app_db(&app)?;
+    conn.execute("DELETE FROM transactions WHERE id = ?1", params![id]).map_err(|e| e.to_string())?;
+    rebuild_holdings(&mut conn)?;
+    let holdings = load_holdings(&conn, false)?;
+    let _ = app.emit("holdings-changed", &holdings);
+    Ok(holdings)
+}
+
+/// FIFO realized gains per matched lot, optionally for one symbol and sells within `from`..=`to`
+/// (YYYY-MM-DD), e.g. a financial year.
+#[tauri::command]
+pub fn get_realized_gains(
+    app: AppHandle,
+    symbol: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<RealizedGains, String> {
+    let conn = db::open_app_db(&app)?;
+    let symbol = symbol.map(|s| s.trim().to_uppercase());
+    let from = from.map(|d| normalize_date(&d).ok_or_else(|| format!("Invalid date: {}", d))).transpose()?;
+    let to = to.map(|d| normalize_date(&d).ok_or_else(|| format!("Invalid date: {}", d))).transpose()?;
+    // Lots are matched over the whole history; only the reported sells are filtered
+    let adjustments = corporate_actions::load_adjustments(&conn)?;
+    let gains: Vec<RealizedGain> = match_fifo(&load_transactions(&conn, symbol.as_deref(), None)?, &adjustments).realized.into_iter()
+        .filter(|gain| from.as_ref().is_none_or(|from| gain.sell_date >= *from) && to.as_ref().is_none_or(|to| gain.sell_date <= *to))
+        .collect();
+    let short_term = gains.iter().filter(|g| !g.long_term).map(|g| g.gain).sum();
+    let long_term = gains.iter().filter(|g| g.long_term).map(|g| g.gain).sum();
+    Ok(RealizedGains { total: short_term + long_term, short_term, long_term, gains })
+}
+
+/// Per-holding and whole-portfolio P&L from the ledger: FIFO cost of what's still held, realized
+/// gains, unrealized P&L at quotes no older than `max_quote_age_secs`, and the portfolio's XIRR.
+#[tauri::command]
+pub async fn get_portfolio_pnl(app: AppHandle, max_quote_age_secs: Option<u64>) -> Result<PortfolioPnl, String> {
+    let (transactions, adjustments) = {
+        let conn = db::open_app_db(&app)?;
+        (load_transactions(&conn, None, None)?, corporate_actions::load_adjustments(&conn)?)
+    };
+    let book = match_fifo(&transactions, &adjustments);
+    let max_age = max_quote_age_secs.unwrap_or(QUOTE_MAX_AGE_SECS);
+    let today = Local::now().date_naive();
+
+    let mut holdings = Vec::new();
+    for ((symbol, exchange), lots) in &book.open {
+        let key = (symbol.clone(), exchange.clone());
+        let quantity: f64 = lots.iter().map(|lot| lot.quantity).sum();
+        let cost_basis: f64 = lots.iter().map(|lot| lot.quantity * lot.unit_cost).sum();
+        let realized_pnl = book.realized.iter().filter(|g| g.symbol == *symbol && g.exchange == *exchange).map(|g| g.gain).sum();
+        let (last_price, quote_error) = if quantity > QUANTITY_EPSILON {
+            match scraper::get_quote(&app, symbol, exchange, max_age).await {
+                Ok(quote) => (quote.price, None),
+                Err(e) => (None, Some(e)),
+            }
+        } else {
+            (None, None)
+        };
+        let market_value = last_price.map(|price| price * quantity);
+        let unrealized_pnl = market_value.map(|value| value - cost_basis);
+        holdings.push(HoldingPnl {
+            symbol: symbol.clone(),
+            exchange: exchange.clone(),
+            quantity,
+            cost_basis,
+            average_cost: if quantity > QUANTITY_EPSILON { cost_basis / quantity } else { 0.0 },
+            last_price,
+            market_value,
+            unrealized_pnl,
+            unrealized_percent: unrealized_pnl.filter(|_| cost_basis > 0.0).map(|pnl| pnl / cost_basis * 100.0),
+            realized_pnl,
+            unmatched_sell_quantity: book.unmatched.get(&key).copied().unwrap_or(0.0),
+            quote_error,
+        });
+    }
+
+    let open: Vec<&HoldingPnl> = holdings.iter().filter(|h| h.quantity > QUANTITY_EPSILON).collect();
+    let unpriced = open.iter().filter(|h| h.last_price.is_none()).count();
+    let invested = open.iter().map(|h| h.cost_basis).sum();
+    // Unpriced holdings are valued at cost so the total isn't understated
+    let market_value = open.iter().map(|h| h.market_value.unwrap_or(h.cost_basis)).sum();
+    let unrealized_pnl = open.iter().filter_map(|h| h.unrealized_pnl).sum::<f64>();
+    let realized_pnl = book.realized.iter().map(|g| g.gain).sum::<f64>();
+
+    // Every trade is a cash flow on its date; what's still held is a final inflow today. Sells
+    // count only the shares FIFO matched, since unmatched ones were bought outside the ledger
+    let xirr = (unpriced == 0).then(|| {
+        let mut flows: Vec<(NaiveDate, f64)> = transactions.iter()
+            .filter_map(|t| {
+                let date = NaiveDate::parse_from_str(&t.trade_date, "%Y-%m-%d").ok()?;
+                Some(match t.side {
+                    TradeSide::Buy => (date, -(t.quantity * t.price + t.charges)),
+                    TradeSide::Sell => {
+                        let matched = t.quantity - book.unmatched_by_sell.get(&t.id).copied().unwrap_or(0.0);
+                        (date, matched * (t.price - t.charges / t.quantity))
+                    }
+                })
+            })
+            .collect();
+        flows.push((today, market_value));
+        xirr(&flows)
+    }).flatten();
+
+    Ok(PortfolioPnl {
+        holdings,
+        invested,
+        market_value,
+        unrealized_pnl,
+        realized_pnl,
+        total_pnl: unrealized_pnl + realized_pnl,
+        xirr: xirr.map(|rate| rate * 100.0),
+        unpriced,
+        as_of: today.format("%Y-%m-%d").to_string(),
+    })
+}
@@ -0,0 +1,361 @@
+// PPTX - presentation deck (title, key metrics, ratio trends, AI summary) for a completed analysis
+use std::io::Write;
+use tauri::{AppHandle, Manager};
+use zip::write::SimpleFileOptions;
+
+use crate::activity::{self, ActivityKind};
+use crate::formatting::{Formatter, NumberStyle};
+use crate::ollama;
+use crate::ratios::{self, Ratio};
+use crate::report;
+use crate::settings::SettingsState;
+use crate::statements::{self, StoredDocument};
+use crate::usage::{self, UsageKind};
+
+// 16:9, in EMU
+const SLIDE_WIDTH: i64 = 12_192_000;
+const SLIDE_HEIGHT: i64 = 6_858_000;
+const MARGIN: i64 = 600_000;
+const ROW_HEIGHT: i64 = 420_000;
+
+const ACCENT: &str = "2A579E";
+const INK: &str = "21262F";
+const MUTED: &str = "737A87";
+const BAND: &str = "EFF2F7";
+
+const NS: &str = r#"xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main""#;
+
+const GROUP_PROPS: &str = r#"<p:nvGrpSpPr><p:cNvPr id="1" name=""/><p:cNvGrpSpPr/><p:nvPr/></p:nvGrpSpPr><p:grpSpPr><a:xfrm><a:off x="0" y="0"/><a:ext cx="0" cy="0"/><a:chOff x="0" y="0"/><a:chExt cx="0" cy="0"/></a:xfrm></p:grpSpPr>"#;
+
+const THEME: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<a:theme xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" name="Analysis"><a:themeElements>
+<a:clrScheme name="Analysis"><a:dk1><a:srgbClr val="21262F"/></a:dk1><a:lt1><a:srgbClr val="FFFFFF"/></a:lt1><a:dk2><a:srgbClr val="2A579E"/></a:dk2><a:lt2><a:srgbClr val="EFF2F7"/></a:lt2><a:accent1><a:srgbClr val="2A579E"/></a:accent1><a:accent2><a:srgbClr val="9EBAE0"/></a:accent2><a:accent3><a:srgbClr val="1F8040"/></a:accent3><a:accent4><a:srgbClr val="B82E2E"/></a:accent4><a:accent5><a:srgbClr val="737A87"/></a:accent5><a:accent6><a:srgbClr val="E0A030"/></a:accent6><a:hlink><a:srgbClr val="2A579E"/></a:hlink><a:folHlink><a:srgbClr val="6B3FA0"/></a:folHlink></a:clrScheme>
+<a:fontScheme name="Analysis"><a:majorFont><a:latin typeface="Calibri"/><a:ea typeface=""/><a:cs typeface=""/></a:majorFont><a:minorFont><a:latin typeface="Calibri"/><a:ea typeface=""/><a:cs typeface=""/></a:minorFont></a:fontScheme>
+<a:fmtScheme name="Analysis"><a:fillStyleLst><a:solidFill><a:schemeClr val="phClr"/></a:solidFill><a:solidFill><a:schemeClr val="phClr"/></a:solidFill><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:fillStyleLst>
+<a:lnStyleLst><a:ln w="9525"><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:ln><a:ln w="25400"><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:ln><a:ln w="38100"><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:ln></a:lnStyleLst>
+<a:effectStyleLst><a:effectStyle><a:effectLst/></a:effectStyle><a:effectStyle><a:effectLst/></a:effectStyle><a:effectStyle><a:effectLst/></a:effectStyle></a:effectStyleLst>
+<a:bgFillStyleLst><a:solidFill><a:schemeClr val="phClr"/></a:solidFill><a:solidFill><a:schemeClr val="phClr"/></a:solidFill><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:bgFillStyleLst></a:fmtScheme>
+</a:themeElements></a:theme>"#;
+
+fn xml_escape(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_control() || *c == '\t')
+        .collect::<String>()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// One run of text: (text, size in points, bold, hex colour).
+type Run<'a> = (&'a str, u32, bool, &'a str);
+
+fn paragraph(run: Run, align: &str) -> String {
+    let (text, size, bold, color) = run;
+    format!(
+        r#"<a:p><a:pPr algn="{}"/><a:r><a:rPr lang="en-US" sz="{}" b="{}" dirty="0"><a:solidFill><a:srgbClr val="{}"/></a:solidFill></a:rPr><a:t>{}</a:t></a:r></a:p>"#,
+        align, size * 100, if bold { 1 } else { 0 }, color, xml_escape(text)
+    )
+}
+
+/// Builds one slide's shape tree; shape ids must be unique per slide.
+struct Slide {
+    shapes: Vec<String>,
+    next_id: u32,
+}
+
+impl Slide {
+    fn new() -> Self {
+        Self { shapes: Vec::new(), next_id: 2 }
+    }
+
+    fn id(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn rect(&mut self, x: i64, y: i64, cx: i64, cy: i64, fill: &str) {
+        let id = self.id();
+        self.shapes.push(format!(
+            r#"<p:sp><p:nvSpPr><p:cNvPr id="{id}" name="Band {id}"/><p:cNvSpPr/><p:nvPr/></p:nvSpPr><p:spPr><a:xfrm><a:off x="{x}" y="{y}"/><a:ext cx="{cx}" cy="{cy}"/></a:xfrm><a:prstGeom prst="rect"><a:avLst/></a:prstGeom><a:solidFill><a:srgbClr val="{fill}"/></a:solidFill><a:ln><a:noFill/></a:ln></p:spPr></p:sp>"#
+        ));
+    }
+
+    fn text(&mut self, x: i64, y: i64, cx: i64, cy: i64, runs: &[Run]) {
+        let id = self.id();
+        let body: String = runs.iter().map(|run| paragraph(*run, "l")).collect();
+        self.shapes.push(format!(
+            r#"<p:sp><p:nvSpPr><p:cNvPr id="{id}" name="Text {id}"/><p:cNvSpPr txBox="1"/><p:nvPr/></p:nvSpPr><p:spPr><a:xfrm><a:off x="{x}" y="{y}"/><a:ext cx="{cx}" cy="{cy}"/></a:xfrm><a:prstGeom prst="rect"><a:avLst/></a:prstGeom><a:noFill/></p:spPr><p:txBody><a:bodyPr wrap="square" rtlCol="0"><a:normAutofit/></a:bodyPr><a:lstStyle/>{body}</p:txBody></p:sp>"#
+        ));
+    }
+
+    fn title(&mut self, text: &str) {
+        self.rect(0, 0, SLIDE_WIDTH, 120_000, ACCENT);
+        self.text(MARGIN, 300_000, SLIDE_WIDTH - 2 * MARGIN, 700_000, &[(text, 28, true, ACCENT)]);
+    }
+
+    /// First column left-aligned, the rest right-aligned; first row is the header.
+    fn table(&mut self, x: i64, y: i64, widths: &[i64], rows: &[Vec<String>]) {
+        let id = self.id();
+        let grid: String = widths.iter().map(|w| format!(r#"<a:gridCol w="{}"/>"#, w)).collect();
+        let body: String = rows.iter().enumerate().map(|(r, row)| {
+            let cells: String = row.iter().enumerate().map(|(c, cell)| {
+                let (color, fill) = if r == 0 { ("FFFFFF", ACCENT) } else if r % 2 == 0 { (INK, BAND) } else { (INK, "FFFFFF") };
+                format!(
+                    r#"<a:tc><a:txBody><a:bodyPr/><a:lstStyle/>{}</a:txBody><a:tcPr><a:solidFill><a:srgbClr val="{}"/></a:solidFill></a:tcPr></a:tc>"#,
+                    paragraph((cell, 14, r == 0, color), if c == 0 { "l" } else { "r" }),
+                    fill
+                )
+            }).collect();
+            format!(r#"<a:tr h="{}">{}</a:tr>"#, ROW_HEIGHT, cells)
+        }).collect();
+        let cx: i64 = widths.iter().sum();
+        let cy = ROW_HEIGHT * rows.len() as i64;
+        self.shapes.push(format!(
+            r#"<p:graphicFrame><p:nvGraphicFramePr><p:cNvPr id="{id}" name="Table {id}"/><p:cNvGraphicFramePr><a:graphicFrameLocks noGrp="1"/></p:cNvGraphicFramePr><p:nvPr/></p:nvGraphicFramePr><p:xfrm><a:off x="{x}" y="{y}"/><a:ext cx="{cx}" cy="{cy}"/></p:xfrm><a:graphic><a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/table"><a:tbl><a:tblPr firstRow="1" bandRow="1"/><a:tblGrid>{grid}</a:tblGrid>{body}</a:tbl></a:graphicData></a:graphic></p:graphicFrame>"#
+        ));
+    }
+
+    fn into_xml(self) -> String {
+        format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><p:sld {NS}><p:cSld><p:spTree>{GROUP_PROPS}{}</p:spTree></p:cSld><p:clrMapOvr><a:masterClrMapping/></p:clrMapOvr></p:sld>"#,
+            self.shapes.concat()
+        )
+    }
+}
+
+fn title_slide(document: &StoredDocument, fmt: &Formatter) -> Slide {
+    let mut slide = Slide::new();
+    slide.rect(0, 0, SLIDE_WIDTH, SLIDE_HEIGHT / 2, ACCENT);
+    slide.text(MARGIN, 1_700_000, SLIDE_WIDTH - 2 * MARGIN, 900_000, &[("Financial Analysis", 40, true, "FFFFFF")]);
+    slide.text(MARGIN, 2_500_000, SLIDE_WIDTH - 2 * MARGIN, 600_000, &[(&document.filename, 20, false, "FFFFFF")]);
+    let generated = format!("Prepared {}", fmt.date(&chrono::Local::now()));
+    slide.text(MARGIN, 3_800_000, SLIDE_WIDTH - 2 * MARGIN, 500_000, &[(&generated, 16, false, MUTED)]);
+    slide
+}
+
+fn metrics_slide(document: &StoredDocument, fmt: &Formatter) -> Slide {
+    let mut slide = Slide::new();
+    slide.title("Key Metrics");
+    let figures = report::chart_figures(&document.items);
+    if figures.is_empty() {
+        slide.text(MARGIN, 1_300_000, SLIDE_WIDTH - 2 * MARGIN, 600_000, &[("No headline figures were recognised in this document.", 16, false, MUTED)]);
+        return slide;
+    }
+    let mut rows = vec![vec![
+        "Metric".to_string(),
+        fmt.amount_heading("Current Year"),
+        fmt.amount_heading("Previous Year"),
+        "Change".to_string(),
+    ]];
+    for (name, current, previous) in figures {
+        let change = match (current - previous) / previous.abs() * 100.0 {
+            _ if previous == 0.0 => "-".to_string(),
+            change if change > 0.0 => format!("+{}%", fmt.number(change, 1)),
+            change => format!("{}%", fmt.number(change, 1)),
+        };
+        rows.push(vec![name.to_string(), fmt.amount(current), fmt.amount(previous), change]);
+    }
+    slide.table(MARGIN, 1_300_000, &[4_200_000, 2_400_000, 2_400_000, 1_992_000], &rows);
+    slide
+}
+
+fn ratios_slide(ratios: &[Ratio], fmt: &Formatter) -> Slide {
+    let mut slide = Slide::new();
+    slide.title("Ratio Trends");
+    if ratios.is_empty() {
+        slide.text(MARGIN, 1_300_000, SLIDE_WIDTH - 2 * MARGIN, 600_000, &[("Not enough line items were recognised to compute ratios.", 16, false, MUTED)]);
+        return slide;
+    }
+    let mut rows = vec![["Ratio", "Previous Year", "Current Year", "Trend"].map(String::from).to_vec()];
+    for ratio in ratios {
+        let trend = match (ratio.current, ratio.previous) {
+            (Some(c), Some(p)) if c > p => "Up",
+            (Some(c), Some(p)) if c < p => "Down",
+            (Some(_), Some(_)) => "Flat",
+            _ => "-",
+        };
+        rows.push(vec![
+            ratio.name.clone(),
+            fmt.ratio(ratio.previous, &ratio.unit),
+            fmt.ratio(ratio.current, &ratio.unit),
+            trend.to_string(),
+        ]);
+    }
+    slide.table(MARGIN, 1_300_000, &[4_600_000, 2_300_000, 2_300_000, 1_792_000], &rows);
+    slide
+}
+
+fn summary_slide(points: &[String], source: &str) -> Slide {
+    let mut slide = Slide::new();
+    slide.title("Summary");
+    let bullets: Vec<String> = points.iter().map(|p| format!("\u{2022}  {}", p)).collect();
+    let runs: Vec<Run> = bullets.iter().map(|b| (b.as_str(), 18, false, INK)).collect();
+    slide.text(MARGIN, 1_300_000, SLIDE_WIDTH - 2 * MARGIN, 4_600_000, &runs);
+    slide.text(MARGIN, SLIDE_HEIGHT - 700_000, SLIDE_WIDTH - 2 * MARGIN, 400_000, &[(source, 11, false, MUTED)]);
+    slide
+}
+
+/// Plain statements drawn from the ratios when the model isn't available.
+fn fallback_summary(document: &StoredDocument, ratios: &[Ratio], fmt: &Formatter) -> Vec<String> {
+    let mut points: Vec<String> = report::chart_figures(&document.items).into_iter()
+        .filter(|(_, _, previous)| *previous != 0.0)
+        .take(3)
+        .map(|(name, current, previous)| {
+            let change = (current - previous) / previous.abs() * 100.0;
+            let direction = if change >= 0.0 { "rose" } else { "fell" };
+            format!("{} {} {}% to {}", name, direction, fmt.number(change.abs(), 1), fmt.figure(current))
+        })
+        .collect();
+    points.extend(ratios.iter().take(3).filter_map(|ratio| {
+        Some(format!("{} of {}", ratio.name, fmt.ratio(Some(ratio.current?), &ratio.unit)))
+    }));
+    if points.is_empty() {
+        points.push(format!("{} line items were extracted from {}", document.items.len(), document.filename));
+    }
+    points
+}
+
+async fn ai_summary(app: &AppHandle, document: &StoredDocument, ratios: &[Ratio], fmt: &Formatter) -> Result<Vec<String>, String> {
+    let model = {
+        let state = app.state::<SettingsState>();
+        let store = state.read().await;
+        store.get().llm.selected_model.clone()
+    };
+    if model.is_empty() {
+        return Err("No model selected".to_string());
+    }
+
+    let figures: Vec<String> = report::chart_figures(&document.items).into_iter()
+        .map(|(name, current, previous)| format!("{}: current {} / previous {}", name, fmt.figure(current), fmt.figure(previous)))
+        .collect();
+    let ratio_lines: Vec<String> = ratios.iter()
+        .map(|r| format!("{}: current {} / previous {}", r.name, fmt.ratio(r.current, &r.unit), fmt.ratio(r.previous, &r.unit)))
+        .collect();
+    let prompt = format!(
+        "You are preparing an investment committee slide. Using only the figures below, write 4 to 5 short bullet points \
+         (one per line, no numbering, under 20 words each) on performance, balance sheet strength and notable changes.\n\n\
+         Company report: {}\n\nKey figures:\n{}\n\nRatios:\n{}",
+        document.filename, figures.join("\n"), ratio_lines.join("\n")
+    );
+
+    let text = ollama::generate_completion(app.clone(), app.state(), prompt, model, Vec::new()).await?;
+    let points: Vec<String> = text.lines()
+        .map(|line| line.trim().trim_start_matches(['-', '*', '\u{2022}']).trim().to_string())
+        .filter(|line| !line.is_empty())
+        .take(6)
+        .collect();
+    if points.is_empty() {
+        return Err("Empty summary".to_string());
+    }
+    Ok(points)
+}
+
+fn write_deck(path: &str, slides: Vec<Slide>, title: &str) -> Result<(), String> {
+    let file = std::fs::File::create(path).map_err(|e| format!("Cannot create {}: {}", path, e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default();
+    let count = slides.len();
+
+    let slide_overrides: String = (1..=count)
+        .map(|n| format!(r#"<Override PartName="/ppt/slides/slide{}.xml" ContentType="application/vnd.openxmlformats-officedocument.presentationml.slide+xml"/>"#, n))
+        .collect();
+    let slide_ids: String = (1..=count).map(|n| format!(r#"<p:sldId id="{}" r:id="rId{}"/>"#, 255 + n, n + 2)).collect();
+    let slide_rels: String = (1..=count)
+        .map(|n| format!(r#"<Relationship Id="rId{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slide" Target="slides/slide{}.xml"/>"#, n + 2, n))
+        .collect();
+    let created = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+
+    let mut parts: Vec<(String, String)> = vec![
+        ("[Content_Types].xml".into(), format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Override PartName="/ppt/presentation.xml" ContentType="application/vnd.openxmlformats-officedocument.presentationml.presentation.main+xml"/><Override PartName="/ppt/slideMasters/slideMaster1.xml" ContentType="application/vnd.openxmlformats-officedocument.presentationml.slideMaster+xml"/><Override PartName="/ppt/slideLayouts/slideLayout1.xml" ContentType="application/vnd.openxmlformats-officedocument.presentationml.slideLayout+xml"/><Override PartName="/ppt/theme/theme1.xml" ContentType="application/vnd.openxmlformats-officedocument.theme+xml"/>{slide_overrides}<Override PartName="/docProps/core.xml" ContentType="application/vnd.openxmlformats-package.core-properties+xml"/><Override PartName="/docProps/app.xml" ContentType="application/vnd.openxmlformats-officedocument.extended-properties+xml"/></Types>"#
+        )),
+        ("_rels/.rels".into(), r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="ppt/presentation.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/><Relationship Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/extended-properties" Target="docProps/app.xml"/></Relationships>"#.into()),
+        ("docProps/core.xml".into(), format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"><dc:title>{}</dc:title><dcterms:created xsi:type="dcterms:W3CDTF">{created}</dcterms:created></cp:coreProperties>"#,
+            xml_escape(title)
+        )),
+        ("docProps/app.xml".into(), format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/extended-properties"><Application>Financial Calculator</Application><Slides>{count}</Slides></Properties>"#
+        )),
+        ("ppt/presentation.xml".into(), format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><p:presentation {NS}><p:sldMasterIdLst><p:sldMasterId id="2147483648" r:id="rId1"/></p:sldMasterIdLst><p:sldIdLst>{slide_ids}</p:sldIdLst><p:sldSz cx="{SLIDE_WIDTH}" cy="{SLIDE_HEIGHT}"/><p:notesSz cx="6858000" cy="9144000"/></p:presentation>"#
+        )),
+        ("ppt/_rels/presentation.xml.rels".into(), format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slideMaster" Target="slideMasters/slideMaster1.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/theme" Target="theme/theme1.xml"/>{slide_rels}</Relationships>"#
+        )),
+        ("ppt/slideMasters/slideMaster1.xml".into(), format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><p:sldMaster {NS}><p:cSld><p:bg><p:bgRef idx="1001"><a:schemeClr val="bg1"/></p:bgRef></p:bg><p:spTree>{GROUP_PROPS}</p:spTree></p:cSld><p:clrMap bg1="lt1" tx1="dk1" bg2="lt2" tx2="dk2" accent1="accent1" accent2="accent2" accent3="accent3" accent4="accent4" accent5="accent5" accent6="accent6" hlink="hlink" folHlink="folHlink"/><p:sldLayoutIdLst><p:sldLayoutId id="2147483649" r:id="rId1"/></p:sldLayoutIdLst></p:sldMaster>"#
+        )),
+        ("ppt/slideMasters/_rels/slideMaster1.xml.rels".into(), r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slideLayout" Target="../slideLayouts/slideLayout1.xml"/><Relationship Id="rId2" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/theme" Target="../theme/theme1.xml"/></Relationships>"#.into()),
+        ("ppt/slideLayouts/slideLayout1.xml".into(), format!(
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><p:sldLayout {NS} type="blank" preserve="1"><p:cSld name="Blank"><p:spTree>{GROUP_PROPS}</p:spTree></p:cSld><p:clrMapOvr><a:masterClrMapping/></p:clrMapOvr></p:sldLayout>"#
+        )),
+        ("ppt/slideLayouts/_rels/slideLayout1.xml.rels".into(), r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slideMaster" Target="../slideMasters/slideMaster1.xml"/></Relationships>"#.into()),
+        ("ppt/theme/theme1.xml".into(), THEME.into()),
+    ];
+    for (n, slide) in slides.into_iter().enumerate() {
+        parts.push((format!("ppt/slides/slide{}.xml", n + 1), slide.into_xml()));
+        parts.push((
+            format!("ppt/slides/_rels/slide{}.xml.rels", n + 1),
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/slideLayout" Target="../slideLayouts/slideLayout1.xml"/></Relationships>"#.into(),
+        ));
+    }
+
+    for (name, content) in parts {
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(content.as_bytes()).map_err(|e| e.to_string())?;
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn write_presentation(
+    app: &AppHandle,
+    document_id: i64,
+    path: String,
+    include_ai_summary: Option<bool>,
+) -> Result<String, String> {
+    let mut document = statements::load_document(app, document_id)?;
+    if document.items.is_empty() {
+        return Err(format!("Document {} has no extracted items to present", document_id));
+    }
+    let fmt = Formatter::for_document(app, &mut document, &NumberStyle::default())?;
+    let ratios = ratios::for_document(&document);
+
+    let (points, source) = if include_ai_summary.unwrap_or(true) {
+        match ai_summary(app, &document, &ratios, &fmt).await {
+            Ok(points) => (points, "Summary drafted by the local AI model from the extracted figures; review before presenting."),
+            Err(e) => {
+                eprintln!("[PPTX] AI summary unavailable, using computed highlights: {}", e);
+                (fallback_summary(&document, &ratios, &fmt), "Highlights computed from the extracted figures.")
+            }
+        }
+    } else {
+        (fallback_summary(&document, &ratios, &fmt), "Highlights computed from the extracted figures.")
+    };
+
+    let slides = vec![
+        title_slide(&document, &fmt),
+        metrics_slide(&document, &fmt),
+        ratios_slide(&ratios, &fmt),
+        summary_slide(&points, source),
+    ];
+    write_deck(&path, slides, &document.filename)?;
+    eprintln!("[PPTX] Wrote {} for document {}", path, document_id);
+    Ok(path)
+}
+
+// Tauri Commands
+#[tauri::command]
+pub async fn export_analysis_pptx(
+    app: AppHandle,
+    document_id: i64,
+    path: String,
+    include_ai_summary: Option<bool>,
+) -> Result<String, String> {
+    let path = usage::track(&app, UsageKind::Export, write_presentation(&app, document_id, path, include_ai_summary)).await?;
+    activity::record(&app, ActivityKind::ReportGenerated, Some(document_id), &format!("Exported {}", path), None);
+    Ok(path)
+}
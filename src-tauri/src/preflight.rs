@@ -0,0 +1,258 @@
+// Preflight - warms everything a first analysis or chat would otherwise wait on (Python imports,
+// the selected Ollama model, the databases, the scraper sessions) at startup, reporting each
+// stage as it becomes ready so the UI can show a loading sequence instead of failing on first use
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+use crate::app_lock;
+use crate::db;
+use crate::http;
+use crate::ollama;
+use crate::profiles;
+use crate::python_bridge;
+use crate::python_setup;
+use crate::scraper::{self, NativeScraper};
+use crate::settings::{self, SettingsState};
+
+// Loading a large model from disk can take minutes
+const MODEL_LOAD_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreflightStage {
+    Python,
+    Ollama,
+    Database,
+    Scrapers,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StageStatus {
+    Pending,
+    Running,
+    Ready,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StageState {
+    pub stage: PreflightStage,
+    pub status: StageStatus,
+    pub message: String,
+    pub millis: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightReport {
+    pub running: bool,
+    /// Every stage finished without failing
+    pub ready: bool,
+    pub started_at: Option<i64>,
+    pub finished_at: Option<i64>,
+    pub stages: Vec<StageState>,
+}
+
+pub struct Preflight {
+    running: AtomicBool,
+    report: Mutex<PreflightReport>,
+}
+
+impl Preflight {
+    pub fn new() -> Self {
+        Self { running: AtomicBool::new(false), report: Mutex::new(PreflightReport::default()) }
+    }
+
+    fn snapshot(&self) -> PreflightReport {
+        self.report.lock().map(|report| report.clone()).unwrap_or_default()
+    }
+
+    fn update(&self, app: &AppHandle, state: StageState) {
+        if let Ok(mut report) = self.report.lock() {
+            match report.stages.iter_mut().find(|s| s.stage == state.stage) {
+                Some(existing) => *existing = state.clone(),
+                None => report.stages.push(state.clone()),
+            }
+        }
+        let _ = app.emit("preflight-stage", &state);
+    }
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+type Outcome = (StageStatus, String);
+
+/// Import every module the pipeline uses once, so their bytecode and shared libraries are cached
+/// before the first analysis spawns api.py.
+async fn warm_python(app: &AppHandle) -> Outcome {
+    let Some(python) = python_bridge::find_python(&python_bridge::python_settings(app).interpreter_path) else {
+        return (StageStatus::Failed, "Python not found".to_string());
+    };
+    match python_setup::check_modules(app, &python).await {
+        Ok(modules) => {
+            let missing: Vec<&str> = modules.iter()
+                .filter(|module| module.required && !module.installed)
+                .map(|module| module.name.as_str())
+                .collect();
+            if missing.is_empty() {
+                (StageStatus::Ready, format!("{} modules imported", modules.iter().filter(|m| m.installed).count()))
+            } else {
+                (StageStatus::Failed, format!("Required modules missing: {}", missing.join(", ")))
+            }
+        }
+        Err(e) => (StageStatus::Failed, e.to_string()),
+    }
+}
+
+/// Load the selected model into memory; an empty prompt makes Ollama load it without generating.
+async fn preload_model(app: &AppHandle) -> Outcome {
+    let state = app.state::<SettingsState>();
+    let (enabled, llm) = {
+        let store = state.read().await;
+        (store.get().enable_ai, store.get().llm.clone())
+    };
+    if !enabled {
+        return (StageStatus::Skipped, "AI is turned off".to_string());
+    }
+    if llm.selected_model.is_empty() {
+        return (StageStatus::Skipped, "No model selected".to_string());
+    }
+    let bridge_url = match ollama::get_base_url(&state).await {
+        Ok(url) => url,
+        Err(e) => return (StageStatus::Failed, e.to_string()),
+    };
+    let loaded = http::client(app).post(format!("{}/api/generate", bridge_url))
+        .json(&serde_json::json!({
+            "model": llm.selected_model,
+            "prompt": "",
+            "stream": false,
+            "keep_alive": llm.keep_alive,
+        }))
+        .timeout(MODEL_LOAD_TIMEOUT)
+        .send()
+        .await;
+    match loaded {
+        Ok(res) if res.status().is_success() => (StageStatus::Ready, format!("{} loaded", llm.selected_model)),
+        Ok(res) => (StageStatus::Failed, format!("Ollama answered {} loading {}", res.status(), llm.selected_model)),
+        Err(e) => (StageStatus::Failed, format!("Ollama not reachable at {}: {}", bridge_url, e)),
+    }
+}
+
+/// Open (and so migrate) the app database and check the pipeline database is readable.
+fn open_databases(app: &AppHandle) -> Outcome {
+    if app_lock::is_locked(app) {
+        return (StageStatus::Skipped, app_lock::LOCKED_ERROR.to_string());
+    }
+    if let Err(e) = db::open_app_db(app) {
+        return (StageStatus::Failed, e);
+    }
+    let path = profiles::active_db_path(app);
+    if !path.exists() {
+        return (StageStatus::Ready, "App database ready; the first analysis creates the pipeline database".to_string());
+    }
+    let checked = Connection::open(&path).and_then(|conn| {
+        conn.query_row("PRAGMA quick_check", [], |row| row.get::<_, String>(0))
+    });
+    match checked {
+        Ok(result) if result == "ok" => (StageStatus::Ready, "Both databases open and pass a quick check".to_string()),
+        Ok(result) => (StageStatus::Failed, format!("Pipeline database is damaged: {}", result)),
+        Err(e) => (StageStatus::Failed, e.to_string()),
+    }
+}
+
+async fn prime_scrapers(app: &AppHandle) -> Outcome {
+    if settings::is_offline(app) {
+        return (StageStatus::Skipped, "Offline mode is on".to_string());
+    }
+    let native = app.state::<NativeScraper>();
+    match native.prime_sessions(scraper::request_timeout(app)).await {
+        Ok(()) => (StageStatus::Ready, "Market data sessions open".to_string()),
+        // Quotes still work through the fallback providers
+        Err(e) => (StageStatus::Failed, e),
+    }
+}
+
+async fn stage(app: &AppHandle, stage: PreflightStage, work: impl std::future::Future<Output = Outcome>) {
+    let preflight = app.state::<Preflight>();
+    preflight.update(app, StageState { stage, status: StageStatus::Running, message: String::new(), millis: 0 });
+    let started = Instant::now();
+    let (status, message) = work.await;
+    if status == StageStatus::Failed {
+        warn!(stage = ?stage, "Preflight: {}", message);
+    }
+    preflight.update(app, StageState { stage, status, message, millis: started.elapsed().as_millis() as u64 });
+}
+
+/// Run every stage concurrently. A run already in progress is not restarted; its report so far is
+/// returned instead.
+pub async fn run(app: &AppHandle) -> PreflightReport {
+    let preflight = app.state::<Preflight>();
+    if preflight.running.swap(true, Ordering::SeqCst) {
+        return preflight.snapshot();
+    }
+    if let Ok(mut report) = preflight.report.lock() {
+        *report = PreflightReport {
+            running: true,
+            ready: false,
+            started_at: Some(now_secs()),
+            finished_at: None,
+            stages: [PreflightStage::Python, PreflightStage::Ollama, PreflightStage::Database, PreflightStage::Scrapers]
+                .into_iter()
+                .map(|stage| StageState { stage, status: StageStatus::Pending, message: String::new(), millis: 0 })
+                .collect(),
+        };
+    }
+
+    let db_handle = app.clone();
+    tokio::join!(
+        stage(app, PreflightStage::Python, warm_python(app)),
+        stage(app, PreflightStage::Ollama, preload_model(app)),
+        stage(app, PreflightStage::Database, async move {
+            tauri::async_runtime::spawn_blocking(move || open_databases(&db_handle))
+                .await
+                .unwrap_or_else(|e| (StageStatus::Failed, e.to_string()))
+        }),
+        stage(app, PreflightStage::Scrapers, prime_scrapers(app)),
+    );
+
+    let report = match preflight.report.lock() {
+        Ok(mut report) => {
+            report.running = false;
+            report.ready = report.stages.iter().all(|s| s.status != StageStatus::Failed);
+            report.finished_at = Some(now_secs());
+            report.clone()
+        }
+        Err(_) => PreflightReport::default(),
+    };
+    preflight.running.store(false, Ordering::SeqCst);
+    info!(ready = report.ready, "Preflight finished");
+    let _ = app.emit("preflight-finished", &report);
+    report
+}
+
+// Tauri Commands
+/// Warm everything up again, e.g. after fixing what a failed stage reported. Progress arrives as
+/// `preflight-stage` events.
+#[tauri::command]
+pub async fn run_preflight(app: AppHandle) -> Result<PreflightReport, String> {
+    Ok(run(&app).await)
+}
+
+/// Where the startup preflight is, for a window that opened after its events were sent.
+#[tauri::command]
+pub fn get_preflight_status(app: AppHandle) -> Result<PreflightReport, String> {
+    Ok(app.state::<Preflight>().snapshot())
+}
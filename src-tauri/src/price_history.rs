@@ -0,0 +1,176 @@
+// Price History - OHLC candles downloaded once and served from SQLite afterwards
+use chrono::{Duration as ChronoDuration, Local};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+use crate::db;
+use crate::market_providers;
+use crate::scraper::Candle;
+use crate::settings::{self, MarketDataProvider};
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS price_history (
+    symbol TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    interval TEXT NOT NULL,    -- '1d', '1wk', '1mo'
+    date TEXT NOT NULL,        -- YYYY-MM-DD
+    timestamp INTEGER NOT NULL,
+    open REAL NOT NULL,
+    high REAL NOT NULL,
+    low REAL NOT NULL,
+    close REAL NOT NULL,
+    volume REAL,
+    PRIMARY KEY (symbol, exchange, interval, date)
+);
+CREATE TABLE IF NOT EXISTS price_history_meta (
+    symbol TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    interval TEXT NOT NULL,
+    covered_from TEXT NOT NULL, -- earliest date a download has covered
+    fetched_at INTEGER NOT NULL,
+    PRIMARY KEY (symbol, exchange, interval)
+);
+";
+
+// Stored series younger than this are served without re-downloading
+const STALE_AFTER_SECS: i64 = 6 * 60 * 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoricalPrices {
+    pub symbol: String,
+    pub exchange: String,
+    pub range: String,
+    pub interval: String,
+    pub candles: Vec<Candle>,
+    pub cached: bool,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Map a user range ("1m", "6mo", "1y", "max") to Yahoo's name and the first date it covers.
+fn parse_range(range: &str) -> Result<(&'static str, String), String> {
+    let (yahoo, days) = match range.trim().to_lowercase().as_str() {
+        "1m" | "1mo" => ("1mo", Some(31)),
+        "3m" | "3mo" => ("3mo", Some(92)),
+        "6m" | "6mo" => ("6mo", Some(183)),
+        "1y" => ("1y", Some(366)),
+        "2y" => ("2y", Some(731)),
+        "5y" => ("5y", Some(1827)),
+        "10y" => ("10y", Some(3653)),
+        "max" => ("max", None),
+        other => return Err(format!("Unsupported range: {}", other)),
+    };
+    let start = match days {
+        Some(days) => (Local::now().date_naive() - ChronoDuration::days(days)).format("%Y-%m-%d").to_string(),
+        None => "0000-01-01".to_string(),
+    };
+    Ok((yahoo, start))
+}
+
+fn parse_interval(interval: &str) -> Result<&'static str, String> {
+    match interval.trim().to_lowercase().as_str() {
+        "1d" | "day" | "daily" => Ok("1d"),
+        "1wk" | "week" | "weekly" => Ok("1wk"),
+        "1mo" | "month" | "monthly" => Ok("1mo"),
+        other => Err(format!("Unsupported interval: {}", other)),
+    }
+}
+
+fn is_covered(conn: &Connection, symbol: &str, exchange: &str, interval: &str, start: &str) -> Result<bool, String> {
+    let meta: Option<(String, i64)> = conn.query_row(
+        "SELECT covered_from, fetched_at FROM price_history_meta WHERE symbol = ?1 AND exchange = ?2 AND interval = ?3",
+        params![symbol, exchange, interval],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    ).optional().map_err(|e| e.to_string())?;
+
+    Ok(match meta {
+        Some((covered_from, fetched_at)) => covered_from.as_str() <= start && now_secs() - fetched_at < STALE_AFTER_SECS,
+        None => false,
+    })
+}
+
+fn store_candles(conn: &mut Connection, symbol: &str, exchange: &str, interval: &str, start: &str, candles: &[Candle]) -> Result<(), String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for c in candles {
+        tx.execute(
+            "INSERT OR REPLACE INTO price_history (symbol, exchange, interval, date, timestamp, open, high, low, close, volume)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            params![symbol, exchange, interval, c.date, c.timestamp, c.open, c.high, c.low, c.close, c.volume],
+        ).map_err(|e| e.to_string())?;
+    }
+    tx.execute(
+        "INSERT INTO price_history_meta (symbol, exchange, interval, covered_from, fetched_at) VALUES (?1, ?2, ?3, ?4, ?5)
+         ON CONFLICT(symbol, exchange, interval) DO UPDATE SET
+             covered_from = MIN(covered_from, excluded.covered_from),
+             fetched_at = excluded.fetched_at",
+        params![symbol, exchange, interval, start, now_secs()],
+    ).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+pub fn load_candles(conn: &Connection, symbol: &str, exchange: &str, interval: &str, start: &str) -> Result<Vec<Candle>, String> {
+    let mut stmt = conn.prepare(
+        "SELECT date, timestamp, open, high, low, close, volume FROM price_history
+         WHERE symbol = ?1 AND exchange = ?2 AND interval = ?3 AND date >= ?4 ORDER BY date ASC"
+    ).map_err(|e| e.to_string())?;
+
+    let rows = stmt.query_map(params![symbol, exchange, interval, start], |row| {
+        Ok(Candle {
+            date: row.get(0)?,
+            timestamp: row.get(1)?,
+            open: row.get(2)?,
+            high: row.get(3)?,
+            low: row.get(4)?,
+            close: row.get(5)?,
+            volume: row.get(6)?,
+        })
+    }).map_err(|e| e.to_string())?;
+
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+// Tauri Commands
+#[tauri::command]
+pub async fn get_historical_prices(
+    app: AppHandle,
+    symbol: String,
+    exchange: String,
+    range: Option<String>,
+    interval: Option<String>,
+    provider: Option<MarketDataProvider>,
+) -> Result<HistoricalPrices, String> {
+    let symbol = symbol.trim().to_uppercase();
+    let exchange = exchange.trim().to_uppercase();
+    let range = range.unwrap_or_else(|| "1y".to_string());
+    let (yahoo_range, start) = parse_range(&range)?;
+    let interval = parse_interval(interval.as_deref().unwrap_or("1d"))?;
+
+    let mut conn = db::open_app_db(&app)?;
+    let cached = is_covered(&conn, &symbol, &exchange, interval, &start)?;
+
+    if !cached {
+        settings::ensure_online(&app)?;
+        let provider = market_providers::resolve(&app, provider);
+        eprintln!("[PriceHistory] Downloading {} {} ({} / {}) from {}", symbol, exchange, yahoo_range, interval, provider.as_str());
+        let candles = market_providers::fetch_history(&app, provider, &symbol, &exchange, yahoo_range, interval).await?;
+        store_candles(&mut conn, &symbol, &exchange, interval, &start, &candles)?;
+    }
+
+    let candles = load_candles(&conn, &symbol, &exchange, interval, &start)?;
+    Ok(HistoricalPrices {
+        symbol,
+        exchange,
+        range,
+        interval: interval.to_string(),
+        candles,
+        cached,
+    })
+}
@@ -0,0 +1,313 @@
+// Profiles - named workspaces bundling settings, database and chat history
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::market_cache::MarketCache;
+use crate::settings::{self, SettingsState, SettingsStore};
+
+pub const DEFAULT_PROFILE_ID: &str = "default";
+const DB_FILENAME: &str = "extracted_data.db";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Profile {
+    pub id: String,
+    pub name: String,
+    pub created_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfileIndex {
+    active: String,
+    profiles: Vec<Profile>,
+}
+
+impl Default for ProfileIndex {
+    fn default() -> Self {
+        Self {
+            active: DEFAULT_PROFILE_ID.to_string(),
+            profiles: vec![Profile {
+                id: DEFAULT_PROFILE_ID.to_string(),
+                name: "Default".to_string(),
+                created_at: now_secs(),
+            }],
+        }
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Turn a display name ("Client A") into a filesystem-safe id ("client-a").
+fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_matches('-').to_string()
+}
+
+/// The database Python has always used: extracted_data.db in the project root.
+fn legacy_db_path() -> PathBuf {
+    let candidates = vec![
+        PathBuf::from(DB_FILENAME),          // From project root (tauri dev)
+        PathBuf::from("..").join(DB_FILENAME), // From src-tauri
+    ];
+
+    let path = candidates
+        .into_iter()
+        .find(|p| p.exists())
+        .unwrap_or_else(|| PathBuf::from(DB_FILENAME));
+
+    fs::canonicalize(&path).unwrap_or(path)
+}
+
+/// Where one profile keeps its files.
+#[derive(Debug, Clone)]
+pub struct ProfileFiles {
+    pub settings: PathBuf,
+    pub db: PathBuf,
+    pub app_db: PathBuf,
+    pub chat_history: PathBuf,
+}
+
+pub struct ProfileManager {
+    app_dir: PathBuf,
+    index_path: PathBuf,
+    index: ProfileIndex,
+}
+
+impl ProfileManager {
+    pub fn new(app_handle: &AppHandle) -> Result<Self, String> {
+        let app_dir = app_handle.path().app_data_dir()
+            .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+        fs::create_dir_all(&app_dir).map_err(|e| e.to_string())?;
+
+        let index_path = app_dir.join("profiles.json");
+        let mut index = if index_path.exists() {
+            let content = fs::read_to_string(&index_path).map_err(|e| e.to_string())?;
+            serde_json::from_str(&content).unwrap_or_default()
+        } else {
+            ProfileIndex::default()
+        };
+
+        // Never end up without a usable active profile
+        if !index.profiles.iter().any(|p| p.id == index.active) {
+            index.active = DEFAULT_PROFILE_ID.to_string();
+        }
+        if !index.profiles.iter().any(|p| p.id == DEFAULT_PROFILE_ID) {
+            index.profiles.insert(0, ProfileIndex::default().profiles.remove(0));
+        }
+
+        Ok(Self { app_dir, index_path, index })
+    }
+
+    pub fn list(&self) -> &[Profile] {
+        &self.index.profiles
+    }
+
+    pub fn active(&self) -> &Profile {
+        self.index.profiles.iter()
+            .find(|p| p.id == self.index.active)
+            .unwrap_or(&self.index.profiles[0])
+    }
+
+    /// The default profile keeps the original file locations so existing installs
+    /// carry on unchanged; every other profile lives under `profiles/<id>/`.
+    fn profile_dir(&self, id: &str) -> PathBuf {
+        if id == DEFAULT_PROFILE_ID {
+            self.app_dir.clone()
+        } else {
+            self.app_dir.join("profiles").join(id)
+        }
+    }
+
+    /// App-owned state (caches, watchlists, ...) lives in app_data.db, apart from
+    /// extracted_data.db, which the Python pipeline wipes at the start of each analysis.
+    pub fn files(&self, id: &str) -> ProfileFiles {
+        let dir = self.profile_dir(id);
+        ProfileFiles {
+            settings: dir.join("settings.json"),
+            db: if id == DEFAULT_PROFILE_ID { legacy_db_path() } else { dir.join(DB_FILENAME) },
+            app_db: dir.join("app_data.db"),
+            chat_history: dir.join("chat_history"),
+        }
+    }
+
+    pub fn settings_path(&self) -> PathBuf {
+        self.files(&self.index.active).settings
+    }
+
+    pub fn db_path(&self) -> PathBuf {
+        self.files(&self.index.active).db
+    }
+
+    pub fn app_db_path(&self) -> PathBuf {
+        self.files(&self.index.active).app_db
+    }
+
+    pub fn chat_history_dir(&self) -> PathBuf {
+        self.files(&self.index.active).chat_history
+    }
+
+    pub fn reports_dir(&self) -> PathBuf {
+        self.profile_dir(&self.index.active).join("reports")
+    }
+
+    pub fn session_path(&self) -> PathBuf {
+        self.profile_dir(&self.index.active).join("session.json")
+    }
+
+    pub fn create(&mut self, name: &str) -> Result<Profile, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Profile name cannot be empty".to_string());
+        }
+        if self.index.profiles.iter().any(|p| p.name.eq_ignore_ascii_case(name)) {
+            return Err(format!("A profile named '{}' already exists", name));
+        }
+
+        let base = slugify(name);
+        let base = if base.is_empty() { "profile".to_string() } else { base };
+        let mut id = base.clone();
+        let mut n = 2;
+        while self.index.profiles.iter().any(|p| p.id == id) {
+            id = format!("{}-{}", base, n);
+            n += 1;
+        }
+
+        let dir = self.profile_dir(&id);
+        fs::create_dir_all(dir.join("chat_history")).map_err(|e| e.to_string())?;
+
+        let profile = Profile { id, name: name.to_string(), created_at: now_secs() };
+        self.index.profiles.push(profile.clone());
+        self.save()?;
+        Ok(profile)
+    }
+
+    pub fn set_active(&mut self, id: &str) -> Result<Profile, String> {
+        let profile = self.index.profiles.iter()
+            .find(|p| p.id == id)
+            .cloned()
+            .ok_or_else(|| format!("Unknown profile: {}", id))?;
+
+        fs::create_dir_all(self.profile_dir(id)).map_err(|e| e.to_string())?;
+        self.index.active = profile.id.clone();
+        self.save()?;
+        Ok(profile)
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&self.index).map_err(|e| e.to_string())?;
+        settings::write_atomic(&self.index_path, json.as_bytes())
+    }
+}
+
+/// Database path of the active profile, for anything that opens extracted_data.db.
+pub fn active_db_path(app: &AppHandle) -> PathBuf {
+    let state = app.state::<std::sync::Mutex<ProfileManager>>();
+    let manager = state.lock().unwrap();
+    manager.db_path()
+}
+
+pub fn active_app_db_path(app: &AppHandle) -> PathBuf {
+    let state = app.state::<std::sync::Mutex<ProfileManager>>();
+    let manager = state.lock().unwrap();
+    manager.app_db_path()
+}
+
+pub fn active_profile_id(app: &AppHandle) -> String {
+    let state = app.state::<std::sync::Mutex<ProfileManager>>();
+    let manager = state.lock().unwrap();
+    manager.active().id.clone()
+}
+
+pub fn active_chat_history_dir(app: &AppHandle) -> PathBuf {
+    let state = app.state::<std::sync::Mutex<ProfileManager>>();
+    let manager = state.lock().unwrap();
+    manager.chat_history_dir()
+}
+
+/// Every profile's files, by profile id, the active one first.
+pub fn all_profile_files(app: &AppHandle) -> Vec<(String, ProfileFiles)> {
+    let state = app.state::<std::sync::Mutex<ProfileManager>>();
+    let manager = state.lock().unwrap();
+    let active = manager.active().id.clone();
+    let mut ids: Vec<String> = manager.list().iter().map(|p| p.id.clone()).filter(|id| *id != active).collect();
+    ids.insert(0, active);
+    ids.into_iter().map(|id| {
+        let files = manager.files(&id);
+        (id, files)
+    }).collect()
+}
+
+pub fn active_reports_dir(app: &AppHandle) -> PathBuf {
+    let state = app.state::<std::sync::Mutex<ProfileManager>>();
+    let manager = state.lock().unwrap();
+    manager.reports_dir()
+}
+
+pub fn active_session_path(app: &AppHandle) -> PathBuf {
+    let state = app.state::<std::sync::Mutex<ProfileManager>>();
+    let manager = state.lock().unwrap();
+    manager.session_path()
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn list_profiles(
+    state: tauri::State<'_, std::sync::Mutex<ProfileManager>>,
+) -> Result<serde_json::Value, String> {
+    let manager = state.lock().map_err(|e| e.to_string())?;
+    Ok(serde_json::json!({
+        "active": manager.active().id,
+        "profiles": manager.list(),
+    }))
+}
+
+#[tauri::command]
+pub fn create_profile(
+    state: tauri::State<'_, std::sync::Mutex<ProfileManager>>,
+    name: String,
+) -> Result<Profile, String> {
+    let mut manager = state.lock().map_err(|e| e.to_string())?;
+    manager.create(&name)
+}
+
+#[tauri::command]
+pub fn switch_profile(
+    app: AppHandle,
+    profiles: tauri::State<'_, std::sync::Mutex<ProfileManager>>,
+    settings: tauri::State<'_, SettingsState>,
+    id: String,
+) -> Result<Profile, String> {
+    let (profile, settings_path) = {
+        let mut manager = profiles.lock().map_err(|e| e.to_string())?;
+        let profile = manager.set_active(&id)?;
+        (profile, manager.settings_path())
+    };
+
+    // Swap the active settings so every command picks up the new profile
+    let new_store = SettingsStore::new(settings_path)?;
+    {
+        let mut store = settings::blocking_write(&settings);
+        *store = new_store;
+    }
+    app.state::<MarketCache>().clear_memory();
+
+    eprintln!("[Profiles] Switched to profile: {}", profile.name);
+    let _ = app.emit("profile-switched", &profile);
+    Ok(profile)
+}
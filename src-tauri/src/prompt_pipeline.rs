@@ -0,0 +1,139 @@
+// Prompt pipeline - settings-driven stages around chat: before sending, masks personal
+// identifiers and rewrites Indian-format numbers; after the reply, pulls out JSON blocks and
+// reduces markdown to plain text
+use regex::{Captures, Regex};
+use tauri::{AppHandle, Manager};
+use tracing::debug;
+
+use crate::ollama::ChatRequest;
+use crate::settings::{self, PromptPipelineSettings, SettingsState};
+
+fn pipeline_settings(app: &AppHandle) -> PromptPipelineSettings {
+    let state = app.state::<SettingsState>();
+    let pipeline = settings::blocking_read(&state).get().prompt_pipeline.clone();
+    pipeline
+}
+
+fn regex(pattern: &str) -> Regex {
+    Regex::new(pattern).expect("valid pattern")
+}
+
+/// Mask identifiers; most specific patterns first so a card number isn't half-read as Aadhaar.
+fn redact_pii(text: &str) -> String {
+    let patterns = [
+        (r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}", "[EMAIL]"),
+        (r"\b[A-Z]{5}[0-9]{4}[A-Z]\b", "[PAN]"),
+        (r"\b[A-Z]{4}0[A-Z0-9]{6}\b", "[IFSC]"),
+        (r"\b\d{4}[ -]?\d{4}[ -]?\d{4}[ -]?\d{4}\b", "[CARD]"),
+        (r"\b[2-9]\d{3}[ -]?\d{4}[ -]?\d{4}\b", "[AADHAAR]"),
+        (r"(?:\+91[ -]?|\b0?)[6-9]\d{9}\b", "[PHONE]"),
+    ];
+    let mut text = text.to_string();
+    for (pattern, replacement) in patterns {
+        text = regex(pattern).replace_all(&text, replacement).into_owned();
+    }
+    // Bare digit runs are usually amounts, so account numbers only next to their label
+    regex(r"(?i)(\b(?:a/c|acct|account)(?:\s*(?:no|number|#))?\.?\s*[:\-]?\s*)\d{9,18}\b")
+        .replace_all(&text, "${1}[ACCOUNT]")
+        .into_owned()
+}
+
+fn format_number(value: f64) -> String {
+    let text = format!("{:.2}", value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// "1,23,45,678" and "12,345,678" -> "12345678"; "2.5 crore" -> "25000000".
+fn normalize_indian_numbers(text: &str) -> String {
+    let grouped = regex(r"\b\d{1,3}(?:,\d{2,3})+(?:\.\d+)?\b").replace_all(text, |caps: &Captures| {
+        let number = &caps[0];
+        let integer = number.split('.').next().unwrap_or(number);
+        let groups: Vec<&str> = integer.split(',').skip(1).collect();
+        let (last, middle) = groups.split_last().expect("at least one group");
+        let indian = last.len() == 3 && middle.iter().all(|g| g.len() == 2);
+        let western = groups.iter().all(|g| g.len() == 3);
+        if indian || western { number.replace(',', "") } else { number.to_string() }
+    });
+    regex(r"(?i)\b(\d+(?:\.\d+)?)\s*(lakhs?|lacs?|crores?|cr)\b\.?").replace_all(&grouped, |caps: &Captures| {
+        let Ok(value) = caps[1].parse::<f64>() else { return caps[0].to_string() };
+        let scale = if caps[2].to_lowercase().starts_with('c') { 1e7 } else { 1e5 };
+        format_number(value * scale)
+    }).into_owned()
+}
+
+/// Fenced ```json blocks, or the whole reply / its outermost braces when there are none.
+fn extract_json(text: &str) -> Vec<serde_json::Value> {
+    let fenced: Vec<serde_json::Value> = regex(r"(?s)```(?:json)?\s*\n(.*?)```")
+        .captures_iter(text)
+        .filter_map(|caps| serde_json::from_str(caps[1].trim()).ok())
+        .collect();
+    if !fenced.is_empty() {
+        return fenced;
+    }
+    if let Ok(value) = serde_json::from_str(text.trim()) {
+        return vec![value];
+    }
+    let (Some(start), Some(end)) = (text.find(['{', '[']), text.rfind(['}', ']'])) else { return Vec::new() };
+    if start >= end {
+        return Vec::new();
+    }
+    serde_json::from_str(&text[start..=end]).map(|value| vec![value]).unwrap_or_default()
+}
+
+fn strip_markdown(text: &str) -> String {
+    let mut lines = Vec::new();
+    for line in text.lines() {
+        let trimmed = line.trim();
+        // Fences, horizontal rules and table separators carry no text
+        if trimmed.starts_with("```") || regex(r"^(?:-{3,}|\*{3,}|_{3,})$").is_match(trimmed) || regex(r"^\|?[\s:|-]+\|[\s:|-]*$").is_match(trimmed) {
+            continue;
+        }
+        let mut line = regex(r"^\s{0,3}#{1,6}\s+").replace(line, "").into_owned();
+        line = regex(r"^(\s*)>\s?").replace(&line, "$1").into_owned();
+        line = regex(r"^(\s*)[*+]\s+").replace(&line, "$1- ").into_owned();
+        if line.trim_start().starts_with('|') {
+            line = line.trim().trim_matches('|').split('|').map(str::trim).collect::<Vec<_>>().join("\t");
+        }
+        line = regex(r"!?\[([^\]]*)\]\([^)]*\)").replace_all(&line, "$1").into_owned();
+        line = regex(r"(\*\*|__)(.+?)(\*\*|__)").replace_all(&line, "$2").into_owned();
+        line = regex(r"\*([^*\s][^*]*)\*").replace_all(&line, "$1").into_owned();
+        line = regex(r"`([^`]*)`").replace_all(&line, "$1").into_owned();
+        lines.push(line);
+    }
+    lines.join("\n").trim().to_string()
+}
+
+/// Run the enabled pre-send stages over every message of the request.
+pub fn prepare(app: &AppHandle, mut request: ChatRequest) -> Result<ChatRequest, String> {
+    let settings = pipeline_settings(app);
+    if !settings.redact_pii && !settings.normalize_indian_numbers {
+        return Ok(request);
+    }
+    for message in &mut request.messages {
+        if settings.redact_pii {
+            message.content = redact_pii(&message.content);
+        }
+        if settings.normalize_indian_numbers {
+            message.content = normalize_indian_numbers(&message.content);
+        }
+    }
+    debug!(redact = settings.redact_pii, normalize = settings.normalize_indian_numbers, "Prepared chat prompt");
+    Ok(request)
+}
+
+/// Run the enabled reply stages over an Ollama /api/chat response: JSON blocks go to `json`,
+/// `message.content` is replaced by plain text. Returns whether any stage ran.
+pub fn finish(app: &AppHandle, reply: &mut serde_json::Value) -> Result<bool, String> {
+    let settings = pipeline_settings(app);
+    if !settings.extract_json && !settings.strip_markdown {
+        return Ok(false);
+    }
+    let content = reply["message"]["content"].as_str().unwrap_or("").to_string();
+    if settings.extract_json {
+        reply["json"] = serde_json::Value::Array(extract_json(&content));
+    }
+    if settings.strip_markdown && reply["message"].is_object() {
+        reply["message"]["content"] = strip_markdown(&content).into();
+    }
+    Ok(true)
+}
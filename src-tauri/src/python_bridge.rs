@@ -1,15 +1,48 @@
 // Python Bridge - Direct Python invocation with streaming progress support
-use std::io::{BufRead, BufReader, Write, Read};
-use std::process::{Command, Stdio};
-use std::path::PathBuf;
+use std::process::Stdio;
+use std::path::{Path, PathBuf};
 use std::env;
 use std::time::{Duration, Instant};
-use std::thread;
 use serde::{Deserialize, Serialize};
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::OwnedSemaphorePermit;
+use tracing::{debug, error, info, warn};
 
 use rusqlite::{Connection, params};
 
+use crate::activity::{self, ActivityKind};
+use crate::app_lock;
+use crate::bank_statements;
+use crate::chunks;
+use crate::db;
+use crate::document_locks::DocumentLocks;
+use crate::error::AppError;
+use crate::gst_invoices;
+use crate::integrity;
+use crate::jobs::{self, JobSource};
+use crate::numbers;
+use crate::ocr;
+use crate::page_ranges::{self, SplitDocument};
+use crate::parse_cache;
+use crate::partial_results::{PartialAnalysis, Recorder};
+use crate::pipeline_profile::{Profiler, Stage};
+use crate::profiles;
+use crate::python_setup;
+use crate::recent_files::{self, RecentStatus};
+use crate::market_cache::MarketCache;
+use crate::market_providers;
+use crate::metric_cache::MetricCache;
+use crate::scraper::{self, NativeScraper};
+use crate::settings::{self, MarketDataProvider, PythonSettings, ScraperSettings, SettingsState};
+use crate::statements::{self, StoredDocument};
+use crate::tasks::{self, TaskHandle, TaskKind};
+use crate::terminology;
+use crate::timeouts::{self, Operation};
+use crate::usage::{self, UsageKind};
+use crate::windows;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PythonRequest {
     pub command: String,
@@ -20,6 +53,41 @@ pub struct PythonRequest {
     pub file_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub options: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    /// The user's label mappings from `terminology`, applied over the built-in terms
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mappings: Option<serde_json::Value>,
+}
+
+// Error codes shared with python/api.py for encrypted PDFs
+pub const PDF_PASSWORD_REQUIRED: &str = "PDF_PASSWORD_REQUIRED";
+pub const PDF_DECRYPTION_FAILED: &str = "PDF_DECRYPTION_FAILED";
+// python/api.py speaks a different protocol version than this build
+pub const BRIDGE_OUTDATED: &str = "BRIDGE_OUTDATED";
+
+/// Bump together with `BRIDGE_PROTOCOL_VERSION` in python/api.py whenever the
+/// request or response shapes exchanged with the script change.
+pub const BRIDGE_PROTOCOL_VERSION: u64 = 3;
+
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(30);
+// How long a script may take to exit once it has answered
+const CLEANUP_GRACE: Duration = Duration::from_secs(5);
+// How often a running analysis looks for cancellation
+const CANCEL_POLL: Duration = Duration::from_millis(250);
+// Metric cache key for python/api.py `calculate_metrics`
+const METRIC_SET: &str = "python_calculate_metrics";
+
+/// Result of the `get_bridge_version` exchange that opens every api.py session.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BridgeVersion {
+    pub expected: u64,
+    /// None when the script predates the handshake
+    pub script: Option<u64>,
+    pub python_version: Option<String>,
+    pub script_path: String,
+    pub compatible: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -36,6 +104,16 @@ pub struct PythonResponse {
     pub message: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// Machine-readable failure reason, e.g. `PDF_DECRYPTION_FAILED`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// Set when the result was replayed from the parse cache
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_hit: Option<bool>,
+    /// The pipeline document the parse was stored as; none when nothing was stored, e.g. on a
+    /// replay from the parse cache
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub doc_id: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -52,68 +130,61 @@ pub struct ProgressUpdate {
     pub partial_text: Option<String>,
 }
 
-fn find_python() -> Option<String> {
-    for cmd in &["python3", "python"] {
-        if Command::new(cmd)
-            .arg("--version")
-            .stdout(Stdio::null())
-            .stderr(Stdio::null())
-            .status()
-            .is_ok()
-        {
-            return Some(cmd.to_string());
-        }
-    }
-    None
+/// What `reanalyze_pages` changed in a stored document.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PageReanalysis {
+    pub document_id: i64,
+    pub pages: Vec<u32>,
+    pub removed_items: usize,
+    pub added_items: usize,
+    /// The document as it now stands
+    pub document: StoredDocument,
 }
 
-fn run_python_script_with_timeout(script: String, timeout_secs: u64) -> Result<String, String> {
-    let python_cmd = find_python().ok_or("Python not found")?;
-    
-    let mut child = Command::new(&python_cmd)
+pub fn python_settings(app: &AppHandle) -> PythonSettings {
+    let state = app.state::<SettingsState>();
+    let store = settings::blocking_read(&state);
+    store.get().python.clone()
+}
+
+fn scraper_settings(app: &AppHandle) -> ScraperSettings {
+    let state = app.state::<SettingsState>();
+    let store = settings::blocking_read(&state);
+    store.get().scraper.clone()
+}
+
+/// A supported interpreter, the user-configured one first; see `python_setup` for the search.
+pub fn find_python(interpreter_path: &str) -> Option<String> {
+    python_setup::find(interpreter_path)
+}
+
+async fn run_python_script_with_timeout(app: &AppHandle, script: String, operation: Operation) -> Result<String, AppError> {
+    let python_cmd = find_python(&python_settings(app).interpreter_path).ok_or(AppError::PythonNotFound)?;
+    let db_path = db::pipeline_db_path(app)?;
+    let _slot = jobs::python_slot(app).await;
+
+    let child = Command::new(&python_cmd)
         .arg("-c")
         .arg(&script)
+        .env("FINCALC_DB_PATH", db_path)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
+        .kill_on_drop(true)
         .spawn()
-        .map_err(|e| format!("Failed to spawn Python: {}", e))?;
-        
-    let start = Instant::now();
-    let timeout = Duration::from_secs(timeout_secs);
-    
-    loop {
-        match child.try_wait() {
-            Ok(Some(status)) => {
-                if !status.success() {
-                    let mut stderr = String::new();
-                    if let Some(mut err_pipe) = child.stderr.take() {
-                         let _ = err_pipe.read_to_string(&mut stderr);
-                    }
-                    return Err(format!("Script failed: {}", stderr));
-                }
-                break;
-            },
-            Ok(None) => {
-                if start.elapsed() > timeout {
-                    let _ = child.kill();
-                    return Err("Operation timed out".to_string());
-                }
-                thread::sleep(Duration::from_millis(50));
-            },
-            Err(e) => return Err(format!("Error waiting for process: {}", e)),
-        }
-    }
-    
-    let mut stdout_str = String::new();
-    if let Some(mut out_pipe) = child.stdout.take() {
-        out_pipe.read_to_string(&mut stdout_str)
-            .map_err(|e| format!("Failed to read output: {}", e))?;
+        .map_err(|e| AppError::Python(format!("Failed to spawn Python: {}", e)))?;
+
+    // Timing out drops the child, which kills it
+    let output = timeouts::run(app, operation, async {
+        child.wait_with_output().await.map_err(|e| AppError::Python(format!("Error waiting for process: {}", e)))
+    }).await?;
+    if !output.status.success() {
+        return Err(AppError::Python(format!("Script failed: {}", String::from_utf8_lossy(&output.stderr))));
     }
-    
-    Ok(stdout_str)
+    String::from_utf8(output.stdout).map_err(|e| AppError::Python(format!("Failed to read output: {}", e)))
 }
 
-fn find_api_script() -> Result<PathBuf, String> {
+fn find_api_script() -> Result<PathBuf, AppError> {
     // Try multiple possible locations
     let candidates = vec![
         PathBuf::from("python/api.py"),           // From project root (tauri dev)
@@ -129,12 +200,154 @@ fn find_api_script() -> Result<PathBuf, String> {
     
     // Last resort: use current dir info for debugging
     let cwd = env::current_dir().unwrap_or_default();
-    Err(format!(
+    Err(AppError::ScriptNotFound(format!(
         "Python API script not found. CWD: {:?}. Tried: python/api.py, ../python/api.py",
         cwd
+    )))
+}
+
+/// A running python/api.py: requests go in as JSON lines on stdin, progress and the response
+/// come back as JSON lines on stdout. stderr is logged in the background.
+struct ApiProcess {
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: Lines<BufReader<ChildStdout>>,
+    // The `max_concurrent_jobs` slot this interpreter counts against, if it needed one
+    _slot: Option<OwnedSemaphorePermit>,
+}
+
+impl ApiProcess {
+    fn start(python_cmd: &str, api_script: &Path, db_path: &Path, log_stderr: bool) -> Result<Self, AppError> {
+        let mut child = Command::new(python_cmd)
+            .arg(api_script)
+            .env("FINCALC_DB_PATH", db_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(if log_stderr { Stdio::piped() } else { Stdio::null() })
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| AppError::Python(format!("Failed to spawn Python: {} (script: {:?})", e, api_script)))?;
+        if let Some(stderr) = child.stderr.take() {
+            // Keep draining so a chatty script never blocks on a full pipe
+            tauri::async_runtime::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                let mut logged = 0;
+                while let Ok(Some(line)) = lines.next_line().await {
+                    if logged < 10 {
+                        warn!("stderr: {}", line);
+                        logged += 1;
+                    }
+                }
+            });
+        }
+        let stdin = child.stdin.take().ok_or(AppError::Python("Failed to get Python stdin".to_string()))?;
+        let stdout = child.stdout.take().ok_or(AppError::Python("Failed to capture Python stdout".to_string()))?;
+        Ok(Self { child, stdin: Some(stdin), stdout: BufReader::new(stdout).lines(), _slot: None })
+    }
+
+    async fn send(&mut self, request: &str) -> Result<(), AppError> {
+        let stdin = self.stdin.as_mut().ok_or(AppError::Python("Python stdin is closed".to_string()))?;
+        stdin.write_all(request.as_bytes()).await
+            .map_err(|e| AppError::Python(format!("Failed to write to Python stdin: {}", e)))?;
+        stdin.write_all(b"\n").await
+            .map_err(|e| AppError::Python(format!("Failed to write newline: {}", e)))?;
+        stdin.flush().await
+            .map_err(|e| AppError::Python(format!("Failed to flush stdin: {}", e)))
+    }
+
+    /// Closing stdin signals EOF, so the script exits after its current request.
+    fn close_stdin(&mut self) {
+        self.stdin = None;
+    }
+
+    /// Next JSON line; imported modules may print other lines first. None at EOF.
+    async fn next_json(&mut self) -> Option<String> {
+        loop {
+            match self.stdout.next_line().await {
+                Ok(Some(line)) if line.trim().starts_with('{') => return Some(line),
+                Ok(Some(_)) => continue,
+                Ok(None) | Err(_) => return None,
+            }
+        }
+    }
+
+    /// The first line that parses as a response; callers bound the wait with `timeouts::run`.
+    async fn response(&mut self) -> Option<PythonResponse> {
+        while let Some(line) = self.next_json().await {
+            debug!("stdout: {}", &line[..line.len().min(200)]);
+            if let Ok(response) = serde_json::from_str::<PythonResponse>(&line) {
+                return Some(response);
+            }
+        }
+        None
+    }
+
+    /// Give the script `grace` to exit on its own, then kill it.
+    async fn finish(mut self, grace: Duration) {
+        self.close_stdin();
+        match tokio::time::timeout(grace, self.child.wait()).await {
+            Ok(status) => debug!("Python exit status: {:?}", status),
+            Err(_) => {
+                warn!("Process still running after response received, killing it");
+                let _ = self.child.kill().await;
+            }
+        }
+    }
+
+    async fn handshake(&mut self, api_script: &Path) -> Result<BridgeVersion, AppError> {
+        self.send("{\"command\":\"get_bridge_version\"}").await?;
+        let reply = tokio::time::timeout(HANDSHAKE_TIMEOUT, self.next_json())
+            .await
+            .map_err(|_| AppError::Timeout("Python did not answer the bridge version check in time".to_string()))?
+            .and_then(|line| serde_json::from_str::<serde_json::Value>(line.trim()).ok());
+        let reply = reply.ok_or(AppError::Python("No reply from Python to the bridge version check; the script may have crashed on startup".to_string()))?;
+        // A script from before the handshake answers with "Unknown command" and no version
+        let script = reply["protocolVersion"].as_u64();
+        Ok(BridgeVersion {
+            expected: BRIDGE_PROTOCOL_VERSION,
+            script,
+            python_version: reply["pythonVersion"].as_str().map(str::to_string),
+            script_path: api_script.to_string_lossy().to_string(),
+            compatible: script == Some(BRIDGE_PROTOCOL_VERSION),
+        })
+    }
+}
+
+fn outdated_error(version: &BridgeVersion) -> AppError {
+    let (found, side) = match version.script {
+        Some(v) if v > version.expected => (format!("protocol {}", v), "this app is older than its python/ folder. Update the app"),
+        Some(v) => (format!("protocol {}", v), "the python/ folder is out of date. Replace it with the one shipped with this release"),
+        None => ("no protocol version".to_string(), "the python/ folder is out of date. Replace it with the one shipped with this release"),
+    };
+    AppError::BridgeOutdated(format!(
+        "{}: {} speaks {}, but this app expects protocol {}; {}, or reinstall, then restart the app.",
+        BRIDGE_OUTDATED, version.script_path, found, version.expected, side
     ))
 }
 
+/// Start python/api.py and make sure it speaks our protocol before any real request is sent.
+/// Waits for a free Python slot first unless the calling analysis already holds one.
+async fn spawn_api(app: &AppHandle, python_cmd: &str, api_script: &Path) -> Result<ApiProcess, AppError> {
+    let db_path = db::pipeline_db_path(app)?;
+    let slot = jobs::python_slot(app).await;
+    let mut api = spawn_api_at(&db_path, python_cmd, api_script).await?;
+    api._slot = slot;
+    Ok(api)
+}
+
+/// `spawn_api` against another DB, e.g. a page range worker's scratch DB.
+async fn spawn_api_at(db_path: &Path, python_cmd: &str, api_script: &Path) -> Result<ApiProcess, AppError> {
+    let mut api = ApiProcess::start(python_cmd, api_script, db_path, true)?;
+    match api.handshake(api_script).await {
+        Ok(version) if version.compatible => Ok(api),
+        Ok(version) => {
+            warn!(expected = version.expected, script = ?version.script, "Python bridge version mismatch");
+            Err(outdated_error(&version))
+        }
+        Err(e) => Err(e),
+    }
+}
+
 #[tauri::command]
 pub async fn run_python_analysis(
     app: AppHandle,
@@ -142,13 +355,164 @@ pub async fn run_python_analysis(
     content: Option<String>,
     file_name: Option<String>,
     options: Option<serde_json::Value>,
-) -> Result<PythonResponse, String> {
-    let python_cmd = find_python().ok_or("Python not found. Please install Python 3.x")?;
+) -> Result<PythonResponse, AppError> {
+    // Its results would land in a database that unlocking then overwrites
+    if app_lock::is_locked(&app) {
+        return Err(AppError::Internal(app_lock::LOCKED_ERROR.to_string()));
+    }
+    let label = file_name.clone().unwrap_or_else(|| file_path.clone());
+    let task = tasks::register(&app, TaskKind::Analysis, &label);
+    let profiler = Profiler::new(&label);
+    let started = Instant::now();
+    // Inline content has no path to reopen
+    let recent = content.is_none().then(|| (file_path.clone(), file_name.clone()));
+    let source = file_path.clone();
+    // Invoices and bank statements are read natively and kept apart from the statement documents
+    let invoice = gst_invoices::requested(options.as_ref());
+    let bank_statement = bank_statements::requested(options.as_ref());
+    let result = if invoice {
+        gst_invoices::analyze(&app, &file_path, content.is_some(), file_name.as_deref(), options.as_ref()).await
+    } else if bank_statement {
+        bank_statements::analyze(&app, &file_path, content.is_some(), file_name.as_deref(), options.as_ref()).await
+    } else {
+        let work = analyze(&app, file_path, content, file_name, options, &task, &profiler);
+        jobs::run_python_job(&app, &source, &label, JobSource::Manual, work).await
+    };
+    // Python reports parse failures inside a successful response
+    let outcome = match &result {
+        Ok(response) if response.status != "success" => {
+            Err(response.message.clone().or(response.error.clone()).unwrap_or_else(|| "Analysis failed".to_string()))
+        }
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    };
+    if let Some((path, name)) = recent {
+        let status = match &result {
+            Err(AppError::Cancelled(_)) => RecentStatus::Cancelled,
+            _ if outcome.is_ok() => RecentStatus::Success,
+            _ => RecentStatus::Failed,
+        };
+        let error = outcome.clone().err();
+        // Replays, native imports and failures store no pipeline document
+        let document_id = result.as_ref().ok().filter(|_| status == RecentStatus::Success).and_then(|r| r.doc_id);
+        let handle = app.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            if let Some(document_id) = document_id {
+                integrity::record(&handle, document_id, &path);
+            }
+            recent_files::record(&handle, &path, name.as_deref(), status, error.as_deref(), document_id);
+        });
+    }
+    usage::record(&app, UsageKind::Analysis, started.elapsed(), outcome.is_ok());
+    profiler.finish(&app, &outcome);
+    task.finish(&outcome);
+    result
+}
+
+/// Continue an analysis that stopped part-way (see `list_partial_analyses`): only the pages
+/// without stored results are parsed, then everything is merged and stored as one document.
+#[tauri::command]
+pub async fn resume_analysis(app: AppHandle, document_id: i64) -> Result<PythonResponse, AppError> {
+    let (recorder, partial) = Recorder::reopen(&app, document_id)?;
+    let label = partial.file_name.clone().unwrap_or_else(|| partial.file_path.clone());
+    let task = tasks::register(&app, TaskKind::Analysis, &label);
+    let profiler = Profiler::new(&label);
+    let started = Instant::now();
+    let work = resume(&app, &recorder, &partial, &task, &profiler);
+    let result = jobs::run_python_job(&app, &partial.file_path, &label, JobSource::Manual, work).await;
+    match &result {
+        Ok(_) => recorder.complete(),
+        Err(e) => recorder.fail(&e.to_string()),
+    }
+    usage::record(&app, UsageKind::Analysis, started.elapsed(), result.is_ok());
+    profiler.finish(&app, &result);
+    task.finish(&result);
+    result
+}
+
+async fn analyze(
+    app: &AppHandle,
+    file_path: String,
+    content: Option<String>,
+    file_name: Option<String>,
+    options: Option<serde_json::Value>,
+    task: &TaskHandle,
+    profiler: &Profiler,
+) -> Result<PythonResponse, AppError> {
+    let py_settings = python_settings(app);
+    let python_cmd = find_python(&py_settings.interpreter_path).ok_or(AppError::PythonNotFound)?;
     let api_script = find_api_script()?;
     
-    eprintln!("[PythonBridge] Using Python: {}", python_cmd);
-    eprintln!("[PythonBridge] Script path: {:?}", api_script);
-    eprintln!("[PythonBridge] File to analyze: {}", file_path);
+    info!("Using Python: {}", python_cmd);
+    debug!("Script path: {:?}", api_script);
+    info!("File to analyze: {}", file_path);
+
+    let password = options.as_ref()
+        .and_then(|o| o.get("password"))
+        .and_then(|v| v.as_str())
+        .filter(|p| !p.is_empty())
+        .map(str::to_string);
+
+    // Identical file and options already parsed: replay the stored result
+    let no_cache = options.as_ref()
+        .and_then(|o| o.get("no_cache"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    // Changing a mapping changes what a parse finds, so the mappings are part of the cache key
+    let mappings = terminology::for_parse(app);
+    let lookup_started = Instant::now();
+    let cache = match parse_cache::file_hash(&file_path, content.as_deref()) {
+        Ok(hash) => Some((parse_cache::cache_key(&hash, options.as_ref(), &mappings), hash)),
+        Err(e) => {
+            warn!("Skipping parse cache: {}", e);
+            None
+        }
+    };
+    // Held until the results are stored; a second run of the same file then replays them
+    let lock_key = cache.as_ref().map_or_else(|| file_path.clone(), |(_, hash)| hash.clone());
+    let _document_lock = app.state::<DocumentLocks>().acquire(&lock_key, task).await?;
+    if let Some((key, hash)) = cache.as_ref().filter(|_| !no_cache) {
+        if let Some(mut response) = parse_cache::lookup(app, key) {
+            info!("Parse cache hit for {}", hash);
+            profiler.since(Stage::CacheLookup, lookup_started);
+            profiler.set_mode("cache_hit");
+            response.cache_hit = Some(true);
+            response.doc_id = None;
+            let _ = app.emit("cache-hit", serde_json::json!({ "fileName": file_name, "fileHash": hash }));
+            return Ok(response);
+        }
+    }
+    profiler.since(Stage::CacheLookup, lookup_started);
+    let cached_file_name = file_name.clone();
+
+    // Scanned reports have no text layer: OCR them into a searchable copy and parse that
+    let ocr_requested = options.as_ref()
+        .and_then(|o| o.get("ocr"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let ocr_document = if ocr_requested {
+        if content.is_some() || !Path::new(&file_path).exists() {
+            return Err(AppError::InvalidInput("OCR mode needs a file on disk (file_path)".to_string()));
+        }
+        let language = options.as_ref()
+            .and_then(|o| o.get("ocr_language"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("eng")
+            .to_string();
+        // Tesseract runs synchronously, page by page
+        let (ocr_app, ocr_path, ocr_password) = (app.clone(), file_path.clone(), password.clone());
+        let ocr_started = Instant::now();
+        let document = tauri::async_runtime::spawn_blocking(move || ocr::ocr_pdf(&ocr_app, &ocr_path, &language, ocr_password.as_deref()))
+            .await
+            .map_err(|e| e.to_string())??;
+        profiler.since(Stage::Ocr, ocr_started);
+        Some(document)
+    } else {
+        None
+    };
+    let file_path = ocr_document.as_ref()
+        .map(|doc| doc.path.to_string_lossy().to_string())
+        .unwrap_or(file_path);
     
     // Build request
     let request = PythonRequest {
@@ -157,227 +521,576 @@ pub async fn run_python_analysis(
         content,
         file_name,
         options,
+        password,
+        mappings: Some(mappings),
     };
-    
-    let request_json = serde_json::to_string(&request)
-        .map_err(|e| format!("Failed to serialize request: {}", e))?;
-    
-    eprintln!("[PythonBridge] Request JSON length: {}", request_json.len());
-    
-    // Spawn Python process
-    let mut child = Command::new(&python_cmd)
-        .arg(&api_script)
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(|e| format!("Failed to spawn Python: {} (script: {:?})", e, api_script))?;
-    
-    // Send request - take stdin BEFORE sending
-    {
-        let stdin = child.stdin.as_mut()
-            .ok_or("Failed to get Python stdin")?;
-        
-        stdin.write_all(request_json.as_bytes())
-            .map_err(|e| format!("Failed to write to Python stdin: {}", e))?;
-        stdin.write_all(b"\n")
-            .map_err(|e| format!("Failed to write newline: {}", e))?;
-        stdin.flush()
-            .map_err(|e| format!("Failed to flush stdin: {}", e))?;
-    }
-    // stdin is dropped here, closing the pipe (signals EOF to Python)
-    
-    // Read stderr for debugging
-    let stderr = child.stderr.take();
-    
-    // Read response from stdout with timeout
-    let stdout = child.stdout.take()
-        .ok_or("Failed to capture Python stdout")?;
-    let reader = BufReader::new(stdout);
-    
-    let mut final_response: Option<PythonResponse> = None;
-    let timeout_duration = Duration::from_secs(900); // 900 second timeout (15 mins) for very large PDFs
-    let start_time = Instant::now();
-
-    for line in reader.lines() {
-        // Check timeout
-        if start_time.elapsed() > timeout_duration {
-            eprintln!("[PythonBridge] Timeout reached after 900 seconds, killing Python process");
-            let _ = child.kill();
-            return Err("PDF analysis timed out after 15 minutes. The document may be very large (>500 pages) or heavily formatted. Consider splitting the document or checking if it contains images that require OCR.".to_string());
-        }
-        
-        if let Ok(line) = line {
-            if !line.trim().starts_with('{') {
-                continue; // Skip non-JSON lines
+
+    // Large PDFs are split into page ranges parsed side by side
+    let split = if request.content.is_none() && request.password.is_none() {
+        let (split_path, threshold) = (request.file_path.clone(), py_settings.parallel_page_threshold);
+        let workers = page_ranges::worker_count(py_settings.max_parallel_workers);
+        let split_started = Instant::now();
+        let split = tauri::async_runtime::spawn_blocking(move || page_ranges::split(&split_path, threshold, workers))
+            .await
+            .map_err(|e| e.to_string())??;
+        if split.is_some() {
+            profiler.since(Stage::Split, split_started);
+        }
+        split
+    } else {
+        None
+    };
+
+    // Items are kept as they stream in when the run can be resumed from the same file later
+    let resumable = ocr_document.is_none()
+        && request.content.is_none()
+        && request.password.is_none()
+        && request.file_path.to_lowercase().ends_with(".pdf");
+    let recorder = if resumable {
+        Recorder::start(app, &request.file_path, request.file_name.as_deref(), request.options.as_ref())
+            .map_err(|e| warn!("Partial results won't be kept: {}", e))
+            .ok()
+    } else {
+        None
+    };
+
+    let outcome = async {
+        match &split {
+            Some(split) => {
+                profiler.set_mode("parallel_ranges");
+                let started = Instant::now();
+                let responses = run_ranges(app, split, &request, task, recorder.as_ref(), profiler).await?;
+                let results = responses.into_iter().map(|response| response.extracted_data.unwrap_or_default()).collect();
+                let mut extracted = page_ranges::merge(&split.ranges, results, split.total_pages);
+                extracted["metadata"]["processingTime"] = started.elapsed().as_secs_f64().into();
+                store_merged(app, &request, extracted, profiler).await.map(Some)
             }
-            
-            eprintln!("[PythonBridge] stdout: {}", &line[..line.len().min(200)]);
-            
-            // Try to parse as progress update first
-            if let Ok(progress) = serde_json::from_str::<ProgressUpdate>(&line) {
-                if progress.status == "progress" {
+            None => {
+                let request_json = serde_json::to_string(&request)
+                    .map_err(|e| format!("Failed to serialize request: {}", e))?;
+
+                debug!("Request JSON length: {}", request_json.len());
+
+                // Spawn Python process and send the request; closing stdin signals EOF to Python
+                let spawn_started = Instant::now();
+                let mut api = spawn_api(app, &python_cmd, &api_script).await?;
+                profiler.since(Stage::Spawn, spawn_started);
+                api.send(&request_json).await?;
+                api.close_stdin();
+
+                let timeout_secs = py_settings.analysis_timeout_secs; // Default 15 mins for very large PDFs
+                let deadline = tokio::time::Instant::now() + Duration::from_secs(timeout_secs);
+                let label = cached_file_name.as_deref().unwrap_or("document");
+                let mut stream = recorder.as_ref().map(|recorder| recorder.stream(1));
+                let mut clock = profiler.parse_clock(1);
+                let response = follow_parse(&mut api, label, deadline, timeout_secs, task, |progress| {
+                    clock.progress(&progress.message);
                     // Emit progress event to frontend
                     let _ = app.emit("pdf-progress", progress.clone());
-                    eprintln!("[PythonBridge] Progress: {}% - Page {}/{}", 
+                    task.progress(progress.percentage as f64, &progress.message);
+                    debug!("Progress: {}% - Page {}/{}",
                         progress.percentage, progress.current_page, progress.total_pages);
-                    continue; // Continue reading for more updates
+                }, |item| {
+                    if let Some(stream) = stream.as_mut() {
+                        let write_started = Instant::now();
+                        stream.item(item);
+                        profiler.since(Stage::PartialWrites, write_started);
+                    }
+                }).await?;
+                clock.response();
+
+                // We already have the response, so only wait briefly for the process to clean up
+                api.finish(CLEANUP_GRACE).await;
+                Ok(response)
+            }
+        }
+    }.await;
+
+    if let Some(recorder) = recorder {
+        match &outcome {
+            Ok(Some(response)) if response.status == "success" => recorder.complete(),
+            Ok(Some(response)) => recorder.fail(response.message.as_deref().or(response.error.as_deref()).unwrap_or("Analysis failed")),
+            Ok(None) => recorder.fail("No response from Python"),
+            Err(e) => recorder.fail(&e.to_string()),
+        }
+    }
+
+    match outcome? {
+        Some(mut response) => {
+            debug!("Returning successful response");
+            if let Some(extracted) = response.extracted_data.as_mut() {
+                normalize_items(extracted);
+            }
+            // api.py stored a single-run parse itself and says as which document; merged ranges
+            // went through `store_merged`
+            if split.is_none() && response.status == "success" {
+                if let Some(doc_id) = response.doc_id {
+                    statements::document_stored(app, doc_id);
+                    chunks::index_in_background(app, doc_id);
+                    let name = request.file_name.as_deref().unwrap_or(&request.file_path);
+                    activity::record(app, ActivityKind::DocumentImported, Some(doc_id), &format!("Imported {}", name), None);
                 }
             }
-            
-            // Try to parse as final response
-            if let Ok(response) = serde_json::from_str::<PythonResponse>(&line) {
-                final_response = Some(response);
-                // Break after receiving final response to prevent hanging
-                break;
+            if let Some((key, hash)) = &cache {
+                let store_started = Instant::now();
+                parse_cache::store(app, key, hash, cached_file_name.as_deref(), &response);
+                profiler.since(Stage::CacheWrite, store_started);
             }
+            Ok(response)
         }
+        None => Err(AppError::Python("No response from Python. Process may have timed out or crashed.".to_string())),
     }
-    
-    // If we have a response, we can proceed even if process is still cleaning up
-    if final_response.is_some() {
-        eprintln!("[PythonBridge] Received final response, cleaning up process...");
-    }
-    
-    // Capture stderr (with a shorter timeout to avoid blocking)
-    if let Some(stderr) = stderr {
-        let stderr_reader = BufReader::new(stderr);
-        for line in stderr_reader.lines().take(10) {
-            if let Ok(line) = line {
-                eprintln!("[PythonBridge] stderr: {}", line);
+}
+
+/// Follow a sent parse request until its response, handing progress lines to `on_progress` and
+/// streamed items to `on_item`. Timeouts and cancels return early; the caller then drops `api`,
+/// which kills Python.
+async fn follow_parse(
+    api: &mut ApiProcess,
+    label: &str,
+    deadline: tokio::time::Instant,
+    timeout_secs: u64,
+    task: &TaskHandle,
+    mut on_progress: impl FnMut(ProgressUpdate),
+    mut on_item: impl FnMut(serde_json::Value),
+) -> Result<Option<PythonResponse>, AppError> {
+    let mut cancel_check = tokio::time::interval(CANCEL_POLL);
+    loop {
+        let line = tokio::select! {
+            line = api.next_json() => line,
+            _ = tokio::time::sleep_until(deadline) => {
+                error!("Timeout reached after {} seconds, killing Python process", timeout_secs);
+                return Err(timeouts::expired(Operation::Analysis, timeout_secs));
+            }
+            _ = cancel_check.tick() => {
+                if task.is_cancelled() {
+                    info!("Analysis of {} cancelled, killing Python process", label);
+                    return Err(AppError::Cancelled("Analysis cancelled".to_string()));
+                }
+                continue;
+            }
+        };
+        let Some(line) = line else { return Ok(None) };
+        debug!("stdout: {}", &line[..line.len().min(200)]);
+
+        // Try to parse as progress update first
+        if let Ok(progress) = serde_json::from_str::<ProgressUpdate>(&line) {
+            if progress.status == "progress" {
+                if let Some(serde_json::Value::Array(items)) = &progress.partial_items {
+                    items.iter().cloned().for_each(&mut on_item);
+                }
+                on_progress(progress);
+                continue; // Continue reading for more updates
             }
         }
-    }
-    
-    // Wait for process to finish with a shorter timeout (5 seconds) since we already have the response
-    let cleanup_timeout = Duration::from_secs(5);
-    let cleanup_start = Instant::now();
-    let mut status = None;
-    
-    while cleanup_start.elapsed() < cleanup_timeout {
-        match child.try_wait() {
-            Ok(Some(s)) => {
-                status = Some(s);
-                break;
+
+        // Try to parse as final response; streamed items also carry a status
+        if let Ok(mut line) = serde_json::from_str::<serde_json::Value>(&line) {
+            if line["status"] == "item_stream" {
+                on_item(line["item"].take());
+                continue;
             }
-            Ok(None) => {
-                // Still running, wait a bit
-                thread::sleep(Duration::from_millis(50));
+            if let Ok(response) = serde_json::from_value::<PythonResponse>(line) {
+                return Ok(Some(response));
             }
-            Err(e) => {
-                eprintln!("[PythonBridge] Error checking process status: {}", e);
-                break;
+        }
+    }
+}
+
+/// Parse every range of `split` in its own process at once; the responses come back in range
+/// order. Items are recorded per range when `recorder` is given.
+async fn run_ranges(
+    app: &AppHandle,
+    split: &SplitDocument,
+    request: &PythonRequest,
+    task: &TaskHandle,
+    recorder: Option<&Recorder>,
+    profiler: &Profiler,
+) -> Result<Vec<PythonResponse>, AppError> {
+    info!(pages = split.total_pages, ranges = split.ranges.len(), "Parsing page ranges in parallel");
+    let py_settings = python_settings(app);
+    let python_cmd = find_python(&py_settings.interpreter_path).ok_or(AppError::PythonNotFound)?;
+    let api_script = find_api_script()?;
+    let timeout_secs = py_settings.analysis_timeout_secs;
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(timeout_secs);
+    // Latest percentage per range, for one overall figure weighted by page count
+    let percentages = std::sync::Mutex::new(vec![0; split.ranges.len()]);
+    let range_pages: i32 = split.ranges.iter().map(|range| range.pages() as i32).sum();
+
+    let workers = split.ranges.iter().enumerate().map(|(index, range)| {
+        let (percentages, python_cmd, api_script) = (&percentages, &python_cmd, &api_script);
+        async move {
+            let request = PythonRequest {
+                command: "parse".to_string(),
+                file_path: range.path.to_string_lossy().to_string(),
+                content: None,
+                file_name: request.file_name.clone(),
+                options: request.options.clone(),
+                password: None,
+                mappings: request.mappings.clone(),
+            };
+            let request_json = serde_json::to_string(&request)
+                .map_err(|e| format!("Failed to serialize request: {}", e))?;
+            let spawn_started = Instant::now();
+            let mut api = spawn_api_at(&range.db_path, python_cmd, api_script).await?;
+            profiler.since(Stage::Spawn, spawn_started);
+            api.send(&request_json).await?;
+            api.close_stdin();
+
+            let label = format!("pages {}-{}", range.first, range.last);
+            let mut stream = recorder.map(|recorder| recorder.stream(range.first));
+            let mut clock = profiler.parse_clock(range.first);
+            let response = follow_parse(&mut api, &label, deadline, timeout_secs, task, |progress| {
+                clock.progress(&progress.message);
+                let pages_done = {
+                    let mut percentages = percentages.lock().unwrap_or_else(|e| e.into_inner());
+                    percentages[index] = progress.percentage.clamp(0, 100);
+                    split.ranges.iter().zip(percentages.iter())
+                        .map(|(range, percentage)| range.pages() as i32 * percentage)
+                        .sum::<i32>() / 100
+                };
+                let update = ProgressUpdate {
+                    status: "progress".to_string(),
+                    current_page: pages_done,
+                    total_pages: range_pages,
+                    percentage: pages_done * 100 / range_pages.max(1),
+                    message: format!("Pages {}-{}: {}", range.first, range.last, progress.message),
+                    partial_items: progress.partial_items,
+                    partial_text: None,
+                };
+                let _ = app.emit("pdf-progress", update.clone());
+                task.progress(update.percentage as f64, &update.message);
+            }, |item| {
+                if let Some(stream) = stream.as_mut() {
+                    let write_started = Instant::now();
+                    stream.item(item);
+                    profiler.since(Stage::PartialWrites, write_started);
+                }
+            }).await?;
+            clock.response();
+            api.finish(CLEANUP_GRACE).await;
+
+            match response {
+                Some(response) if response.status == "success" => {
+                    if let Some(stream) = stream {
+                        let write_started = Instant::now();
+                        stream.finish(range.last);
+                        profiler.since(Stage::PartialWrites, write_started);
+                    }
+                    Ok(response)
+                }
+                Some(response) => Err(AppError::Python(format!(
+                    "Pages {}-{}: {}",
+                    range.first,
+                    range.last,
+                    response.message.or(response.error).unwrap_or_else(|| "Analysis failed".to_string())
+                ))),
+                None => Err(AppError::Python(format!("No response from Python for pages {}-{}", range.first, range.last))),
             }
         }
+    });
+    // The first failure drops the other workers, which kills their processes
+    futures_util::future::try_join_all(workers).await
+}
+
+/// Amounts in `extractedData.items` as numbers; parsers pass some through as written.
+fn normalize_items(extracted: &mut serde_json::Value) {
+    if let Some(items) = extracted.get_mut("items").and_then(|items| items.as_array_mut()) {
+        items.iter_mut().for_each(numbers::normalize_item);
     }
-    
-    // Kill process if still running after cleanup timeout
-    if status.is_none() {
-        eprintln!("[PythonBridge] Process still running after response received, killing it");
-        let _ = child.kill();
-        // Try one more time to get exit status
-        status = child.try_wait().ok().flatten();
+}
+
+/// Store merged `extractedData` in the active pipeline DB and recompute metrics over all of its
+/// items, since each range only saw its own.
+async fn store_merged(app: &AppHandle, request: &PythonRequest, mut extracted: serde_json::Value, profiler: &Profiler) -> Result<PythonResponse, AppError> {
+    normalize_items(&mut extracted);
+    let items = extracted["items"].as_array().cloned().unwrap_or_default();
+    info!(items = items.len(), "Storing merged page ranges");
+
+    let file_name = request.file_name.clone().unwrap_or_else(|| "document".to_string());
+    let write_started = Instant::now();
+    let doc_id = statements::insert_document(app, &file_name, &extracted["metadata"], &items)?;
+    statements::save_text_chunks(app, doc_id, extracted["text"].as_str().unwrap_or(""))?;
+    profiler.since(Stage::DbWrites, write_started);
+    statements::document_stored(app, doc_id);
+    chunks::index_in_background(app, doc_id);
+    activity::record(app, ActivityKind::DocumentImported, Some(doc_id), &format!("Imported {}", file_name), None);
+
+    let metrics_started = Instant::now();
+    let metrics = match calculate_metrics(app.clone(), serde_json::to_string(&items).map_err(|e| e.to_string())?).await {
+        Ok(response) if response.status == "success" => response.metrics,
+        Ok(response) => {
+            warn!("Metrics over merged ranges failed: {:?}", response.message);
+            None
+        }
+        Err(e) => {
+            warn!("Metrics over merged ranges failed: {}", e);
+            None
+        }
+    };
+    profiler.since(Stage::Metrics, metrics_started);
+    Ok(PythonResponse {
+        status: "success".to_string(),
+        extracted_data: Some(extracted),
+        metrics: metrics.or(Some(serde_json::Value::Array(Vec::new()))),
+        metadata: None,
+        message: None,
+        error: None,
+        code: None,
+        cache_hit: None,
+        doc_id: Some(doc_id),
+    })
+}
+
+/// Parse the pages a partial analysis is missing and merge them with the stored ones.
+async fn resume(app: &AppHandle, recorder: &Recorder, partial: &PartialAnalysis, task: &TaskHandle, profiler: &Profiler) -> Result<PythonResponse, AppError> {
+    let py_settings = python_settings(app);
+    profiler.set_mode("resumed");
+    if !Path::new(&partial.file_path).exists() {
+        return Err(AppError::NotFound(format!("{} no longer exists", partial.file_path)));
     }
-    
-    eprintln!("[PythonBridge] Python exit status: {:?}", status);
-    
-    match final_response {
-        Some(response) => {
-            eprintln!("[PythonBridge] Returning successful response");
-            Ok(response)
+    let lock_key = parse_cache::file_hash(&partial.file_path, None).unwrap_or_else(|_| partial.file_path.clone());
+    let _document_lock = app.state::<DocumentLocks>().acquire(&lock_key, task).await?;
+
+    let started = Instant::now();
+    let done = recorder.done_pages()?;
+    let workers = page_ranges::worker_count(py_settings.max_parallel_workers);
+    let split_path = partial.file_path.clone();
+    let split_started = Instant::now();
+    let split = tauri::async_runtime::spawn_blocking(move || page_ranges::split_missing(&split_path, &done, workers))
+        .await
+        .map_err(|e| e.to_string())??;
+    profiler.since(Stage::Split, split_started);
+    info!(document_id = recorder.document_id, missing = split.ranges.iter().map(|r| r.pages()).sum::<u32>(), "Resuming analysis");
+
+    let request = PythonRequest {
+        command: "parse".to_string(),
+        file_path: partial.file_path.clone(),
+        content: None,
+        file_name: partial.file_name.clone(),
+        options: partial.options.clone(),
+        password: None,
+        mappings: Some(terminology::for_parse(app)),
+    };
+    let responses = run_ranges(app, &split, &request, task, Some(recorder), profiler).await?;
+    let results = responses.into_iter().map(|response| response.extracted_data.unwrap_or_default()).collect();
+    let mut extracted = page_ranges::merge(&split.ranges, results, split.total_pages);
+
+    // The stored items cover the pages parsed before as well as the ones just parsed
+    let mut items = recorder.items()?;
+    page_ranges::unique_ids(&mut items);
+    extracted["items"] = serde_json::Value::Array(items);
+    extracted["metadata"]["analysisMode"] = "resumed".into();
+    extracted["metadata"]["processingTime"] = started.elapsed().as_secs_f64().into();
+    let response = store_merged(app, &request, extracted, profiler).await?;
+    if let Some(doc_id) = response.doc_id {
+        integrity::record(app, doc_id, &partial.file_path);
+    }
+
+    let store_started = Instant::now();
+    if let Ok(hash) = parse_cache::file_hash(&partial.file_path, None) {
+        let key = parse_cache::cache_key(&hash, partial.options.as_ref(), request.mappings.as_ref().unwrap_or(&serde_json::Value::Null));
+        parse_cache::store(app, &key, &hash, partial.file_name.as_deref(), &response);
+    }
+    profiler.since(Stage::CacheWrite, store_started);
+    Ok(response)
+}
+
+/// Parse `pages` of a stored document's source file again and put the new items in place of
+/// those pages' old ones.
+async fn reanalyze(
+    app: &AppHandle,
+    document_id: i64,
+    pages: &[u32],
+    options: Option<serde_json::Value>,
+    file_path: &str,
+    task: &TaskHandle,
+    profiler: &Profiler,
+) -> Result<PageReanalysis, AppError> {
+    profiler.set_mode("reanalyzed");
+    let stored = statements::load_stored_document(app, document_id)?;
+    let lock_key = parse_cache::file_hash(file_path, None).unwrap_or_else(|_| file_path.to_string());
+    let _document_lock = app.state::<DocumentLocks>().acquire(&lock_key, task).await?;
+
+    let started = Instant::now();
+    let workers = page_ranges::worker_count(python_settings(app).max_parallel_workers);
+    let (split_path, split_pages) = (file_path.to_string(), pages.to_vec());
+    let split_started = Instant::now();
+    let mut split = tauri::async_runtime::spawn_blocking(move || page_ranges::split_pages(&split_path, &split_pages, workers))
+        .await
+        .map_err(|e| e.to_string())??;
+    profiler.since(Stage::Split, split_started);
+    info!(document_id, pages = ?pages, ranges = split.ranges.len(), "Re-extracting pages");
+
+    // Only the chosen pages go through OCR, which is what makes this quicker than a full pass
+    let ocr_language = options.as_ref()
+        .filter(|o| o.get("ocr").and_then(|v| v.as_bool()).unwrap_or(false))
+        .map(|o| o.get("ocr_language").and_then(|v| v.as_str()).unwrap_or("eng").to_string());
+    let mut ocr_documents = Vec::new();
+    if let Some(language) = ocr_language {
+        let ocr_started = Instant::now();
+        for range in split.ranges.iter_mut() {
+            let (ocr_app, ocr_path, language) = (app.clone(), range.path.to_string_lossy().to_string(), language.clone());
+            let document = tauri::async_runtime::spawn_blocking(move || ocr::ocr_pdf(&ocr_app, &ocr_path, &language, None))
+                .await
+                .map_err(|e| e.to_string())??;
+            range.path = document.path.clone();
+            ocr_documents.push(document);
         }
-        None => Err("No response from Python. Process may have timed out or crashed.".to_string()),
+        profiler.since(Stage::Ocr, ocr_started);
+    }
+
+    let request = PythonRequest {
+        command: "parse".to_string(),
+        file_path: file_path.to_string(),
+        content: None,
+        file_name: Some(stored.filename.clone()),
+        options,
+        password: None,
+        mappings: Some(terminology::for_parse(app)),
+    };
+    let responses = run_ranges(app, &split, &request, task, None, profiler).await?;
+    let results = responses.into_iter().map(|response| response.extracted_data.unwrap_or_default()).collect();
+    let mut extracted = page_ranges::merge(&split.ranges, results, split.total_pages);
+    normalize_items(&mut extracted);
+    let fresh = extracted["items"].as_array().cloned().unwrap_or_default();
+    let added_items = fresh.len();
+
+    let page_set = pages.iter().copied().collect();
+    let (items, removed_items) = page_ranges::replace_pages(stored.items, fresh, &page_set);
+    let mut metadata = stored.metadata;
+    if !metadata.is_object() {
+        metadata = serde_json::json!({});
+    }
+    let mut reanalyzed: Vec<u32> = metadata["reanalyzedPages"].as_array()
+        .map(|done| done.iter().filter_map(|p| p.as_u64().map(|p| p as u32)).collect())
+        .unwrap_or_default();
+    reanalyzed.extend(pages);
+    reanalyzed.sort_unstable();
+    reanalyzed.dedup();
+    metadata["reanalyzedPages"] = serde_json::json!(reanalyzed);
+
+    let write_started = Instant::now();
+    statements::replace_document(app, document_id, &stored.filename, &metadata, &items)?;
+    profiler.since(Stage::DbWrites, write_started);
+    statements::document_stored(app, document_id);
+    info!(document_id, removed_items, added_items, elapsed = ?started.elapsed(), "Re-extracted pages");
+
+    let reanalysis = PageReanalysis {
+        document_id,
+        pages: pages.to_vec(),
+        removed_items,
+        added_items,
+        document: statements::load_document(app, document_id)?,
+    };
+    let _ = app.emit("document-reanalyzed", serde_json::json!({
+        "documentId": document_id,
+        "pages": pages,
+        "removedItems": removed_items,
+        "addedItems": added_items,
+    }));
+    Ok(reanalysis)
+}
+
+/// Extract a few pages of a stored document again, e.g. with `{"ocr": true}` or another table
+/// strategy in `options`, and merge the corrected items into it rather than re-parsing the whole
+/// report. `file_path` is only needed when the document wasn't analyzed from a file on this
+/// machine.
+#[tauri::command]
+pub async fn reanalyze_pages(
+    app: AppHandle,
+    document_id: i64,
+    pages: Vec<u32>,
+    options: Option<serde_json::Value>,
+    file_path: Option<String>,
+) -> Result<PageReanalysis, AppError> {
+    if pages.is_empty() {
+        return Err(AppError::InvalidInput("No pages to re-extract".to_string()));
+    }
+    let file_path = file_path
+        .or_else(|| recent_files::path_for_document(&app, document_id))
+        .ok_or_else(|| AppError::NotFound(format!("The source file of document {} is unknown; pass its path", document_id)))?;
+    if !Path::new(&file_path).is_file() {
+        return Err(AppError::NotFound(format!("{} no longer exists", file_path)));
     }
+    let mut pages = pages;
+    pages.sort_unstable();
+    pages.dedup();
+
+    let name = Path::new(&file_path).file_name().map_or_else(|| file_path.clone(), |n| n.to_string_lossy().into_owned());
+    let label = format!("{} (pages {})", name, pages.iter().map(u32::to_string).collect::<Vec<_>>().join(", "));
+    let task = tasks::register(&app, TaskKind::Analysis, &label);
+    let profiler = Profiler::new(&label);
+    let started = Instant::now();
+    let work = reanalyze(&app, document_id, &pages, options, &file_path, &task, &profiler);
+    let result = jobs::run_python_job(&app, &file_path, &label, JobSource::Manual, work).await;
+    usage::record(&app, UsageKind::Analysis, started.elapsed(), result.is_ok());
+    profiler.finish(&app, &result);
+    task.finish(&result);
+    result
 }
 
+/// Run the version exchange on its own, e.g. for a settings page health check.
 #[tauri::command]
-pub async fn update_terminology_mapping(
-    mappings: serde_json::Value,
-) -> Result<(), String> {
-    let python_cmd = find_python().ok_or("Python not found")?;
+pub async fn get_bridge_version(app: AppHandle) -> Result<BridgeVersion, AppError> {
+    let python_cmd = find_python(&python_settings(&app).interpreter_path).ok_or(AppError::PythonNotFound)?;
     let api_script = find_api_script()?;
-    
-    let request = serde_json::json!({
-        "command": "update_mapping",
-        "mappings": mappings
-    });
-    
-    let mut child = Command::new(&python_cmd)
-        .arg(&api_script)
-        .stdin(Stdio::piped())
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()
-        .map_err(|e| format!("Failed to spawn Python: {}", e))?;
-    
-    if let Some(mut stdin) = child.stdin.take() {
-        stdin.write_all(request.to_string().as_bytes())
-            .map_err(|e| format!("Failed to write: {}", e))?;
-        stdin.write_all(b"\n").ok();
-        stdin.flush().ok();
-    }
-    
-    let _ = child.wait();
-    Ok(())
+    let db_path = db::pipeline_db_path(&app)?;
+    let _slot = jobs::python_slot(&app).await;
+    let mut api = ApiProcess::start(&python_cmd, &api_script, &db_path, false)?;
+    let version = api.handshake(&api_script).await;
+    api.finish(CLEANUP_GRACE).await;
+    version
 }
 
 #[tauri::command]
 pub async fn calculate_metrics(
-    _app: AppHandle,
+    app: AppHandle,
     items_json: String,
-) -> Result<PythonResponse, String> {
-    let python_cmd = find_python().ok_or("Python not found")?;
+) -> Result<PythonResponse, AppError> {
+    let cache = app.state::<MetricCache>();
+    if let Some(metrics) = cache.get(&app, &items_json, METRIC_SET) {
+        debug!("Metric cache hit");
+        return Ok(PythonResponse {
+            status: "success".to_string(),
+            extracted_data: None,
+            metrics: Some(metrics),
+            metadata: None,
+            message: None,
+            error: None,
+            code: None,
+            cache_hit: Some(true),
+            doc_id: None,
+        });
+    }
+
+    let python_cmd = find_python(&python_settings(&app).interpreter_path).ok_or(AppError::PythonNotFound)?;
     let api_script = find_api_script()?;
     
-    let _request = serde_json::json!({
+    let request = serde_json::json!({
         "command": "calculate_metrics",
         "items_json": items_json
     });
     
-    let mut child = Command::new(&python_cmd)
-        .arg(&api_script)
-        .stdin(Stdio::piped())
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()
-        .map_err(|e| format!("Failed to spawn Python: {}", e))?;
+    let mut api = spawn_api(&app, &python_cmd, &api_script).await?;
+    api.send(&request.to_string()).await?;
+    api.close_stdin();
     
-    eprintln!("[PythonBridge] Calculating metrics from {} items", items_json.len());
+    info!("Calculating metrics from {} items", items_json.len());
     
-    // Read response from stdout
-    let stdout = child.stdout.take()
-        .ok_or("Failed to capture Python stdout")?;
-    let reader = BufReader::new(stdout);
-    
-    let mut final_response: Option<PythonResponse> = None;
-    let _timeout_duration = Duration::from_secs(60); // 60 second timeout for metrics calc
-    
-    for line in reader.lines() {
-        if let Ok(line) = line {
-            if !line.trim().starts_with('{') {
-                continue;
-            }
-            
-            eprintln!("[PythonBridge] stdout: {}", &line[..line.len().min(200)]);
-            
-            // Try to parse as final response
-            if let Ok(response) = serde_json::from_str::<PythonResponse>(&line) {
-                final_response = Some(response);
-                break;
-            }
-        }
-    }
-    
-    // Wait for process to finish
-    let _ = child.wait();
-    eprintln!("[PythonBridge] Metrics calculation complete");
+    let final_response = timeouts::run(&app, Operation::Metrics, async { Ok::<_, AppError>(api.response().await) }).await?;
+    api.finish(CLEANUP_GRACE).await;
+    info!("Metrics calculation complete");
     
     match final_response {
         Some(response) => {
-            eprintln!("[PythonBridge] Returning metrics response");
+            debug!("Returning metrics response");
+            if let Some(metrics) = response.metrics.as_ref().filter(|_| response.status == "success") {
+                cache.put(&app, &items_json, METRIC_SET, metrics);
+            }
             Ok(response)
         }
-        None => Err("No response from Python for metrics calculation".to_string()),
+        None => Err(AppError::Python("No response from Python for metrics calculation".to_string())),
     }
 }
 
@@ -397,33 +1110,132 @@ pub struct CompanySearchResult {
     pub query: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub count: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cached: Option<bool>,
+}
+
+impl CompanySearchResult {
+    fn from_cache(query: String, payload: serde_json::Value) -> Self {
+        Self {
+            success: true,
+            results: Some(payload),
+            error: None,
+            query: Some(query),
+            count: Some(1),
+            cached: Some(true),
+        }
+    }
+
+    /// Structured "offline" answer so callers don't wait out a scraper timeout.
+    fn offline(query: Option<String>) -> Self {
+        Self {
+            success: false,
+            results: Some(serde_json::json!({ "offline": true })),
+            error: Some(settings::OFFLINE_ERROR.to_string()),
+            query,
+            count: Some(0),
+            cached: None,
+        }
+    }
 }
 
 
 
+/// One exchange's search through the Python scrapers.
+async fn search_exchange(app: &AppHandle, query: &str, exchange: &str, limit: i32) -> Result<serde_json::Value, String> {
+    let script = format!(
+        "import sys; sys.path.extend(['python', '../python']); from scraper_bridge import search_companies_bridge; result = search_companies_bridge('{}', '{}', {}); print(result)",
+        query.replace("'", "\\'"),
+        exchange,
+        limit
+    );
+    let stdout = run_python_script_with_timeout(app, script, Operation::ScraperSearch).await?;
+    serde_json::from_str(&stdout).map_err(|e| format!("Failed to parse search results: {}", e))
+}
+
+/// Combine per-exchange answers: results concatenated without repeats (same ISIN, or same
+/// symbol on the same exchange), errors prefixed with their exchange, and a per-exchange
+/// status under `exchanges`. Succeeds if any exchange did.
+fn merge_searches(query: &str, limit: i32, searches: Vec<(&str, Result<serde_json::Value, String>)>) -> serde_json::Value {
+    let mut results: Vec<serde_json::Value> = Vec::new();
+    let mut seen = std::collections::HashSet::new();
+    let mut errors: Vec<String> = Vec::new();
+    let mut exchanges = serde_json::Map::new();
+    for (exchange, search) in searches {
+        let status = match search {
+            Ok(result) => {
+                let success = result["success"].as_bool().unwrap_or(false);
+                let found = result["results"].as_array().cloned().unwrap_or_default();
+                errors.extend(result["errors"].as_array().into_iter().flatten()
+                    .filter_map(|e| e.as_str())
+                    .map(|e| format!("{}: {}", exchange, e)));
+                if let Some(error) = result["error"].as_str() {
+                    errors.push(format!("{}: {}", exchange, error));
+                }
+                let status = serde_json::json!({ "success": success, "count": found.len(), "error": result["error"] });
+                for company in found {
+                    let key = match company["isin"].as_str().filter(|isin| !isin.is_empty()) {
+                        Some(isin) => isin.to_string(),
+                        None => format!("{}:{}", company["exchange"].as_str().unwrap_or(exchange), company["symbol"].as_str().unwrap_or("")),
+                    };
+                    if seen.insert(key) {
+                        results.push(company);
+                    }
+                }
+                status
+            }
+            Err(e) => {
+                warn!(exchange, "Company search failed: {}", e);
+                errors.push(format!("{}: {}", exchange, e));
+                serde_json::json!({ "success": false, "count": 0, "error": e })
+            }
+        };
+        exchanges.insert(exchange.to_string(), status);
+    }
+    results.truncate(limit.max(0) as usize);
+    let success = exchanges.values().any(|status| status["success"].as_bool().unwrap_or(false));
+    serde_json::json!({
+        "success": success,
+        "query": query,
+        "exchange": "BOTH",
+        "count": results.len(),
+        "results": results,
+        "errors": errors,
+        "exchanges": exchanges,
+        "error": if success { None } else { errors.first().cloned() },
+    })
+}
+
+/// Searches "BOTH" as concurrent NSE and BSE lookups, so the slower exchange sets the wait
+/// rather than the sum of the two.
 #[tauri::command]
 pub async fn search_companies(
+    app: AppHandle,
     query: String,
     exchange: Option<String>,
     limit: Option<i32>,
-) -> Result<CompanySearchResult, String> {
-    eprintln!("[PythonBridge] Searching companies: {}", query);
+) -> Result<CompanySearchResult, AppError> {
+    info!("Searching companies: {}", query);
+    if settings::is_offline(&app) {
+        return Ok(CompanySearchResult::offline(Some(query)));
+    }
     
-    let exchange_str = exchange.unwrap_or_else(|| "BOTH".to_string());
+    let scraper = scraper_settings(&app);
+    let exchange_str = exchange.unwrap_or(scraper.default_exchange);
     let limit_val = limit.unwrap_or(10);
-    
-    let script = format!(
-        "import sys; sys.path.extend(['python', '../python']); from scraper_bridge import search_companies_bridge; result = search_companies_bridge('{}', '{}', {}); print(result)",
-        query.replace("'", "\\'"),
-        exchange_str,
-        limit_val
-    );
 
-    match run_python_script_with_timeout(script, 45) {
-        Ok(stdout) => {
-            let result: serde_json::Value = serde_json::from_str(&stdout)
-                .map_err(|e| format!("Failed to parse search results: {}", e))?;
-            
+    let search = if exchange_str.eq_ignore_ascii_case("BOTH") {
+        let (nse, bse) = tokio::join!(
+            search_exchange(&app, &query, "NSE", limit_val),
+            search_exchange(&app, &query, "BSE", limit_val),
+        );
+        Ok(merge_searches(&query, limit_val, vec![("NSE", nse), ("BSE", bse)]))
+    } else {
+        search_exchange(&app, &query, &exchange_str, limit_val).await
+    };
+
+    match search {
+        Ok(result) => {
             let success = result.get("success").and_then(|v| v.as_bool()).unwrap_or(false);
             let count = result.get("count").and_then(|v| v.as_i64()).map(|v| v as i32);
             
@@ -433,16 +1245,18 @@ pub async fn search_companies(
                 error: result.get("error").and_then(|v| v.as_str()).map(|s| s.to_string()),
                 query: Some(query),
                 count,
+                cached: None,
             })
         },
         Err(e) => {
-            eprintln!("[PythonBridge] Search error: {}", e);
+            error!("Search error: {}", e);
             Ok(CompanySearchResult {
                 success: false,
                 results: None,
                 error: Some(e),
                 query: Some(query),
                 count: Some(0),
+                cached: None,
             })
         }
     }
@@ -450,10 +1264,23 @@ pub async fn search_companies(
 
 #[tauri::command]
 pub async fn get_company_details(
+    app: AppHandle,
+    cache: tauri::State<'_, MarketCache>,
     symbol: String,
     exchange: String,
-) -> Result<CompanySearchResult, String> {
-    eprintln!("[PythonBridge] Getting company details: {} on {}", symbol, exchange);
+    force_refresh: Option<bool>,
+) -> Result<CompanySearchResult, AppError> {
+    info!("Getting company details: {} on {}", symbol, exchange);
+
+    let scraper = scraper_settings(&app);
+    if !force_refresh.unwrap_or(false) {
+        if let Some(payload) = cache.get(&app, "DETAILS", &symbol, &exchange, scraper.cache_ttl_secs) {
+            return Ok(CompanySearchResult::from_cache(symbol, payload));
+        }
+    }
+    if settings::is_offline(&app) {
+        return Ok(CompanySearchResult::offline(Some(symbol)));
+    }
     
     let script = format!(
         "import sys; sys.path.extend(['python', '../python']); from scraper_bridge import get_company_details_bridge; result = get_company_details_bridge('{}', '{}'); print(result)",
@@ -461,12 +1288,15 @@ pub async fn get_company_details(
         exchange
     );
 
-    match run_python_script_with_timeout(script, 15) {
+    match run_python_script_with_timeout(&app, script, Operation::ScraperRequest).await {
         Ok(stdout) => {
             let result: serde_json::Value = serde_json::from_str(&stdout)
                 .map_err(|e| format!("Failed to parse company details: {}", e))?;
             
             let success = result.get("success").and_then(|v| v.as_bool()).unwrap_or(false);
+            if success {
+                cache.put(&app, "DETAILS", &symbol, &exchange, &result);
+            }
             
             Ok(CompanySearchResult {
                 success,
@@ -474,16 +1304,18 @@ pub async fn get_company_details(
                 error: result.get("error").and_then(|v| v.as_str()).map(|s| s.to_string()),
                 query: Some(symbol),
                 count: if success { Some(1) } else { Some(0) },
+                cached: None,
             })
         },
         Err(e) => {
-            eprintln!("[PythonBridge] Details error: {}", e);
+            error!("Details error: {}", e);
             Ok(CompanySearchResult {
                 success: false,
                 results: None,
-                error: Some(e),
+                error: Some(e.to_string()),
                 query: Some(symbol),
                 count: Some(0),
+                cached: None,
             })
         }
     }
@@ -491,10 +1323,54 @@ pub async fn get_company_details(
 
 #[tauri::command]
 pub async fn get_stock_quote(
+    app: AppHandle,
+    cache: tauri::State<'_, MarketCache>,
     symbol: String,
     exchange: String,
-) -> Result<CompanySearchResult, String> {
-    eprintln!("[PythonBridge] Getting stock quote: {} on {}", symbol, exchange);
+    force_refresh: Option<bool>,
+    provider: Option<MarketDataProvider>,
+) -> Result<CompanySearchResult, AppError> {
+    info!("Getting stock quote: {} on {}", symbol, exchange);
+
+    let scraper = scraper_settings(&app);
+    if !force_refresh.unwrap_or(false) {
+        if let Some(payload) = cache.get(&app, "QUOTE", &symbol, &exchange, scraper.cache_ttl_secs) {
+            return Ok(CompanySearchResult::from_cache(symbol, payload));
+        }
+    }
+
+    if settings::is_offline(&app) {
+        return Ok(CompanySearchResult::offline(Some(symbol)));
+    }
+
+    // The chosen provider first; behind the native one the Python scrapers remain the fallback
+    let timeout = Duration::from_secs(scraper.request_timeout_secs);
+    let provider = market_providers::resolve(&app, provider);
+    match market_providers::fetch_quote(&app, provider, &symbol, &exchange, timeout).await {
+        Ok(quote) => {
+            let payload = scraper::quote_payload(&quote, provider.as_str());
+            cache.put(&app, "QUOTE", &symbol, &exchange, &payload);
+            return Ok(CompanySearchResult {
+                success: true,
+                results: Some(payload),
+                error: None,
+                query: Some(symbol),
+                count: Some(1),
+                cached: None,
+            });
+        }
+        Err(e) if provider != MarketDataProvider::Native => {
+            return Ok(CompanySearchResult {
+                success: false,
+                results: None,
+                error: Some(e),
+                query: Some(symbol),
+                count: Some(0),
+                cached: None,
+            });
+        }
+        Err(e) => warn!("Native quote failed ({}), falling back to Python", e),
+    }
     
     let script = format!(
         "import sys; sys.path.extend(['python', '../python']); from scraper_bridge import get_stock_quote_bridge; result = get_stock_quote_bridge('{}', '{}'); print(result)",
@@ -502,12 +1378,15 @@ pub async fn get_stock_quote(
         exchange
     );
 
-    match run_python_script_with_timeout(script, 15) {
+    match run_python_script_with_timeout(&app, script, Operation::ScraperRequest).await {
         Ok(stdout) => {
             let result: serde_json::Value = serde_json::from_str(&stdout)
                 .map_err(|e| format!("Failed to parse stock quote: {}", e))?;
             
             let success = result.get("success").and_then(|v| v.as_bool()).unwrap_or(false);
+            if success {
+                cache.put(&app, "QUOTE", &symbol, &exchange, &result);
+            }
             
             Ok(CompanySearchResult {
                 success,
@@ -515,16 +1394,18 @@ pub async fn get_stock_quote(
                 error: result.get("error").and_then(|v| v.as_str()).map(|s| s.to_string()),
                 query: Some(symbol),
                 count: if success { Some(1) } else { Some(0) },
+                cached: None,
             })
         },
         Err(e) => {
-            eprintln!("[PythonBridge] Quote error: {}", e);
+            error!("Quote error: {}", e);
             Ok(CompanySearchResult {
                 success: false,
                 results: None,
-                error: Some(e),
+                error: Some(e.to_string()),
                 query: Some(symbol),
                 count: Some(0),
+                cached: None,
             })
         }
     }
@@ -532,16 +1413,20 @@ pub async fn get_stock_quote(
 
 #[tauri::command]
 pub async fn search_web(
+    app: AppHandle,
     query: String,
-) -> Result<CompanySearchResult, String> {
-    eprintln!("[PythonBridge] Web search: {}", query);
+) -> Result<CompanySearchResult, AppError> {
+    info!("Web search: {}", query);
+    if settings::is_offline(&app) {
+        return Ok(CompanySearchResult::offline(Some(query)));
+    }
     
     let script = format!(
         "import sys; sys.path.extend(['python', '../python']); from scraper_bridge import search_web_bridge; result = search_web_bridge('{}'); print(result)",
         query.replace("'", "\\'")
     );
 
-    match run_python_script_with_timeout(script, 30) {
+    match run_python_script_with_timeout(&app, script, Operation::WebSearch).await {
         Ok(stdout) => {
             let result: serde_json::Value = serde_json::from_str(&stdout)
                 .map_err(|e| format!("Failed to parse web search results: {}", e))?;
@@ -555,49 +1440,64 @@ pub async fn search_web(
                 error: result.get("error").and_then(|v| v.as_str()).map(|s| s.to_string()),
                 query: Some(query),
                 count,
+                cached: None,
             })
         },
         Err(e) => {
-            eprintln!("[PythonBridge] Web search error: {}", e);
+            error!("Web search error: {}", e);
             Ok(CompanySearchResult {
                 success: false,
                 results: None,
-                error: Some(e),
+                error: Some(e.to_string()),
                 query: Some(query),
                 count: Some(0),
+                cached: None,
             })
         }
     }
 }
 
 #[tauri::command]
-pub async fn get_scraper_status() -> Result<CompanySearchResult, String> {
-    eprintln!("[PythonBridge] Getting scraper status");
+pub async fn get_scraper_status(
+    app: AppHandle,
+    native: tauri::State<'_, NativeScraper>,
+) -> Result<CompanySearchResult, AppError> {
+    debug!("Getting scraper status");
     
-    let python_cmd = find_python().ok_or("Python not found")?;
+    let python_cmd = find_python(&python_settings(&app).interpreter_path).ok_or(AppError::PythonNotFound)?;
+    let db_path = db::pipeline_db_path(&app)?;
+    let _slot = jobs::python_slot(&app).await;
     
     let output = Command::new(&python_cmd)
+        .env("FINCALC_DB_PATH", db_path)
         .arg("-c")
         .arg("import sys; sys.path.extend(['python', '../python']); from scraper_bridge import get_scraper_status_bridge; result = get_scraper_status_bridge(); print(result)")
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
+        .kill_on_drop(true)
         .output()
+        .await
         .map_err(|e| format!("Failed to get scraper status: {}", e))?;
     
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
         return Ok(CompanySearchResult {
             success: false,
-            results: None,
+            // Native counters are still meaningful when the Python side is broken
+            results: Some(serde_json::json!({ "native": native.status() })),
             error: Some(stderr.to_string()),
             query: None,
             count: Some(0),
+            cached: None,
         });
     }
     
     let stdout = String::from_utf8_lossy(&output.stdout);
-    let result: serde_json::Value = serde_json::from_str(&stdout)
+    let mut result: serde_json::Value = serde_json::from_str(&stdout)
         .map_err(|e| format!("Failed to parse scraper status: {}", e))?;
+    if let Some(obj) = result.as_object_mut() {
+        obj.insert("native".to_string(), native.status());
+    }
     
     let success = result.get("success").and_then(|v| v.as_bool()).unwrap_or(false);
     
@@ -607,76 +1507,197 @@ pub async fn get_scraper_status() -> Result<CompanySearchResult, String> {
         error: None,
         query: None,
         count: None,
+        cached: None,
     })
 }
 
 #[tauri::command]
-pub async fn get_db_data() -> Result<serde_json::Value, String> {
-    eprintln!("[PythonBridge] Fetching DB data");
+pub async fn get_db_data(app: AppHandle) -> Result<serde_json::Value, AppError> {
+    info!("Fetching DB data");
 
-    let python_cmd = find_python().ok_or("Python not found")?;
+    let py_settings = python_settings(&app);
+    let python_cmd = find_python(&py_settings.interpreter_path).ok_or(AppError::PythonNotFound)?;
     let api_script = find_api_script()?;
 
     let request = serde_json::json!({
         "command": "get_db_data"
     });
 
-    let mut child = Command::new(&python_cmd)
-        .arg(&api_script)
-        .stdin(Stdio::piped())
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(|e| format!("Failed to spawn Python: {}", e))?;
+    let mut api = spawn_api(&app, &python_cmd, &api_script).await?;
+    api.send(&request.to_string()).await?;
+    api.close_stdin();
+
+    // Extended timeout for DB queries
+    let final_response = timeouts::run(&app, Operation::DbQuery, async { Ok::<_, AppError>(api.response().await) }).await?;
+    api.finish(CLEANUP_GRACE).await;
 
-    // Send request
-    if let Some(mut stdin) = child.stdin.take() {
-        stdin.write_all(request.to_string().as_bytes())
-            .map_err(|e| format!("Failed to write: {}", e))?;
-        stdin.write_all(b"\n").ok();
-        stdin.flush().ok();
+    match final_response {
+        Some(response) => {
+            // Return the full response including status and data
+            let response_value = serde_json::to_value(&response)
+                .map_err(|e| format!("Failed to serialize response: {}", e))?;
+            Ok(response_value)
+        }
+        None => Err(AppError::Python("No response from Python for DB data fetch".to_string())),
     }
+}
 
-    // Read response with extended timeout for DB queries
-    let stdout = child.stdout.take()
-        .ok_or("Failed to capture Python stdout")?;
-    let reader = BufReader::new(stdout);
+// =============================================================================
+// CHUNKED DATABASE FETCH - LARGE RESULT SETS WITHOUT ONE BIG RESPONSE
+// =============================================================================
+
+// Rows per `db-data-chunk` event unless the caller asks for another size
+const DB_CHUNK_ROWS: usize = 500;
+const MAX_DB_CHUNK_ROWS: usize = 10_000;
+
+// The tables `get_db_data` returns, in the same order, without its row limits
+const DB_DATA_TABLES: [(&str, &str); 4] = [
+    ("documents", "ORDER BY id DESC"),
+    ("financial_items", "ORDER BY row_index ASC"),
+    ("scraper_data", "ORDER BY created_at DESC"),
+    ("extraction_checklist", ""),
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DbDataChunk {
+    pub table: String,
+    pub index: usize,
+    pub rows: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DbTableSummary {
+    pub table: String,
+    pub rows: usize,
+    pub chunks: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DbDataSummary {
+    pub tables: Vec<DbTableSummary>,
+    pub chunk_rows: usize,
+    pub cancelled: bool,
+}
+
+fn sql_to_json(value: rusqlite::types::ValueRef) -> serde_json::Value {
+    use rusqlite::types::ValueRef;
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => f.into(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into(),
+        ValueRef::Blob(b) => format!("<{} bytes>", b.len()).into(),
+    }
+}
+
+fn emit_chunk(app: &AppHandle, window: &str, event: &str, table: &str, summary: &mut DbTableSummary, rows: Vec<serde_json::Value>) {
+    let chunk = DbDataChunk { table: table.to_string(), index: summary.chunks, rows };
+    if let Err(e) = app.emit_to(window, event, chunk) {
+        warn!("Failed to emit db-data-chunk event: {}", e);
+    }
+    summary.chunks += 1;
+}
 
-    let mut final_response: Option<PythonResponse> = None;
-    let timeout_duration = Duration::from_secs(30); // 30 seconds for DB query
-    let start_time = Instant::now();
+/// Read each table row by row and emit it `chunk_rows` at a time, so only one chunk is held
+/// here and the frontend can render as chunks land.
+fn stream_tables(
+    app: &AppHandle,
+    window: &str,
+    tables: &[(&str, &str)],
+    chunk_rows: usize,
+    task: &TaskHandle,
+) -> Result<DbDataSummary, String> {
+    let chunk_event = windows::scoped_event(window, "db-data-chunk");
+    let mut summary = DbDataSummary { tables: Vec::new(), chunk_rows, cancelled: false };
 
-    for line in reader.lines() {
-        if start_time.elapsed() > timeout_duration {
-            eprintln!("[PythonBridge] DB data fetch timeout");
-            let _ = child.kill();
-            return Err("Database query timed out after 30 seconds. The database may be locked or contain too much data.".to_string());
+    let db_path = profiles::active_db_path(app);
+    if !db_path.exists() {
+        return Ok(summary);
+    }
+    let conn = Connection::open(&db_path).map_err(|e| e.to_string())?;
+
+    for (position, (table, order)) in tables.iter().enumerate() {
+        let exists = conn.query_row(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            params![table],
+            |_| Ok(()),
+        ).is_ok();
+        if !exists {
+            continue;
         }
 
-        if let Ok(line) = line {
-            if !line.trim().starts_with('{') {
-                continue;
-            }
+        let mut stmt = conn.prepare(&format!("SELECT * FROM {} {}", table, order)).map_err(|e| e.to_string())?;
+        let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+        let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+        let mut table_summary = DbTableSummary { table: table.to_string(), rows: 0, chunks: 0 };
+        let mut chunk: Vec<serde_json::Value> = Vec::with_capacity(chunk_rows);
 
-            if let Ok(response) = serde_json::from_str::<PythonResponse>(&line) {
-                final_response = Some(response);
-                break;
+        while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+            let mut object = serde_json::Map::new();
+            for (i, column) in columns.iter().enumerate() {
+                object.insert(column.clone(), sql_to_json(row.get_ref(i).map_err(|e| e.to_string())?));
+            }
+            chunk.push(serde_json::Value::Object(object));
+            table_summary.rows += 1;
+            if chunk.len() == chunk_rows {
+                if task.is_cancelled() {
+                    summary.cancelled = true;
+                    summary.tables.push(table_summary);
+                    return Ok(summary);
+                }
+                emit_chunk(app, window, &chunk_event, table, &mut table_summary, std::mem::take(&mut chunk));
             }
         }
+        // An empty table still gets one (empty) chunk so the view can show it
+        if !chunk.is_empty() || table_summary.chunks == 0 {
+            emit_chunk(app, window, &chunk_event, table, &mut table_summary, chunk);
+        }
+        task.progress(
+            (position + 1) as f64 / tables.len() as f64 * 100.0,
+            format!("{}: {} rows", table, table_summary.rows),
+        );
+        summary.tables.push(table_summary);
     }
+    Ok(summary)
+}
 
-    // Wait for process to finish
-    let _ = child.wait();
+/// Chunked alternative to `get_db_data` for large databases: rows go to the calling window as
+/// `db-data-chunk` events of `chunk_rows` rows each, then a `db-data-summary` event closes the
+/// stream. The summary is also returned. Reads the active profile's DB directly, so neither side
+/// ever holds the whole result set.
+#[tauri::command]
+pub async fn stream_db_data(
+    app: AppHandle,
+    window: tauri::Window,
+    chunk_rows: Option<usize>,
+    tables: Option<Vec<String>>,
+) -> Result<DbDataSummary, AppError> {
+    let chunk_rows = chunk_rows.unwrap_or(DB_CHUNK_ROWS).clamp(1, MAX_DB_CHUNK_ROWS);
+    let selected: Vec<(&str, &str)> = DB_DATA_TABLES.iter()
+        .filter(|(table, _)| tables.as_ref().is_none_or(|wanted| wanted.iter().any(|w| w == table)))
+        .copied()
+        .collect();
+    info!(chunk_rows, tables = selected.len(), "Streaming DB data");
 
-    match final_response {
-        Some(response) => {
-            // Return the full response including status and data
-            let response_value = serde_json::to_value(&response)
-                .map_err(|e| format!("Failed to serialize response: {}", e))?;
-            Ok(response_value)
-        }
-        None => Err("No response from Python for DB data fetch".to_string()),
+    let window = window.label().to_string();
+    let task = tasks::register(&app, TaskKind::DbStreaming, "DB data");
+    let app_handle = app.clone();
+    let window_label = window.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        let result = stream_tables(&app_handle, &window_label, &selected, chunk_rows, &task);
+        task.finish(&result);
+        result
+    }).await.map_err(|e| e.to_string())?;
+
+    let summary = result?;
+    let summary_event = windows::scoped_event(&window, "db-data-summary");
+    if let Err(e) = app.emit_to(&window, &summary_event, &summary) {
+        warn!("Failed to emit db-data-summary event: {}", e);
     }
+    Ok(summary)
 }
 
 // =============================================================================
@@ -696,13 +1717,16 @@ pub struct DatabaseUpdate {
 pub async fn start_db_streaming(
     app: AppHandle,
     _window: tauri::Window,
-) -> Result<(), String> {
-    eprintln!("[PythonBridge] Starting database streaming for Raw DB view");
+) -> Result<(), AppError> {
+    info!("Starting database streaming for Raw DB view");
 
     // This command initiates a background task that queries the database periodically
     // and sends updates to the frontend
 
     let app_handle = app.clone();
+    // Only one Raw DB view streams at a time
+    tasks::cancel_kind(&app, TaskKind::DbStreaming);
+    let task = tasks::register(&app, TaskKind::DbStreaming, "Raw DB view");
 
     // Spawn background task
     std::thread::spawn(move || {
@@ -713,16 +1737,19 @@ pub async fn start_db_streaming(
 
             // Query database every 2 seconds
             std::thread::sleep(Duration::from_secs(2));
+            if task.is_cancelled() {
+                break;
+            }
 
-            // Get database path (Python uses extracted_data.db)
-            let db_path = "extracted_data.db";
-            if !std::path::Path::new(db_path).exists() {
+            // Get database path of the active profile (re-read so profile switches apply)
+            let db_path = profiles::active_db_path(&app_handle);
+            if !db_path.exists() {
                 continue;
             }
 
             // Open database and query
             let items = match (|| -> Result<Vec<serde_json::Value>, String> {
-                let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+                let conn = Connection::open(&db_path).map_err(|e| e.to_string())?;
                 
                 // Query recent items (with LIMIT to prevent timeout)
                 let mut items: Vec<serde_json::Value> = Vec::new();
@@ -744,7 +1771,7 @@ pub async fn start_db_streaming(
             })() {
                 Ok(items) => items,
                 Err(e) => {
-                    eprintln!("[PythonBridge] Database error: {}", e);
+                    error!("Database error: {}", e);
                     Vec::new()
                 }
             };
@@ -758,14 +1785,17 @@ pub async fn start_db_streaming(
 
             // Emit update to frontend
             if let Err(e) = app_handle.emit("db-update", update.clone()) {
-                eprintln!("[PythonBridge] Failed to emit db-update event: {}", e);
+                warn!("Failed to emit db-update event: {}", e);
             }
 
+            task.progress(counter as f64, format!("{} updates sent", counter));
+
             // Stop after 100 iterations (200 seconds)
             if counter > 100 {
                 break;
             }
         }
+        task.finish(&Ok::<(), String>(()));
     });
 
     Ok(())
@@ -774,12 +1804,13 @@ pub async fn start_db_streaming(
 #[tauri::command]
 pub async fn stop_db_streaming(
     app: AppHandle,
-) -> Result<(), String> {
-    eprintln!("[PythonBridge] Stopping database streaming");
+) -> Result<(), AppError> {
+    info!("Stopping database streaming");
+    tasks::cancel_kind(&app, TaskKind::DbStreaming);
 
     // Just emit a stop event
     if let Err(e) = app.emit("db-streaming-stopped", true) {
-        Err(format!("Failed to emit stop event: {}", e))
+        Err(AppError::Internal(format!("Failed to emit stop event: {}", e)))
     } else {
         Ok(())
     }
@@ -0,0 +1,268 @@
+// Python setup - finds an interpreter new enough for python/api.py (the configured path, PATH,
+// the Windows `py -3` launcher and common install folders) and checks its modules, reported
+// as one structure for the setup wizard
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use tauri::AppHandle;
+use tokio::process::Command;
+use tracing::{debug, info};
+
+use crate::error::AppError;
+use crate::jobs;
+use crate::python_bridge;
+
+/// Oldest Python that python/api.py and its dependencies run on.
+pub const MIN_PYTHON_VERSION: (u32, u32) = (3, 9);
+// (module, needed to parse at all); the others only enable OCR and table extras
+pub const PYTHON_MODULES: &[(&str, bool)] = &[
+    ("fitz", true),
+    ("pdfplumber", true),
+    ("pandas", false),
+    ("pytesseract", false),
+    ("easyocr", false),
+    ("cv2", false),
+];
+pub const MODULE_CHECK_TIMEOUT: Duration = Duration::from_secs(60);
+// Prints the real executable, so `py -3` resolves to a path we can start directly
+const PROBE_SCRIPT: &str = "import sys; print(sys.executable); print('.'.join(map(str, sys.version_info[:3])))";
+
+/// One interpreter that discovery tried.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PythonCandidate {
+    /// As it was started, e.g. "py -3" or an install folder's python.exe
+    pub command: String,
+    pub executable: Option<String>,
+    pub version: Option<String>,
+    /// Runs and is at least `MIN_PYTHON_VERSION`
+    pub supported: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleStatus {
+    pub name: String,
+    pub required: bool,
+    pub installed: bool,
+    /// The import error when not installed
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PythonSetupReport {
+    /// A supported interpreter was found and every required module imports
+    pub ready: bool,
+    /// The interpreter the app will use
+    pub executable: Option<String>,
+    pub version: Option<String>,
+    pub minimum_version: String,
+    pub candidates: Vec<PythonCandidate>,
+    /// Empty when no supported interpreter was found
+    pub modules: Vec<ModuleStatus>,
+    /// What stands between the user and a working setup, in the order to fix them
+    pub problems: Vec<String>,
+}
+
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.split('.');
+    Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+}
+
+/// `python.exe` in `Python3*` folders under `root`, newest version first.
+fn install_dirs(root: PathBuf) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(&root) else { return Vec::new() };
+    let mut found: Vec<(u32, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let minor = name.strip_prefix("Python3")?.parse::<u32>().ok()?;
+            let exe = entry.path().join("python.exe");
+            exe.exists().then_some((minor, exe))
+        })
+        .collect();
+    found.sort_by_key(|(minor, _)| std::cmp::Reverse(*minor));
+    found.into_iter().map(|(_, exe)| exe).collect()
+}
+
+/// Command lines to try, best first: the configured interpreter, then PATH, then on Windows
+/// the launcher and the folders the python.org installer uses.
+fn candidates(interpreter_path: &str) -> Vec<Vec<String>> {
+    let mut candidates: Vec<Vec<String>> = Vec::new();
+    let configured = interpreter_path.trim();
+    if !configured.is_empty() {
+        candidates.push(vec![configured.to_string()]);
+    }
+    if cfg!(windows) {
+        candidates.push(vec!["py".to_string(), "-3".to_string()]);
+        candidates.push(vec!["python".to_string()]);
+        candidates.push(vec!["python3".to_string()]);
+        let roots = [
+            std::env::var("LOCALAPPDATA").ok().map(|dir| PathBuf::from(dir).join("Programs").join("Python")),
+            std::env::var("ProgramFiles").ok().map(PathBuf::from),
+            std::env::var("ProgramFiles(x86)").ok().map(PathBuf::from),
+            Some(PathBuf::from("C:\\")),
+        ];
+        for root in roots.into_iter().flatten() {
+            candidates.extend(install_dirs(root).into_iter().map(|exe| vec![exe.to_string_lossy().to_string()]));
+        }
+    } else {
+        candidates.push(vec!["python3".to_string()]);
+        candidates.push(vec!["python".to_string()]);
+    }
+    candidates
+}
+
+/// Start `command` and ask it for its executable and version.
+fn probe(command: &[String]) -> PythonCandidate {
+    let mut candidate = PythonCandidate {
+        command: command.join(" "),
+        executable: None,
+        version: None,
+        supported: false,
+        error: None,
+    };
+    let output = std::process::Command::new(&command[0])
+        .args(&command[1..])
+        .arg("-c")
+        .arg(PROBE_SCRIPT)
+        .stdin(Stdio::null())
+        .output();
+    let output = match output {
+        Ok(output) if output.status.success() => output,
+        // The Microsoft Store alias runs but only prints an install hint
+        Ok(output) => {
+            candidate.error = Some(String::from_utf8_lossy(&output.stderr).trim().to_string());
+            return candidate;
+        }
+        Err(e) => {
+            candidate.error = Some(e.to_string());
+            return candidate;
+        }
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines().map(str::trim);
+    let executable = lines.next().filter(|exe| !exe.is_empty()).map(str::to_string);
+    let version = lines.next().map(str::to_string);
+    // An embedded interpreter may not know its own path; a plain command still starts it
+    candidate.executable = executable.or_else(|| (command.len() == 1).then(|| command[0].clone()));
+    candidate.supported = candidate.executable.is_some()
+        && version.as_deref().and_then(parse_version).is_some_and(|v| v >= MIN_PYTHON_VERSION);
+    if !candidate.supported && candidate.error.is_none() {
+        candidate.error = Some(format!(
+            "Python {} is older than {}.{}",
+            version.as_deref().unwrap_or("?"),
+            MIN_PYTHON_VERSION.0,
+            MIN_PYTHON_VERSION.1
+        ));
+    }
+    candidate.version = version;
+    candidate
+}
+
+/// The first supported interpreter, trying no more candidates than needed.
+pub fn find(interpreter_path: &str) -> Option<String> {
+    candidates(interpreter_path).iter()
+        .map(|command| probe(command))
+        .find(|candidate| candidate.supported)
+        .and_then(|candidate| candidate.executable)
+}
+
+/// Import every module in `PYTHON_MODULES` with `python`, once a Python slot is free.
+pub async fn check_modules(app: &AppHandle, python: &str) -> Result<Vec<ModuleStatus>, AppError> {
+    let names: Vec<&str> = PYTHON_MODULES.iter().map(|(name, _)| *name).collect();
+    let script = format!(
+        "import importlib, json\n\
+         result = {{}}\n\
+         for name in {}:\n    \
+             try:\n        \
+                 importlib.import_module(name)\n        \
+                 result[name] = None\n    \
+             except Exception as e:\n        \
+                 result[name] = str(e)\n\
+         print(json.dumps(result))",
+        serde_json::to_string(&names).unwrap_or_default()
+    );
+    let _slot = jobs::python_slot(app).await;
+    let child = Command::new(python)
+        .arg("-c")
+        .arg(&script)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| AppError::Python(format!("Failed to spawn Python: {}", e)))?;
+    let output = tokio::time::timeout(MODULE_CHECK_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| AppError::Timeout("Module check timed out".to_string()))?
+        .map_err(|e| AppError::Python(format!("Error waiting for process: {}", e)))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let result: serde_json::Value = serde_json::from_str(stdout.trim())
+        .map_err(|_| AppError::Python(format!("Unreadable module check output: {}", String::from_utf8_lossy(&output.stderr).trim())))?;
+    Ok(PYTHON_MODULES.iter()
+        .map(|(name, required)| ModuleStatus {
+            name: name.to_string(),
+            required: *required,
+            installed: result[*name].is_null(),
+            error: result[*name].as_str().map(str::to_string),
+        })
+        .collect())
+}
+
+// Tauri Commands
+/// Everything discovery tried, the interpreter it picked and that interpreter's modules.
+#[tauri::command]
+pub async fn check_python_setup(app: AppHandle) -> Result<PythonSetupReport, AppError> {
+    let interpreter_path = python_bridge::python_settings(&app).interpreter_path;
+    let candidates = tauri::async_runtime::spawn_blocking(move || {
+        candidates(&interpreter_path).iter().map(|command| probe(command)).collect::<Vec<_>>()
+    })
+    .await
+    .map_err(|e| AppError::Internal(e.to_string()))?;
+    for candidate in &candidates {
+        debug!(command = %candidate.command, version = ?candidate.version, supported = candidate.supported, "Python candidate");
+    }
+
+    let selected = candidates.iter().find(|candidate| candidate.supported).cloned();
+    let minimum_version = format!("{}.{}", MIN_PYTHON_VERSION.0, MIN_PYTHON_VERSION.1);
+    let mut problems = Vec::new();
+    let modules = match selected.as_ref().and_then(|candidate| candidate.executable.as_deref()) {
+        Some(python) => match check_modules(&app, python).await {
+            Ok(modules) => modules,
+            Err(e) => {
+                problems.push(e.to_string());
+                Vec::new()
+            }
+        },
+        None => {
+            let newest = candidates.iter().filter_map(|candidate| candidate.version.as_deref()).next();
+            problems.push(match newest {
+                Some(version) => format!("Python {} was found, but {} or newer is required", version, minimum_version),
+                None => format!("No Python installation found; install Python {} or newer", minimum_version),
+            });
+            Vec::new()
+        }
+    };
+    let missing: Vec<&str> = modules.iter()
+        .filter(|module| module.required && !module.installed)
+        .map(|module| module.name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        problems.push(format!("Required modules missing: {}", missing.join(", ")));
+    }
+
+    let ready = selected.is_some() && problems.is_empty();
+    info!(ready, python = ?selected.as_ref().and_then(|c| c.executable.as_deref()), "Python setup checked");
+    Ok(PythonSetupReport {
+        ready,
+        executable: selected.as_ref().and_then(|candidate| candidate.executable.clone()),
+        version: selected.and_then(|candidate| candidate.version),
+        minimum_version,
+        candidates,
+        modules,
+        problems,
+    })
+}
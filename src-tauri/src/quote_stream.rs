@@ -0,0 +1,220 @@
+// Quote Stream - polls live quotes during market hours and emits `quote-update` when a quote
+// moved enough to matter; idles on weekends and exchange holidays (see `trading_calendar`)
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::scraper::{self, Quote};
+use crate::settings::{self, SettingsState};
+use crate::trading_calendar;
+
+// NSE/BSE have no public websocket feed, so streaming is polling-based.
+// While the market is closed the task only checks the clock at this interval.
+const IDLE_CHECK_SECS: u64 = 60;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteUpdate {
+    pub symbol: String,
+    pub exchange: String,
+    pub quote: Quote,
+    /// Change since the last update emitted for the symbol; None for the first one
+    pub delta: Option<QuoteDelta>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteDelta {
+    pub price: Option<f64>,
+    pub price_percent: Option<f64>,
+    pub volume: Option<f64>,
+}
+
+/// The last quote emitted per (symbol, exchange), for diffing the next poll against.
+type LastEmitted = HashMap<(String, String), Quote>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamStatus {
+    pub active: bool,
+    pub market_open: bool,
+    pub symbols: Vec<(String, String)>,
+}
+
+/// Holds the running stream task, if any. Starting a new stream replaces the old one.
+#[derive(Default)]
+pub struct QuoteStreamer {
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl QuoteStreamer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Abort the polling task; returns false if none was running.
+    pub fn stop(&self) -> bool {
+        let Ok(mut task) = self.task.lock() else { return false };
+        match task.take() {
+            Some(handle) => {
+                handle.abort();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Any of the streamed symbols' exchanges in session; holidays count as closed.
+fn any_open(app: &AppHandle, symbols: &[(String, String)]) -> bool {
+    symbols.iter().any(|(_, exchange)| trading_calendar::is_open(app, exchange))
+}
+
+fn stream_interval(app: &AppHandle) -> u64 {
+    let state = app.state::<SettingsState>();
+    let store = settings::blocking_read(&state);
+    store.get().scraper.stream_interval_secs.max(1)
+}
+
+/// (price, volume) percent thresholds below which a changed quote isn't worth an event.
+fn change_thresholds(app: &AppHandle) -> (f64, f64) {
+    let state = app.state::<SettingsState>();
+    let store = settings::blocking_read(&state);
+    let scraper = &store.get().scraper;
+    (scraper.stream_price_threshold_percent.max(0.0), scraper.stream_volume_threshold_percent.max(0.0))
+}
+
+/// Percent move from `old` to `new`; any move at all from zero counts as 100%.
+fn percent_change(old: f64, new: f64) -> f64 {
+    if old == 0.0 {
+        return if new == 0.0 { 0.0 } else { 100.0 };
+    }
+    (new - old) / old.abs() * 100.0
+}
+
+/// The change worth emitting from `last` to `quote`: Some(None) for the first quote, None when
+/// neither price nor volume moved past its threshold.
+fn diff(last: Option<&Quote>, quote: &Quote, (price_threshold, volume_threshold): (f64, f64)) -> Option<Option<QuoteDelta>> {
+    let Some(last) = last else { return Some(None) };
+    let moved = |old: Option<f64>, new: Option<f64>, threshold: f64| match (old, new) {
+        (Some(old), Some(new)) => new != old && percent_change(old, new).abs() >= threshold,
+        (old, new) => old.is_some() != new.is_some(),
+    };
+    if !moved(last.price, quote.price, price_threshold) && !moved(last.volume, quote.volume, volume_threshold) {
+        return None;
+    }
+    let delta = |old: Option<f64>, new: Option<f64>| Some(new? - old?);
+    Some(Some(QuoteDelta {
+        price: delta(last.price, quote.price),
+        price_percent: last.price.zip(quote.price).map(|(old, new)| percent_change(old, new)),
+        volume: delta(last.volume, quote.volume),
+    }))
+}
+
+async fn poll_once(app: &AppHandle, symbols: &[(String, String)], max_age_secs: u64, last_emitted: &mut LastEmitted) {
+    let thresholds = change_thresholds(app);
+    // A long watchlist at once would get the app rate-limited by NSE/Yahoo
+    let results: Vec<_> = futures_util::stream::iter(symbols)
+        .map(|(symbol, exchange)| async move {
+            (symbol, exchange, scraper::get_quote(app, symbol, exchange, max_age_secs).await)
+        })
+        .buffer_unordered(scraper::MAX_CONCURRENT_QUOTES)
+        .collect()
+        .await;
+
+    for (symbol, exchange, result) in results {
+        match result {
+            Ok(quote) => {
+                let key = (symbol.clone(), exchange.clone());
+                let Some(delta) = diff(last_emitted.get(&key), &quote, thresholds) else { continue };
+                let _ = app.emit("quote-update", &QuoteUpdate {
+                    symbol: symbol.clone(),
+                    exchange: exchange.clone(),
+                    quote: quote.clone(),
+                    delta,
+                });
+                last_emitted.insert(key, quote);
+            }
+            Err(e) => eprintln!("[QuoteStream] Failed to fetch {} ({}): {}", symbol, exchange, e),
+        }
+    }
+}
+
+fn spawn_stream(app: AppHandle, symbols: Vec<(String, String)>) -> JoinHandle<()> {
+    tauri::async_runtime::spawn(async move {
+        let mut was_open = None;
+        let mut last_emitted = LastEmitted::new();
+        loop {
+            // Offline mode idles the stream the same way a closed market does
+            let market_open = any_open(&app, &symbols) && !settings::is_offline(&app);
+            if was_open != Some(market_open) {
+                eprintln!("[QuoteStream] Market {}", if market_open { "open, streaming" } else { "closed, idling" });
+                let _ = app.emit("quote-stream-status", &StreamStatus {
+                    active: true,
+                    market_open,
+                    symbols: symbols.clone(),
+                });
+                was_open = Some(market_open);
+            }
+
+            if market_open {
+                let interval = stream_interval(&app);
+                poll_once(&app, &symbols, interval, &mut last_emitted).await;
+                tokio::time::sleep(Duration::from_secs(interval)).await;
+            } else {
+                tokio::time::sleep(Duration::from_secs(IDLE_CHECK_SECS)).await;
+            }
+        }
+    })
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn start_quote_streaming(
+    app: AppHandle,
+    streamer: tauri::State<'_, QuoteStreamer>,
+    symbols: Vec<(String, String)>,
+) -> Result<StreamStatus, String> {
+    let symbols: Vec<(String, String)> = symbols.into_iter()
+        .map(|(symbol, exchange)| (symbol.trim().to_uppercase(), exchange.trim().to_uppercase()))
+        .filter(|(symbol, _)| !symbol.is_empty())
+        .collect();
+    if symbols.is_empty() {
+        return Err("No symbols to stream".to_string());
+    }
+
+    let mut task = streamer.task.lock().map_err(|e| e.to_string())?;
+    if let Some(handle) = task.take() {
+        handle.abort();
+    }
+    *task = Some(spawn_stream(app.clone(), symbols.clone()));
+
+    Ok(StreamStatus {
+        active: true,
+        market_open: any_open(&app, &symbols),
+        symbols,
+    })
+}
+
+#[tauri::command]
+pub fn stop_quote_streaming(
+    app: AppHandle,
+    streamer: tauri::State<'_, QuoteStreamer>,
+) -> Result<StreamStatus, String> {
+    let mut task = streamer.task.lock().map_err(|e| e.to_string())?;
+    if let Some(handle) = task.take() {
+        handle.abort();
+    }
+
+    let status = StreamStatus {
+        active: false,
+        market_open: trading_calendar::is_open(&app, "NSE"),
+        symbols: Vec::new(),
+    };
+    let _ = app.emit("quote-stream-status", &status);
+    Ok(status)
+}
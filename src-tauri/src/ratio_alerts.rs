@@ -0,0 +1,356 @@
+// Ratio Alerts - thresholds and trends on key ratios ("interest coverage below 2", "current
+// ratio falling 3 periods straight"), checked whenever a document is stored
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use tracing::{info, warn};
+
+use crate::db;
+use crate::duplicates;
+use crate::periods::Period;
+use crate::ratios;
+use crate::statements::{self, StoredDocument};
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS ratio_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    ratio_key TEXT NOT NULL,
+    condition TEXT NOT NULL,   -- 'below', 'above', 'falling_for', 'rising_for'
+    threshold REAL,
+    periods INTEGER NOT NULL DEFAULT 3,
+    active INTEGER NOT NULL DEFAULT 1,
+    created_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS ratio_findings (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    rule_id INTEGER NOT NULL,
+    document_id INTEGER NOT NULL,
+    ratio_key TEXT NOT NULL,
+    period TEXT NOT NULL,
+    observed_value REAL NOT NULL,
+    message TEXT NOT NULL,
+    found_at INTEGER NOT NULL,
+    UNIQUE(rule_id, document_id, period)
+);
+";
+const DEFAULT_TREND_PERIODS: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RatioCondition {
+    /// Latest value under `threshold`
+    Below,
+    /// Latest value over `threshold`
+    Above,
+    /// Down on the period before, `periods` times in a row
+    FallingFor,
+    /// Up on the period before, `periods` times in a row
+    RisingFor,
+}
+
+impl RatioCondition {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RatioCondition::Below => "below",
+            RatioCondition::Above => "above",
+            RatioCondition::FallingFor => "falling_for",
+            RatioCondition::RisingFor => "rising_for",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "below" => Some(RatioCondition::Below),
+            "above" => Some(RatioCondition::Above),
+            "falling_for" => Some(RatioCondition::FallingFor),
+            "rising_for" => Some(RatioCondition::RisingFor),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatioRule {
+    pub id: i64,
+    /// A `ratios::KEYS` entry, e.g. "interest_coverage"
+    pub ratio_key: String,
+    pub condition: RatioCondition,
+    /// For `below` and `above`
+    pub threshold: Option<f64>,
+    /// For `falling_for` and `rising_for`
+    pub periods: u32,
+    pub active: bool,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatioFinding {
+    pub id: i64,
+    pub rule_id: i64,
+    pub document_id: i64,
+    pub ratio_key: String,
+    /// The latest period the rule looked at
+    pub period: String,
+    pub observed_value: f64,
+    pub message: String,
+    pub found_at: i64,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+pub fn load_rules(app: &AppHandle, active_only: bool) -> Result<Vec<RatioRule>, String> {
+    let conn = db::open_app_db(app)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, ratio_key, condition, threshold, periods, active, created_at FROM ratio_rules
+         WHERE ?1 = 0 OR active = 1 ORDER BY id"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![active_only], |row| {
+        let condition: String = row.get(2)?;
+        Ok((row.get(0)?, row.get(1)?, condition, row.get(3)?, row.get(4)?, row.get::<_, i64>(5)?, row.get(6)?))
+    }).map_err(|e| e.to_string())?;
+
+    let mut rules = Vec::new();
+    for row in rows {
+        let (id, ratio_key, condition, threshold, periods, active, created_at) = row.map_err(|e| e.to_string())?;
+        // Skip rows written by a newer version with conditions we don't know
+        if let Some(condition) = RatioCondition::parse(&condition) {
+            rules.push(RatioRule { id, ratio_key, condition, threshold, periods, active: active != 0, created_at });
+        }
+    }
+    Ok(rules)
+}
+
+/// Every period the document's items carry an amount for.
+pub fn document_periods(document: &StoredDocument) -> BTreeSet<Period> {
+    document.items.iter()
+        .filter_map(|item| item["periods"].as_object())
+        .flat_map(|amounts| amounts.keys().filter_map(|key| Period::parse(key)))
+        .collect()
+}
+
+/// The document's items with `period` as their current period and the year before it as the
+/// previous one, for the functions that read (current, previous) pairs.
+pub fn items_at(document: &StoredDocument, period: Period) -> Vec<serde_json::Value> {
+    document.items.iter()
+        .map(|item| {
+            let mut item = item.clone();
+            item["currentPeriod"] = period.to_string().into();
+            item["previousPeriod"] = period.prior().to_string().into();
+            item
+        })
+        .collect()
+}
+
+/// Every ratio at every period the document's items are keyed by, each period compared with
+/// the same period a year earlier.
+pub fn document_series(document: &StoredDocument) -> BTreeMap<String, BTreeMap<Period, f64>> {
+    let mut series: BTreeMap<String, BTreeMap<Period, f64>> = BTreeMap::new();
+    for period in document_periods(document) {
+        for ratio in ratios::compute(&items_at(document, period)) {
+            if let Some(value) = ratio.current {
+                series.entry(ratio.key).or_default().insert(period, value);
+            }
+        }
+    }
+    series
+}
+
+/// Ratio history for the company `document` belongs to: its own periods plus those of the
+/// company's other documents, later documents winning where they restate a period.
+pub fn company_series(app: &AppHandle, document: &StoredDocument) -> Result<BTreeMap<String, BTreeMap<Period, f64>>, String> {
+    let mut series: BTreeMap<String, BTreeMap<Period, f64>> = BTreeMap::new();
+    for document in &company_documents(app, document)? {
+        for (key, values) in document_series(document) {
+            series.entry(key).or_default().extend(values);
+        }
+    }
+    Ok(series)
+}
+
+/// `document` and the company's other stored documents, oldest first.
+pub fn company_documents(app: &AppHandle, document: &StoredDocument) -> Result<Vec<StoredDocument>, String> {
+    let mut documents = vec![document.clone()];
+    if let Some(company) = duplicates::company(document) {
+        for summary in statements::list_documents(app)? {
+            if summary.id == document.id {
+                continue;
+            }
+            let other = statements::load_document(app, summary.id)?;
+            if duplicates::company(&other).as_deref() == Some(company.as_str()) {
+                documents.push(other);
+            }
+        }
+    }
+    documents.sort_by_key(|document| document.id);
+    Ok(documents)
+}
+
+/// (latest period, observed value, message) when `rule` holds for `values`.
+fn evaluate(rule: &RatioRule, values: &BTreeMap<Period, f64>) -> Option<(Period, f64, String)> {
+    let (&latest, &value) = values.iter().next_back()?;
+    match rule.condition {
+        RatioCondition::Below | RatioCondition::Above => {
+            let threshold = rule.threshold?;
+            let met = if rule.condition == RatioCondition::Below { value < threshold } else { value > threshold };
+            met.then(|| (latest, value, format!("{} is {:.2} in {}, {} {:.2}", rule.ratio_key, value, latest, rule.condition.as_str(), threshold)))
+        }
+        RatioCondition::FallingFor | RatioCondition::RisingFor => {
+            // Years with years, quarters with quarters
+            let points: Vec<f64> = values.iter()
+                .filter(|(period, _)| period.quarter.is_some() == latest.quarter.is_some())
+                .map(|(_, value)| *value)
+                .collect();
+            let needed = rule.periods as usize + 1;
+            if points.len() < needed {
+                return None;
+            }
+            let recent = &points[points.len() - needed..];
+            let falling = rule.condition == RatioCondition::FallingFor;
+            let met = recent.windows(2).all(|pair| if falling { pair[1] < pair[0] } else { pair[1] > pair[0] });
+            let direction = if falling { "fallen" } else { "risen" };
+            met.then(|| (latest, value, format!("{} has {} {} periods straight, to {:.2} in {}", rule.ratio_key, direction, rule.periods, value, latest)))
+        }
+    }
+}
+
+/// Evaluate every active rule for `document_id`; findings already recorded for the same rule,
+/// document and period are not raised again. Returns the new ones, each also emitted as
+/// `ratio-alert`.
+pub fn evaluate_document(app: &AppHandle, document_id: i64) -> Result<Vec<RatioFinding>, String> {
+    let rules = load_rules(app, true)?;
+    if rules.is_empty() {
+        return Ok(Vec::new());
+    }
+    let document = statements::load_document(app, document_id)?;
+    let series = company_series(app, &document)?;
+
+    let conn = db::open_app_db(app)?;
+    let mut findings = Vec::new();
+    for rule in &rules {
+        let Some(values) = series.get(&rule.ratio_key) else { continue };
+        let Some((period, observed_value, message)) = evaluate(rule, values) else { continue };
+        let found_at = now_secs();
+        let inserted = conn.execute(
+            "INSERT OR IGNORE INTO ratio_findings (rule_id, document_id, ratio_key, period, observed_value, message, found_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![rule.id, document_id, rule.ratio_key, period.to_string(), observed_value, message, found_at],
+        ).map_err(|e| e.to_string())?;
+        if inserted == 0 {
+            continue;
+        }
+        let finding = RatioFinding {
+            id: conn.last_insert_rowid(),
+            rule_id: rule.id,
+            document_id,
+            ratio_key: rule.ratio_key.clone(),
+            period: period.to_string(),
+            observed_value,
+            message,
+            found_at,
+        };
+        info!(document_id, rule_id = rule.id, "{}", finding.message);
+        let _ = app.emit("ratio-alert", &finding);
+        findings.push(finding);
+    }
+    Ok(findings)
+}
+
+/// `evaluate_document` for a document just stored; never fails the store.
+pub fn check(app: &AppHandle, document_id: i64) {
+    if let Err(e) = evaluate_document(app, document_id) {
+        warn!(document_id, "Ratio rules not evaluated: {}", e);
+    }
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn create_ratio_rule(
+    app: AppHandle,
+    ratio_key: String,
+    condition: RatioCondition,
+    threshold: Option<f64>,
+    periods: Option<u32>,
+) -> Result<RatioRule, String> {
+    let ratio_key = ratio_key.trim().to_lowercase();
+    if !ratios::KEYS.contains(&ratio_key.as_str()) {
+        return Err(format!("Unknown ratio: {} (known: {})", ratio_key, ratios::KEYS.join(", ")));
+    }
+    match condition {
+        RatioCondition::Below | RatioCondition::Above if !threshold.is_some_and(f64::is_finite) => {
+            return Err("Threshold must be a number".to_string());
+        }
+        _ => {}
+    }
+    let periods = periods.unwrap_or(DEFAULT_TREND_PERIODS);
+    if periods == 0 {
+        return Err("Periods must be at least 1".to_string());
+    }
+
+    let created_at = now_secs();
+    let conn = db::open_app_db(&app)?;
+    conn.execute(
+        "INSERT INTO ratio_rules (ratio_key, condition, threshold, periods, active, created_at) VALUES (?1, ?2, ?3, ?4, 1, ?5)",
+        params![ratio_key, condition.as_str(), threshold, periods, created_at],
+    ).map_err(|e| e.to_string())?;
+
+    Ok(RatioRule {
+        id: conn.last_insert_rowid(),
+        ratio_key,
+        condition,
+        threshold,
+        periods,
+        active: true,
+        created_at,
+    })
+}
+
+#[tauri::command]
+pub fn list_ratio_rules(app: AppHandle) -> Result<Vec<RatioRule>, String> {
+    load_rules(&app, false)
+}
+
+#[tauri::command]
+pub fn delete_ratio_rule(app: AppHandle, id: i64) -> Result<(), String> {
+    let conn = db::open_app_db(&app)?;
+    conn.execute("DELETE FROM ratio_rules WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Run the rules over a stored document now, e.g. after correcting its periods or units.
+#[tauri::command]
+pub fn evaluate_ratio_rules(app: AppHandle, document_id: i64) -> Result<Vec<RatioFinding>, String> {
+    evaluate_document(&app, document_id)
+}
+
+/// Findings newest first, optionally for one document.
+#[tauri::command]
+pub fn get_ratio_findings(app: AppHandle, document_id: Option<i64>, limit: Option<i64>) -> Result<Vec<RatioFinding>, String> {
+    let conn = db::open_app_db(&app)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, rule_id, document_id, ratio_key, period, observed_value, message, found_at
+         FROM ratio_findings WHERE ?1 IS NULL OR document_id = ?1 ORDER BY found_at DESC, id DESC LIMIT ?2"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![document_id, limit.unwrap_or(100)], |row| {
+        Ok(RatioFinding {
+            id: row.get(0)?,
+            rule_id: row.get(1)?,
+            document_id: row.get(2)?,
+            ratio_key: row.get(3)?,
+            period: row.get(4)?,
+            observed_value: row.get(5)?,
+            message: row.get(6)?,
+            found_at: row.get(7)?,
+        })
+    }).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
@@ -0,0 +1,228 @@
+// Ratios - key ratios computed natively from a document's extracted line items
+use serde::{Deserialize, Serialize};
+
+use crate::periods;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ratio {
+    pub key: String,
+    pub name: String,
+    pub category: String,
+    /// "x" for multiples, "%" for percentages
+    pub unit: String,
+    pub current: Option<f64>,
+    pub previous: Option<f64>,
+}
+
+// Ind AS / Schedule III wording first; the first label that matches wins
+const REVENUE: &[&str] = &["revenue from operations", "total revenue from operations", "net sales", "revenue", "total income"];
+const NET_PROFIT: &[&str] = &["profit for the year", "profit for the period", "net profit", "profit after tax", "profit/(loss) for the year"];
+const PROFIT_BEFORE_TAX: &[&str] = &["profit before tax", "profit before exceptional items and tax", "profit/(loss) before tax"];
+const FINANCE_COSTS: &[&str] = &["finance costs", "finance cost", "interest expense"];
+const TOTAL_ASSETS: &[&str] = &["total assets"];
+const TOTAL_EQUITY: &[&str] = &["total equity", "equity attributable to owners of the company", "shareholders' funds", "total shareholders' equity"];
+const CURRENT_ASSETS: &[&str] = &["total current assets", "current assets"];
+const CURRENT_LIABILITIES: &[&str] = &["total current liabilities", "current liabilities"];
+const INVENTORIES: &[&str] = &["inventories", "inventory"];
+const TOTAL_BORROWINGS: &[&str] = &["total borrowings", "total debt"];
+const LONG_TERM_BORROWINGS: &[&str] = &["non-current borrowings", "long-term borrowings", "long term borrowings", "borrowings"];
+const SHORT_TERM_BORROWINGS: &[&str] = &["current borrowings", "short-term borrowings", "short term borrowings"];
+const OPERATING_CASH_FLOW: &[&str] = &[
+    "net cash from operating activities",
+    "net cash generated from operating activities",
+    "net cash flow from operating activities",
+    "net cash generated from/(used in) operating activities",
+];
+const CAPITAL_EXPENDITURE: &[&str] = &[
+    "purchase of property, plant and equipment",
+    "acquisition of property, plant and equipment",
+    "purchase of property, plant and equipment (including capital work-in-progress)",
+    "purchase of fixed assets",
+    "capital expenditure",
+];
+const DEPRECIATION: &[&str] = &["depreciation and amortisation expense", "depreciation and amortization expense", "depreciation and amortisation", "depreciation"];
+
+/// Names user formulas can use for the items `compute` finds, besides raw labels.
+pub const MAPPED_ITEMS: &[&str] = &[
+    "revenue",
+    "net_profit",
+    "pbt",
+    "interest",
+    "depreciation",
+    "ebit",
+    "ebitda",
+    "total_assets",
+    "equity",
+    "current_assets",
+    "current_liabilities",
+    "inventories",
+    "debt",
+    "operating_cash_flow",
+    "capex",
+];
+
+/// Keys `compute` can return.
+pub const KEYS: &[&str] = &[
+    "current_ratio",
+    "quick_ratio",
+    "debt_to_equity",
+    "interest_coverage",
+    "net_profit_margin",
+    "return_on_equity",
+    "return_on_assets",
+    "asset_turnover",
+    "cash_conversion",
+];
+
+fn normalize(label: &str) -> String {
+    label.to_lowercase()
+        .replace(['\u{2019}', '`'], "'")
+        .trim_matches(|c: char| !c.is_alphanumeric() && c != ')')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn statement_of(item: &serde_json::Value) -> String {
+    let statement = item["statementType"].as_str().unwrap_or("").to_lowercase();
+    if statement == "cashflow" { "cash_flow".to_string() } else { statement }
+}
+
+/// (current, previous) at its periods for the first item whose label matches one of `labels`,
+/// exact matches before prefix matches. Balance sheet borrowings sit under both
+/// current and non-current headings, so `statement` narrows the search when given.
+pub fn find(items: &[serde_json::Value], labels: &[&str], statement: Option<&str>) -> Option<(f64, f64)> {
+    let candidates: Vec<(&serde_json::Value, String)> = items.iter()
+        .filter(|item| !item["isHeader"].as_bool().unwrap_or(false))
+        .filter(|item| statement.is_none_or(|s| statement_of(item) == s))
+        .map(|item| (item, normalize(item["label"].as_str().unwrap_or(""))))
+        .collect();
+    let hit = labels.iter()
+        .find_map(|label| candidates.iter().find(|(_, l)| l == label))
+        .or_else(|| labels.iter().find_map(|label| candidates.iter().find(|(_, l)| l.starts_with(label))))?;
+    let (current, previous) = periods::amounts(hit.0);
+    Some((current.unwrap_or(0.0), previous.unwrap_or(0.0)))
+}
+
+fn divide(numerator: Option<f64>, denominator: Option<f64>) -> Option<f64> {
+    match (numerator, denominator) {
+        (Some(n), Some(d)) if d != 0.0 => Some(n / d),
+        _ => None,
+    }
+}
+
+/// Total borrowings, else non-current plus current borrowings.
+fn total_debt(items: &[serde_json::Value]) -> Option<(f64, f64)> {
+    let bs = Some("balance_sheet");
+    let long_debt = find(items, LONG_TERM_BORROWINGS, bs);
+    let short_debt = find(items, SHORT_TERM_BORROWINGS, bs);
+    find(items, TOTAL_BORROWINGS, None).or(match (long_debt, short_debt) {
+        (None, None) => None,
+        (l, s) => {
+            let (l, s) = (l.unwrap_or((0.0, 0.0)), s.unwrap_or((0.0, 0.0)));
+            Some((l.0 + s.0, l.1 + s.1))
+        }
+    })
+}
+
+/// `profit` with the magnitude of an expense added back, e.g. EBIT from profit before tax.
+fn add_back(profit: Option<(f64, f64)>, expense: Option<(f64, f64)>) -> Option<(f64, f64)> {
+    match (profit, expense) {
+        (Some(p), Some(e)) => Some((p.0 + e.0.abs(), p.1 + e.1.abs())),
+        (p, _) => p,
+    }
+}
+
+/// (current, previous) for one of `MAPPED_ITEMS`; None when the name isn't mapped or the
+/// item wasn't found.
+pub fn mapped(items: &[serde_json::Value], name: &str) -> Option<(f64, f64)> {
+    let ebit = || add_back(find(items, PROFIT_BEFORE_TAX, None), find(items, FINANCE_COSTS, None));
+    match name {
+        "revenue" => find(items, REVENUE, None),
+        "net_profit" => find(items, NET_PROFIT, None),
+        "pbt" => find(items, PROFIT_BEFORE_TAX, None),
+        "interest" => find(items, FINANCE_COSTS, None).map(|f| (f.0.abs(), f.1.abs())),
+        "depreciation" => find(items, DEPRECIATION, None).map(|d| (d.0.abs(), d.1.abs())),
+        "ebit" => ebit(),
+        "ebitda" => add_back(ebit(), find(items, DEPRECIATION, None)),
+        "total_assets" => find(items, TOTAL_ASSETS, None),
+        "equity" => find(items, TOTAL_EQUITY, None),
+        "current_assets" => find(items, CURRENT_ASSETS, None),
+        "current_liabilities" => find(items, CURRENT_LIABILITIES, None),
+        "inventories" => find(items, INVENTORIES, Some("balance_sheet")).or_else(|| find(items, INVENTORIES, None)),
+        "debt" => total_debt(items),
+        "operating_cash_flow" => find(items, OPERATING_CASH_FLOW, None),
+        "capex" => find(items, CAPITAL_EXPENDITURE, None).map(|c| (c.0.abs(), c.1.abs())),
+        _ => None,
+    }
+}
+
+/// Key ratios for the current and previous year; ratios whose inputs weren't found are left out.
+pub fn compute(items: &[serde_json::Value]) -> Vec<Ratio> {
+    let bs = Some("balance_sheet");
+    let revenue = find(items, REVENUE, None);
+    let net_profit = find(items, NET_PROFIT, None);
+    let pbt = find(items, PROFIT_BEFORE_TAX, None);
+    let finance_costs = find(items, FINANCE_COSTS, None);
+    let total_assets = find(items, TOTAL_ASSETS, None);
+    let equity = find(items, TOTAL_EQUITY, None);
+    let current_assets = find(items, CURRENT_ASSETS, None);
+    let current_liabilities = find(items, CURRENT_LIABILITIES, None);
+    let inventories = find(items, INVENTORIES, bs).or_else(|| find(items, INVENTORIES, None));
+    let operating_cash = find(items, OPERATING_CASH_FLOW, None);
+    let debt = total_debt(items);
+    let ebit = add_back(pbt, finance_costs);
+    let quick_assets = match (current_assets, inventories) {
+        (Some(c), Some(i)) => Some((c.0 - i.0, c.1 - i.1)),
+        (c, None) => c,
+        _ => None,
+    };
+
+    // (key, name, category, unit, numerator, denominator, scale)
+    type Pair = Option<(f64, f64)>;
+    let definitions: [(&str, &str, &str, &str, Pair, Pair, f64); 9] = [
+        ("current_ratio", "Current Ratio", "liquidity", "x", current_assets, current_liabilities, 1.0),
+        ("quick_ratio", "Quick Ratio", "liquidity", "x", quick_assets, current_liabilities, 1.0),
+        ("debt_to_equity", "Debt to Equity", "leverage", "x", debt, equity, 1.0),
+        ("interest_coverage", "Interest Coverage", "leverage", "x", ebit, finance_costs.map(|f| (f.0.abs(), f.1.abs())), 1.0),
+        ("net_profit_margin", "Net Profit Margin", "profitability", "%", net_profit, revenue, 100.0),
+        ("return_on_equity", "Return on Equity", "profitability", "%", net_profit, equity, 100.0),
+        ("return_on_assets", "Return on Assets", "profitability", "%", net_profit, total_assets, 100.0),
+        ("asset_turnover", "Asset Turnover", "efficiency", "x", revenue, total_assets, 1.0),
+        ("cash_conversion", "Operating Cash Flow to Net Profit", "cash_flow", "x", operating_cash, net_profit, 1.0),
+    ];
+
+    definitions.into_iter()
+        .filter_map(|(key, name, category, unit, numerator, denominator, scale)| {
+            let current = divide(numerator.map(|n| n.0), denominator.map(|d| d.0)).map(|r| r * scale);
+            let previous = divide(numerator.map(|n| n.1), denominator.map(|d| d.1)).map(|r| r * scale);
+            current.or(previous)?;
+            Some(Ratio {
+                key: key.to_string(),
+                name: name.to_string(),
+                category: category.to_string(),
+                unit: unit.to_string(),
+                current,
+                previous,
+            })
+        })
+        .collect()
+}
+
+/// Operating cash flow less capital expenditure, (current, previous). Capex is reported
+/// as an outflow with either sign, so its magnitude is subtracted; missing capex counts as zero.
+pub fn free_cash_flow(items: &[serde_json::Value]) -> Option<(f64, f64)> {
+    let operating = find(items, OPERATING_CASH_FLOW, None)?;
+    let capex = find(items, CAPITAL_EXPENDITURE, None).unwrap_or((0.0, 0.0));
+    Some((operating.0 - capex.0.abs(), operating.1 - capex.1.abs()))
+}
+
+/// Built-in ratios plus the user's metric formulas, as computed when the document was loaded.
+pub fn for_document(document: &crate::statements::StoredDocument) -> Vec<Ratio> {
+    let mut ratios = compute(&document.items);
+    if let Some(custom) = document.metadata.get("customMetrics") {
+        ratios.extend(serde_json::from_value::<Vec<Ratio>>(custom.clone()).unwrap_or_default());
+    }
+    ratios
+}
@@ -0,0 +1,629 @@
+// Realtime - keeps a Supabase realtime channel open so notes, items corrected by hand and the
+// watchlist reach the user's other devices within seconds, while `sync_now` moves whole documents.
+//
+// Every such record is a row of `fincalc_records`, keyed by kind and a key all devices agree on
+// and stamped with the writer's clock in milliseconds. A local write lands in `realtime_records`
+// as pending and is pushed over REST; other devices' writes arrive as postgres_changes on the
+// channel. The newer stamp wins on both sides, so every device ends up with the last write.
+// Deletions are rows with `deleted` set. Notes and items are keyed by their document's Supabase
+// id, so they only travel once the document has been synced on both devices.
+use futures_util::{SinkExt, StreamExt};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::Notify;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{debug, info, warn};
+
+use crate::db;
+use crate::http;
+use crate::item_edits;
+use crate::notes::{self, Note, NoteTarget};
+use crate::settings::{self, SettingsState, SupabaseConfig};
+use crate::sync::{self, Supabase};
+use crate::watchlist;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS realtime_records (
+    kind TEXT NOT NULL,                 -- 'note', 'item', 'watchlist'
+    key TEXT NOT NULL,                  -- fincalc_records.key, the same on every device
+    local_id TEXT,                      -- notes.id for notes
+    payload TEXT,                       -- JSON as last written; NULL once deleted
+    updated_at INTEGER NOT NULL,        -- the writer's clock, unix ms
+    pending INTEGER NOT NULL DEFAULT 0, -- written here and not in Supabase yet
+    PRIMARY KEY (kind, key)
+);
+";
+
+/// Appended to the sync setup SQL; the publication line is what lets the channel see the table.
+pub const REMOTE_SCHEMA: &str = "\
+create table if not exists fincalc_records (
+  kind text not null,
+  key text not null,
+  payload jsonb,
+  deleted boolean not null default false,
+  device_id text not null,
+  updated_at bigint not null,
+  primary key (kind, key)
+);
+alter publication supabase_realtime add table fincalc_records;
+";
+
+const RECORDS_TABLE: &str = "fincalc_records";
+const TOPIC: &str = "realtime:fincalc";
+// Supabase drops channels that miss a heartbeat for 60 seconds
+const HEARTBEAT_SECS: u64 = 25;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+// While disabled, unconfigured or offline the task only re-reads the settings at this interval
+const IDLE_CHECK_SECS: u64 = 30;
+const MIN_BACKOFF_SECS: u64 = 2;
+const MAX_BACKOFF_SECS: u64 = 300;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordKind {
+    Note,
+    Item,
+    Watchlist,
+}
+
+impl RecordKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            RecordKind::Note => "note",
+            RecordKind::Item => "item",
+            RecordKind::Watchlist => "watchlist",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    /// `sync.realtime` is off or Supabase isn't configured
+    Disabled,
+    /// Offline mode is on; changes queue up until it is off
+    Offline,
+    Connecting,
+    Connected,
+    /// The last attempt failed; retrying with backoff
+    Reconnecting,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RealtimeStatus {
+    pub state: ConnectionState,
+    pub connected_since: Option<i64>,
+    /// When the last change from another device was applied
+    pub last_received_at: Option<i64>,
+    pub last_pushed_at: Option<i64>,
+    /// Changes pushed and applied since the app started
+    pub pushed: u64,
+    pub applied: u64,
+    /// Local changes not yet in Supabase
+    pub pending: usize,
+    pub last_error: Option<String>,
+}
+
+/// The connection's status, and a wake-up for the task when a local change is waiting to go out.
+pub struct Realtime {
+    status: Mutex<RealtimeStatus>,
+    changed: Notify,
+}
+
+impl Realtime {
+    pub fn new() -> Self {
+        Self {
+            status: Mutex::new(RealtimeStatus {
+                state: ConnectionState::Disabled,
+                connected_since: None,
+                last_received_at: None,
+                last_pushed_at: None,
+                pushed: 0,
+                applied: 0,
+                pending: 0,
+                last_error: None,
+            }),
+            changed: Notify::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RemoteRecord {
+    kind: String,
+    key: String,
+    payload: Option<Value>,
+    #[serde(default)]
+    deleted: bool,
+    device_id: String,
+    updated_at: i64,
+}
+
+/// A Phoenix channel message, as the realtime server frames everything it sends.
+#[derive(Debug, Deserialize)]
+struct ChannelMessage {
+    event: String,
+    #[serde(default)]
+    payload: Value,
+}
+
+struct LocalRecord {
+    local_id: Option<String>,
+    updated_at: i64,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+fn update_status(app: &AppHandle, change: impl FnOnce(&mut RealtimeStatus)) {
+    let realtime = app.state::<Realtime>();
+    let Ok(mut status) = realtime.status.lock() else { return };
+    let before = status.state;
+    change(&mut status);
+    if status.state != before {
+        let _ = app.emit("realtime-status", &*status);
+    }
+}
+
+fn configured(config: &SupabaseConfig) -> bool {
+    !config.url.trim().is_empty() && !config.key.trim().is_empty()
+}
+
+/// Whether local changes should be recorded for other devices.
+fn enabled(app: &AppHandle) -> bool {
+    let state = app.state::<SettingsState>();
+    let store = settings::blocking_read(&state);
+    store.get().sync.realtime && configured(&store.get().supabase_config)
+}
+
+fn load_local(app: &AppHandle, kind: &str, key: &str) -> Result<Option<LocalRecord>, String> {
+    let conn = db::open_app_db(app)?;
+    conn.query_row(
+        "SELECT local_id, updated_at FROM realtime_records WHERE kind = ?1 AND key = ?2",
+        params![kind, key],
+        |row| Ok(LocalRecord { local_id: row.get(0)?, updated_at: row.get(1)? }),
+    ).optional().map_err(|e| e.to_string())
+}
+
+/// Record a local write as pending. The stamp always moves forward, even if the clock went back.
+fn write_pending(app: &AppHandle, kind: RecordKind, key: &str, local_id: Option<&str>, payload: Option<&Value>) -> Result<(), String> {
+    let conn = db::open_app_db(app)?;
+    conn.execute(
+        "INSERT INTO realtime_records (kind, key, local_id, payload, updated_at, pending)
+         VALUES (?1, ?2, ?3, ?4, ?5, 1)
+         ON CONFLICT(kind, key) DO UPDATE SET
+            local_id = IFNULL(excluded.local_id, local_id),
+            payload = excluded.payload,
+            updated_at = MAX(excluded.updated_at, updated_at + 1),
+            pending = 1",
+        params![kind.as_str(), key, local_id, payload.map(Value::to_string), now_millis()],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn publish(app: &AppHandle, kind: RecordKind, key: &str, local_id: Option<&str>, payload: Option<&Value>) {
+    if !enabled(app) {
+        return;
+    }
+    if let Err(e) = write_pending(app, kind, key, local_id, payload) {
+        warn!(kind = kind.as_str(), key, "Failed to queue realtime change: {}", e);
+        return;
+    }
+    app.state::<Realtime>().changed.notify_one();
+}
+
+fn note_key(app: &AppHandle, id: i64) -> Result<Option<String>, String> {
+    let conn = db::open_app_db(app)?;
+    conn.query_row(
+        "SELECT key FROM realtime_records WHERE kind = 'note' AND local_id = ?1",
+        params![id.to_string()],
+        |row| row.get(0),
+    ).optional().map_err(|e| e.to_string())
+}
+
+/// The note's target with its document's Supabase id; None for chat notes and unsynced documents.
+fn remote_target(app: &AppHandle, target: &NoteTarget) -> Result<Option<Value>, String> {
+    let (document_id, item_id) = match target {
+        NoteTarget::Document { document_id } => (*document_id, None),
+        NoteTarget::Item { document_id, item_id } => (*document_id, Some(item_id)),
+        // Chat sessions stay on the device they were held on
+        NoteTarget::ChatMessage { .. } => return Ok(None),
+    };
+    let Some(remote_id) = sync::remote_document_id(app, document_id)? else { return Ok(None) };
+    Ok(Some(match item_id {
+        Some(item_id) => json!({ "kind": "item", "documentId": remote_id, "itemId": item_id }),
+        None => json!({ "kind": "document", "documentId": remote_id }),
+    }))
+}
+
+/// Queue a note added or changed here for other devices.
+pub fn note_saved(app: &AppHandle, note: &Note) {
+    if !enabled(app) {
+        return;
+    }
+    let result = (|| -> Result<(), String> {
+        let Some(target) = remote_target(app, &note.target)? else {
+            debug!(note = note.id, "Note not shared: its document hasn't been synced");
+            return Ok(());
+        };
+        let key = match note_key(app, note.id)? {
+            Some(key) => key,
+            None => sync::new_uuid()?,
+        };
+        let payload = json!({ "target": target, "body": note.body, "author": note.author, "createdAt": note.created_at });
+        publish(app, RecordKind::Note, &key, Some(&note.id.to_string()), Some(&payload));
+        Ok(())
+    })();
+    if let Err(e) = result {
+        warn!(note = note.id, "Failed to share note: {}", e);
+    }
+}
+
+pub fn note_deleted(app: &AppHandle, id: i64) {
+    if !enabled(app) {
+        return;
+    }
+    match note_key(app, id) {
+        Ok(Some(key)) => publish(app, RecordKind::Note, &key, None, None),
+        Ok(None) => {}
+        Err(e) => warn!(note = id, "Failed to share note deletion: {}", e),
+    }
+}
+
+/// Queue an item corrected or added here; `item` is the whole item as now stored.
+pub fn item_saved(app: &AppHandle, document_id: i64, item: &Value) {
+    if !enabled(app) {
+        return;
+    }
+    let Some(item_id) = item["id"].as_str() else { return };
+    match sync::remote_document_id(app, document_id) {
+        Ok(Some(remote_id)) => publish(app, RecordKind::Item, &format!("{}/{}", remote_id, item_id), None, Some(item)),
+        Ok(None) => debug!(document_id, "Item edit not shared: its document hasn't been synced"),
+        Err(e) => warn!(document_id, "Failed to share item edit: {}", e),
+    }
+}
+
+/// Queue a watchlist entry added (`added_at` set) or removed here.
+pub fn watchlist_changed(app: &AppHandle, symbol: &str, exchange: &str, added_at: Option<i64>) {
+    let payload = added_at.map(|added_at| json!({ "symbol": symbol, "exchange": exchange, "addedAt": added_at }));
+    publish(app, RecordKind::Watchlist, &format!("{}:{}", symbol, exchange), None, payload.as_ref());
+}
+
+/// Write another device's note here. Returns the local note id, or None when it can't be placed.
+fn apply_note(app: &AppHandle, local_id: Option<&str>, payload: Option<&Value>) -> Result<Option<String>, String> {
+    let local_id = local_id.and_then(|id| id.parse::<i64>().ok());
+    let Some(payload) = payload else {
+        if let Some(id) = local_id {
+            notes::remove_synced(app, id)?;
+        }
+        return Ok(None);
+    };
+    let target = &payload["target"];
+    let Some(document_id) = target["documentId"].as_str().map(|id| sync::local_document_id(app, id)).transpose()?.flatten() else {
+        return Ok(None);
+    };
+    let target = match (target["kind"].as_str(), target["itemId"].as_str()) {
+        (Some("item"), Some(item_id)) => NoteTarget::Item { document_id, item_id: item_id.to_string() },
+        (Some("document"), _) => NoteTarget::Document { document_id },
+        _ => return Ok(None),
+    };
+    let body = payload["body"].as_str().unwrap_or_default().to_string();
+    let author = payload["author"].as_str().map(str::to_string);
+    let created_at = payload["createdAt"].as_i64().unwrap_or_else(now_secs);
+    let note = notes::store_synced(app, local_id, target, body, author, created_at)?;
+    Ok(Some(note.id.to_string()))
+}
+
+/// Write another device's item here; items deleted by hand don't exist, so there is no deletion.
+fn apply_item(app: &AppHandle, key: &str, payload: Option<&Value>) -> Result<bool, String> {
+    let (Some((remote_id, _)), Some(item)) = (key.split_once('/'), payload) else { return Ok(false) };
+    let Some(document_id) = sync::local_document_id(app, remote_id)? else { return Ok(false) };
+    item_edits::store_synced(app, document_id, item)?;
+    Ok(true)
+}
+
+fn apply_watchlist(app: &AppHandle, key: &str, payload: Option<&Value>) -> Result<bool, String> {
+    let Some((symbol, exchange)) = key.split_once(':') else { return Ok(false) };
+    let added_at = payload.map(|payload| payload["addedAt"].as_i64().unwrap_or_else(now_secs));
+    watchlist::store_synced(app, symbol, exchange, added_at)?;
+    Ok(true)
+}
+
+/// Apply a record from another device if it is newer than ours. Returns whether anything changed.
+fn apply(app: &AppHandle, device_id: &str, record: &RemoteRecord) -> Result<bool, String> {
+    if record.device_id == device_id {
+        return Ok(false);
+    }
+    let local = load_local(app, &record.kind, &record.key)?;
+    if let Some(local) = &local {
+        if local.updated_at > record.updated_at {
+            // A write that lost here may have overwritten ours in Supabase; send ours up again
+            let conn = db::open_app_db(app)?;
+            conn.execute(
+                "UPDATE realtime_records SET pending = 1 WHERE kind = ?1 AND key = ?2",
+                params![record.kind, record.key],
+            ).map_err(|e| e.to_string())?;
+            app.state::<Realtime>().changed.notify_one();
+            return Ok(false);
+        }
+        if local.updated_at == record.updated_at {
+            return Ok(false);
+        }
+    }
+
+    let payload = if record.deleted { None } else { record.payload.as_ref().filter(|p| !p.is_null()) };
+    let local_id = local.and_then(|local| local.local_id);
+    let (applied, local_id) = match record.kind.as_str() {
+        "note" => {
+            let id = apply_note(app, local_id.as_deref(), payload)?;
+            (id.is_some() || payload.is_none(), id)
+        }
+        "item" => (apply_item(app, &record.key, payload)?, None),
+        "watchlist" => (apply_watchlist(app, &record.key, payload)?, None),
+        // Written by a newer version
+        _ => return Ok(false),
+    };
+    if !applied {
+        // Its document isn't here yet; a later catch-up tries again
+        return Ok(false);
+    }
+    let conn = db::open_app_db(app)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO realtime_records (kind, key, local_id, payload, updated_at, pending)
+         VALUES (?1, ?2, ?3, ?4, ?5, 0)",
+        params![record.kind, record.key, local_id, payload.map(Value::to_string), record.updated_at],
+    ).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+fn apply_logged(app: &AppHandle, device_id: &str, record: &RemoteRecord) {
+    match apply(app, device_id, record) {
+        Ok(true) => {
+            debug!(kind = %record.kind, key = %record.key, "Applied realtime change");
+            update_status(app, |status| {
+                status.applied += 1;
+                status.last_received_at = Some(now_secs());
+            });
+        }
+        Ok(false) => {}
+        Err(e) => warn!(kind = %record.kind, key = %record.key, "Failed to apply realtime change: {}", e),
+    }
+}
+
+fn pending_count(app: &AppHandle) -> Result<usize, String> {
+    let conn = db::open_app_db(app)?;
+    conn.query_row("SELECT COUNT(*) FROM realtime_records WHERE pending = 1", [], |row| row.get::<_, i64>(0))
+        .map(|count| count as usize)
+        .map_err(|e| e.to_string())
+}
+
+/// Push every pending local write. Rows written again meanwhile stay pending for the next flush.
+async fn flush(app: &AppHandle, remote: &Supabase, device_id: &str) -> Result<(), String> {
+    let pending: Vec<(String, String, Option<String>, i64)> = {
+        let conn = db::open_app_db(app)?;
+        let mut stmt = conn
+            .prepare("SELECT kind, key, payload, updated_at FROM realtime_records WHERE pending = 1")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?
+    };
+    if pending.is_empty() {
+        return Ok(());
+    }
+    let rows: Vec<Value> = pending.iter()
+        .map(|(kind, key, payload, updated_at)| {
+            let payload = payload.as_deref().and_then(|p| serde_json::from_str::<Value>(p).ok());
+            json!({
+                "kind": kind,
+                "key": key,
+                "deleted": payload.is_none(),
+                "payload": payload,
+                "device_id": device_id,
+                "updated_at": updated_at,
+            })
+        })
+        .collect();
+    remote.upsert(RECORDS_TABLE, "kind,key", &rows).await?;
+
+    let conn = db::open_app_db(app)?;
+    for (kind, key, _, updated_at) in &pending {
+        conn.execute(
+            "UPDATE realtime_records SET pending = 0 WHERE kind = ?1 AND key = ?2 AND updated_at = ?3",
+            params![kind, key, updated_at],
+        ).map_err(|e| e.to_string())?;
+    }
+    update_status(app, |status| {
+        status.pushed += pending.len() as u64;
+        status.last_pushed_at = Some(now_secs());
+    });
+    Ok(())
+}
+
+/// Everything in Supabase, for changes made while this device wasn't listening. Stamps come from
+/// each writer's clock, so filtering on them could skip a write from a device whose clock is behind.
+async fn catch_up(app: &AppHandle, remote: &Supabase, device_id: &str) -> Result<(), String> {
+    let records: Vec<RemoteRecord> = remote.select(RECORDS_TABLE, "select=*&order=kind,key").await?;
+    for record in &records {
+        apply_logged(app, device_id, record);
+    }
+    Ok(())
+}
+
+async fn current_config(app: &AppHandle) -> Option<SupabaseConfig> {
+    let state = app.state::<SettingsState>();
+    let store = state.read().await;
+    let config = &store.get().supabase_config;
+    (store.get().sync.realtime && configured(config)).then(|| config.clone())
+}
+
+fn socket_url(config: &SupabaseConfig) -> Result<String, String> {
+    let base = config.url.trim().trim_end_matches('/');
+    let base = if let Some(host) = base.strip_prefix("https://") {
+        format!("wss://{}", host)
+    } else if let Some(host) = base.strip_prefix("http://") {
+        format!("ws://{}", host)
+    } else {
+        return Err(format!("Supabase URL must start with https://: {}", base));
+    };
+    Ok(format!("{}/realtime/v1/websocket?apikey={}&vsn=1.0.0", base, config.key.trim()))
+}
+
+/// One connection: join the channel, catch up, then push and apply changes until it drops or
+/// realtime is turned off (Ok).
+async fn session(app: &AppHandle, config: &SupabaseConfig) -> Result<(), String> {
+    let remote = Supabase::new(http::client(app), config)?;
+    let device_id = sync::device_id(app)?;
+    // reqwest brings in both rustls backends, so neither is the default until one is installed
+    let _ = rustls::crypto::aws_lc_rs::default_provider().install_default();
+    let (socket, _) = tokio::time::timeout(CONNECT_TIMEOUT, tokio_tungstenite::connect_async(socket_url(config)?))
+        .await
+        .map_err(|_| "Timed out connecting to Supabase realtime".to_string())?
+        .map_err(|e| format!("Supabase realtime connection failed: {}", e))?;
+    let (mut sink, mut stream) = socket.split();
+
+    let join = json!({
+        "topic": TOPIC,
+        "event": "phx_join",
+        "payload": {
+            "config": {
+                "broadcast": { "self": false },
+                "presence": { "key": "" },
+                "postgres_changes": [{ "event": "*", "schema": "public", "table": RECORDS_TABLE }],
+            },
+            "access_token": config.key.trim(),
+        },
+        "ref": "1",
+        "join_ref": "1",
+    });
+    sink.send(Message::Text(join.to_string().into())).await.map_err(|e| e.to_string())?;
+
+    let realtime = app.state::<Realtime>();
+    let mut heartbeat = tokio::time::interval(Duration::from_secs(HEARTBEAT_SECS));
+    heartbeat.tick().await;
+    let mut reference: u64 = 1;
+    let mut joined = false;
+    loop {
+        tokio::select! {
+            message = stream.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => return Err("Supabase realtime closed the connection".to_string()),
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(format!("Supabase realtime connection lost: {}", e)),
+                };
+                let Ok(message) = serde_json::from_str::<ChannelMessage>(text.as_str()) else { continue };
+                match message.event.as_str() {
+                    "phx_reply" if !joined => {
+                        if message.payload["status"] != "ok" {
+                            return Err(format!("Supabase realtime refused the channel: {}", message.payload["response"]));
+                        }
+                        joined = true;
+                        info!("Supabase realtime connected");
+                        update_status(app, |status| {
+                            status.state = ConnectionState::Connected;
+                            status.connected_since = Some(now_secs());
+                            status.last_error = None;
+                        });
+                        flush(app, &remote, &device_id).await?;
+                        catch_up(app, &remote, &device_id).await?;
+                    }
+                    "postgres_changes" => {
+                        let record = &message.payload["data"]["record"];
+                        match serde_json::from_value::<RemoteRecord>(record.clone()) {
+                            Ok(record) => apply_logged(app, &device_id, &record),
+                            Err(e) => debug!("Ignoring realtime change: {}", e),
+                        }
+                    }
+                    "phx_error" | "phx_close" => return Err("Supabase realtime closed the channel".to_string()),
+                    "system" if message.payload["status"] == "error" => {
+                        return Err(format!("Supabase realtime: {}", message.payload["message"]));
+                    }
+                    _ => {}
+                }
+            }
+            _ = heartbeat.tick() => {
+                if current_config(app).await.as_ref() != Some(config) {
+                    let _ = sink.send(Message::Close(None)).await;
+                    return Ok(());
+                }
+                reference += 1;
+                let beat = json!({ "topic": "phoenix", "event": "heartbeat", "payload": {}, "ref": reference.to_string() });
+                sink.send(Message::Text(beat.to_string().into())).await.map_err(|e| e.to_string())?;
+            }
+            _ = realtime.changed.notified(), if joined => {
+                flush(app, &remote, &device_id).await?;
+            }
+        }
+    }
+}
+
+/// Background loop: holds the channel open while `sync.realtime` is on, reconnecting with backoff.
+pub fn start_task(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut backoff = MIN_BACKOFF_SECS;
+        loop {
+            let Some(config) = current_config(&app).await else {
+                update_status(&app, |status| {
+                    status.state = ConnectionState::Disabled;
+                    status.connected_since = None;
+                });
+                tokio::time::sleep(Duration::from_secs(IDLE_CHECK_SECS)).await;
+                continue;
+            };
+            if settings::is_offline(&app) {
+                update_status(&app, |status| {
+                    status.state = ConnectionState::Offline;
+                    status.connected_since = None;
+                });
+                tokio::time::sleep(Duration::from_secs(IDLE_CHECK_SECS)).await;
+                continue;
+            }
+
+            update_status(&app, |status| status.state = ConnectionState::Connecting);
+            match session(&app, &config).await {
+                Ok(()) => backoff = MIN_BACKOFF_SECS,
+                Err(e) => {
+                    warn!("Supabase realtime: {}", e);
+                    update_status(&app, |status| {
+                        // A connection that got as far as joining resets the backoff
+                        if status.state == ConnectionState::Connected {
+                            backoff = MIN_BACKOFF_SECS;
+                        }
+                        status.state = ConnectionState::Reconnecting;
+                        status.connected_since = None;
+                        status.last_error = Some(e);
+                    });
+                    tokio::time::sleep(Duration::from_secs(backoff)).await;
+                    backoff = (backoff * 2).min(MAX_BACKOFF_SECS);
+                }
+            }
+        }
+    });
+}
+
+// Tauri Commands
+/// Whether the realtime channel is up, what has moved over it and what is still queued here.
+#[tauri::command]
+pub fn get_realtime_status(app: AppHandle, realtime: tauri::State<'_, Realtime>) -> Result<RealtimeStatus, String> {
+    let mut status = realtime.status.lock().map_err(|e| e.to_string())?.clone();
+    status.pending = pending_count(&app)?;
+    Ok(status)
+}
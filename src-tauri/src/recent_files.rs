@@ -0,0 +1,194 @@
+// Recent Files - files the user has analyzed, with their outcome, for one-click reopening from
+// the welcome screen; pinned files stay at the top and survive clearing
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+use tracing::warn;
+
+use crate::db;
+use crate::parse_cache;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS recent_files (
+    path TEXT PRIMARY KEY,
+    file_name TEXT NOT NULL,
+    file_hash TEXT,                -- blake3 of the file when last analyzed
+    status TEXT NOT NULL,          -- success, failed or cancelled
+    error TEXT,
+    document_id INTEGER,           -- stored document from the last successful analysis
+    pinned INTEGER NOT NULL DEFAULT 0,
+    first_analyzed_at INTEGER NOT NULL,
+    last_analyzed_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_recent_files_last ON recent_files(last_analyzed_at);
+";
+
+// Unpinned files kept; older ones drop off as new files are analyzed
+const MAX_RECENT: i64 = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecentStatus {
+    Success,
+    Failed,
+    Cancelled,
+}
+
+impl RecentStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            RecentStatus::Success => "success",
+            RecentStatus::Failed => "failed",
+            RecentStatus::Cancelled => "cancelled",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "success" => RecentStatus::Success,
+            "cancelled" => RecentStatus::Cancelled,
+            _ => RecentStatus::Failed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentFile {
+    pub path: String,
+    pub file_name: String,
+    pub file_hash: Option<String>,
+    pub status: RecentStatus,
+    pub error: Option<String>,
+    pub document_id: Option<i64>,
+    pub pinned: bool,
+    pub first_analyzed_at: i64,
+    pub last_analyzed_at: i64,
+    /// Whether the file is still on disk; reopening a missing one would fail
+    pub exists: bool,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Note an analysis of the file at `path`. Hashes the file, so call it off the async runtime;
+/// failures are logged, never surfaced to the analysis.
+pub fn record(app: &AppHandle, path: &str, file_name: Option<&str>, status: RecentStatus, error: Option<&str>, document_id: Option<i64>) {
+    let file_name = file_name
+        .map(str::to_string)
+        .or_else(|| Path::new(path).file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| path.to_string());
+    let hash = parse_cache::file_hash(path, None).ok();
+    let now = now_secs();
+    let recorded = db::open_app_db(app).and_then(|conn| {
+        conn.execute(
+            "INSERT INTO recent_files (path, file_name, file_hash, status, error, document_id, first_analyzed_at, last_analyzed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)
+             ON CONFLICT(path) DO UPDATE SET
+                file_name = excluded.file_name,
+                file_hash = COALESCE(excluded.file_hash, recent_files.file_hash),
+                status = excluded.status,
+                error = excluded.error,
+                document_id = COALESCE(excluded.document_id, recent_files.document_id),
+                last_analyzed_at = excluded.last_analyzed_at",
+            params![path, file_name, hash, status.as_str(), error, document_id, now],
+        ).map_err(|e| e.to_string())?;
+        conn.execute(
+            "DELETE FROM recent_files WHERE pinned = 0 AND path NOT IN
+                (SELECT path FROM recent_files WHERE pinned = 0 ORDER BY last_analyzed_at DESC LIMIT ?1)",
+            params![MAX_RECENT],
+        ).map_err(|e| e.to_string())
+    });
+    if let Err(e) = recorded {
+        warn!("Failed to record recent file: {}", e);
+    }
+}
+
+/// The file a stored document was analyzed from, when it was analyzed on this machine.
+pub fn path_for_document(app: &AppHandle, document_id: i64) -> Option<String> {
+    let conn = db::open_app_db(app).ok()?;
+    conn.query_row(
+        "SELECT path FROM recent_files WHERE document_id = ?1 ORDER BY last_analyzed_at DESC LIMIT 1",
+        params![document_id],
+        |row| row.get(0),
+    ).ok()
+}
+
+// Tauri Commands
+/// Pinned files first, then the most recently analyzed.
+#[tauri::command]
+pub fn get_recent_files(app: AppHandle, limit: Option<usize>) -> Result<Vec<RecentFile>, String> {
+    let conn = db::open_app_db(&app)?;
+    let mut stmt = conn.prepare(
+        "SELECT path, file_name, file_hash, status, error, document_id, pinned, first_analyzed_at, last_analyzed_at
+         FROM recent_files ORDER BY pinned DESC, last_analyzed_at DESC LIMIT ?1",
+    ).map_err(|e| e.to_string())?;
+    let limit = limit.map_or(-1, |l| l as i64);
+    let files = stmt.query_map(params![limit], |row| {
+        let path: String = row.get(0)?;
+        Ok(RecentFile {
+            exists: Path::new(&path).is_file(),
+            path,
+            file_name: row.get(1)?,
+            file_hash: row.get(2)?,
+            status: RecentStatus::parse(&row.get::<_, String>(3)?),
+            error: row.get(4)?,
+            document_id: row.get(5)?,
+            pinned: row.get(6)?,
+            first_analyzed_at: row.get(7)?,
+            last_analyzed_at: row.get(8)?,
+        })
+    }).map_err(|e| e.to_string())?;
+    files.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Pin or unpin a file. A file not yet in the list is added, so it can be pinned before its
+/// first analysis.
+#[tauri::command]
+pub fn pin_file(app: AppHandle, path: String, pinned: bool) -> Result<(), String> {
+    if pinned && !Path::new(&path).is_file() {
+        return Err(format!("File not found: {}", path));
+    }
+    let conn = db::open_app_db(&app)?;
+    let updated = conn.execute("UPDATE recent_files SET pinned = ?2 WHERE path = ?1", params![path, pinned])
+        .map_err(|e| e.to_string())?;
+    if updated == 0 && pinned {
+        let file_name = Path::new(&path).file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_else(|| path.clone());
+        let now = now_secs();
+        conn.execute(
+            "INSERT INTO recent_files (path, file_name, status, pinned, first_analyzed_at, last_analyzed_at)
+             VALUES (?1, ?2, 'success', 1, ?3, ?3)",
+            params![path, file_name, now],
+        ).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Forget recent files: unpinned ones, only those no longer on disk with `missing_only`, and
+/// pinned ones too with `include_pinned`. Returns how many were removed.
+#[tauri::command]
+pub fn clear_recent(app: AppHandle, include_pinned: Option<bool>, missing_only: Option<bool>) -> Result<usize, String> {
+    let conn = db::open_app_db(&app)?;
+    let include_pinned = include_pinned.unwrap_or(false);
+    if !missing_only.unwrap_or(false) {
+        return conn.execute("DELETE FROM recent_files WHERE pinned = 0 OR ?1", params![include_pinned])
+            .map_err(|e| e.to_string());
+    }
+
+    let mut stmt = conn.prepare("SELECT path FROM recent_files WHERE pinned = 0 OR ?1").map_err(|e| e.to_string())?;
+    let paths = stmt.query_map(params![include_pinned], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    let mut removed = 0;
+    for path in paths.iter().filter(|p| !Path::new(p).is_file()) {
+        removed += conn.execute("DELETE FROM recent_files WHERE path = ?1", params![path]).map_err(|e| e.to_string())?;
+    }
+    Ok(removed)
+}
@@ -0,0 +1,539 @@
+// Report - client-ready PDF of a document's statements, key ratios and charts
+use printpdf::{
+    BuiltinFont, Color, LinePoint, Mm, Op, PaintMode, PdfDocument, PdfFontHandle, PdfPage, PdfSaveOptions,
+    Point, Polygon, PolygonRing, Pt, RawImage, Rgb, TextItem, WindingOrder, XObjectTransform,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+use crate::activity::{self, ActivityKind};
+use crate::formatting::Formatter;
+use crate::item_edits;
+use crate::notes::{self, Note};
+use crate::periods;
+use crate::profiles;
+use crate::ratios::{self, Ratio};
+use crate::report_templates::{self, ReportSection, TemplateRef};
+use crate::statements;
+use crate::usage::{self, UsageKind};
+use crate::watchlist::{self, WatchlistEntry};
+
+// A4 portrait, in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 48.0;
+const ROW_HEIGHT: f32 = 15.0;
+
+const INK: (f32, f32, f32) = (0.13, 0.15, 0.19);
+const MUTED: (f32, f32, f32) = (0.45, 0.48, 0.53);
+const ACCENT: (f32, f32, f32) = (0.16, 0.34, 0.62);
+const ACCENT_LIGHT: (f32, f32, f32) = (0.62, 0.73, 0.88);
+const BAND: (f32, f32, f32) = (0.94, 0.95, 0.97);
+
+// Figures charted on the summary page, in the order they're drawn
+const CHART_FIGURES: &[(&str, &[&str])] = &[
+    ("Revenue", &["revenue from operations", "total revenue from operations", "revenue", "total income"]),
+    ("Net Profit", &["profit for the year", "profit for the period", "net profit", "profit after tax"]),
+    ("Total Assets", &["total assets"]),
+    ("Total Equity", &["total equity"]),
+    ("Operating Cash Flow", &["net cash from operating activities", "net cash generated from operating activities"]),
+];
+
+/// Statement sections in report order, with their headings
+pub const STATEMENTS: &[(&str, &str)] = &[
+    ("income_statement", "Statement of Profit and Loss"),
+    ("balance_sheet", "Balance Sheet"),
+    ("cash_flow", "Cash Flow Statement"),
+    ("notes", "Notes"),
+];
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn rgb((r, g, b): (f32, f32, f32)) -> Color {
+    Color::Rgb(Rgb { r, g, b, icc_profile: None })
+}
+
+/// The built-in fonts only cover Latin-1.
+fn printable(text: &str) -> String {
+    text.replace('₹', "Rs.")
+        .chars()
+        .map(|c| match c {
+            '\u{2018}' | '\u{2019}' => '\'',
+            '\u{201C}' | '\u{201D}' => '"',
+            '\u{2013}' | '\u{2014}' => '-',
+            c if (c as u32) < 0x100 && !c.is_control() => c,
+            _ => '?',
+        })
+        .collect()
+}
+
+/// Approximate Helvetica advance widths, enough for right-aligning figures and clipping labels.
+fn text_width(text: &str, size: f32) -> f32 {
+    let em: f32 = text.chars()
+        .map(|c| match c {
+            '0'..='9' => 0.556,
+            ',' | '.' | ' ' | ':' | 'i' | 'l' | 'j' | 'I' => 0.278,
+            '(' | ')' | '-' | 'f' | 't' | 'r' => 0.333,
+            'm' | 'w' | 'M' | 'W' => 0.85,
+            c if c.is_uppercase() => 0.68,
+            _ => 0.53,
+        })
+        .sum();
+    em * size
+}
+
+/// `text` broken into lines no wider than `width`, at spaces where it can be.
+fn wrap(text: &str, size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+            if text_width(&candidate, size) <= width || line.is_empty() {
+                line = candidate;
+            } else {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            }
+        }
+        lines.push(line);
+    }
+    lines.into_iter().map(|line| clip(&line, size, width)).collect()
+}
+
+fn clip(text: &str, size: f32, width: f32) -> String {
+    let text = printable(text);
+    if text_width(&text, size) <= width {
+        return text;
+    }
+    let mut clipped = String::new();
+    for c in text.chars() {
+        if text_width(&clipped, size) + text_width(&format!("{}...", c), size) > width {
+            break;
+        }
+        clipped.push(c);
+    }
+    format!("{}...", clipped.trim_end())
+}
+
+pub fn statement_of(item: &serde_json::Value) -> String {
+    let statement = item["statementType"].as_str().unwrap_or("unknown").to_lowercase();
+    if statement == "cashflow" { "cash_flow".to_string() } else { statement }
+}
+
+/// Lays ops out top to bottom, starting a new page whenever the next block won't fit.
+struct Canvas {
+    doc: PdfDocument,
+    title: String,
+    /// Shown at the right of every footer
+    branding: Option<String>,
+    pages: Vec<PdfPage>,
+    ops: Vec<Op>,
+    y: f32,
+    fmt: Formatter,
+}
+
+impl Canvas {
+    fn new(title: &str, fmt: Formatter) -> Self {
+        Self {
+            doc: PdfDocument::new(title),
+            title: printable(title),
+            branding: None,
+            pages: Vec::new(),
+            ops: Vec::new(),
+            y: PAGE_HEIGHT - MARGIN,
+            fmt,
+        }
+    }
+
+    fn finish_page(&mut self) {
+        let footer = format!("{}  |  Page {}", self.title, self.pages.len() + 1);
+        self.text(MARGIN, MARGIN / 2.0, 8.0, false, MUTED, &footer);
+        if let Some(branding) = self.branding.clone() {
+            let branding = clip(&branding, 8.0, PAGE_WIDTH / 2.0 - MARGIN);
+            self.text_right(PAGE_WIDTH - MARGIN, MARGIN / 2.0, 8.0, false, MUTED, &branding);
+        }
+        let ops = std::mem::take(&mut self.ops);
+        self.pages.push(PdfPage::new(Mm::from(Pt(PAGE_WIDTH)), Mm::from(Pt(PAGE_HEIGHT)), ops));
+        self.y = PAGE_HEIGHT - MARGIN;
+    }
+
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            self.finish_page();
+        }
+    }
+
+    fn text(&mut self, x: f32, y: f32, size: f32, bold: bool, color: (f32, f32, f32), text: &str) {
+        let font = if bold { BuiltinFont::HelveticaBold } else { BuiltinFont::Helvetica };
+        self.ops.extend([
+            Op::StartTextSection,
+            Op::SetTextCursor { pos: Point { x: Pt(x), y: Pt(y) } },
+            Op::SetFont { font: PdfFontHandle::Builtin(font), size: Pt(size) },
+            Op::SetFillColor { col: rgb(color) },
+            Op::ShowText { items: vec![TextItem::Text(printable(text))] },
+            Op::EndTextSection,
+        ]);
+    }
+
+    fn text_right(&mut self, right: f32, y: f32, size: f32, bold: bool, color: (f32, f32, f32), text: &str) {
+        let x = right - text_width(&printable(text), size);
+        self.text(x, y, size, bold, color, text);
+    }
+
+    fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: (f32, f32, f32)) {
+        let corner = |x: f32, y: f32| LinePoint { p: Point { x: Pt(x), y: Pt(y) }, bezier: false };
+        self.ops.extend([
+            Op::SetFillColor { col: rgb(color) },
+            Op::DrawPolygon {
+                polygon: Polygon {
+                    rings: vec![PolygonRing {
+                        points: vec![
+                            corner(x, y),
+                            corner(x + width, y),
+                            corner(x + width, y + height),
+                            corner(x, y + height),
+                        ],
+                    }],
+                    mode: PaintMode::Fill,
+                    winding_order: WindingOrder::NonZero,
+                },
+            },
+        ]);
+    }
+
+    fn heading(&mut self, text: &str) {
+        self.ensure_space(60.0);
+        self.y -= 18.0;
+        self.text(MARGIN, self.y, 15.0, true, ACCENT, text);
+        self.y -= 6.0;
+        self.rect(MARGIN, self.y, PAGE_WIDTH - 2.0 * MARGIN, 1.2, ACCENT);
+        self.y -= 16.0;
+    }
+
+    /// Place a PNG/JPEG so its top-right corner sits at (`right`, `top`), `height` points tall.
+    fn image(&mut self, path: &str, right: f32, top: f32, height: f32) -> Result<(), String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+        let image = RawImage::decode_from_bytes(&bytes, &mut Vec::new())?;
+        if image.height == 0 {
+            return Err(format!("Empty image: {}", path));
+        }
+        // Images are sized by their DPI; pick the one that yields the requested height
+        let dpi = image.height as f32 * 72.0 / height;
+        let width = image.width as f32 * 72.0 / dpi;
+        let id = self.doc.add_image(&image);
+        self.ops.push(Op::UseXobject {
+            id,
+            transform: XObjectTransform {
+                translate_x: Some(Pt(right - width)),
+                translate_y: Some(Pt(top - height)),
+                dpi: Some(dpi),
+                ..Default::default()
+            },
+        });
+        Ok(())
+    }
+
+    fn into_pdf(mut self) -> Vec<u8> {
+        self.finish_page();
+        let pages = std::mem::take(&mut self.pages);
+        self.doc.with_pages(pages).save(&PdfSaveOptions::default(), &mut Vec::new())
+    }
+}
+
+fn draw_cover(canvas: &mut Canvas, title: &str, subtitle: &str, details: &str, logo: Option<&str>) {
+    canvas.rect(0.0, PAGE_HEIGHT - 150.0, PAGE_WIDTH, 150.0, ACCENT);
+    if let Some(logo) = logo.filter(|l| !l.is_empty()) {
+        // A broken logo shouldn't cost the client their report
+        if let Err(e) = canvas.image(logo, PAGE_WIDTH - MARGIN, PAGE_HEIGHT - 40.0, 56.0) {
+            eprintln!("[Report] Skipping logo: {}", e);
+        }
+    }
+    canvas.text(MARGIN, PAGE_HEIGHT - 80.0, 24.0, true, (1.0, 1.0, 1.0), title);
+    let subtitle = clip(subtitle, 13.0, PAGE_WIDTH - 2.0 * MARGIN);
+    canvas.text(MARGIN, PAGE_HEIGHT - 108.0, 13.0, false, (1.0, 1.0, 1.0), &subtitle);
+    let generated = canvas.fmt.datetime(&chrono::Local::now());
+    canvas.text(MARGIN, PAGE_HEIGHT - 180.0, 10.0, false, MUTED, &format!("Generated {}  |  {}", generated, details));
+    canvas.y = PAGE_HEIGHT - 200.0;
+}
+
+fn draw_ratios(canvas: &mut Canvas, ratios: &[Ratio]) {
+    canvas.heading("Key Ratios");
+    if ratios.is_empty() {
+        canvas.text(MARGIN, canvas.y, 10.0, false, MUTED, "Not enough line items were recognised to compute ratios.");
+        canvas.y -= ROW_HEIGHT * 2.0;
+        return;
+    }
+    let right = PAGE_WIDTH - MARGIN;
+    canvas.text(MARGIN, canvas.y, 9.0, true, MUTED, "Ratio");
+    canvas.text(MARGIN + 230.0, canvas.y, 9.0, true, MUTED, "Category");
+    canvas.text_right(right - 90.0, canvas.y, 9.0, true, MUTED, "Current");
+    canvas.text_right(right, canvas.y, 9.0, true, MUTED, "Previous");
+    canvas.y -= ROW_HEIGHT;
+
+    for (i, ratio) in ratios.iter().enumerate() {
+        canvas.ensure_space(ROW_HEIGHT);
+        if i % 2 == 0 {
+            canvas.rect(MARGIN - 4.0, canvas.y - 4.0, right - MARGIN + 8.0, ROW_HEIGHT, BAND);
+        }
+        let category = ratio.category.replace('_', " ");
+        canvas.text(MARGIN, canvas.y, 10.0, false, INK, &ratio.name);
+        canvas.text(MARGIN + 230.0, canvas.y, 10.0, false, MUTED, &category);
+        canvas.text_right(right - 90.0, canvas.y, 10.0, true, INK, &canvas.fmt.ratio(ratio.current, &ratio.unit));
+        canvas.text_right(right, canvas.y, 10.0, false, INK, &canvas.fmt.ratio(ratio.previous, &ratio.unit));
+        canvas.y -= ROW_HEIGHT;
+    }
+}
+
+pub fn chart_figures(items: &[serde_json::Value]) -> Vec<(&'static str, f64, f64)> {
+    CHART_FIGURES.iter()
+        .filter_map(|(name, labels)| {
+            let item = labels.iter().find_map(|label| {
+                items.iter().find(|item| {
+                    item["label"].as_str().map(|l| l.trim().to_lowercase()) == Some(label.to_string())
+                })
+            })?;
+            let (current, previous) = periods::amounts(item);
+            Some((*name, current.unwrap_or(0.0), previous.unwrap_or(0.0)))
+        })
+        .collect()
+}
+
+/// Grouped bars, previous year beside current year, scaled to the largest absolute figure.
+fn draw_chart(canvas: &mut Canvas, figures: &[(&str, f64, f64)]) {
+    if figures.is_empty() {
+        return;
+    }
+    let chart_height = 160.0;
+    canvas.heading("Key Figures");
+    canvas.ensure_space(chart_height + 50.0);
+
+    let max = figures.iter()
+        .flat_map(|(_, current, previous)| [current.abs(), previous.abs()])
+        .fold(0.0_f64, f64::max);
+    let max = if max == 0.0 { 1.0 } else { max };
+    let base = canvas.y - chart_height;
+    let slot = (PAGE_WIDTH - 2.0 * MARGIN) / figures.len() as f32;
+    let bar = (slot * 0.3).min(36.0);
+
+    canvas.rect(MARGIN, base, PAGE_WIDTH - 2.0 * MARGIN, 0.8, MUTED);
+    for (i, (name, current, previous)) in figures.iter().enumerate() {
+        let center = MARGIN + slot * (i as f32 + 0.5);
+        for (offset, value, color) in [(-bar - 2.0, *previous, ACCENT_LIGHT), (2.0, *current, ACCENT)] {
+            // Losses are drawn at their magnitude; the label below keeps the sign
+            let height = ((value.abs() / max) as f32 * (chart_height - 20.0)).max(0.5);
+            canvas.rect(center + offset, base, bar, height, color);
+        }
+        let current_label = printable(&canvas.fmt.figure(*current));
+        canvas.text(center - text_width(name, 9.0) / 2.0, base - 14.0, 9.0, true, INK, name);
+        canvas.text(center - text_width(&current_label, 8.0) / 2.0, base - 26.0, 8.0, false, MUTED, &current_label);
+    }
+
+    let legend_y = base - 44.0;
+    canvas.rect(MARGIN, legend_y, 8.0, 8.0, ACCENT_LIGHT);
+    canvas.text(MARGIN + 12.0, legend_y + 1.0, 8.0, false, MUTED, "Previous year");
+    canvas.rect(MARGIN + 90.0, legend_y, 8.0, 8.0, ACCENT);
+    canvas.text(MARGIN + 102.0, legend_y + 1.0, 8.0, false, MUTED, "Current year");
+    canvas.y = legend_y - 20.0;
+}
+
+fn draw_statement(canvas: &mut Canvas, title: &str, items: &[&serde_json::Value]) {
+    canvas.heading(title);
+    let right = PAGE_WIDTH - MARGIN;
+    let label_width = right - MARGIN - 200.0;
+
+    let table_header = |canvas: &mut Canvas| {
+        canvas.text(MARGIN, canvas.y, 9.0, true, MUTED, "Particulars");
+        let (current, previous) = (canvas.fmt.amount_heading("Current Year"), canvas.fmt.amount_heading("Previous Year"));
+        canvas.text_right(right - 100.0, canvas.y, 9.0, true, MUTED, &current);
+        canvas.text_right(right, canvas.y, 9.0, true, MUTED, &previous);
+        canvas.y -= ROW_HEIGHT;
+    };
+    table_header(canvas);
+
+    for item in items {
+        if canvas.y - ROW_HEIGHT < MARGIN {
+            canvas.finish_page();
+            table_header(canvas);
+        }
+        let label = item_edits::display_label(item);
+        let is_header = item["isHeader"].as_bool().unwrap_or(false);
+        let is_total = item["isTotal"].as_bool().unwrap_or(false);
+        if is_total {
+            canvas.rect(MARGIN - 4.0, canvas.y - 4.0, right - MARGIN + 8.0, ROW_HEIGHT, BAND);
+        }
+        canvas.text(MARGIN, canvas.y, 9.0, is_header || is_total, INK, &clip(&label, 9.0, label_width));
+        if !is_header {
+            let current = canvas.fmt.amount(item["currentYear"].as_f64().unwrap_or(0.0));
+            let previous = canvas.fmt.amount(item["previousYear"].as_f64().unwrap_or(0.0));
+            canvas.text_right(right - 100.0, canvas.y, 9.0, is_total, INK, &current);
+            canvas.text_right(right, canvas.y, 9.0, is_total, INK, &previous);
+        }
+        canvas.y -= ROW_HEIGHT;
+    }
+    if items.iter().any(|item| item_edits::is_manual(item)) {
+        canvas.text(MARGIN, canvas.y, 8.0, false, MUTED, item_edits::MANUAL_FOOTNOTE);
+        canvas.y -= ROW_HEIGHT;
+    }
+    canvas.y -= ROW_HEIGHT;
+}
+
+fn draw_annotations(canvas: &mut Canvas, notes: &[Note], items: &[serde_json::Value]) {
+    canvas.heading("Notes");
+    let width = PAGE_WIDTH - MARGIN * 2.0;
+    for note in notes {
+        let mut about = notes::item_label(note, items).unwrap_or_else(|| "Document".to_string());
+        if let Some(author) = &note.author {
+            about = format!("{}  |  {}", about, author);
+        }
+        canvas.ensure_space(ROW_HEIGHT * 2.0);
+        canvas.text(MARGIN, canvas.y, 9.0, true, MUTED, &clip(&about, 9.0, width));
+        canvas.y -= ROW_HEIGHT;
+        for line in wrap(&note.body, 10.0, width) {
+            canvas.ensure_space(ROW_HEIGHT);
+            canvas.text(MARGIN, canvas.y, 10.0, false, INK, &line);
+            canvas.y -= ROW_HEIGHT;
+        }
+        canvas.y -= ROW_HEIGHT / 2.0;
+    }
+    canvas.y -= ROW_HEIGHT;
+}
+
+fn draw_watchlist(canvas: &mut Canvas, entries: &[WatchlistEntry]) {
+    canvas.heading("Watchlist");
+    let right = PAGE_WIDTH - MARGIN;
+    let columns = [right - 240.0, right - 160.0, right - 80.0, right];
+    let table_header = |canvas: &mut Canvas| {
+        canvas.text(MARGIN, canvas.y, 9.0, true, MUTED, "Symbol");
+        canvas.text(MARGIN + 120.0, canvas.y, 9.0, true, MUTED, "Exchange");
+        for (right, title) in columns.iter().zip(["Last Price", "Change", "Change %", "Updated"]) {
+            canvas.text_right(*right, canvas.y, 9.0, true, MUTED, title);
+        }
+        canvas.y -= ROW_HEIGHT;
+    };
+    table_header(canvas);
+
+    for (i, entry) in entries.iter().enumerate() {
+        if canvas.y - ROW_HEIGHT < MARGIN {
+            canvas.finish_page();
+            table_header(canvas);
+        }
+        if i % 2 == 0 {
+            canvas.rect(MARGIN - 4.0, canvas.y - 4.0, right - MARGIN + 8.0, ROW_HEIGHT, BAND);
+        }
+        let change_color = match entry.day_change {
+            Some(c) if c > 0.0 => (0.12, 0.5, 0.25),
+            Some(c) if c < 0.0 => (0.72, 0.18, 0.18),
+            _ => INK,
+        };
+        let updated = entry.updated_at.map_or_else(|| "-".to_string(), |t| canvas.fmt.timestamp(t));
+        let signed = |v: f64| if v > 0.0 { format!("+{}", canvas.fmt.number(v, 2)) } else { canvas.fmt.number(v, 2) };
+        let price = entry.last_price.map(|p| canvas.fmt.number(p, 2)).unwrap_or_else(|| "-".to_string());
+        let change = entry.day_change.map(signed).unwrap_or_else(|| "-".to_string());
+        let percent = entry.day_change_percent.map(|c| format!("{}%", signed(c))).unwrap_or_else(|| "-".to_string());
+
+        canvas.text(MARGIN, canvas.y, 10.0, true, INK, &entry.symbol);
+        canvas.text(MARGIN + 120.0, canvas.y, 10.0, false, MUTED, &entry.exchange);
+        canvas.text_right(columns[0], canvas.y, 10.0, false, INK, &price);
+        canvas.text_right(columns[1], canvas.y, 10.0, false, change_color, &change);
+        canvas.text_right(columns[2], canvas.y, 10.0, false, change_color, &percent);
+        canvas.text_right(columns[3], canvas.y, 9.0, false, MUTED, &updated);
+        canvas.y -= ROW_HEIGHT;
+    }
+}
+
+fn write_report(app: &AppHandle, stem: &str, bytes: Vec<u8>) -> Result<String, String> {
+    let dir = profiles::active_reports_dir(app);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create reports folder: {}", e))?;
+    let path = dir.join(format!("{}-report-{}.pdf", stem, now_secs()));
+    std::fs::write(&path, bytes).map_err(|e| format!("Failed to write report: {}", e))?;
+    Ok(path.display().to_string())
+}
+
+/// Render a document report into the profile's reports folder; returns the PDF path.
+pub fn render_document_report(app: &AppHandle, document_id: i64, template: Option<&TemplateRef>) -> Result<String, String> {
+    let template = report_templates::resolve(app, template)?;
+    let mut document = statements::load_document(app, document_id)?;
+    if document.items.is_empty() {
+        return Err(format!("Document {} has no extracted items to report on", document_id));
+    }
+    let fmt = Formatter::for_document(app, &mut document, &template.number_style)?;
+    let mut ratios = ratios::for_document(&document);
+    let (filename, items) = (document.filename, document.items);
+    if !template.metrics.is_empty() {
+        ratios = template.metrics.iter()
+            .filter_map(|key| ratios.iter().find(|r| &r.key == key).cloned())
+            .collect();
+    }
+
+    let mut canvas = Canvas::new(&filename, fmt);
+    canvas.branding = template.branding_text.clone().filter(|b| !b.trim().is_empty());
+    for section in &template.sections {
+        match section {
+            ReportSection::Cover => {
+                let details = format!("{} template  |  {} line items", template.name, items.len());
+                draw_cover(&mut canvas, "Financial Analysis Report", &filename, &details, template.logo_path.as_deref());
+            }
+            ReportSection::Ratios => draw_ratios(&mut canvas, &ratios),
+            ReportSection::Chart => draw_chart(&mut canvas, &chart_figures(&items)),
+            ReportSection::Annotations => {
+                let notes = notes::for_document(app, document_id)?;
+                if !notes.is_empty() {
+                    draw_annotations(&mut canvas, &notes, &items);
+                }
+            }
+            section => {
+                let Some(statement) = section.statement() else { continue };
+                let title = STATEMENTS.iter().find(|(s, _)| *s == statement).map(|(_, t)| *t).unwrap_or(statement);
+                let lines: Vec<&serde_json::Value> = items.iter().filter(|item| statement_of(item) == statement).collect();
+                if !lines.is_empty() {
+                    draw_statement(&mut canvas, title, &lines);
+                }
+            }
+        }
+    }
+
+    let stem = std::path::Path::new(&filename)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| format!("document-{}", document_id));
+    let path = write_report(app, &stem, canvas.into_pdf())?;
+    eprintln!("[Report] Wrote {} ({} items, {} ratios, template {})", path, items.len(), ratios.len(), template.name);
+    Ok(path)
+}
+
+/// Latest refreshed prices for every watched symbol; returns the PDF path.
+pub fn render_watchlist_summary(app: &AppHandle) -> Result<String, String> {
+    let entries = watchlist::load_entries(app)?;
+    if entries.is_empty() {
+        return Err("The watchlist is empty".to_string());
+    }
+    let advancing = entries.iter().filter(|e| e.day_change.is_some_and(|c| c > 0.0)).count();
+    let declining = entries.iter().filter(|e| e.day_change.is_some_and(|c| c < 0.0)).count();
+
+    let mut canvas = Canvas::new("Watchlist Summary", Formatter::for_app(app));
+    draw_cover(
+        &mut canvas,
+        "Watchlist Summary",
+        &format!("{} symbols", entries.len()),
+        &format!("{} advancing  |  {} declining", advancing, declining),
+        None,
+    );
+    draw_watchlist(&mut canvas, &entries);
+
+    let path = write_report(app, "watchlist", canvas.into_pdf())?;
+    eprintln!("[Report] Wrote {} ({} symbols)", path, entries.len());
+    Ok(path)
+}
+
+// Tauri Commands
+#[tauri::command]
+pub async fn generate_report(app: AppHandle, document_id: i64, template: Option<TemplateRef>) -> Result<String, String> {
+    let html = usage::measure(&app, UsageKind::Export, || render_document_report(&app, document_id, template.as_ref()))?;
+    activity::record(&app, ActivityKind::ReportGenerated, Some(document_id), "Generated report", None);
+    Ok(html)
+}
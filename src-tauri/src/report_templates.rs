@@ -0,0 +1,284 @@
+// Report Templates - saved report layouts (sections, metrics, branding, number style) per client
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+use crate::db;
+use crate::formatting::NumberStyle;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS report_templates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE,
+    sections TEXT NOT NULL,        -- JSON array of section names, in order
+    metrics TEXT NOT NULL,         -- JSON array of ratio keys; empty means all
+    branding_text TEXT,
+    logo_path TEXT,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS report_template_styles (
+    template_id INTEGER PRIMARY KEY,
+    style TEXT NOT NULL            -- JSON NumberStyle
+);
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportSection {
+    Cover,
+    Ratios,
+    Chart,
+    IncomeStatement,
+    BalanceSheet,
+    CashFlow,
+    Notes,
+    /// The user's notes on the document and its items
+    Annotations,
+}
+
+impl ReportSection {
+    /// The statement type a statement section lists.
+    pub fn statement(&self) -> Option<&'static str> {
+        match self {
+            ReportSection::IncomeStatement => Some("income_statement"),
+            ReportSection::BalanceSheet => Some("balance_sheet"),
+            ReportSection::CashFlow => Some("cash_flow"),
+            ReportSection::Notes => Some("notes"),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportTemplate {
+    /// `None` for the built-in templates
+    pub id: Option<i64>,
+    pub name: String,
+    pub sections: Vec<ReportSection>,
+    /// Ratio keys to include, in order; empty includes every computed ratio
+    pub metrics: Vec<String>,
+    pub branding_text: Option<String>,
+    pub logo_path: Option<String>,
+    /// Grouping, scale and unit suffix for amounts; unset fields follow the settings
+    pub number_style: NumberStyle,
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportTemplateInput {
+    pub name: String,
+    pub sections: Vec<ReportSection>,
+    #[serde(default)]
+    pub metrics: Vec<String>,
+    pub branding_text: Option<String>,
+    pub logo_path: Option<String>,
+    #[serde(default)]
+    pub number_style: NumberStyle,
+}
+
+/// A saved template by id, or a built-in one ("standard", "summary") by name.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TemplateRef {
+    Id(i64),
+    Name(String),
+}
+
+impl TemplateRef {
+    /// Schedules keep the reference as text; numbers are saved template ids.
+    pub fn from_stored(value: &str) -> Self {
+        value.trim().parse().map(TemplateRef::Id).unwrap_or_else(|_| TemplateRef::Name(value.to_string()))
+    }
+
+    pub fn to_stored(&self) -> String {
+        match self {
+            TemplateRef::Id(id) => id.to_string(),
+            TemplateRef::Name(name) => name.clone(),
+        }
+    }
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn builtin(name: &str) -> Option<ReportTemplate> {
+    use ReportSection::*;
+    let (name, sections) = match name.to_lowercase().as_str() {
+        "standard" | "full" => ("standard", vec![Cover, Ratios, Chart, IncomeStatement, BalanceSheet, CashFlow, Notes]),
+        "summary" => ("summary", vec![Cover, Ratios, Chart]),
+        _ => return None,
+    };
+    Some(ReportTemplate {
+        id: None,
+        name: name.to_string(),
+        sections,
+        metrics: Vec::new(),
+        branding_text: None,
+        logo_path: None,
+        number_style: NumberStyle::default(),
+        created_at: None,
+        updated_at: None,
+    })
+}
+
+fn validate(input: &ReportTemplateInput) -> Result<(), String> {
+    if input.name.trim().is_empty() {
+        return Err("Template name cannot be empty".to_string());
+    }
+    if builtin(input.name.trim()).is_some() {
+        return Err(format!("'{}' is a built-in template name", input.name.trim()));
+    }
+    if input.sections.is_empty() {
+        return Err("A template needs at least one section".to_string());
+    }
+    input.number_style.validate()?;
+    if let Some(logo) = input.logo_path.as_deref().filter(|p| !p.is_empty()) {
+        if !std::path::Path::new(logo).is_file() {
+            return Err(format!("Logo not found: {}", logo));
+        }
+    }
+    Ok(())
+}
+
+fn load_templates(app: &AppHandle, id: Option<i64>) -> Result<Vec<ReportTemplate>, String> {
+    let conn = db::open_app_db(app)?;
+    let mut stmt = conn.prepare(
+        "SELECT t.id, t.name, t.sections, t.metrics, t.branding_text, t.logo_path, t.created_at, t.updated_at, s.style
+         FROM report_templates t LEFT JOIN report_template_styles s ON s.template_id = t.id
+         WHERE ?1 IS NULL OR t.id = ?1 ORDER BY t.name COLLATE NOCASE"
+    ).map_err(|e| e.to_string())?;
+
+    let rows = stmt.query_map(params![id], |row| {
+        let sections: String = row.get(2)?;
+        let metrics: String = row.get(3)?;
+        let style: Option<String> = row.get(8)?;
+        Ok(ReportTemplate {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            sections: serde_json::from_str(&sections).unwrap_or_default(),
+            metrics: serde_json::from_str(&metrics).unwrap_or_default(),
+            branding_text: row.get(4)?,
+            logo_path: row.get(5)?,
+            number_style: style.and_then(|s| serde_json::from_str(&s).ok()).unwrap_or_default(),
+            created_at: row.get(6)?,
+            updated_at: row.get(7)?,
+        })
+    }).map_err(|e| e.to_string())?;
+
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Resolve a reference; `None` means the standard layout.
+pub fn resolve(app: &AppHandle, template: Option<&TemplateRef>) -> Result<ReportTemplate, String> {
+    match template {
+        None => Ok(builtin("standard").expect("standard template")),
+        Some(TemplateRef::Id(id)) => load_templates(app, Some(*id))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("Unknown report template: {}", id)),
+        Some(TemplateRef::Name(name)) => {
+            if let Some(template) = builtin(name) {
+                return Ok(template);
+            }
+            // Saved templates can be referred to by name too
+            let conn = db::open_app_db(app)?;
+            let id: Option<i64> = conn.query_row(
+                "SELECT id FROM report_templates WHERE name = ?1 COLLATE NOCASE",
+                params![name.trim()],
+                |row| row.get(0),
+            ).optional().map_err(|e| e.to_string())?;
+            match id {
+                Some(id) => resolve(app, Some(&TemplateRef::Id(id))),
+                None => Err(format!("Unknown report template: {}", name)),
+            }
+        }
+    }
+}
+
+fn save_style(conn: &rusqlite::Connection, id: i64, style: &NumberStyle) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR REPLACE INTO report_template_styles (template_id, style) VALUES (?1, ?2)",
+        params![id, serde_json::to_string(style).map_err(|e| e.to_string())?],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn list_report_templates(app: AppHandle) -> Result<Vec<ReportTemplate>, String> {
+    let mut templates: Vec<ReportTemplate> = ["standard", "summary"].iter().filter_map(|name| builtin(name)).collect();
+    templates.extend(load_templates(&app, None)?);
+    Ok(templates)
+}
+
+#[tauri::command]
+pub fn create_report_template(app: AppHandle, template: ReportTemplateInput) -> Result<ReportTemplate, String> {
+    validate(&template)?;
+    let now = now_secs();
+    let conn = db::open_app_db(&app)?;
+    conn.execute(
+        "INSERT INTO report_templates (name, sections, metrics, branding_text, logo_path, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)",
+        params![
+            template.name.trim(),
+            serde_json::to_string(&template.sections).map_err(|e| e.to_string())?,
+            serde_json::to_string(&template.metrics).map_err(|e| e.to_string())?,
+            template.branding_text,
+            template.logo_path,
+            now,
+        ],
+    ).map_err(|e| match e {
+        rusqlite::Error::SqliteFailure(f, _) if f.code == rusqlite::ErrorCode::ConstraintViolation => {
+            format!("A template named '{}' already exists", template.name.trim())
+        }
+        e => e.to_string(),
+    })?;
+    let id = conn.last_insert_rowid();
+    save_style(&conn, id, &template.number_style)?;
+    resolve(&app, Some(&TemplateRef::Id(id)))
+}
+
+#[tauri::command]
+pub fn update_report_template(app: AppHandle, id: i64, template: ReportTemplateInput) -> Result<ReportTemplate, String> {
+    validate(&template)?;
+    let conn = db::open_app_db(&app)?;
+    let updated = conn.execute(
+        "UPDATE report_templates
+         SET name = ?1, sections = ?2, metrics = ?3, branding_text = ?4, logo_path = ?5, updated_at = ?6
+         WHERE id = ?7",
+        params![
+            template.name.trim(),
+            serde_json::to_string(&template.sections).map_err(|e| e.to_string())?,
+            serde_json::to_string(&template.metrics).map_err(|e| e.to_string())?,
+            template.branding_text,
+            template.logo_path,
+            now_secs(),
+            id,
+        ],
+    ).map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Unknown report template: {}", id));
+    }
+    save_style(&conn, id, &template.number_style)?;
+    resolve(&app, Some(&TemplateRef::Id(id)))
+}
+
+#[tauri::command]
+pub fn delete_report_template(app: AppHandle, id: i64) -> Result<(), String> {
+    let conn = db::open_app_db(&app)?;
+    conn.execute("DELETE FROM report_templates WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM report_template_styles WHERE template_id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,243 @@
+// Retention - deletes what the retention settings no longer keep: the parser's raw page text of
+// old documents, snapshots beyond the newest few per document and the least recently used chat
+// sessions once their files outgrow the cap. Runs periodically; `preview_cleanup` shows the same
+// plan without deleting anything.
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+use crate::app_lock;
+use crate::chat_sessions;
+use crate::db;
+use crate::profiles;
+use crate::settings::{self, RetentionSettings, SettingsState};
+
+const CLEANUP_INTERVAL_SECS: u64 = 6 * 60 * 60;
+// Leave startup to more urgent work
+const FIRST_CLEANUP_DELAY_SECS: u64 = 5 * 60;
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpiredText {
+    pub document_id: i64,
+    pub filename: String,
+    pub processed_at: Option<String>,
+    pub chunks: usize,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpiredSnapshot {
+    pub id: i64,
+    pub document_id: i64,
+    pub label: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpiredChat {
+    pub session_id: String,
+    pub bytes: u64,
+    pub modified_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanupReport {
+    /// Nothing was deleted; this is what a cleanup would do now
+    pub dry_run: bool,
+    pub texts: Vec<ExpiredText>,
+    pub snapshots: Vec<ExpiredSnapshot>,
+    pub chats: Vec<ExpiredChat>,
+    /// Text and chat file bytes freed (or that would be)
+    pub bytes: u64,
+    pub ran_at: i64,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn retention_settings(app: &AppHandle) -> RetentionSettings {
+    let state = app.state::<SettingsState>();
+    let retention = settings::blocking_read(&state).get().retention.clone();
+    retention
+}
+
+fn table_exists(conn: &Connection, table: &str) -> Result<bool, String> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+        params![table],
+        |row| row.get(0),
+    ).map_err(|e| e.to_string())
+}
+
+/// Documents processed more than `days` ago that still have page text.
+fn expired_texts(app: &AppHandle, days: u32) -> Result<Vec<ExpiredText>, String> {
+    let path = profiles::active_db_path(app);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let conn = db::open_pipeline_db(app)?;
+    // Created by the Python pipeline or on first use
+    if !table_exists(&conn, "text_chunks")? || !table_exists(&conn, "documents")? {
+        return Ok(Vec::new());
+    }
+    let mut stmt = conn.prepare(
+        "SELECT d.id, d.filename, d.processed_at, COUNT(t.id), IFNULL(SUM(LENGTH(CAST(t.content AS BLOB))), 0)
+         FROM documents d JOIN text_chunks t ON t.doc_id = d.id
+         WHERE d.processed_at < datetime('now', ?1)
+         GROUP BY d.id ORDER BY d.processed_at"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![format!("-{} days", days)], |row| {
+        Ok(ExpiredText {
+            document_id: row.get(0)?,
+            filename: row.get(1)?,
+            processed_at: row.get(2)?,
+            chunks: row.get::<_, i64>(3)? as usize,
+            bytes: row.get::<_, i64>(4)? as u64,
+        })
+    }).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Every snapshot past the newest `keep` of its document.
+fn expired_snapshots(app: &AppHandle, keep: u32) -> Result<Vec<ExpiredSnapshot>, String> {
+    let conn = db::open_app_db(app)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, document_id, label, created_at FROM (
+            SELECT id, document_id, label, created_at,
+                   ROW_NUMBER() OVER (PARTITION BY document_id ORDER BY created_at DESC, id DESC) AS position
+            FROM analysis_snapshots
+         ) WHERE position > ?1 ORDER BY document_id, created_at"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![keep], |row| {
+        Ok(ExpiredSnapshot { id: row.get(0)?, document_id: row.get(1)?, label: row.get(2)?, created_at: row.get(3)? })
+    }).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+fn modified_secs(path: &Path) -> i64 {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// The least recently written sessions whose removal brings the history under `cap_mb`.
+fn expired_chats(app: &AppHandle, cap_mb: u32) -> Vec<ExpiredChat> {
+    let Ok(entries) = std::fs::read_dir(profiles::active_chat_history_dir(app)) else { return Vec::new() };
+    let mut sessions: Vec<ExpiredChat> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("json"))
+        .filter_map(|path| {
+            let session_id = path.file_stem()?.to_string_lossy().to_string();
+            let bytes = std::fs::metadata(&path).ok()?.len();
+            Some(ExpiredChat { session_id, bytes, modified_at: modified_secs(&path) })
+        })
+        .collect();
+    sessions.sort_by_key(|session| session.modified_at);
+    let mut total: u64 = sessions.iter().map(|session| session.bytes).sum();
+    let cap = cap_mb as u64 * BYTES_PER_MB;
+    sessions.into_iter()
+        .take_while(|session| {
+            let over = total > cap;
+            total = total.saturating_sub(session.bytes);
+            over
+        })
+        .collect()
+}
+
+/// What the current settings would delete.
+fn plan(app: &AppHandle, retention: &RetentionSettings) -> Result<CleanupReport, String> {
+    let texts = if retention.text_days > 0 { expired_texts(app, retention.text_days)? } else { Vec::new() };
+    let snapshots = if retention.snapshots_per_document > 0 {
+        expired_snapshots(app, retention.snapshots_per_document)?
+    } else {
+        Vec::new()
+    };
+    let chats = if retention.chat_history_mb > 0 { expired_chats(app, retention.chat_history_mb) } else { Vec::new() };
+    let bytes = texts.iter().map(|text| text.bytes).sum::<u64>() + chats.iter().map(|chat| chat.bytes).sum::<u64>();
+    Ok(CleanupReport { dry_run: true, texts, snapshots, chats, bytes, ran_at: now_secs() })
+}
+
+fn execute(app: &AppHandle, report: &CleanupReport) -> Result<(), String> {
+    if !report.texts.is_empty() {
+        let conn = db::open_pipeline_db(app)?;
+        for text in &report.texts {
+            conn.execute("DELETE FROM text_chunks WHERE doc_id = ?1", params![text.document_id])
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    if !report.snapshots.is_empty() {
+        let conn = db::open_app_db(app)?;
+        for snapshot in &report.snapshots {
+            conn.execute("DELETE FROM analysis_snapshots WHERE id = ?1", params![snapshot.id])
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    for chat in &report.chats {
+        chat_sessions::delete_session(app, &chat.session_id)?;
+    }
+    Ok(())
+}
+
+/// Apply the retention settings now.
+pub fn cleanup(app: &AppHandle) -> Result<CleanupReport, String> {
+    let mut report = plan(app, &retention_settings(app))?;
+    execute(app, &report)?;
+    report.dry_run = false;
+    if !report.texts.is_empty() || !report.snapshots.is_empty() || !report.chats.is_empty() {
+        info!(
+            texts = report.texts.len(),
+            snapshots = report.snapshots.len(),
+            chats = report.chats.len(),
+            bytes = report.bytes,
+            "Retention cleanup"
+        );
+        let _ = app.emit("retention-cleanup", &report);
+    }
+    Ok(report)
+}
+
+/// Background loop; does nothing while every rule is 0, and waits while the workspace is locked.
+pub fn start_cleanup_task(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(FIRST_CLEANUP_DELAY_SECS)).await;
+        loop {
+            app_lock::unlocked(&app).await;
+            let handle = app.clone();
+            match tauri::async_runtime::spawn_blocking(move || cleanup(&handle)).await {
+                Ok(Err(e)) => warn!("Retention cleanup failed: {}", e),
+                Err(e) => warn!("Retention cleanup failed: {}", e),
+                Ok(Ok(_)) => {}
+            }
+            tokio::time::sleep(Duration::from_secs(CLEANUP_INTERVAL_SECS)).await;
+        }
+    });
+}
+
+// Tauri Commands
+/// What a cleanup would delete under the saved settings, or under `retention` when given, so a
+/// settings screen can show the effect before saving. Deletes nothing.
+#[tauri::command]
+pub fn preview_cleanup(app: AppHandle, retention: Option<RetentionSettings>) -> Result<CleanupReport, String> {
+    let retention = retention.unwrap_or_else(|| retention_settings(&app));
+    plan(&app, &retention)
+}
+
+#[tauri::command]
+pub fn run_cleanup(app: AppHandle) -> Result<CleanupReport, String> {
+    cleanup(&app)
+}
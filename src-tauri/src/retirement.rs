@@ -0,0 +1,232 @@
+// Retirement - the corpus needed to retire on today's expenses, and the year-by-year path of
+// savings towards it and of withdrawals after, for charting
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::inflation;
+
+const MAX_AGE: u32 = 110;
+const DEFAULT_LIFE_EXPECTANCY: u32 = 85;
+// Bisection on the monthly contribution that meets the target
+const SOLVER_ITERATIONS: usize = 100;
+const SOLVER_TOLERANCE: f64 = 1.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetirementInputs {
+    pub current_age: u32,
+    pub retirement_age: u32,
+    /// Age the money has to last to; default 85
+    pub life_expectancy: Option<u32>,
+    /// Already saved towards retirement
+    pub current_savings: f64,
+    /// Saved each month from now until retirement
+    pub monthly_contribution: f64,
+    /// Yearly increase in the contribution, in percent
+    pub contribution_step_up: Option<f64>,
+    /// Yearly return before retirement, in percent
+    pub pre_retirement_return: f64,
+    /// Yearly return on the corpus after retirement, in percent; defaults to the pre-retirement one
+    pub post_retirement_return: Option<f64>,
+    /// Yearly inflation, in percent; the CPI's average over the last ten years when unset
+    pub inflation: Option<f64>,
+    /// Country whose CPI sets the default inflation, ISO alpha-3; India by default
+    pub cpi_country: Option<String>,
+    /// Yearly expenses in retirement, in today's money
+    pub annual_expenses: f64,
+    /// Corpus sized as the first year's expenses over this rate (a "4% rule"), in percent;
+    /// without it, the corpus that runs out exactly at `life_expectancy`
+    pub withdrawal_rate: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetirementPhase {
+    Accumulation,
+    Withdrawal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetirementYear {
+    /// Years from now, starting at 1
+    pub year: u32,
+    /// Age at the end of the year
+    pub age: u32,
+    pub phase: RetirementPhase,
+    pub opening_balance: f64,
+    pub contribution: f64,
+    pub withdrawal: f64,
+    pub growth: f64,
+    /// Never below zero; the year it reaches zero the withdrawal is what was left
+    pub closing_balance: f64,
+    /// The closing balance in today's money
+    pub real_balance: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetirementPlan {
+    /// The inflation the plan assumed, in percent
+    pub inflation: f64,
+    /// "input", or the CPI years it was averaged over
+    pub inflation_source: String,
+    /// At retirement, in the money of that year
+    pub required_corpus: f64,
+    pub projected_corpus: f64,
+    /// Required less projected; zero when on track
+    pub shortfall: f64,
+    /// The first year's expenses in retirement, inflated from today
+    pub first_year_expenses: f64,
+    /// Monthly contribution, stepping up as given, that reaches the required corpus
+    pub required_monthly_contribution: f64,
+    /// The age the projected corpus runs out, if before `life_expectancy`
+    pub depletion_age: Option<u32>,
+    pub years: Vec<RetirementYear>,
+}
+
+fn validate(inputs: &RetirementInputs) -> Result<u32, String> {
+    let life_expectancy = inputs.life_expectancy.unwrap_or(DEFAULT_LIFE_EXPECTANCY);
+    if inputs.retirement_age <= inputs.current_age {
+        return Err("Retirement age must be after the current age".to_string());
+    }
+    if life_expectancy <= inputs.retirement_age || life_expectancy > MAX_AGE {
+        return Err(format!("Life expectancy must be after retirement and at most {}", MAX_AGE));
+    }
+    let amounts = [inputs.current_savings, inputs.monthly_contribution, inputs.annual_expenses];
+    if amounts.iter().any(|a| !a.is_finite() || *a < 0.0) {
+        return Err("Savings, contribution and expenses must not be negative".to_string());
+    }
+    let rates = [Some(inputs.pre_retirement_return), inputs.post_retirement_return, inputs.inflation, inputs.contribution_step_up];
+    if rates.iter().flatten().any(|r| !r.is_finite() || *r <= -100.0) {
+        return Err("Rates must be numbers above -100%".to_string());
+    }
+    if inputs.withdrawal_rate.is_some_and(|r| !r.is_finite() || r <= 0.0) {
+        return Err("Withdrawal rate must be positive".to_string());
+    }
+    Ok(life_expectancy)
+}
+
+/// The balance at retirement from `monthly` stepped up yearly, contributions made through
+/// each year earning half a year's return.
+fn accumulate(inputs: &RetirementInputs, monthly: f64, mut record: impl FnMut(RetirementYear)) -> f64 {
+    let growth = inputs.pre_retirement_return / 100.0;
+    let step_up = inputs.contribution_step_up.unwrap_or(0.0) / 100.0;
+    let inflation = inputs.inflation.unwrap_or(0.0) / 100.0;
+    let mut balance = inputs.current_savings;
+    for year in 1..=inputs.retirement_age - inputs.current_age {
+        let contribution = monthly * 12.0 * (1.0 + step_up).powi(year as i32 - 1);
+        let earned = balance * growth + contribution * ((1.0 + growth).sqrt() - 1.0);
+        let opening_balance = balance;
+        balance += contribution + earned;
+        record(RetirementYear {
+            year,
+            age: inputs.current_age + year,
+            phase: RetirementPhase::Accumulation,
+            opening_balance,
+            contribution,
+            withdrawal: 0.0,
+            growth: earned,
+            closing_balance: balance,
+            real_balance: balance / (1.0 + inflation).powi(year as i32),
+        });
+    }
+    balance
+}
+
+/// Present value at retirement of expenses that rise with inflation, withdrawn at the start of
+/// each year, over `years` years.
+fn annuity_corpus(first_year_expenses: f64, real_return: f64, years: u32) -> f64 {
+    if real_return.abs() < 1e-12 {
+        return first_year_expenses * years as f64;
+    }
+    first_year_expenses * (1.0 - (1.0 + real_return).powi(-(years as i32))) / real_return * (1.0 + real_return)
+}
+
+/// The plan for `inputs`, whose inflation must be set; `inflation_source` says where it came from.
+pub fn plan(inputs: &RetirementInputs, inflation_source: String) -> Result<RetirementPlan, String> {
+    let life_expectancy = validate(inputs)?;
+    let years_to_retire = inputs.retirement_age - inputs.current_age;
+    let years_retired = life_expectancy - inputs.retirement_age;
+    let inflation = inputs.inflation.ok_or("Inflation is required")? / 100.0;
+    let post_return = inputs.post_retirement_return.unwrap_or(inputs.pre_retirement_return) / 100.0;
+    let first_year_expenses = inputs.annual_expenses * (1.0 + inflation).powi(years_to_retire as i32);
+
+    let required_corpus = match inputs.withdrawal_rate {
+        Some(rate) => first_year_expenses / (rate / 100.0),
+        None => annuity_corpus(first_year_expenses, (1.0 + post_return) / (1.0 + inflation) - 1.0, years_retired),
+    };
+
+    let mut years = Vec::new();
+    let projected_corpus = accumulate(inputs, inputs.monthly_contribution, |y| years.push(y));
+
+    let mut balance = projected_corpus;
+    let mut depletion_age = None;
+    for offset in 1..=years_retired {
+        let year = years_to_retire + offset;
+        let opening_balance = balance;
+        let withdrawal = (first_year_expenses * (1.0 + inflation).powi(offset as i32 - 1)).min(balance);
+        let earned = (balance - withdrawal) * post_return;
+        balance = (balance - withdrawal + earned).max(0.0);
+        if balance == 0.0 && depletion_age.is_none() {
+            depletion_age = Some(inputs.current_age + year);
+        }
+        years.push(RetirementYear {
+            year,
+            age: inputs.current_age + year,
+            phase: RetirementPhase::Withdrawal,
+            opening_balance,
+            contribution: 0.0,
+            withdrawal,
+            growth: earned,
+            closing_balance: balance,
+            real_balance: balance / (1.0 + inflation).powi(year as i32),
+        });
+    }
+
+    // The corpus grows with the contribution, so bisect between nothing and enough
+    let corpus_for = |monthly: f64| accumulate(inputs, monthly, |_| {});
+    let required_monthly_contribution = if corpus_for(0.0) >= required_corpus {
+        0.0
+    } else {
+        let mut high = (required_corpus / (years_to_retire as f64 * 12.0)).max(1.0);
+        while corpus_for(high) < required_corpus {
+            high *= 2.0;
+        }
+        let mut low = 0.0;
+        for _ in 0..SOLVER_ITERATIONS {
+            if high - low < SOLVER_TOLERANCE {
+                break;
+            }
+            let mid = (low + high) / 2.0;
+            if corpus_for(mid) < required_corpus { low = mid } else { high = mid }
+        }
+        high
+    };
+
+    Ok(RetirementPlan {
+        inflation: inflation * 100.0,
+        inflation_source,
+        required_corpus,
+        projected_corpus,
+        shortfall: (required_corpus - projected_corpus).max(0.0),
+        first_year_expenses,
+        required_monthly_contribution,
+        depletion_age,
+        years,
+    })
+}
+
+// Tauri Commands
+#[tauri::command]
+pub async fn plan_retirement(app: AppHandle, mut inputs: RetirementInputs) -> Result<RetirementPlan, String> {
+    let source = match inputs.inflation {
+        Some(_) => "input".to_string(),
+        None => {
+            let (rate, source) = inflation::average_inflation(&app, inputs.cpi_country.as_deref(), inflation::DEFAULT_AVERAGE_YEARS).await?;
+            inputs.inflation = Some(rate);
+            source
+        }
+    };
+    plan(&inputs, source)
+}
@@ -0,0 +1,482 @@
+// Scenarios - what-if copies of a document with line items tweaked (revenue +10%, raw material
+// cost +5%), recomputed through totals, profit, ratios and valuation; the stored document is
+// never touched, only the adjustments are kept
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+use tracing::info;
+
+use crate::db;
+use crate::dcf::{self, DcfInputs, DcfResult};
+use crate::metric_formulas;
+use crate::periods::Period;
+use crate::ratios::{self, Ratio};
+use crate::report;
+use crate::statements::{self, StoredDocument};
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS scenarios (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    document_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    adjustments TEXT NOT NULL,     -- JSON array of Adjustment
+    dcf TEXT,                      -- JSON DcfInputs, when the scenario carries a valuation
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_scenarios_document ON scenarios(document_id);
+";
+
+// Changes below this are float noise, not something the scenario moved
+const TOLERANCE: f64 = 1e-6;
+const GRAND_TOTALS: &[&str] = &["total assets", "total equity and liabilities", "total liabilities and equity"];
+const EXPENSE_WORDS: &[&str] = &["expense", "cost", "purchase", "consumed", "depreciation", "amortisation", "amortization"];
+
+/// One tweak to the current period of a line. `percent` applies first, then `amount`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Adjustment {
+    pub label: String,
+    /// Narrows the match when a label appears in more than one statement
+    pub statement_type: Option<String>,
+    /// e.g. 10 for +10%
+    pub percent: Option<f64>,
+    /// In the document's (normalized) units
+    pub amount: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioInfo {
+    pub id: i64,
+    pub document_id: i64,
+    pub name: String,
+    pub adjustments: Vec<Adjustment>,
+    pub dcf: Option<DcfInputs>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineChange {
+    pub statement: String,
+    pub label: String,
+    pub base: f64,
+    pub scenario: f64,
+    /// False for totals and profit lines the scenario moved downstream
+    pub adjusted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricChange {
+    pub key: String,
+    pub name: String,
+    pub unit: String,
+    pub base: Option<f64>,
+    pub scenario: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioResult {
+    pub scenario: ScenarioInfo,
+    pub period: Option<Period>,
+    pub lines: Vec<LineChange>,
+    /// Built-in ratios, metric formulas and free cash flow at the current period
+    pub metrics: Vec<MetricChange>,
+    pub base_valuation: Option<DcfResult>,
+    pub valuation: Option<DcfResult>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComparisonRow {
+    pub key: String,
+    pub name: String,
+    pub unit: String,
+    pub base: Option<f64>,
+    /// One per scenario, in the order asked for
+    pub values: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioComparison {
+    pub document_id: i64,
+    pub scenarios: Vec<ScenarioInfo>,
+    pub rows: Vec<ComparisonRow>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn normalize(label: &str) -> String {
+    label.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+fn label_of(item: &serde_json::Value) -> String {
+    normalize(item["label"].as_str().unwrap_or(""))
+}
+
+fn current(item: &serde_json::Value) -> f64 {
+    crate::periods::amounts(item).0.unwrap_or(0.0)
+}
+
+/// Move an item's current amount by `delta`, in the year column and at its period.
+fn shift(item: &mut serde_json::Value, delta: f64) {
+    let value = current(item) + delta;
+    if let Some(period) = item["currentPeriod"].as_str().map(str::to_string) {
+        if item["periods"].is_object() {
+            item["periods"][period] = value.into();
+        }
+    }
+    item["currentYear"] = value.into();
+}
+
+fn is_profit(label: &str) -> bool {
+    ["profit", "net profit", "operating profit", "ebit", "earnings before", "loss", "(loss)", "total comprehensive income"]
+        .iter()
+        .any(|prefix| label.starts_with(prefix))
+}
+
+fn is_after_tax(label: &str) -> bool {
+    ["after tax", "for the year", "for the period", "total comprehensive income"].iter().any(|s| label.contains(s))
+        || label.starts_with("net profit")
+}
+
+/// Profit after tax over profit before tax, as reported; 1 when either is missing.
+fn retained_share(items: &[serde_json::Value]) -> f64 {
+    match (ratios::mapped(items, "net_profit"), ratios::mapped(items, "pbt")) {
+        (Some((pat, _)), Some((pbt, _))) if pbt > 0.0 => (pat / pbt).clamp(0.0, 1.0),
+        _ => 1.0,
+    }
+}
+
+/// Index of the item an adjustment targets: an exact label before a prefix match.
+fn target(items: &[serde_json::Value], adjustment: &Adjustment) -> Result<usize, String> {
+    let wanted = normalize(&adjustment.label);
+    let statement = adjustment.statement_type.as_deref().map(str::to_lowercase);
+    let candidates: Vec<(usize, String)> = items.iter().enumerate()
+        .filter(|(_, item)| !item["isHeader"].as_bool().unwrap_or(false))
+        .filter(|(_, item)| statement.as_deref().is_none_or(|s| report::statement_of(item) == s))
+        .map(|(i, item)| (i, label_of(item)))
+        .collect();
+    candidates.iter().find(|(_, label)| *label == wanted)
+        .or_else(|| candidates.iter().find(|(_, label)| label.starts_with(&wanted)))
+        .map(|(i, _)| *i)
+        .ok_or_else(|| format!("No line \"{}\" to adjust", adjustment.label))
+}
+
+/// Carry a change of `delta` on `items[index]` to the lines computed from it. In the income
+/// statement, income moves total income and expenses total expenses, and both move the profit
+/// lines below; profit after tax moves at the reported effective tax rate (tax lines stay as
+/// reported). Elsewhere the line's subtotal and the balance sheet grand total move with it.
+fn propagate(items: &mut [serde_json::Value], index: usize, delta: f64, retained: f64) -> BTreeSet<usize> {
+    let statement = report::statement_of(&items[index]);
+    let label = label_of(&items[index]);
+    let is_total = items[index]["isTotal"].as_bool().unwrap_or(false) || label.starts_with("total");
+    let below: Vec<usize> = (index + 1..items.len())
+        .filter(|i| report::statement_of(&items[*i]) == statement && !items[*i]["isHeader"].as_bool().unwrap_or(false))
+        .collect();
+    let mut moved = BTreeSet::new();
+
+    if statement == "income_statement" {
+        let before_total_expenses = below.iter().any(|i| label_of(&items[*i]).starts_with("total expenses"));
+        let after_total_income = items[..index].iter()
+            .any(|item| report::statement_of(item) == statement && label_of(item).starts_with("total income"));
+        let expense = (before_total_expenses && after_total_income)
+            || label.starts_with("total expenses")
+            || (!after_total_income && EXPENSE_WORDS.iter().any(|w| label.contains(w)));
+        let sign = if expense { -1.0 } else { 1.0 };
+        for i in below {
+            let line = label_of(&items[i]);
+            let change = if is_profit(&line) {
+                sign * delta * if is_after_tax(&line) { retained } else { 1.0 }
+            } else if !is_total && if expense {
+                line.starts_with("total expenses")
+            } else {
+                line.starts_with("total income") || line.starts_with("total revenue")
+            } {
+                delta
+            } else {
+                continue;
+            };
+            shift(&mut items[i], change);
+            moved.insert(i);
+        }
+        return moved;
+    }
+
+    let subtotal = if is_total { None } else {
+        below.iter().copied().find(|i| items[*i]["isTotal"].as_bool().unwrap_or(false) || label_of(&items[*i]).starts_with("total"))
+    };
+    let grand_total = (statement == "balance_sheet" && !GRAND_TOTALS.iter().any(|g| label.starts_with(g)))
+        .then(|| below.iter().copied().find(|i| GRAND_TOTALS.iter().any(|g| label_of(&items[*i]).starts_with(g))))
+        .flatten();
+    for i in subtotal.into_iter().chain(grand_total.filter(|g| Some(*g) != subtotal)) {
+        shift(&mut items[i], delta);
+        moved.insert(i);
+    }
+    moved
+}
+
+/// Scenario items, the indexes adjusted and the indexes moved downstream of them.
+pub type Adjusted = (Vec<serde_json::Value>, BTreeSet<usize>, BTreeSet<usize>);
+
+/// The document's items with `adjustments` applied.
+pub fn adjust(document: &StoredDocument, adjustments: &[Adjustment]) -> Result<Adjusted, String> {
+    let mut items = document.items.clone();
+    let retained = retained_share(&items);
+    let (mut adjusted, mut moved) = (BTreeSet::new(), BTreeSet::new());
+    for adjustment in adjustments {
+        let index = target(&items, adjustment)?;
+        let before = current(&items[index]);
+        let after = before * (1.0 + adjustment.percent.unwrap_or(0.0) / 100.0) + adjustment.amount.unwrap_or(0.0);
+        shift(&mut items[index], after - before);
+        adjusted.insert(index);
+        moved.extend(propagate(&mut items, index, after - before, retained));
+    }
+    Ok((items, adjusted, moved))
+}
+
+fn validate(adjustments: &[Adjustment]) -> Result<(), String> {
+    if adjustments.is_empty() {
+        return Err("A scenario needs at least one adjustment".to_string());
+    }
+    for adjustment in adjustments {
+        if adjustment.percent.is_none() && adjustment.amount.is_none() {
+            return Err(format!("Adjustment to \"{}\" needs a percent or an amount", adjustment.label));
+        }
+        if adjustment.percent.into_iter().chain(adjustment.amount).any(|v| !v.is_finite()) {
+            return Err(format!("Adjustment to \"{}\" is not a number", adjustment.label));
+        }
+    }
+    Ok(())
+}
+
+/// Ratios, metric formulas and free cash flow at the current period.
+pub fn metrics(app: &AppHandle, document: &StoredDocument) -> Result<Vec<Ratio>, String> {
+    let mut document = document.clone();
+    metric_formulas::apply(app, &mut document)?;
+    let mut metrics = ratios::for_document(&document);
+    if let Some((fcf, previous)) = ratios::free_cash_flow(&document.items) {
+        metrics.push(Ratio {
+            key: "free_cash_flow".to_string(),
+            name: "Free Cash Flow".to_string(),
+            category: "cash_flow".to_string(),
+            unit: document.metadata["units"]["currency"].as_str().unwrap_or("").to_string(),
+            current: Some(fcf),
+            previous: Some(previous),
+        });
+    }
+    Ok(metrics)
+}
+
+/// DCF on the document's free cash flow unless the inputs give their own base.
+pub fn valuation(app: &AppHandle, document: &StoredDocument, inputs: Option<&DcfInputs>) -> Result<Option<DcfResult>, String> {
+    let Some(inputs) = inputs else { return Ok(None) };
+    let mut inputs = inputs.clone();
+    let source = match inputs.base_cash_flow {
+        Some(_) => "input".to_string(),
+        None => {
+            let (fcf, _) = ratios::free_cash_flow(&document.items)
+                .ok_or_else(|| format!("No operating cash flow found in {} to value", document.filename))?;
+            inputs.base_cash_flow = Some(fcf);
+            document.filename.clone()
+        }
+    };
+    inputs.document_id = None;
+    let mut result = dcf::run(app, &inputs)?;
+    result.base_source = source;
+    Ok(Some(result))
+}
+
+fn load_scenario(app: &AppHandle, id: i64) -> Result<ScenarioInfo, String> {
+    let conn = db::open_app_db(app)?;
+    let (document_id, name, adjustments, dcf, created_at): (i64, String, String, Option<String>, i64) = conn.query_row(
+        "SELECT document_id, name, adjustments, dcf, created_at FROM scenarios WHERE id = ?1",
+        params![id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+    ).map_err(|_| format!("Scenario {} not found", id))?;
+    Ok(ScenarioInfo {
+        id,
+        document_id,
+        name,
+        adjustments: serde_json::from_str(&adjustments).map_err(|e| e.to_string())?,
+        dcf: dcf.map(|d| serde_json::from_str(&d)).transpose().map_err(|e| e.to_string())?,
+        created_at,
+    })
+}
+
+/// Run `scenario` against the document as it is now.
+fn run(app: &AppHandle, scenario: ScenarioInfo) -> Result<ScenarioResult, String> {
+    let base = statements::load_document(app, scenario.document_id)?;
+    let (items, adjusted, moved) = adjust(&base, &scenario.adjustments)?;
+    let scenario_document = StoredDocument { items, ..base.clone() };
+
+    let lines = adjusted.union(&moved)
+        .map(|i| (i, current(&base.items[*i]), current(&scenario_document.items[*i])))
+        .filter(|(i, before, after)| adjusted.contains(i) || (after - before).abs() > TOLERANCE)
+        .map(|(i, before, after)| LineChange {
+            statement: report::statement_of(&base.items[*i]),
+            label: base.items[*i]["label"].as_str().unwrap_or("").to_string(),
+            base: before,
+            scenario: after,
+            adjusted: adjusted.contains(i),
+        })
+        .collect();
+
+    let (before, after) = (metrics(app, &base)?, metrics(app, &scenario_document)?);
+    let mut keys: Vec<&Ratio> = after.iter().collect();
+    keys.extend(before.iter().filter(|r| !after.iter().any(|a| a.key == r.key)));
+    let metrics = keys.into_iter()
+        .map(|ratio| MetricChange {
+            key: ratio.key.clone(),
+            name: ratio.name.clone(),
+            unit: ratio.unit.clone(),
+            base: before.iter().find(|r| r.key == ratio.key).and_then(|r| r.current),
+            scenario: after.iter().find(|r| r.key == ratio.key).and_then(|r| r.current),
+        })
+        .collect();
+
+    let base_valuation = valuation(app, &base, scenario.dcf.as_ref())?;
+    let valuation = valuation(app, &scenario_document, scenario.dcf.as_ref())?;
+    let period = base.metadata["periods"]["current"].as_str().and_then(Period::parse);
+    Ok(ScenarioResult { scenario, period, lines, metrics, base_valuation, valuation })
+}
+
+/// A saved scenario's DCF inputs with the base cash flow fixed to the scenario's free cash
+/// flow, so the valuation can be rerun without redoing the adjustments.
+pub fn dcf_inputs(app: &AppHandle, id: i64) -> Result<DcfInputs, String> {
+    let result = run(app, load_scenario(app, id)?)?;
+    let mut inputs = result.scenario.dcf.ok_or_else(|| format!("Scenario {} has no DCF inputs to value", id))?;
+    let valuation = result.valuation.expect("a scenario with DCF inputs is valued");
+    inputs.base_cash_flow = Some(valuation.base_cash_flow);
+    inputs.document_id = None;
+    Ok(inputs)
+}
+
+// Tauri Commands
+/// Save a scenario for `document_id` and return it run against the document.
+#[tauri::command]
+pub fn create_scenario(
+    app: AppHandle,
+    document_id: i64,
+    name: Option<String>,
+    adjustments: Vec<Adjustment>,
+    dcf: Option<DcfInputs>,
+) -> Result<ScenarioResult, String> {
+    validate(&adjustments)?;
+    let document = statements::load_document(&app, document_id)?;
+    // Fail on labels that don't exist before anything is saved
+    adjust(&document, &adjustments)?;
+    let name = name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty())
+        .unwrap_or_else(|| format!("Scenario for {}", document.filename));
+
+    let created_at = now_secs();
+    let conn = db::open_app_db(&app)?;
+    conn.execute(
+        "INSERT INTO scenarios (document_id, name, adjustments, dcf, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            document_id,
+            name,
+            serde_json::to_string(&adjustments).map_err(|e| e.to_string())?,
+            dcf.as_ref().map(serde_json::to_string).transpose().map_err(|e| e.to_string())?,
+            created_at,
+        ],
+    ).map_err(|e| e.to_string())?;
+    let id = conn.last_insert_rowid();
+    info!(id, document_id, adjustments = adjustments.len(), "Created scenario");
+    run(&app, ScenarioInfo { id, document_id, name, adjustments, dcf, created_at })
+}
+
+#[tauri::command]
+pub fn list_scenarios(app: AppHandle, document_id: Option<i64>) -> Result<Vec<ScenarioInfo>, String> {
+    let conn = db::open_app_db(&app)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, document_id, name, adjustments, dcf, created_at FROM scenarios
+         WHERE ?1 IS NULL OR document_id = ?1 ORDER BY created_at DESC, id DESC"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![document_id], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, String>(2)?, row.get::<_, String>(3)?, row.get::<_, Option<String>>(4)?, row.get::<_, i64>(5)?))
+    }).map_err(|e| e.to_string())?;
+
+    rows.map(|row| {
+        let (id, document_id, name, adjustments, dcf, created_at) = row.map_err(|e| e.to_string())?;
+        Ok(ScenarioInfo {
+            id,
+            document_id,
+            name,
+            adjustments: serde_json::from_str(&adjustments).map_err(|e| e.to_string())?,
+            dcf: dcf.map(|d| serde_json::from_str(&d)).transpose().map_err(|e| e.to_string())?,
+            created_at,
+        })
+    }).collect()
+}
+
+/// A saved scenario, rerun against the document's current figures.
+#[tauri::command]
+pub fn get_scenario(app: AppHandle, id: i64) -> Result<ScenarioResult, String> {
+    let scenario = load_scenario(&app, id)?;
+    run(&app, scenario)
+}
+
+#[tauri::command]
+pub fn delete_scenario(app: AppHandle, id: i64) -> Result<(), String> {
+    let conn = db::open_app_db(&app)?;
+    conn.execute("DELETE FROM scenarios WHERE id = ?1", params![id]).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Metrics and valuation side by side: the document as reported, then each scenario.
+#[tauri::command]
+pub fn compare_scenarios(app: AppHandle, ids: Vec<i64>) -> Result<ScenarioComparison, String> {
+    if ids.is_empty() {
+        return Err("Pick at least one scenario to compare".to_string());
+    }
+    let results = ids.iter()
+        .map(|id| load_scenario(&app, *id).and_then(|scenario| run(&app, scenario)))
+        .collect::<Result<Vec<_>, _>>()?;
+    let document_id = results[0].scenario.document_id;
+    if let Some(other) = results.iter().find(|r| r.scenario.document_id != document_id) {
+        return Err(format!("{} is for another document; compare scenarios of one document", other.scenario.name));
+    }
+
+    let mut rows: Vec<ComparisonRow> = Vec::new();
+    for (position, result) in results.iter().enumerate() {
+        let valuations = [
+            ("enterprise_value", "Enterprise Value", result.base_valuation.as_ref().map(|v| v.enterprise_value), result.valuation.as_ref().map(|v| v.enterprise_value)),
+            ("equity_value", "Equity Value", result.base_valuation.as_ref().map(|v| v.equity_value), result.valuation.as_ref().map(|v| v.equity_value)),
+            ("value_per_share", "Value per Share", result.base_valuation.as_ref().and_then(|v| v.value_per_share), result.valuation.as_ref().and_then(|v| v.value_per_share)),
+        ];
+        let entries = result.metrics.iter()
+            .map(|m| (m.key.clone(), m.name.clone(), m.unit.clone(), m.base, m.scenario))
+            .chain(valuations.into_iter()
+                .filter(|(_, _, base, value)| base.is_some() || value.is_some())
+                .map(|(key, name, base, value)| (key.to_string(), name.to_string(), String::new(), base, value)));
+        for (key, name, unit, base, value) in entries {
+            let row = match rows.iter().position(|row| row.key == key) {
+                Some(i) => &mut rows[i],
+                None => {
+                    rows.push(ComparisonRow { key, name, unit, base, values: vec![None; results.len()] });
+                    rows.last_mut().expect("row was just pushed")
+                }
+            };
+            // Scenarios that carry their own DCF inputs value the base differently; keep the first
+            row.base = row.base.or(base);
+            row.values[position] = value;
+        }
+    }
+
+    Ok(ScenarioComparison {
+        document_id,
+        scenarios: results.into_iter().map(|r| r.scenario).collect(),
+        rows,
+    })
+}
@@ -0,0 +1,422 @@
+// Scheduler - background work on cron expressions: reports, watchlist refreshes, cloud backups
+// and cache cleanup, persisted so schedules survive restarts and runs missed while the app was
+// closed can be caught up on startup
+use chrono::{Datelike, Duration as ChronoDuration, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+use rusqlite::{params, Row};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+use crate::backup;
+use crate::db;
+use crate::jobs::JobStatus;
+use crate::market_cache::MarketCache;
+use crate::metric_cache::MetricCache;
+use crate::notifications;
+use crate::parse_cache;
+use crate::report_templates::TemplateRef;
+use crate::schedules::{self, ReportKind};
+use crate::watchlist;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS scheduled_tasks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    task TEXT NOT NULL,            -- ScheduledTask JSON
+    cron TEXT NOT NULL,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    catch_up INTEGER NOT NULL DEFAULT 1,
+    next_run_at INTEGER,
+    last_run_at INTEGER,
+    last_status TEXT,
+    last_message TEXT,
+    created_at INTEGER NOT NULL
+);
+";
+
+// Due schedules are picked up within this long of their time
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+// Cache entries older than this go when a cleanup doesn't say
+const DEFAULT_CACHE_MAX_AGE_DAYS: u32 = 30;
+// Give up looking for a next run this far ahead (e.g. "0 0 30 2 *" never matches)
+const MAX_SEARCH_STEPS: usize = 200_000;
+
+/// What a schedule does when it fires.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScheduledTask {
+    /// A watchlist summary or a document report, rendered like a report schedule
+    #[serde(rename_all = "camelCase")]
+    Report { report: ReportKind, document_id: Option<i64>, template: Option<TemplateRef> },
+    /// Refetch every watched symbol whose exchange is trading
+    WatchlistRefresh,
+    /// Encrypted cloud backup with the configured storage and retention
+    Backup,
+    /// Drop market-data and parse results older than `max_age_days`, and the metric cache
+    #[serde(rename_all = "camelCase")]
+    CacheCleanup { max_age_days: Option<u32> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Schedule {
+    pub id: i64,
+    pub name: String,
+    pub task: ScheduledTask,
+    pub cron: String,
+    pub enabled: bool,
+    /// Run once on startup when a run was missed while the app was closed
+    pub catch_up: bool,
+    /// None when the expression never matches again
+    pub next_run_at: Option<i64>,
+    pub last_run_at: Option<i64>,
+    pub last_status: Option<String>,
+    pub last_message: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleRun {
+    pub schedule_id: i64,
+    pub name: String,
+    pub success: bool,
+    /// Where the output went, or what went wrong
+    pub message: String,
+    pub finished_at: i64,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// A five-field cron expression (minute hour day-of-month month day-of-week) in local time.
+/// Fields take `*`, numbers, ranges `a-b`, lists `a,b` and steps `*/n` or `a-b/n`; Sunday is 0
+/// or 7. `@hourly`, `@daily`, `@weekly` and `@monthly` are accepted too.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    // Cron's quirk: with both day fields restricted, either may match
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|s| *s > 0).ok_or_else(|| format!("Bad step in '{}'", part))?),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((a, b)) => (a.parse().map_err(|_| format!("Bad range '{}'", range))?, b.parse().map_err(|_| format!("Bad range '{}'", range))?),
+                // "5/15" runs from 5 to the end of the field
+                None => {
+                    let value: u32 = range.parse().map_err(|_| format!("Bad value '{}'", range))?;
+                    (value, if part.contains('/') { max } else { value })
+                }
+            },
+        };
+        if start < min || end > max || start > end {
+            return Err(format!("'{}' is outside {}-{}", part, min, max));
+        }
+        bits |= (start..=end).step_by(step as usize).fold(0u64, |bits, value| bits | 1 << value);
+    }
+    Ok(bits)
+}
+
+impl Cron {
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!("Expected 5 cron fields, got {}", fields.len()));
+        };
+        let mut weekdays = parse_field(weekday, 0, 7)?;
+        // 7 is Sunday as well as 0
+        if weekdays & 1 << 7 != 0 {
+            weekdays |= 1;
+        }
+        Ok(Cron {
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days: parse_field(day, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            weekdays,
+            days_restricted: !day.starts_with('*'),
+            weekdays_restricted: !weekday.starts_with('*'),
+        })
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = self.days & 1 << date.day() != 0;
+        let weekday = self.weekdays & 1 << date.weekday().num_days_from_sunday() != 0;
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            (true, false) => day,
+            (false, true) => weekday,
+            (false, false) => true,
+        }
+    }
+
+    /// The first matching minute strictly after `after`, as unix seconds.
+    pub fn next_after(&self, after: i64) -> Option<i64> {
+        let start = Local.timestamp_opt(after, 0).single()?.naive_local();
+        let mut at = start.with_second(0)?.with_nanosecond(0)? + ChronoDuration::minutes(1);
+        for _ in 0..MAX_SEARCH_STEPS {
+            let date = at.date();
+            if self.months & 1 << date.month() == 0 {
+                let (year, month) = if date.month() == 12 { (date.year() + 1, 1) } else { (date.year(), date.month() + 1) };
+                at = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+            } else if !self.day_matches(date) {
+                at = date.succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if self.hours & 1 << at.hour() == 0 {
+                at = NaiveDateTime::new(date, at.time().with_minute(0)?) + ChronoDuration::hours(1);
+            } else if self.minutes & 1 << at.minute() == 0 {
+                at += ChronoDuration::minutes(1);
+            } else {
+                // Times skipped by a DST change don't exist; move on to the next match
+                match Local.from_local_datetime(&at).earliest() {
+                    Some(local) if local.timestamp() > after => return Some(local.timestamp()),
+                    _ => at += ChronoDuration::minutes(1),
+                }
+            }
+        }
+        None
+    }
+}
+
+const COLUMNS: &str = "id, name, task, cron, enabled, catch_up, next_run_at, last_run_at, last_status, last_message, created_at";
+
+/// None for a task written by a newer version.
+fn schedule_from_row(row: &Row) -> rusqlite::Result<Option<Schedule>> {
+    let task: String = row.get(2)?;
+    let Ok(task) = serde_json::from_str(&task) else { return Ok(None) };
+    Ok(Some(Schedule {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        task,
+        cron: row.get(3)?,
+        enabled: row.get(4)?,
+        catch_up: row.get(5)?,
+        next_run_at: row.get(6)?,
+        last_run_at: row.get(7)?,
+        last_status: row.get(8)?,
+        last_message: row.get(9)?,
+        created_at: row.get(10)?,
+    }))
+}
+
+fn load_schedules(app: &AppHandle, due_before: Option<i64>) -> Result<Vec<Schedule>, String> {
+    let conn = db::open_app_db(app)?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM scheduled_tasks
+         WHERE ?1 IS NULL OR (enabled = 1 AND next_run_at IS NOT NULL AND next_run_at <= ?1)
+         ORDER BY next_run_at IS NULL, next_run_at, id",
+        COLUMNS,
+    )).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![due_before], schedule_from_row)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(rows.into_iter().flatten().collect())
+}
+
+fn load_schedule(app: &AppHandle, id: i64) -> Result<Schedule, String> {
+    load_schedules(app, None)?
+        .into_iter()
+        .find(|schedule| schedule.id == id)
+        .ok_or_else(|| format!("Unknown schedule: {}", id))
+}
+
+/// Do the work; Ok carries a one-line summary of what was produced.
+async fn execute(app: &AppHandle, schedule: &Schedule) -> Result<String, String> {
+    match &schedule.task {
+        ScheduledTask::Report { report, document_id, template } => {
+            let job = schedules::run_report(app, &schedule.name, *report, *document_id, template.clone()).await?;
+            match job.status {
+                JobStatus::Completed => Ok(job.file_path),
+                _ => Err(job.error.unwrap_or_else(|| "Report failed".to_string())),
+            }
+        }
+        ScheduledTask::WatchlistRefresh => {
+            watchlist::refresh_all(app, 0).await?;
+            Ok("Watchlist refreshed".to_string())
+        }
+        ScheduledTask::Backup => {
+            let result = backup::backup_to_cloud(app.clone()).await?;
+            Ok(format!("Uploaded {}", result.backup.key))
+        }
+        ScheduledTask::CacheCleanup { max_age_days } => {
+            let max_age = max_age_days.unwrap_or(DEFAULT_CACHE_MAX_AGE_DAYS) as u64 * 86_400;
+            let quotes = app.state::<MarketCache>().prune(app, max_age)?;
+            let parses = parse_cache::prune(app, max_age)?;
+            let metrics = app.state::<MetricCache>().clear();
+            Ok(format!("Removed {} market-data entries, {} cached parses and {} cached metrics", quotes, parses, metrics))
+        }
+    }
+}
+
+/// Run `schedule` now and record the outcome against it.
+async fn run(app: &AppHandle, schedule: &Schedule) -> ScheduleRun {
+    info!(schedule = schedule.id, name = %schedule.name, "Running schedule");
+    let outcome = execute(app, schedule).await;
+    let run = ScheduleRun {
+        schedule_id: schedule.id,
+        name: schedule.name.clone(),
+        success: outcome.is_ok(),
+        message: outcome.unwrap_or_else(|e| e),
+        finished_at: now_secs(),
+    };
+    let recorded = db::open_app_db(app).and_then(|conn| {
+        conn.execute(
+            "UPDATE scheduled_tasks SET last_run_at = ?1, last_status = ?2, last_message = ?3 WHERE id = ?4",
+            params![run.finished_at, if run.success { "completed" } else { "failed" }, run.message, schedule.id],
+        ).map_err(|e| e.to_string())
+    });
+    if let Err(e) = recorded {
+        warn!(schedule = schedule.id, "Failed to record run: {}", e);
+    }
+    if !run.success {
+        notifications::notify_user(app, "Scheduled task failed", format!("{}: {}", schedule.name, run.message));
+    }
+    let _ = app.emit("schedule-run", &run);
+    run
+}
+
+/// Start every due schedule, moving its next run forward first so a slow one can't run twice.
+/// On startup, runs missed while the app was closed collapse into one, or are skipped for
+/// schedules without `catch_up`.
+fn run_due(app: &AppHandle, startup: bool) -> Result<(), String> {
+    let now = now_secs();
+    let due = load_schedules(app, Some(now))?;
+    if due.is_empty() {
+        return Ok(());
+    }
+    let conn = db::open_app_db(app)?;
+    for schedule in due {
+        let next = Cron::parse(&schedule.cron).ok().and_then(|cron| cron.next_after(now));
+        conn.execute("UPDATE scheduled_tasks SET next_run_at = ?1 WHERE id = ?2", params![next, schedule.id])
+            .map_err(|e| e.to_string())?;
+        let missed = schedule.next_run_at.is_some_and(|at| at < now - 2 * CHECK_INTERVAL.as_secs() as i64);
+        if startup && missed && !schedule.catch_up {
+            info!(schedule = schedule.id, name = %schedule.name, "Skipping run missed while closed");
+            continue;
+        }
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            run(&app, &schedule).await;
+        });
+    }
+    Ok(())
+}
+
+pub fn start_scheduler_task(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut startup = true;
+        loop {
+            if let Err(e) = run_due(&app, startup) {
+                warn!("Schedule check failed: {}", e);
+            }
+            startup = false;
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn list_schedules(app: AppHandle) -> Result<Vec<Schedule>, String> {
+    load_schedules(&app, None)
+}
+
+/// `catch_up` defaults to true: a run missed while the app was closed happens on next launch.
+#[tauri::command]
+pub fn create_schedule(
+    app: AppHandle,
+    name: String,
+    task: ScheduledTask,
+    cron: String,
+    catch_up: Option<bool>,
+) -> Result<Schedule, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Schedule name cannot be empty".to_string());
+    }
+    if let ScheduledTask::Report { report: ReportKind::DocumentReport, document_id: None, .. } = task {
+        return Err("A document report schedule needs a document".to_string());
+    }
+    let cron = cron.trim().to_string();
+    let created_at = now_secs();
+    let next_run_at = Cron::parse(&cron)?.next_after(created_at);
+    if next_run_at.is_none() {
+        return Err(format!("'{}' never runs", cron));
+    }
+    let task_json = serde_json::to_string(&task).map_err(|e| e.to_string())?;
+    let catch_up = catch_up.unwrap_or(true);
+
+    let conn = db::open_app_db(&app)?;
+    conn.execute(
+        "INSERT INTO scheduled_tasks (name, task, cron, enabled, catch_up, next_run_at, created_at)
+         VALUES (?1, ?2, ?3, 1, ?4, ?5, ?6)",
+        params![name, task_json, cron, catch_up, next_run_at, created_at],
+    ).map_err(|e| e.to_string())?;
+
+    Ok(Schedule {
+        id: conn.last_insert_rowid(),
+        name,
+        task,
+        cron,
+        enabled: true,
+        catch_up,
+        next_run_at,
+        last_run_at: None,
+        last_status: None,
+        last_message: None,
+        created_at,
+    })
+}
+
+/// Run a schedule immediately, whether or not it's enabled; its next scheduled run is unchanged.
+#[tauri::command]
+pub async fn run_schedule_now(app: AppHandle, id: i64) -> Result<ScheduleRun, String> {
+    let schedule = load_schedule(&app, id)?;
+    Ok(run(&app, &schedule).await)
+}
+
+#[tauri::command]
+pub fn set_schedule_enabled(app: AppHandle, id: i64, enabled: bool) -> Result<Schedule, String> {
+    let schedule = load_schedule(&app, id)?;
+    // Re-enabling shouldn't fire for the runs skipped while disabled
+    let next_run_at = Cron::parse(&schedule.cron)?.next_after(now_secs());
+    let conn = db::open_app_db(&app)?;
+    conn.execute(
+        "UPDATE scheduled_tasks SET enabled = ?1, next_run_at = ?2 WHERE id = ?3",
+        params![enabled, next_run_at, id],
+    ).map_err(|e| e.to_string())?;
+    load_schedule(&app, id)
+}
+
+#[tauri::command]
+pub fn delete_schedule(app: AppHandle, id: i64) -> Result<(), String> {
+    let conn = db::open_app_db(&app)?;
+    conn.execute("DELETE FROM scheduled_tasks WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,318 @@
+// Report Schedules - regenerate reports at a fixed local time, daily or weekly
+use chrono::{Datelike, Duration as ChronoDuration, Local, NaiveTime, TimeZone};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+use crate::db;
+use crate::jobs::{self, DocumentKind, Job, JobSource, JobStatus};
+use crate::notifications;
+use crate::report;
+use crate::report_templates::TemplateRef;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS report_schedules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    kind TEXT NOT NULL,            -- 'watchlist_summary', 'document_report'
+    document_id INTEGER,
+    template TEXT,
+    weekday INTEGER,               -- 0 = Monday .. 6 = Sunday; NULL runs daily
+    hour INTEGER NOT NULL,
+    minute INTEGER NOT NULL,
+    enabled INTEGER NOT NULL DEFAULT 1,
+    next_run_at INTEGER NOT NULL,
+    last_run_at INTEGER,
+    last_status TEXT,
+    last_output TEXT,
+    created_at INTEGER NOT NULL
+);
+";
+
+// Due schedules are picked up within this long of their time
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportKind {
+    WatchlistSummary,
+    DocumentReport,
+}
+
+impl ReportKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ReportKind::WatchlistSummary => "watchlist_summary",
+            ReportKind::DocumentReport => "document_report",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "watchlist_summary" => Some(ReportKind::WatchlistSummary),
+            "document_report" => Some(ReportKind::DocumentReport),
+            _ => None,
+        }
+    }
+}
+
+/// When a schedule fires, in local time.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScheduleTime {
+    /// 0 = Monday .. 6 = Sunday; `None` runs every day
+    pub weekday: Option<u8>,
+    pub hour: u8,
+    pub minute: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReportSchedule {
+    pub id: i64,
+    pub name: String,
+    pub kind: ReportKind,
+    pub document_id: Option<i64>,
+    pub template: Option<String>,
+    /// 0 = Monday .. 6 = Sunday; `None` runs every day
+    pub weekday: Option<u8>,
+    pub hour: u8,
+    pub minute: u8,
+    pub enabled: bool,
+    pub next_run_at: i64,
+    pub last_run_at: Option<i64>,
+    pub last_status: Option<String>,
+    pub last_output: Option<String>,
+    pub created_at: i64,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// The first matching local time strictly after `after`.
+fn next_run(weekday: Option<u8>, hour: u8, minute: u8, after: i64) -> i64 {
+    let after_local = Local.timestamp_opt(after, 0).single().unwrap_or_else(Local::now);
+    let time = NaiveTime::from_hms_opt(hour as u32, minute as u32, 0).unwrap_or_default();
+    // Eight days covers every weekday plus today's slot having already passed
+    (0..8)
+        .filter_map(|offset| {
+            let date = after_local.date_naive() + ChronoDuration::days(offset);
+            if weekday.is_some_and(|w| date.weekday().num_days_from_monday() != w as u32) {
+                return None;
+            }
+            // Skips times that don't exist on DST change days
+            Local.from_local_datetime(&date.and_time(time)).earliest()
+        })
+        .map(|at| at.timestamp())
+        .find(|at| *at > after)
+        .unwrap_or(after + 86_400)
+}
+
+fn load_schedules(app: &AppHandle, due_before: Option<i64>) -> Result<Vec<ReportSchedule>, String> {
+    let conn = db::open_app_db(app)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, name, kind, document_id, template, weekday, hour, minute, enabled, next_run_at,
+                last_run_at, last_status, last_output, created_at
+         FROM report_schedules
+         WHERE ?1 IS NULL OR (enabled = 1 AND next_run_at <= ?1)
+         ORDER BY next_run_at ASC"
+    ).map_err(|e| e.to_string())?;
+
+    let rows = stmt.query_map(params![due_before], |row| {
+        let kind: String = row.get(2)?;
+        Ok((kind, ReportSchedule {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            kind: ReportKind::WatchlistSummary,
+            document_id: row.get(3)?,
+            template: row.get(4)?,
+            weekday: row.get(5)?,
+            hour: row.get(6)?,
+            minute: row.get(7)?,
+            enabled: row.get(8)?,
+            next_run_at: row.get(9)?,
+            last_run_at: row.get(10)?,
+            last_status: row.get(11)?,
+            last_output: row.get(12)?,
+            created_at: row.get(13)?,
+        }))
+    }).map_err(|e| e.to_string())?;
+
+    let mut schedules = Vec::new();
+    for row in rows {
+        let (kind, mut schedule) = row.map_err(|e| e.to_string())?;
+        if let Some(kind) = ReportKind::parse(&kind) {
+            schedule.kind = kind;
+            schedules.push(schedule);
+        }
+    }
+    Ok(schedules)
+}
+
+/// Render a report as a tracked job; the job records whether it worked and where it went.
+pub async fn run_report(
+    app: &AppHandle,
+    name: &str,
+    kind: ReportKind,
+    document_id: Option<i64>,
+    template: Option<TemplateRef>,
+) -> Result<Job, String> {
+    let render_app = app.clone();
+    let work = async move {
+        tauri::async_runtime::spawn_blocking(move || match kind {
+            ReportKind::WatchlistSummary => report::render_watchlist_summary(&render_app),
+            ReportKind::DocumentReport => {
+                let document_id = document_id.ok_or("Schedule has no document")?;
+                report::render_document_report(&render_app, document_id, template.as_ref())
+            }
+        }).await.map_err(|e| e.to_string())?
+    };
+    jobs::run_tracked(app, name, DocumentKind::Report, JobSource::Schedule, work).await
+}
+
+async fn run_schedule(app: AppHandle, schedule: ReportSchedule) {
+    let template = schedule.template.as_deref().map(TemplateRef::from_stored);
+    let job = match run_report(&app, &schedule.name, schedule.kind, schedule.document_id, template).await {
+        Ok(job) => job,
+        Err(e) => {
+            eprintln!("[Schedules] Could not run '{}': {}", schedule.name, e);
+            return;
+        }
+    };
+    let (status, output, body) = match job.status {
+        JobStatus::Completed => ("completed", Some(job.file_path.clone()), format!("{} is ready", schedule.name)),
+        _ => {
+            let error = job.error.clone().unwrap_or_default();
+            ("failed", None, format!("{} failed: {}", schedule.name, error))
+        }
+    };
+
+    let recorded = db::open_app_db(&app).and_then(|conn| {
+        conn.execute(
+            "UPDATE report_schedules SET last_run_at = ?1, last_status = ?2, last_output = COALESCE(?3, last_output) WHERE id = ?4",
+            params![job.finished_at.unwrap_or_else(now_secs), status, output, schedule.id],
+        ).map_err(|e| e.to_string())
+    });
+    if let Err(e) = recorded {
+        eprintln!("[Schedules] Failed to record run of '{}': {}", schedule.name, e);
+    }
+
+    notifications::notify_user(&app, "Scheduled report", body);
+    let _ = app.emit("scheduled-report-completed", &job);
+}
+
+/// Start every due schedule, moving its next run forward first so a slow report can't run twice.
+fn run_due(app: &AppHandle) -> Result<(), String> {
+    let now = now_secs();
+    let due = load_schedules(app, Some(now))?;
+    if due.is_empty() {
+        return Ok(());
+    }
+    let conn = db::open_app_db(app)?;
+    for schedule in due {
+        // Missed runs while the app was closed collapse into this one
+        let next = next_run(schedule.weekday, schedule.hour, schedule.minute, now);
+        conn.execute(
+            "UPDATE report_schedules SET next_run_at = ?1 WHERE id = ?2",
+            params![next, schedule.id],
+        ).map_err(|e| e.to_string())?;
+        eprintln!("[Schedules] Running '{}'", schedule.name);
+        tauri::async_runtime::spawn(run_schedule(app.clone(), schedule));
+    }
+    Ok(())
+}
+
+pub fn start_schedule_task(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            if let Err(e) = run_due(&app) {
+                eprintln!("[Schedules] Check failed: {}", e);
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn create_report_schedule(
+    app: AppHandle,
+    name: String,
+    kind: ReportKind,
+    document_id: Option<i64>,
+    template: Option<TemplateRef>,
+    at: ScheduleTime,
+) -> Result<ReportSchedule, String> {
+    let template = template.map(|t| t.to_stored());
+    let ScheduleTime { weekday, hour, minute } = at;
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("Schedule name cannot be empty".to_string());
+    }
+    if kind == ReportKind::DocumentReport && document_id.is_none() {
+        return Err("A document report schedule needs a document".to_string());
+    }
+    if weekday.is_some_and(|w| w > 6) || hour > 23 || minute > 59 {
+        return Err("Invalid schedule time".to_string());
+    }
+
+    let created_at = now_secs();
+    let next_run_at = next_run(weekday, hour, minute, created_at);
+    let conn = db::open_app_db(&app)?;
+    conn.execute(
+        "INSERT INTO report_schedules (name, kind, document_id, template, weekday, hour, minute, enabled, next_run_at, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 1, ?8, ?9)",
+        params![name, kind.as_str(), document_id, template, weekday, hour, minute, next_run_at, created_at],
+    ).map_err(|e| e.to_string())?;
+
+    Ok(ReportSchedule {
+        id: conn.last_insert_rowid(),
+        name,
+        kind,
+        document_id,
+        template,
+        weekday,
+        hour,
+        minute,
+        enabled: true,
+        next_run_at,
+        last_run_at: None,
+        last_status: None,
+        last_output: None,
+        created_at,
+    })
+}
+
+#[tauri::command]
+pub fn list_report_schedules(app: AppHandle) -> Result<Vec<ReportSchedule>, String> {
+    load_schedules(&app, None)
+}
+
+#[tauri::command]
+pub fn set_report_schedule_enabled(app: AppHandle, id: i64, enabled: bool) -> Result<(), String> {
+    let conn = db::open_app_db(&app)?;
+    let schedule = load_schedules(&app, None)?
+        .into_iter()
+        .find(|s| s.id == id)
+        .ok_or_else(|| format!("Unknown schedule: {}", id))?;
+    // Re-enabling shouldn't fire immediately for the runs skipped while disabled
+    let next_run_at = next_run(schedule.weekday, schedule.hour, schedule.minute, now_secs());
+    conn.execute(
+        "UPDATE report_schedules SET enabled = ?1, next_run_at = ?2 WHERE id = ?3",
+        params![enabled, next_run_at, id],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_report_schedule(app: AppHandle, id: i64) -> Result<(), String> {
+    let conn = db::open_app_db(&app)?;
+    conn.execute("DELETE FROM report_schedules WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
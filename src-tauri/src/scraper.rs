@@ -0,0 +1,708 @@
+// Native Scraper - NSE/BSE market data fetched directly from Rust
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tokio::sync::{Mutex, Semaphore};
+
+use crate::http;
+use crate::market_cache::MarketCache;
+use crate::market_providers;
+use crate::scraper_usage::{self, UsageEvent};
+use crate::settings::{self, SettingsState};
+use crate::timeouts::{self, Operation};
+
+pub const NSE_BASE: &str = "https://www.nseindia.com";
+const BSE_API_BASE: &str = "https://api.bseindia.com/BseIndiaAPI/api";
+const YAHOO_CHART_BASE: &str = "https://query1.finance.yahoo.com/v8/finance/chart";
+const YAHOO_TIMESERIES_BASE: &str = "https://query2.finance.yahoo.com/ws/fundamentals-timeseries/v1/finance/timeseries";
+// SEC's fair-access policy requires a descriptive User-Agent instead of a browser one
+const SEC_USER_AGENT: &str = "FinancialCalculator/1.0 (desktop app; github.com/nikhil-bhavsar1/Financial-Calculator)";
+// Rotated when a host starts refusing us (403/429/503)
+const BROWSER_UAS: &[&str] = &[
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0 Safari/537.36",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0 Safari/537.36",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:125.0) Gecko/20100101 Firefox/125.0",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 14_4) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.4 Safari/605.1.15",
+    "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0 Safari/537.36",
+];
+
+// Retries on 429/503 before giving up, with exponential backoff plus jitter
+const MAX_RETRIES: u32 = 3;
+const BACKOFF_BASE_MS: u64 = 1000;
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+// Upper bound on simultaneous requests for bulk quote lookups
+pub(crate) const MAX_CONCURRENT_QUOTES: usize = 6;
+
+// NSE hands out short-lived session cookies from its homepage; re-prime well before they lapse
+const NSE_SESSION_TTL: Duration = Duration::from_secs(240);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Quote {
+    pub symbol: String,
+    pub company_name: Option<String>,
+    pub price: Option<f64>,
+    pub change: Option<f64>,
+    pub change_percent: Option<f64>,
+    pub open: Option<f64>,
+    pub high: Option<f64>,
+    pub low: Option<f64>,
+    pub previous_close: Option<f64>,
+    pub volume: Option<f64>,
+    pub exchange: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Candle {
+    pub date: String,       // YYYY-MM-DD (exchange-local trading day)
+    pub timestamp: i64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: Option<f64>,
+}
+
+/// Exchange APIs return numbers either as JSON numbers or as strings like "1,234.50".
+pub fn parse_number(value: &serde_json::Value) -> Option<f64> {
+    match value {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => s.replace(',', "").trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Per-host request counters, surfaced through `get_scraper_status`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostStats {
+    pub requests: u64,
+    pub successes: u64,
+    pub failures: u64,
+    pub retries: u64,
+    pub rate_limited: u64,  // 429/503 responses
+    pub throttled: u64,     // requests delayed by our own per-minute limit
+    pub last_status: Option<u16>,
+    pub last_error: Option<String>,
+    pub last_request_at: Option<i64>,
+}
+
+#[derive(Default)]
+struct HostState {
+    window: VecDeque<Instant>,  // send times within the last minute
+    stats: HostStats,
+}
+
+pub struct NativeScraper {
+    app: AppHandle,
+    // When the NSE cookies were last primed
+    nse_session: Mutex<Option<Instant>>,
+    hosts: std::sync::Mutex<HashMap<String, HostState>>,
+    user_agent: AtomicUsize,
+}
+
+fn host_of(url: &str) -> String {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_string()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn unix_now() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Exponential backoff with up to 50% jitter, or the server's Retry-After when it sends one.
+fn backoff(attempt: u32, retry_after: Option<u64>) -> Duration {
+    if let Some(secs) = retry_after {
+        return Duration::from_secs(secs).min(MAX_BACKOFF);
+    }
+    let base = BACKOFF_BASE_MS.saturating_mul(1 << attempt);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos() as u64)
+        .unwrap_or(0);
+    let jitter = nanos % (base / 2 + 1);
+    Duration::from_millis(base + jitter).min(MAX_BACKOFF)
+}
+
+impl NativeScraper {
+    pub fn new(app: AppHandle) -> Self {
+        Self {
+            app,
+            nse_session: Mutex::new(None),
+            hosts: std::sync::Mutex::new(HashMap::new()),
+            user_agent: AtomicUsize::new(0),
+        }
+    }
+
+    /// The shared client; it keeps the NSE session cookies along with everything else.
+    fn client(&self) -> Client {
+        http::client(&self.app)
+    }
+
+    fn rate_limit_per_minute(&self) -> usize {
+        let state = self.app.state::<SettingsState>();
+        let limit = settings::blocking_read(&state).get().scraper.rate_limit_per_minute;
+        limit as usize
+    }
+
+    fn with_host<T>(&self, host: &str, f: impl FnOnce(&mut HostState) -> T) -> T {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        f(hosts.entry(host.to_string()).or_default())
+    }
+
+    /// Wait until `host` is under the per-minute limit (0 disables limiting), then claim a slot.
+    async fn throttle(&self, host: &str) {
+        let limit = self.rate_limit_per_minute();
+        let mut counted = false;
+        loop {
+            let wait = self.with_host(host, |state| {
+                let now = Instant::now();
+                while state.window.front().is_some_and(|t| now.duration_since(*t) >= Duration::from_secs(60)) {
+                    state.window.pop_front();
+                }
+                if limit == 0 || state.window.len() < limit {
+                    state.window.push_back(now);
+                    return None;
+                }
+                if !counted {
+                    state.stats.throttled += 1;
+                }
+                state.window.front().map(|oldest| Duration::from_secs(60).saturating_sub(now.duration_since(*oldest)))
+            });
+            match wait {
+                None => return,
+                Some(wait) => {
+                    if !counted {
+                        scraper_usage::record(&self.app, host, UsageEvent::Throttled, None, None);
+                    }
+                    counted = true;
+                    tokio::time::sleep(wait.max(Duration::from_millis(50))).await;
+                }
+            }
+        }
+    }
+
+    /// Every native request goes through here: per-host throttling, retries with
+    /// jittered backoff on 429/503, and user-agent rotation when a host pushes back.
+    /// `build` gets the user agent to send and returns the request to execute.
+    async fn send<F>(&self, url: &str, build: F) -> Result<reqwest::Response, String>
+    where
+        F: Fn(&str) -> reqwest::RequestBuilder,
+    {
+        // Commands check offline mode up front; this catches anything that slips past
+        settings::ensure_online(&self.app)?;
+
+        let host = host_of(url);
+        scraper_usage::check_quota(&self.app, &host)?;
+        let mut attempt = 0;
+        loop {
+            self.throttle(&host).await;
+            let ua = BROWSER_UAS[self.user_agent.load(Ordering::Relaxed) % BROWSER_UAS.len()];
+            self.with_host(&host, |state| {
+                state.stats.requests += 1;
+                state.stats.last_request_at = Some(unix_now());
+            });
+            scraper_usage::record(&self.app, &host, UsageEvent::Request, None, None);
+
+            let res = match build(ua).send().await {
+                Ok(res) => res,
+                Err(e) => {
+                    self.with_host(&host, |state| {
+                        state.stats.failures += 1;
+                        state.stats.last_error = Some(e.to_string());
+                    });
+                    scraper_usage::record(&self.app, &host, UsageEvent::Failure, None, Some(&e.to_string()));
+                    return Err(e.to_string());
+                }
+            };
+
+            let status = res.status().as_u16();
+            let pushed_back = matches!(status, 403 | 429 | 503);
+            if pushed_back {
+                self.user_agent.fetch_add(1, Ordering::Relaxed);
+            }
+            if status == 403 {
+                scraper_usage::record(&self.app, &host, UsageEvent::Banned, Some(status), None);
+            }
+
+            let retryable = matches!(status, 429 | 503);
+            if retryable && attempt < MAX_RETRIES {
+                let retry_after = res.headers()
+                    .get(reqwest::header::RETRY_AFTER)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.trim().parse::<u64>().ok());
+                let delay = backoff(attempt, retry_after);
+                self.with_host(&host, |state| {
+                    state.stats.rate_limited += 1;
+                    state.stats.retries += 1;
+                    state.stats.last_status = Some(status);
+                });
+                scraper_usage::record(&self.app, &host, UsageEvent::RateLimited, Some(status), None);
+                scraper_usage::record(&self.app, &host, UsageEvent::Retry, Some(status), None);
+                eprintln!("[Scraper] {} returned {}, retrying in {:?}", host, status, delay);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+                continue;
+            }
+
+            self.with_host(&host, |state| {
+                state.stats.last_status = Some(status);
+                if res.status().is_success() {
+                    state.stats.successes += 1;
+                } else {
+                    state.stats.failures += 1;
+                    state.stats.rate_limited += u64::from(retryable);
+                    state.stats.last_error = Some(format!("HTTP {}", status));
+                }
+            });
+            match (res.status().is_success(), retryable) {
+                (true, _) => scraper_usage::record(&self.app, &host, UsageEvent::Success, Some(status), None),
+                (false, true) => {
+                    scraper_usage::record(&self.app, &host, UsageEvent::RateLimited, Some(status), None);
+                    scraper_usage::record(&self.app, &host, UsageEvent::Failure, Some(status), Some(&format!("HTTP {}", status)));
+                }
+                (false, false) => scraper_usage::record(&self.app, &host, UsageEvent::Failure, Some(status), Some(&format!("HTTP {}", status))),
+            }
+            return Ok(res);
+        }
+    }
+
+    /// Counters for every host contacted this session, and the persisted daily ones with quotas.
+    pub fn status(&self) -> serde_json::Value {
+        let daily = scraper_usage::history(&self.app).unwrap_or_else(|e| {
+            eprintln!("[Scraper] Failed to load usage history: {}", e);
+            Vec::new()
+        });
+        let hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        let stats: HashMap<&String, &HostStats> = hosts.iter().map(|(host, state)| (host, &state.stats)).collect();
+        serde_json::json!({
+            "rateLimitPerMinute": self.rate_limit_per_minute(),
+            "userAgentIndex": self.user_agent.load(Ordering::Relaxed) % BROWSER_UAS.len(),
+            "hosts": stats,
+            "daily": daily,
+        })
+    }
+
+    pub async fn fetch_quote(&self, symbol: &str, exchange: &str, timeout: Duration) -> Result<Quote, String> {
+        match exchange.to_uppercase().as_str() {
+            "NSE" => self.fetch_nse_quote(symbol, timeout).await,
+            "BSE" => self.fetch_bse_quote(symbol, timeout).await,
+            other => Err(format!("Native quotes not supported for exchange: {}", other)),
+        }
+    }
+
+    /// Daily/weekly/monthly candles from Yahoo's chart API, which covers both exchanges
+    /// (`.NS` tickers for NSE, `.BO` for BSE symbols or scrip codes).
+    /// `range` and `interval` use Yahoo's vocabulary ("1y", "1d", "1wk", ...).
+    pub async fn fetch_history(&self, symbol: &str, exchange: &str, range: &str, interval: &str, timeout: Duration) -> Result<Vec<Candle>, String> {
+        let ticker = yahoo_ticker(symbol, exchange)?;
+        self.yahoo_candles(&ticker, range, interval, timeout).await
+    }
+
+    /// Candles for a raw Yahoo ticker, any exchange.
+    pub async fn yahoo_candles(&self, ticker: &str, range: &str, interval: &str, timeout: Duration) -> Result<Vec<Candle>, String> {
+        let result = self.yahoo_chart(ticker, range, interval, timeout).await?;
+        // Dates are reported in exchange time (IST), not UTC
+        let gmt_offset = result.pointer("/meta/gmtoffset").and_then(|v| v.as_i64()).unwrap_or(19800);
+        let timestamps = result.get("timestamp").and_then(|t| t.as_array()).cloned().unwrap_or_default();
+        let series = result.pointer("/indicators/quote/0");
+        let column = |name: &str, i: usize| series
+            .and_then(|s| s.get(name))
+            .and_then(|c| c.get(i))
+            .and_then(|v| v.as_f64());
+
+        let mut candles = Vec::with_capacity(timestamps.len());
+        for (i, ts) in timestamps.iter().enumerate() {
+            let Some(ts) = ts.as_i64() else { continue };
+            // Yahoo pads non-trading slots with nulls
+            let (Some(open), Some(high), Some(low), Some(close)) =
+                (column("open", i), column("high", i), column("low", i), column("close", i)) else { continue };
+
+            let date = chrono::DateTime::from_timestamp(ts + gmt_offset, 0)
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_default();
+            candles.push(Candle { date, timestamp: ts, open, high, low, close, volume: column("volume", i) });
+        }
+        Ok(candles)
+    }
+
+    /// First `chart.result` entry for a raw Yahoo ticker (e.g. "RELIANCE.NS", "^BSESN").
+    pub async fn yahoo_chart(&self, ticker: &str, range: &str, interval: &str, timeout: Duration) -> Result<serde_json::Value, String> {
+        let url = format!("{}/{}?range={}&interval={}", YAHOO_CHART_BASE, url_encode(ticker), range, interval);
+
+        let mut data = self.send(&url, |ua| self.client().get(&url)
+                .header("User-Agent", ua)
+                .header("Accept", "application/json")
+                .timeout(timeout))
+            .await
+            .map_err(|e| format!("Chart request failed: {}", e))?
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| format!("Invalid chart response: {}", e))?;
+
+        if let Some(err) = data.pointer("/chart/error").filter(|e| !e.is_null()) {
+            let description = err.get("description").and_then(|d| d.as_str()).unwrap_or("unknown error");
+            return Err(format!("No chart data for {}: {}", ticker, description));
+        }
+
+        data.pointer_mut("/chart/result/0")
+            .map(serde_json::Value::take)
+            .ok_or_else(|| format!("No chart data for {}", ticker))
+    }
+
+    /// Fundamentals time series (e.g. "annualTotalRevenue") for a raw Yahoo ticker.
+    /// Returns one `timeseries.result` entry per requested type that has data.
+    pub async fn yahoo_timeseries(&self, ticker: &str, types: &[String], timeout: Duration) -> Result<Vec<serde_json::Value>, String> {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let url = format!(
+            "{}/{}?symbol={}&type={}&period1=493590046&period2={}",
+            YAHOO_TIMESERIES_BASE, url_encode(ticker), url_encode(ticker), types.join(","), now
+        );
+
+        let data = self.send(&url, |ua| self.client().get(&url)
+                .header("User-Agent", ua)
+                .header("Accept", "application/json")
+                .timeout(timeout))
+            .await
+            .map_err(|e| format!("Fundamentals request failed: {}", e))?
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| format!("Invalid fundamentals response: {}", e))?;
+
+        if let Some(err) = data.pointer("/timeseries/error").filter(|e| !e.is_null()) {
+            let description = err.get("description").and_then(|d| d.as_str()).unwrap_or("unknown error");
+            return Err(format!("No fundamentals for {}: {}", ticker, description));
+        }
+
+        Ok(data.pointer("/timeseries/result")
+            .and_then(|r| r.as_array())
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    /// JSON from sec.gov / data.sec.gov.
+    pub async fn sec_get_json(&self, url: &str, timeout: Duration) -> Result<serde_json::Value, String> {
+        // SEC wants the same declared agent on every request, so no rotation here
+        let res = self.send(url, |_| self.client().get(url)
+                .header("User-Agent", SEC_USER_AGENT)
+                .header("Accept", "application/json")
+                .timeout(timeout))
+            .await
+            .map_err(|e| format!("SEC request failed: {}", e))?;
+        if !res.status().is_success() {
+            return Err(format!("SEC returned HTTP {}", res.status()));
+        }
+        res.json::<serde_json::Value>()
+            .await
+            .map_err(|e| format!("Invalid SEC response: {}", e))
+    }
+
+    /// JSON from a keyed market-data API (e.g. Alpha Vantage), throttled like everything else.
+    pub async fn get_json(&self, url: &str, timeout: Duration) -> Result<serde_json::Value, String> {
+        let res = self.send(url, |ua| self.client().get(url)
+                .header("User-Agent", ua)
+                .header("Accept", "application/json")
+                .timeout(timeout))
+            .await
+            .map_err(|e| format!("Request to {} failed: {}", host_of(url), e))?;
+        if !res.status().is_success() {
+            return Err(format!("{} returned HTTP {}", host_of(url), res.status()));
+        }
+        res.json::<serde_json::Value>()
+            .await
+            .map_err(|e| format!("Invalid response from {}: {}", host_of(url), e))
+    }
+
+    /// Plain-text download (e.g. the AMFI NAV feed).
+    pub async fn get_text(&self, url: &str, timeout: Duration) -> Result<String, String> {
+        let res = self.send(url, |ua| self.client().get(url)
+                .header("User-Agent", ua)
+                .timeout(timeout))
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+        if !res.status().is_success() {
+            return Err(format!("{} returned HTTP {}", url, res.status()));
+        }
+        res.text().await.map_err(|e| format!("Failed to read response: {}", e))
+    }
+
+    /// NSE rejects API calls that don't carry the cookies set by a normal page visit.
+    async fn ensure_nse_session(&self, timeout: Duration, force: bool) -> Result<(), String> {
+        let mut primed = self.nse_session.lock().await;
+        let fresh = primed.is_some_and(|t| t.elapsed() < NSE_SESSION_TTL);
+        if fresh && !force {
+            return Ok(());
+        }
+
+        self.send(NSE_BASE, |ua| self.client().get(NSE_BASE)
+                .header("User-Agent", ua)
+                .header("Accept", "text/html,application/xhtml+xml")
+                .header("Accept-Language", "en-US,en;q=0.9")
+                .timeout(timeout))
+            .await
+            .map_err(|e| format!("Failed to open NSE session: {}", e))?;
+
+        *primed = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Open the sessions later requests depend on ahead of time.
+    pub async fn prime_sessions(&self, timeout: Duration) -> Result<(), String> {
+        self.ensure_nse_session(timeout, false).await
+    }
+
+    pub async fn nse_get_json(&self, path: &str, referer: &str, timeout: Duration) -> Result<serde_json::Value, String> {
+        self.ensure_nse_session(timeout, false).await?;
+
+        let url = format!("{}{}", NSE_BASE, path);
+        let mut retried = false;
+        loop {
+            let res = self.send(&url, |ua| self.client().get(&url)
+                    .header("User-Agent", ua)
+                    .header("Accept", "application/json, text/plain, */*")
+                    .header("Accept-Language", "en-US,en;q=0.9")
+                    .header("Referer", referer)
+                    .timeout(timeout))
+                .await
+                .map_err(|e| format!("NSE request failed: {}", e))?;
+
+            let status = res.status();
+            // Expired cookies surface as 401/403 - re-prime once and retry
+            if (status.as_u16() == 401 || status.as_u16() == 403) && !retried {
+                retried = true;
+                self.ensure_nse_session(timeout, true).await?;
+                continue;
+            }
+            if !status.is_success() {
+                return Err(format!("NSE returned HTTP {}", status));
+            }
+
+            return res.json::<serde_json::Value>()
+                .await
+                .map_err(|e| format!("Invalid NSE response: {}", e));
+        }
+    }
+
+    /// BSE's API only answers requests that look like they come from bseindia.com.
+    pub async fn bse_get_json(&self, path: &str, timeout: Duration) -> Result<serde_json::Value, String> {
+        let url = format!("{}{}", BSE_API_BASE, path);
+        self.send(&url, |ua| self.client().get(&url)
+                .header("User-Agent", ua)
+                .header("Accept", "application/json, text/plain, */*")
+                .header("Referer", "https://www.bseindia.com/")
+                .header("Origin", "https://www.bseindia.com")
+                .timeout(timeout))
+            .await
+            .map_err(|e| format!("BSE request failed: {}", e))?
+            .json::<serde_json::Value>()
+            .await
+            .map_err(|e| format!("Invalid BSE response: {}", e))
+    }
+
+    async fn fetch_nse_quote(&self, symbol: &str, timeout: Duration) -> Result<Quote, String> {
+        let symbol = symbol.trim().to_uppercase();
+        let encoded: String = url_encode(&symbol);
+        let referer = format!("{}/get-quotes/equity?symbol={}", NSE_BASE, encoded);
+        let data = self.nse_get_json(&format!("/api/quote-equity?symbol={}", encoded), &referer, timeout).await?;
+
+        let price_info = data.get("priceInfo")
+            .ok_or_else(|| format!("No price data for {} on NSE", symbol))?;
+        let day_range = price_info.get("intraDayHighLow");
+        // The day's traded volume is only in the trade-info section; `preOpenMarket` carries the
+        // pre-open auction's quantity. A failure there costs the volume, not the quote.
+        let trade_info = self.nse_get_json(&format!("/api/quote-equity?symbol={}&section=trade_info", encoded), &referer, timeout).await;
+        let volume = match &trade_info {
+            Ok(trade_info) => trade_info.pointer("/securityWiseDP/quantityTraded").and_then(parse_number)
+                // tradeInfo reports it in lakhs of shares
+                .or_else(|| trade_info.pointer("/marketDeptOrderBook/tradeInfo/totalTradedVolume").and_then(parse_number).map(|lakhs| lakhs * 100_000.0)),
+            Err(e) => {
+                eprintln!("[Scraper] No NSE trade info for {}: {}", symbol, e);
+                None
+            }
+        };
+
+        Ok(Quote {
+            symbol: symbol.clone(),
+            company_name: data.pointer("/info/companyName").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            price: price_info.get("lastPrice").and_then(parse_number),
+            change: price_info.get("change").and_then(parse_number),
+            change_percent: price_info.get("pChange").and_then(parse_number),
+            open: price_info.get("open").and_then(parse_number),
+            high: day_range.and_then(|r| r.get("max")).and_then(parse_number),
+            low: day_range.and_then(|r| r.get("min")).and_then(parse_number),
+            previous_close: price_info.get("previousClose").and_then(parse_number),
+            volume,
+            exchange: "NSE".to_string(),
+        })
+    }
+
+    /// BSE's public API is keyed by numeric scrip code (e.g. 500325), not ticker.
+    async fn fetch_bse_quote(&self, scrip_code: &str, timeout: Duration) -> Result<Quote, String> {
+        let code = scrip_code.trim();
+        if code.is_empty() || !code.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("BSE quotes need a numeric scrip code, got: {}", code));
+        }
+
+        let data = self.bse_get_json(&format!("/getScripHeaderData/w?Debtflag=&scripcode={}&seriesid=", code), timeout).await?;
+
+        let header = data.get("Header")
+            .filter(|h| !h.is_null())
+            .ok_or_else(|| format!("No price data for scrip {} on BSE", code))?;
+        let current = data.get("CurrRate");
+
+        Ok(Quote {
+            symbol: code.to_string(),
+            company_name: data.pointer("/Cmpname/FullN").and_then(|v| v.as_str()).map(|s| s.to_string()),
+            price: current.and_then(|c| c.get("LTP")).and_then(parse_number)
+                .or_else(|| header.get("LTP").and_then(parse_number)),
+            change: current.and_then(|c| c.get("Chg")).and_then(parse_number),
+            change_percent: current.and_then(|c| c.get("PcChg")).and_then(parse_number),
+            open: header.get("Open").and_then(parse_number),
+            high: header.get("High").and_then(parse_number),
+            low: header.get("Low").and_then(parse_number),
+            previous_close: header.get("PrevClose").and_then(parse_number),
+            volume: None,
+            exchange: "BSE".to_string(),
+        })
+    }
+}
+
+/// Yahoo ticker for an Indian listing: `.NS` for NSE symbols, `.BO` for BSE symbols or scrip codes.
+pub fn yahoo_ticker(symbol: &str, exchange: &str) -> Result<String, String> {
+    let suffix = match exchange.trim().to_uppercase().as_str() {
+        "NSE" => ".NS",
+        "BSE" => ".BO",
+        other => return Err(format!("Yahoo data not supported for exchange: {}", other)),
+    };
+    Ok(format!("{}{}", symbol.trim().to_uppercase(), suffix))
+}
+
+/// Percent-encode a symbol for use in a query string (NSE symbols contain '&', e.g. M&M).
+pub fn url_encode(value: &str) -> String {
+    value.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Per-request timeout for native scraper calls, from the scraper settings.
+pub fn request_timeout(app: &AppHandle) -> Duration {
+    Duration::from_secs(timeouts::limit_secs(app, Operation::ScraperRequest))
+}
+
+/// Cache payload shape shared with `get_stock_quote`; `source` names the market-data provider.
+pub fn quote_payload(quote: &Quote, source: &str) -> serde_json::Value {
+    serde_json::json!({
+        "success": true,
+        "quote": quote,
+        "source": source,
+    })
+}
+
+/// Quote for background tasks: served from the market cache when younger than
+/// `max_age_secs`, otherwise fetched from the configured provider and written back to the cache.
+pub async fn get_quote(app: &AppHandle, symbol: &str, exchange: &str, max_age_secs: u64) -> Result<Quote, String> {
+    let cache = app.state::<MarketCache>();
+    if let Some(payload) = cache.get(app, "QUOTE", symbol, exchange, max_age_secs) {
+        if let Some(quote) = payload.get("quote").and_then(|q| serde_json::from_value::<Quote>(q.clone()).ok()) {
+            return Ok(quote);
+        }
+    }
+
+    settings::ensure_online(app)?;
+    let provider = market_providers::resolve(app, None);
+    let quote = market_providers::fetch_quote(app, provider, symbol, exchange, request_timeout(app)).await?;
+    cache.put(app, "QUOTE", symbol, exchange, &quote_payload(&quote, provider.as_str()));
+    Ok(quote)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkQuoteResult {
+    pub symbol: String,
+    pub exchange: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote: Option<Quote>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+// Symbols per `quote_list` call; more than this is a bulk job, not an API call
+pub const MAX_QUOTE_LIST: usize = 50;
+
+/// Quotes for several symbols on one exchange, one at a time, honouring the cache TTL.
+/// For the local API and MCP tools, where a failed symbol is reported rather than fatal.
+pub async fn quote_list(app: &AppHandle, symbols: Vec<String>, exchange: &str) -> Vec<BulkQuoteResult> {
+    let max_age = {
+        let state = app.state::<SettingsState>();
+        let max_age = state.read().await.get().scraper.cache_ttl_secs;
+        max_age
+    };
+    let mut results = Vec::with_capacity(symbols.len());
+    for symbol in symbols {
+        let result = get_quote(app, &symbol, exchange, max_age).await;
+        results.push(BulkQuoteResult {
+            symbol,
+            exchange: exchange.to_string(),
+            error: result.as_ref().err().cloned(),
+            quote: result.ok(),
+        });
+    }
+    results
+}
+
+// Tauri Commands
+#[tauri::command]
+pub async fn get_stock_quotes(
+    app: AppHandle,
+    settings: tauri::State<'_, SettingsState>,
+    symbols: Vec<(String, String)>,
+    force_refresh: Option<bool>,
+) -> Result<Vec<BulkQuoteResult>, String> {
+    let max_age = if force_refresh.unwrap_or(false) {
+        0
+    } else {
+        let store = settings.read().await;
+        store.get().scraper.cache_ttl_secs
+    };
+
+    let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_QUOTES));
+    let mut handles = Vec::with_capacity(symbols.len());
+
+    for (symbol, exchange) in symbols {
+        let app = app.clone();
+        let permits = permits.clone();
+        handles.push(tauri::async_runtime::spawn(async move {
+            let _permit = permits.acquire_owned().await;
+            let result = get_quote(&app, &symbol, &exchange, max_age).await;
+            BulkQuoteResult {
+                symbol,
+                exchange,
+                error: result.as_ref().err().cloned(),
+                quote: result.ok(),
+            }
+        }));
+    }
+
+    // Join in input order; one symbol failing never fails the batch
+    let mut results = Vec::with_capacity(handles.len());
+    for handle in handles {
+        results.push(handle.await.map_err(|e| e.to_string())?);
+    }
+    Ok(results)
+}
@@ -0,0 +1,184 @@
+// Scraper Usage - per-host daily request counts, failures and push-back from the exchanges kept
+// in SQLite, and the daily quotas enforced from them, so "why did prices stop updating" has an
+// answer in the scraper status
+use chrono::{Duration as ChronoDuration, Local};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use tracing::warn;
+
+use crate::db;
+use crate::settings::{self, SettingsState};
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS scraper_usage (
+    host TEXT NOT NULL,
+    day TEXT NOT NULL,             -- YYYY-MM-DD, local
+    requests INTEGER NOT NULL DEFAULT 0,
+    successes INTEGER NOT NULL DEFAULT 0,
+    failures INTEGER NOT NULL DEFAULT 0,
+    retries INTEGER NOT NULL DEFAULT 0,
+    rate_limited INTEGER NOT NULL DEFAULT 0,
+    banned INTEGER NOT NULL DEFAULT 0,
+    throttled INTEGER NOT NULL DEFAULT 0,
+    quota_blocked INTEGER NOT NULL DEFAULT 0,
+    last_status INTEGER,
+    last_error TEXT,
+    updated_at INTEGER NOT NULL,
+    PRIMARY KEY (host, day)
+);
+";
+// Days of history in the scraper status
+const HISTORY_DAYS: i64 = 7;
+
+/// Something that happened on a request to a host.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UsageEvent {
+    Request,
+    Success,
+    Failure,
+    /// Resent after a 429/503
+    Retry,
+    /// A 429/503 answer
+    RateLimited,
+    /// A 403, usually the host blocking our address or agent
+    Banned,
+    /// Held back by our own per-minute limit
+    Throttled,
+    /// Refused because the day's quota was used up
+    QuotaBlocked,
+}
+
+impl UsageEvent {
+    fn column(self) -> &'static str {
+        match self {
+            UsageEvent::Request => "requests",
+            UsageEvent::Success => "successes",
+            UsageEvent::Failure => "failures",
+            UsageEvent::Retry => "retries",
+            UsageEvent::RateLimited => "rate_limited",
+            UsageEvent::Banned => "banned",
+            UsageEvent::Throttled => "throttled",
+            UsageEvent::QuotaBlocked => "quota_blocked",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyUsage {
+    pub host: String,
+    pub day: String,
+    pub requests: i64,
+    pub successes: i64,
+    pub failures: i64,
+    pub retries: i64,
+    pub rate_limited: i64,
+    pub banned: i64,
+    pub throttled: i64,
+    pub quota_blocked: i64,
+    pub last_status: Option<u16>,
+    pub last_error: Option<String>,
+    /// Today's quota for the host and what's left of it; None when unlimited
+    pub quota: Option<u32>,
+    pub remaining: Option<i64>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn today() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+/// The host's daily request limit from the scraper settings, falling back to the default one.
+pub fn daily_quota(app: &AppHandle, host: &str) -> Option<u32> {
+    let state = app.state::<SettingsState>();
+    let store = settings::blocking_read(&state);
+    let scraper = &store.get().scraper;
+    let quota = scraper.daily_quotas.get(host).copied().unwrap_or(scraper.default_daily_quota);
+    (quota > 0).then_some(quota)
+}
+
+/// Count `event` against today's row for `host`; failures to record are logged, never fatal.
+pub fn record(app: &AppHandle, host: &str, event: UsageEvent, status: Option<u16>, error: Option<&str>) {
+    let column = event.column();
+    let result = db::open_app_db(app).and_then(|conn| {
+        conn.execute(
+            &format!(
+                "INSERT INTO scraper_usage (host, day, {column}, last_status, last_error, updated_at) VALUES (?1, ?2, 1, ?3, ?4, ?5)
+                 ON CONFLICT(host, day) DO UPDATE SET {column} = {column} + 1,
+                     last_status = IFNULL(excluded.last_status, last_status),
+                     last_error = IFNULL(excluded.last_error, last_error),
+                     updated_at = excluded.updated_at",
+                column = column,
+            ),
+            params![host, today(), status, error, now_secs()],
+        ).map_err(|e| e.to_string())
+    });
+    if let Err(e) = result {
+        warn!(host = %host, "Failed to record scraper usage: {}", e);
+    }
+}
+
+fn requests_today(app: &AppHandle, host: &str) -> i64 {
+    db::open_app_db(app)
+        .and_then(|conn| conn.query_row(
+            "SELECT requests FROM scraper_usage WHERE host = ?1 AND day = ?2",
+            params![host, today()],
+            |row| row.get(0),
+        ).map_err(|e| e.to_string()))
+        .unwrap_or(0)
+}
+
+/// Err when today's quota for `host` is spent; the refusal is counted too.
+pub fn check_quota(app: &AppHandle, host: &str) -> Result<(), String> {
+    let Some(quota) = daily_quota(app, host) else { return Ok(()) };
+    if requests_today(app, host) < quota as i64 {
+        return Ok(());
+    }
+    record(app, host, UsageEvent::QuotaBlocked, None, None);
+    Err(format!("Daily quota of {} requests to {} reached; it resets at midnight", quota, host))
+}
+
+/// Every host's counters for the last week, newest day first.
+pub fn history(app: &AppHandle) -> Result<Vec<DailyUsage>, String> {
+    let since = (Local::now().date_naive() - ChronoDuration::days(HISTORY_DAYS - 1)).format("%Y-%m-%d").to_string();
+    let today = today();
+    let conn = db::open_app_db(app)?;
+    let mut stmt = conn.prepare(
+        "SELECT host, day, requests, successes, failures, retries, rate_limited, banned, throttled, quota_blocked,
+                last_status, last_error
+         FROM scraper_usage WHERE day >= ?1 ORDER BY day DESC, requests DESC"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![since], |row| {
+        Ok(DailyUsage {
+            host: row.get(0)?,
+            day: row.get(1)?,
+            requests: row.get(2)?,
+            successes: row.get(3)?,
+            failures: row.get(4)?,
+            retries: row.get(5)?,
+            rate_limited: row.get(6)?,
+            banned: row.get(7)?,
+            throttled: row.get(8)?,
+            quota_blocked: row.get(9)?,
+            last_status: row.get(10)?,
+            last_error: row.get(11)?,
+            quota: None,
+            remaining: None,
+        })
+    }).map_err(|e| e.to_string())?;
+
+    let mut usage = rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?;
+    for day in usage.iter_mut().filter(|day| day.day == today) {
+        day.quota = daily_quota(app, &day.host);
+        day.remaining = day.quota.map(|quota| (quota as i64 - day.requests).max(0));
+    }
+    Ok(usage)
+}
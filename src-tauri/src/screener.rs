@@ -0,0 +1,207 @@
+// Screener - filters the locally cached company universe by fundamental criteria
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::AppHandle;
+
+use crate::market_cache::MarketCache;
+use crate::scraper::parse_number;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreenMetric {
+    Pe,
+    Roe,
+    DebtToEquity,
+    MarketCap,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenCriteria {
+    pub max_pe: Option<f64>,
+    pub min_roe: Option<f64>,             // percent
+    pub max_debt_to_equity: Option<f64>,  // ratio, e.g. 0.5
+    pub min_market_cap: Option<f64>,
+    pub max_market_cap: Option<f64>,
+    pub sector: Option<String>,
+    pub exchange: Option<String>,
+    pub sort_by: Option<ScreenMetric>,    // defaults to ROE, highest first
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Fundamentals {
+    pub price: Option<f64>,
+    pub pe: Option<f64>,
+    pub roe: Option<f64>,
+    pub debt_to_equity: Option<f64>,
+    pub market_cap: Option<f64>,
+    pub eps: Option<f64>,
+    pub book_value: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenMatch {
+    pub rank: usize,
+    pub symbol: String,
+    pub exchange: String,
+    pub name: Option<String>,
+    pub sector: Option<String>,
+    pub fundamentals: Fundamentals,
+    /// Ratios derived locally (e.g. P/E from price and EPS) rather than scraped
+    pub computed: Vec<ScreenMetric>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenResult {
+    pub universe_size: usize,
+    pub matches: Vec<ScreenMatch>,
+}
+
+// Sources name the same figure differently (NSE, Yahoo, our own scrapers)
+const PE_KEYS: &[&str] = &["pe", "pe_ratio", "peRatio", "trailingPE", "pdSymbolPe"];
+const ROE_KEYS: &[&str] = &["roe", "return_on_equity", "returnOnEquity"];
+// D/E by the unit each field carries it in: our own ratios give it as a multiple, Yahoo's
+// `debtToEquity` as a percentage (45.2 == 0.452). Fields of unknown unit are left out.
+const DE_KEYS: &[(&str, f64)] = &[("debt_to_equity", 1.0), ("debtToEquity", 100.0)];
+const EPS_KEYS: &[&str] = &["eps", "trailingEps", "eps_ttm"];
+const BOOK_VALUE_KEYS: &[&str] = &["book_value", "bookValue", "book_value_per_share"];
+const PRICE_KEYS: &[&str] = &["price", "lastPrice", "last_price", "regularMarketPrice"];
+
+fn lookup(sources: &[&serde_json::Value], keys: &[&str]) -> Option<f64> {
+    sources.iter()
+        .flat_map(|source| keys.iter().filter_map(move |k| source.get(*k)))
+        .find_map(parse_number)
+        .filter(|v| v.is_finite())
+}
+
+/// Pull what the scrapers gave us, then fill gaps with ratios we can compute from it.
+fn extract_fundamentals(company: &serde_json::Value, quote: Option<&serde_json::Value>) -> (Fundamentals, Vec<ScreenMetric>) {
+    let null = serde_json::Value::Null;
+    let extra = company.get("additional_data").unwrap_or(&null);
+    let sources = [company, extra];
+
+    let mut f = Fundamentals {
+        price: quote.and_then(|q| lookup(&[q], PRICE_KEYS)).or_else(|| lookup(&sources, PRICE_KEYS)),
+        pe: lookup(&sources, PE_KEYS),
+        roe: lookup(&sources, ROE_KEYS),
+        debt_to_equity: DE_KEYS.iter()
+            .find_map(|(key, per_unit)| lookup(&sources, &[key]).map(|de| de / per_unit)),
+        market_cap: lookup(&sources, &["market_cap", "marketCap"]),
+        eps: lookup(&sources, EPS_KEYS),
+        book_value: lookup(&sources, BOOK_VALUE_KEYS),
+    };
+
+    let mut computed = Vec::new();
+    if f.pe.is_none() {
+        if let (Some(price), Some(eps)) = (f.price, f.eps.filter(|e| *e > 0.0)) {
+            f.pe = Some(price / eps);
+            computed.push(ScreenMetric::Pe);
+        }
+    }
+    if f.roe.is_none() {
+        if let (Some(eps), Some(bv)) = (f.eps, f.book_value.filter(|b| *b > 0.0)) {
+            f.roe = Some(eps / bv * 100.0);
+            computed.push(ScreenMetric::Roe);
+        }
+    }
+    (f, computed)
+}
+
+fn metric_value(f: &Fundamentals, metric: ScreenMetric) -> Option<f64> {
+    match metric {
+        ScreenMetric::Pe => f.pe,
+        ScreenMetric::Roe => f.roe,
+        ScreenMetric::DebtToEquity => f.debt_to_equity,
+        ScreenMetric::MarketCap => f.market_cap,
+    }
+}
+
+/// A company missing a figure that a criterion needs is excluded, not assumed to pass.
+fn passes(f: &Fundamentals, criteria: &ScreenCriteria) -> bool {
+    let within = |value: Option<f64>, min: Option<f64>, max: Option<f64>| -> bool {
+        if min.is_none() && max.is_none() {
+            return true;
+        }
+        value.is_some_and(|v| min.is_none_or(|m| v >= m) && max.is_none_or(|m| v <= m))
+    };
+
+    // Negative P/E means losses, which never satisfies "P/E below X"
+    within(f.pe.filter(|pe| *pe > 0.0), None, criteria.max_pe)
+        && within(f.roe, criteria.min_roe, None)
+        && within(f.debt_to_equity, None, criteria.max_debt_to_equity)
+        && within(f.market_cap, criteria.min_market_cap, criteria.max_market_cap)
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn screen_companies(
+    app: AppHandle,
+    cache: tauri::State<'_, MarketCache>,
+    criteria: ScreenCriteria,
+) -> Result<ScreenResult, String> {
+    let details = cache.all(&app, "DETAILS")?;
+    let quotes: HashMap<(String, String), serde_json::Value> = cache.all(&app, "QUOTE")?
+        .into_iter()
+        .filter_map(|(symbol, exchange, payload)| Some(((symbol, exchange), payload.get("quote")?.clone())))
+        .collect();
+
+    let exchange_filter = criteria.exchange.as_ref().map(|e| e.trim().to_uppercase());
+    let sector_filter = criteria.sector.as_ref().map(|s| s.trim().to_lowercase());
+
+    let mut matches = Vec::new();
+    for (symbol, exchange, payload) in &details {
+        let Some(company) = payload.get("company") else { continue };
+        if exchange_filter.as_ref().is_some_and(|e| e != "BOTH" && e != exchange) {
+            continue;
+        }
+
+        let sector = company.get("sector").and_then(|s| s.as_str()).map(|s| s.to_string());
+        if let Some(wanted) = &sector_filter {
+            if !sector.as_ref().is_some_and(|s| s.to_lowercase().contains(wanted.as_str())) {
+                continue;
+            }
+        }
+
+        let quote = quotes.get(&(symbol.clone(), exchange.clone()));
+        let (fundamentals, computed) = extract_fundamentals(company, quote);
+        if !passes(&fundamentals, &criteria) {
+            continue;
+        }
+
+        matches.push(ScreenMatch {
+            rank: 0,
+            symbol: symbol.clone(),
+            exchange: exchange.clone(),
+            name: company.get("name").and_then(|n| n.as_str()).map(|s| s.to_string()),
+            sector,
+            fundamentals,
+            computed,
+        });
+    }
+
+    // Lower is better for P/E and leverage, higher for ROE and size; missing values sink
+    let sort_by = criteria.sort_by.unwrap_or(ScreenMetric::Roe);
+    let ascending = matches!(sort_by, ScreenMetric::Pe | ScreenMetric::DebtToEquity);
+    matches.sort_by(|a, b| {
+        match (metric_value(&a.fundamentals, sort_by), metric_value(&b.fundamentals, sort_by)) {
+            (Some(x), Some(y)) if ascending => x.total_cmp(&y),
+            (Some(x), Some(y)) => y.total_cmp(&x),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => a.symbol.cmp(&b.symbol),
+        }
+    });
+    matches.truncate(criteria.limit.unwrap_or(50));
+    for (i, m) in matches.iter_mut().enumerate() {
+        m.rank = i + 1;
+    }
+
+    Ok(ScreenResult {
+        universe_size: details.len(),
+        matches,
+    })
+}
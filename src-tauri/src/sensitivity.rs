@@ -0,0 +1,178 @@
+// Sensitivity - one-at-a-time perturbation of DCF inputs, ranked by how far each swings the
+// valuation, in the shape a tornado chart draws
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::dcf::{self, DcfInputs, DcfResult};
+use crate::scenarios;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Input {
+    BaseCashFlow,
+    GrowthRate,
+    DiscountRate,
+    TerminalGrowthRate,
+    NetDebt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Output {
+    EnterpriseValue,
+    EquityValue,
+    ValuePerShare,
+}
+
+/// Low and high values to try for one input, in its own units (percent for rates).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputRange {
+    pub input: Input,
+    pub low: f64,
+    pub high: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TornadoBar {
+    pub input: Input,
+    pub base_input: f64,
+    pub low_input: f64,
+    pub high_input: f64,
+    /// Output with the input at `low_input`; None when that makes the DCF invalid, e.g. a
+    /// discount rate at or below terminal growth
+    pub low_output: Option<f64>,
+    pub high_output: Option<f64>,
+    /// Distance between the two outputs; bars are sorted widest first
+    pub swing: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SensitivityResult {
+    pub output: Output,
+    pub base_output: f64,
+    pub bars: Vec<TornadoBar>,
+}
+
+impl Input {
+    pub fn get(self, inputs: &DcfInputs) -> f64 {
+        match self {
+            Input::BaseCashFlow => inputs.base_cash_flow.unwrap_or(0.0),
+            Input::GrowthRate => inputs.growth_rate,
+            Input::DiscountRate => inputs.discount_rate,
+            Input::TerminalGrowthRate => inputs.terminal_growth_rate,
+            Input::NetDebt => inputs.net_debt.unwrap_or(0.0),
+        }
+    }
+
+    pub fn set(self, inputs: &mut DcfInputs, value: f64) {
+        match self {
+            Input::BaseCashFlow => inputs.base_cash_flow = Some(value),
+            Input::GrowthRate => inputs.growth_rate = value,
+            Input::DiscountRate => inputs.discount_rate = value,
+            Input::TerminalGrowthRate => inputs.terminal_growth_rate = value,
+            Input::NetDebt => inputs.net_debt = Some(value),
+        }
+    }
+}
+
+impl Output {
+    pub fn of(self, result: &DcfResult) -> Option<f64> {
+        match self {
+            Output::EnterpriseValue => Some(result.enterprise_value),
+            Output::EquityValue => Some(result.equity_value),
+            Output::ValuePerShare => result.value_per_share,
+        }
+    }
+}
+
+/// Ranges used when none are given: ±10% of the base cash flow and net debt, ±2 points of
+/// growth, ±1 point of discount rate and ±0.5 points of terminal growth.
+fn default_ranges(inputs: &DcfInputs) -> Vec<InputRange> {
+    let around = |input: Input, spread: f64| {
+        let base = input.get(inputs);
+        InputRange { input, low: base - spread, high: base + spread }
+    };
+    let mut ranges = vec![
+        around(Input::BaseCashFlow, Input::BaseCashFlow.get(inputs).abs() * 0.1),
+        around(Input::GrowthRate, 2.0),
+        around(Input::DiscountRate, 1.0),
+        around(Input::TerminalGrowthRate, 0.5),
+    ];
+    if inputs.net_debt.is_some_and(|d| d != 0.0) {
+        ranges.push(around(Input::NetDebt, Input::NetDebt.get(inputs).abs() * 0.1));
+    }
+    ranges
+}
+
+fn value(inputs: &DcfInputs, output: Output) -> Option<f64> {
+    let base = inputs.base_cash_flow?;
+    dcf::valuation(base, String::new(), inputs).ok().and_then(|result| output.of(&result))
+}
+
+pub fn run(inputs: &DcfInputs, ranges: &[InputRange], output: Option<Output>) -> Result<SensitivityResult, String> {
+    let output = output.unwrap_or(if inputs.shares_outstanding.is_some_and(|s| s > 0.0) {
+        Output::ValuePerShare
+    } else {
+        Output::EquityValue
+    });
+    let base = dcf::valuation(inputs.base_cash_flow.unwrap_or(0.0), String::new(), inputs)?;
+    let base_output = output.of(&base).ok_or("Value per share needs shares outstanding")?;
+
+    let ranges = if ranges.is_empty() { default_ranges(inputs) } else { ranges.to_vec() };
+    let mut bars = Vec::with_capacity(ranges.len());
+    for range in &ranges {
+        if !range.low.is_finite() || !range.high.is_finite() || range.low > range.high {
+            return Err(format!("Range for {:?} must run from low to high", range.input));
+        }
+        let at = |point: f64| {
+            let mut perturbed = inputs.clone();
+            range.input.set(&mut perturbed, point);
+            value(&perturbed, output)
+        };
+        let (low_output, high_output) = (at(range.low), at(range.high));
+        let swing = match (low_output, high_output) {
+            (Some(low), Some(high)) => (high - low).abs(),
+            (Some(one), None) | (None, Some(one)) => (one - base_output).abs(),
+            (None, None) => 0.0,
+        };
+        bars.push(TornadoBar {
+            input: range.input,
+            base_input: range.input.get(inputs),
+            low_input: range.low,
+            high_input: range.high,
+            low_output,
+            high_output,
+            swing,
+        });
+    }
+    bars.sort_by(|a, b| b.swing.total_cmp(&a.swing));
+    Ok(SensitivityResult { output, base_output, bars })
+}
+
+// Tauri Commands
+/// Sensitivity of a DCF valuation: a saved scenario's (`scenario_id`, the valuations this app
+/// stores), or explicit `inputs`, which may take their base cash flow from a document. With no
+/// `ranges`, the defaults above are used.
+#[tauri::command]
+pub fn run_sensitivity(
+    app: AppHandle,
+    scenario_id: Option<i64>,
+    inputs: Option<DcfInputs>,
+    ranges: Vec<InputRange>,
+    output: Option<Output>,
+) -> Result<SensitivityResult, String> {
+    let inputs = match (scenario_id, inputs) {
+        (Some(id), _) => scenarios::dcf_inputs(&app, id)?,
+        (None, Some(mut inputs)) => {
+            if inputs.base_cash_flow.is_none() {
+                inputs.base_cash_flow = Some(dcf::run(&app, &inputs)?.base_cash_flow);
+            }
+            inputs
+        }
+        (None, None) => return Err("Pass a scenario or DCF inputs to test".to_string()),
+    };
+    run(&inputs, &ranges, output)
+}
@@ -0,0 +1,235 @@
+// Session - what was on screen when the app closed (open documents, active chat, selected tabs,
+// window placement), saved per profile on exit so the next launch can pick up where it left off
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, PhysicalPosition, PhysicalSize, WebviewWindow};
+use tracing::{info, warn};
+
+use crate::profiles;
+use crate::settings;
+use crate::statements;
+use crate::windows::{self, WindowKind, MAIN_WINDOW};
+
+/// View state only the frontend knows; it reports it through `update_session` as it changes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UiState {
+    pub open_document_ids: Vec<i64>,
+    pub active_document_id: Option<i64>,
+    pub active_chat_session: Option<String>,
+    /// View -> selected tab, e.g. "analysis" -> "ratios"
+    pub selected_tabs: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowGeometry {
+    pub label: String,
+    pub kind: WindowKind,
+    pub title: String,
+    /// Query parameters the window was opened with, e.g. a detached chart's document
+    pub params: BTreeMap<String, String>,
+    /// Outer position and inner size in physical pixels, from before any maximize
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub maximized: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    pub saved_at: i64,
+    pub app_version: String,
+    pub ui: UiState,
+    pub windows: Vec<WindowGeometry>,
+}
+
+/// The session as it stands, kept up to date while the app runs and written on exit.
+pub struct SessionStore {
+    ui: Mutex<UiState>,
+    windows: Mutex<BTreeMap<String, WindowGeometry>>,
+}
+
+impl SessionStore {
+    pub fn new() -> Self {
+        Self { ui: Mutex::new(UiState::default()), windows: Mutex::new(BTreeMap::new()) }
+    }
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// The parameters a window was opened with, less the ones `windows` adds itself.
+fn window_params(window: &WebviewWindow) -> BTreeMap<String, String> {
+    window.url()
+        .map(|url| {
+            url.query_pairs()
+                .filter(|(key, _)| key != "window" && key != "label")
+                .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Note a window's placement after it moves or resizes. A maximized or minimized window keeps
+/// its last normal bounds, so restoring then un-maximizing lands somewhere sensible.
+pub fn track(app: &AppHandle, label: &str) {
+    let Some(store) = app.try_state::<SessionStore>() else { return };
+    let Some(kind) = WindowKind::from_label(label) else { return };
+    let Some(window) = app.get_webview_window(label) else { return };
+    let maximized = window.is_maximized().unwrap_or(false);
+    let minimized = window.is_minimized().unwrap_or(false);
+    let Ok(mut tracked) = store.windows.lock() else { return };
+
+    let geometry = tracked.entry(label.to_string()).or_insert_with(|| WindowGeometry {
+        label: label.to_string(),
+        kind,
+        title: window.title().unwrap_or_default(),
+        params: window_params(&window),
+        x: 0,
+        y: 0,
+        width: 0,
+        height: 0,
+        maximized,
+    });
+    geometry.maximized = maximized;
+    if maximized || minimized {
+        return;
+    }
+    if let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size()) {
+        geometry.x = position.x;
+        geometry.y = position.y;
+        geometry.width = size.width;
+        geometry.height = size.height;
+    }
+}
+
+/// A window the user closed isn't part of the session; the main window only hides or ends it.
+pub fn forget(app: &AppHandle, label: &str) {
+    if label == MAIN_WINDOW {
+        return;
+    }
+    if let Some(store) = app.try_state::<SessionStore>() {
+        if let Ok(mut tracked) = store.windows.lock() {
+            tracked.remove(label);
+        }
+    }
+}
+
+/// Write the session for the active profile. Runs on exit; headless runs never track a
+/// session and write nothing.
+pub fn save(app: &AppHandle) {
+    let Some(store) = app.try_state::<SessionStore>() else { return };
+    // Windows still open have the freshest placement
+    for label in app.webview_windows().into_keys() {
+        track(app, &label);
+    }
+    let session = Session {
+        saved_at: now_secs(),
+        app_version: app.package_info().version.to_string(),
+        ui: store.ui.lock().map(|ui| ui.clone()).unwrap_or_default(),
+        windows: store.windows.lock().map(|w| w.values().filter(|g| g.width > 0).cloned().collect()).unwrap_or_default(),
+    };
+    let saved = serde_json::to_vec_pretty(&session)
+        .map_err(|e| e.to_string())
+        .and_then(|json| settings::write_atomic(&profiles::active_session_path(app), &json));
+    match saved {
+        Ok(()) => info!(windows = session.windows.len(), documents = session.ui.open_document_ids.len(), "Saved session"),
+        Err(e) => warn!("Failed to save session: {}", e),
+    }
+}
+
+fn load(app: &AppHandle) -> Option<Session> {
+    let path = profiles::active_session_path(app);
+    let json = std::fs::read(&path).ok()?;
+    match serde_json::from_slice(&json) {
+        Ok(session) => Some(session),
+        Err(e) => {
+            warn!("Ignoring unreadable session {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// A position is kept only if its top-left corner is on a connected monitor; otherwise a
+/// window saved on a since-unplugged screen would open out of reach.
+fn on_screen(app: &AppHandle, x: i32, y: i32) -> bool {
+    app.available_monitors().unwrap_or_default().iter().any(|monitor| {
+        let (position, size) = (monitor.position(), monitor.size());
+        x >= position.x && y >= position.y && x < position.x + size.width as i32 && y < position.y + size.height as i32
+    })
+}
+
+fn place(app: &AppHandle, label: &str, geometry: &WindowGeometry) -> Result<(), String> {
+    let window = app.get_webview_window(label).ok_or_else(|| format!("No window {}", label))?;
+    window.set_size(PhysicalSize::new(geometry.width, geometry.height)).map_err(|e| e.to_string())?;
+    if on_screen(app, geometry.x, geometry.y) {
+        window.set_position(PhysicalPosition::new(geometry.x, geometry.y)).map_err(|e| e.to_string())?;
+    }
+    if geometry.maximized {
+        window.maximize().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+// Tauri Commands
+/// The session saved at the last exit, with documents that have since been deleted dropped.
+#[tauri::command]
+pub fn get_last_session(app: AppHandle) -> Result<Option<Session>, String> {
+    let Some(mut session) = load(&app) else { return Ok(None) };
+    let existing: Vec<i64> = statements::list_documents(&app)?.into_iter().map(|d| d.id).collect();
+    session.ui.open_document_ids.retain(|id| existing.contains(id));
+    if session.ui.active_document_id.is_some_and(|id| !existing.contains(&id)) {
+        session.ui.active_document_id = session.ui.open_document_ids.first().copied();
+    }
+    Ok(Some(session))
+}
+
+/// Put the windows back where they were, reopening detached ones, and hand the view state to
+/// the frontend (also sent as `session-restored`) to reopen documents, chat and tabs.
+#[tauri::command]
+pub async fn restore_session(app: AppHandle) -> Result<Option<Session>, String> {
+    let Some(session) = get_last_session(app.clone())? else { return Ok(None) };
+    for geometry in &session.windows {
+        let label = if geometry.kind == WindowKind::Main {
+            MAIN_WINDOW.to_string()
+        } else {
+            match windows::open_window(app.clone(), geometry.kind, Some(geometry.title.clone()), Some(geometry.params.clone())).await {
+                Ok(window) => window.label,
+                Err(e) => {
+                    warn!(window = %geometry.label, "Failed to reopen window: {}", e);
+                    continue;
+                }
+            }
+        };
+        if let Err(e) = place(&app, &label, geometry) {
+            warn!(window = %label, "Failed to restore window placement: {}", e);
+        }
+    }
+
+    if let Some(store) = app.try_state::<SessionStore>() {
+        if let Ok(mut ui) = store.ui.lock() {
+            *ui = session.ui.clone();
+        }
+    }
+    let _ = app.emit("session-restored", &session);
+    info!(windows = session.windows.len(), documents = session.ui.open_document_ids.len(), "Restored session");
+    Ok(Some(session))
+}
+
+#[tauri::command]
+pub fn update_session(app: AppHandle, ui: UiState) {
+    if let Some(store) = app.try_state::<SessionStore>() {
+        if let Ok(mut current) = store.ui.lock() {
+            *current = ui;
+        }
+    }
+}
@@ -1,11 +1,23 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::fs;
+use std::io::Write;
+use std::sync::mpsc;
+use std::time::Duration;
+use notify::{RecursiveMode, Watcher};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::activity::{self, ActivityKind};
+use crate::derived;
+use crate::error::AppError;
+use crate::jobs::JobManager;
+use crate::theme;
 
 // --- Sub-structs ---
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ApiKeys {
     pub gemini: String,
     pub groq: String,
@@ -16,36 +28,13 @@ pub struct ApiKeys {
     pub nvidia: String,
 }
 
-impl Default for ApiKeys {
-    fn default() -> Self {
-        Self {
-            gemini: String::new(),
-            groq: String::new(),
-            openai: String::new(),
-            openrouter: String::new(),
-            opencode: String::new(),
-            cerebras: String::new(),
-            nvidia: String::new(),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct SupabaseConfig {
     pub url: String,
     pub key: String,
 }
 
-impl Default for SupabaseConfig {
-    fn default() -> Self {
-        Self {
-            url: String::new(),
-            key: String::new(),
-        }
-    }
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FinancialDataApis {
     #[serde(rename = "alphaVantage", default)]
     pub alpha_vantage: String,
@@ -63,20 +52,6 @@ pub struct FinancialDataApis {
     pub angel_one_password: String,
 }
 
-impl Default for FinancialDataApis {
-    fn default() -> Self {
-        Self {
-            alpha_vantage: String::new(),
-            twelve_data: String::new(),
-            fyers_app_id: String::new(),
-            fyers_access_token: String::new(),
-            angel_one_api_key: String::new(),
-            angel_one_client_code: String::new(),
-            angel_one_password: String::new(),
-        }
-    }
-}
-
 // --- Main Structs ---
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -96,10 +71,22 @@ pub struct LLMSettings {
     pub format: Option<String>,     // "json" or null
     #[serde(default = "default_num_gpu")]
     pub num_gpu: i32,
+    #[serde(default)]
+    pub hide_thinking: bool,        // Drop reasoning-model thinking from streamed chat
+    #[serde(default)]
+    pub stop: Option<Vec<String>>,  // Stop sequences
+    #[serde(default)]
+    pub min_p: Option<f32>,         // 0.0 to 1.0
+    #[serde(default)]
+    pub mirostat: Option<u8>,       // 0 = off, 1 = Mirostat, 2 = Mirostat 2.0
+    #[serde(default = "default_embedding_model")]
+    pub embedding_model: String,    // Ollama model for document chunk embeddings
 }
 
 fn default_num_gpu() -> i32 { -1 }
 
+fn default_embedding_model() -> String { "nomic-embed-text".to_string() }
+
 impl Default for LLMSettings {
     fn default() -> Self {
         Self {
@@ -117,6 +104,319 @@ impl Default for LLMSettings {
             repeat_penalty: 1.1,
             format: None,
             num_gpu: -1,
+            hide_thinking: false,
+            stop: None,
+            min_p: None,
+            mirostat: None,
+            embedding_model: default_embedding_model(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScraperSettings {
+    pub default_exchange: String,       // "NSE", "BSE" or "BOTH"
+    pub cache_ttl_secs: u64,
+    pub rate_limit_per_minute: u32,     // Requests per minute per source
+    pub request_timeout_secs: u64,      // Company details / quotes
+    pub search_timeout_secs: u64,       // Company search
+    pub web_search_timeout_secs: u64,
+    #[serde(default = "default_watchlist_refresh_secs")]
+    pub watchlist_refresh_secs: u64,
+    #[serde(default = "default_stream_interval_secs")]
+    pub stream_interval_secs: u64,      // Live quote polling during market hours
+    /// A streamed quote is only re-emitted when its price moved at least this many percent
+    /// or its volume by `stream_volume_threshold_percent`; 0 emits on any change
+    #[serde(default)]
+    pub stream_price_threshold_percent: f64,
+    #[serde(default)]
+    pub stream_volume_threshold_percent: f64,
+    /// Where quotes and price history come from unless a request names a provider
+    #[serde(default)]
+    pub market_data_provider: MarketDataProvider,
+    /// Requests per day to any one host; 0 is unlimited
+    #[serde(default)]
+    pub default_daily_quota: u32,
+    /// Per-host overrides of `default_daily_quota`, keyed by host name (e.g. "www.nseindia.com")
+    #[serde(default)]
+    pub daily_quotas: HashMap<String, u32>,
+}
+
+/// Source of quotes and price history (see `market_providers`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketDataProvider {
+    /// NSE/BSE APIs scraped directly, with the Python scrapers as fallback
+    #[default]
+    Native,
+    /// Yahoo Finance's chart API; any listing Yahoo carries
+    Yahoo,
+    /// Alpha Vantage, with the key from `financialDataApis.alphaVantage`
+    AlphaVantage,
+}
+
+fn default_watchlist_refresh_secs() -> u64 { 60 }
+fn default_stream_interval_secs() -> u64 { 5 }
+
+impl Default for ScraperSettings {
+    fn default() -> Self {
+        Self {
+            default_exchange: "BOTH".to_string(),
+            cache_ttl_secs: 300,
+            rate_limit_per_minute: 30,
+            request_timeout_secs: 15,
+            search_timeout_secs: 45,
+            web_search_timeout_secs: 30,
+            watchlist_refresh_secs: default_watchlist_refresh_secs(),
+            stream_interval_secs: default_stream_interval_secs(),
+            stream_price_threshold_percent: 0.0,
+            stream_volume_threshold_percent: 0.0,
+            market_data_provider: MarketDataProvider::default(),
+            default_daily_quota: 0,
+            daily_quotas: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PythonSettings {
+    pub interpreter_path: String,       // Empty = auto-detect python3/python
+    pub analysis_timeout_secs: u64,
+    pub metrics_timeout_secs: u64,
+    pub db_query_timeout_secs: u64,
+    pub max_concurrent_jobs: usize,     // Python interpreters at once, queued or not; an analysis's page range workers share its slot
+    #[serde(default = "default_parallel_page_threshold")]
+    pub parallel_page_threshold: u32,   // PDFs with more pages are parsed as page ranges in parallel; 0 = never
+    #[serde(default)]
+    pub max_parallel_workers: usize,    // Page range workers; 0 = one per CPU core
+}
+
+fn default_parallel_page_threshold() -> u32 { 300 }
+
+impl Default for PythonSettings {
+    fn default() -> Self {
+        Self {
+            interpreter_path: String::new(),
+            analysis_timeout_secs: 900,
+            metrics_timeout_secs: 60,
+            db_query_timeout_secs: 30,
+            max_concurrent_jobs: 2,
+            parallel_page_threshold: default_parallel_page_threshold(),
+            max_parallel_workers: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiServerSettings {
+    pub enabled: bool,
+    pub port: u16,                      // Always bound to 127.0.0.1
+    pub token: String,                  // Bearer token; generated on first enable
+}
+
+impl Default for ApiServerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 8765,
+            token: String::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrpcSettings {
+    pub enabled: bool,
+    pub port: u16,                      // Always bound to 127.0.0.1; shares the API token
+}
+
+impl Default for GrpcSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 50051,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShortcutSettings {
+    pub new_chat: String,               // Accelerators like "CommandOrControl+Shift+N"; empty = unbound
+    pub quick_quote: String,
+    pub paste_and_analyze: String,
+}
+
+impl Default for ShortcutSettings {
+    fn default() -> Self {
+        Self {
+            new_chat: "CommandOrControl+Shift+N".to_string(),
+            quick_quote: "CommandOrControl+Shift+Q".to_string(),
+            paste_and_analyze: "CommandOrControl+Shift+V".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CloudBackupSettings {
+    pub endpoint: String,               // e.g. https://s3.eu-west-1.amazonaws.com or http://localhost:9000; empty = AWS for `region`
+    pub region: String,
+    pub bucket: String,
+    pub prefix: String,                 // Folder inside the bucket, e.g. "fincalc/"
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub passphrase: String,             // Encrypts backups before upload; without it they can't be restored
+    pub path_style: bool,               // MinIO and most S3-compatible services need path-style URLs
+    pub retention: u32,                 // Newest backups kept per profile; older ones are deleted after an upload
+}
+
+impl Default for CloudBackupSettings {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            region: "us-east-1".to_string(),
+            bucket: String::new(),
+            prefix: String::new(),
+            access_key_id: String::new(),
+            secret_access_key: String::new(),
+            passphrase: String::new(),
+            path_style: true,
+            retention: 7,
+        }
+    }
+}
+
+/// What `sync_now` does when a document changed both locally and in Supabase since the last sync
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Keep the local document and pull the remote version in as a separate copy
+    #[default]
+    KeepBoth,
+    PreferLocal,
+    PreferRemote,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncSettings {
+    #[serde(default)]
+    pub conflict_policy: ConflictPolicy,
+    /// Keep a Supabase realtime channel open so notes, item corrections and the watchlist
+    /// reach other devices within seconds (see `realtime`)
+    #[serde(default)]
+    pub realtime: bool,
+}
+
+/// Shared HTTP client configuration (see `http`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkSettings {
+    /// Proxy URL for all requests, e.g. "http://proxy:8080"; empty uses the system proxy
+    #[serde(default)]
+    pub proxy: String,
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    /// How long an idle pooled connection is kept for reuse
+    #[serde(default = "default_pool_idle_timeout_secs")]
+    pub pool_idle_timeout_secs: u64,
+    #[serde(default = "default_pool_max_idle_per_host")]
+    pub pool_max_idle_per_host: usize,
+}
+
+fn default_connect_timeout_secs() -> u64 { 10 }
+fn default_pool_idle_timeout_secs() -> u64 { 90 }
+fn default_pool_max_idle_per_host() -> usize { 8 }
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            proxy: String::new(),
+            connect_timeout_secs: default_connect_timeout_secs(),
+            pool_idle_timeout_secs: default_pool_idle_timeout_secs(),
+            pool_max_idle_per_host: default_pool_max_idle_per_host(),
+        }
+    }
+}
+
+/// Stages run on chat prompts before they are sent and on replies once they arrive
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PromptPipelineSettings {
+    /// Mask PAN, Aadhaar, account, IFSC, card, email and phone numbers in outgoing messages
+    #[serde(default)]
+    pub redact_pii: bool,
+    /// Rewrite "1,23,45,678" and "2.5 crore" as plain numbers in outgoing messages
+    #[serde(default)]
+    pub normalize_indian_numbers: bool,
+    /// Parse JSON blocks in replies into a separate `json` field
+    #[serde(default)]
+    pub extract_json: bool,
+    /// Reduce replies to plain text
+    #[serde(default)]
+    pub strip_markdown: bool,
+}
+
+/// What the cleanup task deletes (see `retention`); 0 keeps everything for that rule
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionSettings {
+    /// Days the parser's raw page text is kept after a document was processed
+    #[serde(default)]
+    pub text_days: u32,
+    /// Snapshots kept per document, newest first
+    #[serde(default)]
+    pub snapshots_per_document: u32,
+    /// Total size of chat history files in MB; the least recently used sessions go first
+    #[serde(default)]
+    pub chat_history_mb: u32,
+}
+
+/// Limits for calls that had none. Scraper and Python limits live in their own sections; see
+/// `timeouts::Operation` for the full list. 0 waits indefinitely.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeoutSettings {
+    /// Model listing, status and other quick Ollama calls
+    #[serde(default = "default_ollama_request_secs")]
+    pub ollama_request_secs: u64,
+    /// Chats, completions and AI analyses, start to last token
+    #[serde(default = "default_ollama_generation_secs")]
+    pub ollama_generation_secs: u64,
+}
+
+fn default_ollama_request_secs() -> u64 { 30 }
+fn default_ollama_generation_secs() -> u64 { 600 }
+
+impl Default for TimeoutSettings {
+    fn default() -> Self {
+        Self {
+            ollama_request_secs: default_ollama_request_secs(),
+            ollama_generation_secs: default_ollama_generation_secs(),
+        }
+    }
+}
+
+/// The currency and scale documents are converted to for comparisons, ratios and exports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnitSettings {
+    /// ISO code, e.g. "INR"
+    #[serde(default = "default_target_currency")]
+    pub currency: String,
+    /// "units", "thousand", "lakh", "crore", "million" or "billion"
+    #[serde(default = "default_target_scale")]
+    pub scale: String,
+    /// Units of `currency` per unit of another currency, e.g. {"USD": 83.2}; documents in a
+    /// currency without a rate keep it and only change scale
+    #[serde(default)]
+    pub fx_rates: HashMap<String, f64>,
+}
+
+fn default_target_currency() -> String { "INR".to_string() }
+fn default_target_scale() -> String { "crore".to_string() }
+
+impl Default for UnitSettings {
+    fn default() -> Self {
+        Self {
+            currency: default_target_currency(),
+            scale: default_target_scale(),
+            fx_rates: HashMap::new(),
         }
     }
 }
@@ -131,6 +431,11 @@ pub struct AppSettings {
     pub theme: String,
     pub language: String,
 
+    /// Digit grouping in exports and reports: "indian" (1,23,45,678), "western" (12,345,678) or
+    /// "auto", which follows `language`
+    #[serde(default = "default_number_format")]
+    pub number_format: String,
+
     #[serde(rename = "accentColor", default = "default_accent_color")]
     pub accent_color: String,
     
@@ -151,11 +456,83 @@ pub struct AppSettings {
     
     #[serde(rename = "financialDataApis", default)]
     pub financial_data_apis: FinancialDataApis,
+
+    /// Supabase sync of documents, items and metrics (uses `supabase_config`)
+    #[serde(default)]
+    pub sync: SyncSettings,
+
+    /// Encrypted backups to S3-compatible storage
+    #[serde(default)]
+    pub cloud_backup: CloudBackupSettings,
+
+    #[serde(default)]
+    pub network: NetworkSettings,
+
+    /// Chat prompt and reply processing
+    #[serde(default)]
+    pub prompt_pipeline: PromptPipelineSettings,
+
+    #[serde(default)]
+    pub scraper: ScraperSettings,
+
+    #[serde(default)]
+    pub python: PythonSettings,
+
+    #[serde(default)]
+    pub api_server: ApiServerSettings,
+
+    #[serde(default)]
+    pub grpc: GrpcSettings,
+
+    #[serde(default)]
+    pub units: UnitSettings,
+
+    /// Periodic cleanup of old extracted text, snapshots and chat history
+    #[serde(default)]
+    pub retention: RetentionSettings,
+
+    #[serde(default)]
+    pub timeouts: TimeoutSettings,
+
+    /// System-wide keys for quick actions
+    #[serde(default)]
+    pub shortcuts: ShortcutSettings,
+
+    /// Network-dependent commands fail fast instead of waiting for timeouts
+    #[serde(default)]
+    pub offline_mode: bool,
+
+    /// Folders whose new PDFs/spreadsheets are imported automatically
+    #[serde(default)]
+    pub watched_folders: Vec<String>,
+
+    /// Minimum level written to the log file ("error" .. "trace")
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+
+    /// Suppress OS notifications when analyses, pulls and imports finish
+    #[serde(default)]
+    pub mute_notifications: bool,
+
+    /// Look for a new release shortly after startup; manual checks always work
+    #[serde(default = "default_auto_check_updates")]
+    pub auto_check_updates: bool,
+
+    /// Record local usage counts and durations (never sent anywhere); off until opted in
+    #[serde(default)]
+    pub usage_statistics: bool,
+
+    /// Offer the app under "Open with" for PDFs and spreadsheets; off until opted in
+    #[serde(default)]
+    pub file_associations: bool,
 }
 
 fn default_accent_color() -> String { "violet".to_string() }
 fn default_ai_provider() -> String { "gemini".to_string() }
 fn default_enable_ai() -> bool { true }
+fn default_log_level() -> String { "info".to_string() }
+fn default_number_format() -> String { "auto".to_string() }
+fn default_auto_check_updates() -> bool { true }
 
 impl Default for AppSettings {
     fn default() -> Self {
@@ -164,6 +541,7 @@ impl Default for AppSettings {
             auto_start_ollama: true,
             theme: "system".to_string(),
             language: "en".to_string(),
+            number_format: default_number_format(),
             accent_color: default_accent_color(),
             enable_ai: default_enable_ai(),
             ai_provider: default_ai_provider(),
@@ -171,6 +549,25 @@ impl Default for AppSettings {
             model_name: "".to_string(),
             supabase_config: SupabaseConfig::default(),
             financial_data_apis: FinancialDataApis::default(),
+            sync: SyncSettings::default(),
+            cloud_backup: CloudBackupSettings::default(),
+            network: NetworkSettings::default(),
+            prompt_pipeline: PromptPipelineSettings::default(),
+            scraper: ScraperSettings::default(),
+            python: PythonSettings::default(),
+            api_server: ApiServerSettings::default(),
+            grpc: GrpcSettings::default(),
+            units: UnitSettings::default(),
+            retention: RetentionSettings::default(),
+            timeouts: TimeoutSettings::default(),
+            shortcuts: ShortcutSettings::default(),
+            offline_mode: false,
+            watched_folders: Vec::new(),
+            log_level: default_log_level(),
+            mute_notifications: false,
+            auto_check_updates: default_auto_check_updates(),
+            usage_statistics: false,
+            file_associations: false,
         }
     }
 }
@@ -181,15 +578,23 @@ pub struct SettingsStore {
 }
 
 impl SettingsStore {
-    pub fn new(app_handle: &AppHandle) -> Result<Self, String> {
-        let app_dir = app_handle.path().app_data_dir()
-            .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-        fs::create_dir_all(&app_dir).map_err(|e| e.to_string())?;
-        
-        let path = app_dir.join("settings.json");
+    pub fn new(path: PathBuf) -> Result<Self, String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+
         let settings = if path.exists() {
-            let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
-            serde_json::from_str(&content).unwrap_or_else(|_| AppSettings::default())
+            match read_settings_file(&path) {
+                Ok(settings) => settings,
+                Err(e) => {
+                    // Primary file is corrupt (e.g. crash mid-write) - fall back to the last good copy
+                    eprintln!("[Settings] {} unreadable ({}), trying backup", path.display(), e);
+                    read_settings_file(&backup_path(&path)).unwrap_or_else(|e| {
+                        eprintln!("[Settings] Backup unreadable ({}), using defaults", e);
+                        AppSettings::default()
+                    })
+                }
+            }
         } else {
             AppSettings::default()
         };
@@ -201,36 +606,267 @@ impl SettingsStore {
         &self.settings
     }
 
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Re-read the file from disk. Returns true if the contents differ from what we
+    /// hold; a file that fails to parse is ignored so a half-finished edit can't wipe settings.
+    pub fn reload(&mut self) -> Result<bool, String> {
+        let content = fs::read_to_string(&self.path).map_err(|e| e.to_string())?;
+        let settings: AppSettings = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid settings file: {}", e))?;
+
+        let changed = serde_json::to_value(&settings).ok() != serde_json::to_value(&self.settings).ok();
+        if changed {
+            self.settings = settings;
+        }
+        Ok(changed)
+    }
+
+    /// Restore defaults for one section ("llm", "scraper", "python", "timeouts",
+    /// "financial_data_apis") or everything ("all"). API keys are only cleared by their own
+    /// section or "all".
+    pub fn reset(&mut self, section: Option<&str>) -> Result<(), String> {
+        match section.unwrap_or("all") {
+            "llm" => {
+                self.settings.llm = LLMSettings::default();
+                self.settings.auto_start_ollama = AppSettings::default().auto_start_ollama;
+            }
+            "scraper" => {
+                self.settings.scraper = ScraperSettings::default();
+            }
+            "python" => {
+                self.settings.python = PythonSettings::default();
+            }
+            "timeouts" => {
+                self.settings.timeouts = TimeoutSettings::default();
+            }
+            "financial_data_apis" => {
+                self.settings.financial_data_apis = FinancialDataApis::default();
+            }
+            "all" => {
+                self.settings = AppSettings::default();
+            }
+            other => return Err(format!("Unknown settings section: {}", other)),
+        }
+        Ok(())
+    }
+
+    pub fn set_watched_folders(&mut self, folders: Vec<String>) {
+        self.settings.watched_folders = folders;
+    }
+
+    pub fn set_log_level(&mut self, level: String) {
+        self.settings.log_level = level;
+    }
+
+    pub fn set_api_server(&mut self, api_server: ApiServerSettings) {
+        self.settings.api_server = api_server;
+    }
+
+    pub fn set_grpc(&mut self, grpc: GrpcSettings) {
+        self.settings.grpc = grpc;
+    }
+
+    pub fn set_shortcuts(&mut self, shortcuts: ShortcutSettings) {
+        self.settings.shortcuts = shortcuts;
+    }
+
+    pub fn set_file_associations(&mut self, enabled: bool) {
+        self.settings.file_associations = enabled;
+    }
+
+    /// Swap in settings from elsewhere, e.g. a restored backup.
+    pub fn replace(&mut self, settings: AppSettings) {
+        self.settings = settings;
+    }
+
     pub fn save(&self) -> Result<(), String> {
         let json = serde_json::to_string_pretty(&self.settings).map_err(|e| e.to_string())?;
-        fs::write(&self.path, json).map_err(|e| e.to_string())
+
+        // Keep the current file as the single rotating backup, but only if it is valid -
+        // otherwise a corrupt primary would overwrite the last good copy.
+        if read_settings_file(&self.path).is_ok() {
+            fs::copy(&self.path, backup_path(&self.path)).map_err(|e| e.to_string())?;
+        }
+
+        write_atomic(&self.path, json.as_bytes())
     }
 }
 
+/// Managed settings. Settings are read on nearly every command and written only from the
+/// settings screen, so readers share the lock; it is async so commands can await it.
+pub type SettingsState = RwLock<SettingsStore>;
+
+/// Take the read lock from sync code. Plain threads block on it directly; on a runtime worker
+/// the wait is moved off the worker with `block_in_place`, which tokio would otherwise refuse.
+pub fn blocking_read(state: &SettingsState) -> RwLockReadGuard<'_, SettingsStore> {
+    if let Ok(store) = state.try_read() {
+        return store;
+    }
+    match tokio::runtime::Handle::try_current() {
+        Ok(_) => tokio::task::block_in_place(|| state.blocking_read()),
+        Err(_) => state.blocking_read(),
+    }
+}
+
+/// Write-lock counterpart of `blocking_read`.
+pub fn blocking_write(state: &SettingsState) -> RwLockWriteGuard<'_, SettingsStore> {
+    if let Ok(store) = state.try_write() {
+        return store;
+    }
+    match tokio::runtime::Handle::try_current() {
+        Ok(_) => tokio::task::block_in_place(|| state.blocking_write()),
+        Err(_) => state.blocking_write(),
+    }
+}
+
+/// Error returned by network-dependent commands while offline mode is on.
+/// The prefix is stable so the frontend can tell it apart from real failures.
+pub const OFFLINE_ERROR: &str = "offline: network access is disabled in offline mode";
+
+pub fn is_offline(app: &AppHandle) -> bool {
+    let state = app.state::<SettingsState>();
+    let offline = blocking_read(&state).get().offline_mode;
+    offline
+}
+
+pub fn ensure_online(app: &AppHandle) -> Result<(), String> {
+    if is_offline(app) {
+        Err(OFFLINE_ERROR.to_string())
+    } else {
+        Ok(())
+    }
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    path.with_extension("json.bak")
+}
+
+fn read_settings_file(path: &Path) -> Result<AppSettings, String> {
+    let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| e.to_string())
+}
+
+/// Write to a sibling temp file, flush it to disk and rename it over the target,
+/// so readers see either the old or the new contents, never a partial file.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), String> {
+    let tmp_path = path.with_extension("tmp");
+    {
+        let mut file = fs::File::create(&tmp_path).map_err(|e| e.to_string())?;
+        file.write_all(contents).map_err(|e| e.to_string())?;
+        file.sync_all().map_err(|e| e.to_string())?;
+    }
+    fs::rename(&tmp_path, path).map_err(|e| e.to_string())
+}
+
+/// Watch the app data dir for edits to the active settings.json made outside the app
+/// and reload them, emitting `settings-changed`. Our own saves produce identical
+/// contents and are filtered out by `reload`.
+pub fn watch_settings_file(app: AppHandle) -> Result<(), String> {
+    let app_dir = app.path().app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx).map_err(|e| e.to_string())?;
+    // Recursive so per-profile settings files are covered too
+    watcher.watch(&app_dir, RecursiveMode::Recursive).map_err(|e| e.to_string())?;
+
+    std::thread::spawn(move || {
+        // The watcher stops when dropped, so it lives as long as this thread
+        let _watcher = watcher;
+
+        while let Ok(event) = rx.recv() {
+            let touches_settings = match &event {
+                Ok(ev) => ev.paths.iter().any(|p| p.file_name().is_some_and(|n| n == "settings.json")),
+                Err(_) => false,
+            };
+            if !touches_settings {
+                continue;
+            }
+
+            // Editors often write in several steps; let them settle before reading
+            std::thread::sleep(Duration::from_millis(250));
+            while rx.try_recv().is_ok() {}
+
+            let state = app.state::<SettingsState>();
+            let mut store = blocking_write(&state);
+            if !store.path().exists() {
+                continue;
+            }
+            match store.reload() {
+                Ok(true) => {
+                    eprintln!("[Settings] Reloaded settings.json after external change");
+                    let _ = app.emit("settings-changed", store.get());
+                    drop(store);
+                    theme::apply(&app);
+                }
+                Ok(false) => {}
+                Err(e) => eprintln!("[Settings] Ignoring external change: {}", e),
+            }
+        }
+    });
+
+    Ok(())
+}
+
 // Tauri Commands
 #[tauri::command]
-pub fn get_settings(state: tauri::State<'_, std::sync::Mutex<SettingsStore>>) -> Result<AppSettings, String> {
-    let store = state.lock().map_err(|e| e.to_string())?;
+pub async fn get_settings(state: tauri::State<'_, SettingsState>) -> Result<AppSettings, AppError> {
+    let store = state.read().await;
     Ok(store.get().clone())
 }
 
 #[tauri::command]
-pub fn update_llm_settings(
-    state: tauri::State<'_, std::sync::Mutex<SettingsStore>>,
+pub async fn update_llm_settings(
+    app: AppHandle,
+    state: tauri::State<'_, SettingsState>,
     settings: LLMSettings
-) -> Result<(), String> {
-    let mut store = state.lock().map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let mut store = state.write().await;
     store.settings.llm = settings;
-    store.save()
+    store.save().map_err(AppError::Io)?;
+    activity::record(&app, ActivityKind::SettingsChanged, None, "Changed LLM settings", None);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn update_scraper_settings(
+    app: AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    settings: ScraperSettings
+) -> Result<(), AppError> {
+    let mut store = state.write().await;
+    store.settings.scraper = settings;
+    store.save().map_err(AppError::Io)?;
+    activity::record(&app, ActivityKind::SettingsChanged, None, "Changed scraper settings", None);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn update_python_settings(
+    app: AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    jobs: tauri::State<'_, JobManager>,
+    settings: PythonSettings
+) -> Result<(), AppError> {
+    let mut store = state.write().await;
+    jobs.set_limit(settings.max_concurrent_jobs);
+    store.settings.python = settings;
+    store.save().map_err(AppError::Io)?;
+    activity::record(&app, ActivityKind::SettingsChanged, None, "Changed Python settings", None);
+    Ok(())
 }
 
 #[tauri::command]
-pub fn update_setting(
-    state: tauri::State<'_, std::sync::Mutex<SettingsStore>>,
+pub async fn update_setting(
+    app: AppHandle,
+    state: tauri::State<'_, SettingsState>,
     key: String,
     value: serde_json::Value
-) -> Result<(), String> {
-    let mut store = state.lock().map_err(|e| e.to_string())?;
+) -> Result<(), AppError> {
+    let mut store = state.write().await;
     
     match key.as_str() {
         "auto_start_ollama" => {
@@ -239,9 +875,31 @@ pub fn update_setting(
         "theme" => {
             store.settings.theme = value.as_str().unwrap_or("system").to_string();
         }
+        "language" => {
+            store.settings.language = value.as_str().unwrap_or("en").to_string();
+        }
+        "number_format" => {
+            let format = value.as_str().unwrap_or("auto");
+            if !matches!(format, "auto" | "indian" | "western") {
+                return Err(AppError::InvalidInput(format!("Unknown number format: {}", format)));
+            }
+            store.settings.number_format = format.to_string();
+        }
         "accentColor" => {
              store.settings.accent_color = value.as_str().unwrap_or("violet").to_string();
         }
+        "offline_mode" => {
+            store.settings.offline_mode = value.as_bool().unwrap_or(false);
+        }
+        "mute_notifications" => {
+            store.settings.mute_notifications = value.as_bool().unwrap_or(false);
+        }
+        "auto_check_updates" => {
+            store.settings.auto_check_updates = value.as_bool().unwrap_or(true);
+        }
+        "usage_statistics" => {
+            store.settings.usage_statistics = value.as_bool().unwrap_or(false);
+        }
         "enableAI" => {
             store.settings.enable_ai = value.as_bool().unwrap_or(true);
         }
@@ -266,8 +924,72 @@ pub fn update_setting(
                 store.settings.financial_data_apis = val;
             }
         }
-        _ => return Err(format!("Unknown setting: {}", key)),
+        "sync" => {
+            if let Ok(val) = serde_json::from_value(value) {
+                store.settings.sync = val;
+            }
+        }
+        "cloud_backup" => {
+            if let Ok(val) = serde_json::from_value(value) {
+                store.settings.cloud_backup = val;
+            }
+        }
+        "network" => {
+            if let Ok(val) = serde_json::from_value(value) {
+                store.settings.network = val;
+            }
+        }
+        "prompt_pipeline" => {
+            if let Ok(val) = serde_json::from_value(value) {
+                store.settings.prompt_pipeline = val;
+            }
+        }
+        "units" => {
+            if let Ok(val) = serde_json::from_value(value) {
+                store.settings.units = val;
+            }
+        }
+        "retention" => {
+            if let Ok(val) = serde_json::from_value(value) {
+                store.settings.retention = val;
+            }
+        }
+        "timeouts" => {
+            if let Ok(val) = serde_json::from_value(value) {
+                store.settings.timeouts = val;
+            }
+        }
+        _ => return Err(AppError::InvalidInput(format!("Unknown setting: {}", key))),
     }
     
-    store.save()
-}
\ No newline at end of file
+    store.save().map_err(AppError::Io)?;
+    drop(store);
+    // Only the key: values can be API keys
+    activity::record(&app, ActivityKind::SettingsChanged, None, &format!("Changed {}", key), None);
+    if key == "theme" {
+        theme::apply(&app);
+    }
+    if key == "units" {
+        derived::mark_stale(&app, None);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn reset_settings(
+    app: AppHandle,
+    state: tauri::State<'_, SettingsState>,
+    section: Option<String>
+) -> Result<AppSettings, AppError> {
+    let mut store = state.write().await;
+    store.reset(section.as_deref()).map_err(AppError::InvalidInput)?;
+    store.save().map_err(AppError::Io)?;
+
+    let settings = store.get().clone();
+    let _ = app.emit("settings-changed", &settings);
+    drop(store);
+    let summary = section.map_or_else(|| "Reset all settings".to_string(), |section| format!("Reset {} settings", section));
+    activity::record(&app, ActivityKind::SettingsChanged, None, &summary, None);
+    theme::apply(&app);
+    Ok(settings)
+}
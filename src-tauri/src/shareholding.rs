@@ -0,0 +1,229 @@
+// Shareholding - quarterly promoter/FII/DII/public breakdown from NSE filings, stored per symbol
+use chrono::NaiveDate;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+use crate::db;
+use crate::scraper::{self, parse_number, NativeScraper, NSE_BASE};
+use crate::settings;
+use crate::symbols;
+
+// Patterns are filed quarterly; checking daily catches new filings soon enough
+const PATTERN_STALE_AFTER_SECS: i64 = 24 * 60 * 60;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS shareholding_pattern (
+    symbol TEXT NOT NULL,
+    quarter_end TEXT NOT NULL,     -- YYYY-MM-DD
+    promoter REAL,
+    fii REAL,
+    dii REAL,
+    public REAL,
+    employee_trusts REAL,
+    fetched_at INTEGER NOT NULL,
+    PRIMARY KEY (symbol, quarter_end)
+);
+";
+
+/// Percentages of total shares for one quarter. FII/DII come from the filing's
+/// XBRL and are only filled for the latest quarter when it could be parsed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareholdingQuarter {
+    pub quarter_end: String,
+    pub promoter: Option<f64>,
+    pub fii: Option<f64>,
+    pub dii: Option<f64>,
+    pub public: Option<f64>,
+    pub employee_trusts: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShareholdingPattern {
+    pub symbol: String,
+    pub latest: Option<ShareholdingQuarter>,
+    /// Oldest first, for trend charts
+    pub quarters: Vec<ShareholdingQuarter>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Shareholding-as-%-of-total facts in an SHP XBRL filing, keyed by context id.
+fn xbrl_percentages(xml: &str) -> Vec<(String, f64)> {
+    const ELEMENT: &str = "ShareholdingAsAPercentageOfTotalNumberOfShares";
+    let mut facts = Vec::new();
+    for chunk in xml.split('<').filter(|c| c.contains(ELEMENT) && c.contains("contextRef=\"")) {
+        let Some(context) = chunk.split("contextRef=\"").nth(1).and_then(|c| c.split('"').next()) else { continue };
+        let Some(value) = chunk.split('>').nth(1).and_then(|v| v.trim().parse::<f64>().ok()) else { continue };
+        facts.push((context.to_string(), value));
+    }
+    // Filings record percentages either as fractions (unit "pure") or as 0-100
+    if facts.iter().all(|(_, v)| *v <= 1.0) {
+        for (_, v) in facts.iter_mut() {
+            *v *= 100.0;
+        }
+    }
+    facts
+}
+
+/// Category totals have the shortest context id (sub-categories extend it).
+fn category_total(facts: &[(String, f64)], category: &str) -> Option<f64> {
+    facts.iter()
+        .filter(|(context, _)| context.contains(category))
+        .min_by_key(|(context, _)| context.len())
+        .map(|(_, value)| *value)
+}
+
+async fn fetch_institutional_split(native: &NativeScraper, xbrl_url: &str, app: &AppHandle) -> (Option<f64>, Option<f64>) {
+    match native.get_text(xbrl_url, scraper::request_timeout(app)).await {
+        Ok(xml) => {
+            let facts = xbrl_percentages(&xml);
+            (category_total(&facts, "InstitutionsForeign"), category_total(&facts, "InstitutionsDomestic"))
+        }
+        Err(e) => {
+            eprintln!("[Shareholding] XBRL unavailable, skipping FII/DII split: {}", e);
+            (None, None)
+        }
+    }
+}
+
+async fn refresh_pattern(app: &AppHandle, native: &NativeScraper, symbol: &str) -> Result<(), String> {
+    let nse_symbol = symbols::exchange_identifier(app, symbol, "NSE");
+    let encoded = scraper::url_encode(&nse_symbol);
+    let referer = format!("{}/get-quotes/equity?symbol={}", NSE_BASE, encoded);
+    let filings = native.nse_get_json(
+        &format!("/api/corporate-share-holdings-master?index=equities&symbol={}", encoded),
+        &referer,
+        scraper::request_timeout(app),
+    ).await?;
+    let filings = filings.as_array().cloned().unwrap_or_default();
+
+    // Revisions repeat a quarter; the first (most recent) submission wins
+    let mut quarters: Vec<(ShareholdingQuarter, Option<String>)> = Vec::new();
+    for filing in &filings {
+        let Some(quarter_end) = filing.get("date").and_then(|d| d.as_str())
+            .and_then(|d| NaiveDate::parse_from_str(d.trim(), "%d-%b-%Y").ok())
+            .map(|d| d.format("%Y-%m-%d").to_string()) else { continue };
+        if quarters.iter().any(|(q, _)| q.quarter_end == quarter_end) {
+            continue;
+        }
+        quarters.push((
+            ShareholdingQuarter {
+                quarter_end,
+                promoter: filing.get("pr_and_prgrp").and_then(parse_number),
+                fii: None,
+                dii: None,
+                public: filing.get("public_val").and_then(parse_number),
+                employee_trusts: filing.get("employeeTrusts").and_then(parse_number),
+            },
+            filing.get("xbrl").and_then(|x| x.as_str()).filter(|x| x.starts_with("http")).map(str::to_string),
+        ));
+    }
+    if quarters.is_empty() {
+        return Err(format!("No shareholding pattern found for {}", nse_symbol));
+    }
+
+    quarters.sort_by(|a, b| b.0.quarter_end.cmp(&a.0.quarter_end));
+    if let (latest, Some(xbrl_url)) = &mut quarters[0] {
+        let (fii, dii) = fetch_institutional_split(native, xbrl_url, app).await;
+        latest.fii = fii;
+        latest.dii = dii;
+    }
+
+    let mut conn = db::open_app_db(app)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let fetched_at = now_secs();
+    for (q, _) in &quarters {
+        // Keep an earlier FII/DII split when this refresh couldn't parse one
+        tx.execute(
+            "INSERT INTO shareholding_pattern (symbol, quarter_end, promoter, fii, dii, public, employee_trusts, fetched_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT(symbol, quarter_end) DO UPDATE SET
+                promoter = excluded.promoter,
+                fii = COALESCE(excluded.fii, shareholding_pattern.fii),
+                dii = COALESCE(excluded.dii, shareholding_pattern.dii),
+                public = excluded.public,
+                employee_trusts = excluded.employee_trusts,
+                fetched_at = excluded.fetched_at",
+            params![symbol, q.quarter_end, q.promoter, q.fii, q.dii, q.public, q.employee_trusts, fetched_at],
+        ).map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    eprintln!("[Shareholding] Stored {} quarters for {}", quarters.len(), symbol);
+    Ok(())
+}
+
+fn load_pattern(app: &AppHandle, symbol: &str) -> Result<(Vec<ShareholdingQuarter>, Option<i64>), String> {
+    let conn = db::open_app_db(app)?;
+    let mut stmt = conn.prepare(
+        "SELECT quarter_end, promoter, fii, dii, public, employee_trusts, fetched_at
+         FROM shareholding_pattern WHERE symbol = ?1 ORDER BY quarter_end",
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![symbol], |row| {
+        Ok((
+            ShareholdingQuarter {
+                quarter_end: row.get(0)?,
+                promoter: row.get(1)?,
+                fii: row.get(2)?,
+                dii: row.get(3)?,
+                public: row.get(4)?,
+                employee_trusts: row.get(5)?,
+            },
+            row.get::<_, i64>(6)?,
+        ))
+    }).map_err(|e| e.to_string())?;
+
+    let mut quarters = Vec::new();
+    let mut fetched_at: Option<i64> = None;
+    for row in rows {
+        let (quarter, at) = row.map_err(|e| e.to_string())?;
+        fetched_at = Some(fetched_at.map_or(at, |f| f.max(at)));
+        quarters.push(quarter);
+    }
+    Ok((quarters, fetched_at))
+}
+
+// Tauri Commands
+#[tauri::command]
+pub async fn get_shareholding_pattern(
+    app: AppHandle,
+    native: tauri::State<'_, NativeScraper>,
+    symbol: String,
+    force_refresh: Option<bool>,
+) -> Result<ShareholdingPattern, String> {
+    let symbol = symbol.trim().to_uppercase();
+    if symbol.is_empty() {
+        return Err("Symbol is required".to_string());
+    }
+
+    let (_, fetched_at) = load_pattern(&app, &symbol)?;
+    let fresh = fetched_at.is_some_and(|t| now_secs() - t < PATTERN_STALE_AFTER_SECS);
+    if force_refresh.unwrap_or(false) || !fresh {
+        // Stored quarters are still worth returning when offline or the refresh fails
+        let result = match settings::ensure_online(&app) {
+            Ok(()) => refresh_pattern(&app, &native, &symbol).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            if fetched_at.is_none() {
+                return Err(e);
+            }
+            eprintln!("[Shareholding] Refresh failed, using stored pattern: {}", e);
+        }
+    }
+
+    let (quarters, _) = load_pattern(&app, &symbol)?;
+    Ok(ShareholdingPattern {
+        symbol,
+        latest: quarters.last().cloned(),
+        quarters,
+    })
+}
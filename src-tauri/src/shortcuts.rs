@@ -0,0 +1,198 @@
+// Shortcuts - system-wide key combinations for quick actions (new chat, quote lookup, paste and
+// analyze a copied table), registered with the OS through the global-shortcut plugin
+use serde::{Deserialize, Serialize};
+use tauri::plugin::TauriPlugin;
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutEvent, ShortcutState};
+use tracing::{info, warn};
+
+use crate::settings::{self, SettingsState, ShortcutSettings};
+use crate::tray;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    NewChat,
+    QuickQuote,
+    PasteAndAnalyze,
+}
+
+const ACTIONS: [Action; 3] = [Action::NewChat, Action::QuickQuote, Action::PasteAndAnalyze];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutBinding {
+    pub action: Action,
+    /// Empty when the action has no shortcut
+    pub accelerator: String,
+    pub registered: bool,
+    /// Why the shortcut isn't active, e.g. another application holds it
+    pub conflict: Option<String>,
+}
+
+impl Action {
+    /// Event the frontend listens for.
+    fn event(self) -> &'static str {
+        match self {
+            Action::NewChat => "shortcut-new-chat",
+            Action::QuickQuote => "shortcut-quick-quote",
+            Action::PasteAndAnalyze => "shortcut-paste-and-analyze",
+        }
+    }
+
+    fn accelerator(self, shortcuts: &ShortcutSettings) -> &str {
+        match self {
+            Action::NewChat => &shortcuts.new_chat,
+            Action::QuickQuote => &shortcuts.quick_quote,
+            Action::PasteAndAnalyze => &shortcuts.paste_and_analyze,
+        }
+    }
+
+    fn set_accelerator(self, shortcuts: &mut ShortcutSettings, accelerator: String) {
+        match self {
+            Action::NewChat => shortcuts.new_chat = accelerator,
+            Action::QuickQuote => shortcuts.quick_quote = accelerator,
+            Action::PasteAndAnalyze => shortcuts.paste_and_analyze = accelerator,
+        }
+    }
+}
+
+fn configured(app: &AppHandle) -> ShortcutSettings {
+    let state = app.state::<SettingsState>();
+    let shortcuts = settings::blocking_read(&state).get().shortcuts.clone();
+    shortcuts
+}
+
+fn parse(accelerator: &str) -> Result<Option<Shortcut>, String> {
+    if accelerator.trim().is_empty() {
+        return Ok(None);
+    }
+    accelerator.trim().parse::<Shortcut>()
+        .map(Some)
+        .map_err(|e| format!("Invalid shortcut \"{}\": {}", accelerator, e))
+}
+
+fn on_shortcut(app: &AppHandle, shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state() != ShortcutState::Pressed {
+        return;
+    }
+    let shortcuts = configured(app);
+    let Some(action) = ACTIONS.into_iter()
+        .find(|action| parse(action.accelerator(&shortcuts)).ok().flatten().as_ref() == Some(shortcut))
+    else {
+        return;
+    };
+
+    tray::show_main_window(app);
+    let emitted = match action {
+        // The frontend does the table parsing; hand it the text so it needn't ask for clipboard access
+        Action::PasteAndAnalyze => match app.clipboard().read_text() {
+            Ok(text) => app.emit(action.event(), text),
+            Err(e) => {
+                warn!("Clipboard unreadable for paste and analyze: {}", e);
+                app.emit(action.event(), String::new())
+            }
+        },
+        _ => app.emit(action.event(), ()),
+    };
+    if let Err(e) = emitted {
+        warn!(event = action.event(), "Failed to emit shortcut event: {}", e);
+    }
+}
+
+pub fn plugin() -> TauriPlugin<Wry> {
+    tauri_plugin_global_shortcut::Builder::new().with_handler(on_shortcut).build()
+}
+
+/// Two actions sharing one key combination; the OS would only deliver it once.
+fn duplicate(shortcuts: &ShortcutSettings, action: Action, shortcut: &Shortcut) -> Option<Action> {
+    ACTIONS.into_iter()
+        .filter(|other| *other != action)
+        .find(|other| parse(other.accelerator(shortcuts)).ok().flatten().as_ref() == Some(shortcut))
+}
+
+/// Register every configured shortcut, replacing any registered before. Shortcuts that can't be
+/// registered are reported rather than failing the rest.
+pub fn register_all(app: &AppHandle) -> Vec<ShortcutBinding> {
+    let global = app.global_shortcut();
+    if let Err(e) = global.unregister_all() {
+        warn!("Failed to clear global shortcuts: {}", e);
+    }
+    let shortcuts = configured(app);
+
+    let mut bindings = Vec::with_capacity(ACTIONS.len());
+    for (i, action) in ACTIONS.into_iter().enumerate() {
+        let accelerator = action.accelerator(&shortcuts).to_string();
+        let conflict = match parse(&accelerator) {
+            Ok(None) => None,
+            Err(e) => Some(e),
+            // Earlier actions keep a shared combination
+            Ok(Some(shortcut)) => match duplicate(&shortcuts, action, &shortcut) {
+                Some(other) if ACTIONS[..i].contains(&other) => Some(format!("Also bound to {:?}", other)),
+                _ => global.register(shortcut).err().map(|e| format!("In use by another application: {}", e)),
+            },
+        };
+        if let Some(conflict) = &conflict {
+            warn!(action = ?action, accelerator = %accelerator, "Global shortcut not registered: {}", conflict);
+        }
+        bindings.push(ShortcutBinding {
+            registered: !accelerator.trim().is_empty() && conflict.is_none(),
+            action,
+            accelerator,
+            conflict,
+        });
+    }
+    bindings
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn get_shortcuts(app: AppHandle) -> Vec<ShortcutBinding> {
+    let shortcuts = configured(&app);
+    let global = app.global_shortcut();
+    ACTIONS.into_iter()
+        .map(|action| {
+            let accelerator = action.accelerator(&shortcuts).to_string();
+            let (registered, conflict) = match parse(&accelerator) {
+                Ok(None) => (false, None),
+                Err(e) => (false, Some(e)),
+                Ok(Some(shortcut)) if global.is_registered(shortcut) => (true, None),
+                Ok(Some(_)) => (false, Some("Not registered; it may be in use by another application".to_string())),
+            };
+            ShortcutBinding { action, accelerator, registered, conflict }
+        })
+        .collect()
+}
+
+/// Bind `action` to `accelerator`, or unbind it with an empty one. A combination already bound to
+/// another action, or held by another application, is refused and the old binding kept.
+#[tauri::command]
+pub async fn set_shortcut(app: AppHandle, action: Action, accelerator: String) -> Result<Vec<ShortcutBinding>, String> {
+    let mut shortcuts = configured(&app);
+    let previous = parse(action.accelerator(&shortcuts)).ok().flatten();
+    let shortcut = parse(&accelerator)?;
+
+    if let Some(shortcut) = shortcut {
+        if let Some(other) = duplicate(&shortcuts, action, &shortcut) {
+            return Err(format!("{} is already bound to {:?}", accelerator.trim(), other));
+        }
+        if previous != Some(shortcut) {
+            // Registering is the only way to learn whether another application holds it
+            let global = app.global_shortcut();
+            global.register(shortcut)
+                .map_err(|e| format!("{} is in use by another application: {}", accelerator.trim(), e))?;
+            let _ = global.unregister(shortcut);
+        }
+    }
+
+    action.set_accelerator(&mut shortcuts, accelerator.trim().to_string());
+    {
+        let state = app.state::<SettingsState>();
+        let mut store = state.write().await;
+        store.set_shortcuts(shortcuts);
+        store.save()?;
+    }
+    info!(action = ?action, accelerator = %accelerator.trim(), "Global shortcut changed");
+    Ok(register_all(&app))
+}
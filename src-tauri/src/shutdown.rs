@@ -0,0 +1,65 @@
+// Shutdown - stops background work when the app exits: running tasks are cancelled and given a
+// moment to finish their last writes, streams stop, our Ollama server is stopped, the workspace
+// is sealed if the app lock is on, the log is flushed and the session is saved. Closing a window
+// stops the chat stream it was showing.
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, RunEvent};
+use tracing::{info, warn};
+
+use crate::app_lock;
+use crate::logging::LogState;
+use crate::ollama::OllamaBridge;
+use crate::quote_stream::QuoteStreamer;
+use crate::session;
+use crate::tasks::{self, TaskKind};
+
+// How long cancelled tasks get to wind down before the process exits regardless. Analyses
+// check for cancellation every 250 ms and the DB streaming thread every 2 s.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(3);
+const SHUTDOWN_POLL: Duration = Duration::from_millis(50);
+
+pub fn handle_run_event(app: &AppHandle, event: &RunEvent) {
+    if let RunEvent::Exit = event {
+        run(app);
+    }
+}
+
+/// Runs once on exit. Cancelled analyses drop their Python processes, which kills them, and
+/// record their partial results as failed so they can be resumed next time.
+fn run(app: &AppHandle) {
+    session::save(app);
+    let cancelled = tasks::cancel_all(app);
+    info!(cancelled, "Shutting down");
+
+    if let Some(streamer) = app.try_state::<QuoteStreamer>() {
+        streamer.stop();
+    }
+    if let Some(bridge) = app.try_state::<OllamaBridge>() {
+        bridge.stop();
+    }
+
+    // Tasks finish on the async runtime's threads while this one waits
+    let started = Instant::now();
+    while tasks::any_running(app) {
+        if started.elapsed() >= SHUTDOWN_GRACE {
+            warn!("Background tasks still running after {:?}; exiting anyway", SHUTDOWN_GRACE);
+            break;
+        }
+        std::thread::sleep(SHUTDOWN_POLL);
+    }
+
+    app_lock::lock_on_exit(app);
+
+    if let Some(log) = app.try_state::<LogState>() {
+        log.flush();
+    }
+}
+
+/// A closed window can't show its chat stream any more.
+pub fn window_destroyed(app: &AppHandle, label: &str) {
+    session::forget(app, label);
+    let cancelled = tasks::cancel_labelled(app, TaskKind::ChatStream, label);
+    if cancelled > 0 {
+        info!(window = label, "Stopped chat stream of closed window");
+    }
+}
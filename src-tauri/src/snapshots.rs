@@ -0,0 +1,246 @@
+// Snapshots - frozen copies of an analysis (items + ratios) for auditing later changes
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::AppHandle;
+
+use crate::db;
+use crate::ratios::{self, Ratio};
+use crate::report;
+use crate::statements;
+
+// Kept in the app DB: the pipeline DB is wiped at the start of every analysis
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS analysis_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    document_id INTEGER NOT NULL,
+    filename TEXT NOT NULL,
+    label TEXT NOT NULL,
+    items TEXT NOT NULL,           -- JSON array in the frontend item shape
+    ratios TEXT NOT NULL,          -- JSON array of Ratio
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_snapshots_document ON analysis_snapshots(document_id);
+";
+
+// Differences below this are float noise from re-parsing, not edits
+const TOLERANCE: f64 = 1e-6;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotInfo {
+    pub id: i64,
+    pub document_id: i64,
+    pub filename: String,
+    pub label: String,
+    pub item_count: usize,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemChange {
+    pub change: ChangeKind,
+    pub statement: String,
+    pub label: String,
+    pub before_current: Option<f64>,
+    pub after_current: Option<f64>,
+    pub before_previous: Option<f64>,
+    pub after_previous: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RatioChange {
+    pub key: String,
+    pub name: String,
+    pub unit: String,
+    pub before: Option<f64>,
+    pub after: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotDiff {
+    pub from: SnapshotInfo,
+    pub to: SnapshotInfo,
+    pub unchanged_items: usize,
+    pub items: Vec<ItemChange>,
+    pub ratios: Vec<RatioChange>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn load_snapshot(app: &AppHandle, id: i64) -> Result<(SnapshotInfo, Vec<serde_json::Value>, Vec<Ratio>), String> {
+    let conn = db::open_app_db(app)?;
+    let (document_id, filename, label, items, ratios, created_at): (i64, String, String, String, String, i64) = conn.query_row(
+        "SELECT document_id, filename, label, items, ratios, created_at FROM analysis_snapshots WHERE id = ?1",
+        params![id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?)),
+    ).map_err(|_| format!("Snapshot {} not found", id))?;
+
+    let items: Vec<serde_json::Value> = serde_json::from_str(&items).map_err(|e| e.to_string())?;
+    let ratios: Vec<Ratio> = serde_json::from_str(&ratios).map_err(|e| e.to_string())?;
+    let info = SnapshotInfo { id, document_id, filename, label, item_count: items.len(), created_at };
+    Ok((info, items, ratios))
+}
+
+/// Items keyed by statement + normalized label; repeated labels get an occurrence suffix
+/// so e.g. the two "Borrowings" rows (current and non-current) stay distinct.
+fn keyed_items(items: &[serde_json::Value]) -> BTreeMap<(String, String), &serde_json::Value> {
+    let mut keyed = BTreeMap::new();
+    let mut seen: BTreeMap<(String, String), usize> = BTreeMap::new();
+    for item in items.iter().filter(|item| !item["isHeader"].as_bool().unwrap_or(false)) {
+        let statement = report::statement_of(item);
+        let label = item["label"].as_str().unwrap_or("").trim().to_lowercase();
+        let count = seen.entry((statement.clone(), label.clone())).or_insert(0);
+        *count += 1;
+        let label = if *count > 1 { format!("{} #{}", label, count) } else { label };
+        keyed.insert((statement, label), item);
+    }
+    keyed
+}
+
+fn differs(a: Option<f64>, b: Option<f64>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => (a - b).abs() > TOLERANCE,
+        (None, None) => false,
+        _ => true,
+    }
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn create_snapshot(app: AppHandle, document_id: i64, label: String) -> Result<SnapshotInfo, String> {
+    let label = label.trim().to_string();
+    if label.is_empty() {
+        return Err("Snapshot label cannot be empty".to_string());
+    }
+    let document = statements::load_document(&app, document_id)?;
+    let ratios = ratios::for_document(&document);
+    let created_at = now_secs();
+
+    let conn = db::open_app_db(&app)?;
+    conn.execute(
+        "INSERT INTO analysis_snapshots (document_id, filename, label, items, ratios, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            document_id,
+            document.filename,
+            label,
+            serde_json::to_string(&document.items).map_err(|e| e.to_string())?,
+            serde_json::to_string(&ratios).map_err(|e| e.to_string())?,
+            created_at,
+        ],
+    ).map_err(|e| e.to_string())?;
+
+    Ok(SnapshotInfo {
+        id: conn.last_insert_rowid(),
+        document_id,
+        filename: document.filename,
+        label,
+        item_count: document.items.len(),
+        created_at,
+    })
+}
+
+#[tauri::command]
+pub fn list_snapshots(app: AppHandle, document_id: Option<i64>) -> Result<Vec<SnapshotInfo>, String> {
+    let conn = db::open_app_db(&app)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, document_id, filename, label, json_array_length(items), created_at
+         FROM analysis_snapshots WHERE ?1 IS NULL OR document_id = ?1 ORDER BY created_at DESC, id DESC"
+    ).map_err(|e| e.to_string())?;
+
+    let rows = stmt.query_map(params![document_id], |row| {
+        Ok(SnapshotInfo {
+            id: row.get(0)?,
+            document_id: row.get(1)?,
+            filename: row.get(2)?,
+            label: row.get(3)?,
+            item_count: row.get::<_, i64>(4)? as usize,
+            created_at: row.get(5)?,
+        })
+    }).map_err(|e| e.to_string())?;
+
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn delete_snapshot(app: AppHandle, id: i64) -> Result<(), String> {
+    let conn = db::open_app_db(&app)?;
+    conn.execute("DELETE FROM analysis_snapshots WHERE id = ?1", params![id])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// What changed going from snapshot `a` to snapshot `b`.
+#[tauri::command]
+pub fn diff_snapshots(app: AppHandle, a: i64, b: i64) -> Result<SnapshotDiff, String> {
+    let (from, before_items, before_ratios) = load_snapshot(&app, a)?;
+    let (to, after_items, after_ratios) = load_snapshot(&app, b)?;
+    let before = keyed_items(&before_items);
+    let after = keyed_items(&after_items);
+
+    let value = |item: Option<&&serde_json::Value>, key: &str| item.and_then(|i| i[key].as_f64());
+    let mut items = Vec::new();
+    let mut unchanged_items = 0;
+    let keys: std::collections::BTreeSet<_> = before.keys().chain(after.keys()).collect();
+    for key in keys {
+        let (old, new) = (before.get(key), after.get(key));
+        let change = match (old, new) {
+            (Some(_), None) => ChangeKind::Removed,
+            (None, Some(_)) => ChangeKind::Added,
+            _ if differs(value(old, "currentYear"), value(new, "currentYear"))
+                || differs(value(old, "previousYear"), value(new, "previousYear")) => ChangeKind::Changed,
+            _ => {
+                unchanged_items += 1;
+                continue;
+            }
+        };
+        let label = new.or(old).and_then(|i| i["label"].as_str()).unwrap_or(&key.1).to_string();
+        items.push(ItemChange {
+            change,
+            statement: key.0.clone(),
+            label,
+            before_current: value(old, "currentYear"),
+            after_current: value(new, "currentYear"),
+            before_previous: value(old, "previousYear"),
+            after_previous: value(new, "previousYear"),
+        });
+    }
+
+    let mut ratios = Vec::new();
+    let ratio_keys: std::collections::BTreeSet<&str> = before_ratios.iter().chain(&after_ratios).map(|r| r.key.as_str()).collect();
+    for key in ratio_keys {
+        let old = before_ratios.iter().find(|r| r.key == key);
+        let new = after_ratios.iter().find(|r| r.key == key);
+        let (before, after) = (old.and_then(|r| r.current), new.and_then(|r| r.current));
+        if differs(before, after) {
+            let ratio = new.or(old).expect("ratio key came from one of the lists");
+            ratios.push(RatioChange {
+                key: key.to_string(),
+                name: ratio.name.clone(),
+                unit: ratio.unit.clone(),
+                before,
+                after,
+            });
+        }
+    }
+
+    Ok(SnapshotDiff { from, to, unchanged_items, items, ratios })
+}
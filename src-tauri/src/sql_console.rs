@@ -0,0 +1,124 @@
+// SQL Console - single SELECT statements against the active profile's extracted_data.db, run on
+// a read-only connection with row and time limits, for exploring the data in-app
+use rusqlite::types::{Value, ValueRef};
+use rusqlite::{params_from_iter, Connection, OpenFlags};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tauri::AppHandle;
+
+use crate::profiles;
+
+const DEFAULT_MAX_ROWS: usize = 1000;
+const MAX_ROWS_LIMIT: usize = 10_000;
+// Longer queries are interrupted
+const QUERY_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+    /// More rows matched than were returned
+    pub truncated: bool,
+    pub elapsed_ms: u64,
+}
+
+/// The statement's first keyword, past whitespace and comments, lowercased.
+fn first_keyword(sql: &str) -> String {
+    let mut rest = sql.trim_start();
+    loop {
+        if let Some(comment) = rest.strip_prefix("--") {
+            rest = comment.split_once('\n').map(|(_, after)| after).unwrap_or("").trim_start();
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            rest = comment.split_once("*/").map(|(_, after)| after).unwrap_or("").trim_start();
+        } else {
+            break;
+        }
+    }
+    rest.chars().take_while(|c| c.is_ascii_alphabetic()).collect::<String>().to_lowercase()
+}
+
+fn to_sql_value(value: &serde_json::Value) -> Value {
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Integer(*b as i64),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => n.as_f64().map(Value::Real).unwrap_or(Value::Null),
+        },
+        serde_json::Value::String(s) => Value::Text(s.clone()),
+        other => Value::Text(other.to_string()),
+    }
+}
+
+fn to_json(value: ValueRef) -> serde_json::Value {
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => serde_json::Number::from_f64(f).map(serde_json::Value::Number).unwrap_or(serde_json::Value::Null),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into_owned().into(),
+        ValueRef::Blob(b) => format!("<blob, {} bytes>", b.len()).into(),
+    }
+}
+
+fn open_readonly(path: &Path) -> Result<Connection, String> {
+    if !path.exists() {
+        return Err(format!("Database not found: {}", path.display()));
+    }
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+        .map_err(|e| e.to_string())?;
+    conn.execute_batch("PRAGMA query_only = ON;").map_err(|e| e.to_string())?;
+    Ok(conn)
+}
+
+fn run(conn: &Connection, sql: &str, params: &[serde_json::Value], max_rows: usize) -> Result<QueryResult, String> {
+    let started = Instant::now();
+    // Preparing more than one statement is an error, so nothing can ride along after the SELECT
+    let mut stmt = conn.prepare(sql).map_err(|e| e.to_string())?;
+    if !stmt.readonly() {
+        return Err("Only read-only SELECT statements can be run".to_string());
+    }
+    let columns: Vec<String> = stmt.column_names().into_iter().map(str::to_string).collect();
+    let mut rows = stmt.query(params_from_iter(params.iter().map(to_sql_value))).map_err(|e| e.to_string())?;
+
+    let mut result = Vec::new();
+    let mut truncated = false;
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        if result.len() == max_rows {
+            truncated = true;
+            break;
+        }
+        result.push((0..columns.len()).map(|i| row.get_ref(i).map(to_json).unwrap_or(serde_json::Value::Null)).collect());
+    }
+    Ok(QueryResult { columns, rows: result, truncated, elapsed_ms: started.elapsed().as_millis() as u64 })
+}
+
+// Tauri Commands
+/// Run one SELECT (or WITH ... SELECT) against the active profile's extracted_data.db, binding
+/// `params` to its `?` placeholders. At most `max_rows` rows come back (default 1000, at most
+/// 10000) and the query is cancelled after 10 seconds.
+#[tauri::command]
+pub async fn run_readonly_query(
+    app: AppHandle,
+    sql: String,
+    params: Option<Vec<serde_json::Value>>,
+    max_rows: Option<usize>,
+) -> Result<QueryResult, String> {
+    if !matches!(first_keyword(&sql).as_str(), "select" | "with") {
+        return Err("Only SELECT statements can be run".to_string());
+    }
+    let max_rows = max_rows.unwrap_or(DEFAULT_MAX_ROWS).clamp(1, MAX_ROWS_LIMIT);
+    let params = params.unwrap_or_default();
+    let conn = open_readonly(&profiles::active_db_path(&app))?;
+    let interrupt = conn.get_interrupt_handle();
+
+    let query = tauri::async_runtime::spawn_blocking(move || run(&conn, &sql, &params, max_rows));
+    match tokio::time::timeout(Duration::from_secs(QUERY_TIMEOUT_SECS), query).await {
+        Ok(result) => result.map_err(|e| e.to_string())?,
+        Err(_) => {
+            interrupt.interrupt();
+            Err(format!("Query cancelled after {} seconds", QUERY_TIMEOUT_SECS))
+        }
+    }
+}
@@ -0,0 +1,746 @@
+// Statements - multi-year P&L, balance sheet and cash flow for listed companies,
+// written into the pipeline database as if a report had been parsed
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::AppHandle;
+
+use crate::activity::{self, ActivityKind};
+use crate::anomalies;
+use crate::db;
+use crate::derived;
+use crate::duplicates;
+use crate::metric_formulas;
+use crate::numbers;
+use crate::page_ranges;
+use crate::periods;
+use crate::ratio_alerts;
+use crate::scraper::{self, parse_number, NativeScraper};
+use crate::settings;
+use crate::units;
+
+// Mirrors python/database.py so imports work before the first analysis has created the DB
+pub const PIPELINE_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS documents (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    filename TEXT NOT NULL,
+    processed_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    metadata TEXT
+);
+CREATE TABLE IF NOT EXISTS financial_items (
+    id TEXT PRIMARY KEY,
+    doc_id INTEGER,
+    label TEXT,
+    value_current REAL,
+    value_previous REAL,
+    row_index INTEGER,
+    statement_type TEXT,
+    is_header BOOLEAN,
+    source_page INTEGER,
+    source_line_text TEXT,
+    confidence REAL,
+    original_json TEXT,
+    FOREIGN KEY(doc_id) REFERENCES documents(id)
+);
+-- Keyset order for `get_items_after`; NULL doc ids and row indexes sort as 0
+CREATE INDEX IF NOT EXISTS idx_financial_items_keyset
+    ON financial_items(IFNULL(doc_id, 0), IFNULL(row_index, 0), id);
+";
+
+// python/database.py `text_chunks`, for documents whose text is stored from Rust
+const TEXT_CHUNKS_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS text_chunks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    doc_id INTEGER,
+    page_num INTEGER,
+    chunk_index INTEGER,
+    content TEXT,
+    embedding TEXT,
+    FOREIGN KEY(doc_id) REFERENCES documents(id)
+);
+";
+const TEXT_CHUNK_CHARS: usize = 1000;
+
+// Page size for `get_items_after` when none is given, and the most one call returns
+const ITEM_PAGE_ROWS: usize = 200;
+const MAX_ITEM_PAGE_ROWS: usize = 5000;
+// Extraction confidence below which an item is worth a second look, as python/config.py
+// `confidence_medium`
+const LOW_CONFIDENCE: f64 = 0.65;
+
+/// Series name -> (period label -> value)
+pub type Series = BTreeMap<String, BTreeMap<String, f64>>;
+
+/// (source series name, label, is_total)
+pub type StatementLine = (&'static str, &'static str, bool);
+
+/// (frontend statement type, its lines in display order)
+pub type StatementLayout = [(&'static str, &'static [StatementLine])];
+
+// Labels follow Ind AS wording so the metrics engine's label matching
+// picks them up like parsed PDF rows.
+const INCOME_LINES: &[StatementLine] = &[
+    ("OperatingRevenue", "Revenue from Operations", false),
+    ("TotalRevenue", "Total Income", true),
+    ("CostOfRevenue", "Cost of Materials Consumed", false),
+    ("GrossProfit", "Gross Profit", true),
+    ("OperatingExpense", "Operating Expenses", false),
+    ("EBITDA", "EBITDA", true),
+    ("OperatingIncome", "Operating Profit", true),
+    ("InterestExpense", "Finance Costs", false),
+    ("ReconciledDepreciation", "Depreciation and Amortisation Expense", false),
+    ("PretaxIncome", "Profit Before Tax", true),
+    ("TaxProvision", "Tax Expense", false),
+    ("NetIncome", "Profit After Tax", true),
+    ("BasicEPS", "Basic EPS", false),
+    ("DilutedEPS", "Diluted EPS", false),
+];
+
+const BALANCE_LINES: &[StatementLine] = &[
+    ("NetPPE", "Property, Plant and Equipment", false),
+    ("Goodwill", "Goodwill", false),
+    ("Inventory", "Inventories", false),
+    ("AccountsReceivable", "Trade Receivables", false),
+    ("CashAndCashEquivalents", "Cash and Cash Equivalents", false),
+    ("CurrentAssets", "Current Assets", true),
+    ("TotalAssets", "Total Assets", true),
+    ("AccountsPayable", "Trade Payables", false),
+    ("CurrentLiabilities", "Current Liabilities", true),
+    ("LongTermDebt", "Long Term Borrowings", false),
+    ("TotalDebt", "Total Borrowings", true),
+    ("TotalLiabilitiesNetMinorityInterest", "Total Liabilities", true),
+    ("ShareIssued", "Shares Outstanding", false),
+    ("RetainedEarnings", "Retained Earnings", false),
+    ("StockholdersEquity", "Total Equity", true),
+];
+
+const CASH_LINES: &[StatementLine] = &[
+    ("OperatingCashFlow", "Net Cash from Operating Activities", true),
+    ("CapitalExpenditure", "Capital Expenditure", false),
+    ("InvestingCashFlow", "Net Cash used in Investing Activities", true),
+    ("CashDividendsPaid", "Dividends Paid", false),
+    ("FinancingCashFlow", "Net Cash used in Financing Activities", true),
+    ("FreeCashFlow", "Free Cash Flow", true),
+    ("EndCashPosition", "Cash and Cash Equivalents at End of Year", true),
+];
+
+const STATEMENTS: &StatementLayout = &[
+    ("income_statement", INCOME_LINES),
+    ("balance_sheet", BALANCE_LINES),
+    ("cash_flow", CASH_LINES),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedStatements {
+    pub doc_id: i64,
+    pub filename: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub fiscal_years: Vec<String>,
+    pub item_count: usize,
+    pub items: Vec<serde_json::Value>,
+}
+
+/// Fiscal year label for a period end date: Indian FYs end in March, so 2024-03-31 is "2024".
+fn fiscal_year(as_of_date: &str) -> Option<String> {
+    as_of_date.get(..4).map(|y| y.to_string())
+}
+
+/// Collect `series -> (fiscal year -> value)` from the timeseries results.
+fn collect_series(results: &[serde_json::Value]) -> Series {
+    let mut series = BTreeMap::new();
+    for result in results {
+        let Some(kind) = result.pointer("/meta/type/0").and_then(|t| t.as_str()) else { continue };
+        let Some(points) = result.get(kind).and_then(|p| p.as_array()) else { continue };
+
+        let values: BTreeMap<String, f64> = points.iter()
+            .filter_map(|p| {
+                let year = fiscal_year(p.get("asOfDate")?.as_str()?)?;
+                let value = p.pointer("/reportedValue/raw").and_then(parse_number)?;
+                Some((year, value))
+            })
+            .collect();
+        if !values.is_empty() {
+            series.insert(kind.trim_start_matches("annual").to_string(), values);
+        }
+    }
+    series
+}
+
+/// One row to write into `financial_items`, whatever produced it.
+#[derive(Debug, Clone)]
+pub struct LineItem {
+    pub key: String,
+    pub label: String,
+    pub statement: String,
+    pub row_index: usize,
+    /// Period label -> value
+    pub values: BTreeMap<String, f64>,
+    pub is_total: bool,
+    pub is_header: bool,
+}
+
+/// The frontend's item shape (`method` fills extractionMethod); `periods` are sorted oldest first, so the last
+/// two are the current and previous year.
+fn item_json(doc_id: i64, line: &LineItem, periods: &[String], unit: &str, method: &str) -> serde_json::Value {
+    let current = periods.last();
+    let previous = periods.len().checked_sub(2).and_then(|i| periods.get(i));
+    // A year the line has no value for is null, not zero
+    let value_for = |period: Option<&String>| period.and_then(|p| line.values.get(p)).copied();
+    let current_year = value_for(current);
+    let previous_year = value_for(previous);
+    let variation = current_year.zip(previous_year).map(|(current, previous)| current - previous);
+    let variation_percent = variation.zip(previous_year.filter(|p| *p != 0.0))
+        .map(|(variation, previous)| variation / previous.abs() * 100.0);
+
+    serde_json::json!({
+        "id": format!("{}-{}-{}", doc_id, line.statement, line.key),
+        "label": line.label,
+        "normalizedLabel": line.label.to_lowercase(),
+        "currentYear": current_year,
+        "previousYear": previous_year,
+        "allYears": line.values,
+        "variation": variation,
+        "variationPercent": variation_percent,
+        "sourcePage": "",
+        "statementType": line.statement,
+        "confidence": 1.0,
+        "extractionMethod": method,
+        "rowIndex": line.row_index,
+        "isHeader": line.is_header,
+        "isTotal": line.is_total,
+        "isNegative": current_year.is_some_and(|v| v < 0.0),
+        "unit": unit,
+        "isIndAS": true,
+    })
+}
+
+fn layout_lines(layout: &StatementLayout, series: &Series) -> Vec<LineItem> {
+    let mut lines = Vec::new();
+    for (statement, entries) in layout {
+        for (row_index, (key, label, is_total)) in entries.iter().enumerate() {
+            let Some(values) = series.get(*key) else { continue };
+            lines.push(LineItem {
+                key: key.to_string(),
+                label: label.to_string(),
+                statement: statement.to_string(),
+                row_index,
+                values: values.clone(),
+                is_total: *is_total,
+                is_header: false,
+            });
+        }
+    }
+    lines
+}
+
+/// Sorted period labels present in any series, trimmed to the most recent `keep`,
+/// with the series filtered to match.
+pub fn latest_periods(series: Series, keep: Option<usize>) -> (Series, Vec<String>) {
+    let mut periods: Vec<String> = series.values()
+        .flat_map(|values| values.keys().cloned())
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    if let Some(keep) = keep.filter(|k| *k > 0) {
+        periods = periods.split_off(periods.len().saturating_sub(keep));
+    }
+    let series = series.into_iter()
+        .map(|(key, values)| {
+            let values = values.into_iter().filter(|(p, _)| periods.contains(p)).collect();
+            (key, values)
+        })
+        .collect();
+    (series, periods)
+}
+
+/// Insert a document row into the active pipeline DB, then its items keyed by the new document id.
+pub fn save_document(
+    app: &AppHandle,
+    filename: &str,
+    metadata: &serde_json::Value,
+    lines: &[LineItem],
+    periods: &[String],
+    unit: &str,
+    method: &str,
+) -> Result<(i64, Vec<serde_json::Value>), String> {
+    let mut conn = db::open_pipeline_db(app)?;
+    conn.execute_batch(PIPELINE_SCHEMA).map_err(|e| e.to_string())?;
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO documents (filename, metadata) VALUES (?1, ?2)",
+        params![filename, metadata.to_string()],
+    ).map_err(|e| e.to_string())?;
+    let doc_id = tx.last_insert_rowid();
+
+    let items: Vec<serde_json::Value> = lines.iter().map(|line| item_json(doc_id, line, periods, unit, method)).collect();
+    for item in &items {
+        tx.execute(
+            "INSERT OR REPLACE INTO financial_items (
+                id, doc_id, label, value_current, value_previous, row_index, statement_type,
+                is_header, source_page, source_line_text, confidence, original_json
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 0, '', 1.0, ?9)",
+            params![
+                item["id"].as_str(),
+                doc_id,
+                item["label"].as_str(),
+                item["currentYear"].as_f64(),
+                item["previousYear"].as_f64(),
+                item["rowIndex"].as_i64(),
+                item["statementType"].as_str().map(|s| s.to_uppercase()),
+                item["isHeader"].as_bool(),
+                item.to_string(),
+            ],
+        ).map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok((doc_id, items))
+}
+
+/// A document row from the pipeline DB with its items in the frontend shape.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredDocument {
+    pub id: i64,
+    pub filename: String,
+    pub processed_at: Option<String>,
+    pub metadata: serde_json::Value,
+    /// In extraction order
+    pub items: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentSummary {
+    pub id: i64,
+    pub filename: String,
+    pub processed_at: Option<String>,
+    pub item_count: usize,
+}
+
+/// Position in `financial_items` keyset order: the sort key of the last row already seen.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemCursor {
+    pub doc_id: i64,
+    pub row_index: i64,
+    pub id: String,
+}
+
+/// A `financial_items` row as the Raw DB view shows it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawItem {
+    pub id: String,
+    pub doc_id: Option<i64>,
+    pub label: Option<String>,
+    pub value_current: Option<f64>,
+    pub value_previous: Option<f64>,
+    pub row_index: Option<i64>,
+    pub statement_type: Option<String>,
+    pub is_header: Option<bool>,
+    pub source_page: Option<i64>,
+    pub confidence: Option<f64>,
+}
+
+/// Which `financial_items` rows to page through. Items with no confidence recorded only match
+/// when neither bound is set.
+#[derive(Debug, Clone)]
+struct ItemFilter {
+    doc_id: Option<i64>,
+    min_confidence: Option<f64>,
+    max_confidence: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ItemPage {
+    pub items: Vec<RawItem>,
+    /// Pass back to get the following page; None once the end is reached
+    pub next_cursor: Option<ItemCursor>,
+    /// Matching rows overall, counted on the first page only
+    pub total: Option<i64>,
+}
+
+/// Stored documents in the active profile, newest first.
+pub fn list_documents(app: &AppHandle) -> Result<Vec<DocumentSummary>, String> {
+    let conn = db::open_pipeline_db(app)?;
+    conn.execute_batch(PIPELINE_SCHEMA).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(
+        "SELECT d.id, d.filename, d.processed_at, (SELECT COUNT(*) FROM financial_items f WHERE f.doc_id = d.id)
+         FROM documents d ORDER BY d.id DESC"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], |row| {
+        Ok(DocumentSummary {
+            id: row.get(0)?,
+            filename: row.get(1)?,
+            processed_at: row.get(2)?,
+            item_count: row.get::<_, i64>(3)? as usize,
+        })
+    }).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// The most recently stored document, e.g. the one a Python parse just wrote.
+pub fn latest_document_id(app: &AppHandle) -> Result<i64, String> {
+    let conn = db::open_pipeline_db(app)?;
+    conn.execute_batch(PIPELINE_SCHEMA).map_err(|e| e.to_string())?;
+    conn.query_row("SELECT MAX(id) FROM documents", [], |row| row.get::<_, Option<i64>>(0))
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "No document was stored".to_string())
+}
+
+/// A stored document with its figures converted to the configured currency and scale (see
+/// `units`) and keyed by fiscal period (see `periods`), so documents compare with each other,
+/// with the user's metric formulas computed on it.
+pub fn load_document(app: &AppHandle, doc_id: i64) -> Result<StoredDocument, String> {
+    let mut document = load_stored_document(app, doc_id)?;
+    units::normalize(app, &mut document)?;
+    periods::apply(app, &mut document)?;
+    metric_formulas::apply(app, &mut document)?;
+    Ok(document)
+}
+
+/// A stored document with its figures as extracted.
+pub fn load_stored_document(app: &AppHandle, doc_id: i64) -> Result<StoredDocument, String> {
+    let conn = db::open_pipeline_db(app)?;
+    conn.execute_batch(PIPELINE_SCHEMA).map_err(|e| e.to_string())?;
+
+    let (filename, processed_at, metadata): (String, Option<String>, Option<String>) = conn.query_row(
+        "SELECT filename, processed_at, metadata FROM documents WHERE id = ?1",
+        params![doc_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    ).map_err(|_| format!("Document {} not found", doc_id))?;
+
+    let mut stmt = conn.prepare("SELECT original_json FROM financial_items WHERE doc_id = ?1 ORDER BY rowid")
+        .map_err(|e| e.to_string())?;
+    let items = stmt.query_map(params![doc_id], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .filter_map(|json| json.ok().and_then(|json| serde_json::from_str(&json).ok()))
+        .collect();
+    Ok(StoredDocument {
+        id: doc_id,
+        filename,
+        processed_at,
+        metadata: metadata.and_then(|m| serde_json::from_str(&m).ok()).unwrap_or(serde_json::Value::Null),
+        items,
+    })
+}
+
+/// Text the parser kept for one page (python/database.py `text_chunks`); empty if none was stored.
+pub fn page_text(app: &AppHandle, doc_id: i64, page: u32) -> Result<Vec<String>, String> {
+    let conn = db::open_pipeline_db(app)?;
+    // Missing until the Python pipeline or `save_text_chunks` has created it
+    let Ok(mut stmt) = conn.prepare("SELECT content FROM text_chunks WHERE doc_id = ?1 AND page_num = ?2 ORDER BY chunk_index") else {
+        return Ok(Vec::new());
+    };
+    let rows = stmt.query_map(params![doc_id, page], |row| row.get::<_, String>(0)).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Store parser text for RAG in fixed-size chunks, as python/database.py `save_text_chunks` does.
+pub fn save_text_chunks(app: &AppHandle, doc_id: i64, text: &str) -> Result<(), String> {
+    let mut conn = db::open_pipeline_db(app)?;
+    conn.execute_batch(TEXT_CHUNKS_SCHEMA).map_err(|e| e.to_string())?;
+    let chars: Vec<char> = text.chars().collect();
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for (index, chunk) in chars.chunks(TEXT_CHUNK_CHARS).enumerate() {
+        tx.execute(
+            "INSERT INTO text_chunks (doc_id, chunk_index, content) VALUES (?1, ?2, ?3)",
+            params![doc_id, index as i64, chunk.iter().collect::<String>()],
+        ).map_err(|e| e.to_string())?;
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Give `from`'s stored text to `to`, replacing whatever text `to` had.
+pub fn move_text_chunks(app: &AppHandle, from: i64, to: i64) -> Result<(), String> {
+    let mut conn = db::open_pipeline_db(app)?;
+    conn.execute_batch(TEXT_CHUNKS_SCHEMA).map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM text_chunks WHERE doc_id = ?1", params![to]).map_err(|e| e.to_string())?;
+    tx.execute("UPDATE text_chunks SET doc_id = ?1 WHERE doc_id = ?2", params![to, from]).map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Write items in the frontend shape as-is. Row keys are prefixed with the document id because
+/// items from other devices (sync) may reuse ids that already exist here.
+fn store_items(tx: &rusqlite::Transaction, doc_id: i64, items: &[serde_json::Value]) -> Result<(), String> {
+    for (index, item) in items.iter().enumerate() {
+        let mut item = item.clone();
+        numbers::normalize_item(&mut item);
+        let key = item["id"].as_str().map(str::to_string).unwrap_or_else(|| index.to_string());
+        tx.execute(
+            "INSERT OR REPLACE INTO financial_items (
+                id, doc_id, label, value_current, value_previous, row_index, statement_type,
+                is_header, source_page, source_line_text, confidence, original_json
+             ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                format!("{}:{}", doc_id, key),
+                doc_id,
+                item["label"].as_str(),
+                item["currentYear"].as_f64(),
+                item["previousYear"].as_f64(),
+                item["rowIndex"].as_i64().unwrap_or(index as i64),
+                item["statementType"].as_str().map(|s| s.to_uppercase()),
+                item["isHeader"].as_bool(),
+                page_ranges::item_page(&item).unwrap_or(0),
+                item["rawLine"].as_str().unwrap_or(""),
+                item["confidence"].as_f64().unwrap_or(1.0),
+                item.to_string(),
+            ],
+        ).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Store a document whose items are already in the frontend shape, e.g. one pulled from Supabase.
+pub fn insert_document(
+    app: &AppHandle,
+    filename: &str,
+    metadata: &serde_json::Value,
+    items: &[serde_json::Value],
+) -> Result<i64, String> {
+    let mut conn = db::open_pipeline_db(app)?;
+    conn.execute_batch(PIPELINE_SCHEMA).map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO documents (filename, metadata) VALUES (?1, ?2)",
+        params![filename, metadata.to_string()],
+    ).map_err(|e| e.to_string())?;
+    let doc_id = tx.last_insert_rowid();
+    store_items(&tx, doc_id, items)?;
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(doc_id)
+}
+
+/// Overwrite a stored document's filename, metadata and items in one transaction.
+pub fn replace_document(
+    app: &AppHandle,
+    doc_id: i64,
+    filename: &str,
+    metadata: &serde_json::Value,
+    items: &[serde_json::Value],
+) -> Result<(), String> {
+    let mut conn = db::open_pipeline_db(app)?;
+    conn.execute_batch(PIPELINE_SCHEMA).map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let updated = tx.execute(
+        "UPDATE documents SET filename = ?1, metadata = ?2 WHERE id = ?3",
+        params![filename, metadata.to_string(), doc_id],
+    ).map_err(|e| e.to_string())?;
+    if updated == 0 {
+        return Err(format!("Document {} not found", doc_id));
+    }
+    tx.execute("DELETE FROM financial_items WHERE doc_id = ?1", params![doc_id]).map_err(|e| e.to_string())?;
+    store_items(&tx, doc_id, items)?;
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// Checks to run whenever a document lands in the DB, whatever stored it.
+pub fn document_stored(app: &AppHandle, doc_id: i64) {
+    duplicates::notify(app, doc_id);
+    ratio_alerts::check(app, doc_id);
+    anomalies::check(app, doc_id);
+    derived::document_stored(app, doc_id);
+}
+
+/// Remove a stored document with its items, text and extraction checklist.
+pub fn delete_document(app: &AppHandle, doc_id: i64) -> Result<(), String> {
+    let mut conn = db::open_pipeline_db(app)?;
+    conn.execute_batch(PIPELINE_SCHEMA).map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM financial_items WHERE doc_id = ?1", params![doc_id]).map_err(|e| e.to_string())?;
+    // Created by the Python pipeline or on first use, so not every database has them
+    for table in ["text_chunks", "extraction_checklist", "chunks"] {
+        let exists: bool = tx.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+            params![table],
+            |row| row.get(0),
+        ).map_err(|e| e.to_string())?;
+        if exists {
+            tx.execute(&format!("DELETE FROM {} WHERE doc_id = ?1", table), params![doc_id]).map_err(|e| e.to_string())?;
+        }
+    }
+    let deleted = tx.execute("DELETE FROM documents WHERE id = ?1", params![doc_id]).map_err(|e| e.to_string())?;
+    if deleted == 0 {
+        return Err(format!("Document {} not found", doc_id));
+    }
+    tx.commit().map_err(|e| e.to_string())
+}
+
+/// `save_document` for series laid out by a fixed statement layout.
+pub fn save_statements(
+    app: &AppHandle,
+    filename: &str,
+    metadata: &serde_json::Value,
+    layout: &StatementLayout,
+    series: &Series,
+    periods: &[String],
+    currency: &str,
+) -> Result<(i64, Vec<serde_json::Value>), String> {
+    save_document(app, filename, metadata, &layout_lines(layout, series), periods, currency, "api")
+}
+
+fn raw_item(row: &rusqlite::Row) -> rusqlite::Result<RawItem> {
+    Ok(RawItem {
+        id: row.get(0)?,
+        doc_id: row.get(1)?,
+        label: row.get(2)?,
+        value_current: row.get(3)?,
+        value_previous: row.get(4)?,
+        row_index: row.get(5)?,
+        statement_type: row.get(6)?,
+        is_header: row.get(7)?,
+        source_page: row.get(8)?,
+        confidence: row.get(9)?,
+    })
+}
+
+/// Up to `limit` items after `cursor` (from the start when None), ordered by document, row
+/// index and id. Keyset rather than OFFSET paging, so deep pages cost the same as the first.
+fn items_after(app: &AppHandle, cursor: Option<&ItemCursor>, limit: usize, filter: &ItemFilter) -> Result<ItemPage, String> {
+    let conn = db::open_pipeline_db(app)?;
+    conn.execute_batch(PIPELINE_SCHEMA).map_err(|e| e.to_string())?;
+
+    let total = match cursor {
+        Some(_) => None,
+        None => Some(conn.query_row(
+            "SELECT COUNT(*) FROM financial_items
+             WHERE (?1 IS NULL OR doc_id = ?1) AND (?2 IS NULL OR confidence >= ?2) AND (?3 IS NULL OR confidence < ?3)",
+            params![filter.doc_id, filter.min_confidence, filter.max_confidence],
+            |row| row.get(0),
+        ).map_err(|e| e.to_string())?),
+    };
+
+    let mut stmt = conn.prepare(
+        "SELECT id, doc_id, label, value_current, value_previous, row_index, statement_type, is_header, source_page, confidence
+         FROM financial_items
+         WHERE (?1 IS NULL OR doc_id = ?1)
+           AND (?2 IS NULL OR (IFNULL(doc_id, 0), IFNULL(row_index, 0), id) > (?2, ?3, ?4))
+           AND (?6 IS NULL OR confidence >= ?6)
+           AND (?7 IS NULL OR confidence < ?7)
+         ORDER BY IFNULL(doc_id, 0), IFNULL(row_index, 0), id
+         LIMIT ?5"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(
+        params![
+            filter.doc_id,
+            cursor.map(|c| c.doc_id),
+            cursor.map(|c| c.row_index),
+            cursor.map(|c| c.id.as_str()),
+            limit as i64,
+            filter.min_confidence,
+            filter.max_confidence,
+        ],
+        raw_item,
+    ).map_err(|e| e.to_string())?;
+    let items = rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?;
+
+    let next_cursor = match items.last() {
+        Some(last) if items.len() == limit => Some(ItemCursor {
+            doc_id: last.doc_id.unwrap_or(0),
+            row_index: last.row_index.unwrap_or(0),
+            id: last.id.clone(),
+        }),
+        _ => None,
+    };
+    Ok(ItemPage { items, next_cursor, total })
+}
+
+// Tauri Commands
+/// One page of `financial_items` for the Raw DB view's virtualized table, optionally for one
+/// document and within a confidence range (`min_confidence` inclusive, `max_confidence` not).
+#[tauri::command]
+pub fn get_items_after(
+    app: AppHandle,
+    cursor: Option<ItemCursor>,
+    limit: Option<usize>,
+    doc_id: Option<i64>,
+    min_confidence: Option<f64>,
+    max_confidence: Option<f64>,
+) -> Result<ItemPage, String> {
+    let limit = limit.unwrap_or(ITEM_PAGE_ROWS).clamp(1, MAX_ITEM_PAGE_ROWS);
+    let filter = ItemFilter { doc_id, min_confidence, max_confidence };
+    items_after(&app, cursor.as_ref(), limit, &filter)
+}
+
+/// Extracted items scored below `threshold` (default 0.65), least confident first, so the shaky
+/// extractions can be reviewed on their own. Headers and items with no score are left out.
+#[tauri::command]
+pub fn list_low_confidence_items(
+    app: AppHandle,
+    threshold: Option<f64>,
+    doc_id: Option<i64>,
+    limit: Option<usize>,
+) -> Result<Vec<RawItem>, String> {
+    let threshold = threshold.unwrap_or(LOW_CONFIDENCE);
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err(format!("Confidence threshold must be between 0 and 1, got {}", threshold));
+    }
+    let conn = db::open_pipeline_db(&app)?;
+    conn.execute_batch(PIPELINE_SCHEMA).map_err(|e| e.to_string())?;
+    let mut stmt = conn.prepare(
+        "SELECT id, doc_id, label, value_current, value_previous, row_index, statement_type, is_header, source_page, confidence
+         FROM financial_items
+         WHERE confidence < ?1 AND (?2 IS NULL OR doc_id = ?2) AND NOT IFNULL(is_header, 0)
+         ORDER BY confidence, IFNULL(doc_id, 0), IFNULL(row_index, 0), id
+         LIMIT ?3"
+    ).map_err(|e| e.to_string())?;
+    let limit = limit.unwrap_or(MAX_ITEM_PAGE_ROWS).clamp(1, MAX_ITEM_PAGE_ROWS);
+    let rows = stmt.query_map(params![threshold, doc_id, limit as i64], raw_item).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn import_listed_financials(
+    app: AppHandle,
+    native: tauri::State<'_, NativeScraper>,
+    symbol: String,
+    exchange: String,
+    years: Option<usize>,
+) -> Result<ImportedStatements, String> {
+    settings::ensure_online(&app)?;
+    let symbol = symbol.trim().to_uppercase();
+    let exchange = exchange.trim().to_uppercase();
+    let ticker = scraper::yahoo_ticker(&symbol, &exchange)?;
+
+    let types: Vec<String> = STATEMENTS.iter()
+        .flat_map(|(_, lines)| lines.iter().map(|(key, _, _)| format!("annual{}", key)))
+        .collect();
+    eprintln!("[Statements] Downloading {} series for {}", types.len(), ticker);
+    let results = native.yahoo_timeseries(&ticker, &types, scraper::request_timeout(&app)).await?;
+
+    let (series, fiscal_years) = latest_periods(collect_series(&results), years);
+    if fiscal_years.is_empty() {
+        return Err(format!("No financial statements found for {}", ticker));
+    }
+
+    let filename = format!("{} ({}) - Yahoo Finance", symbol, exchange);
+    let metadata = serde_json::json!({
+        "fileName": filename,
+        "source": "yahoo_finance",
+        "symbol": symbol,
+        "exchange": exchange,
+        "fiscalYears": fiscal_years,
+        "currency": "INR",
+        "parser": "listed_company_import",
+    });
+
+    let (doc_id, items) = save_statements(&app, &filename, &metadata, STATEMENTS, &series, &fiscal_years, "INR")?;
+
+    eprintln!("[Statements] Imported {} items for {} as document {}", items.len(), ticker, doc_id);
+    document_stored(&app, doc_id);
+    activity::record(&app, ActivityKind::DocumentImported, Some(doc_id), &format!("Imported {}", filename), None);
+    Ok(ImportedStatements {
+        doc_id,
+        filename,
+        symbol,
+        exchange,
+        fiscal_years,
+        item_count: items.len(),
+        items,
+    })
+}
@@ -0,0 +1,242 @@
+// Symbols - ISIN <-> NSE symbol <-> BSE scrip code mapping, cached locally
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::db;
+use crate::scraper::{self, NativeScraper};
+use crate::settings;
+
+const NSE_EQUITY_LIST_URL: &str = "https://archives.nseindia.com/content/equities/EQUITY_L.csv";
+const BSE_SCRIP_LIST_PATH: &str = "/ListofScripData/w?Group=&Scripcode=&industry=&segment=Equity&status=Active";
+
+// Listings change slowly; refresh the map weekly
+const MAP_STALE_AFTER_SECS: i64 = 7 * 24 * 60 * 60;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS symbol_map (
+    isin TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    nse_symbol TEXT,
+    bse_code TEXT,
+    bse_symbol TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_symbol_map_nse ON symbol_map(nse_symbol);
+CREATE INDEX IF NOT EXISTS idx_symbol_map_bse ON symbol_map(bse_code);
+CREATE INDEX IF NOT EXISTS idx_symbol_map_bse_symbol ON symbol_map(bse_symbol);
+CREATE TABLE IF NOT EXISTS symbol_map_meta (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    fetched_at INTEGER NOT NULL
+);
+";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedSymbol {
+    pub isin: String,
+    pub name: String,
+    pub nse_symbol: Option<String>,
+    pub bse_code: Option<String>,
+    pub bse_symbol: Option<String>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn is_isin(value: &str) -> bool {
+    value.len() == 12 && value.starts_with("IN") && value.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Split one CSV line, honouring double-quoted fields (company names contain commas).
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    fields.push(current);
+    fields.into_iter().map(|f| f.trim().to_string()).collect()
+}
+
+/// EQUITY_L.csv: SYMBOL, NAME OF COMPANY, SERIES, DATE OF LISTING, PAID UP VALUE, MARKET LOT, ISIN NUMBER, FACE VALUE
+fn parse_nse_list(text: &str) -> Vec<(String, String, String)> {
+    text.lines()
+        .skip(1)
+        .map(split_csv_line)
+        .filter(|f| f.len() >= 7 && is_isin(&f[6]))
+        .map(|f| (f[6].clone(), f[0].to_uppercase(), f[1].clone()))
+        .collect()
+}
+
+fn parse_bse_list(data: &serde_json::Value) -> Vec<(String, String, String, String)> {
+    let field = |row: &serde_json::Value, key: &str| row.get(key)
+        .and_then(|v| v.as_str().map(|s| s.trim().to_string()).or_else(|| v.as_u64().map(|n| n.to_string())))
+        .filter(|s| !s.is_empty());
+
+    data.as_array().cloned().unwrap_or_default().iter()
+        .filter_map(|row| {
+            let isin = field(row, "ISIN_NUMBER").filter(|i| is_isin(i))?;
+            Some((
+                isin,
+                field(row, "SCRIP_CD")?,
+                field(row, "scrip_id").unwrap_or_default().to_uppercase(),
+                field(row, "Scrip_Name").or_else(|| field(row, "Issuer_Name")).unwrap_or_default(),
+            ))
+        })
+        .collect()
+}
+
+fn map_is_fresh(conn: &Connection) -> Result<bool, String> {
+    let fetched_at: Option<i64> = conn.query_row("SELECT fetched_at FROM symbol_map_meta WHERE id = 1", [], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())?;
+    Ok(fetched_at.is_some_and(|t| now_secs() - t < MAP_STALE_AFTER_SECS))
+}
+
+/// Rebuild the map from both exchange lists. Either list failing is tolerated as
+/// long as the other one arrives; rows are merged on ISIN.
+async fn refresh_map(app: &AppHandle, force: bool) -> Result<(), String> {
+    if !force && map_is_fresh(&db::open_app_db(app)?)? {
+        return Ok(());
+    }
+    settings::ensure_online(app)?;
+
+    let native = app.state::<NativeScraper>();
+    let timeout = scraper::request_timeout(app) * 3;
+    eprintln!("[Symbols] Downloading NSE and BSE listings");
+    let (nse, bse) = futures_util::join!(
+        native.get_text(NSE_EQUITY_LIST_URL, timeout),
+        native.bse_get_json(BSE_SCRIP_LIST_PATH, timeout),
+    );
+    let nse = nse.map(|t| parse_nse_list(&t)).unwrap_or_else(|e| {
+        eprintln!("[Symbols] NSE listing unavailable: {}", e);
+        Vec::new()
+    });
+    let bse = bse.map(|d| parse_bse_list(&d)).unwrap_or_else(|e| {
+        eprintln!("[Symbols] BSE listing unavailable: {}", e);
+        Vec::new()
+    });
+    if nse.is_empty() && bse.is_empty() {
+        return Err("Could not download exchange listings".to_string());
+    }
+
+    let mut conn = db::open_app_db(app)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for (isin, symbol, name) in &nse {
+        tx.execute(
+            "INSERT INTO symbol_map (isin, name, nse_symbol) VALUES (?1, ?2, ?3)
+             ON CONFLICT(isin) DO UPDATE SET name = excluded.name, nse_symbol = excluded.nse_symbol",
+            params![isin, name, symbol],
+        ).map_err(|e| e.to_string())?;
+    }
+    for (isin, code, symbol, name) in &bse {
+        // Keep NSE's company name when both exchanges list the ISIN
+        tx.execute(
+            "INSERT INTO symbol_map (isin, name, bse_code, bse_symbol) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(isin) DO UPDATE SET bse_code = excluded.bse_code, bse_symbol = excluded.bse_symbol",
+            params![isin, name, code, (!symbol.is_empty()).then_some(symbol)],
+        ).map_err(|e| e.to_string())?;
+    }
+    tx.execute("INSERT OR REPLACE INTO symbol_map_meta (id, fetched_at) VALUES (1, ?1)", params![now_secs()])
+        .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+
+    eprintln!("[Symbols] Mapped {} NSE and {} BSE listings", nse.len(), bse.len());
+    Ok(())
+}
+
+fn row_to_symbol(row: &rusqlite::Row) -> rusqlite::Result<ResolvedSymbol> {
+    Ok(ResolvedSymbol {
+        isin: row.get(0)?,
+        name: row.get(1)?,
+        nse_symbol: row.get(2)?,
+        bse_code: row.get(3)?,
+        bse_symbol: row.get(4)?,
+    })
+}
+
+const SYMBOL_COLUMNS: &str = "isin, name, nse_symbol, bse_code, bse_symbol";
+
+/// Identifier lookup against the local map only; name search is a separate step.
+fn lookup_exact(conn: &Connection, identifier: &str) -> Result<Option<ResolvedSymbol>, String> {
+    conn.query_row(
+        &format!(
+            "SELECT {} FROM symbol_map WHERE isin = ?1 OR nse_symbol = ?1 OR bse_code = ?1 OR bse_symbol = ?1
+             ORDER BY (isin = ?1) DESC, (nse_symbol = ?1) DESC LIMIT 1",
+            SYMBOL_COLUMNS
+        ),
+        params![identifier],
+        row_to_symbol,
+    ).optional().map_err(|e| e.to_string())
+}
+
+/// The identifier an exchange's API expects (NSE symbol / BSE scrip code) for whatever
+/// the user typed. Uses the local map only, so it never blocks on a download;
+/// unknown identifiers pass through unchanged.
+pub fn exchange_identifier(app: &AppHandle, identifier: &str, exchange: &str) -> String {
+    let identifier = identifier.trim().to_uppercase();
+    let resolved = db::open_app_db(app).ok()
+        .and_then(|conn| lookup_exact(&conn, &identifier).ok().flatten());
+
+    let mapped = resolved.and_then(|r| match exchange.trim().to_uppercase().as_str() {
+        "NSE" => r.nse_symbol,
+        "BSE" => r.bse_code,
+        _ => None,
+    });
+    mapped.unwrap_or(identifier)
+}
+
+/// Company name for a symbol / scrip code / ISIN from the local map, if known.
+pub fn company_name(app: &AppHandle, identifier: &str) -> Option<String> {
+    let conn = db::open_app_db(app).ok()?;
+    lookup_exact(&conn, &identifier.trim().to_uppercase()).ok().flatten().map(|r| r.name)
+}
+
+// Tauri Commands
+#[tauri::command]
+pub async fn resolve_symbol(
+    app: AppHandle,
+    identifier: String,
+    limit: Option<usize>,
+    force_refresh: Option<bool>,
+) -> Result<Vec<ResolvedSymbol>, String> {
+    let identifier = identifier.trim().to_uppercase();
+    if identifier.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    if let Err(e) = refresh_map(&app, force_refresh.unwrap_or(false)).await {
+        // A stale map still answers most lookups
+        eprintln!("[Symbols] Refresh failed: {}", e);
+    }
+
+    let conn = db::open_app_db(&app)?;
+    if let Some(exact) = lookup_exact(&conn, &identifier)? {
+        return Ok(vec![exact]);
+    }
+
+    // Fall back to company name: every word must appear
+    let words: Vec<String> = identifier.split_whitespace().map(|w| format!("%{}%", w)).collect();
+    let conditions = (1..=words.len())
+        .map(|i| format!("UPPER(name) LIKE ?{}", i))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    let sql = format!(
+        "SELECT {} FROM symbol_map WHERE {} ORDER BY (nse_symbol IS NOT NULL AND bse_code IS NOT NULL) DESC, name LIMIT {}",
+        SYMBOL_COLUMNS, conditions, limit.unwrap_or(20)
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(rusqlite::params_from_iter(words.iter()), row_to_symbol)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
@@ -0,0 +1,510 @@
+// Sync - pushes documents, items and computed metrics to Supabase and pulls other devices' documents
+// through its REST API (PostgREST), using the URL and key from `supabase_config`.
+//
+// Each document gets a uuid in Supabase and a revision that every push bumps. Locally we remember
+// the revision and a content hash from the last sync, so a document changed on both sides since
+// then is a conflict, settled by `sync.conflict_policy`. Deletions are not synced: a document
+// deleted here stays in Supabase and is not pulled back.
+use reqwest::{Client, Method, RequestBuilder};
+use rusqlite::{params, OptionalExtension};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::{info, warn};
+
+use crate::db;
+use crate::http;
+use crate::ratios;
+use crate::realtime;
+use crate::settings::{self, ConflictPolicy, SettingsState, SupabaseConfig};
+use crate::statements::{self, StoredDocument};
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS sync_documents (
+    document_id INTEGER PRIMARY KEY,   -- pipeline DB documents.id
+    remote_id TEXT NOT NULL UNIQUE,    -- fincalc_documents.id
+    local_hash TEXT NOT NULL,          -- content at the last sync, to spot local edits
+    revision INTEGER NOT NULL,         -- remote revision at the last sync
+    synced_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS sync_state (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+";
+
+/// Run once in the Supabase SQL editor before the first sync.
+pub const REMOTE_SCHEMA: &str = "\
+create table if not exists fincalc_documents (
+  id uuid primary key,
+  filename text not null,
+  processed_at text,
+  metadata jsonb,
+  revision bigint not null default 1,
+  device_id text,
+  updated_at timestamptz not null default now()
+);
+create table if not exists fincalc_items (
+  document_id uuid not null references fincalc_documents(id) on delete cascade,
+  position integer not null,
+  item jsonb not null,
+  primary key (document_id, position)
+);
+create table if not exists fincalc_metrics (
+  document_id uuid not null references fincalc_documents(id) on delete cascade,
+  key text not null,
+  name text,
+  category text,
+  unit text,
+  current double precision,
+  previous double precision,
+  primary key (document_id, key)
+);
+";
+
+const DOCUMENTS_TABLE: &str = "fincalc_documents";
+const ITEMS_TABLE: &str = "fincalc_items";
+const METRICS_TABLE: &str = "fincalc_metrics";
+// PostgREST caps responses at 1000 rows by default
+const PAGE_SIZE: usize = 1000;
+const UPLOAD_CHUNK: usize = 500;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    pub started_at: i64,
+    pub finished_at: i64,
+    pub pushed: usize,
+    pub pulled: usize,
+    /// Documents changed on both sides, settled by the conflict policy
+    pub conflicts: usize,
+    pub unchanged: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncStatus {
+    /// A Supabase URL and key are set
+    pub configured: bool,
+    pub running: bool,
+    pub conflict_policy: ConflictPolicy,
+    pub device_id: Option<String>,
+    pub last_synced_at: Option<i64>,
+    pub last_error: Option<String>,
+    pub last_report: Option<SyncReport>,
+    pub synced_documents: usize,
+    /// Local documents never synced or changed since their last sync
+    pub pending_documents: usize,
+    /// SQL that creates the Supabase tables
+    pub setup_sql: String,
+}
+
+/// Guards against overlapping syncs.
+pub struct SyncEngine {
+    running: AtomicBool,
+}
+
+struct RunGuard<'a>(&'a AtomicBool);
+
+impl Drop for RunGuard<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+impl SyncEngine {
+    pub fn new() -> Self {
+        Self { running: AtomicBool::new(false) }
+    }
+
+    fn begin(&self) -> Option<RunGuard<'_>> {
+        self.running
+            .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+            .ok()
+            .map(|_| RunGuard(&self.running))
+    }
+
+    fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RemoteDocument {
+    id: String,
+    filename: String,
+    metadata: Option<serde_json::Value>,
+    revision: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoteItem {
+    item: serde_json::Value,
+}
+
+#[derive(Debug, Clone)]
+struct SyncedDocument {
+    document_id: i64,
+    remote_id: String,
+    local_hash: String,
+    revision: i64,
+}
+
+pub struct Supabase {
+    client: Client,
+    base: String,
+    key: String,
+}
+
+impl Supabase {
+    pub fn new(client: Client, config: &SupabaseConfig) -> Result<Self, String> {
+        let (url, key) = (config.url.trim(), config.key.trim());
+        if url.is_empty() || key.is_empty() {
+            return Err("Supabase is not configured; set its URL and key in Settings".to_string());
+        }
+        Ok(Self { client, base: format!("{}/rest/v1", url.trim_end_matches('/')), key: key.to_string() })
+    }
+
+    fn request(&self, method: Method, table: &str, query: &str) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}/{}?{}", self.base, table, query))
+            .timeout(REQUEST_TIMEOUT)
+            .header("apikey", &self.key)
+            .bearer_auth(&self.key)
+    }
+
+    async fn send(&self, request: RequestBuilder) -> Result<reqwest::Response, String> {
+        let response = request.send().await.map_err(|e| format!("Supabase request failed: {}", e))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body = response.text().await.unwrap_or_default();
+        Err(match status.as_u16() {
+            401 | 403 => format!("Supabase rejected the key ({}): {}", status, body),
+            // PGRST205: table not in the schema cache
+            404 if body.contains("PGRST205") || body.contains("does not exist") => {
+                "Supabase tables are missing; run the setup SQL from the sync settings first".to_string()
+            }
+            _ => format!("Supabase returned {}: {}", status, body),
+        })
+    }
+
+    pub async fn select<T: DeserializeOwned>(&self, table: &str, query: &str) -> Result<Vec<T>, String> {
+        let mut rows = Vec::new();
+        loop {
+            let page: Vec<T> = self
+                .send(self.request(Method::GET, table, &format!("{}&limit={}&offset={}", query, PAGE_SIZE, rows.len())))
+                .await?
+                .json()
+                .await
+                .map_err(|e| format!("Unexpected Supabase response: {}", e))?;
+            let done = page.len() < PAGE_SIZE;
+            rows.extend(page);
+            if done {
+                return Ok(rows);
+            }
+        }
+    }
+
+    pub async fn upsert(&self, table: &str, on_conflict: &str, rows: &[serde_json::Value]) -> Result<(), String> {
+        for chunk in rows.chunks(UPLOAD_CHUNK) {
+            let request = self
+                .request(Method::POST, table, &format!("on_conflict={}", on_conflict))
+                .header("Prefer", "resolution=merge-duplicates,return=minimal")
+                .json(chunk);
+            self.send(request).await?;
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, table: &str, query: &str) -> Result<(), String> {
+        self.send(self.request(Method::DELETE, table, query)).await.map(|_| ())
+    }
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+pub fn new_uuid() -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    getrandom::fill(&mut bytes).map_err(|e| e.to_string())?;
+    // Version 4, RFC 4122 variant
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    Ok(format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..]))
+}
+
+/// Only ever compared with hashes computed by this install, so the std hasher is enough.
+fn content_hash(document: &StoredDocument) -> String {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    document.filename.hash(&mut hasher);
+    for item in &document.items {
+        item.to_string().hash(&mut hasher);
+    }
+    format!("{:016x}", hasher.finish())
+}
+
+fn get_state(app: &AppHandle, key: &str) -> Result<Option<String>, String> {
+    let conn = db::open_app_db(app)?;
+    conn.query_row("SELECT value FROM sync_state WHERE key = ?1", params![key], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+fn set_state(app: &AppHandle, key: &str, value: &str) -> Result<(), String> {
+    let conn = db::open_app_db(app)?;
+    conn.execute("INSERT OR REPLACE INTO sync_state (key, value) VALUES (?1, ?2)", params![key, value])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Stable per-profile id recorded on pushed documents.
+pub fn device_id(app: &AppHandle) -> Result<String, String> {
+    if let Some(id) = get_state(app, "device_id")? {
+        return Ok(id);
+    }
+    let id = new_uuid()?;
+    set_state(app, "device_id", &id)?;
+    Ok(id)
+}
+
+fn synced_documents(app: &AppHandle) -> Result<Vec<SyncedDocument>, String> {
+    let conn = db::open_app_db(app)?;
+    let mut stmt = conn
+        .prepare("SELECT document_id, remote_id, local_hash, revision FROM sync_documents")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(SyncedDocument { document_id: row.get(0)?, remote_id: row.get(1)?, local_hash: row.get(2)?, revision: row.get(3)? })
+        })
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// The Supabase id a local document was last synced under.
+pub fn remote_document_id(app: &AppHandle, document_id: i64) -> Result<Option<String>, String> {
+    let conn = db::open_app_db(app)?;
+    conn.query_row("SELECT remote_id FROM sync_documents WHERE document_id = ?1", params![document_id], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+/// The local document synced with a Supabase one, if this device has it.
+pub fn local_document_id(app: &AppHandle, remote_id: &str) -> Result<Option<i64>, String> {
+    let conn = db::open_app_db(app)?;
+    conn.query_row("SELECT document_id FROM sync_documents WHERE remote_id = ?1", params![remote_id], |row| row.get(0))
+        .optional()
+        .map_err(|e| e.to_string())
+}
+
+fn record_synced(app: &AppHandle, document_id: i64, remote_id: &str, local_hash: &str, revision: i64) -> Result<(), String> {
+    let conn = db::open_app_db(app)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO sync_documents (document_id, remote_id, local_hash, revision, synced_at)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![document_id, remote_id, local_hash, revision, now_secs()],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+async fn push(app: &AppHandle, remote: &Supabase, document: &StoredDocument, remote_id: &str, revision: i64) -> Result<(), String> {
+    let row = json!({
+        "id": remote_id,
+        "filename": document.filename,
+        "processed_at": document.processed_at,
+        "metadata": document.metadata,
+        "revision": revision,
+        "device_id": device_id(app)?,
+        "updated_at": chrono::Utc::now().to_rfc3339(),
+    });
+    remote.upsert(DOCUMENTS_TABLE, "id", &[row]).await?;
+
+    let filter = format!("document_id=eq.{}", remote_id);
+    remote.delete(ITEMS_TABLE, &filter).await?;
+    let items: Vec<serde_json::Value> = document.items.iter()
+        .enumerate()
+        .map(|(position, item)| json!({ "document_id": remote_id, "position": position, "item": item }))
+        .collect();
+    remote.upsert(ITEMS_TABLE, "document_id,position", &items).await?;
+
+    remote.delete(METRICS_TABLE, &filter).await?;
+    let metrics: Vec<serde_json::Value> = ratios::for_document(document).into_iter()
+        .map(|ratio| json!({
+            "document_id": remote_id,
+            "key": ratio.key,
+            "name": ratio.name,
+            "category": ratio.category,
+            "unit": ratio.unit,
+            "current": ratio.current,
+            "previous": ratio.previous,
+        }))
+        .collect();
+    remote.upsert(METRICS_TABLE, "document_id,key", &metrics).await?;
+
+    record_synced(app, document.id, remote_id, &content_hash(document), revision)
+}
+
+/// Bring a remote document in, over `into` or as a new local document.
+async fn pull(app: &AppHandle, remote: &Supabase, document: &RemoteDocument, into: Option<i64>, filename: &str) -> Result<(), String> {
+    let items: Vec<serde_json::Value> = remote
+        .select::<RemoteItem>(ITEMS_TABLE, &format!("select=item&document_id=eq.{}&order=position", document.id))
+        .await?
+        .into_iter()
+        .map(|row| row.item)
+        .collect();
+    let metadata = document.metadata.clone().unwrap_or(serde_json::Value::Null);
+    let document_id = match into {
+        Some(document_id) => {
+            statements::replace_document(app, document_id, filename, &metadata, &items)?;
+            document_id
+        }
+        None => statements::insert_document(app, filename, &metadata, &items)?,
+    };
+    let stored = statements::load_stored_document(app, document_id)?;
+    record_synced(app, document_id, &document.id, &content_hash(&stored), document.revision)?;
+    statements::document_stored(app, document_id);
+    Ok(())
+}
+
+async fn run(app: &AppHandle) -> Result<SyncReport, String> {
+    settings::ensure_online(app)?;
+    let (config, policy) = {
+        let state = app.state::<SettingsState>();
+        let store = state.read().await;
+        (store.get().supabase_config.clone(), store.get().sync.conflict_policy)
+    };
+    let remote = Supabase::new(http::client(app), &config)?;
+    let mut report = SyncReport { started_at: now_secs(), ..Default::default() };
+
+    let remote_documents: HashMap<String, RemoteDocument> = remote
+        .select::<RemoteDocument>(DOCUMENTS_TABLE, "select=id,filename,metadata,revision&order=id")
+        .await?
+        .into_iter()
+        .map(|document| (document.id.clone(), document))
+        .collect();
+    let synced: HashMap<i64, SyncedDocument> = synced_documents(app)?.into_iter().map(|s| (s.document_id, s)).collect();
+    // Includes documents deleted here since, so they are not pulled back
+    let known_remote: HashSet<String> = synced.values().map(|s| s.remote_id.clone()).collect();
+
+    for summary in statements::list_documents(app)? {
+        let document = statements::load_stored_document(app, summary.id)?;
+        let Some(last) = synced.get(&document.id) else {
+            push(app, &remote, &document, &new_uuid()?, 1).await?;
+            report.pushed += 1;
+            continue;
+        };
+        let Some(current) = remote_documents.get(&last.remote_id) else {
+            // Removed from Supabase by hand; put it back
+            push(app, &remote, &document, &last.remote_id, last.revision + 1).await?;
+            report.pushed += 1;
+            continue;
+        };
+        let local_changed = content_hash(&document) != last.local_hash;
+        let remote_changed = current.revision != last.revision;
+        match (local_changed, remote_changed) {
+            (false, false) => report.unchanged += 1,
+            (true, false) => {
+                push(app, &remote, &document, &last.remote_id, current.revision + 1).await?;
+                report.pushed += 1;
+            }
+            (false, true) => {
+                pull(app, &remote, current, Some(document.id), &current.filename).await?;
+                report.pulled += 1;
+            }
+            (true, true) => {
+                report.conflicts += 1;
+                warn!(document = document.id, remote = %current.id, ?policy, "Sync conflict");
+                match policy {
+                    ConflictPolicy::PreferLocal => {
+                        push(app, &remote, &document, &last.remote_id, current.revision + 1).await?;
+                        report.pushed += 1;
+                    }
+                    ConflictPolicy::PreferRemote => {
+                        pull(app, &remote, current, Some(document.id), &current.filename).await?;
+                        report.pulled += 1;
+                    }
+                    ConflictPolicy::KeepBoth => {
+                        // The local version moves to a new remote document; the remote one comes in as a copy
+                        push(app, &remote, &document, &new_uuid()?, 1).await?;
+                        pull(app, &remote, current, None, &format!("{} (synced copy)", current.filename)).await?;
+                        report.pushed += 1;
+                        report.pulled += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    for document in remote_documents.values().filter(|d| !known_remote.contains(&d.id)) {
+        pull(app, &remote, document, None, &document.filename).await?;
+        report.pulled += 1;
+    }
+
+    report.finished_at = now_secs();
+    Ok(report)
+}
+
+// Tauri Commands
+/// Push local changes and pull other devices' documents, one sync at a time.
+#[tauri::command]
+pub async fn sync_now(app: AppHandle, engine: tauri::State<'_, SyncEngine>) -> Result<SyncReport, String> {
+    let _guard = engine.begin().ok_or("A sync is already running")?;
+    info!("Supabase sync started");
+    match run(&app).await {
+        Ok(report) => {
+            info!(pushed = report.pushed, pulled = report.pulled, conflicts = report.conflicts, "Supabase sync finished");
+            set_state(&app, "last_synced_at", &report.finished_at.to_string())?;
+            set_state(&app, "last_report", &serde_json::to_string(&report).map_err(|e| e.to_string())?)?;
+            set_state(&app, "last_error", "")?;
+            let _ = app.emit("sync-updated", &report);
+            Ok(report)
+        }
+        Err(e) => {
+            warn!("Supabase sync failed: {}", e);
+            set_state(&app, "last_error", &e)?;
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_sync_status(app: AppHandle, engine: tauri::State<'_, SyncEngine>) -> Result<SyncStatus, String> {
+    let (config, policy) = {
+        let state = app.state::<SettingsState>();
+        let store = settings::blocking_read(&state);
+        (store.get().supabase_config.clone(), store.get().sync.conflict_policy)
+    };
+    let synced: HashMap<i64, String> = synced_documents(&app)?.into_iter().map(|s| (s.document_id, s.local_hash)).collect();
+    let mut synced_count = 0;
+    let mut pending = 0;
+    for summary in statements::list_documents(&app)? {
+        match synced.get(&summary.id) {
+            Some(hash) if *hash == content_hash(&statements::load_stored_document(&app, summary.id)?) => synced_count += 1,
+            _ => pending += 1,
+        }
+    }
+    Ok(SyncStatus {
+        configured: !config.url.trim().is_empty() && !config.key.trim().is_empty(),
+        running: engine.is_running(),
+        conflict_policy: policy,
+        device_id: get_state(&app, "device_id")?,
+        last_synced_at: get_state(&app, "last_synced_at")?.and_then(|v| v.parse().ok()),
+        last_error: get_state(&app, "last_error")?.filter(|e| !e.is_empty()),
+        last_report: get_state(&app, "last_report")?.and_then(|r| serde_json::from_str(&r).ok()),
+        synced_documents: synced_count,
+        pending_documents: pending,
+        setup_sql: format!("{}{}", REMOTE_SCHEMA, realtime::REMOTE_SCHEMA),
+    })
+}
@@ -0,0 +1,229 @@
+// Tasks - registry of running background operations (analysis, DB streaming, model pulls)
+// with progress reporting and cooperative cancellation
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::notifications;
+
+// Finished tasks kept for the task list; older ones are dropped
+const MAX_TASK_HISTORY: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskKind {
+    Analysis,
+    DbStreaming,
+    ModelPull,
+    AiAnalysis,
+    ChatStream,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackgroundTask {
+    pub id: u64,
+    pub kind: TaskKind,
+    pub label: String,
+    pub status: TaskStatus,
+    /// 0-100 when the operation can tell how far along it is
+    pub progress: Option<f64>,
+    pub message: Option<String>,
+    /// Set once cancellation was asked for; the task stops at its next check
+    pub cancel_requested: bool,
+    pub error: Option<String>,
+    pub started_at: i64,
+    pub finished_at: Option<i64>,
+}
+
+struct Entry {
+    task: BackgroundTask,
+    cancel: Arc<AtomicBool>,
+}
+
+pub struct TaskRegistry {
+    tasks: Mutex<VecDeque<Entry>>,
+    next_id: AtomicU64,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+impl TaskRegistry {
+    pub fn new() -> Self {
+        Self {
+            tasks: Mutex::new(VecDeque::new()),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn list(&self) -> Vec<BackgroundTask> {
+        self.tasks.lock().map(|tasks| tasks.iter().map(|e| e.task.clone()).collect()).unwrap_or_default()
+    }
+
+    /// Apply `change` to a task and announce it with `background-task-updated`.
+    fn update(&self, app: &AppHandle, id: u64, change: impl FnOnce(&mut BackgroundTask)) -> Option<BackgroundTask> {
+        let task = {
+            let mut tasks = self.tasks.lock().ok()?;
+            let entry = tasks.iter_mut().find(|e| e.task.id == id)?;
+            change(&mut entry.task);
+            entry.task.clone()
+        };
+        let _ = app.emit("background-task-updated", &task);
+        Some(task)
+    }
+
+    /// Ask running tasks matching `filter` to stop; returns how many were asked.
+    fn request_cancel(&self, app: &AppHandle, filter: impl Fn(&BackgroundTask) -> bool) -> usize {
+        let ids: Vec<u64> = match self.tasks.lock() {
+            Ok(tasks) => tasks
+                .iter()
+                .filter(|e| e.task.status == TaskStatus::Running && filter(&e.task))
+                .map(|e| {
+                    e.cancel.store(true, Ordering::Relaxed);
+                    e.task.id
+                })
+                .collect(),
+            Err(_) => return 0,
+        };
+        for id in &ids {
+            self.update(app, *id, |t| t.cancel_requested = true);
+        }
+        ids.len()
+    }
+}
+
+/// Held by the code doing the work. Dropping it without `finish` (an early `?` return)
+/// records the task as failed, or cancelled if that was asked for.
+pub struct TaskHandle {
+    app: AppHandle,
+    id: u64,
+    cancel: Arc<AtomicBool>,
+    finished: bool,
+}
+
+impl TaskHandle {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    pub fn progress(&self, percent: f64, message: impl Into<String>) {
+        let message = message.into();
+        self.app.state::<TaskRegistry>().update(&self.app, self.id, |t| {
+            t.progress = Some(percent.clamp(0.0, 100.0));
+            t.message = Some(message);
+        });
+    }
+
+    pub fn finish<T, E: std::fmt::Display>(mut self, result: &Result<T, E>) {
+        self.finished = true;
+        self.close(result.as_ref().err().map(ToString::to_string));
+    }
+
+    fn close(&self, error: Option<String>) {
+        let cancelled = self.is_cancelled();
+        let finished = self.app.state::<TaskRegistry>().update(&self.app, self.id, |t| {
+            t.finished_at = Some(now_secs());
+            t.status = match (&error, cancelled) {
+                (_, true) => TaskStatus::Cancelled,
+                (Some(_), false) => TaskStatus::Failed,
+                (None, false) => TaskStatus::Completed,
+            };
+            if t.status == TaskStatus::Completed {
+                t.progress = Some(100.0);
+            }
+            t.error = error;
+        });
+        if let Some(task) = finished {
+            notifications::task_finished(&self.app, &task);
+        }
+    }
+}
+
+impl Drop for TaskHandle {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.close(Some("Task ended unexpectedly".to_string()));
+        }
+    }
+}
+
+/// Register a running operation; the returned handle reports progress and sees cancellation.
+pub fn register(app: &AppHandle, kind: TaskKind, label: &str) -> TaskHandle {
+    let registry = app.state::<TaskRegistry>();
+    let cancel = Arc::new(AtomicBool::new(false));
+    let task = BackgroundTask {
+        id: registry.next_id.fetch_add(1, Ordering::Relaxed),
+        kind,
+        label: label.to_string(),
+        status: TaskStatus::Running,
+        progress: None,
+        message: None,
+        cancel_requested: false,
+        error: None,
+        started_at: now_secs(),
+        finished_at: None,
+    };
+    if let Ok(mut tasks) = registry.tasks.lock() {
+        tasks.push_back(Entry { task: task.clone(), cancel: cancel.clone() });
+        while tasks.len() > MAX_TASK_HISTORY {
+            // Never drop a task that's still running
+            match tasks.iter().position(|e| e.task.status != TaskStatus::Running) {
+                Some(index) => { tasks.remove(index); }
+                None => break,
+            }
+        }
+    }
+    let _ = app.emit("background-task-updated", &task);
+    TaskHandle { app: app.clone(), id: task.id, cancel, finished: false }
+}
+
+/// Cancel every running task of one kind (e.g. stopping DB streaming).
+pub fn cancel_kind(app: &AppHandle, kind: TaskKind) -> usize {
+    app.state::<TaskRegistry>().request_cancel(app, |t| t.kind == kind)
+}
+
+/// Cancel running tasks of one kind with a given label, e.g. the chat stream of a closed window.
+pub fn cancel_labelled(app: &AppHandle, kind: TaskKind, label: &str) -> usize {
+    app.state::<TaskRegistry>().request_cancel(app, |t| t.kind == kind && t.label == label)
+}
+
+/// Cancel everything still running, e.g. when the app exits.
+pub fn cancel_all(app: &AppHandle) -> usize {
+    app.state::<TaskRegistry>().request_cancel(app, |_| true)
+}
+
+pub fn any_running(app: &AppHandle) -> bool {
+    app.state::<TaskRegistry>().list().iter().any(|t| t.status == TaskStatus::Running)
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn list_background_tasks(registry: tauri::State<'_, TaskRegistry>) -> Vec<BackgroundTask> {
+    registry.list()
+}
+
+#[tauri::command]
+pub fn cancel_task(app: AppHandle, id: u64) -> Result<(), String> {
+    let registry = app.state::<TaskRegistry>();
+    if registry.request_cancel(&app, |t| t.id == id) == 0 {
+        return Err(format!("No running task {}", id));
+    }
+    Ok(())
+}
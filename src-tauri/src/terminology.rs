@@ -0,0 +1,271 @@
+// Terminology - the user's label mappings (which statement labels mean which financial term),
+// owned here and sent to Python with every parse request
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use tracing::info;
+
+use crate::db;
+use crate::mapping_history;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS terminology_mappings (
+    key TEXT PRIMARY KEY,
+    label TEXT NOT NULL,
+    mapping TEXT NOT NULL,         -- JSON, the full entry as the frontend and Python know it
+    updated_at INTEGER NOT NULL
+);
+";
+
+// Below this a preview match is noise
+const MIN_PREVIEW_SCORE: f64 = 0.4;
+
+/// One term and the statement labels that mean it under each standard. Field names are the ones
+/// the frontend's `TermMapping` and python/terminology_keywords.py use.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TermMapping {
+    pub key: String,
+    pub label: String,
+    #[serde(default)]
+    pub keywords_indas: Vec<String>,
+    #[serde(default)]
+    pub keywords_gaap: Vec<String>,
+    #[serde(default)]
+    pub keywords_ifrs: Vec<String>,
+    /// Everything else the frontend keeps (id, category, description, aliases, ...), passed through
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl TermMapping {
+    /// Keywords from every standard, tagged with the standard they came from.
+    fn keywords(&self) -> impl Iterator<Item = (&'static str, &String)> {
+        self.keywords_indas.iter().map(|k| ("indas", k))
+            .chain(self.keywords_gaap.iter().map(|k| ("gaap", k)))
+            .chain(self.keywords_ifrs.iter().map(|k| ("ifrs", k)))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MappingMatch {
+    pub key: String,
+    pub label: String,
+    /// The keyword that matched best, and the standard it's listed under
+    pub keyword: String,
+    pub standard: String,
+    /// 1.0 for an exact match, down to `MIN_PREVIEW_SCORE`
+    pub score: f64,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn bigrams(text: &str) -> Vec<(char, char)> {
+    let chars: Vec<char> = text.chars().filter(|c| !c.is_whitespace()).collect();
+    chars.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+/// How well a statement label matches a keyword: exact, the keyword as whole words inside the
+/// label, or failing those the Dice coefficient of their character pairs.
+fn similarity(label: &str, keyword: &str) -> f64 {
+    let (label, keyword) = (normalize(label), normalize(keyword));
+    if label.is_empty() || keyword.is_empty() {
+        return 0.0;
+    }
+    if label == keyword {
+        return 1.0;
+    }
+    if format!(" {} ", label).contains(&format!(" {} ", keyword)) {
+        return 0.7 + 0.25 * keyword.len() as f64 / label.len() as f64;
+    }
+    let (a, b) = (bigrams(&label), bigrams(&keyword));
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let mut remaining = b.clone();
+    let shared = a.iter().filter(|pair| {
+        match remaining.iter().position(|other| other == *pair) {
+            Some(i) => {
+                remaining.swap_remove(i);
+                true
+            }
+            None => false,
+        }
+    }).count();
+    0.9 * 2.0 * shared as f64 / (a.len() + b.len()) as f64
+}
+
+fn validate(mapping: &mut TermMapping) -> Result<(), String> {
+    mapping.key = mapping.key.trim().to_string();
+    mapping.label = mapping.label.trim().to_string();
+    if mapping.key.is_empty() {
+        return Err("A mapping needs a key".to_string());
+    }
+    if mapping.label.is_empty() {
+        mapping.label = mapping.key.clone();
+    }
+    for keywords in [&mut mapping.keywords_indas, &mut mapping.keywords_gaap, &mut mapping.keywords_ifrs] {
+        let mut seen = HashSet::new();
+        keywords.retain(|k| !k.trim().is_empty() && seen.insert(normalize(k)));
+        for keyword in keywords.iter_mut() {
+            *keyword = keyword.trim().to_string();
+        }
+    }
+    if mapping.keywords().next().is_none() {
+        return Err(format!("Mapping {} has no keywords", mapping.key));
+    }
+    Ok(())
+}
+
+fn upsert(conn: &Connection, mapping: &TermMapping, now: i64) -> Result<(), String> {
+    let json = serde_json::to_string(mapping).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT INTO terminology_mappings (key, label, mapping, updated_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(key) DO UPDATE SET label = excluded.label, mapping = excluded.mapping, updated_at = excluded.updated_at",
+        params![mapping.key, mapping.label, json, now],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// The mappings in effect, by key.
+pub fn current(app: &AppHandle) -> Result<Vec<TermMapping>, String> {
+    let conn = db::open_app_db(app)?;
+    let mut stmt = conn.prepare("SELECT mapping FROM terminology_mappings ORDER BY key")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt.query_map([], |row| row.get::<_, String>(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    rows.iter()
+        .map(|json| serde_json::from_str(json).map_err(|e| format!("Unreadable terminology mapping: {}", e)))
+        .collect()
+}
+
+/// The mappings in effect as sent to Python; empty when there are none or they can't be read,
+/// so a parse falls back to the built-in terms.
+pub fn for_parse(app: &AppHandle) -> Value {
+    current(app)
+        .ok()
+        .and_then(|mappings| serde_json::to_value(mappings).ok())
+        .unwrap_or_else(|| Value::Array(Vec::new()))
+}
+
+/// Swap the whole set for `mappings`, e.g. from a bulk edit or a revert.
+pub fn replace(app: &AppHandle, mut mappings: Vec<TermMapping>) -> Result<Vec<TermMapping>, String> {
+    for mapping in &mut mappings {
+        validate(mapping)?;
+    }
+    let mut conn = db::open_app_db(app)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute("DELETE FROM terminology_mappings", []).map_err(|e| e.to_string())?;
+    let now = now_secs();
+    for mapping in &mappings {
+        upsert(&tx, mapping, now)?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    current(app)
+}
+
+/// Keep the new set as a version in the mapping history and tell the windows.
+fn changed(app: &AppHandle, mappings: &[TermMapping], reverted_from: Option<i64>) -> Result<i64, String> {
+    let value = serde_json::to_value(mappings).map_err(|e| e.to_string())?;
+    let version = mapping_history::record(app, &value, reverted_from)?;
+    let _ = app.emit("terminology-changed", serde_json::json!({ "version": version, "count": mappings.len() }));
+    Ok(version)
+}
+
+/// Restore an earlier version's mappings, for `revert_mapping`. Returns the new version.
+pub fn restore(app: &AppHandle, mappings: &Value, version: i64) -> Result<(i64, Vec<TermMapping>), String> {
+    let mappings: Vec<TermMapping> = serde_json::from_value(mappings.clone())
+        .map_err(|e| format!("Mapping version {} is unreadable: {}", version, e))?;
+    let mappings = replace(app, mappings)?;
+    let new_version = changed(app, &mappings, Some(version))?;
+    Ok((new_version, mappings))
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn list_mappings(app: AppHandle) -> Result<Vec<TermMapping>, String> {
+    current(&app)
+}
+
+/// Add a mapping, or replace the one with the same key.
+#[tauri::command]
+pub fn add_mapping(app: AppHandle, mut mapping: TermMapping) -> Result<TermMapping, String> {
+    validate(&mut mapping)?;
+    let conn = db::open_app_db(&app)?;
+    upsert(&conn, &mapping, now_secs())?;
+    changed(&app, &current(&app)?, None)?;
+    info!(key = %mapping.key, "Saved terminology mapping");
+    Ok(mapping)
+}
+
+/// Returns whether there was a mapping with that key.
+#[tauri::command]
+pub fn delete_mapping(app: AppHandle, key: String) -> Result<bool, String> {
+    let conn = db::open_app_db(&app)?;
+    let deleted = conn.execute("DELETE FROM terminology_mappings WHERE key = ?1", params![key])
+        .map_err(|e| e.to_string())?;
+    if deleted > 0 {
+        changed(&app, &current(&app)?, None)?;
+        info!(key = %key, "Deleted terminology mapping");
+    }
+    Ok(deleted > 0)
+}
+
+/// Replace every mapping at once, as the frontend's mapping editor does on save.
+#[tauri::command]
+pub fn update_terminology_mapping(app: AppHandle, mappings: Vec<TermMapping>) -> Result<(), String> {
+    let mappings = replace(&app, mappings)?;
+    changed(&app, &mappings, None)?;
+    Ok(())
+}
+
+/// Which mappings a statement label would match, best first. With `mapping`, it's scored as if
+/// already saved, so an edit can be checked before it's added.
+#[tauri::command]
+pub fn preview_mapping_match(
+    app: AppHandle,
+    label: String,
+    mapping: Option<TermMapping>,
+    limit: Option<usize>,
+) -> Result<Vec<MappingMatch>, String> {
+    let mut mappings = current(&app)?;
+    if let Some(candidate) = mapping {
+        mappings.retain(|m| m.key != candidate.key);
+        mappings.push(candidate);
+    }
+    let mut matches: Vec<MappingMatch> = mappings.iter().filter_map(|mapping| {
+        let (standard, keyword, score) = mapping.keywords()
+            .map(|(standard, keyword)| (standard, keyword, similarity(&label, keyword)))
+            .max_by(|a, b| a.2.total_cmp(&b.2))?;
+        (score >= MIN_PREVIEW_SCORE).then(|| MappingMatch {
+            key: mapping.key.clone(),
+            label: mapping.label.clone(),
+            keyword: keyword.clone(),
+            standard: standard.to_string(),
+            score: (score * 1000.0).round() / 1000.0,
+        })
+    }).collect();
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+    matches.truncate(limit.unwrap_or(10));
+    Ok(matches)
+}
@@ -0,0 +1,95 @@
+// Theme - the light or dark theme every window draws in: the user's choice, or the OS mode when
+// the setting is "system", announced to all windows as `theme-changed`
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, Theme};
+use tracing::info;
+
+use crate::settings::{self, SettingsState};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveTheme {
+    /// "light" or "dark"
+    pub theme: String,
+    /// The `theme` setting: "system", "light" or "dark"
+    pub setting: String,
+    pub follows_system: bool,
+}
+
+/// The theme last announced, so each window's own OS change notice is only passed on once.
+pub struct ThemeState {
+    last: Mutex<Option<EffectiveTheme>>,
+}
+
+impl ThemeState {
+    pub fn new() -> Self {
+        Self { last: Mutex::new(None) }
+    }
+}
+
+fn name(theme: Theme) -> &'static str {
+    match theme {
+        Theme::Dark => "dark",
+        _ => "light",
+    }
+}
+
+fn setting(app: &AppHandle) -> String {
+    let state = app.state::<SettingsState>();
+    let theme = settings::blocking_read(&state).get().theme.to_lowercase();
+    theme
+}
+
+/// The OS mode, as the windows see it; light when there's no window to ask.
+fn os_theme(app: &AppHandle) -> Theme {
+    app.webview_windows().values().find_map(|window| window.theme().ok()).unwrap_or(Theme::Light)
+}
+
+fn resolve(setting: String, os: impl FnOnce() -> Theme) -> EffectiveTheme {
+    let theme = match setting.as_str() {
+        "dark" | "light" => setting.clone(),
+        _ => name(os()).to_string(),
+    };
+    let follows_system = !matches!(setting.as_str(), "dark" | "light");
+    EffectiveTheme { theme, setting, follows_system }
+}
+
+pub fn effective(app: &AppHandle) -> EffectiveTheme {
+    resolve(setting(app), || os_theme(app))
+}
+
+fn announce(app: &AppHandle, theme: EffectiveTheme) {
+    let state = app.state::<ThemeState>();
+    let Ok(mut last) = state.last.lock() else { return };
+    if last.as_ref() == Some(&theme) {
+        return;
+    }
+    info!(theme = %theme.theme, setting = %theme.setting, "Theme changed");
+    let _ = app.emit("theme-changed", &theme);
+    *last = Some(theme);
+}
+
+/// Bring the windows' native theme in line with the setting and announce the result. Call after
+/// the `theme` setting changes.
+pub fn apply(app: &AppHandle) {
+    let setting = setting(app);
+    // Forcing a theme hides the OS mode from `Window::theme`, so "system" clears it first
+    app.set_theme(match setting.as_str() {
+        "dark" => Some(Theme::Dark),
+        "light" => Some(Theme::Light),
+        _ => None,
+    });
+    announce(app, resolve(setting, || os_theme(app)));
+}
+
+/// A window's `ThemeChanged` event: the OS switched mode, or `apply` forced one.
+pub fn os_theme_changed(app: &AppHandle, theme: Theme) {
+    announce(app, resolve(setting(app), || theme));
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn get_effective_theme(app: AppHandle) -> EffectiveTheme {
+    effective(&app)
+}
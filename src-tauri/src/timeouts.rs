@@ -0,0 +1,124 @@
+// Timeouts - every configurable operation limit in one place, read from settings and applied by
+// `run`, so a hit always fails the same way: a `timeout` error naming the operation, the setting
+// that bounds it and the limit.
+use serde::Serialize;
+use std::future::Future;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::warn;
+
+use crate::error::AppError;
+use crate::settings::{self, AppSettings, SettingsState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Analysis,
+    Metrics,
+    DbQuery,
+    ScraperRequest,
+    ScraperSearch,
+    WebSearch,
+    OllamaRequest,
+    OllamaGeneration,
+}
+
+impl Operation {
+    /// Settings key, as `section.field`
+    pub fn setting(&self) -> &'static str {
+        match self {
+            Operation::Analysis => "python.analysis_timeout_secs",
+            Operation::Metrics => "python.metrics_timeout_secs",
+            Operation::DbQuery => "python.db_query_timeout_secs",
+            Operation::ScraperRequest => "scraper.request_timeout_secs",
+            Operation::ScraperSearch => "scraper.search_timeout_secs",
+            Operation::WebSearch => "scraper.web_search_timeout_secs",
+            Operation::OllamaRequest => "timeouts.ollama_request_secs",
+            Operation::OllamaGeneration => "timeouts.ollama_generation_secs",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Operation::Analysis => "PDF analysis",
+            Operation::Metrics => "Metrics calculation",
+            Operation::DbQuery => "Database query",
+            Operation::ScraperRequest => "Market data request",
+            Operation::ScraperSearch => "Company search",
+            Operation::WebSearch => "Web search",
+            Operation::OllamaRequest => "Ollama request",
+            Operation::OllamaGeneration => "Model reply",
+        }
+    }
+
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Operation::Analysis => "Raise the analysis timeout under Settings > Python, or split the document.",
+            Operation::Metrics | Operation::DbQuery => "Raise the timeout under Settings > Python.",
+            Operation::ScraperRequest | Operation::ScraperSearch | Operation::WebSearch => {
+                "Raise the timeout under Settings > Scraper, or try again when the site is less busy."
+            }
+            Operation::OllamaRequest | Operation::OllamaGeneration => {
+                "Raise the timeout under Settings > Timeouts, or pick a smaller model."
+            }
+        }
+    }
+
+    fn secs(&self, settings: &AppSettings) -> u64 {
+        match self {
+            Operation::Analysis => settings.python.analysis_timeout_secs,
+            Operation::Metrics => settings.python.metrics_timeout_secs,
+            Operation::DbQuery => settings.python.db_query_timeout_secs,
+            Operation::ScraperRequest => settings.scraper.request_timeout_secs,
+            Operation::ScraperSearch => settings.scraper.search_timeout_secs,
+            Operation::WebSearch => settings.scraper.web_search_timeout_secs,
+            Operation::OllamaRequest => settings.timeouts.ollama_request_secs,
+            Operation::OllamaGeneration => settings.timeouts.ollama_generation_secs,
+        }
+    }
+}
+
+/// Which limit was hit, carried in the error payload.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeoutHit {
+    pub operation: Operation,
+    pub setting: &'static str,
+    pub secs: u64,
+}
+
+impl TimeoutHit {
+    pub fn message(&self) -> String {
+        format!("{} timed out after {} seconds ({})", self.operation.label(), self.secs, self.setting)
+    }
+}
+
+/// The configured limit in seconds; 0 is none.
+pub fn limit_secs(app: &AppHandle, operation: Operation) -> u64 {
+    let state = app.state::<SettingsState>();
+    let secs = operation.secs(settings::blocking_read(&state).get());
+    secs
+}
+
+/// The error for `operation` running past `secs`, for callers that track their own deadline.
+pub fn expired(operation: Operation, secs: u64) -> AppError {
+    let hit = TimeoutHit { operation, setting: operation.setting(), secs };
+    warn!("{}", hit.message());
+    AppError::TimedOut(hit)
+}
+
+/// Run `work` under the configured limit for `operation`.
+pub async fn run<T, E, F>(app: &AppHandle, operation: Operation, work: F) -> Result<T, AppError>
+where
+    E: Into<AppError>,
+    F: Future<Output = Result<T, E>>,
+{
+    let secs = limit_secs(app, operation);
+    if secs == 0 {
+        return work.await.map_err(Into::into);
+    }
+    match tokio::time::timeout(Duration::from_secs(secs), work).await {
+        Ok(result) => result.map_err(Into::into),
+        Err(_) => Err(expired(operation, secs)),
+    }
+}
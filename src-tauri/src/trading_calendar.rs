@@ -0,0 +1,247 @@
+// Trading Calendar - NSE/BSE session hours and exchange holidays, so background quote polling
+// idles while the market is shut; holidays ship bundled and can be refreshed from NSE
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, FixedOffset, NaiveDate, NaiveTime, Utc, Weekday};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use tracing::info;
+
+use crate::corporate_events::{normalize_date, str_field};
+use crate::db;
+use crate::scraper::{self, NativeScraper, NSE_BASE};
+use crate::settings;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS market_holidays (
+    exchange TEXT NOT NULL,
+    date TEXT NOT NULL,            -- YYYY-MM-DD
+    description TEXT NOT NULL,
+    updated_at INTEGER NOT NULL,
+    PRIMARY KEY (exchange, date)
+);
+";
+
+// Equity trading holidays as published by NSE; BSE closes on the same days. A year present in
+// `market_holidays` replaces its bundled list.
+const BUNDLED_HOLIDAYS: &[(&str, &str)] = &[
+    ("2025-02-26", "Mahashivratri"),
+    ("2025-03-14", "Holi"),
+    ("2025-03-31", "Id-Ul-Fitr (Ramadan Eid)"),
+    ("2025-04-10", "Shri Mahavir Jayanti"),
+    ("2025-04-14", "Dr. Baba Saheb Ambedkar Jayanti"),
+    ("2025-04-18", "Good Friday"),
+    ("2025-05-01", "Maharashtra Day"),
+    ("2025-08-15", "Independence Day"),
+    ("2025-08-27", "Ganesh Chaturthi"),
+    ("2025-10-02", "Mahatma Gandhi Jayanti/Dussehra"),
+    ("2025-10-21", "Diwali Laxmi Pujan"),
+    ("2025-10-22", "Diwali-Balipratipada"),
+    ("2025-11-05", "Prakash Gurpurb Sri Guru Nanak Dev"),
+    ("2025-12-25", "Christmas"),
+    ("2026-01-26", "Republic Day"),
+    ("2026-03-03", "Holi"),
+    ("2026-03-26", "Shri Ram Navami"),
+    ("2026-03-31", "Shri Mahavir Jayanti"),
+    ("2026-04-03", "Good Friday"),
+    ("2026-04-14", "Dr. Baba Saheb Ambedkar Jayanti"),
+    ("2026-05-01", "Maharashtra Day"),
+    ("2026-05-28", "Bakri Id"),
+    ("2026-06-26", "Muharram"),
+    ("2026-09-14", "Ganesh Chaturthi"),
+    ("2026-10-02", "Mahatma Gandhi Jayanti"),
+    ("2026-10-20", "Dussehra"),
+    ("2026-11-10", "Diwali-Balipratipada"),
+    ("2026-11-24", "Prakash Gurpurb Sri Guru Nanak Dev"),
+    ("2026-12-25", "Christmas"),
+];
+// Regular equity session, IST
+const SESSION_OPEN: (u32, u32) = (9, 15);
+const SESSION_CLOSE: (u32, u32) = (15, 30);
+// How far ahead to look for the next session (covers Diwali weeks and long weekends)
+const NEXT_OPEN_SEARCH_DAYS: i64 = 14;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Holiday {
+    pub date: String,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarketStatus {
+    pub exchange: String,
+    pub open: bool,
+    /// Why it's closed: "weekend", "holiday", "pre_open" or "after_close"
+    pub reason: Option<String>,
+    pub holiday: Option<String>,
+    /// Start of the next session (RFC 3339, IST) when closed
+    pub next_open: Option<String>,
+    /// Whether the exchange's hours are known; others are always treated as open
+    pub known: bool,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+pub fn ist_now() -> DateTime<FixedOffset> {
+    let ist = FixedOffset::east_opt(5 * 3600 + 30 * 60).expect("valid IST offset");
+    Utc::now().with_timezone(&ist)
+}
+
+/// NSE and BSE share one calendar; None for exchanges whose hours we don't track.
+fn calendar_exchange(exchange: &str) -> Option<&'static str> {
+    match exchange.trim().to_uppercase().as_str() {
+        "NSE" | "BOTH" | "" => Some("NSE"),
+        "BSE" => Some("BSE"),
+        _ => None,
+    }
+}
+
+/// The holiday on `date`, from the downloaded list when that year has one, else the bundled one.
+fn holiday_on(app: &AppHandle, exchange: &str, date: NaiveDate) -> Option<String> {
+    let day = date.format("%Y-%m-%d").to_string();
+    let year = format!("{}-%", date.year());
+    let downloaded = db::open_app_db(app).ok().and_then(|conn| {
+        let year_known: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM market_holidays WHERE exchange = ?1 AND date LIKE ?2",
+            params![exchange, year],
+            |row| row.get(0),
+        ).ok()?;
+        if year_known == 0 {
+            return None;
+        }
+        Some(conn.query_row(
+            "SELECT description FROM market_holidays WHERE exchange = ?1 AND date = ?2",
+            params![exchange, day],
+            |row| row.get::<_, String>(0),
+        ).ok())
+    });
+    match downloaded {
+        Some(holiday) => holiday,
+        None => BUNDLED_HOLIDAYS.iter().find(|(date, _)| *date == day).map(|(_, name)| name.to_string()),
+    }
+}
+
+fn session_time((hour, minute): (u32, u32)) -> NaiveTime {
+    NaiveTime::from_hms_opt(hour, minute, 0).expect("valid session time")
+}
+
+fn is_trading_day(app: &AppHandle, exchange: &str, date: NaiveDate) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && holiday_on(app, exchange, date).is_none()
+}
+
+/// Where `exchange` stands at `now` (IST).
+pub fn status(app: &AppHandle, exchange: &str, now: DateTime<FixedOffset>) -> MarketStatus {
+    let name = exchange.trim().to_uppercase();
+    let Some(calendar) = calendar_exchange(exchange) else {
+        return MarketStatus { exchange: name, open: true, reason: None, holiday: None, next_open: None, known: false };
+    };
+    let today = now.date_naive();
+    let time = now.time();
+    let holiday = holiday_on(app, calendar, today);
+    let reason = if matches!(today.weekday(), Weekday::Sat | Weekday::Sun) {
+        Some("weekend")
+    } else if holiday.is_some() {
+        Some("holiday")
+    } else if time < session_time(SESSION_OPEN) {
+        Some("pre_open")
+    } else if time > session_time(SESSION_CLOSE) {
+        Some("after_close")
+    } else {
+        None
+    };
+
+    let next_open = reason.and_then(|reason| {
+        let first = if reason == "pre_open" { 0 } else { 1 };
+        (first..=NEXT_OPEN_SEARCH_DAYS)
+            .map(|offset| today + ChronoDuration::days(offset))
+            .find(|date| is_trading_day(app, calendar, *date))
+            .and_then(|date| date.and_time(session_time(SESSION_OPEN)).and_local_timezone(*now.offset()).single())
+            .map(|open| open.to_rfc3339())
+    });
+    MarketStatus {
+        exchange: name,
+        open: reason.is_none(),
+        reason: reason.map(str::to_string),
+        holiday,
+        next_open,
+        known: true,
+    }
+}
+
+/// Whether `exchange` is in session right now; exchanges without a known calendar count as open.
+pub fn is_open(app: &AppHandle, exchange: &str) -> bool {
+    status(app, exchange, ist_now()).open
+}
+
+/// NSE's trading-holiday list; the "CM" segment is the equity market.
+async fn fetch_nse_holidays(app: &AppHandle) -> Result<Vec<Holiday>, String> {
+    let native = app.state::<NativeScraper>();
+    let referer = format!("{}/resources/exchange-communication-holidays", NSE_BASE);
+    let data = native.nse_get_json("/api/holiday-master?type=trading", &referer, scraper::request_timeout(app)).await?;
+    let rows = data.get("CM").and_then(|rows| rows.as_array())
+        .ok_or_else(|| "NSE returned no equity holiday list".to_string())?;
+    Ok(rows.iter()
+        .filter_map(|row| Some(Holiday {
+            date: str_field(row, "tradingDate").and_then(|d| normalize_date(&d))?,
+            description: str_field(row, "description").unwrap_or_else(|| "Holiday".to_string()),
+        }))
+        .collect())
+}
+
+// Tauri Commands
+/// Whether `exchange` (default NSE) is trading now, and if not why and when it next opens.
+#[tauri::command]
+pub fn is_market_open(app: AppHandle, exchange: Option<String>) -> MarketStatus {
+    status(&app, exchange.as_deref().unwrap_or("NSE"), ist_now())
+}
+
+/// Holidays for `exchange` in `year` (default this year), downloaded or bundled.
+#[tauri::command]
+pub fn get_market_holidays(app: AppHandle, exchange: Option<String>, year: Option<i32>) -> Result<Vec<Holiday>, String> {
+    let exchange = exchange.as_deref().unwrap_or("NSE");
+    let calendar = calendar_exchange(exchange).ok_or_else(|| format!("No trading calendar for {}", exchange))?;
+    let year = year.unwrap_or_else(|| ist_now().year());
+    let start = NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(|| format!("Invalid year: {}", year))?;
+    let holidays = start.iter_days()
+        .take_while(|date| date.year() == year)
+        .filter_map(|date| holiday_on(&app, calendar, date).map(|description| Holiday { date: date.format("%Y-%m-%d").to_string(), description }))
+        .collect();
+    Ok(holidays)
+}
+
+/// Replace the stored holiday lists with NSE's current one, applied to both exchanges. Returns
+/// how many holidays were stored.
+#[tauri::command]
+pub async fn update_market_holidays(app: AppHandle) -> Result<usize, String> {
+    settings::ensure_online(&app)?;
+    let holidays = fetch_nse_holidays(&app).await?;
+    if holidays.is_empty() {
+        return Err("NSE's holiday list was empty".to_string());
+    }
+    let years: Vec<String> = holidays.iter().map(|holiday| format!("{}-%", &holiday.date[..4])).collect();
+    let mut conn = db::open_app_db(&app)?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    for exchange in ["NSE", "BSE"] {
+        // Each downloaded year is replaced whole so cancelled holidays drop out
+        for year in &years {
+            tx.execute("DELETE FROM market_holidays WHERE exchange = ?1 AND date LIKE ?2", params![exchange, year])
+                .map_err(|e| e.to_string())?;
+        }
+        for holiday in &holidays {
+            tx.execute(
+                "INSERT OR REPLACE INTO market_holidays (exchange, date, description, updated_at) VALUES (?1, ?2, ?3, ?4)",
+                params![exchange, holiday.date, holiday.description, now_secs()],
+            ).map_err(|e| e.to_string())?;
+        }
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    info!(holidays = holidays.len(), "Updated market holidays");
+    Ok(holidays.len())
+}
@@ -0,0 +1,169 @@
+// Tray - system tray icon so alerts, imports and Ollama stay reachable with the window closed
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::{MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent};
+use tauri::{AppHandle, Emitter, Manager, Wry};
+use tracing::{error, info};
+
+use crate::alerts;
+use crate::jobs::{JobManager, JobStatus};
+use crate::ollama::OllamaBridge;
+
+const TRAY_ID: &str = "main";
+const APP_NAME: &str = "Financial Calculator";
+
+pub struct TrayState {
+    paused: AtomicBool,
+    tray: TrayIcon,
+    alerts: MenuItem<Wry>,
+    jobs: MenuItem<Wry>,
+    pause: CheckMenuItem<Wry>,
+    ollama: MenuItem<Wry>,
+}
+
+/// Watchlist refresh and alert checks skip their cycle while this is set.
+pub fn is_refresh_paused(app: &AppHandle) -> bool {
+    app.try_state::<TrayState>().is_some_and(|state| state.paused.load(Ordering::Relaxed))
+}
+
+fn set_refresh_paused(app: &AppHandle, paused: bool) {
+    let Some(state) = app.try_state::<TrayState>() else { return };
+    state.paused.store(paused, Ordering::Relaxed);
+    let _ = state.pause.set_checked(paused);
+    info!("Background refresh {}", if paused { "paused" } else { "resumed" });
+    let _ = app.emit("background-refresh-paused", paused);
+    refresh(app);
+}
+
+pub fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+/// Bring the tray's labels, tooltip and badge up to date with alerts, jobs and Ollama.
+pub fn refresh(app: &AppHandle) {
+    let Some(state) = app.try_state::<TrayState>() else { return };
+
+    let unread = alerts::unread_count(app).unwrap_or(0);
+    let (running, queued) = app.try_state::<JobManager>()
+        .map(|manager| {
+            let jobs = manager.list();
+            let count = |status| jobs.iter().filter(|j| j.status == status).count();
+            (count(JobStatus::Running), count(JobStatus::Queued))
+        })
+        .unwrap_or((0, 0));
+    let owns_ollama = app.try_state::<OllamaBridge>().is_some_and(|bridge| bridge.owns_server());
+
+    let alerts_text = match unread {
+        0 => "No unread alerts".to_string(),
+        1 => "1 unread alert".to_string(),
+        n => format!("{} unread alerts", n),
+    };
+    let jobs_text = match (running, queued) {
+        (0, 0) => "No imports running".to_string(),
+        (running, 0) => format!("{} running", plural(running, "import")),
+        (running, queued) => format!("{} running, {} queued", plural(running, "import"), queued),
+    };
+    let _ = state.alerts.set_text(&alerts_text);
+    let _ = state.jobs.set_text(&jobs_text);
+    let _ = state.ollama.set_text(if owns_ollama { "Ollama: stop" } else { "Ollama: start" });
+
+    let mut tooltip = vec![APP_NAME.to_string()];
+    if unread > 0 {
+        tooltip.push(alerts_text);
+    }
+    if running + queued > 0 {
+        tooltip.push(jobs_text);
+    }
+    if state.paused.load(Ordering::Relaxed) {
+        tooltip.push("Background refresh paused".to_string());
+    }
+    let _ = state.tray.set_tooltip(Some(tooltip.join("\n")));
+    // Shown next to the icon where the platform supports it (macOS, some Linux panels)
+    let _ = state.tray.set_title(if unread > 0 { Some(unread.to_string()) } else { None });
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 { format!("1 {}", noun) } else { format!("{} {}s", count, noun) }
+}
+
+fn toggle_ollama(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let bridge = app.state::<OllamaBridge>();
+        if bridge.owns_server() {
+            bridge.stop();
+        } else if let Err(e) = bridge.start(&app).await {
+            error!("Tray could not start Ollama: {}", e);
+        }
+        refresh(&app);
+    });
+}
+
+fn on_menu_event(app: &AppHandle, event: MenuEvent) {
+    match event.id().as_ref() {
+        "open" => show_main_window(app),
+        "alerts" => {
+            show_main_window(app);
+            let _ = app.emit("tray-open-alerts", ());
+        }
+        "pause" => set_refresh_paused(app, !is_refresh_paused(app)),
+        "ollama" => toggle_ollama(app),
+        // shutdown::run stops the Ollama server and other background work on the way out
+        "quit" => app.exit(0),
+        _ => {}
+    }
+}
+
+pub fn create(app: &AppHandle) -> Result<(), String> {
+    let open = MenuItem::with_id(app, "open", format!("Open {}", APP_NAME), true, None::<&str>).map_err(|e| e.to_string())?;
+    let alerts = MenuItem::with_id(app, "alerts", "No unread alerts", true, None::<&str>).map_err(|e| e.to_string())?;
+    let jobs = MenuItem::with_id(app, "jobs", "No imports running", false, None::<&str>).map_err(|e| e.to_string())?;
+    let pause = CheckMenuItem::with_id(app, "pause", "Pause background refresh", true, false, None::<&str>).map_err(|e| e.to_string())?;
+    let ollama = MenuItem::with_id(app, "ollama", "Ollama: start", true, None::<&str>).map_err(|e| e.to_string())?;
+    let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>).map_err(|e| e.to_string())?;
+    let separator = || PredefinedMenuItem::separator(app).map_err(|e| e.to_string());
+    let menu = Menu::with_items(app, &[
+        &open,
+        &alerts,
+        &jobs,
+        &separator()?,
+        &pause,
+        &ollama,
+        &separator()?,
+        &quit,
+    ]).map_err(|e| e.to_string())?;
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip(APP_NAME)
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(on_menu_event)
+        .on_tray_icon_event(|tray, event| {
+            if let TrayIconEvent::Click { button: MouseButton::Left, button_state: MouseButtonState::Up, .. } = event {
+                show_main_window(tray.app_handle());
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    let tray = builder.build(app).map_err(|e| e.to_string())?;
+
+    app.manage(TrayState { paused: AtomicBool::new(false), tray, alerts, jobs, pause, ollama });
+    refresh(app);
+    Ok(())
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn set_background_refresh_paused(app: AppHandle, paused: bool) {
+    set_refresh_paused(&app, paused);
+}
+
+#[tauri::command]
+pub fn is_background_refresh_paused(app: AppHandle) -> bool {
+    is_refresh_paused(&app)
+}
@@ -0,0 +1,286 @@
+// Units - the currency and scale each document reports in ("₹ in crore", "USD million"),
+// detected or set by the user, and conversion of its figures to the common scale in settings
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use tracing::info;
+
+use crate::db;
+use crate::derived;
+use crate::numbers::Unit;
+use crate::settings::{self, SettingsState, UnitSettings};
+use crate::statements::{self, StoredDocument};
+
+const UNITS_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS document_units (
+    doc_id INTEGER PRIMARY KEY,
+    currency TEXT NOT NULL,
+    scale TEXT NOT NULL,
+    updated_at INTEGER NOT NULL
+);";
+// The "(₹ in crore)" heading sits on the first statement pages
+const HEADING_PAGES: u32 = 6;
+const DEFAULT_CURRENCY: &str = "INR";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnitSource {
+    /// Set with `set_document_units`
+    Override,
+    /// Found in the items, metadata or page text
+    Detected,
+    /// Nothing found; INR as written
+    Default,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentUnits {
+    pub currency: String,
+    /// None when figures are in whole units
+    pub scale: Option<Unit>,
+    pub source: UnitSource,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn open(app: &AppHandle) -> Result<Connection, String> {
+    let conn = db::open_pipeline_db(app)?;
+    conn.execute_batch(UNITS_SCHEMA).map_err(|e| e.to_string())?;
+    Ok(conn)
+}
+
+/// "units" (or empty) for whole units, otherwise a name `Unit::parse` knows.
+pub fn parse_scale(scale: &str) -> Result<Option<Unit>, String> {
+    match scale.trim().to_lowercase().as_str() {
+        "" | "units" | "unit" | "one" | "ones" => Ok(None),
+        other => Unit::parse(other).map(Some).ok_or_else(|| format!("Unknown scale: {}", scale)),
+    }
+}
+
+fn scale_name(scale: Option<Unit>) -> &'static str {
+    scale.map_or("units", Unit::name)
+}
+
+fn currency_code(symbol: &str) -> Option<&'static str> {
+    match symbol.trim().to_lowercase().trim_end_matches('.') {
+        "₹" | "rs" | "inr" | "rupees" => Some("INR"),
+        "$" | "us$" | "usd" => Some("USD"),
+        "€" | "eur" => Some("EUR"),
+        "£" | "gbp" => Some("GBP"),
+        _ => None,
+    }
+}
+
+/// (currency, scale) from a heading such as "(₹ in crore)", "Rs. in lakhs" or "All amounts
+/// in USD million".
+fn heading_units(text: &str) -> Option<(Option<&'static str>, Unit)> {
+    let heading = Regex::new(
+        r"(?i)(?:\(|\bin\b|amounts?\s+in)\s*(₹|rs\.?|inr|usd|us\$|\$|eur|€|gbp|£)?\s*(?:in\s+)?(crores?|lakhs?|lacs?|millions?|mn|thousands?|'000|billions?|bn)\b"
+    ).expect("valid pattern");
+    let caps = heading.captures(text)?;
+    let unit = Unit::parse(&caps[2])?;
+    Some((caps.get(1).and_then(|symbol| currency_code(symbol.as_str())), unit))
+}
+
+/// Units as found in the stored document. Items whose amounts carried their own unit ("12.5
+/// Cr") were already converted to whole units when they were stored.
+pub fn detect(app: &AppHandle, document: &StoredDocument) -> DocumentUnits {
+    let mut currency = document.metadata["currency"].as_str()
+        .or_else(|| document.items.iter().find_map(|item| item["unit"].as_str()))
+        .and_then(|unit| currency_code(unit).or(Some(unit)).filter(|code| code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic())))
+        .map(str::to_uppercase);
+    let self_scaled = document.items.iter().filter(|item| item.get("detectedUnit").is_some()).count();
+    let mut scale = None;
+    if self_scaled * 2 <= document.items.len() {
+        let heading = (1..=HEADING_PAGES)
+            .filter_map(|page| statements::page_text(app, document.id, page).ok())
+            .find_map(|chunks| chunks.iter().find_map(|chunk| heading_units(chunk)));
+        if let Some((symbol, unit)) = heading {
+            scale = Some(unit);
+            currency = currency.or(symbol.map(str::to_string));
+        }
+    }
+    let source = if currency.is_some() || scale.is_some() { UnitSource::Detected } else { UnitSource::Default };
+    DocumentUnits {
+        currency: currency.unwrap_or_else(|| DEFAULT_CURRENCY.to_string()),
+        scale,
+        source,
+    }
+}
+
+fn stored_override(app: &AppHandle, doc_id: i64) -> Result<Option<DocumentUnits>, String> {
+    let conn = open(app)?;
+    let row: Option<(String, String)> = conn.query_row(
+        "SELECT currency, scale FROM document_units WHERE doc_id = ?1",
+        params![doc_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    ).optional().map_err(|e| e.to_string())?;
+    Ok(row.map(|(currency, scale)| DocumentUnits {
+        currency,
+        scale: parse_scale(&scale).unwrap_or(None),
+        source: UnitSource::Override,
+    }))
+}
+
+/// The user's override if there is one, otherwise what detection finds.
+pub fn resolve(app: &AppHandle, document: &StoredDocument) -> Result<DocumentUnits, String> {
+    Ok(stored_override(app, document.id)?.unwrap_or_else(|| detect(app, document)))
+}
+
+/// Multiply the document's amounts to reach `target`; percentages and ratios are unchanged.
+/// Records the source units and the factor under `metadata.units`.
+pub fn convert(document: &mut StoredDocument, units: &DocumentUnits, target: &UnitSettings) {
+    let target_scale = parse_scale(&target.scale).unwrap_or(None);
+    let fx = if units.currency.eq_ignore_ascii_case(&target.currency) {
+        Some(1.0)
+    } else {
+        target.fx_rates.get(&units.currency.to_uppercase()).copied()
+    };
+    let currency = if fx.is_some() { target.currency.to_uppercase() } else { units.currency.clone() };
+    let factor = units.scale.map_or(1.0, Unit::scale) / target_scale.map_or(1.0, Unit::scale) * fx.unwrap_or(1.0);
+
+    for item in &mut document.items {
+        let Some(fields) = item.as_object_mut() else { continue };
+        for field in ["currentYear", "previousYear", "variation"] {
+            if let Some(value) = fields.get(field).and_then(|v| v.as_f64()) {
+                fields.insert(field.to_string(), (value * factor).into());
+            }
+        }
+        if let Some(serde_json::Value::Object(years)) = fields.get_mut("allYears") {
+            for value in years.values_mut() {
+                if let Some(amount) = value.as_f64() {
+                    *value = (amount * factor).into();
+                }
+            }
+        }
+        fields.insert("unit".to_string(), currency.clone().into());
+        fields.insert("scale".to_string(), scale_name(target_scale).into());
+    }
+
+    if !document.metadata.is_object() {
+        document.metadata = serde_json::json!({});
+    }
+    document.metadata["units"] = serde_json::json!({
+        "source": units,
+        "currency": currency,
+        "scale": scale_name(target_scale),
+        "factor": factor,
+        "currencyConverted": fx.is_some(),
+    });
+}
+
+/// Pin a document's units, e.g. for one written in the configured scale rather than parsed.
+pub fn record(app: &AppHandle, doc_id: i64, currency: &str, scale: Option<Unit>) -> Result<(), String> {
+    open(app)?.execute(
+        "INSERT OR REPLACE INTO document_units (doc_id, currency, scale, updated_at) VALUES (?1, ?2, ?3, ?4)",
+        params![doc_id, currency, scale_name(scale), now_secs()],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Convert a document from `from` to the scale of `to`; currencies are left alone.
+pub fn rescale(document: &mut StoredDocument, from: &DocumentUnits, to: &DocumentUnits) {
+    let target = UnitSettings {
+        currency: from.currency.clone(),
+        scale: scale_name(to.scale).to_string(),
+        fx_rates: Default::default(),
+    };
+    convert(document, from, &target);
+}
+
+/// Currency and scale a loaded document's figures are in, from `metadata.units`.
+pub fn presented(document: &StoredDocument) -> (String, Option<Unit>) {
+    let units = &document.metadata["units"];
+    let currency = units["currency"].as_str().unwrap_or(DEFAULT_CURRENCY).to_string();
+    let scale = units["scale"].as_str().and_then(|scale| parse_scale(scale).ok()).flatten();
+    (currency, scale)
+}
+
+/// Rescale a loaded document to `scale`, e.g. for a report template that presents lakhs.
+pub fn present_in(document: &mut StoredDocument, scale: Option<Unit>) {
+    let (currency, current) = presented(document);
+    if current == scale {
+        return;
+    }
+    let from = DocumentUnits { currency, scale: current, source: UnitSource::Override };
+    let to = DocumentUnits { scale, ..from.clone() };
+    rescale(document, &from, &to);
+}
+
+/// Convert a freshly loaded document to the configured scale.
+pub fn normalize(app: &AppHandle, document: &mut StoredDocument) -> Result<(), String> {
+    let units = resolve(app, document)?;
+    let target = {
+        let state = app.state::<SettingsState>();
+        let store = settings::blocking_read(&state);
+        store.get().units.clone()
+    };
+    convert(document, &units, &target);
+    Ok(())
+}
+
+/// Convert a freshly loaded document to the configured scale only, keeping its own currency,
+/// e.g. to translate it at historical rates rather than the configured ones.
+pub fn normalize_scale(app: &AppHandle, document: &mut StoredDocument) -> Result<(), String> {
+    let units = resolve(app, document)?;
+    let scale = {
+        let state = app.state::<SettingsState>();
+        let store = settings::blocking_read(&state);
+        store.get().units.scale.clone()
+    };
+    let target = UnitSettings {
+        currency: units.currency.clone(),
+        scale,
+        fx_rates: Default::default(),
+    };
+    convert(document, &units, &target);
+    Ok(())
+}
+
+// Tauri Commands
+/// The units a document's figures are in, before any conversion.
+#[tauri::command]
+pub fn get_document_units(app: AppHandle, document_id: i64) -> Result<DocumentUnits, String> {
+    let document = statements::load_stored_document(&app, document_id)?;
+    resolve(&app, &document)
+}
+
+/// Override what detection found; with neither argument the override is removed. Returns the
+/// units now in effect.
+#[tauri::command]
+pub fn set_document_units(
+    app: AppHandle,
+    document_id: i64,
+    currency: Option<String>,
+    scale: Option<String>,
+) -> Result<DocumentUnits, String> {
+    let document = statements::load_stored_document(&app, document_id)?;
+    let conn = open(&app)?;
+    derived::mark_stale(&app, Some(document_id));
+    if currency.is_none() && scale.is_none() {
+        conn.execute("DELETE FROM document_units WHERE doc_id = ?1", params![document_id]).map_err(|e| e.to_string())?;
+        info!(document_id, "Cleared unit override");
+        return resolve(&app, &document);
+    }
+
+    let current = resolve(&app, &document)?;
+    let currency = match currency {
+        Some(currency) => currency_code(&currency).map(str::to_string).unwrap_or_else(|| currency.trim().to_uppercase()),
+        None => current.currency,
+    };
+    let scale = match scale {
+        Some(scale) => parse_scale(&scale)?,
+        None => current.scale,
+    };
+    record(&app, document_id, &currency, scale)?;
+    info!(document_id, currency = %currency, scale = scale_name(scale), "Set unit override");
+    Ok(DocumentUnits { currency, scale, source: UnitSource::Override })
+}
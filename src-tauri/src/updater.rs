@@ -0,0 +1,129 @@
+// Updater - checks the release feed and installs signed updates through the Tauri updater
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_updater::{Update, UpdaterExt};
+use tracing::{info, warn};
+
+use crate::notifications;
+use crate::settings::{self, SettingsState};
+
+// Give startup work (profiles, imports, Ollama) a head start before hitting the network
+const STARTUP_CHECK_DELAY: Duration = Duration::from_secs(30);
+
+/// The update found by the last check, kept so `install_update` doesn't have to look again.
+pub struct PendingUpdate(Mutex<Option<Update>>);
+
+impl PendingUpdate {
+    pub fn new() -> Self {
+        Self(Mutex::new(None))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateInfo {
+    pub current_version: String,
+    pub version: String,
+    pub published_at: Option<i64>,
+    /// Release notes as published (markdown)
+    pub notes: Option<String>,
+    /// Bullet points from the notes, for a compact "what's new" list
+    pub changes: Vec<String>,
+}
+
+fn changes_from_notes(notes: &str) -> Vec<String> {
+    notes
+        .lines()
+        .map(str::trim)
+        .filter_map(|line| line.strip_prefix("- ").or_else(|| line.strip_prefix("* ")))
+        .map(|change| change.trim().to_string())
+        .filter(|change| !change.is_empty())
+        .collect()
+}
+
+fn describe(update: &Update) -> UpdateInfo {
+    UpdateInfo {
+        current_version: update.current_version.clone(),
+        version: update.version.clone(),
+        published_at: update.date.map(|date| date.unix_timestamp()),
+        notes: update.body.clone(),
+        changes: update.body.as_deref().map(changes_from_notes).unwrap_or_default(),
+    }
+}
+
+async fn check(app: &AppHandle) -> Result<Option<UpdateInfo>, String> {
+    settings::ensure_online(app)?;
+    let update = app.updater()
+        .map_err(|e| e.to_string())?
+        .check()
+        .await
+        .map_err(|e| format!("Update check failed: {}", e))?;
+    let info = update.as_ref().map(describe);
+    *app.state::<PendingUpdate>().0.lock().map_err(|e| e.to_string())? = update;
+    Ok(info)
+}
+
+/// Check once after startup when automatic checks are enabled; a found update is announced, not installed.
+pub fn start_update_check(app: AppHandle) {
+    let state = app.state::<SettingsState>();
+    let enabled = settings::blocking_read(&state).get().auto_check_updates;
+    if !enabled {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(STARTUP_CHECK_DELAY).await;
+        match check(&app).await {
+            Ok(Some(info)) => {
+                info!("Update {} available (running {})", info.version, info.current_version);
+                notifications::notify_user(&app, "Update available", format!("Financial Calculator {} is ready to install", info.version));
+                let _ = app.emit("update-available", &info);
+            }
+            Ok(None) => {}
+            Err(e) => warn!("{}", e),
+        }
+    });
+}
+
+// Tauri Commands
+#[tauri::command]
+pub async fn check_for_updates(app: AppHandle) -> Result<Option<UpdateInfo>, String> {
+    check(&app).await
+}
+
+/// Download, verify and install the pending update, then restart into it.
+#[tauri::command]
+pub async fn install_update(app: AppHandle) -> Result<(), String> {
+    // Without the release signing key every download would fail verification
+    let signed = app.config().plugins.0.get("updater")
+        .and_then(|config| config["pubkey"].as_str())
+        .is_some_and(|key| !key.trim().is_empty());
+    if !signed {
+        return Err("This build has no updater signing key configured; download the new version manually".to_string());
+    }
+    let pending = app.state::<PendingUpdate>().0.lock().map_err(|e| e.to_string())?.take();
+    let update = match pending {
+        Some(update) => update,
+        None => {
+            check(&app).await?;
+            app.state::<PendingUpdate>().0.lock().map_err(|e| e.to_string())?
+                .take()
+                .ok_or("Already up to date")?
+        }
+    };
+
+    info!("Installing update {}", update.version);
+    let mut downloaded: u64 = 0;
+    update.download_and_install(
+        |chunk, total| {
+            downloaded += chunk as u64;
+            let _ = app.emit("update-download-progress", serde_json::json!({ "downloaded": downloaded, "total": total }));
+        },
+        || {
+            let _ = app.emit("update-downloaded", ());
+        },
+    ).await.map_err(|e| format!("Update failed: {}", e))?;
+
+    app.restart()
+}
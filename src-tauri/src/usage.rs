@@ -0,0 +1,174 @@
+// Usage - opt-in, local-only counts and durations of analyses, chats and exports.
+// Nothing here is ever sent anywhere; it only feeds the "your usage" panel.
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+
+use crate::db;
+use crate::settings::{self, SettingsState};
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS usage_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,            -- 'analysis', 'chat', 'export'
+    duration_ms INTEGER NOT NULL,
+    success INTEGER NOT NULL,
+    recorded_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_usage_events_recorded ON usage_events(recorded_at);
+";
+
+const DEFAULT_DAYS: u32 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageKind {
+    Analysis,
+    Chat,
+    Export,
+}
+
+impl UsageKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            UsageKind::Analysis => "analysis",
+            UsageKind::Chat => "chat",
+            UsageKind::Export => "export",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "analysis" => Some(UsageKind::Analysis),
+            "chat" => Some(UsageKind::Chat),
+            "export" => Some(UsageKind::Export),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KindStatistics {
+    pub kind: UsageKind,
+    pub count: i64,
+    pub failures: i64,
+    pub total_ms: i64,
+    pub average_ms: i64,
+    pub last_used_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyCount {
+    /// Local date, YYYY-MM-DD
+    pub day: String,
+    pub kind: UsageKind,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppStatistics {
+    /// Whether new activity is being recorded
+    pub enabled: bool,
+    pub since: i64,
+    pub kinds: Vec<KindStatistics>,
+    pub daily: Vec<DailyCount>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn enabled(app: &AppHandle) -> bool {
+    app.try_state::<SettingsState>()
+        .is_some_and(|state| settings::blocking_read(&state).get().usage_statistics)
+}
+
+/// Record one finished operation; does nothing unless the user opted in.
+pub fn record(app: &AppHandle, kind: UsageKind, duration: Duration, success: bool) {
+    if !enabled(app) {
+        return;
+    }
+    let recorded = db::open_app_db(app).and_then(|conn| {
+        conn.execute(
+            "INSERT INTO usage_events (kind, duration_ms, success, recorded_at) VALUES (?1, ?2, ?3, ?4)",
+            params![kind.as_str(), duration.as_millis() as i64, success, now_secs()],
+        ).map_err(|e| e.to_string())
+    });
+    if let Err(e) = recorded {
+        tracing::warn!("Failed to record usage: {}", e);
+    }
+}
+
+/// Time `work` and record it under `kind`.
+pub async fn track<T, E, F>(app: &AppHandle, kind: UsageKind, work: F) -> Result<T, E>
+where
+    F: Future<Output = Result<T, E>>,
+{
+    let started = Instant::now();
+    let result = work.await;
+    record(app, kind, started.elapsed(), result.is_ok());
+    result
+}
+
+/// Synchronous counterpart of `track`.
+pub fn measure<T>(app: &AppHandle, kind: UsageKind, work: impl FnOnce() -> Result<T, String>) -> Result<T, String> {
+    let started = Instant::now();
+    let result = work();
+    record(app, kind, started.elapsed(), result.is_ok());
+    result
+}
+
+// Tauri Commands
+/// Totals per kind and a per-day breakdown for the last `days` days (default 30).
+#[tauri::command]
+pub fn get_app_statistics(app: AppHandle, days: Option<u32>) -> Result<AppStatistics, String> {
+    let since = now_secs() - days.unwrap_or(DEFAULT_DAYS) as i64 * 86_400;
+    let conn = db::open_app_db(&app)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT kind, COUNT(*), SUM(success = 0), SUM(duration_ms), MAX(recorded_at)
+         FROM usage_events WHERE recorded_at >= ?1 GROUP BY kind ORDER BY kind"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![since], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?, row.get::<_, i64>(3)?, row.get(4)?))
+    }).map_err(|e| e.to_string())?;
+    let mut kinds = Vec::new();
+    for row in rows {
+        let (kind, count, failures, total_ms, last_used_at) = row.map_err(|e| e.to_string())?;
+        if let Some(kind) = UsageKind::parse(&kind) {
+            kinds.push(KindStatistics { kind, count, failures, total_ms, average_ms: total_ms / count.max(1), last_used_at });
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT date(recorded_at, 'unixepoch', 'localtime') AS day, kind, COUNT(*)
+         FROM usage_events WHERE recorded_at >= ?1 GROUP BY day, kind ORDER BY day, kind"
+    ).map_err(|e| e.to_string())?;
+    let rows = stmt.query_map(params![since], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?))
+    }).map_err(|e| e.to_string())?;
+    let mut daily = Vec::new();
+    for row in rows {
+        let (day, kind, count) = row.map_err(|e| e.to_string())?;
+        if let Some(kind) = UsageKind::parse(&kind) {
+            daily.push(DailyCount { day, kind, count });
+        }
+    }
+
+    Ok(AppStatistics { enabled: enabled(&app), since, kinds, daily })
+}
+
+#[tauri::command]
+pub fn clear_app_statistics(app: AppHandle) -> Result<(), String> {
+    let conn = db::open_app_db(&app)?;
+    conn.execute("DELETE FROM usage_events", []).map_err(|e| e.to_string())?;
+    Ok(())
+}
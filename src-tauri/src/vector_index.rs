@@ -0,0 +1,162 @@
+// Vector Index - the active profile's chunk embeddings held in memory, unit-length, for
+// semantic search; rebuilt from the chunks table whenever its embeddings change
+use rayon::prelude::*;
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager};
+use tracing::info;
+
+use crate::chunks;
+use crate::profiles;
+
+const DEFAULT_TOP_K: usize = 8;
+const MAX_TOP_K: usize = 100;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub chunk_id: i64,
+    pub document_id: i64,
+    pub filename: Option<String>,
+    pub page: Option<u32>,
+    pub content: String,
+    /// Cosine similarity to the query, -1 to 1
+    pub score: f32,
+}
+
+/// What the index was built from; any embedding written since changes it.
+#[derive(Debug, Clone, PartialEq)]
+struct IndexKey {
+    db_path: PathBuf,
+    model: String,
+    embedded: i64,
+    max_id: i64,
+}
+
+struct Entry {
+    chunk_id: i64,
+    document_id: i64,
+    vector: Vec<f32>,
+}
+
+struct Snapshot {
+    key: IndexKey,
+    entries: Arc<Vec<Entry>>,
+}
+
+#[derive(Default)]
+pub struct VectorIndex {
+    snapshot: Mutex<Option<Snapshot>>,
+}
+
+impl VectorIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Scaled to unit length so a dot product is the cosine; None for a zero vector.
+fn unit(mut vector: Vec<f32>) -> Option<Vec<f32>> {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm == 0.0 || !norm.is_finite() {
+        return None;
+    }
+    vector.iter_mut().for_each(|v| *v /= norm);
+    Some(vector)
+}
+
+fn current_key(app: &AppHandle, model: &str) -> Result<IndexKey, String> {
+    let conn = chunks::open(app)?;
+    let (embedded, max_id) = conn.query_row(
+        "SELECT COUNT(*), IFNULL(MAX(id), 0) FROM chunks WHERE embedding IS NOT NULL AND model = ?1",
+        params![model],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    ).map_err(|e| e.to_string())?;
+    Ok(IndexKey { db_path: profiles::active_db_path(app), model: model.to_string(), embedded, max_id })
+}
+
+/// The embeddings from `model`, loaded again only when they've changed since the last search.
+fn entries(app: &AppHandle, model: &str) -> Result<Arc<Vec<Entry>>, String> {
+    let key = current_key(app, model)?;
+    let index = app.state::<VectorIndex>();
+    let mut snapshot = index.snapshot.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(current) = snapshot.as_ref().filter(|current| current.key == key) {
+        return Ok(current.entries.clone());
+    }
+
+    let conn = chunks::open(app)?;
+    let mut stmt = conn.prepare("SELECT id, doc_id, embedding FROM chunks WHERE embedding IS NOT NULL AND model = ?1")
+        .map_err(|e| e.to_string())?;
+    let entries: Vec<Entry> = stmt.query_map(params![model], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, Vec<u8>>(2)?))
+    }).map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter_map(|(chunk_id, document_id, blob)| Some(Entry { chunk_id, document_id, vector: unit(chunks::decode(&blob))? }))
+        .collect();
+    info!(vectors = entries.len(), model = %model, "Built vector index");
+    let entries = Arc::new(entries);
+    *snapshot = Some(Snapshot { key, entries: entries.clone() });
+    Ok(entries)
+}
+
+/// The `top_k` chunks nearest `query`, optionally within some documents. Exact: every vector is
+/// scored, which stays quick for the tens of thousands of chunks a profile holds.
+pub async fn search(app: &AppHandle, query: &str, top_k: usize, document_ids: Option<&[i64]>) -> Result<Vec<SearchHit>, String> {
+    let model = chunks::embedding_model(app).await;
+    if model.is_empty() {
+        return Err("No embedding model configured".to_string());
+    }
+    let query_vector = chunks::embed(app, &model, &[query.to_string()]).await?
+        .into_iter()
+        .next()
+        .and_then(unit)
+        .ok_or_else(|| "The query produced no embedding".to_string())?;
+
+    let entries = entries(app, &model)?;
+    let mut scored: Vec<(f32, i64)> = entries.par_iter()
+        .filter(|entry| entry.vector.len() == query_vector.len())
+        .filter(|entry| document_ids.is_none_or(|ids| ids.contains(&entry.document_id)))
+        .map(|entry| (entry.vector.iter().zip(&query_vector).map(|(a, b)| a * b).sum::<f32>(), entry.chunk_id))
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    scored.truncate(top_k.clamp(1, MAX_TOP_K));
+
+    let conn = chunks::open(app)?;
+    let mut stmt = conn.prepare(
+        "SELECT doc_id, page_num, content, json_extract(metadata, '$.filename') FROM chunks WHERE id = ?1",
+    ).map_err(|e| e.to_string())?;
+    scored.into_iter()
+        .map(|(score, chunk_id)| {
+            stmt.query_row(params![chunk_id], |row| {
+                Ok(SearchHit {
+                    chunk_id,
+                    document_id: row.get(0)?,
+                    page: row.get(1)?,
+                    content: row.get(2)?,
+                    filename: row.get(3)?,
+                    score,
+                })
+            }).map_err(|e| e.to_string())
+        })
+        .collect()
+}
+
+// Tauri Commands
+/// Passages that mean what `query` asks about, closest first, even where the wording differs
+/// ("contingent liabilities" finding "claims against the company not acknowledged as debts").
+#[tauri::command]
+pub async fn semantic_search(
+    app: AppHandle,
+    query: String,
+    top_k: Option<usize>,
+    document_ids: Option<Vec<i64>>,
+) -> Result<Vec<SearchHit>, String> {
+    if query.trim().is_empty() {
+        return Err("Query required".to_string());
+    }
+    search(&app, query.trim(), top_k.unwrap_or(DEFAULT_TOP_K), document_ids.as_deref()).await
+}
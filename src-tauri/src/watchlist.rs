@@ -0,0 +1,182 @@
+// Watchlist - saved symbols refreshed in the background
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::db;
+use crate::realtime;
+use crate::scraper;
+use crate::settings::{self, SettingsState};
+use crate::trading_calendar;
+use crate::tray;
+
+pub const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS watchlist (
+    symbol TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    added_at INTEGER NOT NULL,
+    last_price REAL,
+    day_change REAL,
+    day_change_percent REAL,
+    updated_at INTEGER,
+    PRIMARY KEY (symbol, exchange)
+);
+";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchlistEntry {
+    pub symbol: String,
+    pub exchange: String,
+    pub added_at: i64,
+    pub last_price: Option<f64>,
+    pub day_change: Option<f64>,
+    pub day_change_percent: Option<f64>,
+    pub updated_at: Option<i64>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+pub fn load_entries(app: &AppHandle) -> Result<Vec<WatchlistEntry>, String> {
+    let conn = db::open_app_db(app)?;
+    let mut stmt = conn.prepare(
+        "SELECT symbol, exchange, added_at, last_price, day_change, day_change_percent, updated_at
+         FROM watchlist ORDER BY added_at ASC"
+    ).map_err(|e| e.to_string())?;
+
+    let rows = stmt.query_map([], |row| {
+        Ok(WatchlistEntry {
+            symbol: row.get(0)?,
+            exchange: row.get(1)?,
+            added_at: row.get(2)?,
+            last_price: row.get(3)?,
+            day_change: row.get(4)?,
+            day_change_percent: row.get(5)?,
+            updated_at: row.get(6)?,
+        })
+    }).map_err(|e| e.to_string())?;
+
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+/// Refresh every watched symbol once and emit `watchlist-update` with the results.
+pub async fn refresh_all(app: &AppHandle, max_age_secs: u64) -> Result<(), String> {
+    let entries = load_entries(app)?;
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    // A closed market's prices don't move; only entries never priced are fetched then
+    let due = entries.iter().filter(|entry| entry.last_price.is_none() || trading_calendar::is_open(app, &entry.exchange));
+    for entry in due {
+        match scraper::get_quote(app, &entry.symbol, &entry.exchange, max_age_secs).await {
+            Ok(quote) => {
+                let conn = db::open_app_db(app)?;
+                conn.execute(
+                    "UPDATE watchlist SET last_price = ?1, day_change = ?2, day_change_percent = ?3, updated_at = ?4
+                     WHERE symbol = ?5 AND exchange = ?6",
+                    params![quote.price, quote.change, quote.change_percent, now_secs(), entry.symbol, entry.exchange],
+                ).map_err(|e| e.to_string())?;
+            }
+            Err(e) => eprintln!("[Watchlist] Failed to refresh {} ({}): {}", entry.symbol, entry.exchange, e),
+        }
+    }
+
+    let updated = load_entries(app)?;
+    let _ = app.emit("watchlist-update", &updated);
+    Ok(())
+}
+
+/// Add or remove an entry another device changed, without publishing it back, and emit
+/// `watchlist-update` so the list redraws.
+pub fn store_synced(app: &AppHandle, symbol: &str, exchange: &str, added_at: Option<i64>) -> Result<(), String> {
+    let conn = db::open_app_db(app)?;
+    match added_at {
+        Some(added_at) => conn.execute(
+            "INSERT OR IGNORE INTO watchlist (symbol, exchange, added_at) VALUES (?1, ?2, ?3)",
+            params![symbol, exchange, added_at],
+        ),
+        None => conn.execute("DELETE FROM watchlist WHERE symbol = ?1 AND exchange = ?2", params![symbol, exchange]),
+    }.map_err(|e| e.to_string())?;
+    let _ = app.emit("watchlist-update", &load_entries(app)?);
+    Ok(())
+}
+
+/// Background loop; the interval is re-read each cycle so settings changes apply live.
+pub fn start_refresh_task(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let interval = {
+                let state = app.state::<SettingsState>();
+                let store = state.read().await;
+                store.get().scraper.watchlist_refresh_secs.max(10)
+            };
+
+            if settings::is_offline(&app) {
+                // Nothing to refresh; check again next cycle
+            } else if tray::is_refresh_paused(&app) {
+                // Paused from the tray menu
+            } else if let Err(e) = refresh_all(&app, interval).await {
+                eprintln!("[Watchlist] Refresh failed: {}", e);
+            }
+
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+        }
+    });
+}
+
+// Tauri Commands
+#[tauri::command]
+pub fn add_to_watchlist(
+    app: AppHandle,
+    symbol: String,
+    exchange: String,
+) -> Result<Vec<WatchlistEntry>, String> {
+    let symbol = symbol.trim().to_uppercase();
+    let exchange = exchange.trim().to_uppercase();
+    if symbol.is_empty() {
+        return Err("Symbol cannot be empty".to_string());
+    }
+
+    let conn = db::open_app_db(&app)?;
+    let added_at = now_secs();
+    let added = conn.execute(
+        "INSERT OR IGNORE INTO watchlist (symbol, exchange, added_at) VALUES (?1, ?2, ?3)",
+        params![symbol, exchange, added_at],
+    ).map_err(|e| e.to_string())?;
+    if added > 0 {
+        realtime::watchlist_changed(&app, &symbol, &exchange, Some(added_at));
+    }
+
+    load_entries(&app)
+}
+
+#[tauri::command]
+pub fn remove_from_watchlist(
+    app: AppHandle,
+    symbol: String,
+    exchange: String,
+) -> Result<Vec<WatchlistEntry>, String> {
+    let (symbol, exchange) = (symbol.trim().to_uppercase(), exchange.trim().to_uppercase());
+    let conn = db::open_app_db(&app)?;
+    let removed = conn.execute(
+        "DELETE FROM watchlist WHERE symbol = ?1 AND exchange = ?2",
+        params![symbol, exchange],
+    ).map_err(|e| e.to_string())?;
+    if removed > 0 {
+        realtime::watchlist_changed(&app, &symbol, &exchange, None);
+    }
+
+    load_entries(&app)
+}
+
+#[tauri::command]
+pub fn get_watchlist(app: AppHandle) -> Result<Vec<WatchlistEntry>, String> {
+    load_entries(&app)
+}
@@ -0,0 +1,178 @@
+// Windows - secondary windows (raw DB viewer, detached chat, chart detail) and per-window event names
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Manager, Url, WebviewUrl, WebviewWindowBuilder};
+
+pub const MAIN_WINDOW: &str = "main";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowKind {
+    Main,
+    DbViewer,
+    Chat,
+    ChartDetail,
+}
+
+impl WindowKind {
+    /// Label, or label prefix for kinds that can be opened more than once
+    fn label_prefix(&self) -> &'static str {
+        match self {
+            WindowKind::Main => MAIN_WINDOW,
+            WindowKind::DbViewer => "db-viewer",
+            WindowKind::Chat => "chat",
+            WindowKind::ChartDetail => "chart",
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            WindowKind::Main => "main",
+            WindowKind::DbViewer => "db_viewer",
+            WindowKind::Chat => "chat",
+            WindowKind::ChartDetail => "chart_detail",
+        }
+    }
+
+    // One DB viewer is enough; chats and charts can be detached side by side
+    fn single_instance(&self) -> bool {
+        matches!(self, WindowKind::Main | WindowKind::DbViewer)
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            WindowKind::Main => "Financial Calculator",
+            WindowKind::DbViewer => "Database Viewer",
+            WindowKind::Chat => "AI Chat",
+            WindowKind::ChartDetail => "Chart",
+        }
+    }
+
+    fn size(&self) -> (f64, f64) {
+        match self {
+            WindowKind::Main => (1200.0, 800.0),
+            WindowKind::DbViewer => (1000.0, 700.0),
+            WindowKind::Chat => (480.0, 720.0),
+            WindowKind::ChartDetail => (900.0, 600.0),
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        [WindowKind::Main, WindowKind::DbViewer, WindowKind::Chat, WindowKind::ChartDetail]
+            .into_iter()
+            .find(|kind| {
+                let prefix = kind.label_prefix();
+                label == prefix || label.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('-') && !kind.single_instance())
+            })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowInfo {
+    pub label: String,
+    pub kind: WindowKind,
+    pub title: String,
+    pub focused: bool,
+}
+
+/// Event name for a window: the main window keeps the plain name, others get `name:label`
+/// so e.g. a detached chat's stream never lands in the main window's chat.
+pub fn scoped_event(window_label: &str, event: &str) -> String {
+    if window_label == MAIN_WINDOW {
+        event.to_string()
+    } else {
+        format!("{}:{}", event, window_label)
+    }
+}
+
+fn next_label(app: &AppHandle, kind: WindowKind) -> String {
+    let prefix = kind.label_prefix();
+    if kind.single_instance() {
+        return prefix.to_string();
+    }
+    (1..)
+        .map(|n| format!("{}-{}", prefix, n))
+        .find(|label| app.get_webview_window(label).is_none())
+        .expect("window labels are unbounded")
+}
+
+/// `index.html?window=<kind>&label=<label>&...` so the frontend knows which view to render.
+fn window_url(kind: WindowKind, label: &str, params: &BTreeMap<String, String>) -> Result<WebviewUrl, String> {
+    let mut url = Url::parse("app://localhost/index.html").map_err(|e| e.to_string())?;
+    {
+        let mut query = url.query_pairs_mut();
+        query.append_pair("window", kind.as_str());
+        query.append_pair("label", label);
+        for (key, value) in params {
+            query.append_pair(key, value);
+        }
+    }
+    Ok(WebviewUrl::App(format!("index.html?{}", url.query().unwrap_or_default()).into()))
+}
+
+fn info(window: &tauri::WebviewWindow) -> Option<WindowInfo> {
+    Some(WindowInfo {
+        label: window.label().to_string(),
+        kind: WindowKind::from_label(window.label())?,
+        title: window.title().unwrap_or_default(),
+        focused: window.is_focused().unwrap_or(false),
+    })
+}
+
+// Tauri Commands
+/// Open (or focus, for single-instance kinds) a secondary window. `params` are passed to
+/// the frontend in the query string, e.g. `{ "documentId": "3", "chart": "revenue" }`.
+#[tauri::command]
+pub async fn open_window(
+    app: AppHandle,
+    kind: WindowKind,
+    title: Option<String>,
+    params: Option<BTreeMap<String, String>>,
+) -> Result<WindowInfo, String> {
+    if kind == WindowKind::Main {
+        return Err("The main window is always open".to_string());
+    }
+    let label = next_label(&app, kind);
+    if let Some(window) = app.get_webview_window(&label) {
+        window.show().map_err(|e| e.to_string())?;
+        window.set_focus().map_err(|e| e.to_string())?;
+        return info(&window).ok_or_else(|| format!("Unknown window {}", label));
+    }
+
+    let url = window_url(kind, &label, &params.unwrap_or_default())?;
+    let (width, height) = kind.size();
+    let window = WebviewWindowBuilder::new(&app, &label, url)
+        .title(title.as_deref().unwrap_or(kind.title()))
+        .inner_size(width, height)
+        .min_inner_size(360.0, 320.0)
+        .resizable(true)
+        .build()
+        .map_err(|e| e.to_string())?;
+    tracing::info!(label = %label, "Opened window");
+    info(&window).ok_or_else(|| format!("Unknown window {}", label))
+}
+
+#[tauri::command]
+pub fn list_windows(app: AppHandle) -> Vec<WindowInfo> {
+    let mut windows: Vec<WindowInfo> = app.webview_windows().values().filter_map(info).collect();
+    windows.sort_by(|a, b| a.label.cmp(&b.label));
+    windows
+}
+
+#[tauri::command]
+pub fn focus_window(app: AppHandle, label: String) -> Result<(), String> {
+    let window = app.get_webview_window(&label).ok_or_else(|| format!("No window {}", label))?;
+    window.show().map_err(|e| e.to_string())?;
+    window.unminimize().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn close_window(app: AppHandle, label: String) -> Result<(), String> {
+    if label == MAIN_WINDOW {
+        return Err("The main window can't be closed this way".to_string());
+    }
+    let window = app.get_webview_window(&label).ok_or_else(|| format!("No window {}", label))?;
+    window.close().map_err(|e| e.to_string())
+}
@@ -0,0 +1,242 @@
+// Workspace - one zip with the active profile's databases, settings (secrets removed), reports and
+// chat history, for moving to another machine or handing an analysis to a colleague
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tracing::info;
+
+use crate::backup;
+use crate::diagnostics::{self, add_file};
+use crate::profiles;
+use crate::settings::{AppSettings, SettingsState};
+
+const FORMAT: u32 = 1;
+const KIND: &str = "fincalc-workspace";
+
+// Archive entries
+const MANIFEST_ENTRY: &str = "manifest.json";
+const PIPELINE_DB_ENTRY: &str = "pipeline.db";
+const APP_DB_ENTRY: &str = "app.db";
+const SETTINGS_ENTRY: &str = "settings.json";
+const REPORTS_DIR: &str = "reports";
+const CHAT_HISTORY_DIR: &str = "chat_history";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Manifest {
+    kind: String,
+    format: u32,
+    app_version: String,
+    profile: String,
+    created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceSummary {
+    pub path: String,
+    pub has_database: bool,
+    pub has_settings: bool,
+    pub reports: usize,
+    pub chat_sessions: usize,
+    /// Profile and app version the archive was made from
+    pub source_profile: String,
+    pub source_version: String,
+    pub created_at: i64,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Files under `dir`, recursively, with their path relative to it ("a/b.pdf").
+fn files_under(dir: &Path) -> Vec<(String, PathBuf)> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&current) else { continue };
+        for path in entries.filter_map(|e| e.ok().map(|e| e.path())) {
+            if path.is_dir() {
+                pending.push(path);
+            } else if let Ok(relative) = path.strip_prefix(dir) {
+                let name = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+                files.push((name, path));
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Put back this machine's secrets wherever the archive has the redaction marker.
+pub fn restore_secrets(imported: Value, current: &Value) -> Value {
+    match imported {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    let current = current.get(&key).unwrap_or(&Value::Null);
+                    (key, restore_secrets(value, current))
+                })
+                .collect(),
+        ),
+        Value::String(text) if text == diagnostics::REDACTED => match current {
+            Value::String(_) => current.clone(),
+            _ => Value::String(String::new()),
+        },
+        other => other,
+    }
+}
+
+// Tauri Commands
+/// Write the active profile's workspace to `path` (a .zip). API keys, tokens and passwords are
+/// left out; the importing side keeps its own.
+#[tauri::command]
+pub async fn export_workspace(app: AppHandle, path: String) -> Result<WorkspaceSummary, String> {
+    let settings = {
+        let state = app.state::<SettingsState>();
+        let store = state.read().await;
+        serde_json::to_value(store.get()).map_err(|e| e.to_string())?
+    };
+    let manifest = Manifest {
+        kind: KIND.to_string(),
+        format: FORMAT,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        profile: profiles::active_profile_id(&app),
+        created_at: now_secs(),
+    };
+    let pipeline_db = profiles::active_db_path(&app);
+    let app_db = profiles::active_app_db_path(&app);
+    let reports = files_under(&profiles::active_reports_dir(&app));
+    let chats = files_under(&profiles::active_chat_history_dir(&app));
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let file = fs::File::create(&path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+        let mut zip = zip::ZipWriter::new(file);
+        add_file(&mut zip, MANIFEST_ENTRY, &serde_json::to_vec_pretty(&manifest).map_err(|e| e.to_string())?)?;
+        let mut has_database = false;
+        if let Some(bytes) = backup::snapshot_db(&pipeline_db, PIPELINE_DB_ENTRY)? {
+            add_file(&mut zip, PIPELINE_DB_ENTRY, &bytes)?;
+            has_database = true;
+        }
+        if let Some(bytes) = backup::snapshot_db(&app_db, APP_DB_ENTRY)? {
+            add_file(&mut zip, APP_DB_ENTRY, &bytes)?;
+        }
+        let redacted = diagnostics::redact(settings, false);
+        add_file(&mut zip, SETTINGS_ENTRY, &serde_json::to_vec_pretty(&redacted).map_err(|e| e.to_string())?)?;
+        for (prefix, files) in [(REPORTS_DIR, &reports), (CHAT_HISTORY_DIR, &chats)] {
+            for (name, file) in files {
+                let contents = fs::read(file).map_err(|e| format!("Failed to read {}: {}", file.display(), e))?;
+                add_file(&mut zip, &format!("{}/{}", prefix, name), &contents)?;
+            }
+        }
+        zip.finish().map_err(|e| e.to_string())?;
+        info!(path = %path, reports = reports.len(), chats = chats.len(), "Exported workspace");
+        Ok(WorkspaceSummary {
+            path,
+            has_database,
+            has_settings: true,
+            reports: reports.len(),
+            chat_sessions: chats.len(),
+            source_profile: manifest.profile,
+            source_version: manifest.app_version,
+            created_at: manifest.created_at,
+        })
+    }).await.map_err(|e| e.to_string())?
+}
+
+/// Load a workspace archive into the active profile, replacing its databases and settings and
+/// adding its reports and chat history. Create and switch to an empty profile first to keep
+/// the current data separate.
+#[tauri::command]
+pub async fn import_workspace(app: AppHandle, path: String) -> Result<WorkspaceSummary, String> {
+    let current_settings = {
+        let state = app.state::<SettingsState>();
+        let store = state.read().await;
+        serde_json::to_value(store.get()).map_err(|e| e.to_string())?
+    };
+    let pipeline_db = profiles::active_db_path(&app);
+    let app_db = profiles::active_app_db_path(&app);
+    let reports_dir = profiles::active_reports_dir(&app);
+    let chats_dir = profiles::active_chat_history_dir(&app);
+
+    let (summary, settings) = tauri::async_runtime::spawn_blocking(move || -> Result<(WorkspaceSummary, Option<AppSettings>), String> {
+        let file = fs::File::open(&path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+        let mut zip = zip::ZipArchive::new(file).map_err(|e| format!("Not a workspace archive: {}", e))?;
+        let mut entries = Vec::new();
+        for index in 0..zip.len() {
+            let mut entry = zip.by_index(index).map_err(|e| e.to_string())?;
+            // enclosed_name rejects absolute paths and `..`
+            let Some(name) = entry.enclosed_name() else { continue };
+            if entry.is_dir() {
+                continue;
+            }
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents).map_err(|e| e.to_string())?;
+            entries.push((name, contents));
+        }
+
+        // Validate everything before touching the profile
+        let manifest: Manifest = entries.iter()
+            .find(|(name, _)| name == Path::new(MANIFEST_ENTRY))
+            .and_then(|(_, contents)| serde_json::from_slice(contents).ok())
+            .filter(|m: &Manifest| m.kind == KIND)
+            .ok_or("Not a workspace archive (missing manifest)")?;
+        if manifest.format > FORMAT {
+            return Err(format!("This workspace was made by a newer version ({}); update the app first", manifest.app_version));
+        }
+        let settings = match entries.iter().find(|(name, _)| name == Path::new(SETTINGS_ENTRY)) {
+            Some((_, contents)) => {
+                let imported: Value = serde_json::from_slice(contents).map_err(|e| format!("Workspace settings are invalid: {}", e))?;
+                let merged: AppSettings = serde_json::from_value(restore_secrets(imported, &current_settings))
+                    .map_err(|e| format!("Workspace settings are invalid: {}", e))?;
+                Some(merged)
+            }
+            None => None,
+        };
+
+        let mut summary = WorkspaceSummary {
+            path: path.clone(),
+            has_database: false,
+            has_settings: settings.is_some(),
+            reports: 0,
+            chat_sessions: 0,
+            source_profile: manifest.profile,
+            source_version: manifest.app_version,
+            created_at: manifest.created_at,
+        };
+        for (name, contents) in &entries {
+            if name == Path::new(PIPELINE_DB_ENTRY) {
+                backup::replace_file(&pipeline_db, contents)?;
+                summary.has_database = true;
+            } else if name == Path::new(APP_DB_ENTRY) {
+                backup::replace_file(&app_db, contents)?;
+            } else if let Ok(relative) = name.strip_prefix(REPORTS_DIR) {
+                backup::replace_file(&reports_dir.join(relative), contents)?;
+                summary.reports += 1;
+            } else if let Ok(relative) = name.strip_prefix(CHAT_HISTORY_DIR) {
+                backup::replace_file(&chats_dir.join(relative), contents)?;
+                summary.chat_sessions += 1;
+            }
+        }
+        Ok((summary, settings))
+    }).await.map_err(|e| e.to_string())??;
+
+    if let Some(settings) = settings {
+        {
+            let state = app.state::<SettingsState>();
+            let mut store = state.write().await;
+            store.replace(settings.clone());
+            store.save()?;
+        }
+        let _ = app.emit("settings-changed", &settings);
+    }
+    info!(path = %summary.path, reports = summary.reports, chats = summary.chat_sessions, "Imported workspace");
+    Ok(summary)
+}